@@ -70,12 +70,23 @@ mod error;
 mod metadata;
 mod monotonic;
 mod relay_service;
+mod stream_service;
 mod system;
 mod unique;
 
 pub use error::OckamError;
 pub use metadata::OckamMessage;
-pub use relay_service::{RelayService, RelayServiceOptions};
+#[cfg(feature = "std")]
+pub use relay_service::RelaysStorage;
+pub use relay_service::{
+    RelayInfo, RelayRegistry, RelayService, RelayServiceOptions, RelaysRepository,
+};
+#[cfg(feature = "std")]
+pub use stream_service::FileStreamStorage;
+pub use stream_service::{
+    InMemoryStreamStorage, StreamIndexService, StreamService, StreamServiceOptions, StreamStorage,
+    DEFAULT_PULL_LIMIT,
+};
 pub use system::{SystemBuilder, SystemHandler, WorkerSystem};
 pub use unique::unique_with_prefix;
 
@@ -10,6 +10,9 @@ pub use ordering::ReceiverOrdering;
 mod handshake;
 pub use handshake::HandshakeInit;
 
+mod window;
+pub use window::{ReceiverWindow, SenderWindow, DEFAULT_PIPE_WINDOW};
+
 use crate::{
     protocols::pipe::{internal::InternalCmd, PipeMessage},
     Context,
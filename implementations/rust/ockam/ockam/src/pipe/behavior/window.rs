@@ -0,0 +1,304 @@
+use crate::{
+    delay::DelayedEvent,
+    pipe::{BehaviorHook, PipeModifier},
+    protocols::pipe::{
+        internal::{Credit, InternalCmd, Resend},
+        PipeMessage,
+    },
+    Context, MessageReceiveOptions, OckamError,
+};
+use core::time::Duration;
+use ockam_core::compat::{boxed::Box, vec::Vec};
+use ockam_core::{async_trait, compat::collections::BTreeMap, Address, AllowAll, Result, Route};
+
+/// Default number of messages which can be in-flight at the same time
+pub const DEFAULT_PIPE_WINDOW: u64 = 32;
+
+/// Time to wait for a credit before re-sending in-flight messages
+const RETRANSMIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sender side of a windowed pipe
+///
+/// At most `window` messages can be sent without having been
+/// credited by the receiver.  When the window is full the pipe
+/// sender stops handling new messages until the receiver advertises
+/// more credit, so that producers fill the sender mailbox instead of
+/// the mailboxes of every hop to the receiver.  Messages which are
+/// not credited in time are re-sent.
+///
+/// This behavior must be used with a [`ReceiverWindow`] and replaces
+/// the [`SenderConfirm`](super::SenderConfirm) behavior.
+pub struct SenderWindow {
+    /// Highest message index which can be sent
+    limit: u64,
+    /// Messages sent but not yet credited by the receiver
+    in_flight: BTreeMap<u64, PipeMessage>,
+    /// Context used to send messages and receive credits.  It is
+    /// created when sending the first message
+    credit_ctx: Option<Context>,
+    window: u64,
+}
+
+impl Clone for SenderWindow {
+    fn clone(&self) -> Self {
+        Self::new(self.window)
+    }
+}
+
+impl Default for SenderWindow {
+    fn default() -> Self {
+        Self::new(DEFAULT_PIPE_WINDOW)
+    }
+}
+
+impl SenderWindow {
+    /// Create a sender window allowing `window` in-flight messages
+    /// until the receiver advertises its own window
+    pub fn new(window: u64) -> Self {
+        Self {
+            // Pipe messages are 1-indexed
+            limit: window,
+            in_flight: BTreeMap::new(),
+            credit_ctx: None,
+            window,
+        }
+    }
+
+    /// Apply a credit received from the receiver
+    fn apply_credit(&mut self, credit: &Credit) {
+        trace!(
+            "Received pipe credit up to index {} with window {}",
+            credit.idx,
+            credit.window
+        );
+        self.in_flight = self.in_flight.split_off(&(credit.idx + 1));
+        self.limit = self.limit.max(credit.idx + credit.window);
+    }
+
+    /// Context used to send messages and receive credits, which is only
+    /// available once the first message was sent
+    fn credit_ctx(&self) -> Result<&Context> {
+        self.credit_ctx
+            .as_ref()
+            .ok_or_else(|| OckamError::SystemAddressNotBound.into())
+    }
+
+    /// Receive a credit from the receiver, with a timeout
+    async fn receive_credit(&mut self, timeout: Duration) -> Result<bool> {
+        let credit_ctx = self
+            .credit_ctx
+            .as_mut()
+            .ok_or(OckamError::SystemAddressNotBound)?;
+        match credit_ctx
+            .receive_extended::<InternalCmd>(MessageReceiveOptions::new().with_timeout(timeout))
+            .await
+        {
+            Ok(msg) => {
+                match msg.body() {
+                    InternalCmd::Credit(credit) => self.apply_credit(&credit),
+                    cmd => trace!("SenderWindow behavior ignoring {:?}", cmd),
+                }
+                Ok(true)
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Apply all the credits received so far, without waiting
+    async fn drain_credits(&mut self) -> Result<()> {
+        while self.receive_credit(Duration::ZERO).await? {}
+        Ok(())
+    }
+
+    async fn retransmit(&mut self, ctx: &Context, this: &Address, peer: &Route) -> Result<()> {
+        let credit_ctx = self.credit_ctx()?;
+        for (idx, msg) in self.in_flight.iter() {
+            debug!("Re-sending uncredited message index '{}' to {}", idx, peer);
+            credit_ctx.send(peer.clone(), msg.clone()).await?;
+        }
+        ctx.metrics()
+            .record_pipe_retransmissions(self.in_flight.len());
+        self.record_occupancy(ctx, this);
+        Ok(())
+    }
+
+    fn record_occupancy(&self, ctx: &Context, this: &Address) {
+        ctx.metrics()
+            .record_pipe_window_occupancy(this, self.in_flight.len());
+    }
+}
+
+#[async_trait]
+impl BehaviorHook for SenderWindow {
+    async fn on_external(
+        &mut self,
+        this: Address,
+        peer: Route,
+        ctx: &mut Context,
+        msg: &PipeMessage,
+    ) -> Result<PipeModifier> {
+        if self.credit_ctx.is_none() {
+            let addr = Address::random_tagged("SenderWindow.credit");
+            self.credit_ctx = Some(ctx.new_detached(addr, AllowAll, AllowAll).await?);
+        }
+        self.drain_credits().await?;
+
+        // Apply backpressure: block the pipe sender until the message
+        // fits in the window advertised by the receiver
+        let index = msg.index.u64();
+        while index > self.limit {
+            debug!("Pipe window is full, waiting for credit to send {}", index);
+            if !self.receive_credit(RETRANSMIT_TIMEOUT).await? {
+                self.retransmit(ctx, &this, &peer).await?;
+            }
+        }
+
+        self.in_flight.insert(index, msg.clone());
+        self.record_occupancy(ctx, &this);
+
+        // Send the message from the credit context so that the
+        // receiver credits are sent there
+        self.credit_ctx()?.send(peer, msg.clone()).await?;
+
+        DelayedEvent::new(ctx, this.into(), InternalCmd::Resend(Resend { idx: index }))
+            .await?
+            .with_duration(RETRANSMIT_TIMEOUT)
+            .spawn();
+
+        // The message was already sent
+        Ok(PipeModifier::Drop)
+    }
+
+    async fn on_internal(
+        &mut self,
+        this: Address,
+        peer: Route,
+        ctx: &mut Context,
+        msg: &InternalCmd,
+    ) -> Result<()> {
+        match msg {
+            InternalCmd::Resend(Resend { idx }) if self.credit_ctx.is_some() => {
+                self.drain_credits().await?;
+                if let Some(msg) = self.in_flight.get(idx).cloned() {
+                    debug!("Message index '{}' was not credited: re-sending", idx);
+                    self.credit_ctx()?.send(peer, msg).await?;
+                    ctx.metrics().record_pipe_retransmissions(1);
+
+                    DelayedEvent::new(ctx, this.into(), InternalCmd::Resend(Resend { idx: *idx }))
+                        .await?
+                        .with_duration(RETRANSMIT_TIMEOUT)
+                        .spawn();
+                }
+                Ok(())
+            }
+            cmd => {
+                trace!("SenderWindow behavior ignoring {:?}", cmd);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Receiver side of a windowed pipe
+///
+/// Messages are delivered in order.  After each delivery the
+/// receiver credits the sender with the index of the last delivered
+/// message and the number of messages it accepts after it.  Messages
+/// outside of this window are dropped.
+#[derive(Clone)]
+pub struct ReceiverWindow {
+    window: u64,
+    /// Index of the last delivered message
+    delivered: u64,
+    /// Messages received out-of-order
+    journal: BTreeMap<u64, PipeMessage>,
+}
+
+impl Default for ReceiverWindow {
+    fn default() -> Self {
+        Self::new(DEFAULT_PIPE_WINDOW)
+    }
+}
+
+impl ReceiverWindow {
+    /// Create a receiver window accepting `window` messages after
+    /// the last delivered one
+    pub fn new(window: u64) -> Self {
+        Self {
+            window,
+            delivered: 0,
+            journal: BTreeMap::new(),
+        }
+    }
+
+    async fn credit(&self, ctx: &mut Context, sender: Route) -> Result<()> {
+        ctx.send(
+            sender,
+            InternalCmd::Credit(Credit {
+                idx: self.delivered,
+                window: self.window,
+            }),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl BehaviorHook for ReceiverWindow {
+    async fn on_external(
+        &mut self,
+        _: Address,
+        sender: Route,
+        ctx: &mut Context,
+        msg: &PipeMessage,
+    ) -> Result<PipeModifier> {
+        let index = msg.index.u64();
+
+        if index <= self.delivered {
+            // The credit for this message may have been lost
+            debug!("Ignoring already delivered message index {}", index);
+            self.credit(ctx, sender).await?;
+            return Ok(PipeModifier::Drop);
+        }
+
+        if index > self.delivered + self.window {
+            warn!(
+                "Dropping message index {} outside of the pipe window",
+                index
+            );
+            return Ok(PipeModifier::Drop);
+        }
+
+        if index > self.delivered + 1 {
+            info!("Enqueueing message with index {}", index);
+            self.journal.insert(index, msg.clone());
+            return Ok(PipeModifier::Drop);
+        }
+
+        // Deliver this message and all the queued messages which
+        // directly follow it
+        let mut to_deliver: Vec<PipeMessage> = vec![msg.clone()];
+        while let Some(next) = self.journal.remove(&(index + to_deliver.len() as u64)) {
+            to_deliver.push(next);
+        }
+        for msg in to_deliver {
+            ctx.forward(crate::pipe::unpack_pipe_message(&msg)?).await?;
+            self.delivered = msg.index.u64();
+        }
+
+        self.credit(ctx, sender).await?;
+
+        // The messages were already forwarded
+        Ok(PipeModifier::Drop)
+    }
+
+    async fn on_internal(
+        &mut self,
+        _: Address,
+        _: Route,
+        _: &mut Context,
+        _: &InternalCmd,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
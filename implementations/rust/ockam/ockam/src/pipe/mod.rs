@@ -4,7 +4,7 @@
 mod behavior;
 pub use behavior::{
     BehaviorHook, HandshakeInit, PipeBehavior, PipeModifier, ReceiverConfirm, ReceiverOrdering,
-    SenderConfirm,
+    ReceiverWindow, SenderConfirm, SenderWindow, DEFAULT_PIPE_WINDOW,
 };
 
 mod listener;
@@ -50,6 +50,12 @@ impl Worker for PipeSender {
         Ok(())
     }
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // The window occupancy is recorded under the internal address by windowed pipes
+        ctx.metrics().remove_pipe_window(&self.int_addr);
+        Ok(())
+    }
+
     async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
         match msg.msg_addr() {
             addr if addr == self.int_addr => self.handle_internal(ctx, msg).await?,
@@ -6,6 +6,11 @@
 //!
 //! This module is a replacement for [`pipe`](crate::pipe) and should
 //! replace it at some point in the future.
+//!
+//! A persistent pipe stores its outgoing messages and the index of
+//! the last delivered message in a [`PipeStorage`], which provides
+//! at-least-once delivery with deduplication across restarts of
+//! either end of the pipe.
 
 mod listener;
 mod receiver;
@@ -17,19 +22,36 @@ use crate::{
         receiver::PipeReceiver,
         sender::{PeerRoute, PipeSender},
     },
-    system::hooks::pipe::{ReceiverConfirm, ReceiverOrdering, SenderConfirm, SenderOrdering},
+    system::hooks::pipe::{
+        ReceiverConfirm, ReceiverOrdering, ReceiverPersistence, SenderConfirm, SenderOrdering,
+        SenderPersistence,
+    },
     Context, OckamMessage, SystemBuilder, WorkerSystem,
 };
 use ockam_core::compat::sync::Arc;
 use ockam_core::{
-    compat::collections::BTreeSet, Address, AllowAll, Mailbox, Mailboxes, Result, Route,
+    compat::{collections::BTreeSet, string::String, vec::Vec},
+    Address, AllowAll, Mailbox, Mailboxes, Result, Route,
 };
-use ockam_node::WorkerBuilder;
+use ockam_node::{KeyValueStorage, WorkerBuilder};
+#[cfg(feature = "std")]
+use std::path::PathBuf;
 
 const CLUSTER_NAME: &str = "_internal.pipe2";
 type PipeSystem = WorkerSystem<Context, OckamMessage>;
 type PipeSystemBuilder = SystemBuilder<Context, OckamMessage>;
 
+/// Storage used by persistent pipes for their outbox and delivery state
+pub type PipeStorage = Arc<dyn KeyValueStorage<String, Vec<u8>>>;
+
+enum Persistence {
+    /// Messages are stored in the given key/value storage
+    Storage(PipeStorage),
+    /// Messages are stored in a file at the given path
+    #[cfg(feature = "std")]
+    File(PathBuf),
+}
+
 enum Mode {
     /// In static mode this pipe will connect to a well-known peer, or
     /// receive _one_ connection on a well-known address, or does
@@ -102,6 +124,8 @@ pub struct PipeBuilder {
     tx_fin: Address,
     /// "Fin" address on the receiver
     rx_fin: Address,
+    /// Storage of a persistent pipe
+    persistence: Option<Persistence>,
 }
 
 /// A simple wrapper around possible pipe hooks
@@ -157,6 +181,7 @@ impl PipeBuilder {
             recv: None,
             tx_fin: Address::random_local(),
             rx_fin: Address::random_local(),
+            persistence: None,
             mode,
         }
     }
@@ -212,10 +237,74 @@ impl PipeBuilder {
         self
     }
 
+    /// Make this pipe persistent, storing its state in a file
+    ///
+    /// Outgoing messages are kept on disk until they are
+    /// acknowledged, and re-sent when the pipe is re-created with the
+    /// same path after a restart.  The receiver stores the index of
+    /// the last delivered message to drop duplicates.  A persistent
+    /// pipe always enforces ordering and delivery acknowledgements,
+    /// so both ends of the pipe must be persistent.
+    #[cfg(feature = "std")]
+    pub fn persistent<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.persistence = Some(Persistence::File(path.into()));
+        self
+    }
+
+    /// Make this pipe persistent, storing its state in the given
+    /// key/value storage
+    ///
+    /// See [`persistent`](Self::persistent) for details.
+    pub fn persistent_storage(mut self, storage: PipeStorage) -> Self {
+        self.persistence = Some(Persistence::Storage(storage));
+        self
+    }
+
+    async fn pipe_storage(&self) -> Result<Option<PipeStorage>> {
+        match &self.persistence {
+            Some(Persistence::Storage(storage)) => Ok(Some(storage.clone())),
+            #[cfg(feature = "std")]
+            Some(Persistence::File(path)) => Ok(Some(Arc::new(
+                ockam_node::FileKeyValueStorage::<String, Vec<u8>>::create(path).await?,
+            ))),
+            None => Ok(None),
+        }
+    }
+
     async fn build_systems(&self) -> Result<(PipeSystemBuilder, PipeSystemBuilder)> {
         let mut send_hooks = SystemBuilder::new();
         let mut recv_hooks = SystemBuilder::new();
 
+        // A persistent pipe replaces the ordering and delivery hooks
+        if let Some(storage) = self.pipe_storage().await? {
+            let (tx_addr, rx_addr) = (Address::random_local(), Address::random_local());
+
+            // Only set up the sender hook for an outgoing pipe, since it
+            // starts re-sending its outbox as soon as it is initialised
+            if self.peer.is_some() {
+                send_hooks
+                    .add(
+                        tx_addr.clone(),
+                        "persistence",
+                        SenderPersistence::new(storage.clone()),
+                    )
+                    .default(self.tx_fin.clone())
+                    .condition("self", tx_addr.clone());
+                send_hooks.set_entry(tx_addr);
+            }
+
+            recv_hooks
+                .add(
+                    rx_addr.clone(),
+                    "persistence",
+                    ReceiverPersistence::new(storage),
+                )
+                .default(self.rx_fin.clone());
+            recv_hooks.set_entry(rx_addr);
+
+            return Ok((send_hooks, recv_hooks));
+        }
+
         let (ord_tx_addr, ord_rx_addr) = (Address::random_local(), Address::random_local());
         let (ack_tx_addr, ack_rx_addr) = (Address::random_local(), Address::random_local());
 
@@ -18,6 +18,15 @@ pub struct Ack {
     pub idx: u64,
 }
 
+/// Grant the sender credit to send more messages
+#[derive(Debug, Serialize, Deserialize, Message)]
+pub struct Credit {
+    /// The index of the last message delivered by the receiver
+    pub idx: u64,
+    /// The number of messages the sender may send after `idx`
+    pub window: u64,
+}
+
 /// Payload sent from handshake listener to newly spawned receiver
 #[derive(Debug, Serialize, Deserialize, Message)]
 pub struct Handshake {
@@ -38,6 +47,8 @@ pub enum InternalCmd {
     Handshake(Handshake),
     /// Initialise a pipe sender with a route
     InitSender,
+    /// Advertise the receive window of a pipe receiver
+    Credit(Credit),
 }
 
 impl InternalCmd {
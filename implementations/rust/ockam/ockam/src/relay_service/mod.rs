@@ -1,7 +1,9 @@
 mod options;
+mod registry;
 mod relay;
 #[allow(clippy::module_inception)]
 mod relay_service;
 
 pub use options::*;
+pub use registry::*;
 pub use relay_service::*;
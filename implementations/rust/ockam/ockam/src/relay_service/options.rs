@@ -1,3 +1,4 @@
+use crate::relay_service::RelayRegistry;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::flow_control::{FlowControlId, FlowControls};
@@ -9,6 +10,7 @@ pub struct RelayServiceOptions {
     pub(super) relays_incoming_access_control: Arc<dyn IncomingAccessControl>,
     pub(super) consumer_service: Vec<FlowControlId>,
     pub(super) consumer_relay: Vec<FlowControlId>,
+    pub(super) registry: RelayRegistry,
 }
 
 impl RelayServiceOptions {
@@ -19,6 +21,7 @@ impl RelayServiceOptions {
             relays_incoming_access_control: Arc::new(AllowAll),
             consumer_service: vec![],
             consumer_relay: vec![],
+            registry: RelayRegistry::new(),
         }
     }
 
@@ -72,6 +75,14 @@ impl RelayServiceOptions {
         self
     }
 
+    /// Use the given registry to keep track of the created relays.
+    /// When the registry is persistent, the relays it contains are restored
+    /// when the service is created
+    pub fn with_registry(mut self, registry: RelayRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
     pub(super) fn setup_flow_control_for_relay_service(
         &self,
         flow_controls: &FlowControls,
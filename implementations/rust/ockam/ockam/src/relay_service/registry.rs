@@ -0,0 +1,273 @@
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, OutgoingAccessControl, RelayMessage, Result, Route};
+use ockam_identity::{Identifier, TimestampInSeconds};
+use serde::{Deserialize, Serialize};
+
+/// Registration of a relay created by a [`RelayService`](crate::RelayService)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RelayInfo {
+    alias: String,
+    forward_route: Route,
+    owner: Option<Identifier>,
+    last_heartbeat: TimestampInSeconds,
+}
+
+impl RelayInfo {
+    /// Create a new relay registration
+    pub fn new(
+        alias: impl Into<String>,
+        forward_route: Route,
+        owner: Option<Identifier>,
+        last_heartbeat: TimestampInSeconds,
+    ) -> Self {
+        Self {
+            alias: alias.into(),
+            forward_route,
+            owner,
+            last_heartbeat,
+        }
+    }
+
+    /// Alias of the relay, this is also the address of the relay worker
+    pub fn alias(&self) -> &str {
+        &self.alias
+    }
+
+    /// Route used to forward messages to the registered worker
+    pub fn forward_route(&self) -> &Route {
+        &self.forward_route
+    }
+
+    /// Identity which registered the relay, when the registration
+    /// was received over a secure channel
+    pub fn owner(&self) -> Option<&Identifier> {
+        self.owner.as_ref()
+    }
+
+    /// Time of the last registration or heartbeat received for this relay
+    pub fn last_heartbeat(&self) -> TimestampInSeconds {
+        self.last_heartbeat
+    }
+}
+
+/// Storage for relay registrations
+#[async_trait]
+pub trait RelaysRepository: Send + Sync + 'static {
+    /// Store or update a relay registration
+    async fn put(&self, relay: RelayInfo) -> Result<()>;
+
+    /// Delete a relay registration
+    async fn delete(&self, alias: &str) -> Result<()>;
+
+    /// Return all the stored relay registrations
+    async fn list(&self) -> Result<Vec<RelayInfo>>;
+}
+
+/// Relays created by a [`RelayService`](crate::RelayService).
+///
+/// The registry can be shared with the service by using
+/// [`RelayServiceOptions::with_registry`](crate::RelayServiceOptions::with_registry)
+/// in order to list the existing relays. When a [`RelaysRepository`] is set, relays with a
+/// static alias are persisted and restored when the service is started again, so that
+/// a restarted node accepts traffic for those aliases without waiting for new registrations.
+#[derive(Clone, Default)]
+pub struct RelayRegistry {
+    relays: Arc<RwLock<BTreeMap<String, RelayInfo>>>,
+    repository: Option<Arc<dyn RelaysRepository>>,
+}
+
+impl RelayRegistry {
+    /// Create a registry keeping relays in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry persisting relay registrations with the given repository
+    pub fn with_repository(mut self, repository: Arc<dyn RelaysRepository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Create a registry persisting relay registrations to a file
+    #[cfg(feature = "std")]
+    pub async fn create_persistent(path: &std::path::Path) -> Result<Self> {
+        Ok(Self::new().with_repository(RelaysStorage::create(path).await?))
+    }
+
+    /// Return all the relays, ordered by alias
+    pub fn list(&self) -> Vec<RelayInfo> {
+        self.relays.read().unwrap().values().cloned().collect()
+    }
+
+    /// Return the relay registered with the given alias
+    pub fn get(&self, alias: &str) -> Option<RelayInfo> {
+        self.relays.read().unwrap().get(alias).cloned()
+    }
+
+    /// Remove a relay registration, including from the repository
+    /// The corresponding relay worker must be stopped separately
+    pub async fn remove(&self, alias: &str) -> Result<Option<RelayInfo>> {
+        let removed = self.relays.write().unwrap().remove(alias);
+        if let Some(repository) = &self.repository {
+            repository.delete(alias).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Add or update a relay registration.
+    /// Only relays with a static alias are persisted since dynamic aliases
+    /// are never registered again by clients.
+    /// A registration is only written to the repository when its route or owner changes,
+    /// not for each heartbeat, so the persisted heartbeat is the time of the last change
+    pub(super) async fn register(&self, relay: RelayInfo, persistent: bool) -> Result<()> {
+        let previous = self
+            .relays
+            .write()
+            .unwrap()
+            .insert(relay.alias.clone(), relay.clone());
+        let changed = match previous {
+            Some(previous) => {
+                previous.forward_route != relay.forward_route || previous.owner != relay.owner
+            }
+            None => true,
+        };
+        match &self.repository {
+            Some(repository) if persistent && changed => repository.put(relay).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Load the persisted relays in memory and return them
+    pub(super) async fn restore(&self) -> Result<Vec<RelayInfo>> {
+        let relays = match &self.repository {
+            Some(repository) => repository.list().await?,
+            None => return Ok(vec![]),
+        };
+        let mut registered = self.relays.write().unwrap();
+        for relay in relays.iter() {
+            registered.insert(relay.alias.clone(), relay.clone());
+        }
+        Ok(relays)
+    }
+
+    /// Return the current forward route for a relay
+    pub(super) fn forward_route(&self, alias: &str) -> Option<Route> {
+        self.relays
+            .read()
+            .unwrap()
+            .get(alias)
+            .map(|r| r.forward_route.clone())
+    }
+
+    /// Check that a message is sent to the next hop of the forward route of a relay
+    fn is_next_hop(&self, alias: &str, relay_msg: &RelayMessage) -> Result<bool> {
+        let forward_route = match self.forward_route(alias) {
+            Some(forward_route) => forward_route,
+            None => return Ok(false),
+        };
+        match forward_route.next() {
+            Ok(next_hop) => Ok(relay_msg.onward_route().next()? == next_hop),
+            // We are accessed with our node, no transport is involved
+            Err(_) => Ok(true),
+        }
+    }
+}
+
+/// Outgoing access control for a relay: messages can only be sent to the next hop of its
+/// forward route, which is updated every time the relay is registered again
+#[derive(Debug)]
+pub(super) struct RelayOutgoingAccessControl {
+    pub(super) registry: RelayRegistry,
+    pub(super) alias: String,
+}
+
+#[async_trait]
+impl OutgoingAccessControl for RelayOutgoingAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        self.registry.is_next_hop(&self.alias, relay_msg)
+    }
+}
+
+/// Outgoing access control for the relay service: the service can only reply to the
+/// registration of an existing relay, on behalf of that relay
+#[derive(Debug)]
+pub(super) struct RelayServiceOutgoingAccessControl {
+    pub(super) registry: RelayRegistry,
+}
+
+#[async_trait]
+impl OutgoingAccessControl for RelayServiceOutgoingAccessControl {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let alias = match relay_msg.return_route().next() {
+            Ok(alias) => alias.address(),
+            Err(_) => return ockam_core::deny(),
+        };
+        self.registry.is_next_hop(alias, relay_msg)
+    }
+}
+
+impl core::fmt::Debug for RelayRegistry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RelayRegistry")
+            .field("relays", &self.relays)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+pub use storage::*;
+
+#[cfg(feature = "std")]
+mod storage {
+    use super::*;
+    use ockam_node::{FileValueStorage, ValueStorage};
+    use std::path::Path;
+
+    /// Storage for relay registrations backed by a file
+    pub struct RelaysStorage {
+        storage: FileValueStorage<BTreeMap<String, RelayInfo>>,
+    }
+
+    impl RelaysStorage {
+        /// Create a new file storage for relay registrations
+        pub async fn create(path: &Path) -> Result<Arc<dyn RelaysRepository>> {
+            Ok(Arc::new(Self {
+                storage: FileValueStorage::create(path).await?,
+            }))
+        }
+    }
+
+    #[async_trait]
+    impl RelaysRepository for RelaysStorage {
+        async fn put(&self, relay: RelayInfo) -> Result<()> {
+            self.storage
+                .update_value(move |mut relays: BTreeMap<String, RelayInfo>| {
+                    relays.insert(relay.alias.clone(), relay.clone());
+                    Ok(relays)
+                })
+                .await
+        }
+
+        async fn delete(&self, alias: &str) -> Result<()> {
+            let alias = alias.to_string();
+            self.storage
+                .update_value(move |mut relays: BTreeMap<String, RelayInfo>| {
+                    relays.remove(&alias);
+                    Ok(relays)
+                })
+                .await
+        }
+
+        async fn list(&self) -> Result<Vec<RelayInfo>> {
+            self.storage
+                .read_value(|relays: BTreeMap<String, RelayInfo>| {
+                    Ok(relays.into_values().collect())
+                })
+                .await
+        }
+    }
+}
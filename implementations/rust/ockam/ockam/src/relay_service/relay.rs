@@ -1,49 +1,58 @@
-use crate::Context;
+use crate::relay_service::registry::{RelayOutgoingAccessControl, RelayRegistry};
+use crate::{Context, OckamError};
 use ockam_core::compat::sync::Arc;
-use ockam_core::compat::{boxed::Box, vec::Vec};
+use ockam_core::compat::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
 use ockam_core::{
-    Address, AllowAll, AllowOnwardAddress, Any, IncomingAccessControl, LocalMessage,
-    OutgoingAccessControl, Result, Route, Routed, TransportMessage, Worker,
+    Address, Any, IncomingAccessControl, LocalMessage, Result, Route, Routed, TransportMessage,
+    Worker,
 };
 use ockam_node::WorkerBuilder;
 use tracing::info;
 
 pub(super) struct Relay {
-    forward_route: Route,
+    alias: String,
+    registry: RelayRegistry,
     // this option will be `None` after this worker is initialized, because
     // while initializing, the worker will send the payload contained in this
-    // field to the `forward_route`, to indicate a successful connection
-    payload: Option<Vec<u8>>,
+    // field to the registration route, to indicate a successful connection.
+    // It is also `None` for relays restored from a persisted registration
+    registration: Option<(Route, Vec<u8>)>,
 }
 
 impl Relay {
     pub(super) async fn create(
         ctx: &Context,
         address: Address,
-        forward_route: Route,
-        registration_payload: Vec<u8>,
+        registry: RelayRegistry,
+        registration: Option<(Route, Vec<u8>)>,
         incoming_access_control: Arc<dyn IncomingAccessControl>,
     ) -> Result<()> {
-        info!("Created new alias {} for {}", address, forward_route);
-
-        // Should be able to reach last and second last hops
-        let outgoing_access_control: Arc<dyn OutgoingAccessControl> = if forward_route.len() == 1 {
-            // We are accessed with our node, no transport is involved
-            Arc::new(AllowAll)
-        } else {
-            let next_hop = forward_route.next()?.clone();
-            Arc::new(AllowOnwardAddress(next_hop))
+        let alias = address.address().to_string();
+        if let Some(forward_route) = registry.forward_route(&alias) {
+            info!("Created new alias {} for {}", address, forward_route);
+        }
+
+        // Should be able to reach the next hop of the forward route, which can change
+        // when the relay is registered again
+        let outgoing_access_control = RelayOutgoingAccessControl {
+            registry: registry.clone(),
+            alias: alias.clone(),
         };
 
         let relay = Self {
-            forward_route,
-            payload: Some(registration_payload.clone()),
+            alias,
+            registry,
+            registration,
         };
 
         WorkerBuilder::new(relay)
             .with_address(address)
             .with_incoming_access_control_arc(incoming_access_control)
-            .with_outgoing_access_control_arc(outgoing_access_control)
+            .with_outgoing_access_control(outgoing_access_control)
             .start(ctx)
             .await?;
 
@@ -57,17 +66,18 @@ impl Worker for Relay {
     type Message = Any;
 
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        let payload = self
-            .payload
-            .take()
-            .expect("payload must be available on init");
-        let msg = TransportMessage::v1(self.forward_route.clone(), ctx.address(), payload);
+        if let Some((registration_route, payload)) = self.registration.take() {
+            let msg = TransportMessage::v1(registration_route, ctx.address(), payload);
+            ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
+        }
 
-        ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
+        ctx.metrics().record_relay_created();
 
-        // Remove the last hop so that just route to the node itself is left
-        self.forward_route.modify().pop_back();
+        Ok(())
+    }
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        ctx.metrics().record_relay_removed();
         Ok(())
     }
 
@@ -82,11 +92,16 @@ impl Worker for Relay {
         // Remove my address from the onward_route
         transport_message.onward_route.step()?;
 
+        let forward_route = self
+            .registry
+            .forward_route(&self.alias)
+            .ok_or(OckamError::UnknownForwarderDestinationAddress)?;
+
         // Prepend forward route
         transport_message
             .onward_route
             .modify()
-            .prepend_route(self.forward_route.clone());
+            .prepend_route(forward_route);
 
         let next_hop = transport_message.onward_route.next()?.clone();
         let prev_hop = transport_message.return_route.next()?.clone();
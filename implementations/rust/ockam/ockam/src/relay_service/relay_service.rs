@@ -1,9 +1,14 @@
+use crate::relay_service::registry::RelayServiceOutgoingAccessControl;
 use crate::relay_service::relay::Relay;
-use crate::{Context, RelayServiceOptions};
+use crate::{Context, OckamError, RelayInfo, RelayServiceOptions};
 use core::str::from_utf8;
 use ockam_core::compat::boxed::Box;
-use ockam_core::{Address, Any, DenyAll, Result, Routed, Worker};
+use ockam_core::compat::string::ToString;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Address, Any, LocalMessage, Result, Routed, TransportMessage, Worker};
+use ockam_identity::{utils, IdentitySecureChannelLocalInfo};
 use ockam_node::WorkerBuilder;
+use tracing::{info, warn};
 
 /// Alias worker to register remote workers under local names.
 ///
@@ -16,7 +21,8 @@ pub struct RelayService {
 }
 
 impl RelayService {
-    /// Start a forwarding service
+    /// Start a forwarding service.
+    /// The relays persisted in the registry of the options, if any, are restored first
     pub async fn create(
         ctx: &Context,
         address: impl Into<Address>,
@@ -26,14 +32,36 @@ impl RelayService {
 
         options.setup_flow_control_for_relay_service(ctx.flow_controls(), &address);
 
+        for relay in options.registry.restore().await? {
+            let relay_address = Address::from_string(relay.alias());
+            info!(
+                "Restoring alias {} for {}",
+                relay_address,
+                relay.forward_route()
+            );
+            options.setup_flow_control_for_relay(ctx.flow_controls(), &relay_address);
+            Relay::create(
+                ctx,
+                relay_address,
+                options.registry.clone(),
+                None,
+                options.relays_incoming_access_control.clone(),
+            )
+            .await?;
+        }
+
         let service_incoming_access_control = options.service_incoming_access_control.clone();
+        // The service only sends replies to registrations of existing relays
+        let service_outgoing_access_control = RelayServiceOutgoingAccessControl {
+            registry: options.registry.clone(),
+        };
 
         let s = Self { options };
 
         WorkerBuilder::new(s)
             .with_address(address)
             .with_incoming_access_control_arc(service_incoming_access_control)
-            .with_outgoing_access_control(DenyAll)
+            .with_outgoing_access_control(service_outgoing_access_control)
             .start(ctx)
             .await?;
 
@@ -51,29 +79,66 @@ impl Worker for RelayService {
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
-        let forward_route = msg.return_route();
-        let payload = msg.into_transport_message().payload;
+        let local_message = msg.into_local_message();
+        let owner = IdentitySecureChannelLocalInfo::find_info(&local_message)
+            .ok()
+            .map(|info| info.their_identity_id());
+        let registration_route = local_message.transport().return_route.clone();
+        let payload = local_message.into_transport_message().payload;
 
         let random_address = Address::random_tagged("Relay.service");
 
         // TODO: assume that the first byte is length, ignore it.
         // We have to improve this actually parse the payload.
-        let address = match payload.get(1..) {
+        let (address, is_static) = match payload.get(1..) {
             Some(address) => match from_utf8(address) {
-                Ok(v) if v != "register" => Address::from_string(v),
-                _ => random_address,
+                Ok(v) if v != "register" => (Address::from_string(v), true),
+                _ => (random_address, false),
             },
-            None => random_address,
+            None => (random_address, false),
         };
 
+        // Remove the last hop so that just route to the node itself is left
+        let mut forward_route = registration_route.clone();
+        forward_route.modify().pop_back();
+
+        let alias = address.address().to_string();
+        let existing = self.options.registry.get(&alias);
+
+        if let Some(existing) = &existing {
+            // An alias registered by an identity can only be refreshed by that identity.
+            // An alias registered without an identity can only be refreshed from the same route
+            let is_refresh = match existing.owner() {
+                Some(existing_owner) => owner.as_ref() == Some(existing_owner),
+                None => owner.is_none() && existing.forward_route() == &forward_route,
+            };
+            if !is_refresh {
+                warn!("Relay {} is already registered", alias);
+                return Err(OckamError::AliasAlreadyRegistered.into());
+            }
+        }
+
+        // Relays without an owner are not persisted: once the node is restarted,
+        // their route is gone and nobody could prove that they can register them again
+        let persistent = is_static && owner.is_some();
+        let relay = RelayInfo::new(alias, forward_route, owner, utils::now()?);
+        self.options.registry.register(relay, persistent).await?;
+
+        if existing.is_some() {
+            // The relay is already running: it now uses the new forward route,
+            // acknowledge the registration on its behalf
+            let msg = TransportMessage::v1(registration_route, address, payload);
+            return ctx.forward(LocalMessage::new(msg, Vec::new())).await;
+        }
+
         self.options
             .setup_flow_control_for_relay(ctx.flow_controls(), &address);
 
         Relay::create(
             ctx,
             address,
-            forward_route,
-            payload,
+            self.options.registry.clone(),
+            Some((registration_route, payload)),
             self.options.relays_incoming_access_control.clone(),
         )
         .await?;
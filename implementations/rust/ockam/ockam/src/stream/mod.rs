@@ -159,7 +159,8 @@ impl Stream {
     ///
     /// The `route` parameter is the route to a remote which hosts a
     /// `stream_service` and `stream_index_service`, such as
-    /// hub.ockam.io.
+    /// hub.ockam.io, or a node running a [`StreamService`](crate::StreamService)
+    /// and a [`StreamIndexService`](crate::StreamIndexService).
     ///
     /// Streams that do not already exists will be created, and
     /// existing stream identifiers will automatically be re-used.
@@ -0,0 +1,97 @@
+use crate::identity::storage::Storage;
+use crate::protocols::stream::{requests::*, responses::*};
+use crate::protocols::{ProtocolParser, ProtocolPayload};
+use crate::stream_service::storage::StreamIndices;
+use crate::{Context, StreamServiceOptions};
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{Address, AllowAll, Any, Decodable, Result, Routed, Worker};
+use ockam_node::WorkerBuilder;
+
+/// Service persisting the index of the last message consumed by each client of a stream.
+///
+/// Clients are identified by their `client_id`, so that a consumer restarted with the same
+/// `client_id` resumes reading a stream where it stopped.
+pub struct StreamIndexService {
+    indices: StreamIndices,
+}
+
+impl StreamIndexService {
+    /// Start a stream index service
+    pub async fn create(
+        ctx: &Context,
+        address: impl Into<Address>,
+        storage: Arc<dyn Storage>,
+        options: StreamServiceOptions,
+    ) -> Result<()> {
+        let address = address.into();
+
+        options.setup_flow_control(ctx.flow_controls(), &address);
+
+        let s = Self {
+            indices: StreamIndices::new(storage),
+        };
+
+        WorkerBuilder::new(s)
+            .with_address(address)
+            .with_incoming_access_control_arc(options.incoming_access_control)
+            .with_outgoing_access_control(AllowAll)
+            .start(ctx)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[crate::worker]
+impl Worker for StreamIndexService {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let pp = ProtocolPayload::decode(msg.payload())?;
+        match Request::parse(pp) {
+            Ok(Request::Index(IndexRequest::Get {
+                client_id,
+                stream_name,
+            })) => {
+                let index = self.indices.get(&stream_name, &client_id).await?;
+                debug!(
+                    "Get index of client '{}' for stream '{}': {:?}",
+                    client_id, stream_name, index
+                );
+                ctx.send(
+                    msg.return_route(),
+                    IndexResponse::new(client_id, stream_name, index),
+                )
+                .await
+            }
+            Ok(Request::Index(IndexRequest::Save {
+                client_id,
+                stream_name,
+                index,
+            })) => {
+                debug!(
+                    "Save index of client '{}' for stream '{}': {}",
+                    client_id,
+                    stream_name,
+                    index.u64()
+                );
+                self.indices
+                    .save(&stream_name, &client_id, index.u64())
+                    .await
+            }
+            _ => {
+                warn!(
+                    "Unhandled message for stream index service {}",
+                    ctx.address()
+                );
+                Ok(())
+            }
+        }
+    }
+}
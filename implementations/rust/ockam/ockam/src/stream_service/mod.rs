@@ -0,0 +1,13 @@
+mod index_service;
+mod options;
+mod storage;
+#[allow(clippy::module_inception)]
+mod stream_service;
+mod stream_worker;
+
+pub use index_service::*;
+pub use options::*;
+#[cfg(feature = "std")]
+pub use storage::FileStreamStorage;
+pub use storage::{InMemoryStreamStorage, StreamStorage, DEFAULT_PULL_LIMIT};
+pub use stream_service::*;
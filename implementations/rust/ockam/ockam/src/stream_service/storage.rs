@@ -0,0 +1,319 @@
+use crate::identity::storage::Storage;
+use crate::OckamError;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, Result};
+
+/// Namespace of the persisted consumer indices
+const STREAM_INDEX: &str = "stream_index";
+
+/// Maximum number of messages returned by a pull request which doesn't specify a limit
+pub const DEFAULT_PULL_LIMIT: u64 = 64;
+
+/// Storage for the messages of the streams hosted by a [`StreamService`](crate::StreamService).
+///
+/// The storage is the only owner of the length of each stream so that several services
+/// sharing the same storage never assign the same index to two messages.
+#[async_trait]
+pub trait StreamStorage: Send + Sync + 'static {
+    /// Append a message to a stream and return its index
+    async fn append(&self, stream_name: &str, data: Vec<u8>) -> Result<u64>;
+
+    /// Read at most `limit` messages of a stream, starting at `index`
+    async fn read(&self, stream_name: &str, index: u64, limit: u64) -> Result<Vec<(u64, Vec<u8>)>>;
+}
+
+/// Non-persistent stream storage, stored in RAM
+#[derive(Clone, Default)]
+pub struct InMemoryStreamStorage {
+    streams: Arc<RwLock<BTreeMap<String, Vec<Vec<u8>>>>>,
+}
+
+impl InMemoryStreamStorage {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Constructor
+    pub fn create() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+}
+
+#[async_trait]
+impl StreamStorage for InMemoryStreamStorage {
+    async fn append(&self, stream_name: &str, data: Vec<u8>) -> Result<u64> {
+        let mut streams = self.streams.write().unwrap();
+        let messages = streams.entry(stream_name.to_string()).or_default();
+        messages.push(data);
+        Ok(messages.len() as u64 - 1)
+    }
+
+    async fn read(&self, stream_name: &str, index: u64, limit: u64) -> Result<Vec<(u64, Vec<u8>)>> {
+        let streams = self.streams.read().unwrap();
+        let messages = match streams.get(stream_name) {
+            Some(messages) => messages,
+            None => return Ok(vec![]),
+        };
+        Ok(messages
+            .iter()
+            .enumerate()
+            .skip(index.min(usize::MAX as u64) as usize)
+            .take(limit.min(usize::MAX as u64) as usize)
+            .map(|(i, data)| (i as u64, data.clone()))
+            .collect())
+    }
+}
+
+/// Append-only log of the messages of a stream, persisted in a [`StreamStorage`]
+pub(super) struct StreamLog {
+    stream_name: String,
+    storage: Arc<dyn StreamStorage>,
+}
+
+impl StreamLog {
+    /// Open the log of a stream, it is created when the first message is appended
+    pub(super) fn open(stream_name: String, storage: Arc<dyn StreamStorage>) -> Self {
+        Self {
+            stream_name,
+            storage,
+        }
+    }
+
+    /// Name of the stream
+    pub(super) fn stream_name(&self) -> &str {
+        &self.stream_name
+    }
+
+    /// Append a message to the log and return its index
+    pub(super) async fn append(&self, data: Vec<u8>) -> Result<u64> {
+        self.storage.append(&self.stream_name, data).await
+    }
+
+    /// Read at most `limit` messages starting at `index`.
+    /// A limit of zero returns at most [`DEFAULT_PULL_LIMIT`] messages
+    pub(super) async fn read(&self, index: u64, limit: u64) -> Result<Vec<(u64, Vec<u8>)>> {
+        let limit = if limit == 0 {
+            DEFAULT_PULL_LIMIT
+        } else {
+            limit
+        };
+        self.storage.read(&self.stream_name, index, limit).await
+    }
+}
+
+/// Consumer indices of all the streams, persisted in a [`Storage`]
+pub(super) struct StreamIndices {
+    storage: Arc<dyn Storage>,
+}
+
+impl StreamIndices {
+    pub(super) fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Return the index saved by a client for a given stream
+    pub(super) async fn get(&self, stream_name: &str, client_id: &str) -> Result<Option<u64>> {
+        match self
+            .storage
+            .get(&Self::index_id(stream_name), client_id)
+            .await?
+        {
+            Some(index) => Ok(Some(decode_u64(&index)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Save the index of a client for a given stream
+    pub(super) async fn save(&self, stream_name: &str, client_id: &str, index: u64) -> Result<()> {
+        self.storage
+            .set(
+                &Self::index_id(stream_name),
+                client_id.to_string(),
+                index.to_be_bytes().to_vec(),
+            )
+            .await
+    }
+
+    fn index_id(stream_name: &str) -> String {
+        format!("{}.{}", STREAM_INDEX, stream_name)
+    }
+}
+
+fn decode_u64(bytes: &[u8]) -> Result<u64> {
+    let bytes: [u8; 8] = bytes.try_into().map_err(|_| OckamError::InvalidParameter)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+#[cfg(feature = "std")]
+pub use file::*;
+
+#[cfg(feature = "std")]
+mod file {
+    use super::*;
+    use crate::tokio::task;
+    use ockam_core::errcode::{Kind, Origin};
+    use ockam_core::Error;
+    use std::fs::{File, OpenOptions};
+    use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+
+    /// Stream storage keeping one append-only file per stream in a directory.
+    ///
+    /// Each message is stored as a big-endian `u32` length followed by its data, so that
+    /// appending a message only writes that message. The position of each message is
+    /// indexed in memory when the file of a stream is first opened.
+    #[derive(Clone)]
+    pub struct FileStreamStorage {
+        directory: PathBuf,
+        streams: Arc<Mutex<BTreeMap<String, StreamFile>>>,
+    }
+
+    struct StreamFile {
+        file: File,
+        offsets: Vec<u64>,
+        end: u64,
+    }
+
+    impl FileStreamStorage {
+        /// Create a stream storage in the given directory
+        pub async fn create(directory: &Path) -> Result<Arc<Self>> {
+            std::fs::create_dir_all(directory).map_err(|e| map_io_err(directory, e))?;
+            Ok(Arc::new(Self {
+                directory: directory.to_path_buf(),
+                streams: Default::default(),
+            }))
+        }
+
+        /// Run a blocking operation on the file of a stream, opening it if necessary
+        async fn with_stream<R: Send + 'static>(
+            &self,
+            stream_name: &str,
+            f: impl FnOnce(&mut StreamFile) -> Result<R> + Send + 'static,
+        ) -> Result<R> {
+            let streams = self.streams.clone();
+            let stream_name = stream_name.to_string();
+            // Stream names are chosen by clients, don't use them as file names directly
+            let path = self
+                .directory
+                .join(format!("{}.log", hex::encode(stream_name.as_bytes())));
+            let tr = move || -> Result<R> {
+                let mut streams = streams.lock().unwrap();
+                let stream = match streams.entry(stream_name) {
+                    std::collections::btree_map::Entry::Occupied(e) => e.into_mut(),
+                    std::collections::btree_map::Entry::Vacant(e) => {
+                        e.insert(StreamFile::open(&path)?)
+                    }
+                };
+                f(stream)
+            };
+            task::spawn_blocking(tr)
+                .await
+                .map_err(|e| Error::new(Origin::Application, Kind::Io, e))?
+        }
+    }
+
+    impl StreamFile {
+        fn open(path: &Path) -> Result<Self> {
+            let file = OpenOptions::new()
+                .read(true)
+                .append(true)
+                .create(true)
+                .open(path)
+                .map_err(|e| map_io_err(path, e))?;
+            let size = file.metadata().map_err(|e| map_io_err(path, e))?.len();
+
+            let mut offsets = vec![];
+            let mut end = 0;
+            let mut reader = BufReader::new(&file);
+            while end + 4 <= size {
+                let mut length = [0u8; 4];
+                reader
+                    .read_exact(&mut length)
+                    .map_err(|e| map_io_err(path, e))?;
+                let next = end + 4 + u32::from_be_bytes(length) as u64;
+                if next > size {
+                    break;
+                }
+                reader
+                    .seek_relative(next as i64 - end as i64 - 4)
+                    .map_err(|e| map_io_err(path, e))?;
+                offsets.push(end);
+                end = next;
+            }
+
+            // Drop a message which was only partially written
+            if end < size {
+                warn!("Truncating the incomplete last message of {:?}", path);
+                file.set_len(end).map_err(|e| map_io_err(path, e))?;
+            }
+
+            Ok(Self { file, offsets, end })
+        }
+
+        fn append(&mut self, data: Vec<u8>) -> Result<u64> {
+            let length = u32::try_from(data.len()).map_err(|_| OckamError::InvalidParameter)?;
+            let mut record = Vec::with_capacity(4 + data.len());
+            record.extend_from_slice(&length.to_be_bytes());
+            record.extend_from_slice(&data);
+            self.file
+                .write_all(&record)
+                .and_then(|_| self.file.sync_data())
+                .map_err(|e| Error::new(Origin::Application, Kind::Io, e))?;
+            self.offsets.push(self.end);
+            self.end += record.len() as u64;
+            Ok(self.offsets.len() as u64 - 1)
+        }
+
+        fn read(&mut self, index: u64, limit: u64) -> Result<Vec<(u64, Vec<u8>)>> {
+            let length = self.offsets.len() as u64;
+            let mut messages = vec![];
+            for i in index.min(length)..length.min(index.saturating_add(limit)) {
+                self.file
+                    .seek(SeekFrom::Start(self.offsets[i as usize]))
+                    .map_err(|e| Error::new(Origin::Application, Kind::Io, e))?;
+                let mut size = [0u8; 4];
+                self.file
+                    .read_exact(&mut size)
+                    .map_err(|e| Error::new(Origin::Application, Kind::Io, e))?;
+                let mut data = vec![0u8; u32::from_be_bytes(size) as usize];
+                self.file
+                    .read_exact(&mut data)
+                    .map_err(|e| Error::new(Origin::Application, Kind::Io, e))?;
+                messages.push((i, data));
+            }
+            Ok(messages)
+        }
+    }
+
+    #[async_trait]
+    impl StreamStorage for FileStreamStorage {
+        async fn append(&self, stream_name: &str, data: Vec<u8>) -> Result<u64> {
+            self.with_stream(stream_name, move |stream| stream.append(data))
+                .await
+        }
+
+        async fn read(
+            &self,
+            stream_name: &str,
+            index: u64,
+            limit: u64,
+        ) -> Result<Vec<(u64, Vec<u8>)>> {
+            self.with_stream(stream_name, move |stream| stream.read(index, limit))
+                .await
+        }
+    }
+
+    fn map_io_err(path: &Path, err: std::io::Error) -> Error {
+        Error::new(
+            Origin::Application,
+            Kind::Io,
+            format!("{err} for path {:?}", path),
+        )
+    }
+}
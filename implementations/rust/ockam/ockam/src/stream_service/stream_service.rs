@@ -0,0 +1,122 @@
+use crate::protocols::stream::requests::*;
+use crate::protocols::{ProtocolParser, ProtocolPayload};
+use crate::stream_service::storage::StreamStorage;
+use crate::stream_service::stream_worker::StreamWorker;
+use crate::{Context, StreamServiceOptions};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::rand::{self, Rng};
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::{boxed::Box, string::String};
+use ockam_core::{Address, AllowAll, Any, Decodable, Result, Routed, Worker};
+use ockam_node::WorkerBuilder;
+
+/// Service hosting streams: append-only logs of messages persisted in a [`StreamStorage`].
+///
+/// Each stream is served by a dedicated worker which is created the first time the stream is
+/// requested. Messages pushed to a stream are kept in the order they were received and can be
+/// pulled by index.
+///
+/// To talk with this service, you can use a [`Stream`](crate::stream::Stream), which expects this
+/// service to be started at the `"stream"` address, and the [`StreamIndexService`](crate::StreamIndexService)
+/// to be started at the `"stream_index"` address.
+pub struct StreamService {
+    storage: Arc<dyn StreamStorage>,
+    options: StreamServiceOptions,
+    streams: BTreeMap<String, Address>,
+}
+
+impl StreamService {
+    /// Start a stream service
+    pub async fn create(
+        ctx: &Context,
+        address: impl Into<Address>,
+        storage: Arc<dyn StreamStorage>,
+        options: StreamServiceOptions,
+    ) -> Result<()> {
+        let address = address.into();
+
+        options.setup_flow_control(ctx.flow_controls(), &address);
+
+        let incoming_access_control = options.incoming_access_control.clone();
+
+        let s = Self {
+            storage,
+            options,
+            streams: BTreeMap::new(),
+        };
+
+        WorkerBuilder::new(s)
+            .with_address(address)
+            .with_incoming_access_control_arc(incoming_access_control)
+            .with_outgoing_access_control(AllowAll)
+            .start(ctx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Return the address of the worker serving a stream, starting it if necessary
+    async fn stream_address(&mut self, ctx: &Context, stream_name: String) -> Result<Address> {
+        if let Some(address) = self.streams.get(&stream_name) {
+            return Ok(address.clone());
+        }
+
+        let address = Address::random_tagged("StreamService.stream");
+        self.options
+            .setup_flow_control(ctx.flow_controls(), &address);
+        StreamWorker::create(
+            ctx,
+            address.clone(),
+            stream_name.clone(),
+            self.storage.clone(),
+            self.options.incoming_access_control.clone(),
+        )
+        .await?;
+
+        self.streams.insert(stream_name, address.clone());
+        Ok(address)
+    }
+}
+
+#[crate::worker]
+impl Worker for StreamService {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let pp = ProtocolPayload::decode(msg.payload())?;
+        let stream_name = match Request::parse(pp) {
+            Ok(Request::CreateStream(CreateStreamRequest { stream_name })) => stream_name,
+            _ => {
+                warn!("Unhandled message for stream service {}", ctx.address());
+                return Ok(());
+            }
+        };
+
+        let stream_name = match stream_name {
+            Some(stream_name) if stream_name.is_empty() => {
+                warn!("Cannot create a stream with an empty name");
+                return Ok(());
+            }
+            Some(stream_name) => stream_name,
+            None => {
+                let random: [u8; 16] = rand::thread_rng().gen();
+                hex::encode(random)
+            }
+        };
+
+        let address = self.stream_address(ctx, stream_name).await?;
+
+        // Forward the request to the stream worker which replies to the client
+        let mut message = msg.into_local_message();
+        let transport_message = message.transport_mut();
+        transport_message.onward_route.step()?;
+        transport_message.onward_route.modify().prepend(address);
+
+        ctx.forward(message).await
+    }
+}
@@ -0,0 +1,109 @@
+use crate::protocols::stream::{requests::*, responses::*};
+use crate::protocols::{ProtocolParser, ProtocolPayload};
+use crate::stream_service::storage::{StreamLog, StreamStorage};
+use crate::Context;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
+use ockam_core::{
+    Address, AllowAll, Any, Decodable, IncomingAccessControl, Result, Routed, Worker,
+};
+use ockam_node::WorkerBuilder;
+
+/// Worker serving the messages of a single stream
+pub(super) struct StreamWorker {
+    log: StreamLog,
+}
+
+impl StreamWorker {
+    pub(super) async fn create(
+        ctx: &Context,
+        address: Address,
+        stream_name: String,
+        storage: Arc<dyn StreamStorage>,
+        incoming_access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Result<()> {
+        info!(
+            "Created stream worker {} for stream '{}'",
+            address, stream_name
+        );
+
+        let worker = Self {
+            log: StreamLog::open(stream_name, storage),
+        };
+
+        WorkerBuilder::new(worker)
+            .with_address(address)
+            .with_incoming_access_control_arc(incoming_access_control)
+            .with_outgoing_access_control(AllowAll)
+            .start(ctx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn push(&self, request_id: u64, data: Vec<u8>) -> ProtocolPayload {
+        match self.log.append(data).await {
+            Ok(index) => PushConfirm::new(request_id, Status::Ok, index),
+            Err(e) => {
+                error!("Failed to append a message to a stream: {}", e);
+                PushConfirm::new(request_id, Status::Error, 0)
+            }
+        }
+    }
+
+    async fn pull(&self, request_id: u64, index: u64, limit: u64) -> Result<ProtocolPayload> {
+        let messages: Vec<StreamMessage> = self
+            .log
+            .read(index, limit)
+            .await?
+            .into_iter()
+            .map(|(index, data)| StreamMessage {
+                index: index.into(),
+                data,
+            })
+            .collect();
+        Ok(PullResponse::new(request_id, messages))
+    }
+}
+
+#[crate::worker]
+impl Worker for StreamWorker {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let return_route = msg.return_route();
+        let pp = ProtocolPayload::decode(msg.payload())?;
+        if !Request::check_id(pp.protocol.as_str()) {
+            warn!("Unhandled message for stream worker {}", ctx.address());
+            return Ok(());
+        }
+
+        let response = match Request::parse(pp)? {
+            // Stream creation requests are forwarded by the stream service so that
+            // clients can learn the address of this worker from the response
+            Request::CreateStream(_) => InitResponse::new(self.log.stream_name()),
+            Request::Push(PushRequest { request_id, data }) => {
+                self.push(request_id.u64(), data).await
+            }
+            Request::Pull(PullRequest {
+                request_id,
+                index,
+                limit,
+            }) => {
+                self.pull(request_id.u64(), index.u64(), limit.u64())
+                    .await?
+            }
+            Request::Index(_) => {
+                warn!("Index requests must be sent to the stream index service");
+                return Ok(());
+            }
+        };
+
+        ctx.send(return_route, response).await
+    }
+}
@@ -2,9 +2,11 @@
 
 mod delivery;
 mod ordering;
+mod persistence;
 
 /// System handler hooks for Ockam pipes
 pub mod pipe {
     pub use super::delivery::{ReceiverConfirm, SenderConfirm};
     pub use super::ordering::{ReceiverOrdering, SenderOrdering};
+    pub use super::persistence::{ReceiverPersistence, SenderPersistence};
 }
@@ -0,0 +1,392 @@
+use crate::{
+    // Warning: delay::DelayedEvent is not the ockam_node one and will
+    // be deprecated soon!
+    delay::DelayedEvent,
+    pipe2::PipeStorage,
+    Context,
+    OckamError,
+    OckamMessage,
+    Result,
+    Routed,
+    SystemHandler,
+};
+use ockam_core::{
+    async_trait,
+    compat::{
+        boxed::Box,
+        collections::BTreeMap,
+        rand::random,
+        string::{String, ToString},
+        vec::Vec,
+    },
+    Address, Any, Decodable, Encodable, Message, Route,
+};
+use serde::{Deserialize, Serialize};
+
+/// Interval between two re-transmissions of unacknowledged messages
+const RESEND_INTERVAL_SECS: u64 = 5;
+
+/// A small metadata type to encode the message index
+#[derive(Message, Serialize, Deserialize)]
+struct Index(u64);
+
+fn outbox_key(sender_id: &str, index: u64) -> String {
+    format!("sender.{sender_id}.outbox.{index}")
+}
+
+fn next_index_key(sender_id: &str) -> String {
+    format!("sender.{sender_id}.next_index")
+}
+
+fn first_index_key(sender_id: &str) -> String {
+    format!("sender.{sender_id}.first_index")
+}
+
+fn delivered_key(sender_id: &str) -> String {
+    format!("receiver.{sender_id}.delivered")
+}
+
+async fn get_index(storage: &PipeStorage, key: &String) -> Result<Option<u64>> {
+    match storage.get(key).await? {
+        Some(data) => Ok(Some(Index::decode(&data)?.0)),
+        None => Ok(None),
+    }
+}
+
+async fn put_index(storage: &PipeStorage, key: String, index: u64) -> Result<()> {
+    storage.put(key, Index(index).encode()?).await
+}
+
+fn message_type(msg: &OckamMessage) -> Option<&str> {
+    msg.generic
+        .as_ref()
+        .and_then(|data| data.get("ockam.pipe.type"))
+        .and_then(|tt| core::str::from_utf8(tt).ok())
+}
+
+/// Sender side of a persistent pipe
+///
+/// Every outgoing message is written to an outbox in the pipe
+/// storage before being sent, and only removed from it once the
+/// receiver acknowledged it.  When the sender is restarted with the
+/// same storage, all the messages left in the outbox are sent again.
+#[derive(Clone)]
+pub struct SenderPersistence {
+    storage: PipeStorage,
+    next: Option<Address>,
+    this: Option<Address>,
+    /// Identifier of this sender, which survives restarts
+    sender_id: String,
+    /// Index of the next outgoing message
+    next_index: u64,
+    /// Messages which were not acknowledged yet
+    pending: BTreeMap<u64, OckamMessage>,
+}
+
+impl SenderPersistence {
+    pub fn new(storage: PipeStorage) -> Self {
+        Self {
+            storage,
+            next: None,
+            this: None,
+            sender_id: String::new(),
+            next_index: 1,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Load the sender state and the content of the outbox
+    async fn load(&mut self) -> Result<()> {
+        let sender_id_key = "sender.id".to_string();
+        self.sender_id = match self.storage.get(&sender_id_key).await? {
+            Some(data) => String::decode(&data)?,
+            None => {
+                let sender_id = hex::encode(random::<[u8; 8]>());
+                self.storage.put(sender_id_key, sender_id.encode()?).await?;
+                sender_id
+            }
+        };
+
+        let first_index = get_index(&self.storage, &first_index_key(&self.sender_id))
+            .await?
+            .unwrap_or(1);
+        self.next_index = get_index(&self.storage, &next_index_key(&self.sender_id))
+            .await?
+            .unwrap_or(1);
+
+        for index in first_index..self.next_index {
+            if let Some(data) = self
+                .storage
+                .get(&outbox_key(&self.sender_id, index))
+                .await?
+            {
+                self.pending.insert(index, OckamMessage::decode(&data)?);
+            }
+        }
+
+        if !self.pending.is_empty() {
+            info!(
+                "Restored {} unacknowledged pipe messages from the outbox",
+                self.pending.len()
+            );
+        }
+        Ok(())
+    }
+
+    async fn send_pending(&self, ctx: &mut Context, msg: OckamMessage) -> Result<()> {
+        let this = self.this.as_ref().unwrap();
+        ctx.send(
+            self.next.as_ref().unwrap().clone(),
+            msg.scope_data(this.encode()?),
+        )
+        .await
+    }
+
+    async fn schedule_resend(&self, ctx: &Context, secs: Option<u64>) -> Result<()> {
+        let event = DelayedEvent::new(ctx, self.this.as_ref().unwrap().clone().into(), {
+            OckamMessage::new(Any)?.generic_data(
+                "ockam.pipe.type",
+                "ockam.pipe.resend_notify".as_bytes().to_vec(),
+            )
+        })
+        .await?;
+        match secs {
+            Some(secs) => event.with_seconds(secs).spawn(),
+            None => event.spawn(),
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SystemHandler<Context, OckamMessage> for SenderPersistence {
+    async fn initialize(
+        &mut self,
+        ctx: &mut Context,
+        routes: &mut BTreeMap<String, Address>,
+    ) -> Result<()> {
+        self.next = Some(
+            routes
+                .remove("default")
+                .ok_or(OckamError::SystemInvalidConfiguration)?,
+        );
+        self.this = Some(
+            routes
+                .remove("self")
+                .ok_or(OckamError::SystemInvalidConfiguration)?,
+        );
+        self.load().await?;
+
+        // Trigger the re-transmission of the restored outbox as soon
+        // as the pipe is running
+        self.schedule_resend(ctx, None).await
+    }
+
+    async fn handle_message(
+        &mut self,
+        self_addr: Address,
+        ctx: &mut Context,
+        msg: Routed<OckamMessage>,
+    ) -> Result<()> {
+        trace!(
+            "SenderPersistence '{}' handling incoming message",
+            self_addr
+        );
+
+        let msg = msg.body();
+        let msg_type = message_type(&msg).map(|tt| tt.to_string());
+        match msg_type.as_deref() {
+            // A user message is stored in the outbox before being
+            // forwarded to the next step in the system
+            None => {
+                let index = self.next_index;
+                self.next_index += 1;
+
+                let outer_msg = OckamMessage::wrap(msg)?
+                    .scope_data(self.sender_id.encode()?)
+                    .scope_data(Index(index).encode()?);
+
+                self.storage
+                    .put(outbox_key(&self.sender_id, index), outer_msg.encode()?)
+                    .await?;
+                put_index(
+                    &self.storage,
+                    next_index_key(&self.sender_id),
+                    self.next_index,
+                )
+                .await?;
+                self.pending.insert(index, outer_msg.clone());
+
+                self.send_pending(ctx, outer_msg).await?;
+            }
+
+            // An acknowledged message can be removed from the outbox
+            Some("ockam.pipe.ack") => {
+                let Index(index) = msg
+                    .scope
+                    .get(0)
+                    .ok_or_else(|| OckamError::InvalidParameter.into())
+                    .and_then(|idx| Index::decode(idx))?;
+                debug!("Received ACK for persisted message: {}", index);
+
+                if self.pending.remove(&index).is_some() {
+                    self.storage
+                        .delete(&outbox_key(&self.sender_id, index))
+                        .await?;
+                    let first_index = self
+                        .pending
+                        .keys()
+                        .next()
+                        .cloned()
+                        .unwrap_or(self.next_index);
+                    put_index(&self.storage, first_index_key(&self.sender_id), first_index).await?;
+                }
+            }
+
+            // Periodically re-send all the unacknowledged messages
+            Some("ockam.pipe.resend_notify") => {
+                for (index, msg) in self.pending.iter() {
+                    debug!("Re-sending unacknowledged message: {}", index);
+                    self.send_pending(ctx, msg.clone()).await?;
+                }
+                self.schedule_resend(ctx, Some(RESEND_INTERVAL_SECS))
+                    .await?;
+            }
+
+            // Any other type is an invalid message that will be dropped
+            Some(tt) => {
+                error!("Invalid OckamMessage type '{}'.  dropping message", tt);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Receiver side of a persistent pipe
+///
+/// Messages are delivered in order and the index of the last
+/// delivered message is persisted for each sender, so that messages
+/// re-sent after a restart of either end are acknowledged again but
+/// not delivered twice.
+#[derive(Clone)]
+pub struct ReceiverPersistence {
+    storage: PipeStorage,
+    next: Option<Address>,
+    /// Index of the last delivered message for each sender
+    delivered: BTreeMap<String, u64>,
+    /// Messages received out-of-order, with the route for their ACK
+    journal: BTreeMap<(String, u64), (OckamMessage, Route)>,
+}
+
+impl ReceiverPersistence {
+    pub fn new(storage: PipeStorage) -> Self {
+        Self {
+            storage,
+            next: None,
+            delivered: BTreeMap::new(),
+            journal: BTreeMap::new(),
+        }
+    }
+
+    async fn last_delivered(&mut self, sender_id: &str) -> Result<u64> {
+        if let Some(index) = self.delivered.get(sender_id) {
+            return Ok(*index);
+        }
+        let index = get_index(&self.storage, &delivered_key(sender_id))
+            .await?
+            .unwrap_or(0);
+        self.delivered.insert(sender_id.to_string(), index);
+        Ok(index)
+    }
+
+    async fn ack(ctx: &mut Context, index: u64, ack_route: Route) -> Result<()> {
+        let ack = OckamMessage::new(Any)?
+            .scope_data(Index(index).encode()?)
+            .generic_data("ockam.pipe.type", "ockam.pipe.ack".as_bytes().to_vec());
+        ctx.send(ack_route, ack).await
+    }
+}
+
+#[async_trait]
+impl SystemHandler<Context, OckamMessage> for ReceiverPersistence {
+    async fn initialize(
+        &mut self,
+        _: &mut Context,
+        routes: &mut BTreeMap<String, Address>,
+    ) -> Result<()> {
+        self.next = Some(
+            routes
+                .remove("default")
+                .ok_or(OckamError::SystemInvalidConfiguration)?,
+        );
+        Ok(())
+    }
+
+    async fn handle_message(
+        &mut self,
+        self_addr: Address,
+        ctx: &mut Context,
+        msg: Routed<OckamMessage>,
+    ) -> Result<()> {
+        trace!(
+            "ReceiverPersistence '{}' handling incoming message",
+            self_addr
+        );
+
+        // First grab the return route so we may edit it later
+        let mut return_route = msg.return_route();
+
+        // Grab the scope metadata from the message
+        let inner = msg.body();
+        let sender_id = inner
+            .scope
+            .get(0)
+            .ok_or_else(|| OckamError::InvalidParameter.into())
+            .and_then(|id| String::decode(id))?;
+        let Index(index) = inner
+            .scope
+            .get(1)
+            .ok_or_else(|| OckamError::InvalidParameter.into())
+            .and_then(|idx| Index::decode(idx))?;
+        let ack_addr = inner
+            .scope
+            .get(2)
+            .ok_or_else(|| OckamError::InvalidParameter.into())
+            .and_then(|addr| Address::decode(addr))?;
+        let ack_route = return_route.modify().pop_back().append(ack_addr).into();
+
+        let mut last_delivered = self.last_delivered(&sender_id).await?;
+        if index <= last_delivered {
+            debug!("Ignoring already delivered message: {}", index);
+            return Self::ack(ctx, index, ack_route).await;
+        }
+        if index > last_delivered + 1 {
+            info!("Enqueueing message with index {}", index);
+            self.journal
+                .insert((sender_id, index), (inner.peel()?, ack_route));
+            return Ok(());
+        }
+
+        // Deliver the message and all the queued messages which
+        // directly follow it
+        let next_addr = self.next.as_ref().unwrap().clone();
+        let mut to_deliver = vec![(index, inner.peel()?, ack_route)];
+        while let Some((msg, ack_route)) = self
+            .journal
+            .remove(&(sender_id.clone(), index + to_deliver.len() as u64))
+        {
+            to_deliver.push((index + to_deliver.len() as u64, msg, ack_route));
+        }
+
+        for (index, msg, ack_route) in to_deliver {
+            ctx.send(next_addr.clone(), msg).await?;
+            last_delivered = index;
+            put_index(&self.storage, delivered_key(&sender_id), last_delivered).await?;
+            Self::ack(ctx, index, ack_route).await?;
+        }
+        self.delivered.insert(sender_id, last_delivered);
+
+        Ok(())
+    }
+}
@@ -299,3 +299,45 @@ async fn layered_pipe(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+/// A windowed pipe delivers messages in order while at most `window`
+/// messages are waiting for a credit
+#[ockam::test]
+async fn static_windowed_pipe(ctx: &mut Context) -> Result<()> {
+    receiver_with_behavior(ctx, "pipe-receiver", ReceiverWindow::new(2)).await?;
+    let tx = connect_static_with_behavior(ctx, "pipe-receiver", SenderWindow::new(2)).await?;
+
+    let mut child_ctx = ctx.new_detached("child", AllowAll, AllowAll).await?;
+    let sent: Vec<String> = (0..10).map(|i| format!("Message number {i}")).collect();
+    for msg in sent.iter() {
+        child_ctx
+            .send(route![tx.clone(), "child"], msg.clone())
+            .await?;
+    }
+
+    for msg in sent {
+        let received = child_ctx.receive::<String>().await?;
+        assert_eq!(received, msg);
+    }
+
+    assert_eq!(ctx.metrics().pipe_retransmissions(), 0);
+
+    ctx.stop().await
+}
+
+/// Messages which are never credited by the receiver are re-sent
+#[ockam::test]
+async fn windowed_pipe_retransmits(ctx: &mut Context) -> Result<()> {
+    receiver_with_behavior(ctx, "pipe-receiver", DropDelivery).await?;
+    let tx = connect_static_with_behavior(ctx, "pipe-receiver", SenderWindow::new(1)).await?;
+
+    let mut child_ctx = ctx.new_detached("child", AllowAll, AllowAll).await?;
+    child_ctx
+        .send(route![tx.clone(), "child"], "Hello Ockam!".to_string())
+        .await?;
+
+    ctx.sleep(std::time::Duration::from_secs(6)).await;
+    assert!(ctx.metrics().pipe_retransmissions() > 0);
+
+    ctx.stop().await
+}
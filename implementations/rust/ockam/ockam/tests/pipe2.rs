@@ -1,5 +1,8 @@
 use ockam::{pipe2::PipeBuilder, Context};
+use ockam_core::compat::sync::Arc;
 use ockam_core::{compat::string::String, route, Address, AllowAll, Result};
+use ockam_node::InMemoryKeyValueStorage;
+use std::time::Duration;
 use tracing::info;
 
 #[ockam::test]
@@ -207,3 +210,88 @@ async fn dynamic_delivery_and_ordering_pipe(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+#[ockam::test]
+async fn fixed_persistent_pipe(ctx: &mut Context) -> Result<()> {
+    let rx_addr = Address::random_local();
+    let storage = Arc::new(InMemoryKeyValueStorage::default());
+
+    // Start a static receiver
+    let rx = PipeBuilder::fixed()
+        .receive(rx_addr.clone())
+        .persistent_storage(storage.clone())
+        .build(ctx)
+        .await?;
+    info!("Created receiver pipe: {}", rx.addr());
+
+    // Connect to a static receiver
+    let sender = PipeBuilder::fixed()
+        .connect(rx_addr)
+        .persistent_storage(storage)
+        .build(ctx)
+        .await?;
+
+    let mut child_ctx = ctx.new_detached("child", AllowAll, AllowAll).await?;
+    for i in 0..3 {
+        let msg = format!("Hello through the pipe {i}");
+        child_ctx
+            .send(route![sender.addr(), "child"], msg.clone())
+            .await?;
+
+        let msg2 = child_ctx.receive::<String>().await?;
+        assert_eq!(msg, *msg2);
+    }
+
+    ctx.stop().await
+}
+
+#[ockam::test]
+async fn persistent_pipe_survives_sender_restart(ctx: &mut Context) -> Result<()> {
+    let rx_addr = Address::random_local();
+    let sender_storage = Arc::new(InMemoryKeyValueStorage::default());
+    let receiver_storage = Arc::new(InMemoryKeyValueStorage::default());
+
+    // Send a message while the receiver doesn't exist yet
+    let sender = PipeBuilder::fixed()
+        .connect(rx_addr.clone())
+        .persistent_storage(sender_storage.clone())
+        .build(ctx)
+        .await?;
+
+    let mut child_ctx = ctx.new_detached("child", AllowAll, AllowAll).await?;
+    let msg = String::from("Hello through the restarted pipe");
+    child_ctx
+        .send(route![sender.addr(), "child"], msg.clone())
+        .await?;
+
+    // Stop the sender, the message is still in its outbox
+    ctx.sleep(Duration::from_millis(200)).await;
+    ctx.stop_worker(sender.addr()).await?;
+
+    let _rx = PipeBuilder::fixed()
+        .receive(rx_addr.clone())
+        .persistent_storage(receiver_storage)
+        .build(ctx)
+        .await?;
+
+    // A new sender with the same storage delivers the pending message
+    let _sender = PipeBuilder::fixed()
+        .connect(rx_addr)
+        .persistent_storage(sender_storage)
+        .build(ctx)
+        .await?;
+
+    let msg2 = child_ctx.receive::<String>().await?;
+    assert_eq!(msg, *msg2);
+
+    // The message is delivered only once even if it is re-sent
+    ctx.sleep(Duration::from_secs(6)).await;
+    assert!(child_ctx
+        .receive_extended::<String>(
+            ockam::MessageReceiveOptions::new().with_timeout(Duration::from_millis(100))
+        )
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
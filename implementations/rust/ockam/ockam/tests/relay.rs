@@ -1,7 +1,7 @@
 use ockam::identity::{secure_channels, SecureChannelListenerOptions, SecureChannelOptions};
 use ockam::remote::{RemoteRelay, RemoteRelayOptions};
 use ockam::workers::Echoer;
-use ockam::{RelayService, RelayServiceOptions};
+use ockam::{RelayRegistry, RelayService, RelayServiceOptions};
 use ockam_core::{route, AllowAll, Result};
 use ockam_node::{Context, MessageReceiveOptions};
 use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
@@ -253,3 +253,173 @@ async fn test4(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+// Node creates a Relay service with a persistent registry and a static Remote Relay
+// registered over a secure channel.
+// The relay can only be registered again by its owner and is restored by a new Relay service
+// using the same storage
+#[ockam_macros::test]
+async fn test_persistent_relay(ctx: &mut Context) -> Result<()> {
+    let path = std::env::temp_dir().join(format!("relays-{}.json", rand::random::<u64>()));
+    let registry = RelayRegistry::create_persistent(&path).await?;
+    let listener_options = SecureChannelListenerOptions::new();
+    let options = RelayServiceOptions::new()
+        .service_as_consumer(&listener_options.spawner_flow_control_id())
+        .relay_as_consumer(&listener_options.spawner_flow_control_id())
+        .with_registry(registry.clone());
+    RelayService::create(ctx, "forwarding_service", options).await?;
+
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+    let relay_identity = identities_creation.create_identity().await?;
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            relay_identity.identifier(),
+            "relay_listener",
+            listener_options,
+        )
+        .await?;
+
+    let server_identity = identities_creation.create_identity().await?;
+    let server_options = SecureChannelOptions::new();
+    ctx.start_worker("echoer", Echoer).await?;
+    ctx.flow_controls()
+        .add_consumer("echoer", &server_options.producer_flow_control_id());
+    let server_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            server_identity.identifier(),
+            route!["relay_listener"],
+            server_options,
+        )
+        .await?;
+
+    let remote_info = RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        server_channel.clone(),
+        "alias",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+    assert_eq!(remote_info.remote_address(), "alias");
+
+    let relays = registry.list();
+    assert_eq!(relays.len(), 1);
+    assert_eq!(relays[0].alias(), "alias");
+    assert_eq!(relays[0].owner(), Some(server_identity.identifier()));
+    let first_heartbeat = relays[0].last_heartbeat();
+
+    // Registering the same alias again by its owner updates the existing relay
+    let remote_info = RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        server_channel.clone(),
+        "alias",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+    assert_eq!(remote_info.remote_address(), "alias");
+    assert_eq!(registry.list().len(), 1);
+    assert!(registry.get("alias").unwrap().last_heartbeat() >= first_heartbeat);
+
+    // The alias can't be registered by anybody else
+    let other_identity = identities_creation.create_identity().await?;
+    let other_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            other_identity.identifier(),
+            route!["relay_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    assert!(RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        other_channel,
+        "alias",
+        RemoteRelayOptions::new(),
+    )
+    .await
+    .is_err());
+    assert_eq!(
+        registry.get("alias").unwrap().owner(),
+        Some(server_identity.identifier())
+    );
+
+    // Simulate a restart of the relay node
+    ctx.stop_worker("alias").await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+
+    let restored_registry = RelayRegistry::create_persistent(&path).await?;
+    let options = RelayServiceOptions::new().with_registry(restored_registry.clone());
+    RelayService::create(ctx, "restored_forwarding_service", options).await?;
+    assert_eq!(restored_registry.list().len(), 1);
+    assert_eq!(
+        restored_registry.get("alias").unwrap().forward_route(),
+        registry.get("alias").unwrap().forward_route()
+    );
+
+    let resp = ctx
+        .send_and_receive::<String>(route!["alias", "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(resp, "Hello");
+
+    let _ = std::fs::remove_file(&path);
+    ctx.stop().await
+}
+
+// A relay registered without a secure channel can't be taken over from another route
+// and is not persisted
+#[ockam_macros::test]
+async fn test_unowned_relay_is_not_taken_over(ctx: &mut Context) -> Result<()> {
+    let path = std::env::temp_dir().join(format!("relays-{}.json", rand::random::<u64>()));
+    let registry = RelayRegistry::create_persistent(&path).await?;
+    let tcp_listener_options = TcpListenerOptions::new();
+    let options = RelayServiceOptions::new()
+        .service_as_consumer(&tcp_listener_options.spawner_flow_control_id())
+        .relay_as_consumer(&tcp_listener_options.spawner_flow_control_id())
+        .with_registry(registry.clone());
+    RelayService::create(ctx, "forwarding_service", options).await?;
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = tcp.listen("127.0.0.1:0", tcp_listener_options).await?;
+    let connection = tcp
+        .connect(listener.socket_string(), TcpConnectionOptions::new())
+        .await?;
+    RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        connection,
+        "alias",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+    let forward_route = registry.get("alias").unwrap().forward_route().clone();
+
+    // Another connection has a different route
+    let other_connection = tcp
+        .connect(listener.socket_string(), TcpConnectionOptions::new())
+        .await?;
+    assert!(RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        other_connection,
+        "alias",
+        RemoteRelayOptions::new(),
+    )
+    .await
+    .is_err());
+    assert_eq!(
+        registry.get("alias").unwrap().forward_route(),
+        &forward_route
+    );
+
+    let restored_registry = RelayRegistry::create_persistent(&path).await?;
+    RelayService::create(
+        ctx,
+        "restored_forwarding_service",
+        RelayServiceOptions::new().with_registry(restored_registry.clone()),
+    )
+    .await?;
+    assert!(restored_registry.list().is_empty());
+
+    let _ = std::fs::remove_file(&path);
+    ctx.stop().await
+}
@@ -0,0 +1,217 @@
+use ockam::identity::storage::{InMemoryStorage, Storage};
+use ockam::protocols::stream::{requests::*, responses::*};
+use ockam::protocols::{ProtocolParser, ProtocolPayload};
+use ockam::stream::Stream;
+use ockam::workers::Echoer;
+use ockam::{
+    FileStreamStorage, InMemoryStreamStorage, StreamIndexService, StreamService,
+    StreamServiceOptions, StreamStorage,
+};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, Result, Route};
+use ockam_node::Context;
+use std::time::Duration;
+
+async fn start_stream_services(
+    ctx: &Context,
+    (stream_address, index_address): (&str, &str),
+    streams: Arc<dyn StreamStorage>,
+    indices: Arc<dyn Storage>,
+) -> Result<()> {
+    StreamService::create(ctx, stream_address, streams, StreamServiceOptions::new()).await?;
+    StreamIndexService::create(ctx, index_address, indices, StreamServiceOptions::new()).await
+}
+
+async fn receive_response(ctx: &mut Context) -> Result<(Response, Route)> {
+    let msg = ctx.receive::<ProtocolPayload>().await?;
+    let return_route = msg.return_route();
+    Ok((Response::parse(msg.body())?, return_route))
+}
+
+// Two stream clients exchange messages through streams hosted on the same node
+#[ockam_macros::test]
+async fn bidirectional_stream(ctx: &mut Context) -> Result<()> {
+    start_stream_services(
+        ctx,
+        ("stream", "stream_index"),
+        InMemoryStreamStorage::create(),
+        InMemoryStorage::create(),
+    )
+    .await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let (sender, _receiver) = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50))
+        .client_id("initiator")
+        .connect(route![], "initiator-to-responder", "responder-to-initiator")
+        .await?;
+
+    Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50))
+        .client_id("responder")
+        .connect(route![], "responder-to-initiator", "initiator-to-responder")
+        .await?;
+
+    ctx.send(route![sender, "echoer"], "Hello World!".to_string())
+        .await?;
+
+    let reply = ctx.receive::<String>().await?;
+    assert_eq!(reply.as_body(), "Hello World!");
+
+    ctx.stop().await
+}
+
+// Stream messages and consumer indices survive a restart of the services
+#[ockam_macros::test]
+async fn persistent_stream(ctx: &mut Context) -> Result<()> {
+    let streams = InMemoryStreamStorage::create();
+    let indices = InMemoryStorage::create();
+    start_stream_services(
+        ctx,
+        ("stream", "stream_index"),
+        streams.clone(),
+        indices.clone(),
+    )
+    .await?;
+
+    ctx.send(route!["stream"], CreateStreamRequest::new("s".to_string()))
+        .await?;
+    let (response, stream_route) = receive_response(ctx).await?;
+    assert!(matches!(response, Response::Init(InitResponse { stream_name }) if stream_name == "s"));
+
+    for (request_id, data) in ["a", "b", "c"].into_iter().enumerate() {
+        ctx.send(
+            stream_route.clone(),
+            PushRequest::new(request_id as u64, data),
+        )
+        .await?;
+        let (response, _) = receive_response(ctx).await?;
+        match response {
+            Response::PushConfirm(confirm) => {
+                assert_eq!(confirm.status, Status::Ok);
+                assert_eq!(confirm.index.u64(), request_id as u64);
+            }
+            _ => panic!("unexpected response"),
+        }
+    }
+
+    ctx.send(route!["stream_index"], IndexRequest::save("s", "client", 2))
+        .await?;
+    // Saving an index has no response, wait for the service to have processed it
+    ctx.send(route!["stream_index"], IndexRequest::get("s", "client"))
+        .await?;
+    receive_response(ctx).await?;
+
+    // Restart the services on other addresses, using the same storage
+    start_stream_services(ctx, ("stream2", "stream_index2"), streams, indices).await?;
+
+    ctx.send(route!["stream_index2"], IndexRequest::get("s", "client"))
+        .await?;
+    let (response, _) = receive_response(ctx).await?;
+    let index = match response {
+        Response::Index(IndexResponse { index, .. }) => index.unwrap().u64(),
+        _ => panic!("unexpected response"),
+    };
+    assert_eq!(index, 2);
+
+    let previous_stream_route = stream_route;
+    ctx.send(route!["stream2"], CreateStreamRequest::new("s".to_string()))
+        .await?;
+    let (_, stream_route) = receive_response(ctx).await?;
+
+    ctx.send(stream_route.clone(), PullRequest::new(0, index, 0))
+        .await?;
+    let (response, _) = receive_response(ctx).await?;
+    match response {
+        Response::PullResponse(PullResponse { messages, .. }) => {
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0].index.u64(), 2);
+            assert_eq!(messages[0].data, b"c");
+        }
+        _ => panic!("unexpected response"),
+    }
+
+    // New messages are appended after the persisted ones
+    ctx.send(stream_route.clone(), PushRequest::new(3, "d"))
+        .await?;
+    let (response, _) = receive_response(ctx).await?;
+    assert!(
+        matches!(response, Response::PushConfirm(PushConfirm { index, .. }) if index.u64() == 3)
+    );
+
+    // The services share the length of the stream, the previous service doesn't
+    // overwrite the message appended by the new one
+    ctx.send(previous_stream_route, PushRequest::new(4, "e"))
+        .await?;
+    let (response, _) = receive_response(ctx).await?;
+    assert!(
+        matches!(response, Response::PushConfirm(PushConfirm { index, .. }) if index.u64() == 4)
+    );
+
+    ctx.send(stream_route, PullRequest::new(1, 0, 2)).await?;
+    let (response, _) = receive_response(ctx).await?;
+    match response {
+        Response::PullResponse(PullResponse { messages, .. }) => {
+            let data: Vec<_> = messages.into_iter().map(|m| m.data).collect();
+            assert_eq!(data, vec![b"a".to_vec(), b"b".to_vec()]);
+        }
+        _ => panic!("unexpected response"),
+    }
+
+    ctx.stop().await
+}
+
+// Stream messages are appended to a file and read back after the storage is opened again
+#[ockam_macros::test]
+async fn file_stream_storage(ctx: &mut Context) -> Result<()> {
+    let directory = std::env::temp_dir().join(format!("streams-{}", rand::random::<u64>()));
+
+    let storage = FileStreamStorage::create(&directory).await?;
+    for data in ["a", "b", "c"] {
+        storage.append("s", data.as_bytes().to_vec()).await?;
+    }
+    assert_eq!(storage.append("other", b"x".to_vec()).await?, 0);
+    drop(storage);
+
+    let storage = FileStreamStorage::create(&directory).await?;
+    assert_eq!(storage.append("s", b"d".to_vec()).await?, 3);
+    let messages = storage.read("s", 1, 2).await?;
+    assert_eq!(messages, vec![(1, b"b".to_vec()), (2, b"c".to_vec())]);
+    assert!(storage.read("s", 10, 2).await?.is_empty());
+    assert!(storage.read("unknown", 0, 2).await?.is_empty());
+
+    let _ = std::fs::remove_dir_all(&directory);
+    ctx.stop().await
+}
+
+// A pull request without a limit returns a bounded number of messages
+#[ockam_macros::test]
+async fn pull_default_limit(ctx: &mut Context) -> Result<()> {
+    let streams = InMemoryStreamStorage::create();
+    for i in 0..ockam::DEFAULT_PULL_LIMIT + 1 {
+        streams.append("s", i.to_be_bytes().to_vec()).await?;
+    }
+    start_stream_services(
+        ctx,
+        ("stream", "stream_index"),
+        streams,
+        InMemoryStorage::create(),
+    )
+    .await?;
+
+    ctx.send(route!["stream"], CreateStreamRequest::new("s".to_string()))
+        .await?;
+    let (_, stream_route) = receive_response(ctx).await?;
+    ctx.send(stream_route, PullRequest::new(0, 0, 0)).await?;
+    let (response, _) = receive_response(ctx).await?;
+    match response {
+        Response::PullResponse(PullResponse { messages, .. }) => {
+            assert_eq!(messages.len() as u64, ockam::DEFAULT_PULL_LIMIT);
+        }
+        _ => panic!("unexpected response"),
+    }
+
+    ctx.stop().await
+}
@@ -5,7 +5,7 @@ use ockam_core::compat::fmt;
 use ockam_core::compat::fmt::Debug;
 use ockam_core::compat::fmt::Formatter;
 use ockam_core::compat::str;
-use ockam_core::compat::vec::vec;
+use ockam_core::compat::vec::{vec, Vec};
 use ockam_core::Result;
 use ockam_core::{IncomingAccessControl, RelayMessage};
 use tracing as log;
@@ -16,7 +16,10 @@ use crate::{eval, Env, Expr};
 use ockam_core::compat::format;
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
-use ockam_identity::{Identifier, IdentitiesRepository, IdentitySecureChannelLocalInfo};
+use ockam_identity::{
+    AttributesEntry, Identifier, IdentitiesRepository, IdentitySecureChannelLocalInfo,
+    RevocationLists,
+};
 
 /// This AccessControl uses a storage for authenticated attributes in order
 /// to verify if a policy expression is valid
@@ -26,6 +29,7 @@ pub struct AbacAccessControl {
     repository: Arc<dyn IdentitiesRepository>,
     expression: Expr,
     environment: Env,
+    revocation_lists: Option<Arc<RevocationLists>>,
 }
 
 /// Debug implementation printing out the policy expression only
@@ -47,9 +51,17 @@ impl AbacAccessControl {
             repository,
             expression,
             environment,
+            revocation_lists: None,
         }
     }
 
+    /// Ignore the attributes attested by an authority for a subject which has since been
+    /// revoked by that authority, or while the revocation list of that authority is outdated
+    pub fn with_revocation_lists(mut self, revocation_lists: Arc<RevocationLists>) -> Self {
+        self.revocation_lists = Some(revocation_lists);
+        self
+    }
+
     /// Create an AccessControl which will verify that the sender of
     /// a message has an authenticated attribute with the correct name and value
     pub fn create(
@@ -72,8 +84,20 @@ impl AbacAccessControl {
     pub async fn is_identity_authorized(&self, id: Identifier) -> Result<bool> {
         let mut environment = self.environment.clone();
 
-        // Get identity attributes and populate the environment:
-        if let Some(attrs) = self.repository.get_attributes(&id).await? {
+        // Get identity attributes and populate the environment.
+        // The attributes attested by each authority are checked separately before being merged
+        let attributes = match self.repository.get_attributes(&id).await? {
+            Some(attrs) => {
+                let entries: Vec<AttributesEntry> = attrs
+                    .attested_entries()
+                    .into_iter()
+                    .filter(|attrs| !self.is_revoked(&id, attrs))
+                    .collect();
+                AttributesEntry::merge(&entries)
+            }
+            None => None,
+        };
+        if let Some(attrs) = attributes {
             for (key, value) in attrs.attrs() {
                 let key = match from_utf8(key) {
                     Ok(key) => key,
@@ -156,6 +180,36 @@ impl AbacAccessControl {
     }
 }
 
+impl AbacAccessControl {
+    /// Return true if the attributes of an identity can't be used because the authority
+    /// which attested them revoked them, or might have revoked them
+    fn is_revoked(&self, id: &Identifier, attrs: &AttributesEntry) -> bool {
+        let (revocation_lists, authority) = match (&self.revocation_lists, attrs.attested_by()) {
+            (Some(revocation_lists), Some(authority)) => (revocation_lists, authority),
+            _ => return false,
+        };
+        if revocation_lists.is_stale(&authority) {
+            log::warn! {
+                policy    = %self.expression,
+                id        = %id,
+                authority = %authority,
+                "the revocation list of the authority is outdated; attributes ignored"
+            }
+            return true;
+        }
+        if revocation_lists.is_revoked(&authority, id, attrs.created_at()) {
+            log::debug! {
+                policy    = %self.expression,
+                id        = %id,
+                authority = %authority,
+                "the attributes were revoked; attributes ignored"
+            }
+            return true;
+        }
+        false
+    }
+}
+
 #[async_trait]
 impl IncomingAccessControl for AbacAccessControl {
     /// Returns true if the sender of the message is validated by the expression stored in AbacAccessControl
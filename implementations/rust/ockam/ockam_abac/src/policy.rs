@@ -9,7 +9,7 @@ use ockam_core::compat::format;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, RelayMessage};
 use ockam_core::{IncomingAccessControl, Result};
-use ockam_identity::IdentitiesRepository;
+use ockam_identity::{Identifier, IdentitiesRepository, RevocationLists};
 use tracing as log;
 
 /// Evaluates a policy expression against an environment of attributes.
@@ -22,6 +22,7 @@ pub struct PolicyAccessControl {
     policies: Arc<dyn PolicyStorage>,
     repository: Arc<dyn IdentitiesRepository>,
     environment: Env,
+    revocation_lists: Option<Arc<RevocationLists>>,
 }
 
 /// Debug implementation writing out the resource, action and initial environment
@@ -55,14 +56,29 @@ impl PolicyAccessControl {
             policies,
             repository,
             environment: env,
+            revocation_lists: None,
         }
     }
+
+    /// Ignore the subject attributes which were revoked by their authority,
+    /// or for which the revocation list of the authority is outdated
+    pub fn with_revocation_lists(mut self, revocation_lists: Arc<RevocationLists>) -> Self {
+        self.revocation_lists = Some(revocation_lists);
+        self
+    }
 }
 
-#[async_trait]
-impl IncomingAccessControl for PolicyAccessControl {
-    async fn is_authorized(&self, msg: &RelayMessage) -> Result<bool> {
-        // Load the policy expression for resource and action:
+impl PolicyAccessControl {
+    /// Returns true if the identity is authorized by the policy of the resource and action
+    pub async fn is_identity_authorized(&self, id: Identifier) -> Result<bool> {
+        match self.decision().await? {
+            Decision::Constant(b) => Ok(b),
+            Decision::Evaluate(access_control) => access_control.is_identity_authorized(id).await,
+        }
+    }
+
+    /// Load the policy expression for resource and action
+    async fn decision(&self) -> Result<Decision> {
         let expr = if let Some(expr) = self
             .policies
             .get_policy(&self.resource, &self.action)
@@ -71,7 +87,7 @@ impl IncomingAccessControl for PolicyAccessControl {
             if let Expr::Bool(b) = expr {
                 // If the policy is a constant there is no need to populate
                 // the environment or look for message metadata.
-                return Ok(b);
+                return Ok(Decision::Constant(b));
             } else {
                 expr
             }
@@ -82,11 +98,32 @@ impl IncomingAccessControl for PolicyAccessControl {
                 action   = %self.action,
                 "no policy found; access denied"
             }
-            return Ok(false);
+            return Ok(Decision::Constant(false));
         };
 
-        AbacAccessControl::new(self.repository.clone(), expr, self.environment.clone())
-            .is_authorized(msg)
-            .await
+        let mut access_control =
+            AbacAccessControl::new(self.repository.clone(), expr, self.environment.clone());
+        if let Some(revocation_lists) = &self.revocation_lists {
+            access_control = access_control.with_revocation_lists(revocation_lists.clone());
+        }
+        Ok(Decision::Evaluate(access_control))
+    }
+}
+
+/// Result of loading the policy of a resource and action
+enum Decision {
+    /// The policy doesn't depend on the subject
+    Constant(bool),
+    /// The policy must be evaluated for the subject
+    Evaluate(AbacAccessControl),
+}
+
+#[async_trait]
+impl IncomingAccessControl for PolicyAccessControl {
+    async fn is_authorized(&self, msg: &RelayMessage) -> Result<bool> {
+        match self.decision().await? {
+            Decision::Constant(b) => Ok(b),
+            Decision::Evaluate(access_control) => access_control.is_authorized(msg).await,
+        }
     }
 }
@@ -1,7 +1,9 @@
 use minicbor::Decoder;
 use ockam::identity::utils::now;
 use ockam::identity::{secure_channel_required, TRUST_CONTEXT_ID};
-use ockam::identity::{AttributesEntry, IdentityAttributesReader, IdentityAttributesWriter};
+use ockam::identity::{
+    AttributesEntry, IdentityAttributesReader, IdentityAttributesWriter, RevocationListIssuer,
+};
 use ockam::identity::{Identifier, IdentitySecureChannelLocalInfo};
 use ockam_core::api::{Method, RequestHeader, Response};
 use ockam_core::compat::sync::Arc;
@@ -16,6 +18,7 @@ pub struct DirectAuthenticator {
     trust_context: String,
     attributes_writer: Arc<dyn IdentityAttributesWriter>,
     attributes_reader: Arc<dyn IdentityAttributesReader>,
+    revocation_list_issuer: Option<RevocationListIssuer>,
 }
 
 impl DirectAuthenticator {
@@ -28,9 +31,16 @@ impl DirectAuthenticator {
             trust_context,
             attributes_writer,
             attributes_reader,
+            revocation_list_issuer: None,
         })
     }
 
+    /// Revoke the credentials of deleted members
+    pub fn with_revocation_list_issuer(mut self, issuer: RevocationListIssuer) -> Self {
+        self.revocation_list_issuer = Some(issuer);
+        self
+    }
+
     async fn add_member<'a>(
         &self,
         enroller: &Identifier,
@@ -52,6 +62,14 @@ impl DirectAuthenticator {
         self.attributes_writer.put_attributes(id, entry).await
     }
 
+    async fn delete_member(&self, id: &Identifier) -> Result<()> {
+        self.attributes_writer.delete(id).await?;
+        if let Some(revocation_list_issuer) = &self.revocation_list_issuer {
+            revocation_list_issuer.revoke(id).await?;
+        }
+        Ok(())
+    }
+
     async fn list_members(&self) -> Result<HashMap<Identifier, AttributesEntry>> {
         let all_attributes = self.attributes_reader.list().await?;
         let attested_by_me = all_attributes.into_iter().collect();
@@ -98,7 +116,7 @@ impl Worker for DirectAuthenticator {
                 }
                 (Some(Method::Delete), [id]) | (Some(Method::Delete), ["members", id]) => {
                     let identifier = Identifier::try_from(id.to_string())?;
-                    self.attributes_writer.delete(&identifier).await?;
+                    self.delete_member(&identifier).await?;
 
                     Response::ok(&req).to_vec()?
                 }
@@ -2,12 +2,12 @@ use std::path::Path;
 
 use tracing::info;
 
-use ockam::identity::storage::LmdbStorage;
+use ockam::identity::storage::{LmdbStorage, Storage};
 use ockam::identity::Vault;
 use ockam::identity::{
     CredentialsIssuer, Identifier, Identities, IdentitiesRepository, IdentitiesStorage,
-    IdentityAttributesReader, IdentityAttributesWriter, SecureChannelListenerOptions,
-    SecureChannels, TrustEveryonePolicy,
+    IdentityAttributesReader, IdentityAttributesWriter, RevocationListIssuer,
+    RevocationListIssuerWorker, SecureChannelListenerOptions, SecureChannels, TrustEveryonePolicy,
 };
 use ockam_abac::expr::{and, eq, ident, str};
 use ockam_abac::{AbacAccessControl, Env};
@@ -30,11 +30,13 @@ use crate::{actions, DefaultAddress};
 /// An Authority is able to start a few services
 //   - a direct authenticator
 //   - a credential issuer
+//   - a revocation list issuer
 //   - an enrollment token issuer
 //   - an enrollment token acceptor
 pub struct Authority {
     identifier: Identifier,
     secure_channels: Arc<SecureChannels>,
+    revocation_list_issuer: RevocationListIssuer,
 }
 
 /// Public functions to:
@@ -57,7 +59,8 @@ impl Authority {
     pub async fn create(configuration: &Configuration) -> Result<Authority> {
         debug!(?configuration, "creating the authority");
         let vault = Self::create_secure_channels_vault(configuration).await?;
-        let repository = Self::create_identities_repository(configuration).await?;
+        let storage = Self::create_storage(configuration).await?;
+        let repository = Self::create_identities_repository(configuration, storage.clone());
         let secure_channels = SecureChannels::builder()
             .with_vault(vault)
             .with_identities_repository(repository)
@@ -66,9 +69,16 @@ impl Authority {
         let identifier = configuration.identifier();
         info!(identifier=%identifier, "retrieved the authority identifier");
 
+        let revocation_list_issuer = RevocationListIssuer::new(
+            secure_channels.identities().credentials(),
+            &identifier,
+            storage,
+        );
+
         Ok(Authority {
             identifier,
             secure_channels,
+            revocation_list_issuer,
         })
     }
 
@@ -123,7 +133,8 @@ impl Authority {
             self.attributes_writer(),
             self.attributes_reader(),
         )
-        .await?;
+        .await?
+        .with_revocation_list_issuer(self.revocation_list_issuer.clone());
 
         let name = configuration.authenticator_name();
         ctx.flow_controls()
@@ -209,6 +220,27 @@ impl Authority {
         Ok(())
     }
 
+    /// Start the revocation list service, serving the signed list of the identities
+    /// whose credentials have been revoked
+    pub async fn start_revocation_list_issuer(
+        &self,
+        ctx: &Context,
+        secure_channel_flow_control_id: &FlowControlId,
+        configuration: &Configuration,
+    ) -> Result<()> {
+        let worker = RevocationListIssuerWorker::new(self.revocation_list_issuer.clone());
+
+        let address = DefaultAddress::REVOCATION_LIST.to_string();
+        ctx.flow_controls()
+            .add_consumer(address.clone(), secure_channel_flow_control_id);
+
+        self.start(ctx, configuration, address.clone(), AnyMember, worker)
+            .await?;
+
+        info!("started a revocation list issuer at '{address}'");
+        Ok(())
+    }
+
     /// Start the Okta service to retrieve attributes authenticated by Okta
     pub async fn start_okta(
         &self,
@@ -270,23 +302,34 @@ impl Authority {
         self.identities_repository().as_attributes_reader().clone()
     }
 
-    /// Create an identity vault backed by a FileStorage
+    /// Create an identity vault backed by a FileStorage, which is encrypted
+    /// if the vault configuration says so
     async fn create_secure_channels_vault(configuration: &Configuration) -> Result<Vault> {
         let vault_path = &configuration.vault_path;
         Self::create_ockam_directory_if_necessary(vault_path)?;
-        let vault = Vault::create_with_persistent_storage_path(vault_path).await?;
+        let vault = if configuration.vault_config.is_encrypted() {
+            let passphrase = configuration.vault_config.passphrase()?;
+            Vault::create_with_encrypted_storage_path(vault_path, &passphrase).await?
+        } else {
+            Vault::create_with_persistent_storage_path(vault_path).await?
+        };
         Ok(vault)
     }
 
-    /// Create an authenticated storage backed by a Lmdb database
-    async fn create_identities_repository(
-        configuration: &Configuration,
-    ) -> Result<Arc<dyn IdentitiesRepository>> {
+    /// Create a storage backed by a Lmdb database
+    async fn create_storage(configuration: &Configuration) -> Result<Arc<dyn Storage>> {
         let storage_path = &configuration.storage_path;
         Self::create_ockam_directory_if_necessary(storage_path)?;
-        let storage = Arc::new(LmdbStorage::new(&storage_path).await?);
+        Ok(Arc::new(LmdbStorage::new(&storage_path).await?))
+    }
+
+    /// Create an authenticated storage backed by a Lmdb database
+    fn create_identities_repository(
+        configuration: &Configuration,
+        storage: Arc<dyn Storage>,
+    ) -> Arc<dyn IdentitiesRepository> {
         let repository = Arc::new(IdentitiesStorage::new(storage));
-        Ok(Self::bootstrap_repository(repository, configuration))
+        Self::bootstrap_repository(repository, configuration)
     }
 
     /// Create a directory to save storage files if they haven't been  created before
@@ -1,4 +1,5 @@
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
+use crate::cli_state::VaultConfig;
 use crate::DefaultAddress;
 
 use ockam::identity::utils::now;
@@ -22,6 +23,10 @@ pub struct Configuration {
     /// path where secrets should be persisted
     pub vault_path: PathBuf,
 
+    /// configuration of the vault, used to decrypt its storage when it is encrypted
+    #[serde(default)]
+    pub vault_config: VaultConfig,
+
     /// Project identifier on the Orchestrator node
     pub project_identifier: String,
 
@@ -2,7 +2,7 @@ use ockam::identity::models::ChangeHistory;
 use ockam::identity::utils::now;
 use ockam::identity::{
     AttributesEntry, Identifier, IdentitiesReader, IdentitiesRepository, IdentitiesWriter,
-    IdentityAttributesReader, IdentityAttributesWriter,
+    IdentityAttributesReader, IdentityAttributesWriter, TimestampInSeconds,
 };
 use ockam_core::async_trait;
 use ockam_core::compat::sync::Arc;
@@ -87,6 +87,17 @@ impl IdentityAttributesWriter for BootstrapedIdentityStore {
     async fn delete(&self, identity: &Identifier) -> Result<()> {
         self.repository.delete(identity).await
     }
+
+    async fn delete_attested_by(
+        &self,
+        identity: &Identifier,
+        authority: &Identifier,
+        created_until: TimestampInSeconds,
+    ) -> Result<()> {
+        self.repository
+            .delete_attested_by(identity, authority, created_until)
+            .await
+    }
 }
 
 #[async_trait]
@@ -330,6 +330,48 @@ impl CliState {
             .build())
     }
 
+    /// Return the vault storing the primary key of an identity.
+    /// The vault of an identity is not recorded in its state, so the default vault is
+    /// checked first and then the other vaults
+    pub async fn identity_vault(&self, identifier: &Identifier) -> Result<VaultState> {
+        let identity = self
+            .get_identities(Vault::create())
+            .await?
+            .get_identity(identifier)
+            .await?;
+        let public_key = identity.get_latest_public_key()?;
+
+        let default_vault = self.vaults.default().ok();
+        let vaults = default_vault.iter().cloned().chain(
+            self.vaults
+                .list()?
+                .into_iter()
+                .filter(|v| Some(v) != default_vault.as_ref()),
+        );
+        for vault_state in vaults {
+            // A vault which can't be opened, for example an encrypted vault without
+            // its passphrase, can't be used to rotate the identity anyway
+            let vault = match vault_state.get().await {
+                Ok(vault) => vault,
+                Err(err) => {
+                    debug!(vault = %vault_state.name(), %err, "skipping vault");
+                    continue;
+                }
+            };
+            if vault
+                .identity_vault
+                .get_secret_key_handle(&public_key)
+                .await
+                .is_ok()
+            {
+                return Ok(vault_state);
+            }
+        }
+        Err(CliStateError::InvalidOperation(format!(
+            "None of the vaults stores the primary key of the identity {identifier}"
+        )))
+    }
+
     pub async fn default_identities(&self) -> Result<Arc<Identities>> {
         Ok(Identities::builder()
             .with_vault(self.vaults.default()?.vault().await?)
@@ -10,12 +10,13 @@ use miette::{IntoDiagnostic, WrapErr};
 use nix::errno::Errno;
 use ockam::identity::Identifier;
 use ockam::identity::Vault;
-use ockam::LmdbStorage;
+use ockam::{LmdbStorage, RelayRegistry};
 use ockam_core::compat::collections::HashSet;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Mutex;
 use sysinfo::{Pid, ProcessExt, ProcessStatus, System, SystemExt};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -115,6 +116,55 @@ impl NodeState {
         Ok(())
     }
 
+    /// Return the resources which were created on this node and which must
+    /// be created again when the node is restarted
+    pub fn resources(&self) -> Result<Vec<NodeResource>> {
+        let path = self.paths.resources();
+        if path.exists() {
+            let contents = std::fs::read_to_string(path)?;
+            Ok(serde_json::from_str(&contents)?)
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// Persist a resource, replacing any resource with the same path and name
+    pub fn add_resource(&self, resource: NodeResource) -> Result<()> {
+        let _guard = lock_resources();
+        let mut resources = self.resources()?;
+        resources.retain(|r| !r.is(&resource.path, &resource.name));
+        resources.push(resource);
+        self.set_resources(&resources)
+    }
+
+    /// Remove a persisted resource. Nothing happens if the resource was not persisted
+    pub fn remove_resource(&self, path: &str, name: &str) -> Result<()> {
+        let _guard = lock_resources();
+        let mut resources = self.resources()?;
+        let len = resources.len();
+        resources.retain(|r| !r.is(path, name));
+        if resources.len() != len {
+            self.set_resources(&resources)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_resources(&self, resources: &[NodeResource]) -> Result<()> {
+        let contents = serde_json::to_string(resources)?;
+        std::fs::write(self.paths.resources(), contents)?;
+        Ok(())
+    }
+
+    /// Remove all the persisted resources
+    pub fn clear_resources(&self) -> Result<()> {
+        let path = self.paths.resources();
+        if path.exists() {
+            std::fs::remove_file(path)?;
+            info!(name = %self.name(), "node resources cleared");
+        }
+        Ok(())
+    }
+
     pub fn pid(&self) -> Result<Option<i32>> {
         let path = self.paths.pid();
         if path.exists() {
@@ -161,6 +211,17 @@ impl NodeState {
         Ok(LmdbStorage::new(self.paths.policies_storage()).await?)
     }
 
+    /// Storage for the consumers registered with the kafka producers of this node
+    pub async fn kafka_storage(&self) -> Result<LmdbStorage> {
+        Ok(LmdbStorage::new(self.paths.kafka_storage()).await?)
+    }
+
+    /// Registry of the relays hosted by the relay service of this node, persisted
+    /// so that they are restored when the node is restarted
+    pub async fn relay_registry(&self) -> Result<RelayRegistry> {
+        Ok(RelayRegistry::create_persistent(&self.paths.relays()).await?)
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -309,6 +370,9 @@ pub struct NodeSetupConfig {
     pub authority_node: Option<bool>,
     pub project: Option<ProjectLookup>,
     pub api_transport: Option<CreateTransportJson>,
+    /// The arguments used to start the node, reused when the node is restarted.
+    /// The field might be missing in previous configuration files, hence it is an Option
+    pub launch_arguments: Option<NodeLaunchArguments>,
 }
 
 impl NodeSetupConfig {
@@ -332,6 +396,11 @@ impl NodeSetupConfig {
         self
     }
 
+    pub fn set_launch_arguments(mut self, launch_arguments: NodeLaunchArguments) -> Self {
+        self.launch_arguments = Some(launch_arguments);
+        self
+    }
+
     pub fn api_transport(&self) -> Result<&CreateTransportJson> {
         self.api_transport.as_ref().ok_or_else(|| {
             CliStateError::InvalidOperation(
@@ -341,6 +410,66 @@ impl NodeSetupConfig {
     }
 }
 
+/// The arguments a node was started with, other than its name and api transport
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct NodeLaunchArguments {
+    pub metrics_address: Option<String>,
+    #[serde(default)]
+    pub transports: Vec<String>,
+    pub project_path: Option<PathBuf>,
+    pub trusted_identities: Option<String>,
+    pub trusted_identities_file: Option<PathBuf>,
+    pub reload_from_trusted_identities_file: Option<PathBuf>,
+    pub launch_config: Option<String>,
+    pub authority_identity: Option<String>,
+    pub credential: Option<String>,
+    pub trust_context: Option<String>,
+    pub project: Option<String>,
+}
+
+/// Lock held while the persisted resources of a node are updated. The resources
+/// are restored in the background when a node starts, while the node manager
+/// can already persist new resources
+static RESOURCES_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_resources() -> std::sync::MutexGuard<'static, ()> {
+    RESOURCES_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A resource created with a request to the node manager, such as a portal or a relay.
+/// The request is sent again to the node manager when the node is restarted.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct NodeResource {
+    /// Path of the request which created the resource
+    pub path: String,
+    /// Name of the resource, unique for a given path
+    pub name: String,
+    /// Hex-encoded CBOR body of the request
+    pub body: String,
+}
+
+impl NodeResource {
+    pub fn new(path: impl Into<String>, name: impl Into<String>, body: &[u8]) -> Self {
+        Self {
+            path: path.into(),
+            name: name.into(),
+            body: hex::encode(body),
+        }
+    }
+
+    pub fn body(&self) -> Result<Vec<u8>> {
+        hex::decode(&self.body).map_err(|e| {
+            CliStateError::InvalidData(format!("Invalid body for resource {}: {e}", self.name))
+        })
+    }
+
+    fn is(&self, path: &str, name: &str) -> bool {
+        self.path == path && self.name == name
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct NodePaths {
     path: PathBuf,
@@ -369,6 +498,10 @@ impl NodePaths {
         self.path.join("pid")
     }
 
+    fn resources(&self) -> PathBuf {
+        self.path.join("resources.json")
+    }
+
     fn version(&self) -> PathBuf {
         self.path.join("version")
     }
@@ -384,6 +517,14 @@ impl NodePaths {
     fn policies_storage(&self) -> PathBuf {
         self.path.join("policies_storage.lmdb")
     }
+
+    fn kafka_storage(&self) -> PathBuf {
+        self.path.join("kafka_storage.lmdb")
+    }
+
+    fn relays(&self) -> PathBuf {
+        self.path.join("relays.json")
+    }
 }
 
 mod backwards_compatibility {
@@ -492,6 +633,7 @@ mod traits {
                         authority_node: setup.authority_node,
                         project: setup.project,
                         api_transport: None,
+                        launch_arguments: None,
                     };
                     if let Some(t) = setup
                         .transports
@@ -600,7 +742,11 @@ pub async fn init_node_state(
         .vault(vault_state.path().clone())
         .identity(identity_state.path().clone())
         .build(cli_state)?;
-    cli_state.nodes.overwrite(node_name, node_config)?;
+    // A new node doesn't have the resources of a previous node with the same name
+    cli_state
+        .nodes
+        .overwrite(node_name, node_config)?
+        .clear_resources()?;
 
     info!(name=%node_name, "node state initialized");
     Ok(())
@@ -703,6 +849,40 @@ mod tests {
         assert_eq!(config.transports.len(), 1);
     }
 
+    #[test]
+    fn node_resources_are_replaced_and_removed() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let node_dir = tmp_dir.path().join("n");
+        std::fs::create_dir(&node_dir).unwrap();
+        let paths = NodePaths::new(&node_dir);
+        std::fs::write(
+            paths.setup(),
+            serde_json::to_string(&NodeSetupConfig::default()).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(paths.version(), ConfigVersion::latest().to_string()).unwrap();
+        let node_state = NodeState::load(node_dir).unwrap();
+        assert!(node_state.resources().unwrap().is_empty());
+
+        let inlet = NodeResource::new("/node/inlet", "i", &[1]);
+        let outlet = NodeResource::new("/node/outlet", "i", &[2]);
+        node_state.add_resource(inlet).unwrap();
+        node_state.add_resource(outlet.clone()).unwrap();
+        let updated_inlet = NodeResource::new("/node/inlet", "i", &[3]);
+        node_state.add_resource(updated_inlet.clone()).unwrap();
+        assert_eq!(
+            node_state.resources().unwrap(),
+            vec![outlet, updated_inlet.clone()]
+        );
+        assert_eq!(updated_inlet.body().unwrap(), vec![3]);
+
+        node_state.remove_resource("/node/outlet", "i").unwrap();
+        assert_eq!(node_state.resources().unwrap(), vec![updated_inlet]);
+
+        node_state.clear_resources().unwrap();
+        assert!(node_state.resources().unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn migrate_node_config_from_v1_to_v2() {
         // Create a v1 setup.json file
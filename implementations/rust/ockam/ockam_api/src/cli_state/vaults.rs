@@ -5,13 +5,23 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use ockam::identity::Vault;
+use ockam::vault::storage::PersistentStorage;
+use ockam_core::env::get_env;
 use ockam_vault_aws::AwsSigningVault;
+#[cfg(feature = "pkcs11")]
+use ockam_vault_pkcs11::{Pkcs11Config, Pkcs11SigningVault};
 
 use crate::cli_state::traits::StateItemTrait;
 use crate::cli_state::{CliStateError, StateDirTrait, DATA_DIR_NAME};
 
 use super::Result;
 
+/// Environment variable containing the passphrase of encrypted vaults
+pub const OCKAM_VAULT_PASSPHRASE: &str = "OCKAM_VAULT_PASSPHRASE";
+
+/// Environment variable containing the user PIN of a PKCS#11 token
+pub const OCKAM_PKCS11_PIN: &str = "OCKAM_PKCS11_PIN";
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct VaultsState {
     dir: PathBuf,
@@ -52,14 +62,46 @@ impl VaultState {
             vault.credential_vault = aws_vault;
 
             Ok(vault)
+        } else if let Some(pkcs11) = &self.config.pkcs11 {
+            pkcs11.vault().await
         } else {
-            let vault =
-                Vault::create_with_persistent_storage_path(self.vault_file_path().as_path())
-                    .await?;
-            Ok(vault)
+            self.vault().await
         }
     }
 
+    /// Encrypt the storage of an existing unencrypted vault.
+    /// The passphrase is read from the key file if provided, or from the
+    /// [`OCKAM_VAULT_PASSPHRASE`] environment variable
+    pub async fn encrypt(&self, key_file: Option<PathBuf>) -> Result<VaultState> {
+        if self.config.aws_kms {
+            return Err(CliStateError::InvalidOperation(format!(
+                "The vault {} is stored in AWS KMS and can't be encrypted",
+                self.name
+            )));
+        }
+        if self.config.is_pkcs11() {
+            return Err(CliStateError::InvalidOperation(format!(
+                "The vault {} is stored in a PKCS#11 token and can't be encrypted",
+                self.name
+            )));
+        }
+        if self.config.encrypted {
+            return Err(CliStateError::InvalidOperation(format!(
+                "The vault {} is already encrypted",
+                self.name
+            )));
+        }
+        let config = self.config.clone().with_encryption(key_file);
+        let passphrase = config.passphrase()?;
+        PersistentStorage::encrypt(self.vault_file_path(), &passphrase).await?;
+        let state = Self {
+            config,
+            ..self.clone()
+        };
+        state.persist()?;
+        Ok(state)
+    }
+
     fn build_data_path(name: &str, path: &Path) -> PathBuf {
         path.parent()
             .expect("Should have parent")
@@ -73,7 +115,12 @@ impl VaultState {
 
     pub async fn vault(&self) -> Result<Vault> {
         let path = self.vault_file_path().clone();
-        let vault = Vault::create_with_persistent_storage_path(path.as_path()).await?;
+        let vault = if self.config.encrypted {
+            let passphrase = self.config.passphrase()?;
+            Vault::create_with_encrypted_storage_path(path.as_path(), &passphrase).await?
+        } else {
+            Vault::create_with_persistent_storage_path(path.as_path()).await?
+        };
         Ok(vault)
     }
 
@@ -85,14 +132,21 @@ impl VaultState {
 impl Display for VaultState {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Name: {}", self.name)?;
-        writeln!(
-            f,
-            "Type: {}",
-            match self.config.is_aws() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-        )?;
+        let vault_type = if self.config.is_aws() {
+            "AWS KMS"
+        } else if self.config.is_pkcs11() {
+            "PKCS#11"
+        } else {
+            "OCKAM"
+        };
+        writeln!(f, "Type: {vault_type}")?;
+        if let Some(pkcs11) = &self.config.pkcs11 {
+            writeln!(f, "PKCS#11 module: {}", pkcs11.module.display())?;
+            writeln!(f, "PKCS#11 slot: {}", pkcs11.slot)?;
+        }
+        if self.config.is_encrypted() {
+            writeln!(f, "Encrypted: true")?;
+        }
         Ok(())
     }
 }
@@ -101,16 +155,100 @@ impl Display for VaultState {
 pub struct VaultConfig {
     #[serde(default)]
     aws_kms: bool,
+    /// The storage file is encrypted with a key derived from a passphrase
+    #[serde(default)]
+    encrypted: bool,
+    /// File containing the passphrase of an encrypted vault. If not set the passphrase
+    /// is read from the [`OCKAM_VAULT_PASSPHRASE`] environment variable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_file: Option<PathBuf>,
+    /// Signing keys are stored on a PKCS#11 token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pkcs11: Option<Pkcs11VaultConfig>,
+}
+
+/// Location of a PKCS#11 token. The user PIN is not stored and is read from the
+/// [`OCKAM_PKCS11_PIN`] environment variable when the vault is loaded
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Pkcs11VaultConfig {
+    module: PathBuf,
+    slot: u64,
+}
+
+impl Pkcs11VaultConfig {
+    #[cfg(feature = "pkcs11")]
+    async fn vault(&self) -> Result<Vault> {
+        let mut vault = Vault::create();
+        let pkcs11_vault = Arc::new(Pkcs11SigningVault::create(self.to_config()?).await?);
+        vault.identity_vault = pkcs11_vault.clone();
+        vault.credential_vault = pkcs11_vault;
+
+        Ok(vault)
+    }
+
+    #[cfg(not(feature = "pkcs11"))]
+    async fn vault(&self) -> Result<Vault> {
+        Err(CliStateError::InvalidOperation(
+            "PKCS#11 vaults are not supported by this build, enable the pkcs11 feature".to_string(),
+        ))
+    }
+
+    #[cfg(feature = "pkcs11")]
+    fn to_config(&self) -> Result<Pkcs11Config> {
+        let config = Pkcs11Config::new(self.module.clone(), self.slot);
+        Ok(match get_env::<String>(OCKAM_PKCS11_PIN)? {
+            Some(pin) if !pin.is_empty() => config.with_pin(pin),
+            _ => config,
+        })
+    }
 }
 
 impl VaultConfig {
     pub fn new(aws_kms: bool) -> Result<Self> {
-        Ok(Self { aws_kms })
+        Ok(Self {
+            aws_kms,
+            ..Default::default()
+        })
+    }
+
+    /// Encrypt the vault storage with a passphrase read from a key file,
+    /// or from the [`OCKAM_VAULT_PASSPHRASE`] environment variable
+    pub fn with_encryption(mut self, key_file: Option<PathBuf>) -> Self {
+        self.encrypted = true;
+        self.key_file = key_file;
+        self
+    }
+
+    /// Store the signing keys on a PKCS#11 token, accessed with the given module and slot
+    pub fn with_pkcs11(mut self, module: PathBuf, slot: u64) -> Self {
+        self.pkcs11 = Some(Pkcs11VaultConfig { module, slot });
+        self
     }
 
     pub fn is_aws(&self) -> bool {
         self.aws_kms
     }
+
+    pub fn is_pkcs11(&self) -> bool {
+        self.pkcs11.is_some()
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    /// Return the passphrase used to encrypt the vault storage
+    pub fn passphrase(&self) -> Result<Vec<u8>> {
+        if let Some(key_file) = &self.key_file {
+            return Ok(std::fs::read(key_file)?);
+        }
+        match get_env::<String>(OCKAM_VAULT_PASSPHRASE)? {
+            Some(passphrase) if !passphrase.is_empty() => Ok(passphrase.into_bytes()),
+            _ => Err(CliStateError::InvalidOperation(format!(
+                "The vault is encrypted, please set its passphrase with the {OCKAM_VAULT_PASSPHRASE} environment variable"
+            ))),
+        }
+    }
 }
 
 mod traits {
@@ -197,6 +335,10 @@ mod traits {
 
         fn delete(&self) -> Result<()> {
             std::fs::remove_file(&self.path)?;
+            // Vaults backed by AWS KMS or a PKCS#11 token don't have a storage file
+            if self.config.is_aws() || self.config.is_pkcs11() {
+                return Ok(());
+            }
             std::fs::remove_file(&self.data_path)?;
             std::fs::remove_file(self.data_path.with_extension("json.lock"))?;
             Ok(())
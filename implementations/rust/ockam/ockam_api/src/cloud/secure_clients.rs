@@ -10,8 +10,8 @@ use ockam_node::Context;
 use ockam_transport_tcp::TcpTransport;
 
 use crate::error::ApiError;
-use crate::multiaddr_to_route;
 use crate::nodes::NodeManager;
+use crate::{multiaddr_to_route, TransportFlowControls};
 
 pub const OCKAM_CONTROLLER_ADDR: &str = "OCKAM_CONTROLLER_ADDR";
 pub const DEFAULT_CONTROLLER_ADDRESS: &str = "/dnsaddr/orchestrator.ockam.io/tcp/6252/service/api";
@@ -183,14 +183,16 @@ impl NodeManager {
         tcp_transport: &TcpTransport,
         multiaddr: &MultiAddr,
     ) -> Result<Route> {
-        let secure_route = multiaddr_to_route(multiaddr, tcp_transport)
-            .await
-            .ok_or_else(|| {
-                ApiError::core(format!(
-                    "Couldn't convert MultiAddr to route: multiaddr={multiaddr}"
-                ))
-            })?
-            .route;
+        // the controller, the authorities and the projects are only reachable over TCP
+        let secure_route =
+            multiaddr_to_route(multiaddr, tcp_transport, &TransportFlowControls::default())
+                .await
+                .ok_or_else(|| {
+                    ApiError::core(format!(
+                        "Couldn't convert MultiAddr to route: multiaddr={multiaddr}"
+                    ))
+                })?
+                .route;
         debug!("using the secure route {secure_route}");
         Ok(secure_route)
     }
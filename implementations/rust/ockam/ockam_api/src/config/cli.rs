@@ -8,10 +8,10 @@ use crate::{cli_state, multiaddr_to_transport_route, DefaultAddress, HexByteVec}
 use ockam::identity::{
     identities, AuthorityService, CredentialsMemoryRetriever, CredentialsRetriever, Identifier,
     Identities, Identity, RemoteCredentialsRetriever, RemoteCredentialsRetrieverInfo,
-    SecureChannels, TrustContext,
+    RemoteRevocationListRetriever, RevocationListRetriever, SecureChannels, TrustContext,
 };
 use ockam_core::compat::sync::Arc;
-use ockam_core::{Result, Route};
+use ockam_core::{AsyncTryClone, Result, Route};
 use ockam_multiaddr::MultiAddr;
 use ockam_transport_tcp::TcpTransport;
 use serde::{Deserialize, Serialize};
@@ -79,6 +79,10 @@ impl LegacyCliConfig {
 pub struct TrustContextConfig {
     id: String,
     authority: Option<TrustAuthorityConfig>,
+    /// Authorities trusted in addition to the main authority.
+    /// Their attributes are namespaced in order to not be confused with the main authority attributes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    additional_authorities: Vec<TrustAuthorityConfig>,
     path: Option<PathBuf>,
 }
 
@@ -87,10 +91,19 @@ impl TrustContextConfig {
         Self {
             id,
             authority,
+            additional_authorities: vec![],
             path: None,
         }
     }
 
+    pub fn with_additional_authorities(
+        mut self,
+        additional_authorities: Vec<TrustAuthorityConfig>,
+    ) -> Self {
+        self.additional_authorities = additional_authorities;
+        self
+    }
+
     pub fn id(&self) -> &str {
         &self.id
     }
@@ -109,34 +122,36 @@ impl TrustContextConfig {
             .ok_or_else(|| ApiError::core("Missing authority on trust context config"))
     }
 
+    pub fn additional_authorities(&self) -> &[TrustAuthorityConfig] {
+        &self.additional_authorities
+    }
+
     pub async fn to_trust_context(
         &self,
         secure_channels: Arc<SecureChannels>,
         tcp_transport: Option<TcpTransport>,
     ) -> Result<TrustContext> {
-        let authority = if let Some(authority_config) = self.authority.as_ref() {
-            let identity = authority_config.identity().await?;
-            let credential_retriever =
-                if let Some(retriever_type) = &authority_config.own_credential {
-                    Some(
-                        retriever_type
-                            .to_credential_retriever(secure_channels.clone(), tcp_transport)
-                            .await?,
-                    )
-                } else {
-                    None
-                };
-
-            Some(AuthorityService::new(
-                secure_channels.identities().credentials(),
-                identity.identifier().clone(),
-                credential_retriever,
-            ))
-        } else {
-            None
-        };
+        let mut authorities = vec![];
+        for authority_config in self
+            .authority
+            .iter()
+            .chain(self.additional_authorities.iter())
+        {
+            let tcp_transport = match &tcp_transport {
+                Some(tcp_transport) => Some(tcp_transport.async_try_clone().await?),
+                None => None,
+            };
+            authorities.push(
+                authority_config
+                    .to_authority_service(secure_channels.clone(), tcp_transport)
+                    .await?,
+            );
+        }
 
-        Ok(TrustContext::new(self.id.to_string(), authority))
+        Ok(TrustContext::new_with_authorities(
+            self.id.to_string(),
+            authorities,
+        ))
     }
 
     pub fn from_authority_identity(
@@ -222,6 +237,8 @@ impl TryFrom<ProjectLookup> for TrustContextConfig {
 pub struct TrustAuthorityConfig {
     identity: String,
     own_credential: Option<CredentialRetrieverConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
 }
 
 impl TrustAuthorityConfig {
@@ -229,9 +246,19 @@ impl TrustAuthorityConfig {
         Self {
             identity,
             own_credential,
+            namespace: None,
         }
     }
 
+    pub fn with_namespace(mut self, namespace: String) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
     pub fn identity_str(&self) -> &str {
         &self.identity
     }
@@ -252,6 +279,43 @@ impl TrustAuthorityConfig {
             .as_ref()
             .ok_or_else(|| ApiError::core("Missing own credential on trust authority config"))
     }
+
+    async fn to_authority_service(
+        &self,
+        secure_channels: Arc<SecureChannels>,
+        tcp_transport: Option<TcpTransport>,
+    ) -> Result<AuthorityService> {
+        let identity = self.identity().await?;
+        let credential_retriever = if let Some(retriever_type) = &self.own_credential {
+            Some(
+                retriever_type
+                    .to_credential_retriever(secure_channels.clone(), tcp_transport)
+                    .await?,
+            )
+        } else {
+            None
+        };
+        let revocation_list_retriever = if let Some(retriever_type) = &self.own_credential {
+            retriever_type
+                .to_revocation_list_retriever(secure_channels.clone())
+                .await?
+        } else {
+            None
+        };
+
+        let mut authority = AuthorityService::new(
+            secure_channels.identities().credentials(),
+            identity.identifier().clone(),
+            credential_retriever,
+        );
+        if let Some(retriever) = revocation_list_retriever {
+            authority = authority.with_revocation_list_retriever(retriever);
+        }
+        if let Some(namespace) = &self.namespace {
+            authority = authority.with_namespace(namespace.clone());
+        }
+        Ok(authority)
+    }
 }
 
 /// Type of credential retriever
@@ -293,6 +357,28 @@ impl CredentialRetrieverConfig {
             }
         }
     }
+
+    /// Return a retriever for the authority revocation list when the authority can be reached
+    async fn to_revocation_list_retriever(
+        &self,
+        secure_channels: Arc<SecureChannels>,
+    ) -> Result<Option<Arc<dyn RevocationListRetriever>>> {
+        match self {
+            CredentialRetrieverConfig::FromCredentialIssuer(issuer_config) => {
+                let revocation_list_info = RemoteCredentialsRetrieverInfo::new(
+                    issuer_config.resolve_identity().await?.identifier().clone(),
+                    issuer_config.resolve_route().await?,
+                    DefaultAddress::REVOCATION_LIST.into(),
+                );
+
+                Ok(Some(Arc::new(RemoteRevocationListRetriever::new(
+                    secure_channels,
+                    revocation_list_info,
+                ))))
+            }
+            _ => Ok(None),
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -0,0 +1,979 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use minicbor::{Decode, Decoder, Encode};
+use ockam::identity::models::{CredentialSignature, PurposeKeyAttestation, PurposePublicKey};
+use ockam::identity::storage::Storage;
+use ockam::identity::{
+    secure_channel_required, Identifier, IdentitiesRepository, IdentitySecureChannelLocalInfo,
+    RevocationLists, SecureChannels,
+};
+use ockam_abac::expr::{eq, ident, str};
+use ockam_abac::{Env, PolicyAccessControl, PolicyStorage, Resource};
+use ockam_core::api::{Method, RequestHeader, Response};
+use ockam_core::compat::collections::HashMap;
+use ockam_core::compat::sync::Arc;
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::{Address, Result, Routed, Worker};
+use ockam_node::compat::tokio::sync::Mutex;
+use ockam_node::Context;
+use ockam_vault::{
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, SecretBufferHandle, VaultForSecureChannels,
+    X25519PublicKey, X25519SecretKeyHandle,
+};
+use rand::{thread_rng, RngCore};
+
+use crate::actions;
+use crate::error::ApiError;
+
+const DATA_KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+
+/// Data keys of a topic are replaced by a new one after this interval
+pub(crate) const DEFAULT_DATA_KEY_ROTATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Number of data keys kept by a producer for each topic, so that they can be wrapped
+/// for the consumers registering after the records were produced
+const MAX_RETAINED_DATA_KEYS: usize = 16;
+
+/// Number of data keys kept by a consumer once they have been unwrapped.
+/// They can always be unwrapped again from the records
+const MAX_CACHED_DATA_KEYS: usize = 1024;
+
+/// Prefix of the resource of a topic policy, on the producer node
+pub(crate) const KAFKA_TOPIC_RESOURCE_PREFIX: &str = "kafka-topic:";
+
+/// Salt of the derivation of the keys wrapping the data keys
+const WRAPPING_SALT: &[u8; 32] = b"ockam_kafka_data_key_wrapping_v1";
+
+/// A fresh wrapping key is derived for every wrapped data key, so the nonce can be constant
+const WRAPPING_NONCE: [u8; NONCE_LENGTH] = [0u8; NONCE_LENGTH];
+
+/// Prefix of the data signed by a producer for a wrapped data key
+const DATA_KEY_SIGNATURE_TAG: &[u8] = b"ockam_kafka_data_key";
+
+/// Alias of the relay of a producer, also the address of its data key service.
+/// It only depends on the producer identity so that it doesn't change when the producer restarts
+fn producer_alias(producer: &Identifier) -> String {
+    format!("data_keys_{producer}")
+}
+
+/// Namespace of the consumers registered for a topic, in the producer node storage
+fn consumers_namespace(topic_name: &str) -> String {
+    format!("kafka_consumers.{topic_name}")
+}
+
+/// A data key encrypted for one consumer
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct WrappedKey {
+    /// Secure channel purpose key of the consumer
+    #[n(1)] pub(crate) consumer_key: X25519PublicKey,
+    /// Data key encrypted with a key derived from the ephemeral key and the consumer key
+    #[n(2)] pub(crate) wrapped_key: Vec<u8>,
+}
+
+/// Data key used to encrypt a record, sent alongside the record.
+///
+/// The data key is wrapped for every consumer registered when it was created, so that
+/// those consumers can decrypt the record without contacting the producer, even after
+/// the producer restarted. Other consumers can register with the producer, through its
+/// relay, to get the data keys it still retains.
+///
+/// The wrapped data key is signed by the producer identity, so that a consumer only
+/// accepts the data keys of the producers authorized by the topic policy.
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct WrappedDataKey {
+    /// Alias of the producer relay, also the address of its data key service
+    #[n(1)] pub(crate) producer: String,
+    /// Identifier of the data key on the producer node
+    #[n(2)] pub(crate) key_id: String,
+    /// Topic encrypted with this data key
+    #[n(3)] pub(crate) topic_name: String,
+    /// Public key of the ephemeral key used to wrap the data key
+    #[n(4)] pub(crate) ephemeral_key: X25519PublicKey,
+    /// The data key, wrapped for each consumer
+    #[n(5)] pub(crate) wrapped_keys: Vec<WrappedKey>,
+    /// Attestation of the producer credential purpose key, used to sign the data key
+    #[n(6)] pub(crate) attestation: PurposeKeyAttestation,
+    /// Signature of the other fields with the producer credential purpose key
+    #[n(7)] pub(crate) signature: CredentialSignature,
+}
+
+impl WrappedDataKey {
+    /// Return the data signed by the producer: a tag, distinguishing it from the other
+    /// data signed with the same purpose key, followed by the encoded fields of the data key
+    fn signed_data(
+        producer: &str,
+        key_id: &str,
+        topic_name: &str,
+        ephemeral_key: &X25519PublicKey,
+        wrapped_keys: &[WrappedKey],
+    ) -> Result<Vec<u8>> {
+        let mut data = DATA_KEY_SIGNATURE_TAG.to_vec();
+        data.extend(minicbor::to_vec((
+            producer,
+            key_id,
+            topic_name,
+            ephemeral_key,
+            wrapped_keys,
+        ))?);
+        Ok(data)
+    }
+}
+
+/// Request sent by a consumer to the [`KafkaDataKeyService`] to receive the data keys of a topic
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct ConsumerRegistration {
+    /// Attestation of the consumer secure channel purpose key, used to wrap the data keys
+    #[n(1)] pub(crate) attestation: PurposeKeyAttestation,
+}
+
+/// Response of the [`KafkaDataKeyService`]: the data keys retained for a topic,
+/// wrapped for the registered consumer
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct RegisteredDataKeys {
+    #[n(1)] pub(crate) data_keys: Vec<WrappedDataKey>,
+}
+
+/// Policies deciding which consumers receive the data keys of a topic, and which
+/// producers are trusted for the data keys of a topic.
+///
+/// The policy of a topic is set on the producer and consumer nodes for the resource
+/// `kafka-topic:<topic name>` and the action `handle_message`. When it is missing, the
+/// members of the trust context are authorized.
+#[derive(Clone)]
+pub(crate) struct TopicPolicies {
+    policies: Arc<dyn PolicyStorage>,
+    repository: Arc<dyn IdentitiesRepository>,
+    revocation_lists: Arc<RevocationLists>,
+    trust_context_id: String,
+}
+
+impl TopicPolicies {
+    pub(crate) fn new(
+        policies: Arc<dyn PolicyStorage>,
+        repository: Arc<dyn IdentitiesRepository>,
+        revocation_lists: Arc<RevocationLists>,
+        trust_context_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            policies,
+            repository,
+            revocation_lists,
+            trust_context_id: trust_context_id.into(),
+        }
+    }
+
+    /// Return true if a consumer can receive the data keys of a topic,
+    /// or if the data keys of a producer can be used for a topic
+    pub(crate) async fn is_authorized(
+        &self,
+        topic_name: &str,
+        identifier: &Identifier,
+    ) -> Result<bool> {
+        let resource = Resource::new(&format!("{KAFKA_TOPIC_RESOURCE_PREFIX}{topic_name}"));
+        let action = actions::HANDLE_MESSAGE;
+
+        if self
+            .policies
+            .get_policy(&resource, &action)
+            .await?
+            .is_none()
+        {
+            let fallback = eq([
+                ident("resource.trust_context_id"),
+                ident("subject.trust_context_id"),
+            ]);
+            self.policies
+                .set_policy(&resource, &action, &fallback)
+                .await?
+        }
+
+        let mut env = Env::new();
+        env.put("resource.id", str(resource.as_str()));
+        env.put("action.id", str(action.as_str()));
+        env.put(
+            "resource.trust_context_id",
+            str(self.trust_context_id.as_str()),
+        );
+
+        PolicyAccessControl::new(
+            self.policies.clone(),
+            self.repository.clone(),
+            resource,
+            action,
+            env,
+        )
+        .with_revocation_lists(self.revocation_lists.clone())
+        .is_identity_authorized(identifier.clone())
+        .await
+    }
+}
+
+/// A data key created by a producer
+struct TopicKey {
+    key: Vec<u8>,
+    created_at: Instant,
+    wrapped: WrappedDataKey,
+}
+
+/// Data keys retained by a producer for a topic, the most recent last
+#[derive(Default)]
+struct TopicKeys {
+    keys: VecDeque<TopicKey>,
+    /// Set when a consumer registered since the last data key was created
+    stale: bool,
+}
+
+impl TopicKeys {
+    fn current(&self, rotation_interval: Duration) -> Option<&TopicKey> {
+        if self.stale {
+            return None;
+        }
+        self.keys
+            .back()
+            .filter(|k| k.created_at.elapsed() < rotation_interval)
+    }
+
+    fn push(&mut self, key: TopicKey) {
+        if self.keys.len() == MAX_RETAINED_DATA_KEYS {
+            self.keys.pop_front();
+        }
+        self.keys.push_back(key);
+        self.stale = false;
+    }
+}
+
+/// Data keys used to encrypt the kafka records of a topic for all its authorized consumers.
+///
+/// A producer creates a data key per topic, wraps it for the consumers registered for that
+/// topic and sends it alongside every record. The data key is replaced by a new one after
+/// the rotation interval, or when a new consumer registers.
+///
+/// A consumer verifies that a data key was signed by a producer authorized for the topic, then
+/// unwraps it with its secure channel purpose key. When a data key is not wrapped for it yet,
+/// it registers with the [`KafkaDataKeyService`] of the producer.
+pub(crate) struct KafkaDataKeys {
+    secure_channels: Arc<SecureChannels>,
+    identifier: Identifier,
+    consumers: Arc<dyn Storage>,
+    topic_policies: TopicPolicies,
+    alias: String,
+    rotation_interval: Duration,
+    topic_keys: Mutex<HashMap<String, TopicKeys>>,
+    unwrapped_keys: Mutex<HashMap<(Identifier, String), Vec<u8>>>,
+}
+
+impl KafkaDataKeys {
+    /// Create the data keys of a node, the consumers registered with this node
+    /// are persisted in the `consumers` storage
+    pub(crate) fn new(
+        secure_channels: Arc<SecureChannels>,
+        identifier: Identifier,
+        consumers: Arc<dyn Storage>,
+        topic_policies: TopicPolicies,
+    ) -> Self {
+        let alias = producer_alias(&identifier);
+        Self {
+            secure_channels,
+            identifier,
+            consumers,
+            topic_policies,
+            alias,
+            rotation_interval: DEFAULT_DATA_KEY_ROTATION_INTERVAL,
+            topic_keys: Default::default(),
+            unwrapped_keys: Default::default(),
+        }
+    }
+
+    /// Replace the data keys of a topic after this interval
+    pub(crate) fn with_rotation_interval(mut self, rotation_interval: Duration) -> Self {
+        self.rotation_interval = rotation_interval;
+        self
+    }
+
+    /// Address of the service registering the consumers of this node, which is also
+    /// the alias of the relay used by the consumers to reach this node
+    pub(crate) fn alias(&self) -> &str {
+        &self.alias
+    }
+
+    fn vault(&self) -> Arc<dyn VaultForSecureChannels> {
+        self.secure_channels.vault().secure_channel_vault
+    }
+
+    /// Encrypt some content with the current data key of a topic, creating a new data key if
+    /// there is none yet, if it is older than the rotation interval or if a consumer registered
+    pub(crate) async fn encrypt(
+        &self,
+        topic_name: &str,
+        content: &[u8],
+    ) -> Result<(WrappedDataKey, Vec<u8>)> {
+        let mut topic_keys = self.topic_keys.lock().await;
+        let topic = topic_keys.entry(topic_name.to_string()).or_default();
+
+        if topic.current(self.rotation_interval).is_none() {
+            let key = self.create_topic_key(topic_name).await?;
+            debug!(
+                "created the data key {} for the topic {topic_name}",
+                key.wrapped.key_id
+            );
+            topic.push(key);
+        }
+
+        let current = topic.keys.back().unwrap();
+        Ok((
+            current.wrapped.clone(),
+            Self::encrypt_content(&current.key, content)?,
+        ))
+    }
+
+    /// Create a data key for a topic, wrapped for its registered consumers
+    async fn create_topic_key(&self, topic_name: &str) -> Result<TopicKey> {
+        let mut key_id = [0u8; 16];
+        let mut key = vec![0u8; DATA_KEY_LENGTH];
+        thread_rng().fill_bytes(&mut key_id);
+        thread_rng().fill_bytes(&mut key);
+        let key_id = hex::encode(key_id);
+
+        let consumer_keys = self.authorized_consumer_keys(topic_name).await?;
+        let wrapped = self.wrap(topic_name, &key_id, &key, &consumer_keys).await?;
+        Ok(TopicKey {
+            key,
+            created_at: Instant::now(),
+            wrapped,
+        })
+    }
+
+    /// Return the purpose keys of the consumers registered for a topic
+    /// which are still authorized by the topic policy
+    async fn authorized_consumer_keys(&self, topic_name: &str) -> Result<Vec<X25519PublicKey>> {
+        let namespace = consumers_namespace(topic_name);
+        let mut consumer_keys: Vec<X25519PublicKey> = vec![];
+        for consumer in self.consumers.keys(&namespace).await? {
+            let attestation = match self.consumers.get(&namespace, &consumer).await? {
+                Some(attestation) => minicbor::decode::<PurposeKeyAttestation>(&attestation)?,
+                None => continue,
+            };
+            let consumer_key = match self.verify_consumer_key(None, &attestation).await {
+                Ok((subject, consumer_key)) => {
+                    if !self
+                        .topic_policies
+                        .is_authorized(topic_name, &subject)
+                        .await?
+                    {
+                        debug!("the consumer {subject} is not authorized anymore for the topic {topic_name}");
+                        continue;
+                    }
+                    consumer_key
+                }
+                Err(e) => {
+                    warn!("the purpose key of the consumer {consumer} is not valid anymore: {e}");
+                    continue;
+                }
+            };
+            if !consumer_keys.contains(&consumer_key) {
+                consumer_keys.push(consumer_key);
+            }
+        }
+        Ok(consumer_keys)
+    }
+
+    /// Verify the attestation of a consumer secure channel purpose key
+    async fn verify_consumer_key(
+        &self,
+        expected_subject: Option<&Identifier>,
+        attestation: &PurposeKeyAttestation,
+    ) -> Result<(Identifier, X25519PublicKey)> {
+        let data = self
+            .secure_channels
+            .identities()
+            .purpose_keys()
+            .purpose_keys_verification()
+            .verify_purpose_key_attestation(expected_subject, attestation)
+            .await?;
+        match data.public_key {
+            PurposePublicKey::SecureChannelStatic(public_key) => Ok((data.subject, public_key)),
+            PurposePublicKey::CredentialSigning(_) => Err(ApiError::core(
+                "the consumer purpose key is not a secure channel key",
+            )),
+        }
+    }
+
+    /// Register a consumer for a topic, if it is authorized by the topic policy, and
+    /// return the data keys retained for the topic, wrapped for that consumer
+    pub(crate) async fn register_consumer(
+        &self,
+        topic_name: &str,
+        consumer: &Identifier,
+        attestation: &PurposeKeyAttestation,
+    ) -> Result<Option<Vec<WrappedDataKey>>> {
+        let (_, consumer_key) = self
+            .verify_consumer_key(Some(consumer), attestation)
+            .await?;
+        if !self
+            .topic_policies
+            .is_authorized(topic_name, consumer)
+            .await?
+        {
+            return Ok(None);
+        }
+
+        let mut topic_keys = self.topic_keys.lock().await;
+        let topic = topic_keys.entry(topic_name.to_string()).or_default();
+
+        // the next data key of the topic must also be wrapped for a new consumer
+        let namespace = consumers_namespace(topic_name);
+        let attestation = minicbor::to_vec(attestation)?;
+        if self
+            .consumers
+            .get(&namespace, &consumer.to_string())
+            .await?
+            != Some(attestation.clone())
+        {
+            self.consumers
+                .set(&namespace, consumer.to_string(), attestation)
+                .await?;
+            topic.stale = true;
+            info!("registered the consumer {consumer} for the topic {topic_name}");
+        }
+
+        let mut data_keys = vec![];
+        for key in topic.keys.iter() {
+            data_keys.push(
+                self.wrap(
+                    topic_name,
+                    &key.wrapped.key_id,
+                    &key.key,
+                    &[consumer_key.clone()],
+                )
+                .await?,
+            );
+        }
+        Ok(Some(data_keys))
+    }
+
+    /// Wrap a data key for some consumers, using a new ephemeral key,
+    /// and sign it with the credential purpose key of this node
+    async fn wrap(
+        &self,
+        topic_name: &str,
+        key_id: &str,
+        key: &[u8],
+        consumer_keys: &[X25519PublicKey],
+    ) -> Result<WrappedDataKey> {
+        let vault = self.vault();
+        let ephemeral_secret = vault.generate_ephemeral_x25519_secret_key().await?;
+        let wrapped_keys = self
+            .wrap_for_consumers(&ephemeral_secret, key_id, key, consumer_keys)
+            .await;
+        let ephemeral_key = vault.get_x25519_public_key(&ephemeral_secret).await;
+        vault
+            .delete_ephemeral_x25519_secret_key(ephemeral_secret)
+            .await?;
+        let ephemeral_key = ephemeral_key?;
+        let wrapped_keys = wrapped_keys?;
+
+        let purpose_key = self
+            .secure_channels
+            .identities()
+            .purpose_keys()
+            .purpose_keys_creation()
+            .get_or_create_credential_purpose_key(&self.identifier)
+            .await?;
+        let signed_data = WrappedDataKey::signed_data(
+            &self.alias,
+            key_id,
+            topic_name,
+            &ephemeral_key,
+            &wrapped_keys,
+        )?;
+        let vault = self.secure_channels.vault();
+        let signed_data_hash = vault.verifying_vault.sha256(&signed_data).await?;
+        let signature = vault
+            .credential_vault
+            .sign(purpose_key.key(), &signed_data_hash.0)
+            .await?;
+
+        Ok(WrappedDataKey {
+            producer: self.alias.clone(),
+            key_id: key_id.to_string(),
+            topic_name: topic_name.to_string(),
+            ephemeral_key,
+            wrapped_keys,
+            attestation: purpose_key.attestation().clone(),
+            signature: signature.into(),
+        })
+    }
+
+    /// Verify that a data key was signed by a producer authorized by the topic policy
+    /// and return the identifier of that producer
+    async fn verify_producer(&self, data_key: &WrappedDataKey) -> Result<Identifier> {
+        let data = self
+            .secure_channels
+            .identities()
+            .purpose_keys()
+            .purpose_keys_verification()
+            .verify_purpose_key_attestation(None, &data_key.attestation)
+            .await?;
+        let public_key = match data.public_key {
+            PurposePublicKey::CredentialSigning(public_key) => public_key,
+            PurposePublicKey::SecureChannelStatic(_) => {
+                return Err(ApiError::core(
+                    "the producer purpose key is not a credential signing key",
+                ))
+            }
+        };
+
+        let signed_data = WrappedDataKey::signed_data(
+            &data_key.producer,
+            &data_key.key_id,
+            &data_key.topic_name,
+            &data_key.ephemeral_key,
+            &data_key.wrapped_keys,
+        )?;
+        let verifying_vault = self.secure_channels.vault().verifying_vault;
+        let signed_data_hash = verifying_vault.sha256(&signed_data).await?;
+        if !verifying_vault
+            .verify_signature(
+                &public_key.into(),
+                &signed_data_hash.0,
+                &data_key.signature.clone().into(),
+            )
+            .await?
+        {
+            return Err(ApiError::core("invalid signature of the data key"));
+        }
+
+        let producer = data.subject;
+        if data_key.producer != producer_alias(&producer) {
+            return Err(ApiError::core(format!(
+                "the data key {} was not created by the producer {producer}",
+                data_key.key_id
+            )));
+        }
+        if !self
+            .topic_policies
+            .is_authorized(&data_key.topic_name, &producer)
+            .await?
+        {
+            return Err(ApiError::core(format!(
+                "the producer {producer} is not authorized for the topic {}",
+                data_key.topic_name
+            )));
+        }
+        Ok(producer)
+    }
+
+    async fn wrap_for_consumers(
+        &self,
+        ephemeral_secret: &X25519SecretKeyHandle,
+        key_id: &str,
+        key: &[u8],
+        consumer_keys: &[X25519PublicKey],
+    ) -> Result<Vec<WrappedKey>> {
+        let vault = self.vault();
+        let mut wrapped_keys = vec![];
+        for consumer_key in consumer_keys {
+            let wrapping_key = self.wrapping_key(ephemeral_secret, consumer_key).await?;
+            let wrapped_key = vault
+                .aead_encrypt(&wrapping_key, key, &WRAPPING_NONCE, key_id.as_bytes())
+                .await;
+            vault.delete_aead_secret_key(wrapping_key).await?;
+            wrapped_keys.push(WrappedKey {
+                consumer_key: consumer_key.clone(),
+                wrapped_key: wrapped_key?,
+            });
+        }
+        Ok(wrapped_keys)
+    }
+
+    /// Derive the key wrapping a data key from a Diffie-Hellman between
+    /// the producer ephemeral key and the consumer purpose key
+    async fn wrapping_key(
+        &self,
+        secret_key: &X25519SecretKeyHandle,
+        public_key: &X25519PublicKey,
+    ) -> Result<AeadSecretKeyHandle> {
+        let vault = self.vault();
+        let dh = vault.x25519_ecdh(secret_key, public_key).await?;
+        let salt = vault.import_secret_buffer(WRAPPING_SALT.to_vec()).await?;
+        let hkdf_output = vault.hkdf(&salt, Some(&dh), HKDFNumberOfOutputs::Two).await;
+        vault.delete_secret_buffer(salt).await?;
+        vault.delete_secret_buffer(dh).await?;
+
+        let [wrapping_key, unused]: [SecretBufferHandle; 2] = hkdf_output?
+            .0
+             .0
+            .try_into()
+            .map_err(|_| ApiError::core("invalid key derivation output"))?;
+        vault.delete_secret_buffer(unused).await?;
+        vault.convert_secret_buffer_to_aead_key(wrapping_key).await
+    }
+
+    /// Return a data key if it is already known or if it is wrapped for this node.
+    /// The data key is only used if it was signed by a producer authorized for the topic
+    pub(crate) async fn unwrap(&self, data_key: &WrappedDataKey) -> Result<Option<Vec<u8>>> {
+        let producer = self.verify_producer(data_key).await?;
+        let cache_key = (producer, data_key.key_id.clone());
+        if let Some(key) = self.unwrapped_keys.lock().await.get(&cache_key) {
+            return Ok(Some(key.clone()));
+        }
+
+        let purpose_key = self
+            .secure_channels
+            .identities()
+            .purpose_keys()
+            .purpose_keys_creation()
+            .get_or_create_secure_channel_purpose_key(&self.identifier)
+            .await?;
+        let wrapped_key = match data_key
+            .wrapped_keys
+            .iter()
+            .find(|k| &k.consumer_key == purpose_key.public_key())
+        {
+            Some(wrapped_key) => wrapped_key,
+            None => return Ok(None),
+        };
+
+        let vault = self.vault();
+        let wrapping_key = self
+            .wrapping_key(purpose_key.key(), &data_key.ephemeral_key)
+            .await?;
+        let key = vault
+            .aead_decrypt(
+                &wrapping_key,
+                &wrapped_key.wrapped_key,
+                &WRAPPING_NONCE,
+                data_key.key_id.as_bytes(),
+            )
+            .await;
+        vault.delete_aead_secret_key(wrapping_key).await?;
+        let key = key?;
+
+        let mut unwrapped_keys = self.unwrapped_keys.lock().await;
+        if unwrapped_keys.len() >= MAX_CACHED_DATA_KEYS {
+            unwrapped_keys.clear();
+        }
+        unwrapped_keys.insert(cache_key, key.clone());
+        Ok(Some(key))
+    }
+
+    /// Return the registration of this node as a consumer
+    pub(crate) async fn consumer_registration(&self) -> Result<ConsumerRegistration> {
+        let purpose_key = self
+            .secure_channels
+            .identities()
+            .purpose_keys()
+            .purpose_keys_creation()
+            .get_or_create_secure_channel_purpose_key(&self.identifier)
+            .await?;
+        Ok(ConsumerRegistration {
+            attestation: purpose_key.attestation().clone(),
+        })
+    }
+
+    /// Encrypt some content with a data key, the result is a random nonce followed by the ciphertext
+    fn encrypt_content(key: &[u8], content: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = Self::cipher(key)?
+            .encrypt(Nonce::from_slice(&nonce), content)
+            .map_err(|_| ApiError::core("cannot encrypt the content with the data key"))?;
+
+        let mut encrypted = Vec::with_capacity(NONCE_LENGTH + ciphertext.len());
+        encrypted.extend_from_slice(&nonce);
+        encrypted.extend_from_slice(&ciphertext);
+        Ok(encrypted)
+    }
+
+    /// Decrypt some content encrypted with [`KafkaDataKeys::encrypt`]
+    pub(crate) fn decrypt(key: &[u8], encrypted: &[u8]) -> Result<Vec<u8>> {
+        if encrypted.len() < NONCE_LENGTH {
+            return Err(ApiError::core("the encrypted content is too short"));
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+        Self::cipher(key)?
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| ApiError::core("cannot decrypt the content with the data key"))
+    }
+
+    fn cipher(key: &[u8]) -> Result<Aes256Gcm> {
+        if key.len() != DATA_KEY_LENGTH {
+            return Err(ApiError::core("invalid data key length"));
+        }
+        Ok(Aes256Gcm::new(Key::from_slice(key)))
+    }
+}
+
+/// This service registers the consumers of the topics encrypted by a producer node,
+/// over a secure channel, and returns them the data keys of those topics
+pub(crate) struct KafkaDataKeyService {
+    data_keys: Arc<KafkaDataKeys>,
+}
+
+impl KafkaDataKeyService {
+    pub(crate) async fn create(
+        context: &Context,
+        data_keys: Arc<KafkaDataKeys>,
+        secure_channel_listener_flow_control_id: &FlowControlId,
+    ) -> Result<()> {
+        let address = Address::from_string(data_keys.alias());
+        // the consumers reach this service through the default secure channel listener
+        context
+            .flow_controls()
+            .add_consumer(address.clone(), secure_channel_listener_flow_control_id);
+
+        context.start_worker(address, Self { data_keys }).await
+    }
+}
+
+#[ockam::worker]
+impl Worker for KafkaDataKeyService {
+    type Message = Vec<u8>;
+    type Context = Context;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        if let Ok(i) = IdentitySecureChannelLocalInfo::find_info(m.local_message()) {
+            let from = i.their_identity_id();
+            let mut dec = Decoder::new(m.as_body());
+            let req: RequestHeader = dec.decode()?;
+            trace! {
+                target: "ockam_api::kafka::data_keys",
+                from   = %from,
+                id     = %req.id(),
+                method = ?req.method(),
+                path   = %req.path(),
+                body   = %req.has_body(),
+                "request"
+            }
+
+            let res = match (req.method(), req.path_segments::<3>().as_slice()) {
+                (Some(Method::Post), ["consumers", topic_name]) => {
+                    let registration: ConsumerRegistration = dec.decode()?;
+                    match self
+                        .data_keys
+                        .register_consumer(topic_name, &from, &registration.attestation)
+                        .await
+                    {
+                        Ok(Some(data_keys)) => Response::ok(&req)
+                            .body(RegisteredDataKeys { data_keys })
+                            .to_vec()?,
+                        Ok(None) => {
+                            warn!("unauthorized consumer {from} for the topic {topic_name}");
+                            Response::forbidden(&req, "unauthorized consumer").to_vec()?
+                        }
+                        Err(e) => {
+                            warn!("cannot register the consumer {from}: {e}");
+                            Response::bad_request(&req, &e.to_string()).to_vec()?
+                        }
+                    }
+                }
+                _ => Response::unknown_path(&req).to_vec()?,
+            };
+            c.send(m.return_route(), res).await
+        } else {
+            secure_channel_required(c, m).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::identity::storage::InMemoryStorage;
+    use ockam::identity::{secure_channels, Identity};
+    use ockam_abac::mem::Memory;
+    use ockam_abac::Expr;
+
+    #[ockam_macros::test]
+    async fn data_keys_are_wrapped_for_the_authorized_consumers(
+        context: &mut Context,
+    ) -> Result<()> {
+        let secure_channels = secure_channels();
+        let identities = secure_channels.identities();
+        let producer = identities.identities_creation().create_identity().await?;
+        let consumer = identities.identities_creation().create_identity().await?;
+        let other_consumer = identities.identities_creation().create_identity().await?;
+
+        // only the first consumer is authorized for the topic by the producer,
+        // and the consumers only trust the data keys of the producer
+        let policies = Arc::new(Memory::new());
+        let consumer_policies = Arc::new(Memory::new());
+        set_topic_policy(&policies, consumer.identifier()).await?;
+        set_topic_policy(&consumer_policies, producer.identifier()).await?;
+
+        let data_keys = |identity: &Identity, policies: Arc<Memory>| {
+            KafkaDataKeys::new(
+                secure_channels.clone(),
+                identity.identifier().clone(),
+                InMemoryStorage::create(),
+                TopicPolicies::new(
+                    policies,
+                    identities.repository(),
+                    identities.revocation_lists(),
+                    "test_trust_context_id",
+                ),
+            )
+        };
+        let producer_data_keys = data_keys(&producer, policies.clone());
+        let consumer_data_keys = data_keys(&consumer, consumer_policies.clone());
+        let other_consumer_data_keys = data_keys(&other_consumer, consumer_policies);
+
+        // a record produced before the consumer registration is not wrapped for it
+        let (first_key, first_record) = producer_data_keys.encrypt("topic", b"first").await?;
+        assert!(first_key.wrapped_keys.is_empty());
+        assert_eq!(consumer_data_keys.unwrap(&first_key).await?, None);
+
+        // once registered, the consumer receives the retained data keys
+        let registration = consumer_data_keys.consumer_registration().await?;
+        let retained = producer_data_keys
+            .register_consumer("topic", consumer.identifier(), &registration.attestation)
+            .await?
+            .unwrap();
+        assert_eq!(retained.len(), 1);
+        let key = consumer_data_keys.unwrap(&retained[0]).await?.unwrap();
+        assert_eq!(KafkaDataKeys::decrypt(&key, &first_record)?, b"first");
+
+        // the data key is rotated and the next one is wrapped for the registered consumer
+        let (second_key, second_record) = producer_data_keys.encrypt("topic", b"second").await?;
+        assert_ne!(second_key.key_id, first_key.key_id);
+        assert_eq!(second_key.wrapped_keys.len(), 1);
+        let key = consumer_data_keys.unwrap(&second_key).await?.unwrap();
+        assert_eq!(KafkaDataKeys::decrypt(&key, &second_record)?, b"second");
+        let (third_key, _) = producer_data_keys.encrypt("topic", b"third").await?;
+        assert_eq!(third_key, second_key);
+
+        // the consumer is not authorized anymore once the policy changes
+        policies
+            .set_policy(
+                &Resource::new(&format!("{KAFKA_TOPIC_RESOURCE_PREFIX}topic")),
+                &actions::HANDLE_MESSAGE,
+                &Expr::Bool(false),
+            )
+            .await?;
+        let producer_data_keys = producer_data_keys.with_rotation_interval(Duration::ZERO);
+        let (fourth_key, _) = producer_data_keys.encrypt("topic", b"fourth").await?;
+        assert!(fourth_key.wrapped_keys.is_empty());
+
+        // an unauthorized consumer cannot register
+        let registration = other_consumer_data_keys.consumer_registration().await?;
+        assert!(producer_data_keys
+            .register_consumer(
+                "topic",
+                other_consumer.identifier(),
+                &registration.attestation
+            )
+            .await?
+            .is_none());
+
+        // a consumer cannot register the purpose key of another identity
+        let registration = consumer_data_keys.consumer_registration().await?;
+        assert!(producer_data_keys
+            .register_consumer(
+                "topic",
+                other_consumer.identifier(),
+                &registration.attestation
+            )
+            .await
+            .is_err());
+
+        context.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn data_keys_are_only_unwrapped_when_signed_by_an_authorized_producer(
+        context: &mut Context,
+    ) -> Result<()> {
+        let secure_channels = secure_channels();
+        let identities = secure_channels.identities();
+        let producer = identities.identities_creation().create_identity().await?;
+        let other_producer = identities.identities_creation().create_identity().await?;
+        let consumer = identities.identities_creation().create_identity().await?;
+
+        // the producers authorize the consumer, which only trusts the first producer
+        let policies = Arc::new(Memory::new());
+        let consumer_policies = Arc::new(Memory::new());
+        set_topic_policy(&policies, consumer.identifier()).await?;
+        set_topic_policy(&consumer_policies, producer.identifier()).await?;
+
+        let data_keys = |identity: &Identity, policies: Arc<Memory>| {
+            KafkaDataKeys::new(
+                secure_channels.clone(),
+                identity.identifier().clone(),
+                InMemoryStorage::create(),
+                TopicPolicies::new(
+                    policies,
+                    identities.repository(),
+                    identities.revocation_lists(),
+                    "test_trust_context_id",
+                ),
+            )
+        };
+        let producer_data_keys = data_keys(&producer, policies.clone());
+        let other_producer_data_keys = data_keys(&other_producer, policies);
+        let consumer_data_keys = data_keys(&consumer, consumer_policies);
+
+        // the alias of a producer doesn't change when the producer restarts
+        assert_eq!(
+            producer_data_keys.alias(),
+            data_keys(&producer, Arc::new(Memory::new())).alias()
+        );
+
+        let registration = consumer_data_keys.consumer_registration().await?;
+        for data_keys in [&producer_data_keys, &other_producer_data_keys] {
+            data_keys
+                .register_consumer("topic", consumer.identifier(), &registration.attestation)
+                .await?
+                .unwrap();
+        }
+        let (data_key, record) = producer_data_keys.encrypt("topic", b"record").await?;
+        let key = consumer_data_keys.unwrap(&data_key).await?.unwrap();
+        assert_eq!(KafkaDataKeys::decrypt(&key, &record)?, b"record");
+
+        // the data keys of a producer which is not authorized for the topic are rejected
+        let (other_data_key, _) = other_producer_data_keys.encrypt("topic", b"other").await?;
+        assert!(consumer_data_keys.unwrap(&other_data_key).await.is_err());
+
+        // a data key can't be attributed to another producer, even with the same key id
+        let mut forged_data_key = other_data_key.clone();
+        forged_data_key.producer = data_key.producer.clone();
+        forged_data_key.key_id = data_key.key_id.clone();
+        assert!(consumer_data_keys.unwrap(&forged_data_key).await.is_err());
+        let mut forged_data_key = other_data_key;
+        forged_data_key.attestation = data_key.attestation.clone();
+        assert!(consumer_data_keys.unwrap(&forged_data_key).await.is_err());
+
+        // a modified data key is rejected, even when the original one was already unwrapped
+        let mut modified_data_key = data_key.clone();
+        modified_data_key.topic_name = "other_topic".to_string();
+        assert!(consumer_data_keys.unwrap(&modified_data_key).await.is_err());
+        let mut modified_data_key = data_key;
+        modified_data_key.ephemeral_key = X25519PublicKey([1u8; 32]);
+        assert!(consumer_data_keys.unwrap(&modified_data_key).await.is_err());
+
+        context.stop().await
+    }
+
+    /// Only authorize an identity for the topic `topic`
+    async fn set_topic_policy(policies: &Memory, identifier: &Identifier) -> Result<()> {
+        policies
+            .set_policy(
+                &Resource::new(&format!("{KAFKA_TOPIC_RESOURCE_PREFIX}topic")),
+                &actions::HANDLE_MESSAGE,
+                &eq([ident("subject.identifier"), str(identifier.to_string())]),
+            )
+            .await
+    }
+}
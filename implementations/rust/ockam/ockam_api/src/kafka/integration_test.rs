@@ -3,7 +3,7 @@ mod test {
     use std::sync::atomic::{AtomicBool, Ordering};
     use std::time::Duration;
 
-    use bytes::{Buf, BufMut, BytesMut};
+    use bytes::{Buf, BufMut, Bytes, BytesMut};
     use indexmap::IndexMap;
     use kafka_protocol::messages::produce_request::{PartitionProduceData, TopicProduceData};
     use kafka_protocol::messages::{
@@ -29,7 +29,9 @@ mod test {
     use uuid::Uuid;
 
     use ockam::compat::tokio::io::DuplexStream;
+    use ockam::identity::storage::InMemoryStorage;
     use ockam::Context;
+    use ockam_abac::mem::Memory;
     use ockam_core::async_trait;
     use ockam_core::compat::sync::Arc;
     use ockam_core::route;
@@ -43,10 +45,12 @@ mod test {
     use crate::kafka::protocol_aware::utils::{encode_request, encode_response};
     use crate::kafka::secure_channel_map::RelayCreator;
     use crate::kafka::{
-        ConsumerNodeAddr, KafkaInletController, KafkaPortalListener,
-        KafkaSecureChannelControllerImpl,
+        ConsumerNodeAddr, KafkaDataKeyService, KafkaDataKeys, KafkaInletController,
+        KafkaPortalListener, KafkaSecureChannelControllerImpl, RecordKeyEncryptor, TopicPolicies,
+        RECORD_KEY_SECRET_LENGTH,
     };
     use crate::test_utils::NodeManagerHandle;
+    use crate::DefaultAddress;
 
     //TODO: upgrade to 13 by adding a metadata request to map uuid<=>topic_name
     const TEST_KAFKA_API_VERSION: i16 = 12;
@@ -65,18 +69,40 @@ mod test {
         }
     }
 
+    /// Create the data keys of a producer or a consumer, all the members of the
+    /// trust context are authorized by the default topic policies
+    fn create_data_keys(handler: &NodeManagerHandle) -> Arc<KafkaDataKeys> {
+        let identities = handler.secure_channels.identities();
+        Arc::new(KafkaDataKeys::new(
+            handler.secure_channels.clone(),
+            handler.identifier.clone(),
+            InMemoryStorage::create(),
+            TopicPolicies::new(
+                Arc::new(Memory::new()),
+                identities.repository(),
+                identities.revocation_lists(),
+                "test_trust_context_id",
+            ),
+        ))
+    }
+
     async fn create_kafka_service(
         context: &Context,
         handler: &NodeManagerHandle,
         listener_address: Address,
         outlet_address: Address,
+        record_key_encryptor: Option<RecordKeyEncryptor>,
+        data_keys: Option<Arc<KafkaDataKeys>>,
     ) -> ockam::Result<u16> {
-        let secure_channel_controller = KafkaSecureChannelControllerImpl::new_extended(
+        let mut secure_channel_controller = KafkaSecureChannelControllerImpl::new_extended(
             handler.secure_channels.clone(),
             ConsumerNodeAddr::Relay(MultiAddr::try_from("/service/api")?),
             Some(HopRelayCreator {}),
             "test_trust_context_id".to_string(),
         );
+        if let Some(data_keys) = data_keys {
+            secure_channel_controller = secure_channel_controller.with_data_keys(data_keys);
+        }
 
         let mut interceptor_multiaddr = MultiAddr::default();
         interceptor_multiaddr.push_back(Service::new(listener_address.address()))?;
@@ -102,6 +128,7 @@ mod test {
             context,
             inlet_controller,
             secure_channel_controller.into_trait(),
+            record_key_encryptor,
             listener_address,
         )
         .await?;
@@ -114,6 +141,231 @@ mod test {
     async fn producer__flow_with_mock_kafka__content_encryption_and_decryption(
         context: &mut Context,
     ) -> ockam::Result<()> {
+        let (encrypted_records, plain_records) =
+            produce_and_consume_with_mock_kafka(context, None, false).await?;
+
+        let encrypted_record = encrypted_records.get(0).unwrap();
+        assert_ne!(
+            encrypted_record.value.as_ref().unwrap(),
+            "hello world!".as_bytes()
+        );
+        //without the record key encryption, the key and headers are left untouched
+        assert_eq!(
+            encrypted_record.key.as_ref().unwrap(),
+            "customer-1".as_bytes()
+        );
+        assert_eq!(
+            encrypted_record
+                .headers
+                .get(&StrBytes::from_str("trace-id"))
+                .unwrap(),
+            &Some(Bytes::from("1234"))
+        );
+
+        let plain_record = plain_records.get(0).unwrap();
+        assert_eq!(
+            plain_record.value.as_ref().unwrap(),
+            "hello world!".as_bytes()
+        );
+        assert_eq!(plain_record.key.as_ref().unwrap(), "customer-1".as_bytes());
+
+        Ok(())
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn producer__flow_with_mock_kafka__key_and_headers_encryption_and_decryption(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let record_key_encryptor = RecordKeyEncryptor::new(&[7; RECORD_KEY_SECRET_LENGTH])?;
+        let (encrypted_records, plain_records) =
+            produce_and_consume_with_mock_kafka(context, Some(record_key_encryptor.clone()), false)
+                .await?;
+
+        //the broker only sees the encrypted key, which is deterministic
+        //and the encrypted headers
+        let encrypted_record = encrypted_records.get(0).unwrap();
+        assert_ne!(
+            encrypted_record.value.as_ref().unwrap(),
+            "hello world!".as_bytes()
+        );
+        assert_eq!(
+            encrypted_record.key.as_ref().unwrap().to_vec(),
+            record_key_encryptor.encrypt(b"customer-1")?
+        );
+        assert_eq!(encrypted_record.headers.len(), 1);
+        assert!(encrypted_record
+            .headers
+            .get(&StrBytes::from_str("trace-id"))
+            .is_none());
+        assert!(encrypted_record
+            .headers
+            .get(&StrBytes::from_str("ockam_encrypted_headers"))
+            .is_some());
+
+        let plain_record = plain_records.get(0).unwrap();
+        assert_eq!(
+            plain_record.value.as_ref().unwrap(),
+            "hello world!".as_bytes()
+        );
+        assert_eq!(plain_record.key.as_ref().unwrap(), "customer-1".as_bytes());
+        assert_eq!(plain_record.headers.len(), 1);
+        assert_eq!(
+            plain_record
+                .headers
+                .get(&StrBytes::from_str("trace-id"))
+                .unwrap(),
+            &Some(Bytes::from("1234"))
+        );
+
+        Ok(())
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn producer__flow_with_mock_kafka__foreign_key_and_headers_are_left_untouched(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let record_key_encryptor = RecordKeyEncryptor::new(&[7; RECORD_KEY_SECRET_LENGTH])?;
+        let (_, plain_records) =
+            produce_and_consume_with_mock_kafka(context, Some(record_key_encryptor), true).await?;
+
+        //the record protected by the producer is still decrypted
+        let plain_record = plain_records.get(0).unwrap();
+        assert_eq!(plain_record.key.as_ref().unwrap(), "customer-1".as_bytes());
+
+        //the other record is returned as it was set by its producer
+        let foreign_record = plain_records.get(1).unwrap();
+        assert_eq!(
+            foreign_record.value.as_ref().unwrap(),
+            "hello world!".as_bytes()
+        );
+        assert_eq!(foreign_record.key.as_ref().unwrap(), "plain-key".as_bytes());
+        assert_eq!(
+            foreign_record
+                .headers
+                .get(&StrBytes::from_str("ockam_encrypted_headers"))
+                .unwrap(),
+            &Some(Bytes::from("set by another producer"))
+        );
+
+        Ok(())
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 10_000)]
+    async fn producer__flow_with_mock_kafka__data_key_encryption_for_several_consumers(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = crate::util::test_utils::start_manager_for_tests(context).await?;
+
+        let producer_data_keys = create_data_keys(&handler);
+        let secure_channel_listener_flow_control_id = context
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+            .unwrap();
+        KafkaDataKeyService::create(
+            context,
+            producer_data_keys.clone(),
+            &secure_channel_listener_flow_control_id,
+        )
+        .await?;
+
+        let producer_bootstrap_port = create_kafka_service(
+            context,
+            &handler,
+            "kafka_producer_listener".into(),
+            "kafka_producer_outlet".into(),
+            None,
+            Some(producer_data_keys),
+        )
+        .await?;
+
+        let mut producer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+        handler
+            .tcp
+            .create_outlet(
+                "kafka_producer_outlet",
+                format!("127.0.0.1:{}", producer_mock_kafka.port),
+                TcpOutletOptions::new(),
+            )
+            .await?;
+        let mut request = simulate_kafka_producer_and_read_request(
+            producer_bootstrap_port,
+            &mut producer_mock_kafka,
+        )
+        .await;
+
+        //every consumer registers with the producer to unwrap the data key of the same record
+        let mut consumer_mock_kafkas = vec![];
+        for consumer in ["kafka_consumer_1", "kafka_consumer_2"] {
+            let consumer_bootstrap_port = create_kafka_service(
+                context,
+                &handler,
+                format!("{consumer}_listener").into(),
+                format!("{consumer}_outlet").into(),
+                None,
+                Some(create_data_keys(&handler)),
+            )
+            .await?;
+
+            let mut consumer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+            handler
+                .tcp
+                .create_outlet(
+                    format!("{consumer}_outlet"),
+                    format!("127.0.0.1:{}", consumer_mock_kafka.port),
+                    TcpOutletOptions::new(),
+                )
+                .await?;
+
+            let plain_fetch_response = simulate_kafka_consumer_and_read_response(
+                consumer_bootstrap_port,
+                &mut consumer_mock_kafka,
+                &request,
+            )
+            .await;
+
+            let plain_content = plain_fetch_response
+                .responses
+                .get(0)
+                .as_ref()
+                .unwrap()
+                .partitions
+                .get(0)
+                .as_ref()
+                .unwrap()
+                .records
+                .as_ref()
+                .unwrap();
+
+            let mut plain_content = BytesMut::from(plain_content.as_ref());
+            let records = RecordBatchDecoder::decode(&mut plain_content).unwrap();
+            assert_eq!(
+                records.get(0).as_ref().unwrap().value.as_ref().unwrap(),
+                "hello world!".as_bytes()
+            );
+            consumer_mock_kafkas.push(consumer_mock_kafka);
+        }
+
+        context.stop().await?;
+        for consumer_mock_kafka in consumer_mock_kafkas {
+            consumer_mock_kafka.destroy_and_wait().await;
+        }
+        producer_mock_kafka.destroy_and_wait().await;
+        Ok(())
+    }
+
+    /// Send a record from a producer to a consumer through a mock kafka, and return
+    /// the records seen by the broker and the records received by the consumer.
+    /// When `foreign_record` is set, the broker also returns a record with a key and an
+    /// `ockam_encrypted_headers` header which were not set by an ockam producer.
+    /// The context is stopped once the records have been received
+    async fn produce_and_consume_with_mock_kafka(
+        context: &mut Context,
+        record_key_encryptor: Option<RecordKeyEncryptor>,
+        foreign_record: bool,
+    ) -> ockam::Result<(Vec<Record>, Vec<Record>)> {
         let handler = crate::util::test_utils::start_manager_for_tests(context).await?;
 
         let consumer_bootstrap_port = create_kafka_service(
@@ -121,6 +373,8 @@ mod test {
             &handler,
             "kafka_consumer_listener".into(),
             "kafka_consumer_outlet".into(),
+            record_key_encryptor.clone(),
+            None,
         )
         .await?;
 
@@ -129,6 +383,8 @@ mod test {
             &handler,
             "kafka_producer_listener".into(),
             "kafka_producer_outlet".into(),
+            record_key_encryptor,
+            None,
         )
         .await?;
 
@@ -164,7 +420,7 @@ mod test {
                 TcpOutletOptions::new(),
             )
             .await?;
-        let request = simulate_kafka_producer_and_read_request(
+        let mut request = simulate_kafka_producer_and_read_request(
             producer_bootstrap_port,
             &mut producer_mock_kafka,
         )
@@ -185,12 +441,37 @@ mod test {
             .unwrap();
 
         let mut encrypted_body = BytesMut::from(encrypted_body.as_ref());
-        let records = RecordBatchDecoder::decode(&mut encrypted_body).unwrap();
-
-        assert_ne!(
-            records.get(0).unwrap().value.as_ref().unwrap(),
-            "hello world!".as_bytes()
-        );
+        let encrypted_records = RecordBatchDecoder::decode(&mut encrypted_body).unwrap();
+
+        if foreign_record {
+            let mut record = encrypted_records.get(0).unwrap().clone();
+            record.offset = 1;
+            record.key = Some(Bytes::from("plain-key"));
+            record.headers = IndexMap::from([(
+                StrBytes::from_str("ockam_encrypted_headers"),
+                Some(Bytes::from("set by another producer")),
+            )]);
+
+            let mut encoded = BytesMut::new();
+            RecordBatchEncoder::encode(
+                &mut encoded,
+                encrypted_records.iter().chain([&record]),
+                &RecordEncodeOptions {
+                    version: 2,
+                    compression: Compression::None,
+                },
+            )
+            .unwrap();
+            request
+                .topic_data
+                .values_mut()
+                .next()
+                .unwrap()
+                .partition_data
+                .get_mut(0)
+                .unwrap()
+                .records = Some(encoded.freeze());
+        }
 
         let mut consumer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
         handler
@@ -226,17 +507,12 @@ mod test {
             .unwrap();
 
         let mut plain_content = BytesMut::from(plain_content.as_ref());
-        let records = RecordBatchDecoder::decode(&mut plain_content).unwrap();
-
-        assert_eq!(
-            records.get(0).as_ref().unwrap().value.as_ref().unwrap(),
-            "hello world!".as_bytes()
-        );
+        let plain_records = RecordBatchDecoder::decode(&mut plain_content).unwrap();
 
         context.stop().await?;
         consumer_mock_kafka.destroy_and_wait().await;
         producer_mock_kafka.destroy_and_wait().await;
-        Ok(())
+        Ok((encrypted_records, plain_records))
     }
 
     async fn simulate_kafka_producer_and_read_request(
@@ -278,9 +554,12 @@ mod test {
                 offset: 0,
                 sequence: 0,
                 timestamp: 0,
-                key: None,
+                key: Some(Bytes::from("customer-1")),
                 value: Some(BytesMut::from("hello world!").freeze()),
-                headers: Default::default(),
+                headers: IndexMap::from([(
+                    StrBytes::from_str("trace-id"),
+                    Some(Bytes::from("1234")),
+                )]),
             }]
             .iter(),
             &RecordEncodeOptions {
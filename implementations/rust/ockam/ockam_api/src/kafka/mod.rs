@@ -1,6 +1,7 @@
 //!This service allows encrypted transparent communication from the kafka producer
 //! to the kafka consumer without any modification in the existing application.
 
+mod data_keys;
 mod inlet_controller;
 mod integration_test;
 mod length_delimited;
@@ -9,13 +10,17 @@ mod outlet_service;
 mod portal_listener;
 mod portal_worker;
 mod protocol_aware;
+mod record_key_encryptor;
 mod secure_channel_map;
 
+pub(crate) use data_keys::{KafkaDataKeyService, KafkaDataKeys, TopicPolicies};
 pub(crate) use inlet_controller::KafkaInletController;
 use ockam_core::Address;
 pub(crate) use outlet_service::prefix_relay::PrefixRelayService;
 pub(crate) use outlet_service::OutletManagerService;
 pub(crate) use portal_listener::KafkaPortalListener;
+pub(crate) use record_key_encryptor::RecordKeyEncryptor;
+pub use record_key_encryptor::RECORD_KEY_SECRET_LENGTH;
 pub(crate) use secure_channel_map::ConsumerNodeAddr;
 pub(crate) use secure_channel_map::KafkaSecureChannelControllerImpl;
 
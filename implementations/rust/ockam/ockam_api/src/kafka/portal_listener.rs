@@ -7,6 +7,7 @@ use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::portal_worker::KafkaPortalWorker;
 use crate::kafka::protocol_aware::TopicUuidMap;
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
+use crate::kafka::RecordKeyEncryptor;
 
 ///First point of ingress of kafka connections, at the first message it spawns new stateful workers
 /// to take care of the connection.
@@ -14,6 +15,7 @@ pub(crate) struct KafkaPortalListener {
     inlet_controller: KafkaInletController,
     secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
     uuid_to_name: TopicUuidMap,
+    record_key_encryptor: Option<RecordKeyEncryptor>,
 }
 
 #[ockam::worker]
@@ -48,6 +50,7 @@ impl Worker for KafkaPortalListener {
             self.secure_channel_controller.clone(),
             self.uuid_to_name.clone(),
             self.inlet_controller.clone(),
+            self.record_key_encryptor.clone(),
             None,
             flow_control_id,
             route![inlet_responder_address],
@@ -78,6 +81,7 @@ impl KafkaPortalListener {
         context: &Context,
         inlet_controller: KafkaInletController,
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
+        record_key_encryptor: Option<RecordKeyEncryptor>,
         listener_address: Address,
     ) -> ockam_core::Result<()> {
         context
@@ -87,6 +91,7 @@ impl KafkaPortalListener {
                     inlet_controller,
                     secure_channel_controller,
                     uuid_to_name: Default::default(),
+                    record_key_encryptor,
                 },
             )
             .await
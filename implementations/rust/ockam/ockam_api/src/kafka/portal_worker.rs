@@ -16,7 +16,7 @@ use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::length_delimited::{length_encode, KafkaMessageDecoder};
 use crate::kafka::protocol_aware::{InletInterceptorImpl, KafkaMessageInterceptor, TopicUuidMap};
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
-use crate::kafka::KAFKA_OUTLET_BOOTSTRAP_ADDRESS;
+use crate::kafka::{RecordKeyEncryptor, KAFKA_OUTLET_BOOTSTRAP_ADDRESS};
 
 ///by default kafka supports up to 1MB messages, 16MB is the maximum suggested
 pub(crate) const MAX_KAFKA_MESSAGE_SIZE: u32 = 16 * 1024 * 1024;
@@ -391,6 +391,7 @@ impl KafkaPortalWorker {
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
         uuid_to_name: TopicUuidMap,
         inlet_map: KafkaInletController,
+        record_key_encryptor: Option<RecordKeyEncryptor>,
         max_kafka_message_size: Option<u32>,
         flow_control_id: Option<FlowControlId>,
         inlet_responder_route: Route,
@@ -399,6 +400,7 @@ impl KafkaPortalWorker {
             secure_channel_controller,
             uuid_to_name,
             inlet_map,
+            record_key_encryptor,
         ));
 
         let requests_worker_address = Address::random_tagged("KafkaPortalWorker.requests");
@@ -754,6 +756,7 @@ mod test {
             secure_channel_controller,
             Default::default(),
             inlet_map,
+            None,
             Some(TEST_MAX_KAFKA_MESSAGE_SIZE),
             None,
             route![context.address()],
@@ -818,6 +821,7 @@ mod test {
             inlet_map.clone(),
             None,
             None,
+            None,
             route![context.address()],
         )
         .await?;
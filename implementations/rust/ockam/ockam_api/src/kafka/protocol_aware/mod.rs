@@ -1,6 +1,7 @@
+use crate::kafka::data_keys::WrappedDataKey;
 use crate::kafka::portal_worker::InterceptError;
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
-use crate::kafka::KafkaInletController;
+use crate::kafka::{KafkaInletController, RecordKeyEncryptor};
 use bytes::BytesMut;
 use kafka_protocol::messages::ApiKey;
 use minicbor::{Decode, Encode};
@@ -13,6 +14,7 @@ use ockam_core::{async_trait, Address};
 use ockam_node::Context;
 
 mod metadata_interceptor;
+mod record_batch;
 mod request;
 mod response;
 mod tests;
@@ -53,6 +55,7 @@ pub(crate) struct InletInterceptorImpl {
     uuid_to_name: TopicUuidMap,
     secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
     inlet_map: KafkaInletController,
+    record_key_encryptor: Option<RecordKeyEncryptor>,
 }
 
 #[async_trait]
@@ -79,8 +82,29 @@ impl KafkaMessageInterceptor for InletInterceptorImpl {
 #[cbor(map)]
 ///Wraps the content within every record batch
 struct MessageWrapper {
-    #[n(1)] consumer_decryptor_address: Address,
-    #[n(2)] content: Vec<u8>
+    #[n(1)] consumer_decryptor_address: Option<Address>,
+    #[n(2)] content: Vec<u8>,
+    #[n(3)] data_key: Option<WrappedDataKey>,
+}
+
+/// Name of the record header containing the original record headers, encrypted,
+/// when the record keys and headers are protected
+const ENCRYPTED_HEADERS_KEY: &str = "ockam_encrypted_headers";
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+///Original headers of a record, encrypted as a whole
+struct RecordHeaders {
+    #[n(1)] headers: Vec<RecordHeader>
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+struct RecordHeader {
+    #[n(1)] key: String,
+    #[n(2)] value: Option<Vec<u8>>
 }
 
 impl InletInterceptorImpl {
@@ -88,12 +112,14 @@ impl InletInterceptorImpl {
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
         uuid_to_name: TopicUuidMap,
         inlet_map: KafkaInletController,
+        record_key_encryptor: Option<RecordKeyEncryptor>,
     ) -> InletInterceptorImpl {
         Self {
             request_map: Arc::new(Mutex::new(Default::default())),
             uuid_to_name,
             secure_channel_controller,
             inlet_map,
+            record_key_encryptor,
         }
     }
 }
@@ -0,0 +1,213 @@
+use bytes::{Bytes, BytesMut};
+use kafka_protocol::records::{
+    Compression, Record, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions,
+};
+use lz4::{BlockMode, BlockSize, Decoder, EncoderBuilder};
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::kafka::portal_worker::InterceptError;
+
+//layout of the v2 record batch header, see https://kafka.apache.org/documentation/#recordbatch
+const BATCH_LENGTH_OFFSET: usize = 8;
+const MAGIC_OFFSET: usize = 16;
+const CRC_OFFSET: usize = 17;
+const ATTRIBUTES_OFFSET: usize = 21;
+pub(super) const RECORDS_OFFSET: usize = 61;
+const LOG_OVERHEAD: usize = 12;
+const COMPRESSION_MASK: i16 = 0x7;
+
+/// The records of a record batch, with the compression codec used by the client
+/// so that it can be used again once the records have been modified
+pub(super) struct DecodedRecordBatch {
+    pub(super) records: Vec<Record>,
+    pub(super) compression: Compression,
+}
+
+/// Decode the records of one or more record batches, keeping the codec of each batch
+///
+/// `kafka_protocol` only supports gzip and snappy, lz4 and zstd batches are decompressed
+/// beforehand
+pub(super) fn decode_record_batches(
+    content: &[u8],
+) -> Result<Vec<DecodedRecordBatch>, InterceptError> {
+    let mut batches = vec![];
+    for batch in split_batches(content)? {
+        let compression = batch_compression(batch)?;
+        let mut batch = match compression {
+            Compression::Lz4 | Compression::Zstd => transcode_batch(batch, None)?,
+            _ => BytesMut::from(batch),
+        };
+        let records = RecordBatchDecoder::decode(&mut batch).map_err(|_| invalid_data())?;
+        batches.push(DecodedRecordBatch {
+            records,
+            compression,
+        });
+    }
+    Ok(batches)
+}
+
+/// Encode record batches, each one compressed with its own codec
+pub(super) fn encode_record_batches(
+    batches: &[DecodedRecordBatch],
+) -> Result<Bytes, InterceptError> {
+    let mut encoded = BytesMut::new();
+    for batch in batches {
+        encoded.extend_from_slice(&encode_record_batch(&batch.records, batch.compression)?);
+    }
+    Ok(encoded.freeze())
+}
+
+/// Encode records in a v2 record batch compressed with the given codec
+pub(super) fn encode_record_batch(
+    records: &[Record],
+    compression: Compression,
+) -> Result<Bytes, InterceptError> {
+    let native_compression = match compression {
+        Compression::Lz4 | Compression::Zstd => Compression::None,
+        compression => compression,
+    };
+
+    let mut encoded = BytesMut::new();
+    RecordBatchEncoder::encode(
+        &mut encoded,
+        records.iter(),
+        &RecordEncodeOptions {
+            version: 2,
+            compression: native_compression,
+        },
+    )
+    .map_err(|_| invalid_data())?;
+
+    if native_compression != compression {
+        encoded = transcode_batch(&encoded, Some(compression))?;
+    }
+    Ok(encoded.freeze())
+}
+
+/// Return the compression codec of a record batch, legacy message sets are
+/// always re-encoded without compression
+pub(super) fn batch_compression(content: &[u8]) -> Result<Compression, InterceptError> {
+    if content.len() < RECORDS_OFFSET || content[MAGIC_OFFSET] != 2 {
+        return Ok(Compression::None);
+    }
+
+    match attributes(content) & COMPRESSION_MASK {
+        0 => Ok(Compression::None),
+        1 => Ok(Compression::Gzip),
+        2 => Ok(Compression::Snappy),
+        3 => Ok(Compression::Lz4),
+        4 => Ok(Compression::Zstd),
+        other => {
+            warn!("unknown kafka compression codec: {other}");
+            Err(invalid_data())
+        }
+    }
+}
+
+/// Split some content into record batches, or legacy message sets.
+/// A partial batch at the end of a fetch response is dropped, as the client would do
+fn split_batches(content: &[u8]) -> Result<Vec<&[u8]>, InterceptError> {
+    let mut batches = vec![];
+    let mut remaining = content;
+
+    while !remaining.is_empty() {
+        if remaining.len() < LOG_OVERHEAD {
+            trace!("dropping a partial record batch");
+            break;
+        }
+        let batch_length = i32::from_be_bytes(
+            remaining[BATCH_LENGTH_OFFSET..LOG_OVERHEAD]
+                .try_into()
+                .map_err(|_| invalid_data())?,
+        );
+        let batch_end = usize::try_from(batch_length)
+            .ok()
+            .and_then(|length| length.checked_add(LOG_OVERHEAD))
+            .ok_or_else(invalid_data)?;
+        if batch_end > remaining.len() {
+            trace!("dropping a partial record batch");
+            break;
+        }
+        let (batch, rest) = remaining.split_at(batch_end);
+        batches.push(batch);
+        remaining = rest;
+    }
+
+    Ok(batches)
+}
+
+/// Rewrite a lz4 or zstd v2 record batch: the records are decompressed when no
+/// compression is given, otherwise the uncompressed records are compressed with it.
+/// Other batches are copied as they are
+fn transcode_batch(
+    batch: &[u8],
+    compression: Option<Compression>,
+) -> Result<BytesMut, InterceptError> {
+    if batch.len() < RECORDS_OFFSET || batch[MAGIC_OFFSET] != 2 {
+        return Ok(BytesMut::from(batch));
+    }
+
+    let attributes = attributes(batch);
+    let (records, attributes) = match (compression, attributes & COMPRESSION_MASK) {
+        (None, 3) => (
+            lz4_decompress(&batch[RECORDS_OFFSET..])?,
+            attributes & !COMPRESSION_MASK,
+        ),
+        (None, 4) => (
+            zstd::decode_all(&batch[RECORDS_OFFSET..]).map_err(InterceptError::Io)?,
+            attributes & !COMPRESSION_MASK,
+        ),
+        (Some(Compression::Lz4), 0) => (
+            lz4_compress(&batch[RECORDS_OFFSET..])?,
+            attributes | Compression::Lz4 as i16,
+        ),
+        (Some(Compression::Zstd), 0) => (
+            zstd::encode_all(&batch[RECORDS_OFFSET..], 0).map_err(InterceptError::Io)?,
+            attributes | Compression::Zstd as i16,
+        ),
+        _ => return Ok(BytesMut::from(batch)),
+    };
+
+    let batch_length =
+        i32::try_from(RECORDS_OFFSET - LOG_OVERHEAD + records.len()).map_err(|_| invalid_data())?;
+    let mut transcoded = BytesMut::with_capacity(RECORDS_OFFSET + records.len());
+    transcoded.extend_from_slice(&batch[..RECORDS_OFFSET]);
+    transcoded.extend_from_slice(&records);
+
+    transcoded[BATCH_LENGTH_OFFSET..LOG_OVERHEAD].copy_from_slice(&batch_length.to_be_bytes());
+    transcoded[ATTRIBUTES_OFFSET..ATTRIBUTES_OFFSET + 2].copy_from_slice(&attributes.to_be_bytes());
+    let crc = crc32c::crc32c(&transcoded[ATTRIBUTES_OFFSET..]);
+    transcoded[CRC_OFFSET..ATTRIBUTES_OFFSET].copy_from_slice(&crc.to_be_bytes());
+
+    Ok(transcoded)
+}
+
+fn attributes(batch: &[u8]) -> i16 {
+    i16::from_be_bytes([batch[ATTRIBUTES_OFFSET], batch[ATTRIBUTES_OFFSET + 1]])
+}
+
+fn lz4_compress(content: &[u8]) -> Result<Vec<u8>, InterceptError> {
+    //kafka only supports lz4 frames with independent blocks
+    let mut encoder = EncoderBuilder::new()
+        .block_mode(BlockMode::Independent)
+        .block_size(BlockSize::Max64KB)
+        .build(Vec::new())
+        .map_err(InterceptError::Io)?;
+    encoder.write_all(content).map_err(InterceptError::Io)?;
+    let (compressed, result) = encoder.finish();
+    result.map_err(InterceptError::Io)?;
+    Ok(compressed)
+}
+
+fn lz4_decompress(content: &[u8]) -> Result<Vec<u8>, InterceptError> {
+    let mut decompressed = Vec::new();
+    Decoder::new(content)
+        .map_err(InterceptError::Io)?
+        .read_to_end(&mut decompressed)
+        .map_err(InterceptError::Io)?;
+    Ok(decompressed)
+}
+
+fn invalid_data() -> InterceptError {
+    InterceptError::Io(Error::from(ErrorKind::InvalidData))
+}
@@ -4,10 +4,7 @@ use kafka_protocol::messages::produce_request::ProduceRequest;
 use kafka_protocol::messages::request_header::RequestHeader;
 use kafka_protocol::messages::ApiKey;
 use kafka_protocol::protocol::buf::ByteBuf;
-use kafka_protocol::protocol::Decodable;
-use kafka_protocol::records::{
-    Compression, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions,
-};
+use kafka_protocol::protocol::{Decodable, StrBytes};
 use minicbor::encode::Encoder;
 use ockam_node::Context;
 use std::convert::TryFrom;
@@ -15,8 +12,12 @@ use std::io::{Error, ErrorKind};
 use tracing::warn;
 
 use crate::kafka::portal_worker::InterceptError;
+use crate::kafka::protocol_aware::record_batch::{decode_record_batches, encode_record_batches};
 use crate::kafka::protocol_aware::utils::{decode_body, encode_request};
-use crate::kafka::protocol_aware::{InletInterceptorImpl, MessageWrapper, RequestInfo};
+use crate::kafka::protocol_aware::{
+    InletInterceptorImpl, MessageWrapper, RecordHeader, RecordHeaders, RequestInfo,
+    ENCRYPTED_HEADERS_KEY,
+};
 
 impl InletInterceptorImpl {
     ///Parse request and map request <=> response
@@ -164,53 +165,68 @@ impl InletInterceptorImpl {
         for (topic_name, topic) in request.topic_data.iter_mut() {
             for data in &mut topic.partition_data {
                 if let Some(content) = data.records.take() {
-                    let mut content = BytesMut::from(content.as_ref());
-                    let mut records = RecordBatchDecoder::decode(&mut content)
-                        .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+                    //the records are encoded again with the codec chosen by the client
+                    //for each batch
+                    let mut batches = decode_record_batches(&content)?;
 
-                    for record in records.iter_mut() {
+                    for record in batches
+                        .iter_mut()
+                        .flat_map(|batch| batch.records.iter_mut())
+                    {
                         if let Some(record_value) = record.value.take() {
-                            let encrypted_content = self
-                                .secure_channel_controller
-                                .encrypt_content_for(
+                            let wrapped_content = self
+                                .encrypt_and_wrap(
                                     context,
                                     topic_name,
                                     data.index,
                                     record_value.to_vec(),
                                 )
-                                .await
-                                .map_err(InterceptError::Ockam)?;
-
-                            //TODO: to target multiple consumers we could duplicate
-                            // the content with a dedicated encryption for each consumer
-                            let wrapper = MessageWrapper {
-                                consumer_decryptor_address: encrypted_content
-                                    .consumer_decryptor_address,
-                                content: encrypted_content.content,
-                            };
-
-                            let mut write_buffer = Vec::with_capacity(1024);
-                            let mut encoder = Encoder::new(&mut write_buffer);
-                            encoder.encode(wrapper).map_err(|_err| {
-                                InterceptError::Io(Error::from(ErrorKind::InvalidData))
-                            })?;
-
-                            record.value = Some(write_buffer.into());
+                                .await?;
+                            record.value = Some(wrapped_content.into());
+                        }
+
+                        //when enabled, the keys are encrypted deterministically so the
+                        //broker can still use them for partitioning, and the headers
+                        //are encrypted like the value within a single header
+                        if let Some(record_key_encryptor) = &self.record_key_encryptor {
+                            if let Some(record_key) = record.key.take() {
+                                let encrypted_key = record_key_encryptor
+                                    .encrypt(&record_key)
+                                    .map_err(InterceptError::Ockam)?;
+                                record.key = Some(encrypted_key.into());
+                            }
+
+                            if !record.headers.is_empty() {
+                                let headers = RecordHeaders {
+                                    headers: record
+                                        .headers
+                                        .drain(..)
+                                        .map(|(key, value)| RecordHeader {
+                                            key: key.to_string(),
+                                            value: value.map(|value| value.to_vec()),
+                                        })
+                                        .collect(),
+                                };
+                                let encoded_headers = minicbor::to_vec(headers).map_err(|_| {
+                                    InterceptError::Io(Error::from(ErrorKind::InvalidData))
+                                })?;
+                                let wrapped_headers = self
+                                    .encrypt_and_wrap(
+                                        context,
+                                        topic_name,
+                                        data.index,
+                                        encoded_headers,
+                                    )
+                                    .await?;
+                                record.headers.insert(
+                                    StrBytes::from_str(ENCRYPTED_HEADERS_KEY),
+                                    Some(wrapped_headers.into()),
+                                );
+                            }
                         }
                     }
 
-                    let mut encoded = BytesMut::new();
-                    RecordBatchEncoder::encode(
-                        &mut encoded,
-                        records.iter(),
-                        &RecordEncodeOptions {
-                            version: 2,
-                            compression: Compression::None,
-                        },
-                    )
-                    .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
-
-                    data.records = Some(encoded.freeze());
+                    data.records = Some(encode_record_batches(&batches)?);
                 }
             }
         }
@@ -222,4 +238,36 @@ impl InletInterceptorImpl {
             ApiKey::ProduceKey,
         )
     }
+
+    /// Encrypt some content for the consumer of a topic partition and wrap it together
+    /// with the address of the consumer decryptor
+    async fn encrypt_and_wrap(
+        &self,
+        context: &mut Context,
+        topic_name: &str,
+        partition_id: i32,
+        content: Vec<u8>,
+    ) -> Result<Vec<u8>, InterceptError> {
+        let encrypted_content = self
+            .secure_channel_controller
+            .encrypt_content_for(context, topic_name, partition_id, content)
+            .await
+            .map_err(InterceptError::Ockam)?;
+
+        //when data keys are used, the content can be decrypted by every authorized
+        //consumer, otherwise only by the consumer at the end of the secure channel
+        let wrapper = MessageWrapper {
+            consumer_decryptor_address: encrypted_content.consumer_decryptor_address,
+            content: encrypted_content.content,
+            data_key: encrypted_content.data_key,
+        };
+
+        let mut write_buffer = Vec::with_capacity(1024);
+        let mut encoder = Encoder::new(&mut write_buffer);
+        encoder
+            .encode(wrapper)
+            .map_err(|_err| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+        Ok(write_buffer)
+    }
 }
@@ -9,17 +9,17 @@ use kafka_protocol::messages::response_header::ResponseHeader;
 use kafka_protocol::messages::ApiKey;
 use kafka_protocol::protocol::buf::ByteBuf;
 use kafka_protocol::protocol::Decodable;
-use kafka_protocol::records::{
-    Compression, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions,
-};
 use minicbor::decode::Decoder;
 use ockam_node::Context;
 use tracing::{trace, warn};
 
 use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::portal_worker::InterceptError;
+use crate::kafka::protocol_aware::record_batch::{decode_record_batches, encode_record_batches};
 use crate::kafka::protocol_aware::utils::{decode_body, encode_response, string_to_str_bytes};
-use crate::kafka::protocol_aware::{InletInterceptorImpl, MessageWrapper, RequestInfo};
+use crate::kafka::protocol_aware::{
+    InletInterceptorImpl, MessageWrapper, RecordHeaders, RequestInfo, ENCRYPTED_HEADERS_KEY,
+};
 
 impl InletInterceptorImpl {
     pub(crate) async fn intercept_response_impl(
@@ -224,42 +224,61 @@ impl InletInterceptorImpl {
         for response in response.responses.iter_mut() {
             for partition in response.partitions.iter_mut() {
                 if let Some(content) = partition.records.take() {
-                    let mut content = BytesMut::from(content.as_ref());
-                    let mut records = RecordBatchDecoder::decode(&mut content)
-                        .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+                    //the records are encoded again with the codec chosen by the producer
+                    //for each batch
+                    let mut batches = decode_record_batches(&content)?;
 
-                    for record in records.iter_mut() {
+                    for record in batches
+                        .iter_mut()
+                        .flat_map(|batch| batch.records.iter_mut())
+                    {
                         if let Some(record_value) = record.value.take() {
-                            let message_wrapper: MessageWrapper =
-                                Decoder::new(record_value.as_ref()).decode().map_err(|_| {
-                                    InterceptError::Io(Error::from(ErrorKind::InvalidData))
-                                })?;
-
-                            let decrypted_content = self
-                                .secure_channel_controller
-                                .decrypt_content_for(
-                                    context,
-                                    &message_wrapper.consumer_decryptor_address,
-                                    message_wrapper.content,
-                                )
-                                .await
-                                .map_err(InterceptError::Ockam)?;
-
+                            let decrypted_content =
+                                self.unwrap_and_decrypt(context, &record_value).await?;
                             record.value = Some(decrypted_content.into());
                         }
+
+                        //keys and headers are only protected when a record key secret is
+                        //set, records which were not protected by a producer sharing the
+                        //same secret are left untouched
+                        if let Some(record_key_encryptor) = &self.record_key_encryptor {
+                            if let Some(record_key) = record.key.take() {
+                                record.key = match record_key_encryptor.decrypt(&record_key) {
+                                    Ok(decrypted_key) => Some(decrypted_key.into()),
+                                    Err(e) => {
+                                        warn!(
+                                            "Leaving a record key which cannot be decrypted: {e}"
+                                        );
+                                        Some(record_key)
+                                    }
+                                };
+                            }
+
+                            //the original headers are restored when they have been encrypted
+                            if let Some(Some(wrapped_headers)) =
+                                record.headers.get(ENCRYPTED_HEADERS_KEY).cloned()
+                            {
+                                match self.decrypt_headers(context, &wrapped_headers).await {
+                                    Ok(headers) => {
+                                        record.headers.swap_remove(ENCRYPTED_HEADERS_KEY);
+                                        for header in headers.headers {
+                                            record.headers.insert(
+                                                string_to_str_bytes(header.key),
+                                                header.value.map(|value| value.into()),
+                                            );
+                                        }
+                                    }
+                                    Err(e) => {
+                                        warn!(
+                                            "Leaving record headers which cannot be decrypted: {e:?}"
+                                        );
+                                    }
+                                }
+                            }
+                        }
                     }
 
-                    let mut encoded = BytesMut::new();
-                    RecordBatchEncoder::encode(
-                        &mut encoded,
-                        records.iter(),
-                        &RecordEncodeOptions {
-                            version: 2,
-                            compression: Compression::None,
-                        },
-                    )
-                    .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
-                    partition.records = Some(encoded.freeze());
+                    partition.records = Some(encode_record_batches(&batches)?);
                 }
             }
         }
@@ -271,4 +290,38 @@ impl InletInterceptorImpl {
             ApiKey::FetchKey,
         )
     }
+
+    /// Decrypt the headers of a record which were encrypted by the producer
+    /// within a single header
+    async fn decrypt_headers(
+        &self,
+        context: &mut Context,
+        wrapped_headers: &[u8],
+    ) -> Result<RecordHeaders, InterceptError> {
+        let decrypted_headers = self.unwrap_and_decrypt(context, wrapped_headers).await?;
+        minicbor::decode(&decrypted_headers)
+            .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))
+    }
+
+    /// Decode a content wrapped by the producer and decrypt it
+    /// using the relative secure channel
+    async fn unwrap_and_decrypt(
+        &self,
+        context: &mut Context,
+        wrapped_content: &[u8],
+    ) -> Result<Vec<u8>, InterceptError> {
+        let message_wrapper: MessageWrapper = Decoder::new(wrapped_content)
+            .decode()
+            .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+        self.secure_channel_controller
+            .decrypt_content_for(
+                context,
+                message_wrapper.consumer_decryptor_address.as_ref(),
+                message_wrapper.data_key.as_ref(),
+                message_wrapper.content,
+            )
+            .await
+            .map_err(InterceptError::Ockam)
+    }
 }
@@ -1,21 +1,35 @@
 #[cfg(test)]
 mod test {
+    use crate::kafka::data_keys::WrappedDataKey;
     use crate::kafka::inlet_controller::KafkaInletController;
+    use crate::kafka::protocol_aware::record_batch::{
+        batch_compression, decode_record_batches, encode_record_batch, encode_record_batches,
+        DecodedRecordBatch, RECORDS_OFFSET,
+    };
     use crate::kafka::protocol_aware::utils::{encode_request, encode_response};
     use crate::kafka::protocol_aware::InletInterceptorImpl;
     use crate::kafka::protocol_aware::KafkaMessageInterceptor;
+    use crate::kafka::protocol_aware::MessageWrapper;
     use crate::kafka::secure_channel_map::{KafkaEncryptedContent, KafkaSecureChannelController};
     use crate::port_range::PortRange;
+    use bytes::{Bytes, BytesMut};
+    use indexmap::IndexMap;
+    use kafka_protocol::messages::fetch_request::{FetchPartition, FetchTopic};
+    use kafka_protocol::messages::fetch_response::{FetchableTopicResponse, PartitionData};
+    use kafka_protocol::messages::produce_request::{PartitionProduceData, TopicProduceData};
     use kafka_protocol::messages::ApiKey;
     use kafka_protocol::messages::BrokerId;
     use kafka_protocol::messages::{ApiVersionsRequest, MetadataRequest, MetadataResponse};
     use kafka_protocol::messages::{ApiVersionsResponse, RequestHeader, ResponseHeader};
-    use kafka_protocol::protocol::{Builder, StrBytes};
+    use kafka_protocol::messages::{FetchRequest, FetchResponse, ProduceRequest, TopicName};
+    use kafka_protocol::protocol::{Builder, Decodable, StrBytes};
+    use kafka_protocol::records::{Compression, Record, TimestampType};
     use ockam_core::compat::sync::Arc;
     use ockam_core::route;
     use ockam_core::{async_trait, Address};
     use ockam_multiaddr::MultiAddr;
     use ockam_node::Context;
+    use std::io::Read;
 
     struct DummySecureChannelController;
 
@@ -30,14 +44,16 @@ mod test {
         ) -> ockam_core::Result<KafkaEncryptedContent> {
             Ok(KafkaEncryptedContent {
                 content,
-                consumer_decryptor_address: Address::from_string("arbitrary string"),
+                consumer_decryptor_address: Some(Address::from_string("arbitrary string")),
+                data_key: None,
             })
         }
 
         async fn decrypt_content_for(
             &self,
             _context: &mut Context,
-            _consumer_decryptor_address: &Address,
+            _consumer_decryptor_address: Option<&Address>,
+            _data_key: Option<&WrappedDataKey>,
             encrypted_content: Vec<u8>,
         ) -> ockam_core::Result<Vec<u8>> {
             Ok(encrypted_content)
@@ -70,6 +86,7 @@ mod test {
             Arc::new(DummySecureChannelController {}),
             Default::default(),
             inlet_map,
+            None,
         );
 
         let mut correlation_id = 0;
@@ -204,4 +221,349 @@ mod test {
 
         context.stop().await
     }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__gzip_record_batch__compression_preserved(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        produce_and_fetch_with_compression(context, &[Compression::Gzip]).await;
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__snappy_record_batch__compression_preserved(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        produce_and_fetch_with_compression(context, &[Compression::Snappy]).await;
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__lz4_record_batch__compression_preserved(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        produce_and_fetch_with_compression(context, &[Compression::Lz4]).await;
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__zstd_record_batch__compression_preserved(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        produce_and_fetch_with_compression(context, &[Compression::Zstd]).await;
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__uncompressed_record_batch__stays_uncompressed(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        produce_and_fetch_with_compression(context, &[Compression::None]).await;
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__record_batches_with_different_codecs__each_compression_preserved(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        produce_and_fetch_with_compression(
+            context,
+            &[
+                Compression::Lz4,
+                Compression::None,
+                Compression::Zstd,
+                Compression::Gzip,
+            ],
+        )
+        .await;
+        context.stop().await
+    }
+
+    /// The records of the lz4 and zstd fixtures, uncompressed: "hello world!" without a key,
+    /// then "hello again!" with the key "customer-1", 5 milliseconds later
+    const BATCH_RECORDS: &str = "24000000011868656c6c6f20776f726c64210038000a0214637573746f6d65722d311868656c6c6f20616761696e2100";
+
+    /// A lz4 batch framed like the Java client does: independent blocks of at most 64KB,
+    /// no block or content checksum.
+    ///
+    /// The records were compressed with the lz4 1.9.4 command line tool, using
+    /// `lz4 -B4 -BI --no-frame-crc -c`, and the batch header and its CRC-32C
+    /// were computed for the compressed records.
+    /// Checked by decompressing the records with `lz4 -d -c` and comparing them
+    /// to [`BATCH_RECORDS`]
+    const LZ4_BATCH: &str = "00000000000000000000006effffffff02d81116510003000000010000018bcfe568000000018bcfe56805ffffffffffffffffffffffffffff0000000204224d186040822e000000f31324000000011868656c6c6f20776f726c64210038000a0214637573746f6d65722d311d0070616761696e210000000000";
+
+    /// A zstd batch framed like a streaming compressor does: no content size, no checksum.
+    ///
+    /// The records were compressed with the zstd 1.5.7 command line tool, using
+    /// `zstd -3 --no-check --no-content-size -c`, and the batch header and its CRC-32C
+    /// were computed for the compressed records.
+    /// Checked by decompressing the records with `zstd -d -c` and comparing them
+    /// to [`BATCH_RECORDS`]
+    const ZSTD_BATCH: &str = "00000000000000000000006affffffff0289f89c720004000000010000018bcfe568000000018bcfe56805ffffffffffffffffffffffffffff0000000228b52ffd005881010024000000011868656c6c6f20776f726c64210038000a0214637573746f6d65722d311868656c6c6f20616761696e2100";
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn record_batch__lz4_and_zstd_batches_framed_like_clients__decoded() {
+        let records = hex::decode(BATCH_RECORDS).unwrap();
+        let lz4_batch = hex::decode(LZ4_BATCH).unwrap();
+        let zstd_batch = hex::decode(ZSTD_BATCH).unwrap();
+
+        // the compressed records of the fixtures are the expected records
+        let mut lz4_records = vec![];
+        lz4::Decoder::new(&lz4_batch[RECORDS_OFFSET..])
+            .unwrap()
+            .read_to_end(&mut lz4_records)
+            .unwrap();
+        assert_eq!(lz4_records, records);
+        assert_eq!(
+            zstd::decode_all(&zstd_batch[RECORDS_OFFSET..]).unwrap(),
+            records
+        );
+
+        let mut content = lz4_batch;
+        content.extend(zstd_batch);
+
+        let batches = decode_record_batches(&content).unwrap();
+        let compressions: Vec<Compression> = batches.iter().map(|b| b.compression).collect();
+        assert_eq!(compressions, vec![Compression::Lz4, Compression::Zstd]);
+
+        for batch in batches.iter() {
+            assert_eq!(batch.records.len(), 2);
+            assert_eq!(batch.records[0].key, None);
+            assert_eq!(
+                batch.records[0].value.as_deref(),
+                Some("hello world!".as_bytes())
+            );
+            assert_eq!(
+                batch.records[1].key.as_deref(),
+                Some("customer-1".as_bytes())
+            );
+            assert_eq!(
+                batch.records[1].value.as_deref(),
+                Some("hello again!".as_bytes())
+            );
+            assert_eq!(batch.records[1].offset, 1);
+            assert_eq!(batch.records[1].timestamp, 1_700_000_000_005);
+        }
+
+        // the batches are encoded again with their own codec, and can be decoded back
+        let encoded = encode_record_batches(&batches).unwrap();
+        let decoded = decode_record_batches(&encoded).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].compression, Compression::Lz4);
+        assert_eq!(decoded[1].compression, Compression::Zstd);
+        for (decoded_batch, batch) in decoded.iter().zip(batches.iter()) {
+            let values = |batch: &DecodedRecordBatch| -> Vec<Option<Bytes>> {
+                batch.records.iter().map(|r| r.value.clone()).collect()
+            };
+            assert_eq!(values(decoded_batch), values(batch));
+        }
+    }
+
+    const TEST_API_VERSION: i16 = 12;
+    const TEST_TOPIC_NAME: &str = "my-topic-name";
+    const TEST_VALUE: &str = "hello world!";
+
+    /// Send a produce request and a fetch response through the interceptor, with one record
+    /// batch compressed with each given codec, and check that the codecs are kept on both sides
+    async fn produce_and_fetch_with_compression(
+        context: &mut Context,
+        compressions: &[Compression],
+    ) {
+        let inlet_map = KafkaInletController::new(
+            MultiAddr::default(),
+            route![],
+            route![],
+            [127, 0, 0, 1].into(),
+            PortRange::new(0, 0).unwrap(),
+        );
+
+        let interceptor = InletInterceptorImpl::new(
+            Arc::new(DummySecureChannelController {}),
+            Default::default(),
+            inlet_map,
+            None,
+        );
+
+        // a large and repetitive value, so that the compression is effective
+        let values: Vec<String> = (0..100).map(|n| format!("{TEST_VALUE} {n}")).collect();
+        let records: Vec<Record> = values.iter().map(|value| create_record(value)).collect();
+        let mut produced = BytesMut::new();
+        for compression in compressions {
+            let batch = encode_record_batch(&records, *compression).unwrap();
+            assert_eq!(batch_compression(&batch).unwrap(), *compression);
+            produced.extend_from_slice(&batch);
+        }
+        let produced = produced.freeze();
+
+        let mut topic_data = IndexMap::new();
+        topic_data.insert(
+            TopicName::from(StrBytes::from_str(TEST_TOPIC_NAME)),
+            TopicProduceData::builder()
+                .partition_data(vec![PartitionProduceData::builder()
+                    .index(1)
+                    .records(Some(produced))
+                    .unknown_tagged_fields(Default::default())
+                    .build()
+                    .unwrap()])
+                .unknown_tagged_fields(Default::default())
+                .build()
+                .unwrap(),
+        );
+
+        let mut produce_request = interceptor
+            .intercept_request(
+                context,
+                encode_request(
+                    &request_header(1, ApiKey::ProduceKey),
+                    &ProduceRequest::builder()
+                        .transactional_id(None)
+                        .acks(0)
+                        .timeout_ms(0)
+                        .topic_data(topic_data)
+                        .unknown_tagged_fields(Default::default())
+                        .build()
+                        .unwrap(),
+                    TEST_API_VERSION,
+                    ApiKey::ProduceKey,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        RequestHeader::decode(
+            &mut produce_request,
+            ApiKey::ProduceKey.request_header_version(TEST_API_VERSION),
+        )
+        .unwrap();
+        let produce_request =
+            ProduceRequest::decode(&mut produce_request, TEST_API_VERSION).unwrap();
+        let encrypted = produce_request.topic_data[0].partition_data[0]
+            .records
+            .clone()
+            .unwrap();
+
+        let encrypted_batches = decode_record_batches(&encrypted).unwrap();
+        assert_eq!(batch_compressions(&encrypted_batches), compressions);
+        for batch in encrypted_batches.iter() {
+            assert_eq!(batch.records.len(), values.len());
+            for (record, value) in batch.records.iter().zip(values.iter()) {
+                let wrapper: MessageWrapper =
+                    minicbor::decode(record.value.as_ref().unwrap()).unwrap();
+                assert_eq!(wrapper.content, value.as_bytes());
+            }
+        }
+
+        // the fetch request is needed to map the fetch response
+        let mut fetch_partition = FetchPartition::default();
+        fetch_partition.partition = 1;
+        let mut fetch_topic = FetchTopic::default();
+        fetch_topic.topic = TopicName::from(StrBytes::from_str(TEST_TOPIC_NAME));
+        fetch_topic.partitions = vec![fetch_partition];
+        let mut fetch_request = FetchRequest::default();
+        fetch_request.topics = vec![fetch_topic];
+        interceptor
+            .intercept_request(
+                context,
+                encode_request(
+                    &request_header(2, ApiKey::FetchKey),
+                    &fetch_request,
+                    TEST_API_VERSION,
+                    ApiKey::FetchKey,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let mut partition_data = PartitionData::default();
+        partition_data.partition_index = 1;
+        partition_data.records = Some(encrypted);
+        let mut topic_response = FetchableTopicResponse::default();
+        topic_response.topic = TopicName::from(StrBytes::from_str(TEST_TOPIC_NAME));
+        topic_response.partitions = vec![partition_data];
+        let mut fetch_response = FetchResponse::default();
+        fetch_response.responses = vec![topic_response];
+
+        let mut fetch_response = interceptor
+            .intercept_response(
+                context,
+                encode_response(
+                    &ResponseHeader::builder()
+                        .correlation_id(2)
+                        .unknown_tagged_fields(Default::default())
+                        .build()
+                        .unwrap(),
+                    &fetch_response,
+                    TEST_API_VERSION,
+                    ApiKey::FetchKey,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        ResponseHeader::decode(
+            &mut fetch_response,
+            ApiKey::FetchKey.response_header_version(TEST_API_VERSION),
+        )
+        .unwrap();
+        let fetch_response = FetchResponse::decode(&mut fetch_response, TEST_API_VERSION).unwrap();
+        let decrypted = fetch_response.responses[0].partitions[0]
+            .records
+            .clone()
+            .unwrap();
+
+        let decrypted_batches = decode_record_batches(&decrypted).unwrap();
+        assert_eq!(batch_compressions(&decrypted_batches), compressions);
+        let expected_values: Vec<&[u8]> = values.iter().map(|value| value.as_bytes()).collect();
+        for batch in decrypted_batches.iter() {
+            let decrypted_values: Vec<&[u8]> = batch
+                .records
+                .iter()
+                .map(|record| record.value.as_ref().unwrap().as_ref())
+                .collect();
+            assert_eq!(decrypted_values, expected_values);
+        }
+    }
+
+    fn batch_compressions(batches: &[DecodedRecordBatch]) -> Vec<Compression> {
+        batches.iter().map(|batch| batch.compression).collect()
+    }
+
+    fn request_header(correlation_id: i32, api_key: ApiKey) -> RequestHeader {
+        RequestHeader::builder()
+            .request_api_version(TEST_API_VERSION)
+            .correlation_id(correlation_id)
+            .request_api_key(api_key as i16)
+            .unknown_tagged_fields(Default::default())
+            .client_id(None)
+            .build()
+            .unwrap()
+    }
+
+    fn create_record(value: &str) -> Record {
+        Record {
+            transactional: false,
+            control: false,
+            partition_leader_epoch: 0,
+            producer_id: 0,
+            producer_epoch: 0,
+            timestamp_type: TimestampType::Creation,
+            offset: 0,
+            sequence: 0,
+            timestamp: 0,
+            key: None,
+            value: Some(Bytes::from(value.to_string())),
+            headers: Default::default(),
+        }
+    }
 }
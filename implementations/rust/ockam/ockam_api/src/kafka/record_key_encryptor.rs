@@ -0,0 +1,139 @@
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use ockam_core::compat::vec::Vec;
+use sha2::Sha256;
+use std::path::Path;
+
+use crate::error::ApiError;
+
+const NONCE_LENGTH: usize = 12;
+const ENCRYPTION_KEY_INFO: &[u8] = b"ockam kafka record key encryption";
+const NONCE_KEY_INFO: &[u8] = b"ockam kafka record key nonce";
+
+/// Length of the secret shared by the kafka producers and consumers to protect record keys
+pub const RECORD_KEY_SECRET_LENGTH: usize = 32;
+
+/// Deterministic encryption of the kafka record keys.
+///
+/// The same record key is always encrypted to the same bytes, so that the broker can still
+/// use the encrypted key to select a partition. The nonce is derived from the record key with
+/// an HMAC (a synthetic IV), which only reveals to the broker whether two keys are equal.
+#[derive(Clone)]
+pub(crate) struct RecordKeyEncryptor {
+    encryption_key: [u8; 32],
+    nonce_key: [u8; 32],
+}
+
+impl RecordKeyEncryptor {
+    /// Derive the encryption keys from a secret shared by all the producers and consumers
+    pub(crate) fn new(secret: &[u8]) -> ockam_core::Result<Self> {
+        if secret.len() != RECORD_KEY_SECRET_LENGTH {
+            return Err(ApiError::core(format!(
+                "the record key secret must be {RECORD_KEY_SECRET_LENGTH} bytes long"
+            )));
+        }
+
+        let hkdf = Hkdf::<Sha256>::new(None, secret);
+        let mut encryption_key = [0u8; 32];
+        let mut nonce_key = [0u8; 32];
+        hkdf.expand(ENCRYPTION_KEY_INFO, &mut encryption_key)
+            .map_err(|_| ApiError::core("cannot derive the record key encryption key"))?;
+        hkdf.expand(NONCE_KEY_INFO, &mut nonce_key)
+            .map_err(|_| ApiError::core("cannot derive the record key nonce key"))?;
+
+        Ok(Self {
+            encryption_key,
+            nonce_key,
+        })
+    }
+
+    /// Derive the encryption keys from a hex-encoded secret stored in a file
+    pub(crate) fn from_file(path: &Path) -> ockam_core::Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ApiError::core(format!(
+                "cannot read the record key secret file {}: {e}",
+                path.display()
+            ))
+        })?;
+        let secret = hex::decode(contents.trim())
+            .map_err(|_| ApiError::core("the record key secret must be hex-encoded"))?;
+        Self::new(&secret)
+    }
+
+    /// Encrypt a record key, the result is the nonce followed by the ciphertext
+    pub(crate) fn encrypt(&self, key: &[u8]) -> ockam_core::Result<Vec<u8>> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.nonce_key)
+            .map_err(|_| ApiError::core("invalid record key nonce key"))?;
+        mac.update(key);
+        let nonce = mac.finalize().into_bytes();
+        let nonce = &nonce[..NONCE_LENGTH];
+
+        let ciphertext = self
+            .cipher()
+            .encrypt(Nonce::from_slice(nonce), key)
+            .map_err(|_| ApiError::core("cannot encrypt the record key"))?;
+
+        let mut encrypted = Vec::with_capacity(NONCE_LENGTH + ciphertext.len());
+        encrypted.extend_from_slice(nonce);
+        encrypted.extend_from_slice(&ciphertext);
+        Ok(encrypted)
+    }
+
+    /// Decrypt a record key encrypted with [`RecordKeyEncryptor::encrypt`]
+    pub(crate) fn decrypt(&self, encrypted: &[u8]) -> ockam_core::Result<Vec<u8>> {
+        if encrypted.len() < NONCE_LENGTH {
+            return Err(ApiError::core("the encrypted record key is too short"));
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+        self.cipher()
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| ApiError::core("cannot decrypt the record key"))
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::from_slice(&self.encryption_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_keys_are_encrypted_deterministically() {
+        let encryptor = RecordKeyEncryptor::new(&[1; RECORD_KEY_SECRET_LENGTH]).unwrap();
+
+        let first = encryptor.encrypt(b"customer-1").unwrap();
+        assert_eq!(first, encryptor.encrypt(b"customer-1").unwrap());
+        assert_ne!(first, encryptor.encrypt(b"customer-2").unwrap());
+        assert_eq!(encryptor.decrypt(&first).unwrap(), b"customer-1");
+
+        // a different secret cannot decrypt the key
+        let other = RecordKeyEncryptor::new(&[2; RECORD_KEY_SECRET_LENGTH]).unwrap();
+        assert_ne!(first, other.encrypt(b"customer-1").unwrap());
+        assert!(other.decrypt(&first).is_err());
+
+        assert!(RecordKeyEncryptor::new(&[1; 16]).is_err());
+    }
+
+    #[test]
+    fn record_key_secret_is_read_from_a_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            format!("{}\n", "01".repeat(RECORD_KEY_SECRET_LENGTH)),
+        )
+        .unwrap();
+        let encryptor = RecordKeyEncryptor::from_file(file.path()).unwrap();
+        let expected = RecordKeyEncryptor::new(&[1; RECORD_KEY_SECRET_LENGTH]).unwrap();
+        assert_eq!(
+            encryptor.encrypt(b"customer-1").unwrap(),
+            expected.encrypt(b"customer-1").unwrap()
+        );
+
+        std::fs::write(file.path(), "not hex").unwrap();
+        assert!(RecordKeyEncryptor::from_file(file.path()).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::kafka::data_keys::{KafkaDataKeys, RegisteredDataKeys, WrappedDataKey};
 use crate::kafka::KAFKA_OUTLET_CONSUMERS;
 use crate::nodes::models::relay::{CreateRelay, RelayInfo};
 use crate::nodes::models::secure_channel::{
@@ -19,15 +20,19 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{async_trait, route, Address, Error, Result};
 use ockam_multiaddr::proto::{Project, Service};
 use ockam_multiaddr::{MultiAddr, Protocol};
+use ockam_node::api::Client;
 use ockam_node::compat::tokio::sync::Mutex;
 use ockam_node::compat::tokio::sync::MutexGuard;
-use ockam_node::Context;
+use ockam_node::{Context, DEFAULT_TIMEOUT};
 
 pub(crate) struct KafkaEncryptedContent {
     /// The encrypted content
     pub(crate) content: Vec<u8>,
-    /// The secure channel identifier used to encrypt the content
-    pub(crate) consumer_decryptor_address: Address,
+    /// The secure channel identifier used to encrypt the content,
+    /// when the content is encrypted for a single consumer
+    pub(crate) consumer_decryptor_address: Option<Address>,
+    /// The data key used to encrypt the content for all the authorized consumers
+    pub(crate) data_key: Option<WrappedDataKey>,
 }
 
 /// Offer simple APIs to encrypt and decrypt kafka messages.
@@ -55,10 +60,13 @@ pub(crate) trait KafkaSecureChannelController: Send + Sync {
 
     /// Decrypts the content based on the consumer decryptor address
     /// the secure channel is expected to be already initialized.
+    /// When a data key was used and it is not wrapped for this consumer,
+    /// the consumer registers with the producer to receive it.
     async fn decrypt_content_for(
         &self,
         context: &mut Context,
-        consumer_decryptor_address: &Address,
+        consumer_decryptor_address: Option<&Address>,
+        data_key: Option<&WrappedDataKey>,
         encrypted_content: Vec<u8>,
     ) -> Result<Vec<u8>>;
 
@@ -135,6 +143,7 @@ impl RelayCreator for NodeManagerRelayCreator {
 
 pub(crate) struct KafkaSecureChannelControllerImpl<F: RelayCreator> {
     inner: Arc<Mutex<InnerSecureChannelControllerImpl<F>>>,
+    data_keys: Option<Arc<KafkaDataKeys>>,
 }
 
 //had to manually implement since #[derive(Clone)] doesn't work well in this situation
@@ -142,6 +151,7 @@ impl<F: RelayCreator> Clone for KafkaSecureChannelControllerImpl<F> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            data_keys: self.data_keys.clone(),
         }
     }
 }
@@ -162,6 +172,9 @@ struct InnerSecureChannelControllerImpl<F: RelayCreator> {
     // describes how to reach the consumer node
     consumer_node_multiaddr: ConsumerNodeAddr,
     topic_relay_set: HashSet<TopicPartition>,
+    // secure channels to the producers data key services, by relay address
+    data_key_channels: HashMap<String, Address>,
+    data_keys_relay_created: bool,
     relay_creator: Option<F>,
     secure_channels: Arc<SecureChannels>,
     access_control: AbacAccessControl,
@@ -211,14 +224,24 @@ impl<F: RelayCreator> KafkaSecureChannelControllerImpl<F> {
             inner: Arc::new(Mutex::new(InnerSecureChannelControllerImpl {
                 topic_encryptor_map: Default::default(),
                 topic_relay_set: Default::default(),
+                data_key_channels: Default::default(),
+                data_keys_relay_created: false,
                 secure_channels,
                 relay_creator,
                 consumer_node_multiaddr,
                 access_control,
             })),
+            data_keys: None,
         }
     }
 
+    /// Encrypt the records with a data key per topic, wrapped for every authorized
+    /// consumer, instead of encrypting them for a single consumer
+    pub(crate) fn with_data_keys(mut self, data_keys: Arc<KafkaDataKeys>) -> Self {
+        self.data_keys = Some(data_keys);
+        self
+    }
+
     pub(crate) fn into_trait(self) -> Arc<dyn KafkaSecureChannelController> {
         Arc::new(self)
     }
@@ -462,6 +485,146 @@ impl<F: RelayCreator> KafkaSecureChannelControllerImpl<F> {
     }
 }
 
+impl<F: RelayCreator> KafkaSecureChannelControllerImpl<F> {
+    /// Encrypt the content with the data key of the topic, the first time
+    /// a relay is created so that the consumers can register with this producer
+    async fn encrypt_content_with_data_key(
+        &self,
+        context: &mut Context,
+        data_keys: &KafkaDataKeys,
+        topic_name: &str,
+        content: Vec<u8>,
+    ) -> Result<KafkaEncryptedContent> {
+        {
+            let mut inner = self.inner.lock().await;
+            if !inner.data_keys_relay_created {
+                let relay_creator = inner.relay_creator.as_ref().ok_or_else(|| {
+                    Error::new(
+                        Origin::Transport,
+                        Kind::Invalid,
+                        "data keys can only be used with a relay to the consumers",
+                    )
+                })?;
+                relay_creator
+                    .create_relay(context, data_keys.alias().to_string())
+                    .await?;
+                inner.data_keys_relay_created = true;
+            }
+        }
+
+        let (data_key, content) = data_keys.encrypt(topic_name, &content).await?;
+        trace!("encrypted content with data key {}", data_key.key_id);
+        Ok(KafkaEncryptedContent {
+            content,
+            consumer_decryptor_address: None,
+            data_key: Some(data_key),
+        })
+    }
+
+    /// Register this consumer with the producer data key service, using a secure channel
+    /// through the producer relay, and return the data keys retained for the topic
+    async fn register_with_producer(
+        &self,
+        context: &mut Context,
+        data_keys: &KafkaDataKeys,
+        data_key: &WrappedDataKey,
+    ) -> Result<Vec<WrappedDataKey>> {
+        let mut inner = self.inner.lock().await;
+
+        let encryptor_address = match inner.data_key_channels.get(&data_key.producer) {
+            Some(encryptor_address) => encryptor_address.clone(),
+            None => {
+                let mut destination = match inner.consumer_node_multiaddr.clone() {
+                    ConsumerNodeAddr::Relay(destination) => destination,
+                    ConsumerNodeAddr::Direct(_) => {
+                        return Err(Error::new(
+                            Origin::Transport,
+                            Kind::Invalid,
+                            "data keys can only be retrieved through a relay to the producer",
+                        ))
+                    }
+                };
+                //consumer__ prefix is added by the orchestrator
+                let producer_relay = format!("consumer__{}", data_key.producer);
+                debug!("creating new secure channel via relay to {producer_relay}");
+                destination.push_back(Service::new(producer_relay))?;
+                destination.push_back(Service::new(DefaultAddress::SECURE_CHANNEL_LISTENER))?;
+
+                let encryptor_address =
+                    Self::request_secure_channel_creation(context, destination).await?;
+
+                if let Err(error) =
+                    Self::validate_consumer_credentials(&inner, &encryptor_address).await
+                {
+                    Self::request_secure_channel_deletion(context, &encryptor_address).await?;
+                    return Err(error);
+                };
+
+                inner
+                    .data_key_channels
+                    .insert(data_key.producer.clone(), encryptor_address.clone());
+                encryptor_address
+            }
+        };
+
+        let client = Client::new(
+            &route![encryptor_address, data_key.producer.clone()],
+            Some(DEFAULT_TIMEOUT),
+        );
+        let response = client
+            .ask(
+                context,
+                Request::post(format!("/consumers/{}", data_key.topic_name))
+                    .body(data_keys.consumer_registration().await?),
+            )
+            .await;
+        // the producer relay doesn't change when the producer restarts,
+        // so the secure channel is created again for the next registration
+        if response.is_err() {
+            inner.data_key_channels.remove(&data_key.producer);
+        }
+        let response: RegisteredDataKeys = response?.success()?;
+        Ok(response.data_keys)
+    }
+
+    /// Decrypt the content with a data key, registering with the producer when
+    /// the data key is not wrapped for this consumer
+    async fn decrypt_content_with_data_key(
+        &self,
+        context: &mut Context,
+        data_keys: &KafkaDataKeys,
+        data_key: &WrappedDataKey,
+        encrypted_content: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        let key = match data_keys.unwrap(data_key).await? {
+            Some(key) => key,
+            None => {
+                debug!(
+                    "registering with the producer {} for the topic {}",
+                    data_key.producer, data_key.topic_name
+                );
+                for retained_key in self
+                    .register_with_producer(context, data_keys, data_key)
+                    .await?
+                {
+                    data_keys.unwrap(&retained_key).await?;
+                }
+                data_keys.unwrap(data_key).await?.ok_or_else(|| {
+                    Error::new(
+                        Origin::Transport,
+                        Kind::NotFound,
+                        format!(
+                            "the data key {} is not available anymore on the producer",
+                            data_key.key_id
+                        ),
+                    )
+                })?
+            }
+        };
+        KafkaDataKeys::decrypt(&key, &encrypted_content)
+    }
+}
+
 #[async_trait]
 impl<F: RelayCreator> KafkaSecureChannelController for KafkaSecureChannelControllerImpl<F> {
     async fn encrypt_content_for(
@@ -471,6 +634,12 @@ impl<F: RelayCreator> KafkaSecureChannelController for KafkaSecureChannelControl
         partition_id: i32,
         content: Vec<u8>,
     ) -> Result<KafkaEncryptedContent> {
+        if let Some(data_keys) = &self.data_keys {
+            return self
+                .encrypt_content_with_data_key(context, data_keys, topic_name, content)
+                .await;
+        }
+
         let secure_channel_entry = self
             .get_or_create_secure_channel_for(context, topic_name, partition_id)
             .await?;
@@ -496,16 +665,38 @@ impl<F: RelayCreator> KafkaSecureChannelController for KafkaSecureChannelControl
         trace!("encrypted content with {consumer_decryptor_address}");
         Ok(KafkaEncryptedContent {
             content: encrypted_content,
-            consumer_decryptor_address,
+            consumer_decryptor_address: Some(consumer_decryptor_address),
+            data_key: None,
         })
     }
 
     async fn decrypt_content_for(
         &self,
         context: &mut Context,
-        consumer_decryptor_address: &Address,
+        consumer_decryptor_address: Option<&Address>,
+        data_key: Option<&WrappedDataKey>,
         encrypted_content: Vec<u8>,
     ) -> Result<Vec<u8>> {
+        if let Some(data_key) = data_key {
+            let data_keys = self.data_keys.as_ref().ok_or_else(|| {
+                Error::new(
+                    Origin::Transport,
+                    Kind::Invalid,
+                    "the content was encrypted with a data key but data keys are not enabled",
+                )
+            })?;
+            return self
+                .decrypt_content_with_data_key(context, data_keys, data_key, encrypted_content)
+                .await;
+        }
+
+        let consumer_decryptor_address = consumer_decryptor_address.ok_or_else(|| {
+            Error::new(
+                Origin::Transport,
+                Kind::Invalid,
+                "the content was encrypted without a secure channel nor a data key",
+            )
+        })?;
         let secure_channel_entry = self
             .get_secure_channel_for(consumer_decryptor_address)
             .await?;
@@ -536,7 +727,8 @@ impl<F: RelayCreator> KafkaSecureChannelController for KafkaSecureChannelControl
     ) -> Result<()> {
         let mut inner = self.inner.lock().await;
         // when using direct mode there is no need to create a relay
-        if inner.relay_creator.is_none() {
+        // when using data keys the producers don't need to reach the consumers
+        if inner.relay_creator.is_none() || self.data_keys.is_some() {
             return Ok(());
         }
 
@@ -0,0 +1,110 @@
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+
+use ockam_core::Result;
+use ockam_node::metrics::Metrics;
+use tiny_http::{Header, Response, Server};
+
+use crate::error::ApiError;
+
+/// Path where the metrics are served
+pub const METRICS_PATH: &str = "/metrics";
+
+const OPEN_METRICS_CONTENT_TYPE: &str =
+    "Content-Type: application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// HTTP server exposing the metrics of a node on [`METRICS_PATH`]
+/// using the OpenMetrics text format.
+///
+/// The server is stopped when this value is dropped
+pub struct MetricsServer {
+    server: Arc<Server>,
+    socket_address: SocketAddr,
+}
+
+impl MetricsServer {
+    /// Start serving the metrics on the given address in a background thread
+    pub fn start(address: &str, metrics: Metrics) -> Result<MetricsServer> {
+        let server = Server::http(address).map_err(|e| {
+            ApiError::core(format!(
+                "failed to start the metrics server on {address}: {e}"
+            ))
+        })?;
+        let socket_address = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| ApiError::core("the metrics server must listen on an IP address"))?;
+        let server = Arc::new(server);
+        info!("serving metrics at http://{socket_address}{METRICS_PATH}");
+
+        let requests = server.clone();
+        thread::spawn(move || {
+            for request in requests.incoming_requests() {
+                let result = if request.url() == METRICS_PATH {
+                    let header = Header::from_str(OPEN_METRICS_CONTENT_TYPE)
+                        .expect("the content type header is valid");
+                    request.respond(Response::from_string(metrics.render()).with_header(header))
+                } else {
+                    request.respond(Response::empty(404))
+                };
+                if let Err(e) = result {
+                    warn!("failed to respond to a metrics request: {e}");
+                }
+            }
+        });
+
+        Ok(MetricsServer {
+            server,
+            socket_address,
+        })
+    }
+
+    /// Address of the metrics server
+    pub fn socket_address(&self) -> SocketAddr {
+        self.socket_address
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.server.unblock()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    #[test]
+    fn test_serve_metrics() -> Result<()> {
+        let metrics = Metrics::new();
+        metrics.record_message_routed(10);
+        let server = MetricsServer::start("127.0.0.1:0", metrics)?;
+
+        let response = get(server.socket_address(), METRICS_PATH);
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.contains("application/openmetrics-text"));
+        assert!(response.contains("ockam_messages_routed_total 1\n"));
+        assert!(response.ends_with("# EOF\n"));
+
+        let response = get(server.socket_address(), "/other");
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+        Ok(())
+    }
+
+    fn get(address: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+}
@@ -1,4 +1,5 @@
 mod plain_tcp;
+mod plain_transport;
 mod project;
 mod secure;
 
@@ -9,12 +10,13 @@ use ockam_core::{async_trait, route, Address, Route, LOCAL};
 use ockam_multiaddr::proto::Service;
 use ockam_multiaddr::{Match, MultiAddr, Protocol};
 use ockam_node::Context;
-use ockam_transport_tcp::{TcpConnection, TcpTransport};
+use ockam_transport_tcp::TcpConnection;
 
 use crate::error::ApiError;
 use crate::nodes::NodeManager;
-use crate::{multiaddr_to_route, DefaultAddress};
+use crate::{multiaddr_to_route, routed_transport_address, DefaultAddress};
 pub(crate) use plain_tcp::PlainTcpInstantiator;
+pub(crate) use plain_transport::PlainTransportInstantiator;
 pub(crate) use project::ProjectInstantiator;
 pub(crate) use secure::SecureChannelInstantiator;
 use std::fmt::{Debug, Formatter};
@@ -60,16 +62,20 @@ impl Connection {
         self.transport_route.clone()
     }
 
-    pub async fn route(&self, tcp_transport: &TcpTransport) -> Result<Route> {
-        multiaddr_to_route(&self.normalized_addr, tcp_transport)
-            .await
-            .map(|r| r.route)
-            .ok_or_else(|| {
-                ApiError::core(format!(
-                    "Couldn't convert MultiAddr to route: normalized_addr={}",
-                    self.normalized_addr
-                ))
-            })
+    pub async fn route(&self, node_manager: &NodeManager) -> Result<Route> {
+        multiaddr_to_route(
+            &self.normalized_addr,
+            node_manager.tcp_transport(),
+            &node_manager.transport_flow_controls(),
+        )
+        .await
+        .map(|r| r.route)
+        .ok_or_else(|| {
+            ApiError::core(format!(
+                "Couldn't convert MultiAddr to route: normalized_addr={}",
+                self.normalized_addr
+            ))
+        })
     }
 }
 
@@ -267,6 +273,10 @@ impl ConnectionBuilder {
                     }
                     route = route.append(address);
                 }
+            } else if let Some(address) = routed_transport_address(&protocol, &mut peekable) {
+                // the hops of the transports which route messages to their own addresses,
+                // like udp, ws or unix sockets, are kept in the multiaddr
+                route = route.append(address);
             }
         }
 
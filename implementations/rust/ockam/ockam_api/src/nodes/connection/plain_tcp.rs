@@ -5,7 +5,7 @@ use std::sync::Arc;
 
 use crate::nodes::NodeManager;
 use ockam_core::{async_trait, Error, Route};
-use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Tcp};
+use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Tcp, Ws};
 use ockam_multiaddr::{Match, MultiAddr, Protocol};
 use ockam_node::Context;
 
@@ -37,13 +37,27 @@ impl Instantiator for PlainTcpInstantiator {
     ) -> Result<Changes, Error> {
         let (before, tcp_piece, after) = extracted;
 
-        let mut tcp = multiaddr_to_route(&tcp_piece, &node_manager.tcp_transport)
-            .await
-            .ok_or_else(|| {
-                ApiError::core(format!(
-                    "Couldn't convert MultiAddr to route: tcp_piece={tcp_piece}"
-                ))
-            })?;
+        // a websocket connection is created by the websocket transport
+        if after.first().map(|p| p.code()) == Some(Ws::CODE) {
+            return Ok(Changes {
+                current_multiaddr: ConnectionBuilder::combine(before, tcp_piece, after)?,
+                flow_control_id: None,
+                secure_channel_encryptors: vec![],
+                tcp_connection: None,
+            });
+        }
+
+        let mut tcp = multiaddr_to_route(
+            &tcp_piece,
+            &node_manager.tcp_transport,
+            &node_manager.transport_flow_controls(),
+        )
+        .await
+        .ok_or_else(|| {
+            ApiError::core(format!(
+                "Couldn't convert MultiAddr to route: tcp_piece={tcp_piece}"
+            ))
+        })?;
 
         let multiaddr = route_to_multiaddr(&tcp.route).ok_or_else(|| {
             ApiError::core(format!(
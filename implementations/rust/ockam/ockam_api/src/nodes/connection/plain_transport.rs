@@ -0,0 +1,83 @@
+use crate::error::ApiError;
+use crate::multiaddr_to_route;
+use crate::nodes::connection::{Changes, ConnectionBuilder, Instantiator};
+use std::sync::Arc;
+
+use crate::nodes::NodeManager;
+use ockam_core::{async_trait, Error, Route};
+use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Tcp, Udp, Unix, Ws};
+use ockam_multiaddr::{Match, MultiAddr, Protocol};
+use ockam_node::Context;
+
+/// Sets up the flow control of a transport which routes messages to its own addresses:
+/// udp, ws or unix sockets. The transport connects when the first message is routed,
+/// so the transport hop is kept in the [`MultiAddr`].
+pub(crate) struct PlainTransportInstantiator {
+    matches: Vec<Match>,
+}
+
+impl PlainTransportInstantiator {
+    /// Matches any host followed by a udp protocol
+    pub(crate) fn udp() -> Self {
+        Self {
+            matches: vec![
+                Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE]),
+                Udp::CODE.into(),
+            ],
+        }
+    }
+
+    /// Matches any host followed by a tcp and a ws protocol
+    pub(crate) fn ws() -> Self {
+        Self {
+            matches: vec![
+                Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE]),
+                Tcp::CODE.into(),
+                Ws::CODE.into(),
+            ],
+        }
+    }
+
+    /// Matches a unix socket
+    pub(crate) fn unix() -> Self {
+        Self {
+            matches: vec![Unix::CODE.into()],
+        }
+    }
+}
+
+#[async_trait]
+impl Instantiator for PlainTransportInstantiator {
+    fn matches(&self) -> Vec<Match> {
+        self.matches.clone()
+    }
+
+    async fn instantiate(
+        &self,
+        _ctx: Arc<Context>,
+        node_manager: &NodeManager,
+        _transport_route: Route,
+        extracted: (MultiAddr, MultiAddr, MultiAddr),
+    ) -> Result<Changes, Error> {
+        let (before, transport_piece, after) = extracted;
+
+        let transport = multiaddr_to_route(
+            &transport_piece,
+            &node_manager.tcp_transport,
+            &node_manager.transport_flow_controls(),
+        )
+        .await
+        .ok_or_else(|| {
+            ApiError::core(format!(
+                "Couldn't convert MultiAddr to route: transport_piece={transport_piece}"
+            ))
+        })?;
+
+        Ok(Changes {
+            current_multiaddr: ConnectionBuilder::combine(before, transport_piece, after)?,
+            flow_control_id: transport.flow_control_id,
+            secure_channel_encryptors: vec![],
+            tcp_connection: None,
+        })
+    }
+}
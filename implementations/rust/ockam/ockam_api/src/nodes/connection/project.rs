@@ -61,13 +61,17 @@ impl Instantiator for ProjectInstantiator {
             node_manager.resolve_project(&project).await?;
 
         debug!(addr = %project_multiaddr, "creating secure channel");
-        let tcp = multiaddr_to_route(&project_multiaddr, &node_manager.tcp_transport)
-            .await
-            .ok_or_else(|| {
-                ApiError::core(format!(
-                    "Couldn't convert MultiAddr to route: project_multiaddr={project_multiaddr}"
-                ))
-            })?;
+        let tcp = multiaddr_to_route(
+            &project_multiaddr,
+            &node_manager.tcp_transport,
+            &node_manager.transport_flow_controls(),
+        )
+        .await
+        .ok_or_else(|| {
+            ApiError::core(format!(
+                "Couldn't convert MultiAddr to route: project_multiaddr={project_multiaddr}"
+            ))
+        })?;
 
         debug!("create a secure channel to the project {project_identifier}");
         let sc = node_manager
@@ -0,0 +1,59 @@
+//! Identity request/response types
+
+use minicbor::{Decode, Encode};
+use std::fmt::{self, Display};
+
+use ockam::identity::Identifier;
+
+/// Key of an identity which can be rotated
+#[derive(Copy, Clone, Debug, Decode, Encode, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum RotatedKey {
+    /// Primary key, creating a new change in the identity change history
+    #[n(0)] Primary,
+    /// Purpose key used to create secure channels
+    #[n(1)] SecureChannel,
+    /// Purpose key used to issue credentials
+    #[n(2)] Credential,
+}
+
+impl Display for RotatedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Primary => "primary key",
+            Self::SecureChannel => "secure channel purpose key",
+            Self::Credential => "credential purpose key",
+        })
+    }
+}
+
+/// Request body when instructing a node to rotate one of the keys of its identity
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RotateKeyRequest {
+    #[n(1)] pub key: RotatedKey,
+}
+
+impl RotateKeyRequest {
+    pub fn new(key: RotatedKey) -> Self {
+        Self { key }
+    }
+}
+
+/// Response body when a key of the node identity has been rotated
+#[derive(Clone, Debug, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RotateKeyResponse {
+    #[n(1)] pub identifier: Identifier,
+    #[n(2)] pub key: RotatedKey,
+    /// Hex-encoded change history of the identity after the rotation
+    #[n(3)] pub change_history: String,
+    /// True if the new change history was sent to the project authority
+    #[n(4)] pub pushed_to_authority: bool,
+    /// Error returned when the new change history could not be sent to the project authority.
+    /// The key is rotated on the node in that case
+    #[n(5)] pub authority_error: Option<String>,
+}
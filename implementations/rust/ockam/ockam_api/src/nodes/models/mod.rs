@@ -5,6 +5,7 @@
 pub mod base;
 pub mod credentials;
 pub mod flow_controls;
+pub mod identity;
 pub mod policy;
 pub mod portal;
 pub mod relay;
@@ -1,5 +1,6 @@
 //! Inlets and outlet request/response types
 
+use std::fmt::{self, Display};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::time::Duration;
 
@@ -8,6 +9,7 @@ use ockam::identity::Identifier;
 use ockam::route;
 use ockam_core::{Address, Route};
 use ockam_multiaddr::MultiAddr;
+use ockam_transport_tcp::{TcpOutletStrategy, TcpOutletTargetStatus};
 use serde::{Deserialize, Serialize};
 
 use crate::error::ApiError;
@@ -127,6 +129,8 @@ pub struct CreateOutlet {
     /// Allow the outlet to be reachable from the default secure channel, useful when we want to
     /// tighten the flow control
     #[n(4)] pub reachable_from_default_secure_channel: bool,
+    /// Distribute the portal connections between `socket_addr` and other targets
+    #[n(5)] pub load_balancing: Option<OutletLoadBalancing>,
 }
 
 impl CreateOutlet {
@@ -141,6 +145,217 @@ impl CreateOutlet {
             worker_addr,
             alias: alias.into(),
             reachable_from_default_secure_channel,
+            load_balancing: None,
+        }
+    }
+
+    pub fn set_load_balancing(&mut self, load_balancing: OutletLoadBalancing) {
+        self.load_balancing = Some(load_balancing)
+    }
+}
+
+/// Load balancing settings of an outlet with several targets
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletLoadBalancing {
+    /// The targets used in addition to the outlet `socket_addr`
+    #[n(1)] pub additional_targets: Vec<SocketAddr>,
+    /// How a target is chosen for each new portal connection
+    #[n(2)] pub strategy: OutletStrategy,
+    /// If set, the targets are periodically checked and the unreachable ones
+    /// don't receive new portal connections until they are reachable again
+    #[n(3)] pub health_check_interval: Option<Duration>,
+}
+
+impl OutletLoadBalancing {
+    pub fn new(
+        additional_targets: Vec<SocketAddr>,
+        strategy: OutletStrategy,
+        health_check_interval: Option<Duration>,
+    ) -> Self {
+        Self {
+            additional_targets,
+            strategy,
+            health_check_interval,
+        }
+    }
+}
+
+/// How an outlet with several targets chooses the target of a new portal connection
+#[derive(Copy, Clone, Debug, Decode, Encode, Serialize, Deserialize, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum OutletStrategy {
+    /// Use each healthy target in turn
+    #[n(0)] RoundRobin,
+    /// Use the healthy target with the fewest open connections
+    #[n(1)] LeastConnections,
+}
+
+impl From<OutletStrategy> for TcpOutletStrategy {
+    fn from(value: OutletStrategy) -> Self {
+        match value {
+            OutletStrategy::RoundRobin => Self::RoundRobin,
+            OutletStrategy::LeastConnections => Self::LeastConnections,
+        }
+    }
+}
+
+impl From<TcpOutletStrategy> for OutletStrategy {
+    fn from(value: TcpOutletStrategy) -> Self {
+        match value {
+            TcpOutletStrategy::RoundRobin => Self::RoundRobin,
+            TcpOutletStrategy::LeastConnections => Self::LeastConnections,
+        }
+    }
+}
+
+impl Display for OutletStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        TcpOutletStrategy::from(*self).fmt(f)
+    }
+}
+
+/// Request body to create a UDP inlet
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateUdpInlet {
+    /// The UDP address the inlet should receive datagrams at.
+    #[n(1)] pub(crate) listen_addr: String,
+    /// The address of the UDP outlet.
+    #[n(2)] pub(crate) outlet_addr: MultiAddr,
+    /// A human-friendly alias for this portal endpoint
+    #[n(3)] pub(crate) alias: Option<String>,
+    /// An authorised identity for secure channels.
+    /// Only set for non-project addresses as for projects the project's
+    /// authorised identity will be used.
+    #[n(4)] pub(crate) authorized: Option<Identifier>,
+    /// The duration after which the session of a UDP peer without traffic is closed
+    #[n(5)] pub(crate) idle_timeout: Option<Duration>,
+    /// The maximum duration to wait for an outlet to be available
+    #[n(6)] pub(crate) wait_for_outlet_duration: Option<Duration>,
+}
+
+impl CreateUdpInlet {
+    pub fn new(listen: String, to: MultiAddr, auth: Option<Identifier>) -> Self {
+        Self {
+            listen_addr: listen,
+            outlet_addr: to,
+            alias: None,
+            authorized: auth,
+            idle_timeout: None,
+            wait_for_outlet_duration: None,
+        }
+    }
+
+    pub fn set_alias(&mut self, a: impl Into<String>) {
+        self.alias = Some(a.into())
+    }
+
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = Some(idle_timeout)
+    }
+
+    pub fn set_wait_ms(&mut self, ms: u64) {
+        self.wait_for_outlet_duration = Some(Duration::from_millis(ms))
+    }
+}
+
+/// Request body to create a UDP outlet
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateUdpOutlet {
+    /// The UDP address the outlet should send datagrams to
+    #[n(1)] pub socket_addr: SocketAddr,
+    /// The address of the outlet worker
+    #[n(2)] pub worker_addr: Address,
+    /// A human-friendly alias for this portal endpoint
+    #[n(3)] pub alias: Option<String>,
+    /// The duration after which a session without traffic is closed
+    #[n(4)] pub idle_timeout: Option<Duration>,
+}
+
+impl CreateUdpOutlet {
+    pub fn new(
+        socket_addr: SocketAddr,
+        worker_addr: Address,
+        alias: impl Into<Option<String>>,
+        idle_timeout: impl Into<Option<Duration>>,
+    ) -> Self {
+        Self {
+            socket_addr,
+            worker_addr,
+            alias: alias.into(),
+            idle_timeout: idle_timeout.into(),
+        }
+    }
+}
+
+/// Request body to create a UDS inlet
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateUdsInlet {
+    /// The path of the socket the inlet should listen at.
+    #[n(1)] pub(crate) socket_path: String,
+    /// The address of the outlet, which can be either a UDS or a TCP outlet.
+    #[n(2)] pub(crate) outlet_addr: MultiAddr,
+    /// A human-friendly alias for this portal endpoint
+    #[n(3)] pub(crate) alias: Option<String>,
+    /// An authorised identity for secure channels.
+    /// Only set for non-project addresses as for projects the project's
+    /// authorised identity will be used.
+    #[n(4)] pub(crate) authorized: Option<Identifier>,
+    /// The maximum duration to wait for an outlet to be available
+    #[n(5)] pub(crate) wait_for_outlet_duration: Option<Duration>,
+}
+
+impl CreateUdsInlet {
+    pub fn new(socket_path: String, to: MultiAddr, auth: Option<Identifier>) -> Self {
+        Self {
+            socket_path,
+            outlet_addr: to,
+            alias: None,
+            authorized: auth,
+            wait_for_outlet_duration: None,
+        }
+    }
+
+    pub fn set_alias(&mut self, a: impl Into<String>) {
+        self.alias = Some(a.into())
+    }
+
+    pub fn set_wait_ms(&mut self, ms: u64) {
+        self.wait_for_outlet_duration = Some(Duration::from_millis(ms))
+    }
+}
+
+/// Request body to create a UDS outlet
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateUdsOutlet {
+    /// The path of the socket the outlet should connect to
+    #[n(1)] pub socket_path: String,
+    /// The address of the outlet worker
+    #[n(2)] pub worker_addr: Address,
+    /// A human-friendly alias for this portal endpoint
+    #[n(3)] pub alias: Option<String>,
+}
+
+impl CreateUdsOutlet {
+    pub fn new(
+        socket_path: impl Into<String>,
+        worker_addr: Address,
+        alias: impl Into<Option<String>>,
+    ) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+            worker_addr,
+            alias: alias.into(),
         }
     }
 }
@@ -196,6 +411,10 @@ pub struct OutletStatus {
     #[n(3)] pub alias: String,
     /// An optional status payload
     #[n(4)] pub payload: Option<String>,
+    /// The strategy of a load-balanced outlet
+    #[n(5)] pub strategy: Option<OutletStrategy>,
+    /// The state of each target of a load-balanced outlet
+    #[n(6)] pub targets: Option<Vec<OutletTargetStatus>>,
 }
 
 impl OutletStatus {
@@ -205,6 +424,8 @@ impl OutletStatus {
             worker_addr: "".into(),
             alias: "".into(),
             payload: Some(reason.into()),
+            strategy: None,
+            targets: None,
         }
     }
 
@@ -219,9 +440,21 @@ impl OutletStatus {
             worker_addr,
             alias: alias.into(),
             payload: payload.into(),
+            strategy: None,
+            targets: None,
         }
     }
 
+    pub fn with_targets(
+        mut self,
+        strategy: OutletStrategy,
+        targets: impl IntoIterator<Item = OutletTargetStatus>,
+    ) -> Self {
+        self.strategy = Some(strategy);
+        self.targets = Some(targets.into_iter().collect());
+        self
+    }
+
     pub fn worker_address(&self) -> Result<MultiAddr, ockam_core::Error> {
         route_to_multiaddr(&route![self.worker_addr.to_string()])
             .ok_or_else(|| ApiError::core("Invalid Worker Address"))
@@ -236,6 +469,28 @@ impl OutletStatus {
     }
 }
 
+/// State of one of the targets of a load-balanced outlet
+#[derive(Clone, Debug, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletTargetStatus {
+    #[n(1)] pub socket_addr: SocketAddr,
+    /// Unhealthy targets don't receive new portal connections
+    #[n(2)] pub healthy: bool,
+    /// The number of open portal connections to this target
+    #[n(3)] pub connections: u64,
+}
+
+impl From<TcpOutletTargetStatus> for OutletTargetStatus {
+    fn from(value: TcpOutletTargetStatus) -> Self {
+        Self {
+            socket_addr: value.socket_addr,
+            healthy: value.healthy,
+            connections: value.connections as u64,
+        }
+    }
+}
+
 /// Response body when returning a list of Inlets
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -263,3 +518,46 @@ impl OutletList {
         Self { list }
     }
 }
+
+/// Response body when interacting with a UDS outlet
+#[derive(Clone, Debug, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UdsOutletStatus {
+    #[n(1)] pub socket_path: String,
+    #[n(2)] pub worker_addr: Address,
+    #[n(3)] pub alias: String,
+}
+
+impl UdsOutletStatus {
+    pub fn new(
+        socket_path: impl Into<String>,
+        worker_addr: Address,
+        alias: impl Into<String>,
+    ) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+            worker_addr,
+            alias: alias.into(),
+        }
+    }
+
+    pub fn worker_address(&self) -> Result<MultiAddr, ockam_core::Error> {
+        route_to_multiaddr(&route![self.worker_addr.to_string()])
+            .ok_or_else(|| ApiError::core("Invalid Worker Address"))
+    }
+}
+
+/// Response body when returning a list of UDS Outlets
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UdsOutletList {
+    #[n(1)] pub list: Vec<UdsOutletStatus>
+}
+
+impl UdsOutletList {
+    pub fn new(list: Vec<UdsOutletStatus>) -> Self {
+        Self { list }
+    }
+}
@@ -93,6 +93,47 @@ impl RelayInfo {
     }
 }
 
+/// Response body when listing the relays hosted by the relay service of a node
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize, serde::Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct HostedRelayInfo {
+    #[n(1)] alias: String,
+    #[n(2)] forward_route: String,
+    #[n(3)] owner: Option<Identifier>,
+    #[n(4)] last_heartbeat: u64,
+}
+
+impl HostedRelayInfo {
+    pub fn alias(&self) -> &str {
+        &self.alias
+    }
+
+    pub fn forward_route(&self) -> &str {
+        &self.forward_route
+    }
+
+    pub fn owner(&self) -> Option<&Identifier> {
+        self.owner.as_ref()
+    }
+
+    /// Time of the last registration or heartbeat, in seconds since the Unix epoch
+    pub fn last_heartbeat(&self) -> u64 {
+        self.last_heartbeat
+    }
+}
+
+impl From<ockam::RelayInfo> for HostedRelayInfo {
+    fn from(inner: ockam::RelayInfo) -> Self {
+        Self {
+            alias: inner.alias().to_string(),
+            forward_route: inner.forward_route().to_string(),
+            owner: inner.owner().cloned(),
+            last_heartbeat: inner.last_heartbeat().0,
+        }
+    }
+}
+
 impl From<RemoteRelayInfo> for RelayInfo {
     fn from(inner: RemoteRelayInfo) -> Self {
         Self {
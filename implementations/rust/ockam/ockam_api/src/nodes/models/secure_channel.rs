@@ -3,7 +3,7 @@ use std::time::Duration;
 use minicbor::{Decode, Encode};
 use serde::Serialize;
 
-use ockam::identity::{Identifier, DEFAULT_TIMEOUT};
+use ockam::identity::{Identifier, TimestampInSeconds, DEFAULT_TIMEOUT};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{route, Address, Result};
 use ockam_multiaddr::MultiAddr;
@@ -214,10 +214,12 @@ pub struct ShowSecureChannelResponse {
     #[n(2)] pub route: Option<String>,
     #[n(3)] pub authorized_identifiers: Option<Vec<String>>,
     #[n(4)] pub flow_control_id: Option<FlowControlId>,
+    /// Last time a message was received over the channel
+    #[n(5)] pub last_activity: Option<TimestampInSeconds>,
 }
 
 impl ShowSecureChannelResponse {
-    pub fn new(info: Option<SecureChannelInfo>) -> Self {
+    pub fn new(info: Option<SecureChannelInfo>, last_activity: Option<TimestampInSeconds>) -> Self {
         Self {
             channel: info
                 .clone()
@@ -232,6 +234,7 @@ impl ShowSecureChannelResponse {
                 })
                 .unwrap_or(None),
             flow_control_id: info.map(|info| info.sc().flow_control_id().clone()),
+            last_activity,
         }
     }
 }
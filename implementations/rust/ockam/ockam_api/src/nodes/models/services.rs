@@ -73,6 +73,8 @@ pub struct StartKafkaConsumerRequest {
     #[n(1)] pub bootstrap_server_addr: SocketAddr,
     #[n(2)] brokers_port_range: (u16, u16),
     #[n(3)] project_route: String,
+    #[n(4)] record_key_secret_file: Option<String>,
+    #[n(5)] fan_out: bool,
 }
 
 impl StartKafkaConsumerRequest {
@@ -85,6 +87,8 @@ impl StartKafkaConsumerRequest {
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
             project_route: project_route.to_string(),
+            record_key_secret_file: None,
+            fan_out: false,
         }
     }
 
@@ -97,6 +101,33 @@ impl StartKafkaConsumerRequest {
     pub fn project_route(&self) -> &String {
         &self.project_route
     }
+
+    /// Protect the record keys and headers, the record keys being encrypted
+    /// deterministically with a secret shared by the producers and consumers.
+    /// The secret is read by the node from the given file, so that it is never sent
+    /// to the node or stored with its configuration
+    pub fn with_record_key_secret_file(
+        mut self,
+        record_key_secret_file: impl Into<String>,
+    ) -> Self {
+        self.record_key_secret_file = Some(record_key_secret_file.into());
+        self
+    }
+
+    pub fn record_key_secret_file(&self) -> Option<&str> {
+        self.record_key_secret_file.as_deref()
+    }
+
+    /// Encrypt the records with a data key per topic, wrapped for every
+    /// authorized consumer, so that several consumers can read the same topic
+    pub fn with_fan_out(mut self, fan_out: bool) -> Self {
+        self.fan_out = fan_out;
+        self
+    }
+
+    pub fn fan_out(&self) -> bool {
+        self.fan_out
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -106,6 +137,8 @@ pub struct StartKafkaProducerRequest {
     #[n(1)] pub bootstrap_server_addr: SocketAddr,
     #[n(2)] brokers_port_range: (u16, u16),
     #[n(3)] project_route: String,
+    #[n(4)] record_key_secret_file: Option<String>,
+    #[n(5)] fan_out: bool,
 }
 
 impl StartKafkaProducerRequest {
@@ -118,6 +151,8 @@ impl StartKafkaProducerRequest {
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
             project_route: project_route.to_string(),
+            record_key_secret_file: None,
+            fan_out: false,
         }
     }
 
@@ -130,6 +165,33 @@ impl StartKafkaProducerRequest {
     pub fn project_route(&self) -> &String {
         &self.project_route
     }
+
+    /// Protect the record keys and headers, the record keys being encrypted
+    /// deterministically with a secret shared by the producers and consumers.
+    /// The secret is read by the node from the given file, so that it is never sent
+    /// to the node or stored with its configuration
+    pub fn with_record_key_secret_file(
+        mut self,
+        record_key_secret_file: impl Into<String>,
+    ) -> Self {
+        self.record_key_secret_file = Some(record_key_secret_file.into());
+        self
+    }
+
+    pub fn record_key_secret_file(&self) -> Option<&str> {
+        self.record_key_secret_file.as_deref()
+    }
+
+    /// Encrypt the records with a data key per topic, wrapped for every
+    /// authorized consumer, so that several consumers can read the same topic
+    pub fn with_fan_out(mut self, fan_out: bool) -> Self {
+        self.fan_out = fan_out;
+        self
+    }
+
+    pub fn fan_out(&self) -> bool {
+        self.fan_out
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -140,6 +202,7 @@ pub struct StartKafkaDirectRequest {
     #[n(2)] bootstrap_server_addr: SocketAddr,
     #[n(3)] brokers_port_range: (u16, u16),
     #[n(4)] consumer_route: Option<String>,
+    #[n(5)] record_key_secret_file: Option<String>,
 }
 
 impl StartKafkaDirectRequest {
@@ -154,6 +217,7 @@ impl StartKafkaDirectRequest {
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
             consumer_route: consumer_route.map(|a| a.to_string()),
+            record_key_secret_file: None,
         }
     }
 
@@ -169,6 +233,22 @@ impl StartKafkaDirectRequest {
     pub fn consumer_route(&self) -> Option<String> {
         self.consumer_route.clone()
     }
+
+    /// Protect the record keys and headers, the record keys being encrypted
+    /// deterministically with a secret shared by the producers and consumers.
+    /// The secret is read by the node from the given file, so that it is never sent
+    /// to the node or stored with its configuration
+    pub fn with_record_key_secret_file(
+        mut self,
+        record_key_secret_file: impl Into<String>,
+    ) -> Self {
+        self.record_key_secret_file = Some(record_key_secret_file.into());
+        self
+    }
+
+    pub fn record_key_secret_file(&self) -> Option<&str> {
+        self.record_key_secret_file.as_deref()
+    }
 }
 
 /// Request body when instructing a node to start an Identity service
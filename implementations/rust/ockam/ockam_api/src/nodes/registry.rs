@@ -1,3 +1,4 @@
+use crate::nodes::models::portal::OutletStatus;
 use crate::nodes::service::Alias;
 use ockam::identity::Identifier;
 use ockam::identity::{SecureChannel, SecureChannelListener};
@@ -5,6 +6,7 @@ use ockam::remote::RemoteRelayInfo;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::{Address, Route};
 use ockam_node::compat::asynchronous::RwLock;
+use ockam_transport_tcp::TcpOutletTargets;
 use std::borrow::Borrow;
 use std::fmt::Display;
 use std::net::SocketAddr;
@@ -180,6 +182,8 @@ impl InletInfo {
 pub struct OutletInfo {
     pub(crate) socket_addr: SocketAddr,
     pub(crate) worker_addr: Address,
+    /// Only set for load-balanced outlets
+    pub(crate) targets: Option<TcpOutletTargets>,
 }
 
 impl OutletInfo {
@@ -191,6 +195,40 @@ impl OutletInfo {
         Self {
             socket_addr: *socket_addr,
             worker_addr,
+            targets: None,
+        }
+    }
+
+    pub(crate) fn with_targets(mut self, targets: TcpOutletTargets) -> Self {
+        self.targets = Some(targets);
+        self
+    }
+
+    pub(crate) fn status(&self, alias: impl Into<String>) -> OutletStatus {
+        let status = OutletStatus::new(self.socket_addr, self.worker_addr.clone(), alias, None);
+        match &self.targets {
+            Some(targets) => status.with_targets(
+                targets.strategy().into(),
+                targets.status().into_iter().map(Into::into),
+            ),
+            None => status,
+        }
+    }
+}
+
+#[cfg(unix)]
+#[derive(Clone)]
+pub struct UdsOutletInfo {
+    pub(crate) socket_path: String,
+    pub(crate) worker_addr: Address,
+}
+
+#[cfg(unix)]
+impl UdsOutletInfo {
+    pub(crate) fn new(socket_path: &str, worker_addr: &Address) -> Self {
+        Self {
+            socket_path: socket_path.to_owned(),
+            worker_addr: worker_addr.clone(),
         }
     }
 }
@@ -208,6 +246,12 @@ pub(crate) struct Registry {
     pub(crate) relays: RegistryOf<String, RemoteRelayInfo>,
     pub(crate) inlets: RegistryOf<Alias, InletInfo>,
     pub(crate) outlets: RegistryOf<Alias, OutletInfo>,
+    pub(crate) udp_inlets: RegistryOf<Alias, InletInfo>,
+    pub(crate) udp_outlets: RegistryOf<Alias, OutletInfo>,
+    #[cfg(unix)]
+    pub(crate) uds_inlets: RegistryOf<Alias, InletInfo>,
+    #[cfg(unix)]
+    pub(crate) uds_outlets: RegistryOf<Alias, UdsOutletInfo>,
 }
 
 pub(crate) struct RegistryOf<K, V> {
@@ -11,24 +11,33 @@ use minicbor::{Decoder, Encode};
 
 pub use node_identities::*;
 use ockam::identity::models::CredentialAndPurposeKey;
+use ockam::identity::storage::Storage;
 use ockam::identity::CredentialsServerModule;
 use ockam::identity::TrustContext;
 use ockam::identity::Vault;
 use ockam::identity::{
     Credentials, CredentialsServer, Identities, IdentitiesRepository, IdentityAttributesReader,
+    RevocationListRefresher, DEFAULT_REVOCATION_LIST_MAX_AGE,
+    DEFAULT_REVOCATION_LIST_REFRESH_INTERVAL,
 };
 use ockam::identity::{Identifier, SecureChannels};
 use ockam::{
-    Address, Context, RelayService, RelayServiceOptions, Result, Routed, TcpTransport, Worker,
+    Address, Context, RelayRegistry, RelayService, RelayServiceOptions, Result, Routed,
+    TcpTransport, Worker,
 };
 use ockam_abac::expr::{eq, ident, str};
 use ockam_abac::{Action, Env, Expr, PolicyAccessControl, PolicyStorage, Resource};
 use ockam_core::api::{Method, RequestHeader, Response};
 use ockam_core::compat::{string::String, sync::Arc};
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::IncomingAccessControl;
 use ockam_core::{AllowAll, AsyncTryClone};
 use ockam_multiaddr::MultiAddr;
+use ockam_transport_udp::UdpTransport;
+#[cfg(unix)]
+use ockam_transport_uds::UdsTransport;
+use ockam_transport_websocket::WebSocketTransport;
 
 use crate::bootstrapped_identities_store::BootstrapedIdentityStore;
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
@@ -38,31 +47,36 @@ use crate::config::cli::TrustContextConfig;
 use crate::config::lookup::ProjectLookup;
 use crate::error::ApiError;
 use crate::nodes::connection::{
-    Connection, ConnectionBuilder, PlainTcpInstantiator, ProjectInstantiator,
-    SecureChannelInstantiator,
+    Connection, ConnectionBuilder, PlainTcpInstantiator, PlainTransportInstantiator,
+    ProjectInstantiator, SecureChannelInstantiator,
 };
 use crate::nodes::models::base::NodeStatus;
-use crate::nodes::models::portal::{OutletList, OutletStatus};
+use crate::nodes::models::portal::OutletList;
 use crate::nodes::models::transport::{TransportMode, TransportType};
 use crate::nodes::models::workers::{WorkerList, WorkerStatus};
 use crate::nodes::registry::KafkaServiceKind;
 use crate::nodes::{InMemoryNode, NODEMANAGER_ADDR};
-use crate::DefaultAddress;
+use crate::{DefaultAddress, TransportFlowControls};
 
 use super::registry::Registry;
 
 pub(crate) mod background_node;
 pub(crate) mod credentials;
 mod flow_controls;
+mod identity;
 pub(crate) mod in_memory_node;
 pub mod message;
 mod node_identities;
+mod node_resources;
 mod node_services;
 mod policy;
 mod portals;
 pub mod relay;
 mod secure_channel;
 mod transport;
+mod udp_portals;
+#[cfg(unix)]
+mod uds_portals;
 
 const TARGET: &str = "ockam_api::nodemanager::service";
 
@@ -95,12 +109,19 @@ pub struct NodeManager {
     node_name: String,
     api_transport_flow_control_id: FlowControlId,
     pub(crate) tcp_transport: TcpTransport,
+    pub(crate) udp_transport: Option<UdpTransport>,
+    ws_transport: Option<WebSocketTransport>,
+    #[cfg(unix)]
+    pub(crate) uds_transport: Option<UdsTransport>,
     enable_credential_checks: bool,
     identifier: Identifier,
     pub(crate) secure_channels: Arc<SecureChannels>,
     trust_context: Option<TrustContext>,
     pub(crate) registry: Registry,
     policies: Arc<dyn PolicyStorage>,
+    kafka_storage: Arc<dyn Storage>,
+    relay_registry: RelayRegistry,
+    persistent_resources: bool,
 }
 
 impl NodeManager {
@@ -140,6 +161,48 @@ impl NodeManager {
         &self.tcp_transport
     }
 
+    pub fn udp_transport(&self) -> Result<&UdpTransport> {
+        self.udp_transport.as_ref().ok_or_else(|| {
+            ockam_core::Error::new(
+                Origin::Node,
+                Kind::NotFound,
+                "The UDP transport is not available on this node",
+            )
+        })
+    }
+
+    #[cfg(unix)]
+    pub fn uds_transport(&self) -> Result<&UdsTransport> {
+        self.uds_transport.as_ref().ok_or_else(|| {
+            ockam_core::Error::new(
+                Origin::Node,
+                Kind::NotFound,
+                "The UDS transport is not available on this node",
+            )
+        })
+    }
+
+    /// Flow controls of the transports started on this node, other than TCP
+    pub fn transport_flow_controls(&self) -> TransportFlowControls {
+        TransportFlowControls {
+            udp: self
+                .udp_transport
+                .as_ref()
+                .and_then(|udp| udp.flow_control_id().cloned()),
+            ws: self
+                .ws_transport
+                .as_ref()
+                .and_then(|ws| ws.flow_control_id().cloned()),
+            #[cfg(unix)]
+            uds: self
+                .uds_transport
+                .as_ref()
+                .and_then(|uds| uds.flow_control_id().cloned()),
+            #[cfg(not(unix))]
+            uds: None,
+        }
+    }
+
     pub async fn list_outlets(&self) -> OutletList {
         OutletList::new(
             self.registry
@@ -147,9 +210,7 @@ impl NodeManager {
                 .entries()
                 .await
                 .iter()
-                .map(|(alias, info)| {
-                    OutletStatus::new(info.socket_addr, info.worker_addr.clone(), alias, None)
-                })
+                .map(|(alias, info)| info.status(alias))
                 .collect(),
         )
     }
@@ -251,13 +312,16 @@ impl NodeManager {
                 self.policies.set_policy(r, a, &fallback).await?
             }
             let policies = self.policies.clone();
-            Ok(Arc::new(PolicyAccessControl::new(
-                policies,
-                self.identities_repository(),
-                r.clone(),
-                a.clone(),
-                env,
-            )))
+            Ok(Arc::new(
+                PolicyAccessControl::new(
+                    policies,
+                    self.identities_repository(),
+                    r.clone(),
+                    a.clone(),
+                    env,
+                )
+                .with_revocation_lists(self.identities().revocation_lists()),
+            ))
         } else {
             Ok(Arc::new(AllowAll))
         }
@@ -275,6 +339,7 @@ pub struct NodeManagerGeneralOptions {
     node_name: String,
     pre_trusted_identities: Option<PreTrustedIdentities>,
     start_default_services: bool,
+    persistent_resources: bool,
 }
 
 impl NodeManagerGeneralOptions {
@@ -289,8 +354,16 @@ impl NodeManagerGeneralOptions {
             node_name,
             pre_trusted_identities,
             start_default_services,
+            persistent_resources: false,
         }
     }
+
+    /// Persist the resources created on the node, so that they can be
+    /// created again when the node is restarted
+    pub fn with_persistent_resources(mut self) -> Self {
+        self.persistent_resources = true;
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -313,6 +386,10 @@ pub struct ApiTransport {
 pub struct NodeManagerTransportOptions {
     api_transport_flow_control_id: FlowControlId,
     tcp_transport: TcpTransport,
+    udp_transport: Option<UdpTransport>,
+    ws_transport: Option<WebSocketTransport>,
+    #[cfg(unix)]
+    uds_transport: Option<UdsTransport>,
 }
 
 impl NodeManagerTransportOptions {
@@ -320,8 +397,31 @@ impl NodeManagerTransportOptions {
         Self {
             api_transport_flow_control_id,
             tcp_transport,
+            udp_transport: None,
+            ws_transport: None,
+            #[cfg(unix)]
+            uds_transport: None,
         }
     }
+
+    /// Use a UDP transport to create UDP inlets and outlets
+    pub fn with_udp_transport(mut self, udp_transport: UdpTransport) -> Self {
+        self.udp_transport = Some(udp_transport);
+        self
+    }
+
+    /// Use a WebSocket transport to create connections to /ws addresses
+    pub fn with_ws_transport(mut self, ws_transport: WebSocketTransport) -> Self {
+        self.ws_transport = Some(ws_transport);
+        self
+    }
+
+    /// Use a UDS transport to create UDS inlets and outlets
+    #[cfg(unix)]
+    pub fn with_uds_transport(mut self, uds_transport: UdsTransport) -> Self {
+        self.uds_transport = Some(uds_transport);
+        self
+    }
 }
 
 pub struct NodeManagerTrustOptions {
@@ -378,12 +478,22 @@ impl NodeManager {
             .build();
 
         let policies: Arc<dyn PolicyStorage> = Arc::new(node_state.policies_storage().await?);
+        let kafka_storage: Arc<dyn Storage> = Arc::new(node_state.kafka_storage().await?);
+        let relay_registry = if general_options.persistent_resources {
+            node_state.relay_registry().await?
+        } else {
+            RelayRegistry::new()
+        };
 
         let mut s = Self {
             cli_state,
             node_name: general_options.node_name,
             api_transport_flow_control_id: transport_options.api_transport_flow_control_id,
             tcp_transport: transport_options.tcp_transport,
+            udp_transport: transport_options.udp_transport,
+            ws_transport: transport_options.ws_transport,
+            #[cfg(unix)]
+            uds_transport: transport_options.uds_transport,
             enable_credential_checks: trust_options.trust_context_config.is_some()
                 && trust_options
                     .trust_context_config
@@ -396,6 +506,9 @@ impl NodeManager {
             trust_context: None,
             registry: Default::default(),
             policies,
+            kafka_storage,
+            relay_registry,
+            persistent_resources: general_options.persistent_resources,
         };
 
         if let Some(tc) = trust_options.trust_context_config {
@@ -440,7 +553,8 @@ impl NodeManager {
             DefaultAddress::RELAY_SERVICE,
             RelayServiceOptions::new()
                 .service_as_consumer(api_flow_control_id)
-                .relay_as_consumer(api_flow_control_id),
+                .relay_as_consumer(api_flow_control_id)
+                .with_registry(self.relay_registry.clone()),
         )
         .await?;
 
@@ -486,6 +600,24 @@ impl NodeManager {
         self.start_echoer_service_impl(ctx, DefaultAddress::ECHO_SERVICE.into())
             .await?;
 
+        // If the trust context authorities publish a revocation list, keep a fresh copy of it
+        // so that revoked credentials are rejected by this node
+        if let Ok(trust_context) = self.trust_context() {
+            for authority in trust_context.authority_services() {
+                if authority.has_revocation_list() {
+                    RevocationListRefresher::start(
+                        ctx,
+                        Address::random_tagged("RevocationListRefresher"),
+                        authority.clone(),
+                        self.identifier.clone(),
+                        DEFAULT_REVOCATION_LIST_REFRESH_INTERVAL,
+                        DEFAULT_REVOCATION_LIST_MAX_AGE,
+                    )
+                    .await?;
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -528,6 +660,12 @@ impl NodeManager {
             .await?
             .instantiate(ctx.clone(), self, PlainTcpInstantiator::new())
             .await?
+            .instantiate(ctx.clone(), self, PlainTransportInstantiator::udp())
+            .await?
+            .instantiate(ctx.clone(), self, PlainTransportInstantiator::ws())
+            .await?
+            .instantiate(ctx.clone(), self, PlainTransportInstantiator::unix())
+            .await?
             .instantiate(
                 ctx.clone(),
                 self,
@@ -637,6 +775,11 @@ impl NodeManagerWorker {
                 encode_response(self.present_credential(req, dec, ctx).await)?
             }
 
+            // ==*== Identity ==*==
+            (Post, ["node", "identity", "actions", "rotate"]) => {
+                encode_response(self.rotate_key(ctx, req, dec.decode()?).await)?
+            }
+
             // ==*== Secure channels ==*==
             (Get, ["node", "secure_channel"]) => self.list_secure_channels(req).await.to_vec()?,
             (Get, ["node", "secure_channel_listener"]) => {
@@ -717,6 +860,7 @@ impl NodeManagerWorker {
                 encode_response(self.show_relay(req, remote_address).await)?
             }
             (Get, ["node", "forwarder"]) => encode_response(self.get_relays(req).await)?,
+            (Get, ["node", "hosted_relays"]) => encode_response(self.get_hosted_relays(req).await)?,
             (Delete, ["node", "forwarder", remote_address]) => {
                 encode_response(self.delete_relay(ctx, req, remote_address).await)?
             }
@@ -743,6 +887,58 @@ impl NodeManagerWorker {
             }
             (Delete, ["node", "portal"]) => todo!(),
 
+            // ==*== UDP Inlets & Outlets ==*==
+            (Get, ["node", "udp-inlet"]) => self.get_udp_inlets(req).await.to_vec()?,
+            (Get, ["node", "udp-inlet", alias]) => {
+                encode_response(self.show_udp_inlet(req, alias).await)?
+            }
+            (Get, ["node", "udp-outlet"]) => self.get_udp_outlets(req).await.to_vec()?,
+            (Get, ["node", "udp-outlet", alias]) => {
+                encode_response(self.show_udp_outlet(req, alias).await)?
+            }
+            (Post, ["node", "udp-inlet"]) => {
+                encode_response(self.create_udp_inlet(ctx, req, dec.decode()?).await)?
+            }
+            (Post, ["node", "udp-outlet"]) => {
+                encode_response(self.create_udp_outlet(ctx, req, dec.decode()?).await)?
+            }
+            (Delete, ["node", "udp-inlet", alias]) => {
+                encode_response(self.delete_udp_inlet(req, alias).await)?
+            }
+            (Delete, ["node", "udp-outlet", alias]) => {
+                encode_response(self.delete_udp_outlet(req, alias).await)?
+            }
+
+            // ==*== UDS Inlets & Outlets ==*==
+            #[cfg(unix)]
+            (Get, ["node", "uds-inlet"]) => self.get_uds_inlets(req).await.to_vec()?,
+            #[cfg(unix)]
+            (Get, ["node", "uds-inlet", alias]) => {
+                encode_response(self.show_uds_inlet(req, alias).await)?
+            }
+            #[cfg(unix)]
+            (Get, ["node", "uds-outlet"]) => self.get_uds_outlets(req).await.to_vec()?,
+            #[cfg(unix)]
+            (Get, ["node", "uds-outlet", alias]) => {
+                encode_response(self.show_uds_outlet(req, alias).await)?
+            }
+            #[cfg(unix)]
+            (Post, ["node", "uds-inlet"]) => {
+                encode_response(self.create_uds_inlet(ctx, req, dec.decode()?).await)?
+            }
+            #[cfg(unix)]
+            (Post, ["node", "uds-outlet"]) => {
+                encode_response(self.create_uds_outlet(ctx, req, dec.decode()?).await)?
+            }
+            #[cfg(unix)]
+            (Delete, ["node", "uds-inlet", alias]) => {
+                encode_response(self.delete_uds_inlet(req, alias).await)?
+            }
+            #[cfg(unix)]
+            (Delete, ["node", "uds-outlet", alias]) => {
+                encode_response(self.delete_uds_outlet(req, alias).await)?
+            }
+
             // ==*== Flow Controls ==*==
             (Post, ["node", "flow_controls", "add_consumer"]) => {
                 encode_response(self.add_consumer(ctx, req, dec))?
@@ -0,0 +1,97 @@
+use ockam::identity::Identifier;
+use ockam::Result;
+use ockam_core::api::{Error, RequestHeader, Response};
+use ockam_node::Context;
+
+use crate::nodes::models::identity::{RotateKeyRequest, RotateKeyResponse, RotatedKey};
+
+use super::{NodeManager, NodeManagerWorker};
+
+impl NodeManagerWorker {
+    pub(super) async fn rotate_key(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        request: RotateKeyRequest,
+    ) -> Result<Response<RotateKeyResponse>, Response<Error>> {
+        match self.node_manager.rotate_key(ctx, request.key).await {
+            Ok(body) => Ok(Response::ok(req).body(body)),
+            Err(err) => Err(Response::internal_error(
+                req,
+                &format!("Failed to rotate the {}: {}", request.key, err),
+            )),
+        }
+    }
+}
+
+impl NodeManager {
+    /// Rotate a key of the node identity.
+    ///
+    /// When the primary key is rotated the existing purpose keys are attested again with the
+    /// new primary key. Then, if the node has a project authority, a new credential is retrieved
+    /// from it. This sends the new change history to the authority so that the credentials
+    /// issued to this identity keep being validated by other members of the project
+    pub async fn rotate_key(&self, ctx: &Context, key: RotatedKey) -> Result<RotateKeyResponse> {
+        let identifier = self.identifier().clone();
+        let purpose_keys_creation = self.identities().purpose_keys().purpose_keys_creation();
+        match key {
+            RotatedKey::Primary => {
+                self.identities()
+                    .identities_creation()
+                    .rotate_identity(&identifier)
+                    .await?;
+                purpose_keys_creation
+                    .reattest_purpose_keys(&identifier)
+                    .await?;
+            }
+            RotatedKey::SecureChannel => {
+                purpose_keys_creation
+                    .rotate_secure_channel_purpose_key(&identifier)
+                    .await?;
+            }
+            RotatedKey::Credential => {
+                purpose_keys_creation
+                    .rotate_credential_purpose_key(&identifier)
+                    .await?;
+            }
+        }
+        info!(%identifier, "rotated the {key}");
+
+        // The key is already rotated at this point, so a failure to reach the authority
+        // is reported in the response instead of failing the whole rotation
+        let mut pushed_to_authority = false;
+        let mut authority_error = None;
+        if key == RotatedKey::Primary && self.enable_credential_checks {
+            match self.refresh_authority_credential(ctx, &identifier).await {
+                Ok(()) => {
+                    info!(%identifier, "sent the new change history to the project authority");
+                    pushed_to_authority = true;
+                }
+                Err(err) => {
+                    warn!(%identifier, %err, "failed to send the change history to the authority");
+                    authority_error = Some(err.to_string());
+                }
+            }
+        }
+
+        let identity = self.identities().get_identity(&identifier).await?;
+        Ok(RotateKeyResponse {
+            identifier,
+            key,
+            change_history: hex::encode(identity.export()?),
+            pushed_to_authority,
+            authority_error,
+        })
+    }
+
+    async fn refresh_authority_credential(
+        &self,
+        ctx: &Context,
+        identifier: &Identifier,
+    ) -> Result<()> {
+        self.trust_context()?
+            .authority()?
+            .refresh_credential(ctx, identifier)
+            .await
+    }
+}
@@ -0,0 +1,51 @@
+use minicbor::Encode;
+
+use ockam_core::Result;
+
+use crate::cli_state::{NodeResource, StateDirTrait};
+use crate::error::ApiError;
+
+use super::NodeManager;
+
+/// Resources created on a node are persisted in the node state, together with the request
+/// which created them. When the node is restarted, the requests are sent again to the
+/// node manager. Failing to persist a resource doesn't fail its creation.
+///
+/// The persisted requests are stored in plain text, so they must not contain secrets.
+/// For example the kafka services are given the path of the file containing their
+/// record key secret, which is read again when the node is restarted.
+impl NodeManager {
+    /// Persist the request which created the resource `name` at `path`
+    pub(super) fn persist_resource<T: Encode<()>>(&self, path: &str, name: &str, request: &T) {
+        if !self.persistent_resources {
+            return;
+        }
+        if let Err(e) = self.add_resource(path, name, request) {
+            warn!(%path, %name, %e, "Failed to persist a node resource");
+        }
+    }
+
+    /// Remove the persisted resource `name` at `path`, once it has been deleted
+    pub(super) fn forget_resource(&self, path: &str, name: &str) {
+        if !self.persistent_resources {
+            return;
+        }
+        let result = self
+            .cli_state
+            .nodes
+            .get(&self.node_name)
+            .and_then(|node_state| node_state.remove_resource(path, name));
+        if let Err(e) = result {
+            warn!(%path, %name, %e, "Failed to remove a persisted node resource");
+        }
+    }
+
+    fn add_resource<T: Encode<()>>(&self, path: &str, name: &str, request: &T) -> Result<()> {
+        let body = minicbor::to_vec(request)
+            .map_err(|e| ApiError::core(format!("Failed to encode the resource: {e}")))?;
+        let node_state = self.cli_state.nodes.get(&self.node_name)?;
+        node_state.add_resource(NodeResource::new(path, name, &body))?;
+        debug!(%path, %name, "Persisted a node resource");
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use std::net::IpAddr;
+use std::path::Path;
 
 use minicbor::Decoder;
 
@@ -8,6 +9,7 @@ use ockam_abac::expr::{eq, ident, str};
 use ockam_abac::Resource;
 use ockam_core::api::{Error, RequestHeader, Response};
 use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::sync::Arc;
 use ockam_core::route;
 use ockam_multiaddr::MultiAddr;
 use ockam_node::WorkerBuilder;
@@ -17,7 +19,8 @@ use crate::echoer::Echoer;
 use crate::error::ApiError;
 use crate::hop::Hop;
 use crate::kafka::{
-    ConsumerNodeAddr, KafkaInletController, KafkaPortalListener, KafkaSecureChannelControllerImpl,
+    ConsumerNodeAddr, KafkaDataKeyService, KafkaDataKeys, KafkaInletController,
+    KafkaPortalListener, KafkaSecureChannelControllerImpl, RecordKeyEncryptor, TopicPolicies,
     KAFKA_OUTLET_BOOTSTRAP_ADDRESS, KAFKA_OUTLET_INTERCEPTOR_ADDRESS,
 };
 use crate::kafka::{OutletManagerService, PrefixRelayService};
@@ -310,6 +313,11 @@ impl NodeManagerWorker {
                 )
                 .await;
         }
+        self.node_manager.persist_resource(
+            &kafka_service_path(&KafkaServiceKind::Outlet),
+            body.address(),
+            &body,
+        );
 
         Ok(Response::ok(request).to_vec()?)
     }
@@ -330,6 +338,10 @@ impl NodeManagerWorker {
             } else {
                 None
             };
+        let record_key_encryptor = body_req
+            .record_key_secret_file()
+            .map(|path| RecordKeyEncryptor::from_file(Path::new(path)))
+            .transpose()?;
 
         if let Err(e) = self
             .start_direct_kafka_service_impl(
@@ -340,11 +352,17 @@ impl NodeManagerWorker {
                 body_req.brokers_port_range(),
                 *body_req.bootstrap_server_addr(),
                 consumer_route,
+                record_key_encryptor,
             )
             .await
         {
             return Ok(e.to_vec()?);
         };
+        self.node_manager.persist_resource(
+            &kafka_service_path(&KafkaServiceKind::Direct),
+            body.address(),
+            &body,
+        );
 
         Ok(Response::ok(req).to_vec()?)
     }
@@ -359,6 +377,7 @@ impl NodeManagerWorker {
         brokers_port_range: (u16, u16),
         bootstrap_server_addr: SocketAddr,
         consumer_route: Option<MultiAddr>,
+        record_key_encryptor: Option<RecordKeyEncryptor>,
     ) -> Result<(), Response<Error>> {
         let default_secure_channel_listener_flow_control_id = context
             .flow_controls()
@@ -431,6 +450,7 @@ impl NodeManagerWorker {
             context,
             inlet_controller,
             secure_channel_controller.into_trait(),
+            record_key_encryptor,
             local_interceptor_address.clone(),
         )
         .await?;
@@ -459,6 +479,10 @@ impl NodeManagerWorker {
         let listener_address: Address = body.address().into();
         let body_req = body.request();
         let outlet_node_multiaddr = body_req.project_route().to_string().parse()?;
+        let record_key_encryptor = body_req
+            .record_key_secret_file()
+            .map(|path| RecordKeyEncryptor::from_file(Path::new(path)))
+            .transpose()?;
 
         if let Err(e) = self
             .start_kafka_service_impl(
@@ -469,11 +493,18 @@ impl NodeManagerWorker {
                 body_req.brokers_port_range(),
                 outlet_node_multiaddr,
                 KafkaServiceKind::Consumer,
+                record_key_encryptor,
+                body_req.fan_out(),
             )
             .await
         {
             return Ok(e.to_vec()?);
         };
+        self.node_manager.persist_resource(
+            &kafka_service_path(&KafkaServiceKind::Consumer),
+            body.address(),
+            &body,
+        );
 
         Ok(Response::ok(req).to_vec()?)
     }
@@ -488,6 +519,10 @@ impl NodeManagerWorker {
         let listener_address: Address = body.address().into();
         let body_req = body.request();
         let outlet_node_multiaddr = body_req.project_route().to_string().parse()?;
+        let record_key_encryptor = body_req
+            .record_key_secret_file()
+            .map(|path| RecordKeyEncryptor::from_file(Path::new(path)))
+            .transpose()?;
 
         if let Err(e) = self
             .start_kafka_service_impl(
@@ -498,11 +533,18 @@ impl NodeManagerWorker {
                 body_req.brokers_port_range(),
                 outlet_node_multiaddr,
                 KafkaServiceKind::Producer,
+                record_key_encryptor,
+                body_req.fan_out(),
             )
             .await
         {
             return Ok(e.to_vec()?);
         };
+        self.node_manager.persist_resource(
+            &kafka_service_path(&KafkaServiceKind::Producer),
+            body.address(),
+            &body,
+        );
 
         Ok(Response::ok(req).to_vec()?)
     }
@@ -517,6 +559,8 @@ impl NodeManagerWorker {
         brokers_port_range: (u16, u16),
         outlet_node_multiaddr: MultiAddr,
         kind: KafkaServiceKind,
+        record_key_encryptor: Option<RecordKeyEncryptor>,
+        fan_out: bool,
     ) -> Result<(), Response<Error>> {
         debug!(
             "outlet_node_multiaddr: {}",
@@ -548,12 +592,44 @@ impl NodeManagerWorker {
             }
         }
 
-        let secure_channel_controller = KafkaSecureChannelControllerImpl::new(
-            secure_channels,
+        let mut secure_channel_controller = KafkaSecureChannelControllerImpl::new(
+            secure_channels.clone(),
             ConsumerNodeAddr::Relay(outlet_node_multiaddr.clone()),
-            trust_context_id,
+            trust_context_id.clone(),
         );
 
+        // with data keys, the producer wraps the data keys of its topics for the consumers
+        // authorized by the topic policies, and registers the new consumers
+        if fan_out {
+            let topic_policies = TopicPolicies::new(
+                self.node_manager.policies.clone(),
+                self.node_manager.identities_repository(),
+                self.node_manager.identities().revocation_lists(),
+                trust_context_id.clone(),
+            );
+            let data_keys = Arc::new(KafkaDataKeys::new(
+                secure_channels,
+                self.node_manager.identifier().clone(),
+                self.node_manager.kafka_storage.clone(),
+                topic_policies,
+            ));
+            if kind == KafkaServiceKind::Producer {
+                let secure_channel_listener_flow_control_id = context
+                    .flow_controls()
+                    .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+                    .ok_or_else(|| {
+                        ApiError::core("Unable to get flow control for secure channel listener")
+                    })?;
+                KafkaDataKeyService::create(
+                    context,
+                    data_keys.clone(),
+                    &secure_channel_listener_flow_control_id,
+                )
+                .await?;
+            }
+            secure_channel_controller = secure_channel_controller.with_data_keys(data_keys);
+        }
+
         let inlet_controller = KafkaInletController::new(
             outlet_node_multiaddr.clone(),
             route![local_interceptor_address.clone()],
@@ -585,6 +661,7 @@ impl NodeManagerWorker {
             context,
             inlet_controller,
             secure_channel_controller.into_trait(),
+            record_key_encryptor,
             local_interceptor_address.clone(),
         )
         .await?;
@@ -635,6 +712,8 @@ impl NodeManagerWorker {
                         .kafka_services
                         .remove(&address)
                         .await;
+                    self.node_manager
+                        .forget_resource(&kafka_service_path(&kind), address.address());
                     Response::ok(req)
                 } else {
                     error!(address = %address, "Service is not a kafka {}", kind.to_string());
@@ -749,3 +828,14 @@ impl NodeManagerWorker {
         list
     }
 }
+
+/// Path of the node manager requests starting a kafka service of the given kind
+fn kafka_service_path(kind: &KafkaServiceKind) -> String {
+    let service = match kind {
+        KafkaServiceKind::Consumer => DefaultAddress::KAFKA_CONSUMER,
+        KafkaServiceKind::Producer => DefaultAddress::KAFKA_PRODUCER,
+        KafkaServiceKind::Outlet => DefaultAddress::KAFKA_OUTLET,
+        KafkaServiceKind::Direct => DefaultAddress::KAFKA_DIRECT,
+    };
+    format!("/node/services/{service}")
+}
@@ -20,7 +20,8 @@ use crate::config::lookup::ProjectLookup;
 use crate::error::ApiError;
 use crate::nodes::connection::Connection;
 use crate::nodes::models::portal::{
-    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
+    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletLoadBalancing,
+    OutletStatus,
 };
 use crate::nodes::registry::{InletInfo, OutletInfo};
 use crate::nodes::service::random_alias;
@@ -30,6 +31,9 @@ use crate::{actions, resources, DefaultAddress};
 
 use super::{NodeManager, NodeManagerWorker};
 
+/// The maximum time a target of a load-balanced outlet has to accept a health check connection
+const OUTLET_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// INLETS
 impl NodeManagerWorker {
     pub(super) async fn get_inlets(&self, req: &RequestHeader) -> Response<InletList> {
@@ -43,7 +47,7 @@ impl NodeManagerWorker {
         dec: &mut Decoder<'_>,
         ctx: &Context,
     ) -> Result<Response<InletStatus>, Response<Error>> {
-        let create_inlet_req: CreateInlet = dec.decode()?;
+        let mut create_inlet_req: CreateInlet = dec.decode()?;
         let CreateInlet {
             listen_addr,
             outlet_addr,
@@ -52,7 +56,7 @@ impl NodeManagerWorker {
             prefix_route,
             suffix_route,
             wait_for_outlet_duration,
-        } = create_inlet_req;
+        } = create_inlet_req.clone();
         match self
             .node_manager
             .create_inlet(
@@ -67,7 +71,12 @@ impl NodeManagerWorker {
             )
             .await
         {
-            Ok(status) => Ok(Response::ok(req).body(status)),
+            Ok(status) => {
+                create_inlet_req.set_alias(&status.alias);
+                self.node_manager
+                    .persist_resource("/node/inlet", &status.alias, &create_inlet_req);
+                Ok(Response::ok(req).body(status))
+            }
             Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
         }
     }
@@ -78,7 +87,10 @@ impl NodeManagerWorker {
         alias: &str,
     ) -> Result<Response<InletStatus>, Response<Error>> {
         match self.node_manager.delete_inlet(alias).await {
-            Ok(status) => Ok(Response::ok(req).body(status)),
+            Ok(status) => {
+                self.node_manager.forget_resource("/node/inlet", alias);
+                Ok(Response::ok(req).body(status))
+            }
             Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
         }
     }
@@ -111,21 +123,33 @@ impl NodeManagerWorker {
             worker_addr,
             alias,
             reachable_from_default_secure_channel,
-            ..
-        } = create_outlet;
+            load_balancing,
+        } = create_outlet.clone();
 
         match self
             .node_manager
-            .create_outlet(
+            .create_load_balanced_outlet(
                 ctx,
                 socket_addr,
                 worker_addr,
                 alias,
                 reachable_from_default_secure_channel,
+                load_balancing,
             )
             .await
         {
-            Ok(outlet_status) => Ok(Response::ok(req).body(outlet_status)),
+            Ok(outlet_status) => {
+                let create_outlet = CreateOutlet {
+                    alias: Some(outlet_status.alias.clone()),
+                    ..create_outlet
+                };
+                self.node_manager.persist_resource(
+                    "/node/outlet",
+                    &outlet_status.alias,
+                    &create_outlet,
+                );
+                Ok(Response::ok(req).body(outlet_status))
+            }
             Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
         }
     }
@@ -137,12 +161,10 @@ impl NodeManagerWorker {
     ) -> Result<Response<OutletStatus>, Response<Error>> {
         match self.node_manager.delete_outlet(alias).await {
             Ok(res) => match res {
-                Some(outlet_info) => Ok(Response::ok(req).body(OutletStatus::new(
-                    outlet_info.socket_addr,
-                    outlet_info.worker_addr.clone(),
-                    alias,
-                    None,
-                ))),
+                Some(outlet_info) => {
+                    self.node_manager.forget_resource("/node/outlet", alias);
+                    Ok(Response::ok(req).body(outlet_info.status(alias)))
+                }
                 None => Err(Response::bad_request(
                     req,
                     &format!("Outlet with alias {alias} not found"),
@@ -180,6 +202,28 @@ impl NodeManager {
         worker_addr: Address,
         alias: Option<String>,
         reachable_from_default_secure_channel: bool,
+    ) -> Result<OutletStatus> {
+        self.create_load_balanced_outlet(
+            ctx,
+            socket_addr,
+            worker_addr,
+            alias,
+            reachable_from_default_secure_channel,
+            None,
+        )
+        .await
+    }
+
+    /// Create an outlet which distributes its portal connections between `socket_addr`
+    /// and the additional targets of `load_balancing`, if any
+    pub async fn create_load_balanced_outlet(
+        &self,
+        ctx: &Context,
+        socket_addr: SocketAddr,
+        worker_addr: Address,
+        alias: Option<String>,
+        reachable_from_default_secure_channel: bool,
+        load_balancing: Option<OutletLoadBalancing>,
     ) -> Result<OutletStatus> {
         info!(
             "Handling request to create outlet portal at {:?}",
@@ -234,23 +278,39 @@ impl NodeManager {
             options
         };
 
+        let mut targets = vec![socket_addr];
+        let options = match &load_balancing {
+            Some(load_balancing) => {
+                targets.extend(load_balancing.additional_targets.iter().copied());
+                let options = options.with_strategy(load_balancing.strategy.into());
+                match load_balancing.health_check_interval {
+                    Some(interval) => options
+                        .with_health_check(interval, interval.min(OUTLET_HEALTH_CHECK_TIMEOUT)),
+                    None => options,
+                }
+            }
+            None => options,
+        };
+
         let res = self
             .tcp_transport
-            .create_tcp_outlet(worker_addr.clone(), socket_addr, options)
+            .create_tcp_outlet_with_targets(worker_addr.clone(), targets, options)
             .await;
 
         Ok(match res {
-            Ok(_) => {
+            Ok(targets) => {
+                let outlet_info = OutletInfo::new(&socket_addr, Some(&worker_addr));
+                let outlet_info = if load_balancing.is_some() {
+                    outlet_info.with_targets(targets)
+                } else {
+                    outlet_info
+                };
+                let outlet_status = outlet_info.status(alias.clone());
+
                 // TODO: Use better way to store outlets?
-                self.registry
-                    .outlets
-                    .insert(
-                        alias.clone(),
-                        OutletInfo::new(&socket_addr, Some(&worker_addr)),
-                    )
-                    .await;
+                self.registry.outlets.insert(alias, outlet_info).await;
 
-                OutletStatus::new(socket_addr, worker_addr, alias, None)
+                outlet_status
             }
             Err(e) => {
                 warn!(at = %socket_addr, err = %e, "Failed to create TCP outlet");
@@ -287,12 +347,7 @@ impl NodeManager {
         info!(%alias, "Handling request to show outlet portal");
         if let Some(outlet_to_show) = self.registry.outlets.get(alias).await {
             debug!(%alias, "Outlet not found in node registry");
-            Some(OutletStatus::new(
-                outlet_to_show.socket_addr,
-                outlet_to_show.worker_addr.clone(),
-                alias,
-                None,
-            ))
+            Some(outlet_to_show.status(alias))
         } else {
             error!(%alias, "Outlet not found in the node registry");
             None
@@ -353,39 +408,21 @@ impl NodeManager {
             }
         }
 
-        let outlet_route = connection.route(self.tcp_transport()).await?;
+        let outlet_route = connection.route(self).await?;
         let outlet_route = route![prefix_route.clone(), outlet_route, suffix_route.clone()];
 
-        let projects = self.cli_state.projects.list()?;
-        let projects = ProjectLookup::from_state(projects)
-            .await
-            .map_err(|e| ockam_core::Error::new(Origin::Node, Kind::NotFound, e))?;
-        let check_credential = self.enable_credential_checks;
-        let project_id = if check_credential {
-            let pid = outlet_addr
-                .first()
-                .and_then(|p| {
-                    if let Some(p) = p.cast::<Project>() {
-                        projects.get(&*p).map(|info| &*info.id)
-                    } else {
-                        None
-                    }
-                })
-                .or_else(|| Some(self.trust_context().ok()?.id()));
-            if pid.is_none() {
-                let message = "Credential check requires a project or trust context";
-                return Err(ockam_core::Error::new(Origin::Node, Kind::Invalid, message));
-            }
-            pid
-        } else {
-            None
-        };
+        let project_id = self.inlet_project_id(&outlet_addr).await?;
 
         let resource = requested_alias
             .map(|a| Resource::new(a.as_str()))
             .unwrap_or(resources::INLET);
         let access_control = self
-            .access_control(&resource, &actions::HANDLE_MESSAGE, project_id, None)
+            .access_control(
+                &resource,
+                &actions::HANDLE_MESSAGE,
+                project_id.as_deref(),
+                None,
+            )
             .await?;
 
         let options = TcpInletOptions::new().with_incoming_access_control(access_control.clone());
@@ -431,6 +468,34 @@ impl NodeManager {
         })
     }
 
+    /// Return the id of the project used to check the credentials of an outlet,
+    /// if credential checks are enabled
+    pub(super) async fn inlet_project_id(&self, outlet_addr: &MultiAddr) -> Result<Option<String>> {
+        if !self.enable_credential_checks {
+            return Ok(None);
+        }
+
+        let projects = self.cli_state.projects.list()?;
+        let projects = ProjectLookup::from_state(projects)
+            .await
+            .map_err(|e| ockam_core::Error::new(Origin::Node, Kind::NotFound, e))?;
+        let pid = outlet_addr
+            .first()
+            .and_then(|p| {
+                if let Some(p) = p.cast::<Project>() {
+                    projects.get(&*p).map(|info| info.id.clone())
+                } else {
+                    None
+                }
+            })
+            .or_else(|| Some(self.trust_context().ok()?.id().to_string()));
+        if pid.is_none() {
+            let message = "Credential check requires a project or trust context";
+            return Err(ockam_core::Error::new(Origin::Node, Kind::Invalid, message));
+        }
+        Ok(pid)
+    }
+
     pub async fn delete_inlet(&self, alias: &str) -> Result<InletStatus> {
         info!(%alias, "Handling request to delete inlet portal");
         if let Some(inlet_to_delete) = self.registry.inlets.remove(alias).await {
@@ -551,7 +616,7 @@ impl InMemoryNode {
                 outlet_addr.clone(),
             )
             .await?;
-        if !connection.route(self.tcp_transport()).await?.is_empty() {
+        if !connection.route(self).await?.is_empty() {
             debug! {
                 %inlet.alias,
                 %inlet.bind_addr,
@@ -657,8 +722,7 @@ impl InMemoryNode {
                         )
                         .await?;
                     *connection_arc.lock().unwrap() = new_connection.clone();
-                    let connection_route =
-                        new_connection.route(node_manager.tcp_transport()).await?;
+                    let connection_route = new_connection.route(&node_manager).await?;
 
                     //we expect a fully normalized MultiAddr
                     let normalized_route = route![prefix_route, connection_route, suffix_route];
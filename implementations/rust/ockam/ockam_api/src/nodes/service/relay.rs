@@ -15,7 +15,7 @@ use ockam_node::Context;
 
 use crate::error::ApiError;
 use crate::nodes::connection::Connection;
-use crate::nodes::models::relay::{CreateRelay, RelayInfo};
+use crate::nodes::models::relay::{CreateRelay, HostedRelayInfo, RelayInfo};
 use crate::nodes::models::secure_channel::{
     CreateSecureChannelRequest, CreateSecureChannelResponse,
 };
@@ -38,13 +38,20 @@ impl NodeManagerWorker {
             alias,
             at_rust_node,
             authorized,
-        } = create_relay;
+        } = create_relay.clone();
         match self
             .node_manager
             .create_relay(ctx, &address, alias, at_rust_node, authorized)
             .await
         {
-            Ok(body) => Ok(Response::ok(req).body(body)),
+            Ok(body) => {
+                self.node_manager.persist_resource(
+                    "/node/forwarder",
+                    body.remote_address(),
+                    &create_relay,
+                );
+                Ok(Response::ok(req).body(body))
+            }
             Err(err) => Err(Response::internal_error(
                 req,
                 &format!("Failed to create relay: {}", err),
@@ -58,9 +65,15 @@ impl NodeManagerWorker {
         req: &RequestHeader,
         remote_address: &str,
     ) -> Result<Response<Option<RelayInfo>>, Response<Error>> {
-        self.node_manager
+        let response = self
+            .node_manager
             .delete_relay(ctx, req, remote_address)
-            .await
+            .await;
+        if response.is_ok() {
+            self.node_manager
+                .forget_resource("/node/forwarder", remote_address);
+        }
+        response
     }
 
     pub async fn show_relay(
@@ -78,6 +91,14 @@ impl NodeManagerWorker {
         debug!("Handling GetRelays request");
         Ok(Response::ok(req).body(self.node_manager.get_relays().await))
     }
+
+    pub async fn get_hosted_relays(
+        &self,
+        req: &RequestHeader,
+    ) -> Result<Response<Vec<HostedRelayInfo>>, Response<Error>> {
+        debug!("Handling GetHostedRelays request");
+        Ok(Response::ok(req).body(self.node_manager.get_hosted_relays()))
+    }
 }
 
 impl NodeManager {
@@ -96,6 +117,16 @@ impl NodeManager {
         relays
     }
 
+    /// This function returns the relays registered by other nodes
+    /// with the relay service of this node
+    pub fn get_hosted_relays(&self) -> Vec<HostedRelayInfo> {
+        self.relay_registry
+            .list()
+            .into_iter()
+            .map(HostedRelayInfo::from)
+            .collect()
+    }
+
     /// Create a new Relay
     /// The Connection encapsulates the list of workers required on the relay route.
     /// This route is monitored in the `InMemoryNode` and the workers are restarted if necessary
@@ -107,7 +138,7 @@ impl NodeManager {
         at_rust_node: bool,
         alias: Option<String>,
     ) -> Result<RelayInfo> {
-        let route = connection.route(self.tcp_transport()).await?;
+        let route = connection.route(self).await?;
         let options = RemoteRelayOptions::new();
 
         let relay = if at_rust_node {
@@ -319,7 +350,7 @@ impl InMemoryNode {
                     connection.add_default_consumers(ctx.clone());
                     *connection_arc.lock().unwrap() = connection.clone();
 
-                    let route = connection.route(node_manager.tcp_transport()).await?;
+                    let route = connection.route(&node_manager).await?;
 
                     let options = RemoteRelayOptions::new();
                     if let Some(alias) = &alias {
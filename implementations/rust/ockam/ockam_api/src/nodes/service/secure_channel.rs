@@ -8,7 +8,7 @@ use ockam::identity::TrustEveryonePolicy;
 use ockam::identity::Vault;
 use ockam::identity::{
     Identifier, Identities, SecureChannelListenerOptions, SecureChannelOptions, SecureChannels,
-    TrustMultiIdentifiersPolicy,
+    TimestampInSeconds, TrustMultiIdentifiersPolicy,
 };
 use ockam::identity::{SecureChannel, SecureChannelListener};
 use ockam::{Address, Result, Route};
@@ -133,7 +133,10 @@ impl NodeManagerWorker {
         let body: ShowSecureChannelRequest = dec.decode()?;
         let sc_address = Address::from(body.channel);
         let info = self.node_manager.get_secure_channel(&sc_address).await;
-        Ok(Response::ok(req).body(ShowSecureChannelResponse::new(info)))
+        let last_activity = self
+            .node_manager
+            .get_secure_channel_last_activity(&sc_address);
+        Ok(Response::ok(req).body(ShowSecureChannelResponse::new(info, last_activity)))
     }
 }
 
@@ -145,13 +148,14 @@ impl NodeManagerWorker {
         dec: &mut Decoder<'_>,
         ctx: &Context,
     ) -> Result<Response<()>, Response<Error>> {
+        let create_listener: CreateSecureChannelListenerRequest = dec.decode()?;
         let CreateSecureChannelListenerRequest {
             addr,
             authorized_identifiers,
             vault,
             identity,
             ..
-        } = dec.decode()?;
+        } = create_listener.clone();
 
         let authorized_identifiers = match authorized_identifiers {
             Some(ids) => {
@@ -174,8 +178,19 @@ impl NodeManagerWorker {
         }
 
         self.node_manager
-            .create_secure_channel_listener(addr, authorized_identifiers, vault, identity, ctx)
+            .create_secure_channel_listener(
+                addr.clone(),
+                authorized_identifiers,
+                vault,
+                identity,
+                ctx,
+            )
             .await?;
+        self.node_manager.persist_resource(
+            "/node/secure_channel_listener",
+            &addr.to_string(),
+            &create_listener,
+        );
 
         Ok(Response::ok(req))
     }
@@ -196,6 +211,8 @@ impl NodeManagerWorker {
             {
                 Some(_) => {
                     trace!(%addr, "Removed secure channel listener");
+                    self.node_manager
+                        .forget_resource("/node/secure_channel_listener", &addr.to_string());
                     Response::ok(req)
                         .body(DeleteSecureChannelListenerResponse::new(addr))
                         .to_vec()?
@@ -279,7 +296,7 @@ impl NodeManager {
         let sc = self
             .create_secure_channel_internal(
                 ctx,
-                connection.route(self.tcp_transport()).await?,
+                connection.route(self).await?,
                 &identifier,
                 authorized_identifiers,
                 timeout,
@@ -391,13 +408,39 @@ impl NodeManager {
 
     pub async fn get_secure_channel(&self, addr: &Address) -> Option<SecureChannelInfo> {
         debug!(%addr, "On show secure channel");
+        self.remove_stopped_secure_channels().await;
         self.registry.secure_channels.get_by_addr(addr).await
     }
 
+    /// Return the last time a message was received over a secure channel
+    pub fn get_secure_channel_last_activity(&self, addr: &Address) -> Option<TimestampInSeconds> {
+        self.secure_channels
+            .secure_channel_registry()
+            .get_channel_by_encryptor_address(addr)
+            .map(|entry| entry.last_activity())
+    }
+
     pub async fn list_secure_channels(&self) -> Vec<SecureChannelInfo> {
+        self.remove_stopped_secure_channels().await;
         let registry = &self.registry.secure_channels;
         registry.list().await
     }
+
+    /// Secure channels can stop by themselves, for example when they are idle.
+    /// Remove them from the node registry
+    async fn remove_stopped_secure_channels(&self) {
+        let identity_registry = self.secure_channels.secure_channel_registry();
+        for info in self.registry.secure_channels.list().await {
+            let addr = info.sc().encryptor_address();
+            if identity_registry
+                .get_channel_by_encryptor_address(addr)
+                .is_none()
+            {
+                debug!(%addr, "removing a stopped secure channel");
+                self.registry.secure_channels.remove_by_addr(addr).await;
+            }
+        }
+    }
 }
 
 /// SECURE CHANNEL LISTENERS
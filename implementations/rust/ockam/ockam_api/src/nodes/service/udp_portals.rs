@@ -0,0 +1,493 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ockam::{Address, Result};
+use ockam_abac::Resource;
+use ockam_core::api::{Error, RequestHeader, Response};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::AsyncTryClone;
+use ockam_multiaddr::MultiAddr;
+use ockam_node::Context;
+use ockam_transport_udp::{UdpInletOptions, UdpOutletOptions};
+
+use crate::nodes::connection::Connection;
+use crate::nodes::models::portal::{
+    CreateUdpInlet, CreateUdpOutlet, InletList, InletStatus, OutletList, OutletStatus,
+};
+use crate::nodes::registry::{InletInfo, OutletInfo};
+use crate::nodes::service::random_alias;
+use crate::nodes::InMemoryNode;
+use crate::{actions, resources, DefaultAddress};
+
+use super::{NodeManager, NodeManagerWorker};
+
+/// UDP INLETS
+impl NodeManagerWorker {
+    pub(super) async fn get_udp_inlets(&self, req: &RequestHeader) -> Response<InletList> {
+        Response::ok(req).body(self.node_manager.list_udp_inlets().await)
+    }
+
+    pub(super) async fn create_udp_inlet(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        mut create_inlet: CreateUdpInlet,
+    ) -> Result<Response<InletStatus>, Response<Error>> {
+        match self
+            .node_manager
+            .create_udp_inlet(ctx, create_inlet.clone())
+            .await
+        {
+            Ok(status) => {
+                create_inlet.set_alias(&status.alias);
+                self.node_manager
+                    .persist_resource("/node/udp-inlet", &status.alias, &create_inlet);
+                Ok(Response::ok(req).body(status))
+            }
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn delete_udp_inlet(
+        &self,
+        req: &RequestHeader,
+        alias: &str,
+    ) -> Result<Response<InletStatus>, Response<Error>> {
+        match self.node_manager.delete_udp_inlet(alias).await {
+            Ok(status) => {
+                self.node_manager.forget_resource("/node/udp-inlet", alias);
+                Ok(Response::ok(req).body(status))
+            }
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn show_udp_inlet(
+        &self,
+        req: &RequestHeader,
+        alias: &str,
+    ) -> Result<Response<InletStatus>, Response<Error>> {
+        match self.node_manager.show_udp_inlet(alias).await {
+            Some(inlet) => Ok(Response::ok(req).body(inlet)),
+            None => Err(Response::not_found(
+                req,
+                &format!("UDP inlet with alias {alias} not found"),
+            )),
+        }
+    }
+}
+
+/// UDP OUTLETS
+impl NodeManagerWorker {
+    pub(super) async fn get_udp_outlets(&self, req: &RequestHeader) -> Response<OutletList> {
+        Response::ok(req).body(self.node_manager.list_udp_outlets().await)
+    }
+
+    pub(super) async fn create_udp_outlet(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        create_outlet: CreateUdpOutlet,
+    ) -> Result<Response<OutletStatus>, Response<Error>> {
+        let CreateUdpOutlet {
+            socket_addr,
+            worker_addr,
+            alias,
+            idle_timeout,
+        } = create_outlet.clone();
+
+        match self
+            .node_manager
+            .create_udp_outlet(ctx, socket_addr, worker_addr, alias, idle_timeout)
+            .await
+        {
+            Ok(outlet_status) => {
+                let create_outlet = CreateUdpOutlet {
+                    alias: Some(outlet_status.alias.clone()),
+                    ..create_outlet
+                };
+                self.node_manager.persist_resource(
+                    "/node/udp-outlet",
+                    &outlet_status.alias,
+                    &create_outlet,
+                );
+                Ok(Response::ok(req).body(outlet_status))
+            }
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn delete_udp_outlet(
+        &self,
+        req: &RequestHeader,
+        alias: &str,
+    ) -> Result<Response<OutletStatus>, Response<Error>> {
+        match self.node_manager.delete_udp_outlet(alias).await {
+            Ok(status) => {
+                self.node_manager.forget_resource("/node/udp-outlet", alias);
+                Ok(Response::ok(req).body(status))
+            }
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn show_udp_outlet(
+        &self,
+        req: &RequestHeader,
+        alias: &str,
+    ) -> Result<Response<OutletStatus>, Response<Error>> {
+        match self.node_manager.show_udp_outlet(alias).await {
+            Some(outlet) => Ok(Response::ok(req).body(outlet)),
+            None => Err(Response::not_found(
+                req,
+                &format!("UDP outlet with alias {alias} not found"),
+            )),
+        }
+    }
+}
+
+/// UDP OUTLETS
+impl NodeManager {
+    pub async fn create_udp_outlet(
+        &self,
+        ctx: &Context,
+        socket_addr: SocketAddr,
+        worker_addr: Address,
+        alias: Option<String>,
+        idle_timeout: Option<Duration>,
+    ) -> Result<OutletStatus> {
+        info!(
+            "Handling request to create UDP outlet portal at {:?}",
+            socket_addr
+        );
+        let udp_transport = self.udp_transport()?;
+        let resource = alias
+            .as_deref()
+            .map(Resource::new)
+            .unwrap_or(resources::UDP_OUTLET);
+
+        let alias = alias.unwrap_or_else(random_alias);
+
+        // Check that there is no entry in the registry with the same alias
+        if self.registry.udp_outlets.contains_key(&alias).await {
+            let message = format!("A UDP outlet with alias '{alias}' already exists");
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::AlreadyExists,
+                message,
+            ));
+        }
+
+        let check_credential = self.enable_credential_checks;
+        let trust_context_id = if check_credential {
+            Some(self.trust_context()?.id())
+        } else {
+            None
+        };
+
+        let access_control = self
+            .access_control(&resource, &actions::HANDLE_MESSAGE, trust_context_id, None)
+            .await?;
+
+        let mut options = UdpOutletOptions::new().with_incoming_access_control(access_control);
+        if let Some(idle_timeout) = idle_timeout {
+            options = options.with_idle_timeout(idle_timeout);
+        }
+        if !check_credential {
+            options = options.as_consumer(&self.api_transport_flow_control_id);
+        }
+        // Accept messages from the default secure channel listener
+        if let Some(flow_control_id) = ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+        {
+            options = options.as_consumer(&flow_control_id);
+        }
+
+        if let Err(e) = udp_transport
+            .create_outlet(worker_addr.clone(), socket_addr.to_string(), options)
+            .await
+        {
+            warn!(at = %socket_addr, err = %e, "Failed to create UDP outlet");
+            let message = format!("Failed to create UDP outlet: {}", e);
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::Internal,
+                message,
+            ));
+        }
+
+        self.registry
+            .udp_outlets
+            .insert(
+                alias.clone(),
+                OutletInfo::new(&socket_addr, Some(&worker_addr)),
+            )
+            .await;
+
+        Ok(OutletStatus::new(socket_addr, worker_addr, alias, None))
+    }
+
+    pub async fn delete_udp_outlet(&self, alias: &str) -> Result<OutletStatus> {
+        info!(%alias, "Handling request to delete UDP outlet portal");
+        match self.registry.udp_outlets.remove(alias).await {
+            Some(deleted_outlet) => {
+                debug!(%alias, "Successfully removed UDP outlet from node registry");
+                if let Err(e) = self
+                    .udp_transport()?
+                    .stop_outlet(deleted_outlet.worker_addr.clone())
+                    .await
+                {
+                    warn!(%alias, %e, "Failed to stop UDP outlet worker");
+                }
+                Ok(OutletStatus::new(
+                    deleted_outlet.socket_addr,
+                    deleted_outlet.worker_addr,
+                    alias,
+                    None,
+                ))
+            }
+            None => {
+                warn!(%alias, "UDP outlet not found in the node registry");
+                let message = format!("UDP outlet with alias {alias} not found");
+                Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::NotFound,
+                    message,
+                ))
+            }
+        }
+    }
+
+    pub(super) async fn show_udp_outlet(&self, alias: &str) -> Option<OutletStatus> {
+        info!(%alias, "Handling request to show UDP outlet portal");
+        self.registry
+            .udp_outlets
+            .get(alias)
+            .await
+            .map(|outlet| OutletStatus::new(outlet.socket_addr, outlet.worker_addr, alias, None))
+    }
+
+    pub async fn list_udp_outlets(&self) -> OutletList {
+        OutletList::new(
+            self.registry
+                .udp_outlets
+                .entries()
+                .await
+                .iter()
+                .map(|(alias, info)| {
+                    OutletStatus::new(info.socket_addr, info.worker_addr.clone(), alias, None)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// UDP INLETS
+impl NodeManager {
+    pub async fn create_udp_inlet(
+        &self,
+        connection: Connection,
+        listen_addr: String,
+        requested_alias: Option<String>,
+        outlet_addr: MultiAddr,
+        idle_timeout: Option<Duration>,
+    ) -> Result<InletStatus> {
+        info!("Handling request to create UDP inlet portal");
+        let udp_transport = self.udp_transport()?;
+
+        let alias = requested_alias.clone().unwrap_or_else(random_alias);
+        debug! {
+            listen_addr = %listen_addr,
+            outlet_addr = %outlet_addr,
+            %alias,
+            "Creating UDP inlet portal"
+        }
+
+        {
+            let registry = &self.registry.udp_inlets;
+
+            // Check that there is no entry in the registry with the same alias
+            if registry.contains_key(&alias).await {
+                let message = format!("A UDP inlet with alias '{alias}' already exists");
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::AlreadyExists,
+                    message,
+                ));
+            }
+
+            // Check that there is no entry in the registry with the same UDP bind address
+            if registry
+                .values()
+                .await
+                .iter()
+                .any(|inlet| inlet.bind_addr == listen_addr)
+            {
+                let message =
+                    format!("A UDP inlet with bind udp address '{listen_addr}' already exists");
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::AlreadyExists,
+                    message,
+                ));
+            }
+        }
+
+        let outlet_route = connection.route(self).await?;
+
+        let project_id = self.inlet_project_id(&outlet_addr).await?;
+        let resource = requested_alias
+            .map(|a| Resource::new(a.as_str()))
+            .unwrap_or(resources::UDP_INLET);
+        let access_control = self
+            .access_control(
+                &resource,
+                &actions::HANDLE_MESSAGE,
+                project_id.as_deref(),
+                None,
+            )
+            .await?;
+
+        let mut options = UdpInletOptions::new().with_incoming_access_control(access_control);
+        if let Some(idle_timeout) = idle_timeout {
+            options = options.with_idle_timeout(idle_timeout);
+        }
+
+        match udp_transport
+            .create_inlet(listen_addr, outlet_route.clone(), options)
+            .await
+        {
+            Ok((socket_address, worker_addr)) => {
+                // when using 0 port, the chosen port will be populated
+                // in the returned socket address
+                let listen_addr = socket_address.to_string();
+
+                self.registry
+                    .udp_inlets
+                    .insert(
+                        alias.clone(),
+                        InletInfo::new(&listen_addr, Some(&worker_addr), &outlet_route),
+                    )
+                    .await;
+
+                Ok(InletStatus::new(
+                    listen_addr,
+                    worker_addr.to_string(),
+                    alias,
+                    None,
+                    outlet_route.to_string(),
+                ))
+            }
+            Err(e) => {
+                warn!(to = %outlet_addr, err = %e, "Failed to create UDP inlet");
+                let message = format!("Failed to create UDP inlet: {}", e);
+                Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Internal,
+                    message,
+                ))
+            }
+        }
+    }
+
+    pub async fn delete_udp_inlet(&self, alias: &str) -> Result<InletStatus> {
+        info!(%alias, "Handling request to delete UDP inlet portal");
+        match self.registry.udp_inlets.remove(alias).await {
+            Some(deleted_inlet) => {
+                debug!(%alias, "Successfully removed UDP inlet from node registry");
+                self.udp_transport()?
+                    .stop_inlet(deleted_inlet.worker_addr.clone())
+                    .await?;
+                Ok(InletStatus::new(
+                    deleted_inlet.bind_addr,
+                    deleted_inlet.worker_addr.to_string(),
+                    alias,
+                    None,
+                    deleted_inlet.outlet_route.to_string(),
+                ))
+            }
+            None => {
+                error!(%alias, "UDP inlet not found in the node registry");
+                let message = format!("UDP inlet with alias {alias} not found");
+                Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::NotFound,
+                    message,
+                ))
+            }
+        }
+    }
+
+    pub async fn show_udp_inlet(&self, alias: &str) -> Option<InletStatus> {
+        info!(%alias, "Handling request to show UDP inlet portal");
+        self.registry.udp_inlets.get(alias).await.map(|inlet| {
+            InletStatus::new(
+                inlet.bind_addr,
+                inlet.worker_addr.to_string(),
+                alias,
+                None,
+                inlet.outlet_route.to_string(),
+            )
+        })
+    }
+
+    pub async fn list_udp_inlets(&self) -> InletList {
+        InletList::new(
+            self.registry
+                .udp_inlets
+                .entries()
+                .await
+                .iter()
+                .map(|(alias, info)| {
+                    InletStatus::new(
+                        &info.bind_addr,
+                        info.worker_addr.to_string(),
+                        alias,
+                        None,
+                        info.outlet_route.to_string(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl InMemoryNode {
+    /// Create a UDP inlet, after establishing a connection to the outlet node.
+    ///
+    /// Unlike TCP inlets, UDP inlets are not monitored by a session: a
+    /// lost connection must be handled by deleting and creating the inlet again.
+    pub async fn create_udp_inlet(
+        &self,
+        ctx: &Context,
+        create_inlet: CreateUdpInlet,
+    ) -> Result<InletStatus> {
+        let CreateUdpInlet {
+            listen_addr,
+            outlet_addr,
+            alias,
+            authorized,
+            idle_timeout,
+            wait_for_outlet_duration,
+        } = create_inlet;
+
+        let duration = wait_for_outlet_duration.unwrap_or(Duration::from_secs(5));
+        let connection_ctx = Arc::new(ctx.async_try_clone().await?);
+        let connection = self
+            .make_connection(
+                connection_ctx,
+                &outlet_addr,
+                None,
+                authorized,
+                None,
+                Some(duration),
+            )
+            .await?;
+
+        self.node_manager
+            .create_udp_inlet(connection, listen_addr, alias, outlet_addr, idle_timeout)
+            .await
+    }
+}
@@ -0,0 +1,476 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ockam::{Address, Result};
+use ockam_abac::Resource;
+use ockam_core::api::{Error, RequestHeader, Response};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::AsyncTryClone;
+use ockam_multiaddr::MultiAddr;
+use ockam_node::Context;
+use ockam_transport_uds::{UdsInletOptions, UdsOutletOptions};
+
+use crate::nodes::connection::Connection;
+use crate::nodes::models::portal::{
+    CreateUdsInlet, CreateUdsOutlet, InletList, InletStatus, UdsOutletList, UdsOutletStatus,
+};
+use crate::nodes::registry::{InletInfo, UdsOutletInfo};
+use crate::nodes::service::random_alias;
+use crate::nodes::InMemoryNode;
+use crate::{actions, resources, DefaultAddress};
+
+use super::{NodeManager, NodeManagerWorker};
+
+/// UDS INLETS
+impl NodeManagerWorker {
+    pub(super) async fn get_uds_inlets(&self, req: &RequestHeader) -> Response<InletList> {
+        Response::ok(req).body(self.node_manager.list_uds_inlets().await)
+    }
+
+    pub(super) async fn create_uds_inlet(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        mut create_inlet: CreateUdsInlet,
+    ) -> Result<Response<InletStatus>, Response<Error>> {
+        match self
+            .node_manager
+            .create_uds_inlet(ctx, create_inlet.clone())
+            .await
+        {
+            Ok(status) => {
+                create_inlet.set_alias(&status.alias);
+                self.node_manager
+                    .persist_resource("/node/uds-inlet", &status.alias, &create_inlet);
+                Ok(Response::ok(req).body(status))
+            }
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn delete_uds_inlet(
+        &self,
+        req: &RequestHeader,
+        alias: &str,
+    ) -> Result<Response<InletStatus>, Response<Error>> {
+        match self.node_manager.delete_uds_inlet(alias).await {
+            Ok(status) => {
+                self.node_manager.forget_resource("/node/uds-inlet", alias);
+                Ok(Response::ok(req).body(status))
+            }
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn show_uds_inlet(
+        &self,
+        req: &RequestHeader,
+        alias: &str,
+    ) -> Result<Response<InletStatus>, Response<Error>> {
+        match self.node_manager.show_uds_inlet(alias).await {
+            Some(inlet) => Ok(Response::ok(req).body(inlet)),
+            None => Err(Response::not_found(
+                req,
+                &format!("UDS inlet with alias {alias} not found"),
+            )),
+        }
+    }
+}
+
+/// UDS OUTLETS
+impl NodeManagerWorker {
+    pub(super) async fn get_uds_outlets(&self, req: &RequestHeader) -> Response<UdsOutletList> {
+        Response::ok(req).body(self.node_manager.list_uds_outlets().await)
+    }
+
+    pub(super) async fn create_uds_outlet(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        create_outlet: CreateUdsOutlet,
+    ) -> Result<Response<UdsOutletStatus>, Response<Error>> {
+        let CreateUdsOutlet {
+            socket_path,
+            worker_addr,
+            alias,
+        } = create_outlet.clone();
+
+        match self
+            .node_manager
+            .create_uds_outlet(ctx, socket_path, worker_addr, alias)
+            .await
+        {
+            Ok(outlet_status) => {
+                let create_outlet = CreateUdsOutlet {
+                    alias: Some(outlet_status.alias.clone()),
+                    ..create_outlet
+                };
+                self.node_manager.persist_resource(
+                    "/node/uds-outlet",
+                    &outlet_status.alias,
+                    &create_outlet,
+                );
+                Ok(Response::ok(req).body(outlet_status))
+            }
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn delete_uds_outlet(
+        &self,
+        req: &RequestHeader,
+        alias: &str,
+    ) -> Result<Response<UdsOutletStatus>, Response<Error>> {
+        match self.node_manager.delete_uds_outlet(alias).await {
+            Ok(status) => {
+                self.node_manager.forget_resource("/node/uds-outlet", alias);
+                Ok(Response::ok(req).body(status))
+            }
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn show_uds_outlet(
+        &self,
+        req: &RequestHeader,
+        alias: &str,
+    ) -> Result<Response<UdsOutletStatus>, Response<Error>> {
+        match self.node_manager.show_uds_outlet(alias).await {
+            Some(outlet) => Ok(Response::ok(req).body(outlet)),
+            None => Err(Response::not_found(
+                req,
+                &format!("UDS outlet with alias {alias} not found"),
+            )),
+        }
+    }
+}
+
+/// UDS OUTLETS
+impl NodeManager {
+    pub async fn create_uds_outlet(
+        &self,
+        ctx: &Context,
+        socket_path: String,
+        worker_addr: Address,
+        alias: Option<String>,
+    ) -> Result<UdsOutletStatus> {
+        info!(
+            "Handling request to create UDS outlet portal at {}",
+            socket_path
+        );
+        let uds_transport = self.uds_transport()?;
+        let resource = alias
+            .as_deref()
+            .map(Resource::new)
+            .unwrap_or(resources::UDS_OUTLET);
+
+        let alias = alias.unwrap_or_else(random_alias);
+
+        // Check that there is no entry in the registry with the same alias
+        if self.registry.uds_outlets.contains_key(&alias).await {
+            let message = format!("A UDS outlet with alias '{alias}' already exists");
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::AlreadyExists,
+                message,
+            ));
+        }
+
+        let check_credential = self.enable_credential_checks;
+        let trust_context_id = if check_credential {
+            Some(self.trust_context()?.id())
+        } else {
+            None
+        };
+
+        let access_control = self
+            .access_control(&resource, &actions::HANDLE_MESSAGE, trust_context_id, None)
+            .await?;
+
+        let mut options = UdsOutletOptions::new().with_incoming_access_control(access_control);
+        if !check_credential {
+            options = options.as_consumer(&self.api_transport_flow_control_id);
+        }
+        // Accept messages from the default secure channel listener
+        if let Some(flow_control_id) = ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+        {
+            options = options.as_consumer(&flow_control_id);
+        }
+
+        if let Err(e) = uds_transport
+            .create_outlet(worker_addr.clone(), &socket_path, options)
+            .await
+        {
+            warn!(at = %socket_path, err = %e, "Failed to create UDS outlet");
+            let message = format!("Failed to create UDS outlet: {}", e);
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::Internal,
+                message,
+            ));
+        }
+
+        self.registry
+            .uds_outlets
+            .insert(
+                alias.clone(),
+                UdsOutletInfo::new(&socket_path, &worker_addr),
+            )
+            .await;
+
+        Ok(UdsOutletStatus::new(socket_path, worker_addr, alias))
+    }
+
+    pub async fn delete_uds_outlet(&self, alias: &str) -> Result<UdsOutletStatus> {
+        info!(%alias, "Handling request to delete UDS outlet portal");
+        match self.registry.uds_outlets.remove(alias).await {
+            Some(deleted_outlet) => {
+                debug!(%alias, "Successfully removed UDS outlet from node registry");
+                if let Err(e) = self
+                    .uds_transport()?
+                    .stop_outlet(deleted_outlet.worker_addr.clone())
+                    .await
+                {
+                    warn!(%alias, %e, "Failed to stop UDS outlet worker");
+                }
+                Ok(UdsOutletStatus::new(
+                    deleted_outlet.socket_path,
+                    deleted_outlet.worker_addr,
+                    alias,
+                ))
+            }
+            None => {
+                warn!(%alias, "UDS outlet not found in the node registry");
+                let message = format!("UDS outlet with alias {alias} not found");
+                Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::NotFound,
+                    message,
+                ))
+            }
+        }
+    }
+
+    pub(super) async fn show_uds_outlet(&self, alias: &str) -> Option<UdsOutletStatus> {
+        info!(%alias, "Handling request to show UDS outlet portal");
+        self.registry
+            .uds_outlets
+            .get(alias)
+            .await
+            .map(|outlet| UdsOutletStatus::new(outlet.socket_path, outlet.worker_addr, alias))
+    }
+
+    pub async fn list_uds_outlets(&self) -> UdsOutletList {
+        UdsOutletList::new(
+            self.registry
+                .uds_outlets
+                .entries()
+                .await
+                .iter()
+                .map(|(alias, info)| {
+                    UdsOutletStatus::new(&info.socket_path, info.worker_addr.clone(), alias)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// UDS INLETS
+impl NodeManager {
+    pub async fn create_uds_inlet(
+        &self,
+        connection: Connection,
+        socket_path: String,
+        requested_alias: Option<String>,
+        outlet_addr: MultiAddr,
+    ) -> Result<InletStatus> {
+        info!("Handling request to create UDS inlet portal");
+        let uds_transport = self.uds_transport()?;
+
+        let alias = requested_alias.clone().unwrap_or_else(random_alias);
+        debug! {
+            socket_path = %socket_path,
+            outlet_addr = %outlet_addr,
+            %alias,
+            "Creating UDS inlet portal"
+        }
+
+        {
+            let registry = &self.registry.uds_inlets;
+
+            // Check that there is no entry in the registry with the same alias
+            if registry.contains_key(&alias).await {
+                let message = format!("A UDS inlet with alias '{alias}' already exists");
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::AlreadyExists,
+                    message,
+                ));
+            }
+
+            // Check that there is no entry in the registry with the same socket path
+            if registry
+                .values()
+                .await
+                .iter()
+                .any(|inlet| inlet.bind_addr == socket_path)
+            {
+                let message =
+                    format!("A UDS inlet with socket path '{socket_path}' already exists");
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::AlreadyExists,
+                    message,
+                ));
+            }
+        }
+
+        let outlet_route = connection.route(self).await?;
+
+        let project_id = self.inlet_project_id(&outlet_addr).await?;
+        let resource = requested_alias
+            .map(|a| Resource::new(a.as_str()))
+            .unwrap_or(resources::UDS_INLET);
+        let access_control = self
+            .access_control(
+                &resource,
+                &actions::HANDLE_MESSAGE,
+                project_id.as_deref(),
+                None,
+            )
+            .await?;
+
+        let options = UdsInletOptions::new().with_incoming_access_control(access_control);
+
+        match uds_transport
+            .create_inlet(&socket_path, outlet_route.clone(), options)
+            .await
+        {
+            Ok((_, worker_addr)) => {
+                self.registry
+                    .uds_inlets
+                    .insert(
+                        alias.clone(),
+                        InletInfo::new(&socket_path, Some(&worker_addr), &outlet_route),
+                    )
+                    .await;
+
+                Ok(InletStatus::new(
+                    socket_path,
+                    worker_addr.to_string(),
+                    alias,
+                    None,
+                    outlet_route.to_string(),
+                ))
+            }
+            Err(e) => {
+                warn!(to = %outlet_addr, err = %e, "Failed to create UDS inlet");
+                let message = format!("Failed to create UDS inlet: {}", e);
+                Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Internal,
+                    message,
+                ))
+            }
+        }
+    }
+
+    pub async fn delete_uds_inlet(&self, alias: &str) -> Result<InletStatus> {
+        info!(%alias, "Handling request to delete UDS inlet portal");
+        match self.registry.uds_inlets.remove(alias).await {
+            Some(deleted_inlet) => {
+                debug!(%alias, "Successfully removed UDS inlet from node registry");
+                self.uds_transport()?
+                    .stop_inlet(deleted_inlet.worker_addr.clone())
+                    .await?;
+                Ok(InletStatus::new(
+                    deleted_inlet.bind_addr,
+                    deleted_inlet.worker_addr.to_string(),
+                    alias,
+                    None,
+                    deleted_inlet.outlet_route.to_string(),
+                ))
+            }
+            None => {
+                error!(%alias, "UDS inlet not found in the node registry");
+                let message = format!("UDS inlet with alias {alias} not found");
+                Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::NotFound,
+                    message,
+                ))
+            }
+        }
+    }
+
+    pub async fn show_uds_inlet(&self, alias: &str) -> Option<InletStatus> {
+        info!(%alias, "Handling request to show UDS inlet portal");
+        self.registry.uds_inlets.get(alias).await.map(|inlet| {
+            InletStatus::new(
+                inlet.bind_addr,
+                inlet.worker_addr.to_string(),
+                alias,
+                None,
+                inlet.outlet_route.to_string(),
+            )
+        })
+    }
+
+    pub async fn list_uds_inlets(&self) -> InletList {
+        InletList::new(
+            self.registry
+                .uds_inlets
+                .entries()
+                .await
+                .iter()
+                .map(|(alias, info)| {
+                    InletStatus::new(
+                        &info.bind_addr,
+                        info.worker_addr.to_string(),
+                        alias,
+                        None,
+                        info.outlet_route.to_string(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl InMemoryNode {
+    /// Create a UDS inlet, after establishing a connection to the outlet node.
+    ///
+    /// Like UDP inlets, UDS inlets are not monitored by a session.
+    pub async fn create_uds_inlet(
+        &self,
+        ctx: &Context,
+        create_inlet: CreateUdsInlet,
+    ) -> Result<InletStatus> {
+        let CreateUdsInlet {
+            socket_path,
+            outlet_addr,
+            alias,
+            authorized,
+            wait_for_outlet_duration,
+        } = create_inlet;
+
+        let duration = wait_for_outlet_duration.unwrap_or(Duration::from_secs(5));
+        let connection_ctx = Arc::new(ctx.async_try_clone().await?);
+        let connection = self
+            .make_connection(
+                connection_ctx,
+                &outlet_addr,
+                None,
+                authorized,
+                None,
+                Some(duration),
+            )
+            .await?;
+
+        self.node_manager
+            .create_uds_inlet(connection, socket_path, alias, outlet_addr)
+            .await
+    }
+}
@@ -1,15 +1,20 @@
 use miette::miette;
-use std::net::{SocketAddrV4, SocketAddrV6};
+use std::iter::Peekable;
+use std::net::IpAddr;
 
 use ockam::TcpTransport;
 use ockam_core::errcode::{Kind, Origin};
-use ockam_core::flow_control::FlowControlId;
-use ockam_core::{Address, Error, Result, Route, TransportType, LOCAL};
+use ockam_core::flow_control::{FlowControlId, FlowControls};
+use ockam_core::{Address, Error, Result, Route, LOCAL};
 use ockam_multiaddr::proto::{
-    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Worker,
+    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Udp, Unix, Worker, Ws,
 };
-use ockam_multiaddr::{Code, MultiAddr, Protocol};
+use ockam_multiaddr::{Code, MultiAddr, ProtoIter, ProtoValue, Protocol};
 use ockam_transport_tcp::{TcpConnection, TcpConnectionOptions, TCP};
+use ockam_transport_udp::UDP;
+#[cfg(unix)]
+use ockam_transport_uds::UDS;
+use ockam_transport_websocket::WS;
 
 use crate::error::ApiError;
 
@@ -80,102 +85,114 @@ pub struct MultiAddrToRouteResult {
     pub tcp_connection: Option<TcpConnection>,
 }
 
+/// Flow controls of the transports started on a node, other than TCP.
+///
+/// Like the TCP connections, those transports only deliver the messages they
+/// receive to the consumers of their flow control.
+#[derive(Clone, Debug, Default)]
+pub struct TransportFlowControls {
+    pub udp: Option<FlowControlId>,
+    pub ws: Option<FlowControlId>,
+    pub uds: Option<FlowControlId>,
+}
+
+impl TransportFlowControls {
+    /// Register a transport address as a producer for the flow control of its transport.
+    /// The workers sending messages to that address, like secure channels, are then made
+    /// consumers of the messages received by the transport.
+    ///
+    /// Return None if the transport is not started on the node
+    fn register(&self, flow_controls: &FlowControls, address: &Address) -> Option<FlowControlId> {
+        let flow_control_id = match address.transport_type() {
+            UDP => self.udp.as_ref(),
+            WS => self.ws.as_ref(),
+            #[cfg(unix)]
+            UDS => self.uds.as_ref(),
+            _ => None,
+        };
+        match flow_control_id {
+            Some(flow_control_id) => {
+                flow_controls.add_producer(address.clone(), flow_control_id, None, vec![]);
+                Some(flow_control_id.clone())
+            }
+            None => {
+                error!(%address, "The transport of this address is not started on the node");
+                None
+            }
+        }
+    }
+}
+
 pub async fn multiaddr_to_route(
     ma: &MultiAddr,
     tcp: &TcpTransport,
+    transports: &TransportFlowControls,
 ) -> Option<MultiAddrToRouteResult> {
     let mut rb = Route::new();
     let mut it = ma.iter().peekable();
 
     let mut flow_control_id = None;
-    let mut number_of_tcp_hops = 0;
+    let mut number_of_transport_hops = 0;
     let mut tcp_connection = None;
 
     while let Some(p) = it.next() {
         match p.code() {
-            Ip4::CODE => {
-                if number_of_tcp_hops >= 1 {
-                    return None; // Only 1 TCP hop is allowed
-                }
-
-                let ip4 = p.cast::<Ip4>()?;
-                let port = it.next()?.cast::<Tcp>()?;
-                let socket_addr = SocketAddrV4::new(*ip4, *port);
-
-                let options = TcpConnectionOptions::new();
-                flow_control_id = Some(options.flow_control_id().clone());
-
-                let connection = match tcp.connect(socket_addr.to_string(), options).await {
-                    Ok(c) => c,
-                    Err(error) => {
-                        error!(%error, %socket_addr, "Couldn't connect to Ip4 address");
-                        return None;
-                    }
+            code @ (Ip4::CODE | Ip6::CODE | DnsAddr::CODE) => {
+                let host = match code {
+                    Ip4::CODE => p.cast::<Ip4>()?.to_string(),
+                    Ip6::CODE => format!("[{}]", *p.cast::<Ip6>()?),
+                    _ => p.cast::<DnsAddr>()?.to_string(),
                 };
-
-                number_of_tcp_hops += 1;
-                rb = rb.append(connection.sender_address().clone());
-
-                tcp_connection = Some(connection);
-            }
-            Ip6::CODE => {
-                if number_of_tcp_hops >= 1 {
-                    return None; // Only 1 TCP hop is allowed
-                }
-
-                let ip6 = p.cast::<Ip6>()?;
-                let port = it.next()?.cast::<Tcp>()?;
-                let socket_addr = SocketAddrV6::new(*ip6, *port, 0, 0);
-
-                let options = TcpConnectionOptions::new();
-                flow_control_id = Some(options.flow_control_id().clone());
-
-                let connection = match tcp.connect(socket_addr.to_string(), options).await {
-                    Ok(c) => c,
-                    Err(error) => {
-                        error!(%error, %socket_addr, "Couldn't connect to Ip6 address");
-                        return None;
-                    }
+                let transport = match host_transport(&mut it) {
+                    Some(transport) => transport,
+                    // a dnsaddr without port is skipped
+                    None if code == DnsAddr::CODE => continue,
+                    None => return None,
                 };
 
-                number_of_tcp_hops += 1;
-                rb = rb.append(connection.sender_address().clone());
-
-                tcp_connection = Some(connection);
-            }
-            DnsAddr::CODE => {
-                if number_of_tcp_hops >= 1 {
-                    return None; // Only 1 TCP hop is allowed
+                if number_of_transport_hops >= 1 {
+                    return None; // Only 1 transport hop is allowed
                 }
+                number_of_transport_hops += 1;
 
-                let host = p.cast::<DnsAddr>()?;
-                if let Some(p) = it.peek() {
-                    if p.code() == Tcp::CODE {
-                        let port = p.cast::<Tcp>()?;
-
+                match transport {
+                    HostTransport::Tcp(port) => {
                         let options = TcpConnectionOptions::new();
                         flow_control_id = Some(options.flow_control_id().clone());
-                        let peer = format!("{}:{}", &*host, *port);
+                        let peer = format!("{host}:{port}");
 
                         let connection = match tcp.connect(&peer, options).await {
                             Ok(c) => c,
                             Err(error) => {
-                                error!(%error, %peer, "Couldn't connect to DNS address");
+                                error!(%error, %peer, "Couldn't connect to {code} address");
                                 return None;
                             }
                         };
 
-                        number_of_tcp_hops += 1;
                         rb = rb.append(connection.sender_address().clone());
-
                         tcp_connection = Some(connection);
-
-                        let _ = it.next();
-
-                        continue;
+                    }
+                    // the other transports connect when the first message is routed
+                    // to their address, they must be started on the node
+                    transport => {
+                        let address = transport.address(&host);
+                        flow_control_id =
+                            Some(transports.register(tcp.ctx().flow_controls(), &address)?);
+                        rb = rb.append(address)
                     }
                 }
             }
+            #[cfg(unix)]
+            Unix::CODE => {
+                if number_of_transport_hops >= 1 {
+                    return None; // Only 1 transport hop is allowed
+                }
+                number_of_transport_hops += 1;
+
+                let address = Address::new(UDS, &*p.cast::<Unix>()?);
+                flow_control_id = Some(transports.register(tcp.ctx().flow_controls(), &address)?);
+                rb = rb.append(address)
+            }
             Worker::CODE => {
                 let local = p.cast::<Worker>()?;
                 rb = rb.append(Address::new(LOCAL, &*local))
@@ -204,6 +221,7 @@ pub async fn multiaddr_to_route(
 
 /// Resolve all the multiaddresses which represent transport addresses
 /// For example /tcp/127.0.0.1/port/4000 is transformed to the Address (TCP, "127.0.0.1:4000")
+/// and /ip4/127.0.0.1/udp/4000 to the Address (UDP, "127.0.0.1:4000")
 /// The creation of a TCP worker and the substitution of that transport address to a worker address
 /// is done later with `context.resolve_transport_route(route)`
 pub fn multiaddr_to_transport_route(ma: &MultiAddr) -> Option<Route> {
@@ -212,30 +230,24 @@ pub fn multiaddr_to_transport_route(ma: &MultiAddr) -> Option<Route> {
 
     while let Some(p) = it.next() {
         match p.code() {
-            Ip4::CODE => {
-                let ip4 = p.cast::<Ip4>()?;
-                let port = it.next()?.cast::<Tcp>()?;
-                let socket_addr = SocketAddrV4::new(*ip4, *port);
-                route = route.append(Address::new(TCP, socket_addr.to_string()))
-            }
-            Ip6::CODE => {
-                let ip6 = p.cast::<Ip6>()?;
-                let port = it.next()?.cast::<Tcp>()?;
-                let socket_addr = SocketAddrV6::new(*ip6, *port, 0, 0);
-                route = route.append(Address::new(TransportType::new(1), socket_addr.to_string()))
-            }
-            DnsAddr::CODE => {
-                let host = p.cast::<DnsAddr>()?;
-                if let Some(p) = it.peek() {
-                    if p.code() == Tcp::CODE {
-                        let port = p.cast::<Tcp>()?;
-                        let addr = format!("{}:{}", &*host, *port);
-                        route = route.append(Address::new(TransportType::new(1), addr));
-                        let _ = it.next();
-                        continue;
-                    }
+            code @ (Ip4::CODE | Ip6::CODE | DnsAddr::CODE) => {
+                let host = match code {
+                    Ip4::CODE => p.cast::<Ip4>()?.to_string(),
+                    Ip6::CODE => format!("[{}]", *p.cast::<Ip6>()?),
+                    _ => p.cast::<DnsAddr>()?.to_string(),
+                };
+                match host_transport(&mut it) {
+                    Some(transport) => route = route.append(transport.address(&host)),
+                    // a dnsaddr without port is skipped
+                    None if code == DnsAddr::CODE => continue,
+                    None => return None,
                 }
             }
+            #[cfg(unix)]
+            Unix::CODE => {
+                let path = p.cast::<Unix>()?;
+                route = route.append(Address::new(UDS, &*path))
+            }
             Worker::CODE => {
                 let local = p.cast::<Worker>()?;
                 route = route.append(Address::new(LOCAL, &*local))
@@ -257,6 +269,75 @@ pub fn multiaddr_to_transport_route(ma: &MultiAddr) -> Option<Route> {
     Some(route.into())
 }
 
+/// Transport protocol following a host in a multiaddr, with its port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HostTransport {
+    /// `/tcp/<port>`
+    Tcp(u16),
+    /// `/tcp/<port>/ws`
+    Ws(u16),
+    /// `/udp/<port>`
+    Udp(u16),
+}
+
+impl HostTransport {
+    fn address(&self, host: &str) -> Address {
+        match self {
+            HostTransport::Tcp(port) => Address::new(TCP, format!("{host}:{port}")),
+            HostTransport::Ws(port) => Address::new(WS, format!("{host}:{port}")),
+            HostTransport::Udp(port) => Address::new(UDP, format!("{host}:{port}")),
+        }
+    }
+}
+
+/// Return the address of a transport hop which is routed by its transport, like
+/// /ip4/127.0.0.1/udp/4000 or /unix/<path>. The protocol following a host is consumed.
+/// TCP hops are not returned since they are replaced by the address of a TCP connection
+pub(crate) fn routed_transport_address(
+    p: &ProtoValue,
+    it: &mut Peekable<ProtoIter>,
+) -> Option<Address> {
+    match p.code() {
+        code @ (Ip4::CODE | Ip6::CODE | DnsAddr::CODE) => {
+            let host = match code {
+                Ip4::CODE => p.cast::<Ip4>()?.to_string(),
+                Ip6::CODE => format!("[{}]", *p.cast::<Ip6>()?),
+                _ => p.cast::<DnsAddr>()?.to_string(),
+            };
+            match host_transport(it)? {
+                HostTransport::Tcp(_) => None,
+                transport => Some(transport.address(&host)),
+            }
+        }
+        #[cfg(unix)]
+        Unix::CODE => Some(Address::new(UDS, &*p.cast::<Unix>()?)),
+        _ => None,
+    }
+}
+
+/// Consume the transport protocol following a host
+fn host_transport(it: &mut Peekable<ProtoIter>) -> Option<HostTransport> {
+    let p = it.peek()?;
+    match p.code() {
+        Tcp::CODE => {
+            let port = *p.cast::<Tcp>()?;
+            let _ = it.next();
+            if it.peek().map(|p| p.code()) == Some(Ws::CODE) {
+                let _ = it.next();
+                Some(HostTransport::Ws(port))
+            } else {
+                Some(HostTransport::Tcp(port))
+            }
+        }
+        Udp::CODE => {
+            let port = *p.cast::<Udp>()?;
+            let _ = it.next();
+            Some(HostTransport::Udp(port))
+        }
+        _ => None,
+    }
+}
+
 /// Try to convert a multiaddr to an Ockam Address
 pub fn multiaddr_to_addr(ma: &MultiAddr) -> Option<Address> {
     let mut it = ma.iter().peekable();
@@ -293,6 +374,26 @@ pub fn try_address_to_multiaddr(a: &Address) -> Result<MultiAddr, Error> {
     let mut ma = MultiAddr::default();
     match a.transport_type() {
         LOCAL => ma.push_back(Service::new(a.address()))?,
+        #[cfg(unix)]
+        UDS => ma.push_back(Unix::new(a.address()))?,
+        transport @ (UDP | WS) => {
+            let (host, port) = a
+                .address()
+                .rsplit_once(':')
+                .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+                .ok_or_else(|| ApiError::core(format!("invalid transport address: {a}")))?;
+            match host.trim_start_matches('[').trim_end_matches(']').parse() {
+                Ok(IpAddr::V4(ip)) => ma.push_back(Ip4::new(ip))?,
+                Ok(IpAddr::V6(ip)) => ma.push_back(Ip6::new(ip))?,
+                Err(_) => ma.push_back(DnsAddr::new(host))?,
+            }
+            if transport == UDP {
+                ma.push_back(Udp::new(port))?
+            } else {
+                ma.push_back(Tcp::new(port))?;
+                ma.push_back(Ws)?
+            }
+        }
         other => {
             error!(target: "ockam_api", transport = %other, "unsupported transport type");
             return Err(ApiError::core(format!("unknown transport type: {other}")));
@@ -343,6 +444,10 @@ pub fn is_local_node(ma: &MultiAddr) -> miette::Result<bool> {
                     .map(|ip6| ip6.is_loopback())
                     .ok_or_else(|| miette!("Invalid \"ip6\" value"))?;
             }
+            // A "/unix" socket is always on the local machine
+            Unix::CODE => {
+                at_rust_node = true;
+            }
             // A MultiAddr starting with "/service" could reference both local and remote nodes.
             _ => {
                 return Err(miette!("Invalid address, protocol not supported"));
@@ -364,6 +469,9 @@ pub fn local_worker(code: &Code) -> Result<bool> {
         | Ip4::CODE
         | Ip6::CODE
         | Tcp::CODE
+        | Udp::CODE
+        | Ws::CODE
+        | Unix::CODE
         | Secure::CODE => Ok(false),
         Worker::CODE | Service::CODE => Ok(true),
 
@@ -526,3 +634,72 @@ pub mod test_utils {
         Ok(identity)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_multiaddr_to_transport_route() {
+        let cases = [
+            (
+                "/ip4/127.0.0.1/tcp/4000",
+                Address::new(TCP, "127.0.0.1:4000"),
+            ),
+            (
+                "/ip4/127.0.0.1/udp/4000",
+                Address::new(UDP, "127.0.0.1:4000"),
+            ),
+            ("/ip6/::1/udp/4000", Address::new(UDP, "[::1]:4000")),
+            (
+                "/dnsaddr/localhost/tcp/4000/ws",
+                Address::new(WS, "localhost:4000"),
+            ),
+            #[cfg(unix)]
+            (
+                "/unix/%2Ftmp%2Fockam.sock",
+                Address::new(UDS, "/tmp/ockam.sock"),
+            ),
+        ];
+        for (ma, address) in cases {
+            let ma = MultiAddr::from_str(&format!("{ma}/service/api")).unwrap();
+            let route = multiaddr_to_transport_route(&ma).unwrap();
+            assert_eq!(route, Route::new().append(address).append("api").into());
+        }
+
+        // a port is required after an ip address
+        let ma = MultiAddr::from_str("/ip4/127.0.0.1/service/api").unwrap();
+        assert!(multiaddr_to_transport_route(&ma).is_none());
+    }
+
+    #[test]
+    fn test_transport_address_to_multiaddr() {
+        let cases = [
+            (
+                Address::new(UDP, "127.0.0.1:4000"),
+                "/ip4/127.0.0.1/udp/4000",
+            ),
+            (Address::new(UDP, "[::1]:4000"), "/ip6/::1/udp/4000"),
+            (
+                Address::new(WS, "localhost:4000"),
+                "/dnsaddr/localhost/tcp/4000/ws",
+            ),
+            #[cfg(unix)]
+            (
+                Address::new(UDS, "/tmp/ockam.sock"),
+                "/unix/%2Ftmp%2Fockam.sock",
+            ),
+        ];
+        for (address, ma) in cases {
+            let converted = try_address_to_multiaddr(&address).unwrap();
+            assert_eq!(converted.to_string(), ma);
+            assert_eq!(
+                multiaddr_to_transport_route(&converted).unwrap(),
+                Route::new().append(address).into()
+            );
+        }
+
+        assert!(try_address_to_multiaddr(&Address::new(UDP, "localhost")).is_err());
+    }
+}
@@ -330,10 +330,12 @@ async fn start_authority_node(
 
     let trusted_identities = cmd.trusted_identities(&identifier)?;
 
+    let vault_state = opts.state.vaults.default()?;
     let configuration = authority_node::Configuration {
         identifier,
         storage_path: opts.state.identities.identities_repository_path()?,
-        vault_path: opts.state.vaults.default()?.vault_file_path().clone(),
+        vault_path: vault_state.vault_file_path().clone(),
+        vault_config: vault_state.config().clone(),
         project_identifier: cmd.project_identifier,
         tcp_listener_address: cmd.tcp_listener_address,
         secure_channel_listener_name: None,
@@ -0,0 +1,163 @@
+use clap::{Args, ValueEnum};
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+
+use ockam::Context;
+use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
+use ockam_api::nodes::models::identity::{RotateKeyRequest, RotateKeyResponse, RotatedKey};
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::api::Request;
+
+use crate::identity::{get_identity_name, initialize_identity_if_default};
+use crate::node::get_node_name;
+use crate::terminal::OckamColor;
+use crate::util::{node_rpc, parse_node_name};
+use crate::{docs, fmt_log, fmt_ok, fmt_warn, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/rotate/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/rotate/after_long_help.txt");
+
+/// Rotate a key of an identity
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct RotateCommand {
+    /// Name of the identity to rotate
+    #[arg(conflicts_with = "at")]
+    name: Option<String>,
+
+    /// Key to rotate. Purpose keys can only be rotated on a running node
+    #[arg(long, value_enum, default_value_t = KeyArg::Primary)]
+    key: KeyArg,
+
+    /// Vault name storing the identity key. By default, the vault storing the
+    /// current primary key of the identity is used
+    #[arg(long, value_name = "VAULT_NAME", conflicts_with = "at")]
+    vault: Option<String>,
+
+    /// Rotate the key of the identity of a running node
+    #[arg(id = "at", value_name = "NODE_NAME", long)]
+    at_node: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+enum KeyArg {
+    Primary,
+    SecureChannel,
+    Credential,
+}
+
+impl From<KeyArg> for RotatedKey {
+    fn from(value: KeyArg) -> Self {
+        match value {
+            KeyArg::Primary => RotatedKey::Primary,
+            KeyArg::SecureChannel => RotatedKey::SecureChannel,
+            KeyArg::Credential => RotatedKey::Credential,
+        }
+    }
+}
+
+impl RotateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        if self.at_node.is_none() {
+            initialize_identity_if_default(&opts, &self.name);
+        }
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, RotateCommand)) -> miette::Result<()> {
+    run_impl(&ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &Context,
+    opts: CommandGlobalOpts,
+    cmd: RotateCommand,
+) -> miette::Result<()> {
+    let key: RotatedKey = cmd.key.into();
+    let response = match &cmd.at_node {
+        Some(_) => {
+            let node_name = get_node_name(&opts.state, &cmd.at_node);
+            let node_name = parse_node_name(&node_name)?;
+            let node = BackgroundNode::create(ctx, &opts.state, &node_name).await?;
+            let req =
+                Request::post("/node/identity/actions/rotate").body(RotateKeyRequest::new(key));
+            node.ask(ctx, req).await?
+        }
+        None => rotate_local_identity(&opts, &cmd).await?,
+    };
+
+    let mut plain = fmt_ok!(
+        "The {} of identity {} has been rotated",
+        response.key,
+        response
+            .identifier
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    );
+    if response.pushed_to_authority {
+        plain.push('\n');
+        plain.push_str(&fmt_ok!(
+            "The new identity change history has been sent to the project authority"
+        ));
+    }
+    if let Some(err) = &response.authority_error {
+        plain.push('\n');
+        plain.push_str(&fmt_warn!(
+            "The new identity change history could not be sent to the project authority: {err}"
+        ));
+        plain.push('\n');
+        plain.push_str(&fmt_log!(
+            "Please rotate the key again once the project authority is reachable"
+        ));
+    }
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .machine(&response.change_history)
+        .json(serde_json::to_string_pretty(&response).into_diagnostic()?)
+        .write_line()?;
+    Ok(())
+}
+
+/// Rotate the primary key of an identity stored in the local state
+async fn rotate_local_identity(
+    opts: &CommandGlobalOpts,
+    cmd: &RotateCommand,
+) -> miette::Result<RotateKeyResponse> {
+    if cmd.key != KeyArg::Primary {
+        return Err(miette!(
+            "Purpose keys can only be rotated on a running node, please use the --at argument"
+        ));
+    }
+    let name = get_identity_name(&opts.state, &cmd.name);
+    let identifier = opts.state.identities.get(&name)?.config().identifier();
+    let vault = match &cmd.vault {
+        Some(vault) => opts.state.vaults.get(vault)?,
+        None => opts.state.identity_vault(&identifier).await?,
+    }
+    .get()
+    .await?;
+
+    let identities = opts.state.get_identities(vault).await?;
+    identities
+        .identities_creation()
+        .rotate_identity(&identifier)
+        .await
+        .into_diagnostic()?;
+    let identity = identities
+        .get_identity(&identifier)
+        .await
+        .into_diagnostic()?;
+
+    Ok(RotateKeyResponse {
+        identifier,
+        key: RotatedKey::Primary,
+        change_history: hex::encode(identity.export().into_diagnostic()?),
+        pushed_to_authority: false,
+        authority_error: None,
+    })
+}
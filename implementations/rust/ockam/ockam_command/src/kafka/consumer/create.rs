@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use clap::{command, Args};
 
@@ -13,7 +14,10 @@ use crate::{
         kafka_default_consumer_server, kafka_default_project_route,
     },
     node::NodeOpts,
-    util::{node_rpc, parsers::socket_addr_parser},
+    util::{
+        node_rpc,
+        parsers::{record_key_secret_file_parser, socket_addr_parser},
+    },
     CommandGlobalOpts,
 };
 
@@ -37,6 +41,16 @@ pub struct CreateCommand {
     /// The route to the project in ockam orchestrator, expected something like /project/<name>
     #[arg(long, default_value_t = kafka_default_project_route())]
     project_route: MultiAddr,
+    /// Also protect the record keys and headers. The record keys are encrypted deterministically,
+    /// so that partitioning still works, with the hex-encoded 32 bytes secret stored in this file.
+    /// The secret must be shared by all the producers and consumers
+    #[arg(long, value_parser = record_key_secret_file_parser)]
+    record_key_secret_file: Option<PathBuf>,
+    /// Encrypt the records with a data key per topic, wrapped for every authorized
+    /// consumer, so that several consumers can read the same topic.
+    /// Must be set on both the producers and the consumers
+    #[arg(long)]
+    fan_out: bool,
 }
 
 impl CreateCommand {
@@ -50,6 +64,8 @@ impl CreateCommand {
             bootstrap_server: self.bootstrap_server,
             brokers_port_range: self.brokers_port_range,
             project_route: self.project_route,
+            record_key_secret_file: self.record_key_secret_file,
+            fan_out: self.fan_out,
         };
         node_rpc(rpc, (opts, arg_opts));
     }
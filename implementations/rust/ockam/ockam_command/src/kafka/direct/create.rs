@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use crate::kafka::direct::rpc::{start, ArgOpts};
 use crate::node::initialize_node_if_default;
@@ -8,7 +9,10 @@ use crate::{
         kafka_default_outlet_server, kafka_direct_default_addr,
     },
     node::NodeOpts,
-    util::{node_rpc, parsers::socket_addr_parser},
+    util::{
+        node_rpc,
+        parsers::{record_key_secret_file_parser, socket_addr_parser},
+    },
     CommandGlobalOpts,
 };
 use clap::{command, Args};
@@ -37,6 +41,11 @@ pub struct CreateCommand {
     /// The route to another kafka consumer node
     #[arg(long)]
     consumer_route: Option<MultiAddr>,
+    /// Also protect the record keys and headers. The record keys are encrypted deterministically,
+    /// so that partitioning still works, with the hex-encoded 32 bytes secret stored in this file.
+    /// The secret must be shared by all the producers and consumers
+    #[arg(long, value_parser = record_key_secret_file_parser)]
+    record_key_secret_file: Option<PathBuf>,
 }
 
 impl CreateCommand {
@@ -51,6 +60,7 @@ impl CreateCommand {
             brokers_port_range: self.brokers_port_range,
             consumer_route: self.consumer_route,
             bootstrap_server: self.bootstrap_server,
+            record_key_secret_file: self.record_key_secret_file,
         };
         node_rpc(start, (opts, arg_opts));
     }
@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use colorful::Colorful;
 use tokio::{sync::Mutex, try_join};
@@ -25,6 +26,7 @@ pub struct ArgOpts {
     pub brokers_port_range: PortRange,
     pub consumer_route: Option<MultiAddr>,
     pub bootstrap_server: SocketAddr,
+    pub record_key_secret_file: Option<PathBuf>,
 }
 
 pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> miette::Result<()> {
@@ -37,6 +39,7 @@ pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) ->
         brokers_port_range,
         consumer_route,
         bootstrap_server,
+        record_key_secret_file,
     } = args;
 
     opts.terminal
@@ -55,12 +58,15 @@ pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) ->
         let node_name = get_node_name(&opts.state, &node_opts.at_node);
         let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
 
-        let payload = StartKafkaDirectRequest::new(
+        let mut payload = StartKafkaDirectRequest::new(
             bind_address.to_owned(),
             bootstrap_server,
             brokers_port_range,
             consumer_route,
         );
+        if let Some(record_key_secret_file) = &record_key_secret_file {
+            payload = payload.with_record_key_secret_file(record_key_secret_file.to_string_lossy());
+        }
         let payload = StartServiceRequest::new(payload, &addr);
         let req = Request::post(endpoint).body(payload);
         start_service_impl(&ctx, &node, &kafka_entity, req).await?;
@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use colorful::Colorful;
 use tokio::{sync::Mutex, try_join};
@@ -24,6 +25,8 @@ pub struct ArgOpts {
     pub bootstrap_server: SocketAddr,
     pub brokers_port_range: PortRange,
     pub project_route: MultiAddr,
+    pub record_key_secret_file: Option<PathBuf>,
+    pub fan_out: bool,
 }
 
 pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> miette::Result<()> {
@@ -35,6 +38,8 @@ pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> mi
         bootstrap_server,
         brokers_port_range,
         project_route,
+        record_key_secret_file,
+        fan_out,
     } = args;
 
     opts.terminal
@@ -49,11 +54,15 @@ pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> mi
         let node_name = get_node_name(&opts.state, &node_opts.at_node);
         let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
 
-        let payload = StartKafkaProducerRequest::new(
+        let mut payload = StartKafkaProducerRequest::new(
             bootstrap_server.to_owned(),
             brokers_port_range,
             project_route,
-        );
+        )
+        .with_fan_out(fan_out);
+        if let Some(record_key_secret_file) = &record_key_secret_file {
+            payload = payload.with_record_key_secret_file(record_key_secret_file.to_string_lossy());
+        }
         let payload = StartServiceRequest::new(payload, &addr);
         let req = Request::post(endpoint).body(payload);
         start_service_impl(&ctx, &node, &kafka_entity, req).await?;
@@ -40,6 +40,7 @@ mod operation;
 mod output;
 mod pager;
 mod policy;
+mod portal;
 mod project;
 mod relay;
 mod reset;
@@ -56,6 +57,9 @@ mod subscription;
 pub mod tcp;
 mod terminal;
 mod trust_context;
+pub mod udp;
+#[cfg(unix)]
+pub mod uds;
 mod upgrade;
 pub mod util;
 mod vault;
@@ -113,6 +117,9 @@ use tcp::{
     outlet::TcpOutletCommand,
 };
 use trust_context::TrustContextCommand;
+use udp::{inlet::UdpInletCommand, outlet::UdpOutletCommand};
+#[cfg(unix)]
+use uds::{inlet::UdsInletCommand, outlet::UdsOutletCommand};
 use upgrade::check_if_an_upgrade_is_available;
 use util::{exitcode, exitcode::ExitCode};
 use vault::VaultCommand;
@@ -321,6 +328,14 @@ pub enum OckamSubcommand {
     TcpOutlet(TcpOutletCommand),
     TcpInlet(TcpInletCommand),
 
+    UdpOutlet(UdpOutletCommand),
+    UdpInlet(UdpInletCommand),
+
+    #[cfg(unix)]
+    UdsOutlet(UdsOutletCommand),
+    #[cfg(unix)]
+    UdsInlet(UdsInletCommand),
+
     KafkaOutlet(KafkaOutletCommand),
     KafkaConsumer(KafkaConsumerCommand),
     KafkaDirect(KafkaDirectCommand),
@@ -444,6 +459,12 @@ impl OckamCommand {
             OckamSubcommand::TcpConnection(c) => c.run(options),
             OckamSubcommand::TcpOutlet(c) => c.run(options),
             OckamSubcommand::TcpInlet(c) => c.run(options),
+            OckamSubcommand::UdpOutlet(c) => c.run(options),
+            OckamSubcommand::UdpInlet(c) => c.run(options),
+            #[cfg(unix)]
+            OckamSubcommand::UdsOutlet(c) => c.run(options),
+            #[cfg(unix)]
+            OckamSubcommand::UdsInlet(c) => c.run(options),
 
             OckamSubcommand::KafkaConsumer(c) => c.run(options),
             OckamSubcommand::KafkaProducer(c) => c.run(options),
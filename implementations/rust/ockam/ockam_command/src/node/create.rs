@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use std::{path::PathBuf, process, str::FromStr};
 
-use clap::Args;
+use clap::{Args, ValueEnum};
 use colorful::Colorful;
 use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
@@ -10,11 +10,17 @@ use rand::prelude::random;
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration};
 use tokio::try_join;
+use tracing::{info, warn};
 
+use ockam::flow_control::FlowControls;
 use ockam::{Address, AsyncTryClone, TcpListenerOptions};
 use ockam::{Context, TcpTransport};
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
-use ockam_api::cli_state::{add_project_info_to_node_state, init_node_state, random_name};
+use ockam_api::cli_state::{
+    add_project_info_to_node_state, init_node_state, random_name, NodeLaunchArguments,
+    NodeResource, NodeState,
+};
+use ockam_api::metrics::MetricsServer;
 use ockam_api::nodes::models::transport::CreateTransportJson;
 use ockam_api::nodes::service::NodeManagerTrustOptions;
 use ockam_api::nodes::BackgroundNode;
@@ -27,8 +33,12 @@ use ockam_api::{
         NodeManagerWorker, NODEMANAGER_ADDR,
     },
 };
-use ockam_core::api::{Request, ResponseHeader, Status};
-use ockam_core::{route, LOCAL};
+use ockam_core::api::{Method, Request, RequestHeader, ResponseHeader, Status};
+use ockam_core::{route, DenyAll, LOCAL};
+use ockam_transport_udp::{UdpTransport, UdpTransportOptions};
+#[cfg(unix)]
+use ockam_transport_uds::UdsTransport;
+use ockam_transport_websocket::WebSocketTransport;
 
 use crate::node::util::{spawn_node, NodeManagerDefaults};
 use crate::secure_channel::listener::create as secure_channel_listener;
@@ -75,6 +85,16 @@ pub struct CreateCommand {
     )]
     pub tcp_listener_address: String,
 
+    /// Serve the node metrics on this address, at the `/metrics` path,
+    /// using the OpenMetrics text format
+    #[arg(display_order = 900, long, value_name = "SOCKET_ADDRESS")]
+    pub metrics_address: Option<String>,
+
+    /// Also start this transport, so that connections and portals can use
+    /// /udp, /ws or /unix addresses. Can be repeated.
+    #[arg(display_order = 900, long = "transport", value_enum)]
+    pub transports: Vec<TransportArg>,
+
     /// `node create` started a child process to run this node in foreground.
     #[arg(long, hide = true)]
     pub child_process: bool,
@@ -110,6 +130,20 @@ pub struct CreateCommand {
     pub trust_context_opts: TrustContextOpts,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum TransportArg {
+    Udp,
+    Ws,
+    #[cfg(unix)]
+    Unix,
+}
+
+impl TransportArg {
+    fn name(&self) -> String {
+        self.to_possible_value().unwrap().get_name().to_string()
+    }
+}
+
 impl Default for CreateCommand {
     fn default() -> Self {
         let node_manager_defaults = NodeManagerDefaults::default();
@@ -117,6 +151,8 @@ impl Default for CreateCommand {
             node_name: random_name(),
             exit_on_eof: false,
             tcp_listener_address: node_manager_defaults.tcp_listener_address,
+            metrics_address: None,
+            transports: vec![],
             foreground: false,
             child_process: false,
             launch_config: None,
@@ -167,6 +203,26 @@ impl CreateCommand {
     pub fn logging_to_stdout(&self) -> bool {
         !self.logging_to_file()
     }
+
+    /// The arguments to use when the node is restarted
+    fn launch_arguments(&self) -> NodeLaunchArguments {
+        NodeLaunchArguments {
+            metrics_address: self.metrics_address.clone(),
+            transports: self.transports.iter().map(|t| t.name()).collect(),
+            project_path: self.trust_context_opts.project_path.clone(),
+            trusted_identities: self.trusted_identities.clone(),
+            trusted_identities_file: self.trusted_identities_file.clone(),
+            reload_from_trusted_identities_file: self.reload_from_trusted_identities_file.clone(),
+            launch_config: self
+                .launch_config
+                .as_ref()
+                .map(|config| serde_json::to_string(config).unwrap()),
+            authority_identity: self.authority_identity.clone(),
+            credential: self.credential.clone(),
+            trust_context: self.trust_context_opts.trust_context.clone(),
+            project: self.trust_context_opts.project.clone(),
+        }
+    }
 }
 
 pub fn parse_launch_config(config_or_path: &str) -> Result<Config> {
@@ -287,6 +343,45 @@ async fn run_foreground_node(
         .await
         .into_diagnostic()?;
 
+    let mut transport_options = NodeManagerTransportOptions::new(
+        listener.flow_control_id().clone(),
+        tcp.async_try_clone().await.into_diagnostic()?,
+    );
+    // each transport produces its own flow control id, so that the messages it receives
+    // only reach the secure channels and portals created over it
+    for transport in &cmd.transports {
+        let flow_control_id = FlowControls::generate_flow_control_id();
+        transport_options = match transport {
+            TransportArg::Udp => transport_options.with_udp_transport(
+                UdpTransport::create_with_options(
+                    &ctx,
+                    UdpTransportOptions::new().as_producer(&flow_control_id),
+                )
+                .await
+                .into_diagnostic()?,
+            ),
+            TransportArg::Ws => transport_options.with_ws_transport(
+                WebSocketTransport::create_as_producer(&ctx, &flow_control_id)
+                    .await
+                    .into_diagnostic()?,
+            ),
+            #[cfg(unix)]
+            TransportArg::Unix => transport_options.with_uds_transport(
+                UdsTransport::create_as_producer(&ctx, &flow_control_id)
+                    .await
+                    .into_diagnostic()?,
+            ),
+        };
+    }
+
+    // the server is stopped when the node stops
+    let _metrics_server = match &cmd.metrics_address {
+        Some(address) => {
+            Some(MetricsServer::start(address, ctx.metrics().clone()).into_diagnostic()?)
+        }
+        None => None,
+    };
+
     let node_state = opts.state.nodes.get(&node_name)?;
     node_state.set_pid(process::id() as i32)?;
     node_state.set_setup(
@@ -294,6 +389,7 @@ async fn run_foreground_node(
             .config()
             .setup_mut()
             .set_verbose(opts.global_args.verbose)
+            .set_launch_arguments(cmd.launch_arguments())
             .set_api_transport(
                 CreateTransportJson::new(
                     TransportType::Tcp,
@@ -313,11 +409,9 @@ async fn run_foreground_node(
             cmd.node_name.clone(),
             pre_trusted_identities,
             cmd.launch_config.is_none(),
-        ),
-        NodeManagerTransportOptions::new(
-            listener.flow_control_id().clone(),
-            tcp.async_try_clone().await.into_diagnostic()?,
-        ),
+        )
+        .with_persistent_resources(),
+        transport_options,
         NodeManagerTrustOptions::new(trust_context_config),
     )
     .await
@@ -346,6 +440,25 @@ async fn run_foreground_node(
         }
     }
 
+    // The resources are restored in the background, so that the node answers
+    // the requests checking that it is up while an inlet waits for its outlet
+    let resources = take_resources(&node_state)?;
+    if !resources.is_empty() {
+        let restore_ctx = ctx
+            .new_detached(
+                Address::random_tagged("Detached.restore_resources"),
+                DenyAll,
+                DenyAll,
+            )
+            .await
+            .into_diagnostic()?;
+        tokio::spawn(restore_resources(
+            restore_ctx,
+            node_state.clone(),
+            resources,
+        ));
+    }
+
     // Create a channel for communicating back to the main thread
     let (tx, mut rx) = tokio::sync::mpsc::channel(2);
     shutdown::wait(
@@ -420,13 +533,52 @@ async fn start_services(ctx: &Context, cfg: &Config) -> miette::Result<()> {
     Ok(())
 }
 
+/// Return the resources which were created on the node before it was stopped, and
+/// remove them from the node state since the node manager persists each resource
+/// again once it is created, possibly with a different name
+fn take_resources(node_state: &NodeState) -> miette::Result<Vec<NodeResource>> {
+    let resources = node_state.resources()?;
+    if !resources.is_empty() {
+        node_state.clear_resources()?;
+    }
+    Ok(resources)
+}
+
+/// Create again the resources which were created on the node before it was stopped.
+/// The resources which can't be created are kept, to be created on the next restart.
+async fn restore_resources(ctx: Context, node_state: NodeState, resources: Vec<NodeResource>) {
+    info!(count = resources.len(), "restoring the node resources");
+    for resource in resources {
+        if let Err(e) = restore_resource(&ctx, &resource).await {
+            warn!(
+                path = %resource.path,
+                name = %resource.name,
+                %e,
+                "failed to restore a node resource"
+            );
+            if let Err(e) = node_state.add_resource(resource) {
+                warn!(%e, "failed to keep a node resource to restore");
+            }
+        }
+    }
+}
+
+async fn restore_resource(ctx: &Context, resource: &NodeResource) -> Result<()> {
+    // The body was already encoded by the node manager
+    let mut req = minicbor::to_vec(RequestHeader::new(Method::Post, &resource.path, true))?;
+    req.extend(resource.body()?);
+    send_to_node_manager(ctx, req).await
+}
+
 async fn send_req_to_node_manager<T>(ctx: &Context, req: Request<T>) -> Result<()>
 where
     T: Encode<()>,
 {
-    let buf: Vec<u8> = ctx
-        .send_and_receive(NODEMANAGER_ADDR, req.to_vec()?)
-        .await?;
+    send_to_node_manager(ctx, req.to_vec()?).await
+}
+
+async fn send_to_node_manager(ctx: &Context, req: Vec<u8>) -> Result<()> {
+    let buf: Vec<u8> = ctx.send_and_receive(NODEMANAGER_ADDR, req).await?;
     let mut dec = Decoder::new(&buf);
     let hdr = dec.decode::<ResponseHeader>()?;
     if hdr.status() != Some(Status::Ok) {
@@ -463,6 +615,8 @@ pub async fn spawn_background_node(
         opts,
         &node_name,
         &cmd.tcp_listener_address,
+        cmd.metrics_address.as_ref(),
+        &cmd.transports.iter().map(|t| t.name()).collect::<Vec<_>>(),
         cmd.trust_context_opts.project_path.as_ref(),
         cmd.trusted_identities.as_ref(),
         cmd.trusted_identities_file.as_ref(),
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Args;
 use colorful::Colorful;
 
@@ -29,6 +31,11 @@ pub struct StartCommand {
 
     #[arg(long, default_value = "false")]
     aws_kms: bool,
+
+    /// Start the node without the portals, relays and services which were
+    /// created on it before it was stopped
+    #[arg(long)]
+    clean: bool,
 }
 
 impl StartCommand {
@@ -57,24 +64,30 @@ async fn run_impl(
         return Ok(());
     }
     node_state.kill_process(false)?;
+    if cmd.clean {
+        node_state.clear_resources()?;
+    }
     let node_setup = node_state.config().setup();
     opts.global_args.verbose = node_setup.verbose;
 
-    // Restart node
+    // Restart node with the arguments it was created with, if they were saved
+    let args = node_setup.launch_arguments.clone().unwrap_or_default();
     spawn_node(
         &opts,
         &node_name,                                    // The selected node name
         &node_setup.api_transport()?.addr.to_string(), // The selected node api address
-        None,                                          // No project information available
-        None,                                          // No trusted identities
-        None,                                          // "
-        None,                                          // "
-        None,                                          // Launch config
-        None,                                          // Authority Identity
-        None,                                          // Credential
-        None,                                          // Trust Context
-        None,                                          // Project Name
-        true,                                          // Restarted nodes will log to files
+        args.metrics_address.as_ref(),
+        &args.transports,
+        args.project_path.as_ref(),
+        args.trusted_identities.as_ref(),
+        args.trusted_identities_file.as_ref(),
+        args.reload_from_trusted_identities_file.as_ref(),
+        args.launch_config,
+        args.authority_identity.as_ref(),
+        args.credential.as_ref(),
+        args.trust_context.map(PathBuf::from).as_ref(),
+        args.project.as_ref(),
+        true, // Restarted nodes will log to files
     )?;
 
     // Print node status
@@ -64,6 +64,8 @@ pub fn spawn_node(
     opts: &CommandGlobalOpts,
     name: &str,
     address: &str,
+    metrics_address: Option<&String>,
+    transports: &[String],
     project: Option<&PathBuf>,
     trusted_identities: Option<&String>,
     trusted_identities_file: Option<&PathBuf>,
@@ -88,6 +90,16 @@ pub fn spawn_node(
         "--child-process".to_string(),
     ];
 
+    if let Some(metrics_address) = metrics_address {
+        args.push("--metrics-address".to_string());
+        args.push(metrics_address.to_string());
+    }
+
+    for transport in transports {
+        args.push("--transport".to_string());
+        args.push(transport.to_string());
+    }
+
     if logging_to_file || !opts.terminal.is_tty() {
         args.push("--no-color".to_string());
     }
@@ -17,7 +17,7 @@ use serde::{Serialize, Serializer};
 use ockam_api::cli_state::{ProjectConfigCompact, StateItemTrait, VaultState};
 use ockam_api::cloud::project::Project;
 use ockam_api::cloud::space::Space;
-use ockam_api::nodes::models::portal::{InletStatus, OutletStatus};
+use ockam_api::nodes::models::portal::{InletStatus, OutletStatus, UdsOutletStatus};
 use ockam_api::nodes::models::secure_channel::{
     CreateSecureChannelResponse, ShowSecureChannelResponse,
 };
@@ -246,7 +246,7 @@ impl Output for ShowSecureChannelResponse {
         let s = match &self.channel {
             Some(addr) => {
                 format!(
-                    "\n  Secure Channel:\n{} {}\n{} {}\n{} {}",
+                    "\n  Secure Channel:\n{} {}\n{} {}\n{} {}\n{} {}",
                     "  •         At: ".light_magenta(),
                     route_to_multiaddr(&route![addr.to_string()])
                         .ok_or(miette!("Invalid Secure Channel Address"))?
@@ -261,7 +261,12 @@ impl Output for ShowSecureChannelResponse {
                         .iter()
                         .map(|id| id.clone().light_yellow().to_string())
                         .collect::<Vec<String>>()
-                        .join("\n\t")
+                        .join("\n\t"),
+                    "  •  Last seen: ".light_magenta(),
+                    self.last_activity
+                        .map(human_readable_time)
+                        .unwrap_or("unknown".to_string())
+                        .light_yellow()
                 )
             }
             None => format!("{}", "Channel not found".red()),
@@ -273,7 +278,7 @@ impl Output for ShowSecureChannelResponse {
 
 impl Output for OutletStatus {
     fn output(&self) -> Result<String> {
-        let output = format!(
+        let mut output = format!(
             r#"
 Outlet {}:
     TCP Address:    {}
@@ -284,11 +289,28 @@ Outlet {}:
             self.worker_address()?
         );
 
+        if let (Some(strategy), Some(targets)) = (&self.strategy, &self.targets) {
+            writeln!(output, "    Strategy:       {strategy}")?;
+            writeln!(output, "    Targets:")?;
+            for target in targets {
+                let health = if target.healthy {
+                    "healthy".green()
+                } else {
+                    "unhealthy".red()
+                };
+                writeln!(
+                    output,
+                    "        {} ({}, {} connections)",
+                    target.socket_addr, health, target.connections
+                )?;
+            }
+        }
+
         Ok(output)
     }
 
     fn list_output(&self) -> Result<String> {
-        let output = format!(
+        let mut output = format!(
             r#"Outlet {}
 From {} to {}"#,
             self.alias
@@ -302,6 +324,53 @@ From {} to {}"#,
                 .color(OckamColor::PrimaryResource.color()),
         );
 
+        if let Some(targets) = &self.targets {
+            let healthy = targets.iter().filter(|t| t.healthy).count();
+            write!(
+                output,
+                "\n{} of {} targets healthy",
+                healthy
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color()),
+                targets.len()
+            )?;
+        }
+
+        Ok(output)
+    }
+}
+
+impl Output for UdsOutletStatus {
+    fn output(&self) -> Result<String> {
+        let output = format!(
+            r#"
+Outlet {}:
+    Socket Path:    {}
+    Worker Address: {}
+"#,
+            self.alias,
+            self.socket_path,
+            self.worker_address()?
+        );
+
+        Ok(output)
+    }
+
+    fn list_output(&self) -> Result<String> {
+        let output = format!(
+            r#"Outlet {}
+From {} to {}"#,
+            self.alias
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            self.worker_address()?
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            self.socket_path
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+        );
+
         Ok(output)
     }
 }
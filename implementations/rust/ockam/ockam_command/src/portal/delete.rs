@@ -0,0 +1,70 @@
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::Context;
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::api::Request;
+
+use crate::fmt_ok;
+use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::portal::PortalKind;
+use crate::tcp::util::alias_parser;
+use crate::util::{node_rpc, parse_node_name};
+use crate::CommandGlobalOpts;
+
+/// Delete a portal
+#[derive(Clone, Debug, Args)]
+pub struct DeleteCommand {
+    /// Name assigned to the portal that will be deleted
+    #[arg(display_order = 900, required = true, id = "ALIAS", value_parser = alias_parser)]
+    alias: String,
+
+    /// Node on which to stop the portal. If none are provided, the default node will be used
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Confirm the deletion without prompting
+    #[arg(display_order = 901, long, short)]
+    yes: bool,
+}
+
+impl DeleteCommand {
+    pub fn run(self, opts: CommandGlobalOpts, kind: &'static PortalKind) {
+        initialize_node_if_default(&opts, &self.node_opts.at_node);
+        node_rpc(run_impl, (opts, self, kind))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd, kind): (CommandGlobalOpts, DeleteCommand, &'static PortalKind),
+) -> miette::Result<()> {
+    let PortalKind {
+        transport,
+        portal,
+        resource,
+        ..
+    } = kind;
+    if opts.terminal.confirmed_with_flag_or_prompt(
+        cmd.yes,
+        format!("Are you sure you want to delete this {transport} {portal}?"),
+    )? {
+        let alias = cmd.alias.clone();
+        let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
+        let node_name = parse_node_name(&node_name)?;
+        let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+        node.tell(&ctx, Request::delete(format!("/node/{resource}/{alias}")))
+            .await?;
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "{transport} {portal} with alias {alias} on node {node_name} has been deleted."
+            ))
+            .machine(&alias)
+            .json(serde_json::json!({ *resource: { "alias": alias, "node": node_name } }))
+            .write_line()
+            .unwrap();
+    }
+    Ok(())
+}
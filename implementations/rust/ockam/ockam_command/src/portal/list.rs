@@ -0,0 +1,89 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use tokio::sync::Mutex;
+use tokio::try_join;
+
+use ockam_api::address::extract_address_value;
+use ockam_api::cli_state::StateDirTrait;
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::api::Request;
+use ockam_node::Context;
+
+use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::portal::{PortalKind, PortalStatus};
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::{docs, CommandGlobalOpts};
+
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+
+/// List portals
+#[derive(Clone, Debug, Args)]
+#[command(before_help = docs::before_help(PREVIEW_TAG))]
+pub struct ListCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl ListCommand {
+    pub fn run<S: PortalStatus>(self, opts: CommandGlobalOpts, kind: &'static PortalKind) {
+        initialize_node_if_default(&opts, &self.node_opts.at_node);
+        node_rpc(run_impl::<S>, (opts, self, kind))
+    }
+}
+
+async fn run_impl<S: PortalStatus>(
+    ctx: Context,
+    (opts, cmd, kind): (CommandGlobalOpts, ListCommand, &'static PortalKind),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
+    let node_name = extract_address_value(&node_name)?;
+
+    if !opts.state.nodes.get(&node_name)?.is_running() {
+        return Err(miette!("The node '{}' is not running", node_name));
+    }
+
+    let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let is_finished: Mutex<bool> = Mutex::new(false);
+
+    let get_portals = async {
+        let portals: S::List = node
+            .ask(&ctx, Request::get(format!("/node/{}", kind.resource)))
+            .await?;
+        *is_finished.lock().await = true;
+        Ok(S::list(portals))
+    };
+
+    let output_messages = vec![format!(
+        "Listing {} {} on node {}...\n",
+        kind.transport,
+        kind.title,
+        node_name
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    )];
+
+    let progress_output = opts
+        .terminal
+        .progress_output(&output_messages, &is_finished);
+
+    let (portals, _) = try_join!(get_portals, progress_output)?;
+
+    let plain = opts.terminal.build_list(
+        &portals,
+        &format!("{} on node {node_name}", kind.title),
+        &format!(
+            "No {} {} found on node {node_name}.",
+            kind.transport, kind.title
+        ),
+    )?;
+    let json = serde_json::to_string_pretty(&portals).into_diagnostic()?;
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .json(json)
+        .write_line()?;
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! Commands shared by the inlets and outlets of the transports only
+//! differing by their creation options, UDP and UDS.
+mod delete;
+mod list;
+mod show;
+
+pub use delete::DeleteCommand;
+pub use list::ListCommand;
+pub use show::ShowCommand;
+
+use indoc::formatdoc;
+use miette::miette;
+use minicbor::Decode;
+use serde::Serialize;
+
+use ockam::route;
+use ockam_api::nodes::models::portal::{
+    InletList, InletStatus, OutletList, OutletStatus, UdsOutletList, UdsOutletStatus,
+};
+use ockam_api::route_to_multiaddr;
+use ockam_core::Address;
+
+use crate::output::Output;
+use crate::Result;
+
+/// Names used by the commands of a kind of portal
+#[derive(Clone, Copy, Debug)]
+pub struct PortalKind {
+    /// Name of the transport, as displayed to the user
+    pub transport: &'static str,
+    /// Either "inlet" or "outlet"
+    pub portal: &'static str,
+    /// Title of a list of portals, "Inlets" for example
+    pub title: &'static str,
+    /// Path of the portals in the node manager API, "udp-inlet" for example
+    pub resource: &'static str,
+}
+
+/// Status of a portal, as returned by the node manager
+pub trait PortalStatus:
+    Output + Serialize + for<'b> Decode<'b, ()> + Send + Sync + 'static
+{
+    /// Response returned when listing the portals of a node
+    type List: for<'b> Decode<'b, ()> + Send;
+
+    /// Statuses of a list of portals
+    fn list(list: Self::List) -> Vec<Self>;
+
+    /// Description of the portal displayed by the `show` command
+    fn show(&self, kind: &PortalKind) -> Result<String>;
+
+    /// Value displayed by the `show` command when its output is used by another program
+    fn machine(&self) -> String;
+}
+
+impl PortalStatus for InletStatus {
+    type List = InletList;
+
+    fn list(list: Self::List) -> Vec<Self> {
+        list.list
+    }
+
+    fn show(&self, kind: &PortalKind) -> Result<String> {
+        let InletStatus {
+            alias,
+            bind_addr,
+            outlet_route,
+            ..
+        } = self;
+        let transport = kind.transport;
+        Ok(formatdoc! {r#"
+            Inlet:
+              Alias: {alias}
+              {transport} Address: {bind_addr}
+              To Outlet Address: {outlet_route}
+        "#})
+    }
+
+    fn machine(&self) -> String {
+        self.bind_addr.clone()
+    }
+}
+
+impl PortalStatus for OutletStatus {
+    type List = OutletList;
+
+    fn list(list: Self::List) -> Vec<Self> {
+        list.list
+    }
+
+    fn show(&self, kind: &PortalKind) -> Result<String> {
+        show_outlet(kind, &self.alias, &self.worker_addr, self.socket_addr)
+    }
+
+    fn machine(&self) -> String {
+        self.socket_addr.to_string()
+    }
+}
+
+impl PortalStatus for UdsOutletStatus {
+    type List = UdsOutletList;
+
+    fn list(list: Self::List) -> Vec<Self> {
+        list.list
+    }
+
+    fn show(&self, kind: &PortalKind) -> Result<String> {
+        show_outlet(kind, &self.alias, &self.worker_addr, &self.socket_path)
+    }
+
+    fn machine(&self) -> String {
+        self.socket_path.clone()
+    }
+}
+
+fn show_outlet(
+    kind: &PortalKind,
+    alias: &str,
+    worker_addr: &Address,
+    to: impl core::fmt::Display,
+) -> Result<String> {
+    let addr = route_to_multiaddr(&route![worker_addr.to_string()])
+        .ok_or_else(|| miette!("Invalid Outlet Address"))?;
+    let transport = kind.transport;
+    Ok(formatdoc! {r#"
+        Outlet:
+          Alias: {alias}
+          From Outlet: {addr}
+          To {transport}: {to}
+    "#})
+}
@@ -0,0 +1,61 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam::Context;
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::api::Request;
+
+use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::portal::{PortalKind, PortalStatus};
+use crate::tcp::util::alias_parser;
+use crate::util::{node_rpc, parse_node_name};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+
+/// Show a portal
+#[derive(Clone, Debug, Args)]
+#[command(before_help = docs::before_help(PREVIEW_TAG))]
+pub struct ShowCommand {
+    /// Name assigned to the portal that will be shown
+    #[arg(display_order = 900, required = true, id = "ALIAS", value_parser = alias_parser)]
+    alias: String,
+
+    /// Node on which the portal was started. If none are provided, the default node will be used
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl ShowCommand {
+    pub fn run<S: PortalStatus>(self, opts: CommandGlobalOpts, kind: &'static PortalKind) {
+        initialize_node_if_default(&opts, &self.node_opts.at_node);
+        node_rpc(run_impl::<S>, (opts, self, kind))
+    }
+}
+
+async fn run_impl<S: PortalStatus>(
+    ctx: Context,
+    (opts, cmd, kind): (CommandGlobalOpts, ShowCommand, &'static PortalKind),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
+    let node_name = parse_node_name(&node_name)?;
+
+    let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let alias = cmd.alias;
+    let status: S = node
+        .ask(
+            &ctx,
+            Request::get(format!("/node/{}/{alias}", kind.resource)),
+        )
+        .await?;
+
+    let json = serde_json::to_string(&status).into_diagnostic()?;
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!("{}", status.show(kind)?))
+        .machine(status.machine())
+        .json(json)
+        .write_line()?;
+    Ok(())
+}
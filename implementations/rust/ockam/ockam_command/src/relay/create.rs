@@ -12,7 +12,7 @@ use ockam::identity::Identifier;
 use ockam::Context;
 use ockam_api::address::extract_address_value;
 use ockam_api::is_local_node;
-use ockam_api::nodes::models::relay::RelayInfo;
+use ockam_api::nodes::models::relay::{HostedRelayInfo, RelayInfo};
 use ockam_api::nodes::service::relay::Relays;
 use ockam_api::nodes::BackgroundNode;
 use ockam_multiaddr::proto::Project;
@@ -191,3 +191,38 @@ Route {}"#,
         Ok(output)
     }
 }
+
+impl Output for HostedRelayInfo {
+    fn output(&self) -> Result<String> {
+        let output = format!(
+            r#"
+Relay {}:
+    Route: {}
+    Owner: {}
+    Last Heartbeat: {}
+"#,
+            self.alias(),
+            self.forward_route(),
+            self.owner()
+                .map(|x| x.to_string())
+                .unwrap_or("<none>".into()),
+            self.last_heartbeat()
+        );
+
+        Ok(output)
+    }
+
+    fn list_output(&self) -> Result<String> {
+        let output = format!(
+            r#"Relay {}
+Owner {}"#,
+            self.alias().color(OckamColor::PrimaryResource.color()),
+            self.owner()
+                .map(|x| x.to_string())
+                .unwrap_or("<none>".into())
+                .color(OckamColor::PrimaryResource.color()),
+        );
+
+        Ok(output)
+    }
+}
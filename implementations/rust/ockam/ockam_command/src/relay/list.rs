@@ -8,7 +8,7 @@ use tracing::trace;
 use ockam::Context;
 use ockam_api::address::extract_address_value;
 use ockam_api::cli_state::StateDirTrait;
-use ockam_api::nodes::models::relay::RelayInfo;
+use ockam_api::nodes::models::relay::{HostedRelayInfo, RelayInfo};
 use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
@@ -31,6 +31,10 @@ pub struct ListCommand {
     ///  List all the relays relaying traffic to the specified node
     #[arg(global = true, long, value_name = "NODE")]
     pub to: Option<String>,
+
+    /// List the relays registered by other nodes with the relay service of the node instead
+    #[arg(long)]
+    pub hosted: bool,
 }
 
 impl ListCommand {
@@ -52,6 +56,9 @@ async fn run_impl(
     }
 
     let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    if cmd.hosted {
+        return list_hosted_relays(&ctx, &opts, &node, &node_name).await;
+    }
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let get_relays = async {
@@ -88,3 +95,27 @@ async fn run_impl(
         .write_line()?;
     Ok(())
 }
+
+async fn list_hosted_relays(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node: &BackgroundNode,
+    node_name: &str,
+) -> miette::Result<()> {
+    let relays: Vec<HostedRelayInfo> = node.ask(ctx, Request::get("/node/hosted_relays")).await?;
+    trace!(?relays, "Hosted relays retrieved");
+
+    let plain = opts.terminal.build_list(
+        &relays,
+        &format!("Relays hosted by Node {node_name}"),
+        &format!("No Relays are hosted by node {node_name}."),
+    )?;
+    let json = serde_json::to_string_pretty(&relays).into_diagnostic()?;
+
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .json(json)
+        .write_line()?;
+    Ok(())
+}
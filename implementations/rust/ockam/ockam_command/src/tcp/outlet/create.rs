@@ -1,6 +1,7 @@
 use std::net::SocketAddr;
+use std::time::Duration;
 
-use clap::Args;
+use clap::{Args, ValueEnum};
 use colorful::Colorful;
 use miette::IntoDiagnostic;
 use tokio::sync::Mutex;
@@ -10,7 +11,9 @@ use ockam::Context;
 use ockam_abac::Resource;
 use ockam_api::address::extract_address_value;
 use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
-use ockam_api::nodes::models::portal::{CreateOutlet, OutletStatus};
+use ockam_api::nodes::models::portal::{
+    CreateOutlet, OutletLoadBalancing, OutletStatus, OutletStrategy,
+};
 use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
@@ -18,6 +21,7 @@ use crate::node::{get_node_name, initialize_node_if_default};
 use crate::policy::{add_default_project_policy, has_policy};
 use crate::tcp::util::alias_parser;
 use crate::terminal::OckamColor;
+use crate::util::duration::duration_parser;
 use crate::util::node_rpc;
 use crate::util::parsers::socket_addr_parser;
 use crate::{display_parse_logs, fmt_log};
@@ -44,6 +48,35 @@ pub struct CreateCommand {
     /// Assign a name to this outlet.
     #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
     alias: Option<String>,
+
+    /// Additional TCP address to send raw tcp traffic to. Can be repeated.
+    /// New connections are distributed between all the targets.
+    #[arg(long = "target", display_order = 903, id = "TARGET_ADDRESS", value_parser = socket_addr_parser)]
+    targets: Vec<SocketAddr>,
+
+    /// How the target of a new connection is chosen when the outlet has several targets.
+    #[arg(long, display_order = 904, id = "STRATEGY", value_enum, default_value_t = StrategyArg::RoundRobin)]
+    strategy: StrategyArg,
+
+    /// Check the targets at this interval and stop sending new connections
+    /// to the ones that are not reachable.
+    #[arg(long, display_order = 905, id = "HEALTH_CHECK_INTERVAL", value_parser = duration_parser)]
+    health_check_interval: Option<Duration>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+enum StrategyArg {
+    RoundRobin,
+    LeastConnections,
+}
+
+impl From<StrategyArg> for OutletStrategy {
+    fn from(value: StrategyArg) -> Self {
+        match value {
+            StrategyArg::RoundRobin => OutletStrategy::RoundRobin,
+            StrategyArg::LeastConnections => OutletStrategy::LeastConnections,
+        }
+    }
 }
 
 impl CreateCommand {
@@ -89,12 +122,19 @@ pub async fn run_impl(
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let send_req = async {
-        let payload = CreateOutlet::new(
+        let mut payload = CreateOutlet::new(
             cmd.to,
             extract_address_value(&cmd.from)?.into(),
             cmd.alias,
             true,
         );
+        if !cmd.targets.is_empty() || cmd.health_check_interval.is_some() {
+            payload.set_load_balancing(OutletLoadBalancing::new(
+                cmd.targets.clone(),
+                cmd.strategy.into(),
+                cmd.health_check_interval,
+            ));
+        }
         let res = send_request(&ctx, &opts, payload, node_name.clone()).await;
         *is_finished.lock().await = true;
         res
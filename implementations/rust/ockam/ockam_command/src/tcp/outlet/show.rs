@@ -54,6 +54,21 @@ pub async fn run_impl(
         .ok_or_else(|| miette!("Invalid Outlet Address"))?;
     println!("  From Outlet: {addr}");
     println!("  To TCP: {}", outlet_status.socket_addr);
+    if let (Some(strategy), Some(targets)) = (&outlet_status.strategy, &outlet_status.targets) {
+        println!("  Strategy: {strategy}");
+        println!("  Targets:");
+        for target in targets {
+            let health = if target.healthy {
+                "healthy"
+            } else {
+                "unhealthy"
+            };
+            println!(
+                "    {}: {}, {} connections",
+                target.socket_addr, health, target.connections
+            );
+        }
+    }
     Ok(())
 }
 
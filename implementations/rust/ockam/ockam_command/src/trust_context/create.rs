@@ -4,6 +4,7 @@ use clap::Args;
 use indoc::formatdoc;
 use miette::{miette, IntoDiagnostic};
 use ockam_api::cli_state::{random_name, StateDirTrait};
+use ockam_api::config::cli::TrustAuthorityConfig;
 
 const LONG_ABOUT: &str = include_str!("./static/create/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/create/after_long_help.txt");
@@ -24,6 +25,12 @@ pub struct CreateCommand {
     #[arg(long)]
     credential: Option<String>,
 
+    /// Additional authority trusted by the trust context, in `namespace=identity` format.
+    /// The identity is the hex-encoded change history of the authority.
+    /// The attributes attested by this authority are prefixed with `namespace.`
+    #[arg(long = "authority", value_name = "NAMESPACE=IDENTITY")]
+    authorities: Vec<String>,
+
     #[command(flatten)]
     trust_context_opts: TrustContextOpts,
 }
@@ -32,9 +39,39 @@ impl CreateCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
         local_cmd(run_impl(opts, self));
     }
+
+    fn additional_authorities(&self) -> miette::Result<Vec<TrustAuthorityConfig>> {
+        let mut authorities: Vec<TrustAuthorityConfig> = vec![];
+        for authority in &self.authorities {
+            let (namespace, identity) = authority.split_once('=').ok_or(miette!(
+                "authority expected in the `namespace=identity` format"
+            ))?;
+            if namespace.is_empty() || namespace.contains('.') {
+                return Err(miette!(
+                    "invalid namespace '{namespace}': it must not be empty nor contain '.' characters"
+                ));
+            }
+            if authorities.iter().any(|a| a.namespace() == Some(namespace)) {
+                return Err(miette!(
+                    "the namespace '{namespace}' is used more than once"
+                ));
+            }
+            if hex::decode(identity).is_err() {
+                return Err(miette!(
+                    "the identity of the authority '{namespace}' must be hex-encoded"
+                ));
+            }
+            authorities.push(
+                TrustAuthorityConfig::new(identity.to_string(), None)
+                    .with_namespace(namespace.to_string()),
+            );
+        }
+        Ok(authorities)
+    }
 }
 
 fn run_impl(opts: CommandGlobalOpts, cmd: CreateCommand) -> miette::Result<()> {
+    let additional_authorities = cmd.additional_authorities()?;
     let config = cmd
         .trust_context_opts
         .to_config(&opts.state)?
@@ -42,7 +79,17 @@ fn run_impl(opts: CommandGlobalOpts, cmd: CreateCommand) -> miette::Result<()> {
         .use_default_trust_context(false)
         .build();
 
+    // the attributes of the main authority are the only ones which are not namespaced
+    let has_main_authority = config.as_ref().map_or(false, |c| c.authority().is_ok());
+    if !additional_authorities.is_empty() && !has_main_authority {
+        return Err(miette!(
+            "The --authority option requires a main authority for the trust context. \
+            Use --project, --project-path or --credential to define it"
+        ));
+    }
+
     if let Some(c) = config {
+        let c = c.with_additional_authorities(additional_authorities);
         opts.state.trust_contexts.create(&cmd.name, c.clone())?;
 
         let auth = if let Ok(auth) = c.authority() {
@@ -51,7 +98,7 @@ fn run_impl(opts: CommandGlobalOpts, cmd: CreateCommand) -> miette::Result<()> {
             "None"
         };
 
-        let output = formatdoc!(
+        let mut output = formatdoc!(
             r#"
             Trust Context:
                 Name: {}
@@ -62,6 +109,13 @@ fn run_impl(opts: CommandGlobalOpts, cmd: CreateCommand) -> miette::Result<()> {
             c.id(),
             auth
         );
+        for authority in c.additional_authorities() {
+            output.push_str(&format!(
+                "    Authority ({}): {}\n",
+                authority.namespace().unwrap_or_default(),
+                authority.identity_str()
+            ));
+        }
 
         opts.terminal
             .stdout()
@@ -0,0 +1,58 @@
+pub mod create;
+
+use crate::portal::{DeleteCommand, ListCommand, PortalKind, ShowCommand};
+use crate::{docs, CommandGlobalOpts};
+use clap::{Args, Subcommand};
+use create::CreateCommand;
+use ockam_api::nodes::models::portal::InletStatus;
+
+const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/after_long_help.txt");
+const DELETE_AFTER_LONG_HELP: &str = include_str!("./static/delete/after_long_help.txt");
+const LIST_AFTER_LONG_HELP: &str = include_str!("./static/list/after_long_help.txt");
+const SHOW_AFTER_LONG_HELP: &str = include_str!("./static/show/after_long_help.txt");
+
+const UDS_INLET: PortalKind = PortalKind {
+    transport: "UDS",
+    portal: "inlet",
+    title: "Inlets",
+    resource: "uds-inlet",
+};
+
+/// Manage UDS Inlets
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP),
+)]
+pub struct UdsInletCommand {
+    #[command(subcommand)]
+    subcommand: UdsInletSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum UdsInletSubCommand {
+    Create(CreateCommand),
+    /// Delete a UDS Inlet
+    #[command(after_long_help = docs::after_help(DELETE_AFTER_LONG_HELP))]
+    Delete(DeleteCommand),
+    /// List UDS Inlets
+    #[command(after_long_help = docs::after_help(LIST_AFTER_LONG_HELP))]
+    List(ListCommand),
+    /// Show a UDS Inlet
+    #[command(after_long_help = docs::after_help(SHOW_AFTER_LONG_HELP))]
+    Show(ShowCommand),
+}
+
+impl UdsInletCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            UdsInletSubCommand::Create(c) => c.run(options),
+            UdsInletSubCommand::Delete(c) => c.run(options, &UDS_INLET),
+            UdsInletSubCommand::List(c) => c.run::<InletStatus>(options, &UDS_INLET),
+            UdsInletSubCommand::Show(c) => c.run::<InletStatus>(options, &UDS_INLET),
+        }
+    }
+}
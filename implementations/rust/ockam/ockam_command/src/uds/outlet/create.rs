@@ -0,0 +1,143 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+use tokio::sync::Mutex;
+use tokio::try_join;
+
+use ockam::Context;
+use ockam_abac::Resource;
+use ockam_api::address::extract_address_value;
+use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
+use ockam_api::nodes::models::portal::{CreateUdsOutlet, UdsOutletStatus};
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::api::Request;
+
+use crate::node::{get_node_name, initialize_node_if_default};
+use crate::policy::{add_default_project_policy, has_policy};
+use crate::tcp::util::alias_parser;
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::{display_parse_logs, fmt_log};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const AFTER_LONG_HELP: &str = include_str!("./static/create/after_long_help.txt");
+
+/// Create UDS Outlets
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct CreateCommand {
+    /// Node on which to start the uds outlet.
+    #[arg(long, display_order = 900, id = "NODE")]
+    at: Option<String>,
+
+    /// Address of the uds outlet.
+    #[arg(long, display_order = 901, id = "OUTLET_ADDRESS", default_value_t = default_from_addr())]
+    from: String,
+
+    /// Path of the socket to send raw uds traffic to.
+    #[arg(long, display_order = 902, id = "SOCKET_PATH")]
+    to: String,
+
+    /// Assign a name to this outlet.
+    #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
+    alias: Option<String>,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_node_if_default(&opts, &self.at);
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+pub fn default_from_addr() -> String {
+    "/service/uds-outlet".to_string()
+}
+
+pub async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, CreateCommand),
+) -> miette::Result<()> {
+    opts.terminal.write_line(&fmt_log!(
+        "Creating UDS Outlet to {}...\n",
+        &cmd.to
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    ))?;
+    display_parse_logs(&opts);
+
+    let node_name = get_node_name(&opts.state, &cmd.at);
+    let node_name = extract_address_value(&node_name)?;
+    let project = opts
+        .state
+        .nodes
+        .get(&node_name)?
+        .config()
+        .setup()
+        .project
+        .to_owned();
+    let resource = Resource::new("uds-outlet");
+    if let Some(p) = project {
+        if !has_policy(&node_name, &ctx, &opts, &resource).await? {
+            add_default_project_policy(&node_name, &ctx, &opts, p, &resource).await?;
+        }
+    }
+
+    let is_finished: Mutex<bool> = Mutex::new(false);
+
+    let send_req = async {
+        let payload = CreateUdsOutlet::new(
+            cmd.to.clone(),
+            extract_address_value(&cmd.from)?.into(),
+            cmd.alias,
+        );
+        let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+        let req = Request::post("/node/uds-outlet").body(payload);
+        let res: crate::Result<UdsOutletStatus> = Ok(node.ask(&ctx, req).await?);
+        *is_finished.lock().await = true;
+        res
+    };
+
+    let output_messages = vec![
+        format!(
+            "Creating outlet service on node {}...",
+            &node_name
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+        ),
+        "Setting up UDS outlet worker...".to_string(),
+        format!(
+            "Hosting outlet service at {}...",
+            &cmd.from
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        ),
+    ];
+
+    let progress_output = opts
+        .terminal
+        .progress_output(&output_messages, &is_finished);
+
+    let (outlet_status, _) = try_join!(send_req, progress_output)?;
+    let machine = outlet_status.worker_address().into_diagnostic()?;
+    let json = serde_json::to_string_pretty(&outlet_status).into_diagnostic()?;
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "Created a new UDS Outlet on node {} from address {} to {}",
+            &node_name
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            format!("/service/{}", extract_address_value(&cmd.from)?)
+                .color(OckamColor::PrimaryResource.color()),
+            &cmd.to
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        ))
+        .machine(machine)
+        .json(json)
+        .write_line()?;
+
+    Ok(())
+}
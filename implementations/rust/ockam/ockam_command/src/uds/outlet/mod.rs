@@ -0,0 +1,58 @@
+pub mod create;
+
+use crate::portal::{DeleteCommand, ListCommand, PortalKind, ShowCommand};
+use crate::{docs, CommandGlobalOpts};
+use clap::{Args, Subcommand};
+use create::CreateCommand;
+use ockam_api::nodes::models::portal::UdsOutletStatus;
+
+const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/after_long_help.txt");
+const DELETE_AFTER_LONG_HELP: &str = include_str!("./static/delete/after_long_help.txt");
+const LIST_AFTER_LONG_HELP: &str = include_str!("./static/list/after_long_help.txt");
+const SHOW_AFTER_LONG_HELP: &str = include_str!("./static/show/after_long_help.txt");
+
+const UDS_OUTLET: PortalKind = PortalKind {
+    transport: "UDS",
+    portal: "outlet",
+    title: "Outlets",
+    resource: "uds-outlet",
+};
+
+/// Manage UDS Outlets
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP),
+)]
+pub struct UdsOutletCommand {
+    #[command(subcommand)]
+    subcommand: UdsOutletSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum UdsOutletSubCommand {
+    Create(CreateCommand),
+    /// Delete a UDS Outlet
+    #[command(after_long_help = docs::after_help(DELETE_AFTER_LONG_HELP))]
+    Delete(DeleteCommand),
+    /// List UDS Outlets
+    #[command(after_long_help = docs::after_help(LIST_AFTER_LONG_HELP))]
+    List(ListCommand),
+    /// Show a UDS Outlet
+    #[command(after_long_help = docs::after_help(SHOW_AFTER_LONG_HELP))]
+    Show(ShowCommand),
+}
+
+impl UdsOutletCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            UdsOutletSubCommand::Create(c) => c.run(options),
+            UdsOutletSubCommand::Delete(c) => c.run(options, &UDS_OUTLET),
+            UdsOutletSubCommand::List(c) => c.run::<UdsOutletStatus>(options, &UDS_OUTLET),
+            UdsOutletSubCommand::Show(c) => c.run::<UdsOutletStatus>(options, &UDS_OUTLET),
+        }
+    }
+}
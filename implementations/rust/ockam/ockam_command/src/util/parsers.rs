@@ -1,9 +1,11 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use miette::miette;
 
 use ockam::identity::Identifier;
+use ockam_api::kafka::RECORD_KEY_SECRET_LENGTH;
 use ockam_transport_tcp::resolve_peer;
 
 use crate::Result;
@@ -30,6 +32,24 @@ pub(crate) fn identity_identifier_parser(input: &str) -> Result<Identifier> {
     Identifier::from_str(input).map_err(|_| miette!("Invalid identity identifier: {input}").into())
 }
 
+/// Helper fn for parsing the path of the file containing the hex-encoded secret used
+/// to encrypt kafka record keys. The file is checked here but only read by the node,
+/// so that the secret is never sent to the node
+pub(crate) fn record_key_secret_file_parser(input: &str) -> Result<PathBuf> {
+    let path = std::fs::canonicalize(input)
+        .map_err(|e| miette!("Cannot find the record key secret file {input}: {e}"))?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| miette!("Cannot read the record key secret file {input}: {e}"))?;
+    let secret = hex::decode(contents.trim())
+        .map_err(|_| miette!("The record key secret must be hex-encoded"))?;
+    if secret.len() != RECORD_KEY_SECRET_LENGTH {
+        return Err(
+            miette!("The record key secret must be {RECORD_KEY_SECRET_LENGTH} bytes long").into(),
+        );
+    }
+    Ok(path)
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::Ipv6Addr;
@@ -97,4 +117,20 @@ mod tests {
         let invalid_input = "192,166,0.1:9999";
         assert!(socket_addr_parser(invalid_input).is_err());
     }
+
+    #[test]
+    fn test_record_key_secret_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+
+        std::fs::write(path, "ab".repeat(RECORD_KEY_SECRET_LENGTH)).unwrap();
+        let parsed = record_key_secret_file_parser(path).unwrap();
+        assert_eq!(parsed, std::fs::canonicalize(path).unwrap());
+
+        std::fs::write(path, "abab").unwrap();
+        assert!(record_key_secret_file_parser(path).is_err());
+        std::fs::write(path, "zz".repeat(RECORD_KEY_SECRET_LENGTH)).unwrap();
+        assert!(record_key_secret_file_parser(path).is_err());
+        assert!(record_key_secret_file_parser("/does/not/exist").is_err());
+    }
 }
@@ -17,7 +17,7 @@ pub struct AttachKeyCommand {
     /// Name of the vault to attach the key to
     vault: String,
 
-    /// AWS KMS key to attach
+    /// AWS KMS key to attach, or hex-encoded id of a P-256 key stored on a PKCS#11 token
     #[arg(short, long)]
     key_id: String,
 }
@@ -37,9 +37,16 @@ async fn rpc(
 
 async fn run_impl(opts: CommandGlobalOpts, cmd: AttachKeyCommand) -> miette::Result<()> {
     let v_state = opts.state.vaults.get(&cmd.vault)?;
-    if !v_state.config().is_aws() {
-        return Err(miette!("Vault {} is not an AWS KMS vault", cmd.vault));
-    }
+    let key_id = if v_state.config().is_aws() {
+        cmd.key_id.as_bytes().to_vec()
+    } else if v_state.config().is_pkcs11() {
+        hex::decode(&cmd.key_id).into_diagnostic()?
+    } else {
+        return Err(miette!(
+            "Vault {} is not an AWS KMS or a PKCS#11 vault",
+            cmd.vault
+        ));
+    };
     let vault = v_state.get().await?;
     let idt = {
         let identities_creation = opts
@@ -48,9 +55,7 @@ async fn run_impl(opts: CommandGlobalOpts, cmd: AttachKeyCommand) -> miette::Res
             .await?
             .identities_creation();
 
-        let handle = SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(
-            cmd.key_id.as_bytes().to_vec(),
-        ));
+        let handle = SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(key_id));
 
         identities_creation
             .identity_builder()
@@ -1,5 +1,8 @@
+use std::path::PathBuf;
+
 use clap::Args;
 use colorful::Colorful;
+use miette::IntoDiagnostic;
 use rand::prelude::random;
 
 use ockam::Context;
@@ -28,8 +31,44 @@ pub struct CreateCommand {
 
     #[arg(long, default_value = "false")]
     aws_kms: bool,
+
+    /// Encrypt the vault storage with a passphrase. The passphrase is read from
+    /// the key file if provided, or from the OCKAM_VAULT_PASSPHRASE environment variable
+    #[arg(long, default_value = "false", conflicts_with = "aws_kms")]
+    encrypted: bool,
+
+    /// Path to a file containing the passphrase of an encrypted vault
+    #[arg(long, value_name = "KEY_FILE", requires = "encrypted")]
+    key_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pkcs11: Pkcs11Args,
+}
+
+// PKCS#11 token storing the identity keys of the vault
+#[cfg(feature = "pkcs11")]
+#[derive(Clone, Debug, Args)]
+struct Pkcs11Args {
+    /// Path to a PKCS#11 module used to store the identity keys on a hardware token.
+    /// The user PIN of the token is read from the OCKAM_PKCS11_PIN environment variable
+    #[arg(
+        long,
+        value_name = "MODULE_PATH",
+        conflicts_with_all = ["aws_kms", "encrypted"],
+        requires = "slot"
+    )]
+    pkcs11_module: Option<PathBuf>,
+
+    /// Slot of the PKCS#11 token
+    #[arg(long, value_name = "SLOT", requires = "pkcs11_module")]
+    slot: Option<u64>,
 }
 
+// The PKCS#11 arguments are only available with the "pkcs11" feature
+#[cfg(not(feature = "pkcs11"))]
+#[derive(Clone, Debug, Args)]
+struct Pkcs11Args {}
+
 impl CreateCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
         node_rpc(rpc, (opts, self));
@@ -45,8 +84,29 @@ async fn run_impl(
     opts: CommandGlobalOpts,
     cmd: CreateCommand,
 ) -> miette::Result<()> {
-    let CreateCommand { name, aws_kms, .. } = cmd;
-    let config = cli_state::VaultConfig::new(aws_kms)?;
+    let mut config = cli_state::VaultConfig::new(cmd.aws_kms)?;
+    #[cfg(feature = "pkcs11")]
+    if let (Some(pkcs11_module), Some(slot)) = (cmd.pkcs11.pkcs11_module, cmd.pkcs11.slot) {
+        // The module is loaded by nodes which may run from another directory
+        let pkcs11_module = pkcs11_module.canonicalize().into_diagnostic()?;
+        config = config.with_pkcs11(pkcs11_module, slot);
+    }
+    let CreateCommand {
+        name,
+        encrypted,
+        key_file,
+        ..
+    } = cmd;
+    if encrypted {
+        // The key file is read by nodes which may run from another directory
+        let key_file = key_file
+            .map(|path| path.canonicalize())
+            .transpose()
+            .into_diagnostic()?;
+        config = config.with_encryption(key_file);
+        // Check that a passphrase is available before creating the vault
+        config.passphrase()?;
+    }
     if opts.state.vaults.is_empty()? {
         opts.terminal.write_line(&fmt_info!(
             "This is the first vault to be created in this environment. It will be set as the default vault"
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam::Context;
+use ockam_api::cli_state::traits::StateDirTrait;
+
+use crate::util::node_rpc;
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/encrypt/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/encrypt/after_long_help.txt");
+
+/// Encrypt the storage of an existing vault
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct EncryptCommand {
+    /// Name of the vault
+    name: Option<String>,
+
+    /// Path to a file containing the passphrase. If not provided the passphrase
+    /// is read from the OCKAM_VAULT_PASSPHRASE environment variable
+    #[arg(long, value_name = "KEY_FILE")]
+    key_file: Option<PathBuf>,
+}
+
+impl EncryptCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, EncryptCommand),
+) -> miette::Result<()> {
+    let name = cmd
+        .name
+        .unwrap_or(opts.state.vaults.default()?.name().to_string());
+    let key_file = cmd
+        .key_file
+        .map(|path| path.canonicalize())
+        .transpose()
+        .into_diagnostic()?;
+    let state = opts.state.vaults.get(&name)?;
+    state.encrypt(key_file).await?;
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!("The vault '{name}' is now encrypted"))
+        .machine(&name)
+        .json(serde_json::json!({ "vault": { "name": &name } }))
+        .write_line()?;
+    Ok(())
+}
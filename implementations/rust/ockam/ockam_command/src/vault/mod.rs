@@ -2,6 +2,7 @@ mod attach_key;
 mod create;
 mod default;
 mod delete;
+mod encrypt;
 mod list;
 mod show;
 
@@ -9,6 +10,7 @@ use crate::vault::attach_key::AttachKeyCommand;
 use crate::vault::create::CreateCommand;
 use crate::vault::default::DefaultCommand;
 use crate::vault::delete::DeleteCommand;
+use crate::vault::encrypt::EncryptCommand;
 use crate::vault::list::ListCommand;
 use crate::vault::show::ShowCommand;
 use crate::{docs, CommandGlobalOpts};
@@ -37,6 +39,7 @@ pub enum VaultSubcommand {
     AttachKey(AttachKeyCommand),
     Show(ShowCommand),
     Delete(DeleteCommand),
+    Encrypt(EncryptCommand),
     List(ListCommand),
     Default(DefaultCommand),
 }
@@ -49,6 +52,7 @@ impl VaultCommand {
             VaultSubcommand::Show(cmd) => cmd.run(opts),
             VaultSubcommand::List(cmd) => cmd.run(opts),
             VaultSubcommand::Delete(cmd) => cmd.run(opts),
+            VaultSubcommand::Encrypt(cmd) => cmd.run(opts),
             VaultSubcommand::Default(cmd) => cmd.run(opts),
         }
     }
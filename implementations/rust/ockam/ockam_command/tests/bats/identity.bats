@@ -47,3 +47,42 @@ teardown() {
   run_success "$OCKAM" node delete "${n}" --yes
   run_success "$OCKAM" identity delete "${i}" --yes
 }
+
+@test "identity - rotate keys" {
+  i=$(random_str)
+  run_success "$OCKAM" identity create "${i}"
+  identifier=$($OCKAM identity show "${i}")
+
+  # The identifier is kept and a new change is added to the change history
+  run_success "$OCKAM" identity rotate "${i}"
+  run_success "$OCKAM" identity show "${i}"
+  assert_output "${identifier}"
+  run_success "$OCKAM" identity show "${i}" --full
+  assert_output --partial "Change[1]:"
+
+  # Purpose keys can only be rotated on a node
+  run_failure "$OCKAM" identity rotate "${i}" --key secure-channel
+
+  n=$(random_str)
+  run_success "$OCKAM" node create "${n}" --identity "${i}"
+  run_success "$OCKAM" identity rotate --at "${n}" --key secure-channel
+  run_success "$OCKAM" identity rotate --at "${n}"
+  run_success "$OCKAM" identity show "${i}" --full
+  assert_output --partial "Change[2]:"
+
+  # Secure channels can still be created with the rotated keys
+  run_success "$OCKAM" secure-channel create --from "${n}" --to "/node/${n}/service/api"
+}
+
+@test "identity - rotate the key of an identity stored in a non-default vault" {
+  v=$(random_str)
+  i=$(random_str)
+  run_success "$OCKAM" vault create
+  run_success "$OCKAM" vault create "${v}"
+  run_success "$OCKAM" identity create "${i}" --vault "${v}"
+
+  # The vault storing the identity key is found without the --vault argument
+  run_success "$OCKAM" identity rotate "${i}"
+  run_success "$OCKAM" identity show "${i}" --full
+  assert_output --partial "Change[1]:"
+}
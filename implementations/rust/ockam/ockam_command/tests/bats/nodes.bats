@@ -71,6 +71,52 @@ force_kill_node() {
   assert_output --partial "/service/echo"
 }
 
+@test "node - is restarted with its metrics endpoint" {
+  n="$(random_str)"
+  port="$(random_port)"
+  run_success "$OCKAM" node create "$n" --metrics-address "127.0.0.1:$port"
+  run_success curl --fail --max-time 10 "127.0.0.1:$port/metrics"
+
+  # Stop node, restart it, and check that the metrics are served again
+  run_success "$OCKAM" node stop "$n"
+  run_success "$OCKAM" node start "$n"
+  run_success curl --fail --max-time 10 "127.0.0.1:$port/metrics"
+  assert_output --partial "# EOF"
+}
+
+@test "node - is restarted with the portals and relays created on it" {
+  n="$(random_str)"
+  m="$(random_str)"
+  run_success "$OCKAM" node create "$n"
+  run_success "$OCKAM" node create "$m"
+  run_success "$OCKAM" tcp-outlet create --at "$n" --to 127.0.0.1:5000 --alias "test-outlet"
+  run_success "$OCKAM" relay create "$n" --at "/node/$m" --to "/node/$n"
+
+  # Stop node, restart it, and check that the outlet and relay are created again
+  run_success "$OCKAM" node stop "$n"
+  run_success "$OCKAM" node start "$n"
+  run_success "$OCKAM" tcp-outlet show test-outlet --at "$n"
+  assert_output --partial "127.0.0.1:5000"
+  run_success "$OCKAM" relay show "forward_to_$n" --at "$n"
+
+  # A deleted outlet is not created again
+  run_success "$OCKAM" tcp-outlet delete test-outlet --at "$n" --yes
+  run_success "$OCKAM" node stop "$n"
+  run_success "$OCKAM" node start "$n"
+  run_failure "$OCKAM" tcp-outlet show test-outlet --at "$n"
+  run_success "$OCKAM" relay show "forward_to_$n" --at "$n"
+}
+
+@test "node - is restarted without the resources created on it with --clean" {
+  n="$(random_str)"
+  run_success "$OCKAM" node create "$n"
+  run_success "$OCKAM" tcp-outlet create --at "$n" --to 127.0.0.1:5000 --alias "test-outlet"
+
+  run_success "$OCKAM" node stop "$n"
+  run_success "$OCKAM" node start "$n" --clean
+  run_failure "$OCKAM" tcp-outlet show test-outlet --at "$n"
+}
+
 @test "node - fail to create two background nodes with the same name" {
   n="$(random_str)"
   run_success "$OCKAM" node create "$n"
@@ -202,3 +202,77 @@ teardown() {
 
   run_failure "$OCKAM" tcp-inlet create --at "$n" --from "127.0.0.1:$port" --to "/node/$n/service/outlet"
 }
+
+@test "portals - udp inlet and outlet CRUD" {
+  outlet_port="$(random_port)"
+  inlet_port="$(random_port)"
+  run_success "$OCKAM" node create n1 --transport udp
+  run_success "$OCKAM" node create n2 --transport udp
+
+  run_success $OCKAM udp-outlet create --at /node/n1 --to "127.0.0.1:$outlet_port" --alias "test-outlet" --idle-timeout 10s
+  assert_output --partial "/service/udp-outlet"
+
+  run_success $OCKAM udp-inlet create --at /node/n2 --from "127.0.0.1:$inlet_port" --to /node/n1/service/udp-outlet --alias "test-inlet"
+
+  run_success $OCKAM udp-inlet show test-inlet --at /node/n2 --output json
+  assert_output --partial "\"alias\":\"test-inlet\""
+  assert_output --partial "\"bind_addr\":\"127.0.0.1:$inlet_port\""
+
+  run_success $OCKAM udp-outlet show test-outlet --at /node/n1
+  assert_output --regexp "To UDP: 127.0.0.1:$outlet_port"
+
+  run_success $OCKAM udp-inlet delete "test-inlet" --at /node/n2 --yes
+  run_failure $OCKAM udp-inlet delete "test-inlet" --at /node/n2 --yes
+  assert_output --partial "not found"
+
+  run_success $OCKAM udp-outlet delete "test-outlet" --at /node/n1 --yes
+  run_failure $OCKAM udp-outlet delete "test-outlet" --at /node/n1 --yes
+  assert_output --partial "not found"
+}
+
+@test "portals - create a tcp inlet to a uds outlet and move traffic through it" {
+  port="$(random_port)"
+  socket="$OCKAM_HOME/target.sock"
+  run_success "$OCKAM" node create n1 --transport unix
+  run_success "$OCKAM" node create n2
+
+  python3 -c "
+import http.server, socketserver, socket, os
+class S(socketserver.UnixStreamServer):
+    def get_request(self):
+        request, _ = super().get_request()
+        return request, ('local', 0)
+S('$socket', http.server.SimpleHTTPRequestHandler).serve_forever()" &
+  server_pid=$!
+  sleep 1
+
+  run_success "$OCKAM" uds-outlet create --at /node/n1 --to "$socket" --alias "uds-outlet"
+  assert_output --partial "/service/uds-outlet"
+  run_success "$OCKAM" tcp-inlet create --at /node/n2 --from "127.0.0.1:$port" --to /node/n1/service/uds-outlet
+
+  run_success curl --fail --head --max-time 10 "127.0.0.1:$port"
+
+  run_success "$OCKAM" uds-outlet show uds-outlet --at /node/n1
+  assert_output --partial "To UDS: $socket"
+  run_success "$OCKAM" uds-outlet delete uds-outlet --at /node/n1 --yes
+
+  kill "$server_pid"
+}
+
+@test "portals - create a load-balanced tcp outlet and report the health of its targets" {
+  unreachable_port="$(random_port)"
+  inlet_port="$(random_port)"
+  run_success "$OCKAM" node create n1
+
+  run_success "$OCKAM" tcp-outlet create --at /node/n1 --to 127.0.0.1:5000 --target "127.0.0.1:$unreachable_port" \
+    --strategy least-connections --health-check-interval 1s --alias "lb-outlet"
+  run_success "$OCKAM" tcp-inlet create --at /node/n1 --from "127.0.0.1:$inlet_port" --to /node/n1/service/outlet
+  sleep 2
+
+  run_success curl --fail --head --max-time 10 "127.0.0.1:$inlet_port"
+
+  run_success "$OCKAM" tcp-outlet show lb-outlet --at /node/n1
+  assert_output --partial "Strategy: least-connections"
+  assert_output --partial "127.0.0.1:5000: healthy"
+  assert_output --partial "127.0.0.1:$unreachable_port: unhealthy"
+}
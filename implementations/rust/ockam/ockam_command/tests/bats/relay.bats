@@ -47,6 +47,20 @@ teardown() {
   assert_output --partial "[]"
 }
 
+@test "relay - list the relays hosted by a node" {
+  run_success --separate-stderr "$OCKAM" node create n1
+  run_success --separate-stderr "$OCKAM" node create n2
+
+  run_success $OCKAM relay create blue --at /node/n1 --to /node/n2
+
+  run_success $OCKAM relay list --to /node/n1 --hosted
+  assert_output --partial "\"alias\": \"forward_to_blue\""
+
+  # Test listing node with no hosted relays
+  run_success $OCKAM relay list --to /node/n2 --hosted
+  assert_output --partial "[]"
+}
+
 @test "relay - show a relay on a node" {
   run_success --separate-stderr "$OCKAM" node create n1
   run_success --separate-stderr "$OCKAM" node create n2
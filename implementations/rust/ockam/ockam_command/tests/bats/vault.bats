@@ -56,3 +56,51 @@ teardown() {
   run_failure "$OCKAM" vault show "${v}"
   run_success "$OCKAM" identity show "${i}"
 }
+
+@test "vault - encrypted storage" {
+  # A passphrase is required to create an encrypted vault
+  v=$(random_str)
+  run_failure "$OCKAM" vault create "${v}" --encrypted
+
+  export OCKAM_VAULT_PASSPHRASE=$(random_str)
+  run_success "$OCKAM" vault create "${v}" --encrypted
+  run_success "$OCKAM" vault show "${v}"
+  assert_output --partial "\"encrypted\": true"
+
+  # Identities can only be created with the right passphrase
+  i=$(random_str)
+  run_success "$OCKAM" identity create "${i}" --vault "${v}"
+  run_success "$OCKAM" node create n --identity "${i}"
+  OCKAM_VAULT_PASSPHRASE=wrong run_failure "$OCKAM" identity create "$(random_str)" --vault "${v}"
+
+  # An existing vault can be encrypted with a key file
+  v=$(random_str)
+  run_success "$OCKAM" vault create "${v}"
+  run_success "$OCKAM" identity create "$(random_str)" --vault "${v}"
+  echo "$(random_str)" >"$OCKAM_HOME/key_file"
+  run_success "$OCKAM" vault encrypt "${v}" --key-file "$OCKAM_HOME/key_file"
+  run_failure "$OCKAM" vault encrypt "${v}" --key-file "$OCKAM_HOME/key_file"
+  run_success "$OCKAM" identity create "$(random_str)" --vault "${v}"
+}
+
+@test "vault - pkcs11 token" {
+  if [ -z "${OCKAM_PKCS11_MODULE}" ]; then
+    skip "OCKAM_PKCS11_MODULE is not set"
+  fi
+
+  # The module and slot are both required
+  v=$(random_str)
+  run_failure "$OCKAM" vault create "${v}" --pkcs11-module "${OCKAM_PKCS11_MODULE}"
+  run_failure "$OCKAM" vault create "${v}" --pkcs11-module "${OCKAM_PKCS11_MODULE}" --slot 0 --aws-kms
+
+  run_success "$OCKAM" vault create "${v}" --pkcs11-module "${OCKAM_PKCS11_MODULE}" --slot "${OCKAM_PKCS11_SLOT}"
+  run_success "$OCKAM" vault show "${v}"
+  assert_output --partial "\"slot\": ${OCKAM_PKCS11_SLOT}"
+
+  # Identity keys are created on the token and the vault can't be encrypted
+  i=$(random_str)
+  run_success "$OCKAM" identity create "${i}" --vault "${v}"
+  run_success "$OCKAM" identity show "${i}"
+  run_failure "$OCKAM" vault encrypt "${v}"
+  run_success "$OCKAM" vault delete "${v}" --yes
+}
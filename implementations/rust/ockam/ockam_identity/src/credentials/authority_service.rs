@@ -1,12 +1,15 @@
 use crate::credentials::credentials_retriever::CredentialsRetriever;
+use crate::credentials::revocation_list_retriever::RevocationListRetriever;
 use crate::models::{CredentialAndPurposeKey, Identifier, TimestampInSeconds};
 use crate::utils::{add_seconds, now};
-use crate::{Credentials, IdentityError};
+use crate::{Credentials, IdentityError, RevocationLists};
 use tracing::debug;
 
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::sync::RwLock;
-use ockam_core::Result;
+use ockam_core::{async_trait, Result};
 use ockam_node::Context;
 
 /// An AuthorityService represents an authority which issued credentials
@@ -15,6 +18,8 @@ pub struct AuthorityService {
     credentials: Arc<Credentials>,
     identifier: Identifier,
     own_credential: Option<Arc<dyn CredentialsRetriever>>,
+    revocation_list: Option<Arc<dyn RevocationListRetriever>>,
+    namespace: Option<String>,
     inner_cache: Arc<RwLock<Option<CachedCredential>>>,
 }
 
@@ -35,10 +40,38 @@ impl AuthorityService {
             credentials,
             identifier,
             own_credential,
+            revocation_list: None,
+            namespace: None,
             inner_cache: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Set a retriever for the revocation list of this authority
+    pub fn with_revocation_list_retriever(
+        mut self,
+        revocation_list: Arc<dyn RevocationListRetriever>,
+    ) -> Self {
+        self.revocation_list = Some(revocation_list);
+        self
+    }
+
+    /// Set the namespace of the attributes attested by this authority.
+    /// The attributes are stored with a `<namespace>.` prefix once verified
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Return the namespace of the attributes attested by this authority
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Return true if a revocation list can be retrieved for this authority
+    pub fn has_revocation_list(&self) -> bool {
+        self.revocation_list.is_some()
+    }
+
     /// Retrieve the credential for an identity within this authority
     pub async fn credential(
         &self,
@@ -82,8 +115,56 @@ impl AuthorityService {
         Ok(credential)
     }
 
+    /// Retrieve a new credential for an identity, even if a valid credential is cached.
+    /// This is used after the keys of the identity were rotated so that the authority
+    /// receives the latest change history of the identity
+    pub async fn refresh_credential(
+        &self,
+        ctx: &Context,
+        subject: &Identifier,
+    ) -> Result<CredentialAndPurposeKey> {
+        *self.inner_cache.write().unwrap() = None;
+        self.credential(ctx, subject).await
+    }
+
+    /// Retrieve the latest revocation list of this authority, verify it and
+    /// enforce it for all the credentials issued by this authority
+    pub async fn refresh_revocation_list(&self, ctx: &Context, subject: &Identifier) -> Result<()> {
+        let retriever = self
+            .revocation_list
+            .clone()
+            .ok_or(IdentityError::UnknownAuthority)?;
+        let revocation_list = retriever.retrieve(ctx, subject).await?;
+        debug!("retrieved the revocation list of {}", self.identifier);
+
+        self.credentials
+            .credentials_verification()
+            .receive_revocation_list(&[self.identifier.clone()], &revocation_list)
+            .await
+    }
+
+    /// Cache of the revocation lists used to verify the credentials of this authority
+    pub fn revocation_lists(&self) -> Arc<RevocationLists> {
+        self.credentials
+            .credentials_verification()
+            .revocation_lists()
+    }
+
     /// Issuer [`Identifier`]
     pub fn identifier(&self) -> &Identifier {
         &self.identifier
     }
 }
+
+#[async_trait]
+impl CredentialsRetriever for AuthorityService {
+    /// Retrieve the credential of an identity within this authority.
+    /// The cached credential is returned until it is about to expire
+    async fn retrieve(
+        &self,
+        ctx: &Context,
+        for_identity: &Identifier,
+    ) -> Result<CredentialAndPurposeKey> {
+        self.credential(ctx, for_identity).await
+    }
+}
@@ -1,5 +1,8 @@
 use crate::models::{CredentialData, PurposeKeyAttestationData};
-use crate::{CredentialsCreation, CredentialsVerification, IdentitiesRepository, PurposeKeys};
+use crate::{
+    CredentialsCreation, CredentialsVerification, IdentitiesRepository, PurposeKeys,
+    RevocationLists,
+};
 
 use ockam_core::compat::sync::Arc;
 use ockam_vault::{VaultForSigning, VaultForVerifyingSignatures};
@@ -20,6 +23,7 @@ pub struct Credentials {
     verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
     purpose_keys: Arc<PurposeKeys>,
     identities_repository: Arc<dyn IdentitiesRepository>,
+    revocation_lists: Arc<RevocationLists>,
 }
 
 impl Credentials {
@@ -29,12 +33,14 @@ impl Credentials {
         verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
         purpose_keys: Arc<PurposeKeys>,
         identities_repository: Arc<dyn IdentitiesRepository>,
+        revocation_lists: Arc<RevocationLists>,
     ) -> Self {
         Self {
             credential_vault,
             verifying_vault,
             purpose_keys,
             identities_repository,
+            revocation_lists,
         }
     }
 
@@ -48,6 +54,11 @@ impl Credentials {
         self.identities_repository.clone()
     }
 
+    /// [`RevocationLists`]
+    pub fn revocation_lists(&self) -> Arc<RevocationLists> {
+        self.revocation_lists.clone()
+    }
+
     /// Return [`CredentialsCreation`]
     pub fn credentials_creation(&self) -> Arc<CredentialsCreation> {
         Arc::new(CredentialsCreation::new(
@@ -64,6 +75,7 @@ impl Credentials {
             self.purpose_keys.purpose_keys_verification(),
             self.verifying_vault.clone(),
             self.identities_repository.clone(),
+            self.revocation_lists.clone(),
         ))
     }
 }
@@ -1,11 +1,13 @@
 use crate::models::{
-    Attributes, Credential, CredentialAndPurposeKey, CredentialData, Identifier, VersionedData,
+    Attributes, Credential, CredentialAndPurposeKey, CredentialData, Identifier, RevocationList,
+    RevocationListAndPurposeKey, RevocationListData, RevokedSubject, VersionedData,
 };
 use crate::utils::{add_seconds, now};
 use crate::{IdentitiesRepository, Identity, PurposeKeyCreation};
 
 use core::time::Duration;
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 use ockam_vault::{VaultForSigning, VaultForVerifyingSignatures};
 
@@ -100,4 +102,56 @@ impl CredentialsCreation {
 
         Ok(res)
     }
+
+    /// Issue a [`RevocationList`]
+    pub async fn issue_revocation_list(
+        &self,
+        issuer: &Identifier,
+        version: u64,
+        revoked: Vec<RevokedSubject>,
+        ttl: Duration,
+    ) -> Result<RevocationListAndPurposeKey> {
+        let issuer_purpose_key = self
+            .purpose_keys_creation
+            .get_or_create_credential_purpose_key(issuer)
+            .await?;
+
+        let created_at = now()?;
+        let expires_at = add_seconds(&created_at, ttl.as_secs());
+
+        let revocation_list_data = RevocationListData {
+            version,
+            revoked,
+            created_at,
+            expires_at,
+        };
+        let revocation_list_data = minicbor::to_vec(revocation_list_data)?;
+
+        let versioned_data = VersionedData {
+            version: 1,
+            data: revocation_list_data,
+        };
+        let versioned_data = minicbor::to_vec(&versioned_data)?;
+
+        let signed_data_hash = self
+            .verifying_vault
+            .sha256(&RevocationList::signed_data(&versioned_data))
+            .await?;
+
+        let signature = self
+            .credential_vault
+            .sign(issuer_purpose_key.key(), &signed_data_hash.0)
+            .await?;
+        let signature = signature.into();
+
+        let revocation_list = RevocationList {
+            data: versioned_data,
+            signature,
+        };
+
+        Ok(RevocationListAndPurposeKey {
+            revocation_list,
+            purpose_key_attestation: issuer_purpose_key.attestation().clone(),
+        })
+    }
 }
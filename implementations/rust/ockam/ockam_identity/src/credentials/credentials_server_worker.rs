@@ -76,9 +76,9 @@ impl CredentialsServerWorker {
                 let res = self
                     .credentials
                     .credentials_verification()
-                    .receive_presented_credential(
+                    .receive_presented_credential_for_trust_context(
                         &sender,
-                        self.trust_context.authorities().await?.as_slice(),
+                        &self.trust_context,
                         &credential_and_purpose_key,
                     )
                     .await;
@@ -108,9 +108,9 @@ impl CredentialsServerWorker {
                 let res = self
                     .credentials
                     .credentials_verification()
-                    .receive_presented_credential(
+                    .receive_presented_credential_for_trust_context(
                         &sender,
-                        self.trust_context.authorities().await?.as_slice(),
+                        &self.trust_context,
                         &credential_and_purpose_key,
                     )
                     .await;
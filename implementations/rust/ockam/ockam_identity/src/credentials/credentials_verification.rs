@@ -1,9 +1,13 @@
 use crate::identities::AttributesEntry;
-use crate::models::{CredentialAndPurposeKey, CredentialData, Identifier, PurposePublicKey};
+use crate::models::{
+    CredentialAndPurposeKey, CredentialData, CredentialSignature, Identifier,
+    PurposeKeyAttestation, PurposeKeyAttestationData, PurposePublicKey, RevocationList,
+    RevocationListAndPurposeKey, RevocationListData,
+};
 use crate::utils::now;
 use crate::{
     CredentialAndPurposeKeyData, IdentitiesRepository, IdentityError, PurposeKeyVerification,
-    TimestampInSeconds,
+    RevocationLists, TimestampInSeconds, TrustContext,
 };
 
 use ockam_core::compat::collections::BTreeMap;
@@ -11,6 +15,7 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 use ockam_vault::VaultForVerifyingSignatures;
+use tracing::debug;
 
 /// We allow Credentials to be created in the future related to this machine's time due to
 /// possible time dyssynchronization
@@ -21,6 +26,7 @@ pub struct CredentialsVerification {
     purpose_keys_verification: Arc<PurposeKeyVerification>,
     verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
     identities_repository: Arc<dyn IdentitiesRepository>,
+    revocation_lists: Arc<RevocationLists>,
 }
 
 impl CredentialsVerification {
@@ -29,11 +35,13 @@ impl CredentialsVerification {
         purpose_keys_verification: Arc<PurposeKeyVerification>,
         verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
         identities_repository: Arc<dyn IdentitiesRepository>,
+        revocation_lists: Arc<RevocationLists>,
     ) -> Self {
         Self {
             purpose_keys_verification,
             verifying_vault,
             identities_repository,
+            revocation_lists,
         }
     }
 
@@ -41,6 +49,11 @@ impl CredentialsVerification {
     pub fn identities_repository(&self) -> Arc<dyn IdentitiesRepository> {
         self.identities_repository.clone()
     }
+
+    /// [`RevocationLists`]
+    pub fn revocation_lists(&self) -> Arc<RevocationLists> {
+        self.revocation_lists.clone()
+    }
 }
 
 impl CredentialsVerification {
@@ -53,46 +66,14 @@ impl CredentialsVerification {
         credential_and_purpose_key: &CredentialAndPurposeKey,
     ) -> Result<CredentialAndPurposeKeyData> {
         let purpose_key_data = self
-            .purpose_keys_verification
-            .verify_purpose_key_attestation(
-                None,
+            .verify_issuer_signature(
+                authorities,
                 &credential_and_purpose_key.purpose_key_attestation,
+                &credential_and_purpose_key.credential.data,
+                &credential_and_purpose_key.credential.signature,
             )
             .await?;
 
-        if !authorities.contains(&purpose_key_data.subject) {
-            return Err(IdentityError::UnknownAuthority.into());
-        }
-
-        let public_key = match purpose_key_data.public_key.clone() {
-            PurposePublicKey::SecureChannelStatic(_) => {
-                return Err(IdentityError::InvalidKeyType.into())
-            }
-
-            PurposePublicKey::CredentialSigning(public_key) => public_key,
-        };
-
-        let public_key = public_key.into();
-
-        let versioned_data_hash = self
-            .verifying_vault
-            .sha256(&credential_and_purpose_key.credential.data)
-            .await?;
-
-        let signature = credential_and_purpose_key
-            .credential
-            .signature
-            .clone()
-            .into();
-
-        if !self
-            .verifying_vault
-            .verify_signature(&public_key, &versioned_data_hash.0, &signature)
-            .await?
-        {
-            return Err(IdentityError::CredentialVerificationFailed.into());
-        }
-
         let versioned_data = credential_and_purpose_key.credential.get_versioned_data()?;
         if versioned_data.version != 1 {
             return Err(IdentityError::UnknownCredentialVersion.into());
@@ -140,6 +121,22 @@ impl CredentialsVerification {
             return Err(IdentityError::CredentialVerificationFailed.into());
         }
 
+        if self.revocation_lists.is_stale(&purpose_key_data.subject) {
+            // Revocations by the issuer can't be ruled out
+            return Err(IdentityError::RevocationListOutdated.into());
+        }
+
+        if let Some(subject) = &credential_data.subject {
+            if self.revocation_lists.is_revoked(
+                &purpose_key_data.subject,
+                subject,
+                credential_data.created_at,
+            ) {
+                // Credential was revoked by its issuer
+                return Err(IdentityError::CredentialRevoked.into());
+            }
+        }
+
         if let Some(_subject_latest_change_hash) = &credential_data.subject_latest_change_hash {
             // TODO: Check how that aligns with the ChangeHistory of the subject that we have in the storage
             //     For example, if we just established a secure channel with that subject,
@@ -163,6 +160,104 @@ impl CredentialsVerification {
         })
     }
 
+    /// Verify a [`super::super::models::RevocationList`]
+    pub async fn verify_revocation_list(
+        &self,
+        authorities: &[Identifier],
+        revocation_list_and_purpose_key: &RevocationListAndPurposeKey,
+    ) -> Result<(Identifier, RevocationListData)> {
+        let purpose_key_data = self
+            .verify_issuer_signature(
+                authorities,
+                &revocation_list_and_purpose_key.purpose_key_attestation,
+                &RevocationList::signed_data(&revocation_list_and_purpose_key.revocation_list.data),
+                &revocation_list_and_purpose_key.revocation_list.signature,
+            )
+            .await?;
+
+        let versioned_data = revocation_list_and_purpose_key
+            .revocation_list
+            .get_versioned_data()?;
+        if versioned_data.version != 1 {
+            return Err(IdentityError::UnknownRevocationListVersion.into());
+        }
+
+        let revocation_list_data = RevocationListData::get_data(&versioned_data)?;
+
+        if revocation_list_data.created_at < purpose_key_data.created_at
+            || revocation_list_data.expires_at > purpose_key_data.expires_at
+        {
+            // Revocation list validity time range should be inside the purpose key validity time range
+            return Err(IdentityError::RevocationListVerificationFailed.into());
+        }
+
+        let now = now()?;
+
+        if revocation_list_data.created_at > now
+            && revocation_list_data.created_at - now > MAX_ALLOWED_TIME_DRIFT
+        {
+            // Revocation list can't be created in the future
+            return Err(IdentityError::RevocationListVerificationFailed.into());
+        }
+
+        if revocation_list_data.expires_at < now {
+            // Revocation list expired
+            return Err(IdentityError::RevocationListVerificationFailed.into());
+        }
+
+        Ok((purpose_key_data.subject, revocation_list_data))
+    }
+
+    /// Receive an Authority's [`super::super::models::RevocationList`]: verify it, cache it
+    /// and remove the attributes that the Authority attested for the newly revoked subjects
+    pub async fn receive_revocation_list(
+        &self,
+        authorities: &[Identifier],
+        revocation_list_and_purpose_key: &RevocationListAndPurposeKey,
+    ) -> Result<()> {
+        let (authority, revocation_list_data) = self
+            .verify_revocation_list(authorities, revocation_list_and_purpose_key)
+            .await?;
+
+        self.revocation_lists.refreshed(&authority);
+
+        let version = revocation_list_data.version;
+        let newly_revoked = match self
+            .revocation_lists
+            .update(&authority, revocation_list_data)
+        {
+            Some(newly_revoked) => newly_revoked,
+            None => return Ok(()), // We already have that list or a more recent one
+        };
+
+        debug!(
+            "received revocation list version {} from {} revoking {} new subject(s)",
+            version,
+            authority,
+            newly_revoked.len()
+        );
+
+        let revoked = self
+            .revocation_lists
+            .get(&authority)
+            .map(|list| list.revoked)
+            .unwrap_or_default();
+        for revoked_subject in revoked
+            .into_iter()
+            .filter(|r| newly_revoked.contains(&r.subject))
+        {
+            self.identities_repository
+                .delete_attested_by(
+                    &revoked_subject.subject,
+                    &authority,
+                    revoked_subject.revoked_at,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Receive someone's [`Credential`]: verify and put attributes from it to the storage
     pub async fn receive_presented_credential(
         &self,
@@ -178,24 +273,141 @@ impl CredentialsVerification {
             )
             .await?;
 
-        let map = credential_data.credential_data.subject_attributes.map;
+        let map = credential_data
+            .credential_data
+            .subject_attributes
+            .map
+            .clone();
         let map: BTreeMap<_, _> = map
             .into_iter()
             .map(|(k, v)| (Vec::<u8>::from(k), Vec::<u8>::from(v)))
             .collect();
 
+        self.put_attributes(subject, map, credential_data).await
+    }
+
+    /// Receive someone's [`Credential`] within a [`TrustContext`]: verify it against all the
+    /// authorities of the trust context and put attributes from it to the storage.
+    ///
+    /// If the issuing authority has a namespace, the attributes are prefixed with that namespace.
+    /// Only the authorities without a namespace can attest unprefixed attributes, like `trust_context_id`,
+    /// and they can't attest attributes in the namespace of another authority
+    pub async fn receive_presented_credential_for_trust_context(
+        &self,
+        subject: &Identifier,
+        trust_context: &TrustContext,
+        credential_and_purpose_key_attestation: &CredentialAndPurposeKey,
+    ) -> Result<()> {
+        let credential_data = self
+            .verify_credential(
+                Some(subject),
+                trust_context.authorities().await?.as_slice(),
+                credential_and_purpose_key_attestation,
+            )
+            .await?;
+
+        let map = credential_data
+            .credential_data
+            .subject_attributes
+            .map
+            .clone();
+        let map: BTreeMap<_, _> =
+            match trust_context.namespace(&credential_data.purpose_key_data.subject) {
+                Some(namespace) => map
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let mut key = Vec::<u8>::from(namespace.as_bytes());
+                        key.push(b'.');
+                        key.extend_from_slice(&Vec::<u8>::from(k));
+                        (key, Vec::<u8>::from(v))
+                    })
+                    .collect(),
+                None => map
+                    .into_iter()
+                    .map(|(k, v)| (Vec::<u8>::from(k), Vec::<u8>::from(v)))
+                    .collect(),
+            };
+
+        if trust_context
+            .namespace(&credential_data.purpose_key_data.subject)
+            .is_none()
+        {
+            for namespace in trust_context.namespaces() {
+                let mut prefix = namespace.as_bytes().to_vec();
+                prefix.push(b'.');
+                if map.keys().any(|k| k.starts_with(&prefix)) {
+                    // The attributes of a namespace can only be attested by the authority of that namespace
+                    return Err(IdentityError::CredentialVerificationFailed.into());
+                }
+            }
+        }
+
+        self.put_attributes(subject, map, credential_data).await
+    }
+}
+
+impl CredentialsVerification {
+    /// Verify that some data was signed by one of the authorities
+    /// using the key attested by the given [`PurposeKeyAttestation`]
+    async fn verify_issuer_signature(
+        &self,
+        authorities: &[Identifier],
+        purpose_key_attestation: &PurposeKeyAttestation,
+        data: &[u8],
+        signature: &CredentialSignature,
+    ) -> Result<PurposeKeyAttestationData> {
+        let purpose_key_data = self
+            .purpose_keys_verification
+            .verify_purpose_key_attestation(None, purpose_key_attestation)
+            .await?;
+
+        if !authorities.contains(&purpose_key_data.subject) {
+            return Err(IdentityError::UnknownAuthority.into());
+        }
+
+        let public_key = match purpose_key_data.public_key.clone() {
+            PurposePublicKey::SecureChannelStatic(_) => {
+                return Err(IdentityError::InvalidKeyType.into())
+            }
+
+            PurposePublicKey::CredentialSigning(public_key) => public_key,
+        };
+
+        let public_key = public_key.into();
+
+        let versioned_data_hash = self.verifying_vault.sha256(data).await?;
+
+        let signature = signature.clone().into();
+
+        if !self
+            .verifying_vault
+            .verify_signature(&public_key, &versioned_data_hash.0, &signature)
+            .await?
+        {
+            return Err(IdentityError::CredentialVerificationFailed.into());
+        }
+
+        Ok(purpose_key_data)
+    }
+
+    /// Store the attributes of a verified credential
+    async fn put_attributes(
+        &self,
+        subject: &Identifier,
+        attributes: BTreeMap<Vec<u8>, Vec<u8>>,
+        credential_data: CredentialAndPurposeKeyData,
+    ) -> Result<()> {
         self.identities_repository
             .put_attributes(
                 subject,
                 AttributesEntry::new(
-                    map,
+                    attributes,
                     now()?,
                     Some(credential_data.credential_data.expires_at),
                     Some(credential_data.purpose_key_data.subject),
-                ),
+                )
+                .with_credential_created_at(credential_data.credential_data.created_at),
             )
-            .await?;
-
-        Ok(())
+            .await
     }
 }
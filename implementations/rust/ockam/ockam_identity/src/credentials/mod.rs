@@ -8,6 +8,10 @@ mod credentials_server;
 mod credentials_server_worker;
 mod credentials_verification;
 mod one_time_code;
+mod revocation_list_issuer;
+mod revocation_list_refresher;
+mod revocation_list_retriever;
+mod revocation_lists;
 mod trust_context;
 
 pub use authority_service::*;
@@ -18,4 +22,8 @@ pub use credentials_retriever::*;
 pub use credentials_server::*;
 pub use credentials_verification::*;
 pub use one_time_code::*;
+pub use revocation_list_issuer::*;
+pub use revocation_list_refresher::*;
+pub use revocation_list_retriever::*;
+pub use revocation_lists::*;
 pub use trust_context::*;
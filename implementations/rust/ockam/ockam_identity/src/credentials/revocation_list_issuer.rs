@@ -0,0 +1,195 @@
+use crate::models::{Identifier, RevocationListAndPurposeKey, RevokedSubject, TimestampInSeconds};
+use crate::storage::Storage;
+use crate::utils::now;
+use crate::{
+    secure_channel_required, Credentials, IdentitySecureChannelLocalInfo, MAX_CREDENTIAL_VALIDITY,
+};
+
+use ockam_core::api::{Method, RequestHeader, Response};
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::string::ToString;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Result, Routed, Worker};
+use ockam_node::compat::asynchronous::Mutex;
+use ockam_node::Context;
+
+use core::time::Duration;
+use minicbor::{Decode, Decoder, Encode};
+use tracing::{debug, trace};
+
+/// Key used to persist the revoked subjects of an issuer
+pub const REVOCATION_LIST_KEY: &str = "REVOCATION_LIST";
+
+/// Default validity of an issued revocation list. Nodes are expected to refresh their copy
+/// of the revocation list before it expires
+pub const DEFAULT_REVOCATION_LIST_VALIDITY: Duration = Duration::from_secs(10 * 60);
+
+/// Persisted state of a revocation list
+#[derive(Clone, Debug, Default, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+struct RevocationListState {
+    #[n(1)] version: u64,
+    #[n(2)] revoked: Vec<RevokedSubject>,
+}
+
+/// This struct maintains the list of subjects revoked by an issuer and issues
+/// signed, versioned revocation lists
+#[derive(Clone)]
+pub struct RevocationListIssuer {
+    credentials: Arc<Credentials>,
+    issuer: Identifier,
+    storage: Arc<dyn Storage>,
+    validity: Duration,
+    /// Serialize the updates of the persisted state, which is read and written by
+    /// the workers revoking subjects and issuing revocation lists
+    state_lock: Arc<Mutex<()>>,
+}
+
+impl RevocationListIssuer {
+    /// Create a new revocation list issuer persisting its state in the given storage
+    pub fn new(
+        credentials: Arc<Credentials>,
+        issuer: &Identifier,
+        storage: Arc<dyn Storage>,
+    ) -> Self {
+        Self {
+            credentials,
+            issuer: issuer.clone(),
+            storage,
+            validity: DEFAULT_REVOCATION_LIST_VALIDITY,
+            state_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Set the validity of the issued revocation lists
+    pub fn with_validity(mut self, validity: Duration) -> Self {
+        self.validity = validity;
+        self
+    }
+
+    /// Revoke all the credentials issued so far for a given subject
+    pub async fn revoke(&self, subject: &Identifier) -> Result<()> {
+        let _lock = self.state_lock.lock().await;
+        let mut state = self.get_state().await?;
+        let revoked_at = now()?;
+        state.revoked.retain(|r| &r.subject != subject);
+        state.revoked.push(RevokedSubject {
+            subject: subject.clone(),
+            revoked_at,
+        });
+        state.version += 1;
+        debug!(
+            "revoked the credentials of {}, revocation list version {}",
+            subject, state.version
+        );
+        self.put_state(&state).await
+    }
+
+    /// Return the list of currently revoked subjects
+    pub async fn revoked(&self) -> Result<Vec<RevokedSubject>> {
+        let _lock = self.state_lock.lock().await;
+        Ok(self.get_state().await?.revoked)
+    }
+
+    /// Issue a signed revocation list with the current list of revoked subjects
+    pub async fn issue_revocation_list(&self) -> Result<RevocationListAndPurposeKey> {
+        let state = {
+            let _lock = self.state_lock.lock().await;
+            self.get_state().await?
+        };
+        self.credentials
+            .credentials_creation()
+            .issue_revocation_list(&self.issuer, state.version, state.revoked, self.validity)
+            .await
+    }
+}
+
+impl RevocationListIssuer {
+    /// Return the persisted state, without the subjects for which all credentials have expired.
+    /// The state lock must be held since the pruned state is written back
+    async fn get_state(&self) -> Result<RevocationListState> {
+        let mut state: RevocationListState = match self
+            .storage
+            .get(&self.issuer.to_string(), REVOCATION_LIST_KEY)
+            .await?
+        {
+            Some(state) => minicbor::decode(&state)?,
+            None => RevocationListState::default(),
+        };
+
+        // A revocation can be forgotten once all the credentials it applies to have expired
+        let now = now()?;
+        let max_validity = TimestampInSeconds(MAX_CREDENTIAL_VALIDITY.as_secs());
+        let count = state.revoked.len();
+        state.revoked.retain(|r| r.revoked_at + max_validity >= now);
+        if state.revoked.len() != count {
+            state.version += 1;
+            self.put_state(&state).await?;
+        }
+
+        Ok(state)
+    }
+
+    async fn put_state(&self, state: &RevocationListState) -> Result<()> {
+        self.storage
+            .set(
+                &self.issuer.to_string(),
+                REVOCATION_LIST_KEY.to_string(),
+                minicbor::to_vec(state)?,
+            )
+            .await
+    }
+}
+
+/// This struct runs as a Worker to serve the revocation list of an issuer
+pub struct RevocationListIssuerWorker {
+    revocation_list_issuer: RevocationListIssuer,
+}
+
+impl RevocationListIssuerWorker {
+    /// Create a new revocation list issuer worker
+    pub fn new(revocation_list_issuer: RevocationListIssuer) -> Self {
+        Self {
+            revocation_list_issuer,
+        }
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for RevocationListIssuerWorker {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        if let Ok(i) = IdentitySecureChannelLocalInfo::find_info(m.local_message()) {
+            let from = i.their_identity_id();
+            let mut dec = Decoder::new(m.as_body());
+            let req: RequestHeader = dec.decode()?;
+            trace! {
+                target: "ockam_identity::credentials::revocation_list_issuer",
+                from   = %from,
+                id     = %req.id(),
+                method = ?req.method(),
+                path   = %req.path(),
+                body   = %req.has_body(),
+                "request"
+            }
+            let res = match (req.method(), req.path()) {
+                (Some(Method::Get), "/") | (Some(Method::Get), "/revocation_list") => {
+                    match self.revocation_list_issuer.issue_revocation_list().await {
+                        Ok(list) => Response::ok(&req).body(list).to_vec()?,
+                        Err(error) => {
+                            Response::internal_error(&req, &error.to_string()).to_vec()?
+                        }
+                    }
+                }
+                _ => Response::unknown_path(&req).to_vec()?,
+            };
+            c.send(m.return_route(), res).await
+        } else {
+            secure_channel_required(c, m).await
+        }
+    }
+}
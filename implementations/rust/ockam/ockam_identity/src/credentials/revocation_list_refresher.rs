@@ -0,0 +1,90 @@
+use crate::models::Identifier;
+use crate::AuthorityService;
+
+use core::time::Duration;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Address, AllowSourceAddress, Result, Routed, Worker};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+use tracing::{debug, warn};
+
+/// Default interval between 2 retrievals of an authority revocation list.
+/// This is the maximum time needed for a revoked credential to be rejected by a node
+pub const DEFAULT_REVOCATION_LIST_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Default maximum age of an authority revocation list. Once the list could not be
+/// refreshed for that long, the credentials of the authority are rejected
+pub const DEFAULT_REVOCATION_LIST_MAX_AGE: Duration = Duration::from_secs(300);
+
+/// This worker periodically retrieves the revocation list of an authority
+/// so that revoked credentials are rejected in a bounded time
+pub struct RevocationListRefresher {
+    authority: AuthorityService,
+    identifier: Identifier,
+    interval: Duration,
+    heartbeat: DelayedEvent<Vec<u8>>,
+}
+
+impl RevocationListRefresher {
+    /// Start a worker refreshing the revocation list of the authority.
+    /// The revocation list is retrieved using the given identifier to authenticate.
+    /// The credentials of the authority are rejected while its list is older than `max_age`
+    pub async fn start(
+        ctx: &Context,
+        address: Address,
+        authority: AuthorityService,
+        identifier: Identifier,
+        interval: Duration,
+        max_age: Duration,
+    ) -> Result<()> {
+        authority
+            .revocation_lists()
+            .require_freshness(authority.identifier(), max_age);
+
+        let heartbeat = DelayedEvent::create(ctx, address.clone(), vec![]).await?;
+        let heartbeat_address = heartbeat.address();
+
+        let refresher = Self {
+            authority,
+            identifier,
+            interval,
+            heartbeat,
+        };
+
+        debug!("starting a revocation list refresher at {}", address);
+        WorkerBuilder::new(refresher)
+            .with_address(address)
+            .with_incoming_access_control(AllowSourceAddress(heartbeat_address))
+            .start(ctx)
+            .await
+    }
+
+    async fn refresh(&mut self, ctx: &Context) -> Result<()> {
+        if let Err(e) = self
+            .authority
+            .refresh_revocation_list(ctx, &self.identifier)
+            .await
+        {
+            warn!(
+                "could not refresh the revocation list of {}: {}",
+                self.authority.identifier(),
+                e
+            );
+        }
+        self.heartbeat.schedule(self.interval).await
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for RevocationListRefresher {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.refresh(ctx).await
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, _m: Routed<Self::Message>) -> Result<()> {
+        self.refresh(ctx).await
+    }
+}
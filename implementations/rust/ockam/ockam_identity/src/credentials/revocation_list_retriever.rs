@@ -0,0 +1,71 @@
+use ockam_core::api::Request;
+use tracing::debug;
+
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Result};
+use ockam_node::{Context, DEFAULT_TIMEOUT};
+
+use crate::models::RevocationListAndPurposeKey;
+use crate::{Identifier, RemoteCredentialsRetrieverInfo, SecureChannels, SecureClient};
+
+/// Trait for retrieving the revocation list of an authority
+#[async_trait]
+pub trait RevocationListRetriever: Send + Sync + 'static {
+    /// Retrieve the latest revocation list, using a given identity to authenticate
+    async fn retrieve(
+        &self,
+        ctx: &Context,
+        for_identity: &Identifier,
+    ) -> Result<RevocationListAndPurposeKey>;
+}
+
+/// Revocation list retriever for revocation lists located on a different node
+pub struct RemoteRevocationListRetriever {
+    secure_channels: Arc<SecureChannels>,
+    issuer: RemoteCredentialsRetrieverInfo,
+}
+
+impl RemoteRevocationListRetriever {
+    /// Create a new remote revocation list retriever
+    pub fn new(
+        secure_channels: Arc<SecureChannels>,
+        issuer: RemoteCredentialsRetrieverInfo,
+    ) -> Self {
+        Self {
+            secure_channels,
+            issuer,
+        }
+    }
+}
+
+#[async_trait]
+impl RevocationListRetriever for RemoteRevocationListRetriever {
+    async fn retrieve(
+        &self,
+        ctx: &Context,
+        for_identity: &Identifier,
+    ) -> Result<RevocationListAndPurposeKey> {
+        debug!("Getting revocation list from: {}", &self.issuer.route);
+        let resolved_route = ctx
+            .resolve_transport_route(self.issuer.route.clone())
+            .await?;
+
+        let client = SecureClient::new(
+            self.secure_channels.clone(),
+            resolved_route,
+            &self.issuer.identifier,
+            for_identity,
+            DEFAULT_TIMEOUT,
+        );
+
+        client
+            .ask(
+                ctx,
+                self.issuer.service_address.address(),
+                Request::get("/"),
+            )
+            .await?
+            .success()
+    }
+}
@@ -0,0 +1,198 @@
+use crate::models::{Identifier, RevocationListData, TimestampInSeconds};
+use crate::utils::now;
+
+use core::time::Duration;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::RwLock;
+use ockam_core::compat::vec::Vec;
+
+/// Cache of the latest verified [`RevocationListData`] received from each Authority.
+///
+/// The cache is shared by all the services created from the same [`crate::Identities`]
+/// so that a revocation list received once is enforced both when verifying new credentials
+/// and when checking the attributes of already verified credentials.
+///
+/// An Authority can also require its revocation list to be refreshed regularly. When the
+/// cached list becomes older than the configured maximum age, for example because the Authority
+/// can't be reached anymore, revocations can't be ruled out and the credentials
+/// of that Authority must be rejected.
+#[derive(Default)]
+pub struct RevocationLists {
+    lists: RwLock<BTreeMap<Identifier, RevocationListData>>,
+    freshness: RwLock<BTreeMap<Identifier, Freshness>>,
+}
+
+/// Maximum age of the revocation list of an Authority and time of its last retrieval
+struct Freshness {
+    max_age: Duration,
+    refreshed_at: Option<TimestampInSeconds>,
+}
+
+impl RevocationLists {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the latest revocation list known for a given Authority
+    pub fn get(&self, authority: &Identifier) -> Option<RevocationListData> {
+        self.lists.read().unwrap().get(authority).cloned()
+    }
+
+    /// Store a revocation list for a given Authority if it is more recent than the cached one.
+    /// Return the subjects which were not revoked by the previous list if the list was stored,
+    /// and None if the list was older than the cached one
+    pub fn update(
+        &self,
+        authority: &Identifier,
+        list: RevocationListData,
+    ) -> Option<Vec<Identifier>> {
+        let mut guard = self.lists.write().unwrap();
+
+        let newly_revoked = match guard.get(authority) {
+            Some(current) if current.version >= list.version => return None,
+            Some(current) => list
+                .revoked
+                .iter()
+                .filter(|r| !current.revoked.contains(r))
+                .map(|r| r.subject.clone())
+                .collect(),
+            None => list.revoked.iter().map(|r| r.subject.clone()).collect(),
+        };
+
+        guard.insert(authority.clone(), list);
+
+        Some(newly_revoked)
+    }
+
+    /// Require the revocation list of an Authority to be refreshed at least
+    /// every `max_age` for its credentials to be accepted
+    pub fn require_freshness(&self, authority: &Identifier, max_age: Duration) {
+        let mut guard = self.freshness.write().unwrap();
+        let refreshed_at = guard.get(authority).and_then(|f| f.refreshed_at);
+        guard.insert(
+            authority.clone(),
+            Freshness {
+                max_age,
+                refreshed_at,
+            },
+        );
+    }
+
+    /// Record that the revocation list of an Authority was just retrieved and verified,
+    /// even if it didn't change
+    pub fn refreshed(&self, authority: &Identifier) {
+        if let Some(freshness) = self.freshness.write().unwrap().get_mut(authority) {
+            freshness.refreshed_at = now().ok();
+        }
+    }
+
+    /// Return true if the revocation list of the Authority must be refreshed regularly
+    /// and was not refreshed within its maximum age
+    pub fn is_stale(&self, authority: &Identifier) -> bool {
+        match self.freshness.read().unwrap().get(authority) {
+            Some(Freshness {
+                max_age,
+                refreshed_at: Some(refreshed_at),
+            }) => match now() {
+                Ok(now) => now.0.saturating_sub(refreshed_at.0) > max_age.as_secs(),
+                Err(_) => true,
+            },
+            Some(Freshness {
+                refreshed_at: None, ..
+            }) => true,
+            None => false,
+        }
+    }
+
+    /// Return true if a credential issued by the Authority for that subject
+    /// at the given time has been revoked
+    pub fn is_revoked(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+        created_at: TimestampInSeconds,
+    ) -> bool {
+        match self.lists.read().unwrap().get(authority) {
+            Some(list) => list
+                .revoked
+                .iter()
+                .any(|r| &r.subject == subject && created_at <= r.revoked_at),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RevokedSubject;
+
+    #[test]
+    fn test_revocation_lists() {
+        let authority = Identifier([1; 20]);
+        let subject = Identifier([2; 20]);
+        let other = Identifier([3; 20]);
+
+        let lists = RevocationLists::new();
+        assert!(!lists.is_revoked(&authority, &subject, TimestampInSeconds(10)));
+
+        let list = |version, revoked_at| RevocationListData {
+            version,
+            revoked: vec![RevokedSubject {
+                subject: subject.clone(),
+                revoked_at: TimestampInSeconds(revoked_at),
+            }],
+            created_at: TimestampInSeconds(0),
+            expires_at: TimestampInSeconds(100),
+        };
+
+        assert_eq!(
+            lists.update(&authority, list(2, 10)),
+            Some(vec![subject.clone()])
+        );
+        assert!(lists.is_revoked(&authority, &subject, TimestampInSeconds(10)));
+        assert!(!lists.is_revoked(&authority, &subject, TimestampInSeconds(11)));
+        assert!(!lists.is_revoked(&authority, &other, TimestampInSeconds(10)));
+        assert!(!lists.is_revoked(&other, &subject, TimestampInSeconds(10)));
+
+        // older or identical versions are ignored
+        assert_eq!(lists.update(&authority, list(1, 20)), None);
+        assert_eq!(lists.update(&authority, list(2, 20)), None);
+        assert!(!lists.is_revoked(&authority, &subject, TimestampInSeconds(15)));
+
+        // a new revocation time for the same subject is reported as a new revocation
+        assert_eq!(
+            lists.update(&authority, list(3, 20)),
+            Some(vec![subject.clone()])
+        );
+        assert!(lists.is_revoked(&authority, &subject, TimestampInSeconds(15)));
+    }
+
+    #[test]
+    fn test_revocation_lists_freshness() {
+        let authority = Identifier([1; 20]);
+        let other = Identifier([3; 20]);
+
+        let lists = RevocationLists::new();
+        assert!(!lists.is_stale(&authority));
+
+        // a list which must be fresh is stale until it is retrieved
+        lists.require_freshness(&authority, Duration::from_secs(60));
+        assert!(lists.is_stale(&authority));
+        assert!(!lists.is_stale(&other));
+
+        lists.refreshed(&authority);
+        assert!(!lists.is_stale(&authority));
+
+        lists.require_freshness(&authority, Duration::ZERO);
+        lists
+            .freshness
+            .write()
+            .unwrap()
+            .get_mut(&authority)
+            .unwrap()
+            .refreshed_at = Some(TimestampInSeconds(now().unwrap().0 - 1));
+        assert!(lists.is_stale(&authority));
+    }
+}
@@ -8,19 +8,27 @@ use crate::models::{CredentialAndPurposeKey, Identifier};
 use crate::{AuthorityService, IdentityError};
 
 /// A trust context defines which authorities are trusted to attest to which attributes, within a context.
-/// Our first implementation assumes that there is only one authority and it is trusted to attest to all attributes within this context.
+///
+/// The first authority of a trust context is used to retrieve the credentials of the current node.
+/// The attributes attested by an authority having a namespace are stored with that namespace as a prefix
+/// so that they cannot be confused with the attributes attested by other authorities.
 #[derive(Clone)]
 pub struct TrustContext {
     /// This is the ID of the trust context; which is primarily used for ABAC policies
     id: String,
-    /// Authority capable of retrieving credentials
-    authority: Option<AuthorityService>,
+    /// Authorities trusted by this context
+    authorities: Vec<AuthorityService>,
 }
 
 impl TrustContext {
     /// Create a new Trust Context
     pub fn new(id: String, authority: Option<AuthorityService>) -> Self {
-        Self { id, authority }
+        Self::new_with_authorities(id, authority.into_iter().collect())
+    }
+
+    /// Create a new Trust Context trusting a set of authorities
+    pub fn new_with_authorities(id: String, authorities: Vec<AuthorityService>) -> Self {
+        Self { id, authorities }
     }
 
     /// Return the ID of the Trust Context
@@ -28,16 +36,44 @@ impl TrustContext {
         &self.id
     }
 
-    /// Return the Authority of the Trust Context
+    /// Return the Authority of the Trust Context, used to retrieve credentials
     pub fn authority(&self) -> Result<&AuthorityService> {
-        self.authority
-            .as_ref()
+        self.authorities
+            .first()
             .ok_or_else(|| IdentityError::UnknownAuthority.into())
     }
 
+    /// Return all the Authorities of the Trust Context
+    pub fn authority_services(&self) -> &[AuthorityService] {
+        &self.authorities
+    }
+
     /// Return the authority identities attached to this trust context
     pub async fn authorities(&self) -> Result<Vec<Identifier>> {
-        Ok(vec![self.authority()?.identifier().clone()])
+        if self.authorities.is_empty() {
+            return Err(IdentityError::UnknownAuthority.into());
+        }
+        Ok(self
+            .authorities
+            .iter()
+            .map(|a| a.identifier().clone())
+            .collect())
+    }
+
+    /// Return the namespace of the attributes attested by a given authority
+    pub fn namespace(&self, authority: &Identifier) -> Option<&str> {
+        self.authorities
+            .iter()
+            .find(|a| a.identifier() == authority)
+            .and_then(|a| a.namespace())
+    }
+
+    /// Return the namespaces of the authorities of the Trust Context
+    pub fn namespaces(&self) -> Vec<&str> {
+        self.authorities
+            .iter()
+            .filter_map(|a| a.namespace())
+            .collect()
     }
 
     /// Return the credential for a given identity if an Authority has been defined
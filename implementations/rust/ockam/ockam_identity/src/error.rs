@@ -50,6 +50,20 @@ pub enum IdentityError {
     InvalidHex,
     /// Secret Key doesn't correspond to the Identity
     WrongSecretKey,
+    /// Credential was revoked by its issuer
+    CredentialRevoked,
+    /// The Revocation List of the credential issuer could not be refreshed recently enough
+    RevocationListOutdated,
+    /// Revocation List Verification Failed
+    RevocationListVerificationFailed,
+    /// Unknown version of the Revocation List
+    UnknownRevocationListVersion,
+    /// Recovery Key is not registered in the latest change of the Identity
+    UnknownRecoveryKey,
+    /// Recovery Keys can only be changed by a change signed with one of them
+    RecoveryKeysChangeNotAllowed,
+    /// Resumption ticket is invalid, has expired or wasn't issued by this listener
+    ResumptionTicketRejected,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
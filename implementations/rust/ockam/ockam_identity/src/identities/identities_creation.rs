@@ -129,6 +129,49 @@ impl IdentitiesCreation {
         Ok(())
     }
 
+    /// Replace the primary key of an existing `Identity` using one of its recovery keys
+    /// and update the stored version. All the previously issued Purpose Keys are revoked
+    pub async fn recover_identity(
+        &self,
+        identifier: &Identifier,
+        recovery_secret_key: SigningSecretKeyHandle,
+    ) -> Result<()> {
+        let builder = self.identity_builder().with_purpose_keys_revocation();
+        let options = builder.build_options().await?;
+
+        self.recover_identity_with_options(identifier, options, recovery_secret_key)
+            .await
+    }
+
+    /// Replace the primary key of an existing `Identity` using one of its recovery keys
+    /// and update the stored version
+    pub async fn recover_identity_with_options(
+        &self,
+        identifier: &Identifier,
+        options: IdentityOptions,
+        recovery_secret_key: SigningSecretKeyHandle,
+    ) -> Result<()> {
+        let change_history = self.repository.get_identity(identifier).await?;
+
+        let identity = Identity::import_from_change_history(
+            Some(identifier),
+            change_history,
+            self.verifying_vault.clone(),
+        )
+        .await?;
+
+        let identity = self
+            .identities_keys()
+            .recover_key_with_options(identity, options, recovery_secret_key)
+            .await?;
+
+        self.repository
+            .update_identity(identity.identifier(), identity.change_history())
+            .await?;
+
+        Ok(())
+    }
+
     /// Import an existing Identity from its binary format
     /// Its secret is expected to exist in the Vault (either generated there, or some Vault
     /// implementations may allow importing a secret)
@@ -187,7 +230,7 @@ impl IdentitiesCreation {
                 IdentityHistoryComparison::Conflict | IdentityHistoryComparison::Older => {
                     return Err(IdentityError::ConsistencyError.into());
                 }
-                IdentityHistoryComparison::Newer => {
+                IdentityHistoryComparison::Newer | IdentityHistoryComparison::Recovered => {
                     self.repository
                         .update_identity(identity.identifier(), identity.change_history())
                         .await?;
@@ -1,6 +1,7 @@
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
-use ockam_vault::{SigningKeyType, SigningSecretKeyHandle};
+use ockam_vault::{SigningKeyType, SigningSecretKeyHandle, VerifyingPublicKey};
 
 use crate::models::TimestampInSeconds;
 use crate::utils::now;
@@ -28,6 +29,7 @@ pub struct IdentityBuilder {
     identities_creation: Arc<IdentitiesCreation>,
 
     revoke_all_purpose_keys: bool,
+    recovery_public_keys: Option<Vec<VerifyingPublicKey>>,
     key: Key,
     ttl: Ttl,
 }
@@ -38,6 +40,7 @@ impl IdentityBuilder {
         Self {
             identities_creation,
             revoke_all_purpose_keys: false,
+            recovery_public_keys: None,
             key: Key::Generate(SigningKeyType::EdDSACurve25519),
             ttl: Ttl::CreatedNowWithTtl(DEFAULT_IDENTITY_TTL),
         }
@@ -80,6 +83,15 @@ impl IdentityBuilder {
         self
     }
 
+    /// Register offline recovery keys which can replace a lost or compromised primary key
+    pub fn with_recovery_public_keys(
+        mut self,
+        recovery_public_keys: Vec<VerifyingPublicKey>,
+    ) -> Self {
+        self.recovery_public_keys = Some(recovery_public_keys);
+        self
+    }
+
     /// Create the corresponding [`IdentityOptions`] object
     pub async fn build_options(self) -> Result<IdentityOptions> {
         let key = match self.key {
@@ -105,8 +117,11 @@ impl IdentityBuilder {
             } => (created_at, expires_at),
         };
 
-        let options =
+        let mut options =
             IdentityOptions::new(key, self.revoke_all_purpose_keys, created_at, expires_at);
+        if let Some(recovery_public_keys) = self.recovery_public_keys {
+            options = options.with_recovery_public_keys(recovery_public_keys);
+        }
 
         Ok(options)
     }
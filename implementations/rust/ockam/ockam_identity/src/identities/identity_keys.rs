@@ -47,13 +47,27 @@ impl IdentitiesKeys {
     pub async fn rotate_key_with_options(
         &self,
         identity: Identity,
-        options: IdentityOptions,
+        mut options: IdentityOptions,
     ) -> Result<Identity> {
         let last_change = match identity.changes().last() {
             Some(last_change) => last_change,
             None => return Err(IdentityError::EmptyIdentity.into()),
         };
 
+        // Keep the previously registered recovery keys. They can only be added with the primary
+        // key when there are none yet, otherwise they must be changed with a recovery key
+        let recovery_public_keys = last_change.recovery_public_keys();
+        match &options.recovery_public_keys {
+            None => options.recovery_public_keys = Some(recovery_public_keys),
+            Some(new_recovery_public_keys)
+                if !recovery_public_keys.is_empty()
+                    && new_recovery_public_keys != &recovery_public_keys =>
+            {
+                return Err(IdentityError::RecoveryKeysChangeNotAllowed.into())
+            }
+            Some(_) => {}
+        }
+
         let last_secret_key = self.get_secret_key(&identity).await?;
 
         let change = self
@@ -82,6 +96,63 @@ impl IdentitiesKeys {
         Ok(identity)
     }
 
+    /// Replace the Identity Key using a recovery key registered in the latest change.
+    /// The recovery key should be present in the Vault, the previous Identity Key is
+    /// not needed and is deleted if it is still present
+    pub async fn recover_key_with_options(
+        &self,
+        identity: Identity,
+        mut options: IdentityOptions,
+        recovery_secret_key: SigningSecretKeyHandle,
+    ) -> Result<Identity> {
+        let last_change = match identity.changes().last() {
+            Some(last_change) => last_change,
+            None => return Err(IdentityError::EmptyIdentity.into()),
+        };
+
+        let recovery_public_key = self
+            .identity_vault
+            .get_verifying_public_key(&recovery_secret_key)
+            .await?;
+        let recovery_public_keys = last_change.recovery_public_keys();
+        if !recovery_public_keys.contains(&recovery_public_key) {
+            return Err(IdentityError::UnknownRecoveryKey.into());
+        }
+
+        if options.recovery_public_keys.is_none() {
+            options.recovery_public_keys = Some(recovery_public_keys);
+        }
+
+        let last_secret_key = self.get_secret_key(&identity).await.ok();
+
+        let change = self
+            .make_change(
+                options,
+                Some((last_change.change_hash().clone(), recovery_secret_key)),
+            )
+            .await?;
+
+        let identity = identity
+            .add_change(change, self.verifying_vault.clone())
+            .await?;
+
+        if let Some(last_secret_key) = last_secret_key {
+            if self
+                .identity_vault
+                .delete_signing_secret_key(last_secret_key)
+                .await
+                .is_err()
+            {
+                error!(
+                    "Error deleting compromised Identity Key for {}",
+                    identity.identifier()
+                );
+            }
+        }
+
+        Ok(identity)
+    }
+
     /// Return the secret key of an identity
     pub async fn get_secret_key(&self, identity: &Identity) -> Result<SigningSecretKeyHandle> {
         if let Some(last_change) = identity.changes().last() {
@@ -114,6 +185,9 @@ impl IdentitiesKeys {
             revoke_all_purpose_keys: identity_options.revoke_all_purpose_keys,
             created_at: identity_options.created_at,
             expires_at: identity_options.expires_at,
+            recovery_public_keys: identity_options
+                .recovery_public_keys
+                .map(|keys| keys.into_iter().map(|k| k.into()).collect()),
         };
 
         let change_data = minicbor::to_vec(&change_data)?;
@@ -157,6 +231,7 @@ mod test {
     use crate::identities;
     use crate::models::Identifier;
     use crate::utils::now;
+    use crate::IdentityHistoryComparison;
     use core::str::FromStr;
     use ockam_core::errcode::{Kind, Origin};
     use ockam_core::Error;
@@ -263,4 +338,119 @@ mod test {
 
         ctx.stop().await
     }
+
+    #[ockam_macros::test]
+    async fn test_identity_key_recovery(ctx: &mut Context) -> Result<()> {
+        let identities = identities();
+        let identities_keys = identities.identities_keys();
+        let vault = identities_keys.identity_vault.clone();
+
+        let recovery_key = vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        let recovery_public_key = vault.get_verifying_public_key(&recovery_key).await?;
+
+        let key1 = vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        let now = now()?;
+        let options1 = IdentityOptions::new(key1, false, now, now + 120.into())
+            .with_recovery_public_keys(vec![recovery_public_key.clone()]);
+        let identity1 = identities_keys.create_initial_key(options1).await?;
+
+        // The attacker rotates the compromised key, recovery keys are kept
+        let key2 = vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        let options2 = IdentityOptions::new(key2, false, now + 10.into(), now + 120.into());
+        let compromised = identities_keys
+            .rotate_key_with_options(identity1.clone(), options2)
+            .await?;
+        assert_eq!(
+            compromised.changes().last().unwrap().recovery_public_keys(),
+            vec![recovery_public_key.clone()]
+        );
+
+        // The owner replaces the compromised key using the recovery key
+        let key3 = vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        let options3 = IdentityOptions::new(key3.clone(), true, now + 20.into(), now + 120.into());
+        let recovered = identities_keys
+            .recover_key_with_options(identity1.clone(), options3, recovery_key.clone())
+            .await?;
+
+        let imported = Identity::import_from_change_history(
+            Some(identity1.identifier()),
+            recovered.change_history().clone(),
+            identities.vault().verifying_vault,
+        )
+        .await?;
+        assert!(imported
+            .changes()
+            .last()
+            .unwrap()
+            .signed_with_recovery_key());
+        assert_eq!(identities_keys.get_secret_key(&recovered).await?, key3);
+
+        // The recovered history wins over the compromised one
+        assert_eq!(
+            recovered.compare(&compromised),
+            IdentityHistoryComparison::Recovered
+        );
+        assert_eq!(
+            compromised.compare(&recovered),
+            IdentityHistoryComparison::Older
+        );
+
+        // The compromised key can't replace or remove the recovery keys
+        let attacker_recovery_key = vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        let attacker_recovery_public_key = vault
+            .get_verifying_public_key(&attacker_recovery_key)
+            .await?;
+        for recovery_public_keys in [vec![attacker_recovery_public_key], vec![]] {
+            let key = vault
+                .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+                .await?;
+            let options = |key| {
+                IdentityOptions::new(key, false, now + 20.into(), now + 120.into())
+                    .with_recovery_public_keys(recovery_public_keys.clone())
+            };
+            assert!(identities_keys
+                .rotate_key_with_options(compromised.clone(), options(key.clone()))
+                .await
+                .is_err());
+
+            // A change built without the checks of rotate_key_with_options is rejected too
+            let last_change = compromised.changes().last().unwrap();
+            let change = identities_keys
+                .make_change(
+                    options(key),
+                    Some((
+                        last_change.change_hash().clone(),
+                        identities_keys.get_secret_key(&compromised).await?,
+                    )),
+                )
+                .await?;
+            assert!(compromised
+                .clone()
+                .add_change(change, identities.vault().verifying_vault)
+                .await
+                .is_err());
+        }
+
+        // A key which is not registered as a recovery key can't be used
+        let unknown_key = vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        let options4 = IdentityOptions::new(key3, false, now + 30.into(), now + 120.into());
+        assert!(identities_keys
+            .recover_key_with_options(recovered, options4, unknown_key)
+            .await
+            .is_err());
+
+        ctx.stop().await
+    }
 }
@@ -1,5 +1,6 @@
 use crate::TimestampInSeconds;
-use ockam_vault::SigningSecretKeyHandle;
+use ockam_core::compat::vec::Vec;
+use ockam_vault::{SigningSecretKeyHandle, VerifyingPublicKey};
 
 /// Options to create an Identity key
 pub struct IdentityOptions {
@@ -7,6 +8,7 @@ pub struct IdentityOptions {
     pub(super) revoke_all_purpose_keys: bool,
     pub(super) created_at: TimestampInSeconds,
     pub(super) expires_at: TimestampInSeconds,
+    pub(super) recovery_public_keys: Option<Vec<VerifyingPublicKey>>,
 }
 
 impl IdentityOptions {
@@ -22,9 +24,21 @@ impl IdentityOptions {
             revoke_all_purpose_keys,
             created_at,
             expires_at,
+            recovery_public_keys: None,
         }
     }
 
+    /// Register recovery public keys which can sign the next change instead of the new key.
+    /// If not set, the recovery keys of the previous change are kept when rotating.
+    /// Once registered, the recovery keys can only be changed when recovering the identity
+    pub fn with_recovery_public_keys(
+        mut self,
+        recovery_public_keys: Vec<VerifyingPublicKey>,
+    ) -> Self {
+        self.recovery_public_keys = Some(recovery_public_keys);
+        self
+    }
+
     /// New key
     pub fn signing_secret_key_handle(&self) -> &SigningSecretKeyHandle {
         &self.signing_secret_key_handle
@@ -44,4 +58,9 @@ impl IdentityOptions {
     pub fn expires_at(&self) -> TimestampInSeconds {
         self.expires_at
     }
+
+    /// Recovery public keys
+    pub fn recovery_public_keys(&self) -> Option<&[VerifyingPublicKey]> {
+        self.recovery_public_keys.as_deref()
+    }
 }
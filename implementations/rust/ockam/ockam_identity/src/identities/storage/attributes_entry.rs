@@ -14,6 +14,8 @@ pub struct AttributesEntry {
     #[n(2)] added: TimestampInSeconds,
     #[n(3)] expires: Option<TimestampInSeconds>,
     #[n(4)] attested_by: Option<Identifier>,
+    #[n(5)] credential_created_at: Option<TimestampInSeconds>,
+    #[n(6)] attested_entries: Option<Vec<AttributesEntry>>,
 }
 
 impl AttributesEntry {
@@ -33,9 +35,17 @@ impl AttributesEntry {
             added,
             expires,
             attested_by,
+            credential_created_at: None,
+            attested_entries: None,
         }
     }
 
+    /// Set the creation time of the credential which attested these attributes
+    pub fn with_credential_created_at(mut self, created_at: TimestampInSeconds) -> Self {
+        self.credential_created_at = Some(created_at);
+        self
+    }
+
     /// The entry attributes
     pub fn attrs(&self) -> &BTreeMap<Vec<u8>, Vec<u8>> {
         &self.attrs
@@ -51,8 +61,61 @@ impl AttributesEntry {
         self.added
     }
 
+    /// Creation time of the credential which attested these attributes, if any
+    pub fn credential_created_at(&self) -> Option<TimestampInSeconds> {
+        self.credential_created_at
+    }
+
+    /// Creation time of the attested attributes: the creation time of their credential,
+    /// or the date the entry was added for entries which were not stored from a credential
+    pub fn created_at(&self) -> TimestampInSeconds {
+        self.credential_created_at.unwrap_or(self.added)
+    }
+
     /// Who attested this attributes for this identity identifier
     pub fn attested_by(&self) -> Option<Identifier> {
         self.attested_by.to_owned()
     }
+
+    /// Return true if these attributes were attested by an authority with a credential
+    pub fn is_attested_by_credential(&self) -> bool {
+        self.attested_by.is_some() && self.credential_created_at.is_some()
+    }
+
+    /// Return the entries attested by each authority which were merged into this entry,
+    /// or this entry alone if it was not merged
+    pub fn attested_entries(&self) -> Vec<AttributesEntry> {
+        match &self.attested_entries {
+            Some(entries) => entries.clone(),
+            None => vec![self.clone()],
+        }
+    }
+
+    /// Merge the attributes attested by several authorities.
+    /// The last entry takes precedence: its attributes override the attributes with the same names
+    /// and it determines the authority and creation time of the merged entry.
+    /// The merged entry expires with the first expiring entry and keeps the merged entries
+    /// so that the revocation of each authority can still be checked on its own entry
+    pub fn merge(entries: &[AttributesEntry]) -> Option<AttributesEntry> {
+        let entries: Vec<AttributesEntry> =
+            entries.iter().flat_map(|e| e.attested_entries()).collect();
+        let (last, others) = entries.split_last()?;
+        let mut merged = last.clone();
+        if others.is_empty() {
+            return Some(merged);
+        }
+        for entry in others {
+            for (name, value) in entry.attrs.iter() {
+                if !merged.attrs.contains_key(name) {
+                    merged.attrs.insert(name.clone(), value.clone());
+                }
+            }
+            merged.expires = match (merged.expires, entry.expires) {
+                (Some(e1), Some(e2)) => Some(e1.min(e2)),
+                (e1, e2) => e1.or(e2),
+            };
+        }
+        merged.attested_entries = Some(entries);
+        Some(merged)
+    }
 }
@@ -7,7 +7,7 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 
 use crate::identity::IdentityConstants;
-use crate::models::{ChangeHistory, Identifier};
+use crate::models::{ChangeHistory, Identifier, TimestampInSeconds};
 use crate::storage::{InMemoryStorage, Storage};
 use crate::utils::now;
 use crate::{
@@ -52,6 +52,69 @@ impl IdentitiesStorage {
     }
 }
 
+impl IdentitiesStorage {
+    /// Return the attributes attested with a credential by each authority, excluding the expired ones
+    async fn get_attested_entries(&self, identity_id: &Identifier) -> Result<Vec<AttributesEntry>> {
+        let id = identity_id.to_string();
+        let entries: Vec<AttributesEntry> = match self
+            .storage
+            .get(&id, IdentityConstants::ATTESTED_ATTRIBUTES_KEY)
+            .await?
+        {
+            Some(entries) => minicbor::decode(&entries)?,
+            // attributes stored before they were kept per authority
+            None => match self
+                .storage
+                .get(&id, IdentityConstants::ATTRIBUTES_KEY)
+                .await?
+            {
+                Some(entry) => {
+                    let entry: AttributesEntry = minicbor::decode(&entry)?;
+                    if entry.is_attested_by_credential() {
+                        vec![entry]
+                    } else {
+                        vec![]
+                    }
+                }
+                None => vec![],
+            },
+        };
+
+        let now = now()?;
+        Ok(entries
+            .into_iter()
+            .filter(|e| !matches!(e.expires(), Some(exp) if exp <= now))
+            .collect())
+    }
+
+    /// Store the attributes attested by each authority along with their merged attributes
+    async fn set_attested_entries(
+        &self,
+        identity_id: &Identifier,
+        entries: Vec<AttributesEntry>,
+    ) -> Result<()> {
+        let id = identity_id.to_string();
+        let merged = match AttributesEntry::merge(&entries) {
+            Some(merged) => merged,
+            None => return self.delete(identity_id).await,
+        };
+        self.storage
+            .set(
+                &id,
+                IdentityConstants::ATTESTED_ATTRIBUTES_KEY.to_string(),
+                minicbor::to_vec(&entries)?,
+            )
+            .await?;
+        self.storage
+            .set(
+                &id,
+                IdentityConstants::ATTRIBUTES_KEY.to_string(),
+                minicbor::to_vec(&merged)?,
+            )
+            .await
+    }
+}
+
 #[async_trait]
 impl IdentityAttributesReader for IdentitiesStorage {
     async fn get_attributes(&self, identity_id: &Identifier) -> Result<Option<AttributesEntry>> {
@@ -70,10 +133,15 @@ impl IdentityAttributesReader for IdentitiesStorage {
         let now = now()?;
         match entry.expires() {
             Some(exp) if exp <= now => {
-                self.storage
-                    .del(&id, IdentityConstants::ATTRIBUTES_KEY)
-                    .await?;
-                Ok(None)
+                // keep the attributes attested by the authorities whose credentials did not expire
+                let entries = if entry.is_attested_by_credential() {
+                    self.get_attested_entries(identity_id).await?
+                } else {
+                    vec![]
+                };
+                let merged = AttributesEntry::merge(&entries);
+                self.set_attested_entries(identity_id, entries).await?;
+                Ok(merged)
             }
             _ => Ok(Some(entry)),
         }
@@ -94,9 +162,28 @@ impl IdentityAttributesReader for IdentitiesStorage {
 #[async_trait]
 impl IdentityAttributesWriter for IdentitiesStorage {
     async fn put_attributes(&self, sender: &Identifier, entry: AttributesEntry) -> Result<()> {
+        // The attributes attested by different authorities are kept side by side
+        // so that the credential of one authority doesn't erase the attributes of another one
+        if entry.is_attested_by_credential() {
+            let mut entries: Vec<AttributesEntry> = self
+                .get_attested_entries(sender)
+                .await?
+                .into_iter()
+                .filter(|e| e.attested_by() != entry.attested_by())
+                .collect();
+            entries.push(entry);
+            return self.set_attested_entries(sender, entries).await;
+        }
+
         // TODO: Implement expiration mechanism in Storage
         let entry = minicbor::to_vec(&entry)?;
 
+        self.storage
+            .del(
+                &sender.to_string(),
+                IdentityConstants::ATTESTED_ATTRIBUTES_KEY,
+            )
+            .await?;
         self.storage
             .set(
                 &sender.to_string(),
@@ -125,6 +212,12 @@ impl IdentityAttributesWriter for IdentitiesStorage {
     }
 
     async fn delete(&self, identity: &Identifier) -> Result<()> {
+        self.storage
+            .del(
+                identity.to_string().as_str(),
+                IdentityConstants::ATTESTED_ATTRIBUTES_KEY,
+            )
+            .await?;
         self.storage
             .del(
                 identity.to_string().as_str(),
@@ -132,6 +225,29 @@ impl IdentityAttributesWriter for IdentitiesStorage {
             )
             .await
     }
+
+    async fn delete_attested_by(
+        &self,
+        identity: &Identifier,
+        authority: &Identifier,
+        created_until: TimestampInSeconds,
+    ) -> Result<()> {
+        let is_revoked = |e: &AttributesEntry| {
+            e.attested_by().as_ref() == Some(authority) && e.created_at() <= created_until
+        };
+        match self.get_attributes(identity).await? {
+            Some(entry) if entry.is_attested_by_credential() => {
+                let entries = self.get_attested_entries(identity).await?;
+                if entries.iter().any(is_revoked) {
+                    let entries = entries.into_iter().filter(|e| !is_revoked(e)).collect();
+                    self.set_attested_entries(identity, entries).await?;
+                }
+                Ok(())
+            }
+            Some(entry) if is_revoked(&entry) => self.delete(identity).await,
+            _ => Ok(()),
+        }
+    }
 }
 
 #[async_trait]
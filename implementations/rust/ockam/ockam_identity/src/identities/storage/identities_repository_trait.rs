@@ -5,7 +5,7 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Result;
 use ockam_core::{async_trait, Error};
 
-use crate::models::{ChangeHistory, Identifier};
+use crate::models::{ChangeHistory, Identifier, TimestampInSeconds};
 use crate::AttributesEntry;
 
 /// Repository for data related to identities: key changes and attributes
@@ -40,7 +40,8 @@ pub trait IdentityAttributesReader: Send + Sync + 'static {
 #[async_trait]
 pub trait IdentityAttributesWriter: Send + Sync + 'static {
     /// Set the attributes associated with the given identity identifier.
-    /// Previous values gets overridden.
+    /// Previous values gets overridden, except for the attributes attested with a credential
+    /// by other authorities, which are kept when the new attributes come from a credential.
     async fn put_attributes(&self, identity: &Identifier, entry: AttributesEntry) -> Result<()>;
 
     /// Store an attribute name/value pair for a given identity
@@ -53,6 +54,16 @@ pub trait IdentityAttributesWriter: Send + Sync + 'static {
 
     /// Remove all attributes for a given identity identifier
     async fn delete(&self, identity: &Identifier) -> Result<()>;
+
+    /// Remove the attributes attested by an authority for a given identity identifier
+    /// with a credential created at or before `created_until`.
+    /// The attributes attested by other authorities are kept
+    async fn delete_attested_by(
+        &self,
+        identity: &Identifier,
+        authority: &Identifier,
+        created_until: TimestampInSeconds,
+    ) -> Result<()>;
 }
 
 /// Trait implementing write access to identities
@@ -10,4 +10,6 @@ impl IdentityConstants {
     pub const CREDENTIALS_PURPOSE_KEY: &'static str = "C_PK";
     /// Attributes key for AttributesStorage
     pub const ATTRIBUTES_KEY: &'static str = "ATTRIBUTES";
+    /// Key of the attributes attested by each authority for AttributesStorage
+    pub const ATTESTED_ATTRIBUTES_KEY: &'static str = "ATTESTED_ATTRIBUTES";
 }
@@ -14,4 +14,8 @@ pub enum IdentityHistoryComparison {
     #[n(3)] Newer,
     /// Known identity is more recent
     #[n(4)] Older,
+    /// Current identity conflicts with the known identity, but at the first diverging change
+    /// it was signed with a recovery key while the known identity wasn't.
+    /// The current identity replaces the known one
+    #[n(5)] Recovered,
 }
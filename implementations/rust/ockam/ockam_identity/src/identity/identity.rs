@@ -82,9 +82,9 @@ impl Identity {
         change_history: ChangeHistory,
         vault: Arc<dyn VaultForVerifyingSignatures>,
     ) -> Result<Identity> {
-        let verified_changes =
+        let mut verified_changes =
             Self::check_entire_consistency(&change_history.0, vault.clone()).await?;
-        Self::verify_all_existing_changes(&verified_changes, &change_history.0, vault).await?;
+        Self::verify_all_existing_changes(&mut verified_changes, &change_history.0, vault).await?;
 
         let identifier = if let Some(first_change) = verified_changes.first() {
             first_change.change_hash().clone().into()
@@ -148,10 +148,20 @@ impl Identity {
     }
 
     /// Compare to a previously known state of the same `Identity`
+    ///
+    /// When both histories diverge, a change signed with a recovery key wins over a change
+    /// signed with the previous primary key, since that key is considered compromised
     pub fn compare(&self, known: &Self) -> IdentityHistoryComparison {
         for change_pair in self.changes.iter().zip(known.changes.iter()) {
             if change_pair.0.change_hash() != change_pair.1.change_hash() {
-                return IdentityHistoryComparison::Conflict;
+                return match (
+                    change_pair.0.signed_with_recovery_key(),
+                    change_pair.1.signed_with_recovery_key(),
+                ) {
+                    (true, false) => IdentityHistoryComparison::Recovered,
+                    (false, true) => IdentityHistoryComparison::Older,
+                    _ => IdentityHistoryComparison::Conflict,
+                };
             }
         }
 
@@ -111,8 +111,9 @@ impl Identity {
     }
 
     /// Verify all changes present in current `IdentityChangeHistory`
+    /// and mark the changes signed with a recovery key
     pub(crate) async fn verify_all_existing_changes(
-        to_be_verified_changes: &[VerifiedChange],
+        to_be_verified_changes: &mut [VerifiedChange],
         changes: &[Change],
         vault: Arc<dyn VaultForVerifyingSignatures>,
     ) -> Result<()> {
@@ -128,7 +129,12 @@ impl Identity {
             };
 
             let new_change = &changes[i];
-            Self::verify_change_signatures(last_verified_change, new_change, vault.clone()).await?
+            let signed_with_recovery_key =
+                Self::verify_change_signatures(last_verified_change, new_change, vault.clone())
+                    .await?;
+            if signed_with_recovery_key {
+                to_be_verified_changes[i].set_signed_with_recovery_key();
+            }
         }
         Ok(())
     }
@@ -144,14 +150,17 @@ impl Identity {
             .await
     }
 
+    /// Return true if the previous signature was made with one of the recovery keys of the
+    /// previous change instead of its primary key.
     /// WARNING: This function assumes all existing changes in chain are verified.
     /// WARNING: Correctness of changes sequence is not verified here.
     async fn verify_change_signatures(
         last_verified_change: Option<&VerifiedChange>,
         new_change: &Change,
         vault: Arc<dyn VaultForVerifyingSignatures>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let new_change_details = Self::get_change_details(new_change, vault.clone()).await?;
+        let mut signed_with_recovery_key = false;
 
         if let Some(last_verified_change) = last_verified_change {
             if let Some(previous_signature) = &new_change.previous_signature {
@@ -163,7 +172,41 @@ impl Identity {
                 )
                 .await?
                 {
-                    return Err(IdentityError::IdentityVerificationFailed.into());
+                    // The change can also be signed with a recovery key registered in the
+                    // previous change, when the previous primary key was lost or compromised
+                    for recovery_public_key in last_verified_change.recovery_public_keys() {
+                        if Self::verify_change_signature(
+                            &recovery_public_key,
+                            new_change_details.change_full_hash,
+                            previous_signature,
+                            vault.clone(),
+                        )
+                        .await?
+                        {
+                            signed_with_recovery_key = true;
+                            break;
+                        }
+                    }
+                    if !signed_with_recovery_key {
+                        return Err(IdentityError::IdentityVerificationFailed.into());
+                    }
+                }
+
+                // Once registered, the recovery keys can't be replaced or removed with the
+                // primary key alone, since that key may be the compromised one
+                let recovery_public_keys = last_verified_change.recovery_public_keys();
+                let new_recovery_public_keys: Vec<VerifyingPublicKey> = new_change_details
+                    .change_data
+                    .recovery_public_keys
+                    .iter()
+                    .flatten()
+                    .map(|k| k.clone().into())
+                    .collect();
+                if !signed_with_recovery_key
+                    && !recovery_public_keys.is_empty()
+                    && new_recovery_public_keys != recovery_public_keys
+                {
+                    return Err(IdentityError::RecoveryKeysChangeNotAllowed.into());
                 }
             } else {
                 // Previous signature should be present if it's not the first change
@@ -186,6 +229,6 @@ impl Identity {
             return Err(IdentityError::IdentityVerificationFailed.into());
         }
 
-        Ok(())
+        Ok(signed_with_recovery_key)
     }
 }
@@ -1,4 +1,5 @@
 use crate::models::{ChangeData, ChangeHash};
+use ockam_core::compat::vec::Vec;
 use ockam_vault::VerifyingPublicKey;
 
 /// Verified Changes of an [`Identity`]
@@ -7,6 +8,7 @@ pub struct VerifiedChange {
     data: ChangeData,
     change_hash: ChangeHash,
     primary_public_key: VerifyingPublicKey,
+    signed_with_recovery_key: bool,
 }
 
 impl VerifiedChange {
@@ -19,9 +21,14 @@ impl VerifiedChange {
             data,
             change_hash,
             primary_public_key,
+            signed_with_recovery_key: false,
         }
     }
 
+    pub(crate) fn set_signed_with_recovery_key(&mut self) {
+        self.signed_with_recovery_key = true;
+    }
+
     /// [`ChangeData`]
     pub fn data(&self) -> &ChangeData {
         &self.data
@@ -36,4 +43,20 @@ impl VerifiedChange {
     pub fn primary_public_key(&self) -> &VerifyingPublicKey {
         &self.primary_public_key
     }
+
+    /// Recovery public keys which can sign the next change
+    pub fn recovery_public_keys(&self) -> Vec<VerifyingPublicKey> {
+        self.data
+            .recovery_public_keys
+            .iter()
+            .flatten()
+            .map(|k| k.clone().into())
+            .collect()
+    }
+
+    /// True if this change was signed with a recovery key of the previous change
+    /// instead of the previous primary key
+    pub fn signed_with_recovery_key(&self) -> bool {
+        self.signed_with_recovery_key
+    }
 }
@@ -24,7 +24,8 @@ pub struct Change {
     /// Self-signature over the data using the key from this same [`Change`]
     #[n(2)] pub signature: ChangeSignature,
     /// Self-signature over the data using the key
-    /// from the previous [`Change`] in the [`ChangeHistory`]
+    /// from the previous [`Change`] in the [`ChangeHistory`], or using one of the
+    /// recovery keys registered in the previous [`Change`]
     #[n(3)] pub previous_signature: Option<ChangeSignature>,
 }
 
@@ -56,6 +57,11 @@ pub struct ChangeData {
     #[n(4)] pub created_at: TimestampInSeconds,
     /// Expiration [`TimestampInSeconds`] (UTC)
     #[n(5)] pub expires_at: TimestampInSeconds,
+    /// Public Keys which can sign the next [`Change`] instead of the Primary Public Key
+    /// of this [`Change`]. They are usually stored offline and are used to replace
+    /// a lost or compromised Primary Public Key. Once set, they can only be changed by a
+    /// [`Change`] signed with one of them
+    #[n(6)] pub recovery_public_keys: Option<Vec<PrimaryPublicKey>>,
 }
 
 /// [`Change`]'s public key
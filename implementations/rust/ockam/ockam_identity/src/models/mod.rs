@@ -3,6 +3,7 @@ mod credential;
 mod credential_and_purpose_key;
 mod identifiers;
 mod purpose_key_attestation;
+mod revocation_list;
 mod timestamp;
 mod utils;
 mod versioned_data;
@@ -12,5 +13,6 @@ pub use credential::*;
 pub use credential_and_purpose_key::*;
 pub use identifiers::*;
 pub use purpose_key_attestation::*;
+pub use revocation_list::*;
 pub use timestamp::*;
 pub use versioned_data::*;
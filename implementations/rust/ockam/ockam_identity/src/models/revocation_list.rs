@@ -0,0 +1,62 @@
+use crate::models::{CredentialSignature, Identifier, PurposeKeyAttestation, TimestampInSeconds};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
+
+/// Tag prepended to the data of a [`RevocationList`] before it is signed, so that the signature
+/// of a [`RevocationList`] can't be accepted for a [`super::Credential`] or the reverse,
+/// even though both are signed with a Credentials [`PurposeKeyAttestation`]
+pub const REVOCATION_LIST_SIGNATURE_TAG: &[u8] = b"ockam_revocation_list";
+
+/// List of subjects whose [`super::Credential`]s were revoked by an Authority
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RevocationList {
+    /// CBOR serialized [`super::VersionedData`]
+    /// where VersionedData::data is CBOR serialized [`RevocationListData`]
+    #[cbor(with = "minicbor::bytes")]
+    #[n(1)] pub data: Vec<u8>,
+    /// Signature over data field, prefixed with [`REVOCATION_LIST_SIGNATURE_TAG`], using
+    /// corresponding Credentials [`PurposeKeyAttestation`]
+    #[n(2)] pub signature: CredentialSignature,
+}
+
+/// Data inside a [`RevocationList`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RevocationListData {
+    /// Version of the list. Each new list issued by an Authority has a greater version
+    #[n(1)] pub version: u64,
+    /// Revoked subjects
+    #[n(2)] pub revoked: Vec<RevokedSubject>,
+    /// Creation [`TimestampInSeconds`] (UTC)
+    #[n(3)] pub created_at: TimestampInSeconds,
+    /// Expiration [`TimestampInSeconds`] (UTC). A fresher list is expected to be retrieved
+    /// from the Authority before that time
+    #[n(4)] pub expires_at: TimestampInSeconds,
+}
+
+/// A subject for which all [`super::Credential`]s created up to a given time are revoked
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RevokedSubject {
+    /// Subject of the revoked [`super::Credential`]s
+    #[n(1)] pub subject: Identifier,
+    /// [`super::Credential`]s created at or before that [`TimestampInSeconds`] (UTC) are revoked
+    #[n(2)] pub revoked_at: TimestampInSeconds,
+}
+
+/// [`RevocationList`] and the corresponding [`PurposeKeyAttestation`] that was used to issue that
+/// [`RevocationList`] and will be used to verify it
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RevocationListAndPurposeKey {
+    /// [`RevocationList`]
+    #[n(1)] pub revocation_list: RevocationList,
+    /// Corresponding [`PurposeKeyAttestation`] that was used to issue that
+    /// [`RevocationList`] and will be used to verify it
+    #[n(2)] pub purpose_key_attestation: PurposeKeyAttestation,
+}
@@ -1,5 +1,5 @@
 use crate::models::utils::get_versioned_data;
-use crate::models::{CredentialData, CredentialSignature, VersionedData};
+use crate::models::{CredentialAndPurposeKey, CredentialData, CredentialSignature, VersionedData};
 use crate::Credential;
 
 use ockam_core::Result;
@@ -19,6 +19,13 @@ impl CredentialData {
     }
 }
 
+impl CredentialAndPurposeKey {
+    /// Extract the [`CredentialData`] of the credential without verifying it
+    pub fn get_credential_data(&self) -> Result<CredentialData> {
+        CredentialData::get_data(&self.credential.get_versioned_data()?)
+    }
+}
+
 impl From<CredentialSignature> for Signature {
     fn from(value: CredentialSignature) -> Self {
         match value {
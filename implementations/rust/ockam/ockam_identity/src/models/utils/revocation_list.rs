@@ -0,0 +1,29 @@
+use crate::models::utils::get_versioned_data;
+use crate::models::{
+    RevocationList, RevocationListData, VersionedData, REVOCATION_LIST_SIGNATURE_TAG,
+};
+
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+impl RevocationList {
+    /// Extract [`VersionedData`]
+    pub fn get_versioned_data(&self) -> Result<VersionedData> {
+        get_versioned_data(&self.data)
+    }
+
+    /// Data signed by the issuer of a [`RevocationList`]: its serialized [`VersionedData`]
+    /// prefixed with [`REVOCATION_LIST_SIGNATURE_TAG`]
+    pub fn signed_data(data: &[u8]) -> Vec<u8> {
+        let mut signed_data = REVOCATION_LIST_SIGNATURE_TAG.to_vec();
+        signed_data.extend_from_slice(data);
+        signed_data
+    }
+}
+
+impl RevocationListData {
+    /// Extract [`RevocationListData`] from [`VersionedData`]
+    pub fn get_data(versioned_data: &VersionedData) -> Result<Self> {
+        Ok(minicbor::decode(&versioned_data.data)?)
+    }
+}
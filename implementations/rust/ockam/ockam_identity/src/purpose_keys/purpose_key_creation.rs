@@ -5,6 +5,7 @@ use crate::models::{
     Identifier, PurposeKeyAttestation, PurposeKeyAttestationData, PurposePublicKey, VersionedData,
 };
 use crate::purpose_keys::storage::PurposeKeysRepository;
+use crate::utils::now;
 use crate::{
     CredentialPurposeKey, CredentialPurposeKeyBuilder, IdentitiesKeys, IdentitiesReader, Identity,
     IdentityError, Purpose, PurposeKeyVerification, SecureChannelPurposeKey,
@@ -156,6 +157,83 @@ impl PurposeKeyCreation {
         Ok((attestation, attestation_data))
     }
 
+    /// Replace the Secure Channel Purpose Key with a freshly generated one.
+    /// The secret of the previous key is deleted from the Vault
+    pub async fn rotate_secure_channel_purpose_key(
+        &self,
+        identifier: &Identifier,
+    ) -> Result<SecureChannelPurposeKey> {
+        let previous_key = self.get_secure_channel_purpose_key(identifier).await.ok();
+        let purpose_key = self.create_secure_channel_purpose_key(identifier).await?;
+
+        if let Some(previous_key) = previous_key {
+            self.vault
+                .secure_channel_vault
+                .delete_static_x25519_secret_key(previous_key.key().clone())
+                .await?;
+        }
+
+        Ok(purpose_key)
+    }
+
+    /// Replace the Credential Purpose Key with a freshly generated one.
+    /// The secret of the previous key is deleted from the Vault
+    pub async fn rotate_credential_purpose_key(
+        &self,
+        identifier: &Identifier,
+    ) -> Result<CredentialPurposeKey> {
+        let previous_key = self.get_credential_purpose_key(identifier).await.ok();
+        let purpose_key = self.create_credential_purpose_key(identifier).await?;
+
+        if let Some(previous_key) = previous_key {
+            self.vault
+                .credential_vault
+                .delete_signing_secret_key(previous_key.key().clone())
+                .await?;
+        }
+
+        Ok(purpose_key)
+    }
+
+    /// Attest again the existing Purpose Keys of an Identity after its primary key was rotated.
+    /// The Purpose Keys are kept, but they are signed with the new primary key and reference the
+    /// latest change of the Identity, which is required for them to be successfully verified
+    pub async fn reattest_purpose_keys(&self, identifier: &Identifier) -> Result<()> {
+        for purpose in [Purpose::SecureChannel, Purpose::Credentials] {
+            let attestation = match self
+                .repository
+                .retrieve_purpose_key(identifier, purpose)
+                .await?
+            {
+                Some(attestation) => attestation,
+                None => continue,
+            };
+
+            // The previous attestation can't be verified anymore since it references an older
+            // change of the Identity, but it was stored by us
+            let versioned_data = attestation.get_versioned_data()?;
+            let attestation_data = PurposeKeyAttestationData::get_data(&versioned_data)?;
+            if &attestation_data.subject != identifier {
+                return Err(IdentityError::PurposeKeyAttestationVerificationFailed.into());
+            }
+
+            let (attestation, _) = self
+                .attest_purpose_key(
+                    identifier.clone(),
+                    attestation_data.public_key,
+                    now()?,
+                    attestation_data.expires_at,
+                )
+                .await?;
+
+            self.repository
+                .set_purpose_key(identifier, purpose, &attestation)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Will try to get own Purpose Key from the repository, if that doesn't succeed - new one
     /// will be generated
     pub async fn get_or_create_secure_channel_purpose_key(
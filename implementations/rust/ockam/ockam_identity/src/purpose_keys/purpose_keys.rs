@@ -166,4 +166,55 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_purpose_keys_rotation() -> Result<()> {
+        let identities = identities();
+        let identities_creation = identities.identities_creation();
+        let purpose_keys = identities.purpose_keys();
+        let purpose_keys_creation = purpose_keys.purpose_keys_creation();
+
+        let identity = identities_creation.create_identity().await?;
+        let secure_channel_key = purpose_keys_creation
+            .create_secure_channel_purpose_key(identity.identifier())
+            .await?;
+
+        // A rotated purpose key has a new secret and a valid attestation
+        let rotated_key = purpose_keys_creation
+            .rotate_secure_channel_purpose_key(identity.identifier())
+            .await?;
+        assert_ne!(secure_channel_key.public_key(), rotated_key.public_key());
+        assert!(purpose_keys_creation
+            .vault()
+            .secure_channel_vault
+            .get_x25519_public_key(secure_channel_key.key())
+            .await
+            .is_err());
+        purpose_keys
+            .purpose_keys_verification()
+            .verify_purpose_key_attestation(Some(identity.identifier()), rotated_key.attestation())
+            .await?;
+
+        // Rotating the primary key invalidates the existing attestations until they are
+        // attested again with the new primary key
+        identities_creation
+            .rotate_identity(identity.identifier())
+            .await?;
+        assert!(purpose_keys
+            .purpose_keys_verification()
+            .verify_purpose_key_attestation(Some(identity.identifier()), rotated_key.attestation())
+            .await
+            .is_err());
+
+        purpose_keys_creation
+            .reattest_purpose_keys(identity.identifier())
+            .await?;
+        let reattested_key = purpose_keys_creation
+            .get_secure_channel_purpose_key(identity.identifier())
+            .await?;
+        assert_eq!(reattested_key.public_key(), rotated_key.public_key());
+        assert_eq!(reattested_key.key(), rotated_key.key());
+
+        Ok(())
+    }
 }
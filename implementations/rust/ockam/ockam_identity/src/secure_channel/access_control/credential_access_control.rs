@@ -4,7 +4,9 @@ use ockam_core::compat::{boxed::Box, sync::Arc, vec::Vec};
 use ockam_core::Result;
 use ockam_core::{async_trait, RelayMessage};
 
-use crate::identities::IdentitiesRepository;
+use crate::credentials::RevocationLists;
+use crate::identities::{AttributesEntry, IdentitiesRepository};
+use crate::models::Identifier;
 use crate::secure_channel::local_info::IdentitySecureChannelLocalInfo;
 
 /// Access control checking that message senders have a specific set of attributes
@@ -12,6 +14,7 @@ use crate::secure_channel::local_info::IdentitySecureChannelLocalInfo;
 pub struct CredentialAccessControl {
     required_attributes: Vec<(Vec<u8>, Vec<u8>)>,
     storage: Arc<dyn IdentitiesRepository>,
+    revocation_lists: Option<Arc<RevocationLists>>,
 }
 
 impl CredentialAccessControl {
@@ -23,6 +26,28 @@ impl CredentialAccessControl {
         Self {
             required_attributes: required_attributes.to_vec(),
             storage,
+            revocation_lists: None,
+        }
+    }
+
+    /// Reject the attributes attested by an authority for a subject which has since been
+    /// revoked by that authority, or while the revocation list of that authority is outdated
+    pub fn with_revocation_lists(mut self, revocation_lists: Arc<RevocationLists>) -> Self {
+        self.revocation_lists = Some(revocation_lists);
+        self
+    }
+}
+
+impl CredentialAccessControl {
+    /// Return true if the authority which attested these attributes revoked them,
+    /// or might have revoked them
+    fn is_revoked(&self, subject: &Identifier, attributes: &AttributesEntry) -> bool {
+        match (&self.revocation_lists, attributes.attested_by()) {
+            (Some(revocation_lists), Some(authority)) => {
+                revocation_lists.is_stale(&authority)
+                    || revocation_lists.is_revoked(&authority, subject, attributes.created_at())
+            }
+            _ => false,
         }
     }
 }
@@ -43,15 +68,23 @@ impl IncomingAccessControl for CredentialAccessControl {
         if let Ok(msg_identity_id) =
             IdentitySecureChannelLocalInfo::find_info(relay_message.local_message())
         {
-            let attributes = match self
-                .storage
-                .get_attributes(&msg_identity_id.their_identity_id())
-                .await?
-            {
+            let their_identity_id = msg_identity_id.their_identity_id();
+            let attributes = match self.storage.get_attributes(&their_identity_id).await? {
                 Some(a) => a,
                 None => return Ok(false), // No attributes for that Identity
             };
 
+            // The attributes attested by each authority are checked separately before being merged
+            let entries: Vec<AttributesEntry> = attributes
+                .attested_entries()
+                .into_iter()
+                .filter(|entry| !self.is_revoked(&their_identity_id, entry))
+                .collect();
+            let attributes = match AttributesEntry::merge(&entries) {
+                Some(a) => a,
+                None => return Ok(false), // The credentials for these attributes were revoked
+            };
+
             for required_attribute in self.required_attributes.iter() {
                 let attr_val = match attributes.attrs().get(&required_attribute.0) {
                     Some(v) => v,
@@ -8,7 +8,8 @@ use crate::secure_channel::role::Role;
 // compatibility each of them have more addresses to simulate old behaviour.
 #[derive(Clone, Debug)]
 pub(crate) struct Addresses {
-    // Used to send decrypted messages and secure channel creation completion notification
+    // Used to send decrypted messages and secure channel creation completion notification,
+    // and to receive the events checking the expiration of the other end credentials
     pub(crate) decryptor_internal: Address,
     // Used for KeyExchange and receiving encrypted messages
     pub(crate) decryptor_remote: Address,
@@ -20,6 +21,8 @@ pub(crate) struct Addresses {
     pub(crate) encryptor: Address,
     // Used to decrypt messages that were received though some channel other than Ockam Routing from the other end of the channel
     pub(crate) encryptor_api: Address,
+    // Used to receive the events scheduled to refresh the credentials presented to the other end of the channel
+    pub(crate) encryptor_internal: Address,
 }
 
 impl Addresses {
@@ -35,6 +38,8 @@ impl Addresses {
         let encryptor = Address::random_tagged(&format!("SecureChannel.{}.encryptor", role_str));
         let encryptor_api =
             Address::random_tagged(&format!("SecureChannel.{}.encryptor.api", role_str));
+        let encryptor_internal =
+            Address::random_tagged(&format!("SecureChannel.{}.encryptor.internal", role_str));
 
         Self {
             decryptor_internal,
@@ -42,6 +47,7 @@ impl Addresses {
             decryptor_api,
             encryptor,
             encryptor_api,
+            encryptor_internal,
         }
     }
 }
@@ -0,0 +1,228 @@
+use core::cmp::min;
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{Address, Result};
+use ockam_node::{Context, DelayedEvent};
+use tracing::{debug, warn};
+
+use crate::models::{CredentialAndPurposeKey, Identifier, TimestampInSeconds};
+use crate::secure_channel::event::SecureChannelEvent;
+use crate::secure_channel::message::RefreshCredentials;
+use crate::utils::now;
+use crate::{CredentialsRetriever, Identities, IdentityError, TrustContext};
+
+/// Duration after which a failed retrieval of new credentials is attempted again
+const CREDENTIALS_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Retriever used to refresh the credentials presented over a secure channel
+#[derive(Clone)]
+pub(crate) struct CredentialsRefresh {
+    pub(crate) retriever: Arc<dyn CredentialsRetriever>,
+    pub(crate) time_gap: Duration,
+}
+
+/// Return the earliest expiration of a list of credentials, if the list is not empty
+pub(crate) fn credentials_expiration(
+    credentials: &[CredentialAndPurposeKey],
+) -> Result<Option<TimestampInSeconds>> {
+    let mut expires_at: Option<TimestampInSeconds> = None;
+    for credential in credentials {
+        let credential_expires_at = credential.get_credential_data()?.expires_at;
+        expires_at =
+            Some(expires_at.map_or(credential_expires_at, |e| min(e, credential_expires_at)));
+    }
+    Ok(expires_at)
+}
+
+/// Number of seconds remaining until the given time, 0 if that time has passed
+fn seconds_until(timestamp: TimestampInSeconds) -> Result<u64> {
+    Ok(timestamp.0.saturating_sub(now()?.0))
+}
+
+/// Retrieve new credentials for our identity before the ones presented to the
+/// other party of a secure channel expire
+pub(crate) struct CredentialsRefresher {
+    identities: Arc<Identities>,
+    identifier: Identifier,
+    refresh: CredentialsRefresh,
+    expires_at: TimestampInSeconds,
+    event: DelayedEvent<SecureChannelEvent>,
+}
+
+impl CredentialsRefresher {
+    /// Create a refresher for credentials expiring at `expires_at`.
+    /// Refresh events are sent to the `destination` address
+    pub(crate) async fn create(
+        ctx: &Context,
+        identities: Arc<Identities>,
+        identifier: Identifier,
+        refresh: CredentialsRefresh,
+        expires_at: TimestampInSeconds,
+        destination: Address,
+    ) -> Result<Self> {
+        let event =
+            DelayedEvent::create(ctx, destination, SecureChannelEvent::RefreshCredentials).await?;
+        Ok(Self {
+            identities,
+            identifier,
+            refresh,
+            expires_at,
+            event,
+        })
+    }
+
+    /// Address sending the refresh events
+    pub(crate) fn address(&self) -> Address {
+        self.event.address()
+    }
+
+    /// Schedule the next refresh, `time_gap` before the current credentials expire
+    pub(crate) async fn schedule(&mut self) -> Result<()> {
+        let delay = seconds_until(self.expires_at)?.saturating_sub(self.refresh.time_gap.as_secs());
+        self.event.schedule(Duration::from_secs(delay)).await
+    }
+
+    /// Retrieve new credentials. If they expire later than the current ones, return them
+    /// so that they can be presented to the other party. Otherwise try again later, until
+    /// the current credentials expire
+    pub(crate) async fn refresh(&mut self, ctx: &Context) -> Result<Option<RefreshCredentials>> {
+        match self.refresh.retriever.retrieve(ctx, &self.identifier).await {
+            Ok(credential) => {
+                let expires_at = credential.get_credential_data()?.expires_at;
+                if expires_at > self.expires_at {
+                    let change_history = self
+                        .identities
+                        .repository()
+                        .get_identity(&self.identifier)
+                        .await?;
+                    self.expires_at = expires_at;
+                    self.schedule().await?;
+                    return Ok(Some(RefreshCredentials {
+                        change_history,
+                        credentials: vec![credential],
+                    }));
+                }
+                debug!(
+                    "the retrieved credential for {} is not newer than the presented one",
+                    self.identifier
+                );
+            }
+            Err(e) => warn!(
+                "failed to retrieve a new credential for {}: {}",
+                self.identifier, e
+            ),
+        };
+
+        let remaining = seconds_until(self.expires_at)?;
+        if remaining == 0 {
+            warn!(
+                "the credentials presented by {} expired without being refreshed",
+                self.identifier
+            );
+            return Ok(None);
+        }
+        self.event
+            .schedule(min(
+                CREDENTIALS_RETRY_INTERVAL,
+                Duration::from_secs(remaining),
+            ))
+            .await?;
+        Ok(None)
+    }
+}
+
+/// Track the expiration of the credentials presented by the other party of a secure channel
+/// and verify the credentials it presents while the channel is open
+pub(crate) struct CredentialsExpiration {
+    identities: Arc<Identities>,
+    trust_context: TrustContext,
+    expires_at: Option<TimestampInSeconds>,
+    event: DelayedEvent<SecureChannelEvent>,
+}
+
+impl CredentialsExpiration {
+    /// Create a tracker sending expiration events to the `destination` address
+    pub(crate) async fn create(
+        ctx: &Context,
+        identities: Arc<Identities>,
+        trust_context: TrustContext,
+        destination: Address,
+    ) -> Result<Self> {
+        let event =
+            DelayedEvent::create(ctx, destination, SecureChannelEvent::CredentialsExpired).await?;
+        Ok(Self {
+            identities,
+            trust_context,
+            expires_at: None,
+            event,
+        })
+    }
+
+    /// Address sending the expiration events
+    pub(crate) fn address(&self) -> Address {
+        self.event.address()
+    }
+
+    /// Expiration of the tracked credentials, if any
+    pub(crate) fn expires_at(&self) -> Option<TimestampInSeconds> {
+        self.expires_at
+    }
+
+    /// Start tracking credentials expiring at `expires_at`, if any
+    pub(crate) async fn track(&mut self, expires_at: Option<TimestampInSeconds>) -> Result<()> {
+        self.expires_at = expires_at;
+        match expires_at {
+            Some(expires_at) => {
+                self.event
+                    .schedule(Duration::from_secs(seconds_until(expires_at)?))
+                    .await
+            }
+            None => {
+                self.event.cancel();
+                Ok(())
+            }
+        }
+    }
+
+    /// Return true if the tracked credentials expired.
+    /// Otherwise the expiration is checked again later
+    pub(crate) async fn check(&mut self) -> Result<bool> {
+        match self.expires_at {
+            Some(expires_at) if expires_at <= now()? => Ok(true),
+            expires_at => {
+                self.track(expires_at).await?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Verify the credentials presented by the other party and store their attributes.
+    /// The expiration of these credentials is tracked from now on
+    pub(crate) async fn receive(
+        &mut self,
+        their_identifier: &Identifier,
+        refresh: RefreshCredentials,
+    ) -> Result<()> {
+        let expires_at = credentials_expiration(&refresh.credentials)?
+            .ok_or(IdentityError::SecureChannelVerificationFailedIncorrectCredential)?;
+
+        self.identities
+            .identities_creation()
+            .import_from_change_history(Some(their_identifier), refresh.change_history)
+            .await?;
+
+        let credentials_verification = self.identities.credentials().credentials_verification();
+        for credential in &refresh.credentials {
+            credentials_verification
+                .receive_presented_credential_for_trust_context(
+                    their_identifier,
+                    &self.trust_context,
+                    credential,
+                )
+                .await?;
+        }
+        debug!("received new credentials from {}", their_identifier);
+
+        self.track(Some(expires_at)).await
+    }
+}
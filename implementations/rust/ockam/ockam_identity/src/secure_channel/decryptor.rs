@@ -5,10 +5,16 @@ use ockam_core::{Decodable, LocalMessage};
 use ockam_node::Context;
 
 use crate::models::Identifier;
-use crate::secure_channel::encryptor::{Encryptor, KEY_RENEWAL_INTERVAL};
+use crate::secure_channel::encryptor::Encryptor;
+use crate::secure_channel::event::SecureChannelEvent;
 use crate::secure_channel::key_tracker::KeyTracker;
+use crate::secure_channel::message::{
+    NewResumptionTicket, RefreshCredentials, SecureChannelMessage,
+};
 use crate::secure_channel::nonce_tracker::NonceTracker;
-use crate::secure_channel::Addresses;
+use crate::secure_channel::{
+    Addresses, CredentialsExpiration, IdleTimeout, LastActivity, ResumptionTicketReceiver,
+};
 use crate::{DecryptionRequest, DecryptionResponse, IdentityError, IdentitySecureChannelLocalInfo};
 
 use ockam_vault::{AeadSecretKeyHandle, VaultForSecureChannels};
@@ -20,21 +26,40 @@ pub(crate) struct DecryptorHandler {
     pub(crate) addresses: Addresses,
     pub(crate) their_identity_id: Identifier,
     pub(crate) decryptor: Decryptor,
+    // true if both parties agreed to refresh their credentials over the channel,
+    // in which case the plaintext is a `SecureChannelMessage`
+    pub(crate) credentials_refresh: bool,
+    pub(crate) credentials_expiration: Option<CredentialsExpiration>,
+    pub(crate) last_activity: LastActivity,
+    pub(crate) idle_timeout: Option<IdleTimeout>,
+    pub(crate) resumption_tickets: Option<ResumptionTicketReceiver>,
 }
 
 impl DecryptorHandler {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         role: &'static str,
         addresses: Addresses,
         key: AeadSecretKeyHandle,
+        renewal_interval: u64,
         vault: Arc<dyn VaultForSecureChannels>,
         their_identity_id: Identifier,
+        credentials_refresh: bool,
+        credentials_expiration: Option<CredentialsExpiration>,
+        last_activity: LastActivity,
+        idle_timeout: Option<IdleTimeout>,
+        resumption_tickets: Option<ResumptionTicketReceiver>,
     ) -> Self {
         Self {
             role,
             addresses,
             their_identity_id,
-            decryptor: Decryptor::new(key, vault),
+            decryptor: Decryptor::new(key, renewal_interval, vault),
+            credentials_refresh,
+            credentials_expiration,
+            last_activity,
+            idle_timeout,
+            resumption_tickets,
         }
     }
 
@@ -57,7 +82,10 @@ impl DecryptorHandler {
         let decrypted_payload = self.decryptor.decrypt(&request.0).await;
 
         let response = match decrypted_payload {
-            Ok(payload) => DecryptionResponse::Ok(payload),
+            Ok(payload) => {
+                self.last_activity.update();
+                DecryptionResponse::Ok(payload)
+            }
             Err(err) => DecryptionResponse::Err(err),
         };
 
@@ -83,6 +111,31 @@ impl DecryptorHandler {
 
         // Decrypt the binary
         let decrypted_payload = self.decryptor.decrypt(&payload).await?;
+        self.last_activity.update();
+
+        // The payload of a SecureChannelMessage can also contain new credentials, a keepalive
+        // or a resumption ticket
+        let decrypted_payload = if self.credentials_refresh {
+            match minicbor::decode(&decrypted_payload)? {
+                SecureChannelMessage::Payload(payload) => payload,
+                SecureChannelMessage::RefreshCredentials(refresh) => {
+                    return self.handle_refresh_credentials(refresh).await
+                }
+                SecureChannelMessage::KeepAlive => {
+                    debug!(
+                        "SecureChannel {} received a keepalive {}",
+                        self.role, &self.addresses.decryptor_remote
+                    );
+                    return Ok(());
+                }
+                SecureChannelMessage::NewResumptionTicket(ticket) => {
+                    self.handle_resumption_ticket(ticket);
+                    return Ok(());
+                }
+            }
+        } else {
+            decrypted_payload
+        };
 
         // Encrypted data should be a TransportMessage
         let mut transport_message = TransportMessage::decode(&decrypted_payload)?;
@@ -115,6 +168,119 @@ impl DecryptorHandler {
         }
     }
 
+    /// Verify the new credentials presented by the other party.
+    /// Invalid credentials are ignored, the channel is then stopped when the previous ones expire
+    async fn handle_refresh_credentials(&mut self, refresh: RefreshCredentials) -> Result<()> {
+        let credentials_expiration = match self.credentials_expiration.as_mut() {
+            Some(credentials_expiration) => credentials_expiration,
+            None => {
+                warn!(
+                    "SecureChannel {} received credentials without a trust context to verify them {}",
+                    self.role, &self.addresses.decryptor_remote
+                );
+                return Ok(());
+            }
+        };
+
+        if let Err(err) = credentials_expiration
+            .receive(&self.their_identity_id, refresh)
+            .await
+        {
+            warn!(
+                "SecureChannel {} received invalid credentials from {}: {}",
+                self.role, self.their_identity_id, err
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Store a ticket to resume this session later, if we accept tickets
+    fn handle_resumption_ticket(&self, ticket: NewResumptionTicket) {
+        match &self.resumption_tickets {
+            Some(resumption_tickets) => {
+                debug!(
+                    "SecureChannel {} received a resumption ticket {}",
+                    self.role, &self.addresses.decryptor_remote
+                );
+                let their_credentials_expiration = self
+                    .credentials_expiration
+                    .as_ref()
+                    .and_then(|credentials_expiration| credentials_expiration.expires_at());
+                resumption_tickets.receive(
+                    &self.their_identity_id,
+                    ticket,
+                    their_credentials_expiration,
+                );
+            }
+            None => warn!(
+                "SecureChannel {} received an unexpected resumption ticket {}",
+                self.role, &self.addresses.decryptor_remote
+            ),
+        }
+    }
+
+    /// Handle an event scheduled by the channel
+    pub(crate) async fn handle_event(
+        &mut self,
+        ctx: &mut Context,
+        event: SecureChannelEvent,
+    ) -> Result<()> {
+        match event {
+            SecureChannelEvent::CredentialsExpired => self.handle_credentials_expired(ctx).await,
+            SecureChannelEvent::IdleCheck => self.handle_idle_check(ctx).await,
+            event => {
+                debug!(
+                    "SecureChannel {} ignores the event {:?} {}",
+                    self.role, event, &self.addresses.decryptor_remote
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Stop the channel if the credentials of the other party expired without being refreshed
+    async fn handle_credentials_expired(&mut self, ctx: &mut Context) -> Result<()> {
+        let expired = match self.credentials_expiration.as_mut() {
+            Some(credentials_expiration) => credentials_expiration.check().await?,
+            None => false,
+        };
+
+        if expired {
+            warn!(
+                "The credentials of {} expired, stopping SecureChannel {} {}",
+                self.their_identity_id, self.role, &self.addresses.encryptor
+            );
+            self.stop_channel(ctx).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Stop the channel if nothing was received from the other party for too long
+    async fn handle_idle_check(&mut self, ctx: &mut Context) -> Result<()> {
+        let last_activity = self.last_activity.get();
+        let idle = match self.idle_timeout.as_mut() {
+            Some(idle_timeout) => idle_timeout.check(last_activity).await?,
+            None => false,
+        };
+
+        if idle {
+            warn!(
+                "{} has been idle since {}, stopping SecureChannel {} {}",
+                self.their_identity_id, last_activity.0, self.role, &self.addresses.encryptor
+            );
+            self.stop_channel(ctx).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Stopping the encryptor stops this decryptor and unregisters the channel
+    async fn stop_channel(&self, ctx: &mut Context) -> Result<()> {
+        ctx.stop_worker(self.addresses.encryptor.clone()).await
+    }
+
     /// Remove the channel keys on shutdown
     pub(crate) async fn shutdown(&self) -> Result<()> {
         self.decryptor.shutdown().await
@@ -128,11 +294,16 @@ pub(crate) struct Decryptor {
 }
 
 impl Decryptor {
-    pub fn new(key: AeadSecretKeyHandle, vault: Arc<dyn VaultForSecureChannels>) -> Self {
+    /// The window of accepted nonces has the size of the key renewal interval
+    pub fn new(
+        key: AeadSecretKeyHandle,
+        renewal_interval: u64,
+        vault: Arc<dyn VaultForSecureChannels>,
+    ) -> Self {
         Self {
             vault,
-            key_tracker: KeyTracker::new(key, KEY_RENEWAL_INTERVAL),
-            nonce_tracker: NonceTracker::new(),
+            key_tracker: KeyTracker::new(key, renewal_interval),
+            nonce_tracker: NonceTracker::new(renewal_interval),
         }
     }
 
@@ -151,7 +322,7 @@ impl Decryptor {
         }
 
         let (nonce, nonce_buffer) = Self::convert_nonce_from_small(&payload[..8])?;
-        let nonce_tracker = self.nonce_tracker.mark(nonce)?;
+        self.nonce_tracker.check(nonce)?;
 
         // get the key corresponding to the current nonce and
         // rekey if necessary
@@ -169,7 +340,7 @@ impl Decryptor {
             .await;
 
         if result.is_ok() {
-            self.nonce_tracker = nonce_tracker;
+            self.nonce_tracker.mark(nonce)?;
             if let Some(key_to_delete) = self.key_tracker.update_key(key)? {
                 self.vault.delete_aead_secret_key(key_to_delete).await?;
             }
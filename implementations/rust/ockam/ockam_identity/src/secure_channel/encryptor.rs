@@ -4,17 +4,22 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{AeadSecretKeyHandle, VaultForSecureChannels};
 
-use crate::IdentityError;
+use crate::models::TimestampInSeconds;
+use crate::utils::now;
+use crate::{IdentityError, RekeyPolicy};
 
 pub(crate) struct Encryptor {
     key: AeadSecretKeyHandle,
     nonce: u64,
     vault: Arc<dyn VaultForSecureChannels>,
+    rekey_policy: RekeyPolicy,
+    last_rekey: Option<TimestampInSeconds>,
 }
 
-// To simplify the implementation we use the same constant for the size of the message
+// To simplify the implementation we use the same value for the size of the message
 // window we accept with the message period used to rekey.
 // This means we only need to keep the current key and the previous one.
+// This is the default value of that period, see [`RekeyPolicy`]
 pub(crate) const KEY_RENEWAL_INTERVAL: u64 = 32;
 
 impl Encryptor {
@@ -46,18 +51,41 @@ impl Encryptor {
         vault.convert_secret_buffer_to_aead_key(buffer).await
     }
 
+    /// When the key has been used for longer than the time interval of the rekey policy
+    /// we skip the remaining nonces of the current interval. The other party then
+    /// renews its key when receiving the first nonce of the next interval
+    fn next_nonce(&self) -> Result<u64> {
+        let renewal_interval = self.rekey_policy.message_interval();
+        let (time_interval, last_rekey) = match (self.rekey_policy.time_interval(), self.last_rekey)
+        {
+            (Some(time_interval), Some(last_rekey)) => (time_interval, last_rekey),
+            _ => return Ok(self.nonce),
+        };
+
+        if self.nonce % renewal_interval == 0
+            || now()?.0.saturating_sub(last_rekey.0) < time_interval.as_secs()
+        {
+            return Ok(self.nonce);
+        }
+
+        (self.nonce / renewal_interval + 1)
+            .checked_mul(renewal_interval)
+            .ok_or_else(|| IdentityError::NonceOverflow.into())
+    }
+
     pub async fn encrypt(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
-        let current_nonce = self.nonce;
+        let current_nonce = self.next_nonce()?;
         if current_nonce == u64::MAX {
             return Err(IdentityError::NonceOverflow.into());
         }
 
-        self.nonce += 1;
+        self.nonce = current_nonce + 1;
 
-        if current_nonce > 0 && current_nonce % KEY_RENEWAL_INTERVAL == 0 {
+        if current_nonce > 0 && current_nonce % self.rekey_policy.message_interval() == 0 {
             let new_key = Self::rekey(&self.vault, &self.key).await?;
             let old_key = core::mem::replace(&mut self.key, new_key);
             self.vault.delete_aead_secret_key(old_key).await?;
+            self.last_rekey = self.rekey_timestamp()?;
         }
 
         let (small_nonce, nonce) = Self::convert_nonce_from_u64(current_nonce);
@@ -77,9 +105,26 @@ impl Encryptor {
     pub fn new(
         key: AeadSecretKeyHandle,
         nonce: u64,
+        rekey_policy: RekeyPolicy,
         vault: Arc<dyn VaultForSecureChannels>,
-    ) -> Self {
-        Self { key, nonce, vault }
+    ) -> Result<Self> {
+        let mut encryptor = Self {
+            key,
+            nonce,
+            vault,
+            rekey_policy,
+            last_rekey: None,
+        };
+        encryptor.last_rekey = encryptor.rekey_timestamp()?;
+        Ok(encryptor)
+    }
+
+    /// The time of the last key renewal is only tracked when the rekey policy has a time interval
+    fn rekey_timestamp(&self) -> Result<Option<TimestampInSeconds>> {
+        match self.rekey_policy.time_interval() {
+            Some(_) => Ok(Some(now()?)),
+            None => Ok(None),
+        }
     }
 
     pub(crate) async fn shutdown(&self) -> Result<()> {
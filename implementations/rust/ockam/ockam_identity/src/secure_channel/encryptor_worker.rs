@@ -7,6 +7,9 @@ use tracing::debug;
 use crate::secure_channel::addresses::Addresses;
 use crate::secure_channel::api::{EncryptionRequest, EncryptionResponse};
 use crate::secure_channel::encryptor::Encryptor;
+use crate::secure_channel::event::SecureChannelEvent;
+use crate::secure_channel::message::{NewResumptionTicket, SecureChannelMessage};
+use crate::secure_channel::{CredentialsRefresher, KeepAliveTimer};
 use crate::IdentityError;
 
 pub(crate) struct EncryptorWorker {
@@ -15,20 +18,36 @@ pub(crate) struct EncryptorWorker {
     addresses: Addresses,
     remote_route: Route,
     encryptor: Encryptor,
+    // true if both parties agreed to refresh their credentials over the channel,
+    // in which case the plaintext is a `SecureChannelMessage`
+    credentials_refresh: bool,
+    credentials_refresher: Option<CredentialsRefresher>,
+    keepalive: Option<KeepAliveTimer>,
+    // ticket sent to the initiator once the channel is established, to resume the session later
+    resumption_ticket: Option<NewResumptionTicket>,
 }
 
 impl EncryptorWorker {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         role: &'static str,
         addresses: Addresses,
         remote_route: Route,
         encryptor: Encryptor,
+        credentials_refresh: bool,
+        credentials_refresher: Option<CredentialsRefresher>,
+        keepalive: Option<KeepAliveTimer>,
+        resumption_ticket: Option<NewResumptionTicket>,
     ) -> Self {
         Self {
             role,
             addresses,
             remote_route,
             encryptor,
+            credentials_refresh,
+            credentials_refresher,
+            keepalive,
+            resumption_ticket,
         }
     }
 
@@ -84,8 +103,64 @@ impl EncryptorWorker {
             msg.into_transport_message().payload,
         );
 
+        let plaintext = if self.credentials_refresh {
+            minicbor::to_vec(SecureChannelMessage::Payload(msg.encode()?))?
+        } else {
+            msg.encode()?
+        };
+
+        self.send_encrypted(ctx, &plaintext).await
+    }
+
+    /// Retrieve new credentials and present them to the other party
+    async fn handle_refresh_credentials(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+    ) -> Result<()> {
+        let refresh = match self.credentials_refresher.as_mut() {
+            Some(refresher) => refresher.refresh(ctx).await?,
+            None => None,
+        };
+
+        if let Some(refresh) = refresh {
+            debug!(
+                "SecureChannel {} sends new credentials {}",
+                self.role, &self.addresses.encryptor
+            );
+            let plaintext = minicbor::to_vec(SecureChannelMessage::RefreshCredentials(refresh))?;
+            self.send_encrypted(ctx, &plaintext).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a keepalive if nothing else was sent to the other party since the previous check
+    async fn handle_keepalive(&mut self, ctx: &mut <Self as Worker>::Context) -> Result<()> {
+        let send_keepalive = match self.keepalive.as_mut() {
+            Some(keepalive) => keepalive.check().await?,
+            None => false,
+        };
+
+        if send_keepalive {
+            debug!(
+                "SecureChannel {} sends a keepalive {}",
+                self.role, &self.addresses.encryptor
+            );
+            let plaintext = minicbor::to_vec(SecureChannelMessage::KeepAlive)?;
+            self.send_encrypted(ctx, &plaintext).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Encrypt a plaintext and send it to the decryptor on the other side
+    async fn send_encrypted(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        plaintext: &[u8],
+    ) -> Result<()> {
         // Encrypt the message
-        let encrypted_payload = self.encryptor.encrypt(&msg.encode()?).await?;
+        let encrypted_payload = self.encryptor.encrypt(plaintext).await?;
 
         // Send the message to the decryptor on the other side
         ctx.send_from_address(
@@ -95,6 +170,10 @@ impl EncryptorWorker {
         )
         .await?;
 
+        if let Some(keepalive) = self.keepalive.as_mut() {
+            keepalive.sent();
+        }
+
         Ok(())
     }
 }
@@ -104,6 +183,24 @@ impl Worker for EncryptorWorker {
     type Message = Any;
     type Context = Context;
 
+    async fn initialize(&mut self, context: &mut Self::Context) -> Result<()> {
+        if let Some(ticket) = self.resumption_ticket.take() {
+            debug!(
+                "SecureChannel {} sends a resumption ticket {}",
+                self.role, &self.addresses.encryptor
+            );
+            let plaintext = minicbor::to_vec(SecureChannelMessage::NewResumptionTicket(ticket))?;
+            self.send_encrypted(context, &plaintext).await?;
+        }
+        if let Some(refresher) = self.credentials_refresher.as_mut() {
+            refresher.schedule().await?;
+        }
+        if let Some(keepalive) = self.keepalive.as_mut() {
+            keepalive.schedule().await?;
+        }
+        Ok(())
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Self::Context,
@@ -115,6 +212,17 @@ impl Worker for EncryptorWorker {
             self.handle_encrypt(ctx, msg).await?;
         } else if msg_addr == self.addresses.encryptor_api {
             self.handle_encrypt_api(ctx, msg).await?;
+        } else if msg_addr == self.addresses.encryptor_internal {
+            match SecureChannelEvent::decode(msg.payload())? {
+                SecureChannelEvent::RefreshCredentials => {
+                    self.handle_refresh_credentials(ctx).await?
+                }
+                SecureChannelEvent::KeepAlive => self.handle_keepalive(ctx).await?,
+                event => debug!(
+                    "SecureChannel {} ignores the event {:?} {}",
+                    self.role, event, &self.addresses.encryptor
+                ),
+            }
         } else {
             return Err(IdentityError::UnknownChannelMsgDestination.into());
         }
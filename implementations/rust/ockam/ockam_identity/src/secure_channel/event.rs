@@ -0,0 +1,15 @@
+use ockam_core::Message;
+use serde::{Deserialize, Serialize};
+
+/// Events scheduled by a secure channel and sent to its own internal addresses
+#[derive(Serialize, Deserialize, Clone, Debug, Message)]
+pub(crate) enum SecureChannelEvent {
+    /// Our credentials must be refreshed
+    RefreshCredentials,
+    /// The credentials of the other party may have expired
+    CredentialsExpired,
+    /// A keepalive must be sent if nothing was sent to the other party recently
+    KeepAlive,
+    /// The other party may have been silent for too long
+    IdleCheck,
+}
@@ -14,6 +14,8 @@ pub enum XXError {
     MessageLenMismatch,
     /// Invalid internal state.
     InvalidInternalState,
+    /// The protocol used by the other party is not accepted.
+    ProtocolNotAccepted,
 }
 
 impl StdError for XXError {}
@@ -24,6 +26,7 @@ impl fmt::Display for XXError {
             Self::InternalVaultError => write!(f, "internal vault error"),
             Self::MessageLenMismatch => write!(f, "message length mismatch"),
             Self::InvalidInternalState => write!(f, "invalid internal state"),
+            Self::ProtocolNotAccepted => write!(f, "handshake protocol not accepted"),
         }
     }
 }
@@ -35,6 +38,7 @@ impl From<XXError> for Error {
             XXError::InternalVaultError => Kind::Internal,
             XXError::MessageLenMismatch => Kind::Misuse,
             XXError::InvalidInternalState => Kind::Internal,
+            XXError::ProtocolNotAccepted => Kind::Unsupported,
         };
 
         Error::new(Origin::KeyExchange, kind, err)
@@ -1,11 +1,13 @@
 use cfg_if::cfg_if;
 use ockam_core::compat::sync::Arc;
-use ockam_core::compat::vec::Vec;
+use ockam_core::compat::vec::{vec, Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{
-    AeadSecretKeyHandle, HKDFNumberOfOutputs, SecretBufferHandle, VaultForSecureChannels,
-    X25519PublicKey, X25519SecretKeyHandle, X25519_PUBLIC_KEY_LENGTH,
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, MlKemCiphertext, MlKemPublicKey,
+    MlKemSecretKeyHandle, SecretBufferHandle, VaultForSecureChannels, X25519PublicKey,
+    X25519SecretKeyHandle, ML_KEM_CIPHERTEXT_LENGTH, ML_KEM_PUBLIC_KEY_LENGTH,
+    X25519_PUBLIC_KEY_LENGTH,
 };
 use sha2::{Digest, Sha256};
 use Status::*;
@@ -13,6 +15,7 @@ use Status::*;
 use crate::secure_channel::handshake::error::XXError;
 use crate::secure_channel::handshake::handshake_state_machine::{HandshakeKeys, Status};
 use crate::secure_channel::Role;
+use crate::HandshakeProtocol;
 
 /// The number of bytes in a SHA256 digest
 pub const SHA256_SIZE: usize = 32;
@@ -23,8 +26,29 @@ pub const AES_GCM_TAGSIZE: usize = 16;
 /// The first members are used in the implementation of some of the protocol steps, for example to
 /// encrypt messages
 /// The variables used in the protocol itself: s, e, rs, re,... are handled in `HandshakeState`
+///
+/// With the hybrid protocol, the initiator also sends an ephemeral ML-KEM public key e1 in
+/// message 1 and the responder mixes a shared secret encapsulated for that key into the
+/// chaining key of message 2, as specified by the `hfs` modifier of the Noise protocol:
+///
+/// -> e, e1
+/// <- e, ee, ekem1, s, es
+/// -> s, se
+///
+/// A session can also be resumed in one round trip with the secret shared in a resumption
+/// ticket, used as a pre-shared key. The ticket is the prologue and the key exchange follows
+/// the NNpsk0 pattern, where ee provides forward secrecy:
+///
+/// -> psk, e
+/// <- e, ee
+///
+/// The classical message 1 only contains e, as sent by the initiators which don't support the
+/// other protocols. The hybrid and resumption messages 1 are longer and start with a marker byte
+/// so that the responder knows how to process them. The marker is not mixed in the handshake
+/// hash, but the protocol name is
 pub(super) struct Handshake {
     vault: Arc<dyn VaultForSecureChannels>,
+    protocol: HandshakeProtocol,
     protocol_name: [u8; 32],
     pub(super) state: HandshakeState,
 }
@@ -51,7 +75,20 @@ impl Handshake {
         // output e.pubKey
         let e_pub_key = self.get_public_key(state.e()?).await?;
         state.mix_hash(&e_pub_key.0);
-        let mut message = e_pub_key.0.to_vec();
+        let mut message = Vec::new();
+
+        // output the marker of the hybrid protocol, the classical message 1 is left unchanged
+        if self.protocol.is_hybrid() {
+            message.push(HYBRID_MARKER);
+        }
+        message.extend_from_slice(&e_pub_key.0);
+
+        // output e1.pubKey for the hybrid protocol
+        if self.protocol.is_hybrid() {
+            let e1_pub_key = self.vault.get_ml_kem_public_key(state.e1()?).await?;
+            state.mix_hash(&e1_pub_key.0);
+            message.extend_from_slice(&e1_pub_key.0);
+        }
 
         // output message 1 payload
         message.extend_from_slice(payload);
@@ -64,14 +101,24 @@ impl Handshake {
     /// Decode the first message to get the ephemeral public key sent by the initiator
     pub(super) async fn decode_message1(&mut self, message: &[u8]) -> Result<Vec<u8>> {
         let mut state = self.state.clone();
+        // skip the marker of the hybrid protocol, already read to select the protocol
+        let message = Self::read_bytes_from(message, self.message1_marker_length())?;
+
         // read e.pubKey
         let key = Self::read_key(message)?;
         state.mix_hash(key);
 
         state.re = Some(X25519PublicKey(*key));
 
+        // read e1.pubKey for the hybrid protocol
+        if self.protocol.is_hybrid() {
+            let key = Self::read_message1_kem_public_key(message)?;
+            state.mix_hash(key);
+            state.re1 = Some(MlKemPublicKey(key.to_vec()));
+        }
+
         // decode payload
-        let payload = Self::read_message1_payload(message)?;
+        let payload = self.read_message1_payload(message)?;
         state.mix_hash(payload);
 
         self.state = state;
@@ -92,6 +139,15 @@ impl Handshake {
         let dh = self.dh(state.e()?, state.re()?).await?;
         self.hkdf(&mut state, dh).await?;
 
+        // for the hybrid protocol, encrypt and output a KEM ciphertext for re1
+        // ck, k = HKDF(ck, KEM shared secret, 2)
+        if self.protocol.is_hybrid() {
+            let (ciphertext, shared_secret) = self.vault.ml_kem_encapsulate(state.re1()?).await?;
+            let c = self.encrypt_and_hash(&mut state, &ciphertext.0).await?;
+            message2.extend_from_slice(c.as_slice());
+            self.hkdf(&mut state, shared_secret).await?;
+        }
+
         // encrypt and output s.pubKey
         let s_pub_key = self.get_public_key(state.s()?).await?;
         let c = self.encrypt_and_hash(&mut state, &s_pub_key.0).await?;
@@ -120,8 +176,20 @@ impl Handshake {
         let dh = self.dh(state.e()?, state.re()?).await?;
         self.hkdf(&mut state, dh).await?;
 
+        // for the hybrid protocol, decrypt the KEM ciphertext and decapsulate it with e1
+        // ck, k = HKDF(ck, KEM shared secret, 2)
+        if self.protocol.is_hybrid() {
+            let c = Self::read_message2_kem_ciphertext(message)?;
+            let ciphertext = MlKemCiphertext(self.hash_and_decrypt(&mut state, c).await?);
+            let shared_secret = self
+                .vault
+                .ml_kem_decapsulate(state.e1()?, &ciphertext)
+                .await?;
+            self.hkdf(&mut state, shared_secret).await?;
+        }
+
         // decrypt rs.pubKey
-        let rs_pub_key = Self::read_message2_encrypted_key(message)?;
+        let rs_pub_key = self.read_message2_encrypted_key(message)?;
         let rs_pub_key = self.hash_and_decrypt(&mut state, rs_pub_key).await?;
         let rs_pub_key = X25519PublicKey(
             rs_pub_key
@@ -135,7 +203,7 @@ impl Handshake {
         self.hkdf(&mut state, dh).await?;
 
         // decrypt payload
-        let c = Self::read_message2_payload(message)?;
+        let c = self.read_message2_payload(message)?;
         let payload = self.hash_and_decrypt(&mut state, c).await?;
 
         self.state = state;
@@ -216,6 +284,96 @@ impl Handshake {
             _ => None,
         }
     }
+
+    /// Initialize the resumption of a session: the ticket is mixed as a prologue
+    /// and the secret it contains is mixed as a pre-shared key
+    pub(super) async fn initialize_resumption(
+        &mut self,
+        ticket: &[u8],
+        secret: &[u8],
+    ) -> Result<()> {
+        self.protocol_name = *RESUMPTION_PROTOCOL_NAME;
+        self.initialize().await?;
+
+        let mut state = self.state.clone();
+        state.mix_hash(ticket);
+
+        // ck, k = HKDF(ck, psk, 2)
+        let psk = self.vault.import_secret_buffer(secret.to_vec()).await?;
+        self.hkdf(&mut state, psk).await?;
+
+        self.state = state;
+        Ok(())
+    }
+
+    /// Encode the first message of a session resumption, sent from the initiator to the responder
+    pub(super) async fn encode_resumption_message1(&mut self, ticket: &[u8]) -> Result<Vec<u8>> {
+        let mut state = self.state.clone();
+        // output e.pubKey
+        let e_pub_key = self.get_public_key(state.e()?).await?;
+        state.mix_hash(&e_pub_key.0);
+        let mut message = vec![RESUMPTION_MARKER];
+        message.extend_from_slice(&e_pub_key.0);
+
+        // output the ticket
+        message.extend_from_slice(ticket);
+
+        self.state = state;
+        Ok(message)
+    }
+
+    /// Decode the first message of a session resumption, once initialized with its ticket
+    pub(super) async fn decode_resumption_message1(&mut self, message: &[u8]) -> Result<()> {
+        let mut state = self.state.clone();
+        // read e.pubKey, after the marker
+        let key = Self::read_key(Self::read_bytes_from(message, MARKER_LENGTH)?)?;
+        state.mix_hash(key);
+        state.re = Some(X25519PublicKey(*key));
+
+        self.state = state;
+        Ok(())
+    }
+
+    /// Encode the second message of a session resumption, sent from the responder to the initiator.
+    /// Its empty encrypted payload proves that the responder knows the pre-shared key
+    pub(super) async fn encode_resumption_message2(&mut self) -> Result<Vec<u8>> {
+        let mut state = self.state.clone();
+        // output e.pubKey
+        let e_pub_key = self.get_public_key(state.e()?).await?;
+        state.mix_hash(&e_pub_key.0);
+        let mut message = e_pub_key.0.to_vec();
+
+        // ck, k = HKDF(ck, DH(e, re), 2)
+        let dh = self.dh(state.e()?, state.re()?).await?;
+        self.hkdf(&mut state, dh).await?;
+
+        // encrypt and output an empty payload
+        let c = self.encrypt_and_hash(&mut state, &[]).await?;
+        message.extend(c);
+
+        self.state = state;
+        Ok(message)
+    }
+
+    /// Decode the second message of a session resumption
+    pub(super) async fn decode_resumption_message2(&mut self, message: &[u8]) -> Result<()> {
+        let mut state = self.state.clone();
+        // read e.pubKey
+        let key = Self::read_key(message)?;
+        state.mix_hash(key);
+        state.re = Some(X25519PublicKey(*key));
+
+        // ck, k = HKDF(ck, DH(e, re), 2)
+        let dh = self.dh(state.e()?, state.re()?).await?;
+        self.hkdf(&mut state, dh).await?;
+
+        // decrypt the empty payload
+        let c = Self::read_bytes_from(message, X25519_PUBLIC_KEY_LENGTH)?;
+        self.hash_and_decrypt(&mut state, c).await?;
+
+        self.state = state;
+        Ok(())
+    }
 }
 
 impl Handshake {
@@ -223,19 +381,85 @@ impl Handshake {
     pub(super) async fn new(
         vault: Arc<dyn VaultForSecureChannels>,
         static_key: X25519SecretKeyHandle,
+        protocol: HandshakeProtocol,
     ) -> Result<Handshake> {
         // 1. generate an ephemeral key pair for this handshake and set it to e
         let ephemeral_key = Self::generate_ephemeral_key(vault.clone()).await?;
+        let mut state = HandshakeState::new(static_key, ephemeral_key);
+
+        // 2. for the hybrid protocol, generate an ephemeral KEM key pair and set it to e1
+        if protocol.is_hybrid() {
+            state.e1 = Some(vault.generate_ephemeral_ml_kem_secret_key().await?);
+        }
 
-        // 2. initialize the handshake
+        // 3. initialize the handshake
         // We currently don't use any payload for message 1
         Ok(Handshake {
             vault,
-            protocol_name: *PROTOCOL_NAME,
-            state: HandshakeState::new(static_key, ephemeral_key),
+            protocol,
+            protocol_name: Self::protocol_name_for(protocol),
+            state,
         })
     }
 
+    /// Select the protocol used by the initiator, before initializing the handshake on the
+    /// responder side. A hybrid message 1 starts with a marker and contains a KEM public key,
+    /// any other message 1 is a classical one, unchanged from the initiators which only support
+    /// the classical protocol.
+    /// Return an error if the protocol is not the accepted one
+    pub(super) fn select_protocol(
+        &mut self,
+        message1: &[u8],
+        accepted_protocol: Option<HandshakeProtocol>,
+    ) -> Result<()> {
+        const HYBRID_MESSAGE1_LENGTH: usize =
+            MARKER_LENGTH + X25519_PUBLIC_KEY_LENGTH + ML_KEM_PUBLIC_KEY_LENGTH;
+        let protocol = if message1.len() == HYBRID_MESSAGE1_LENGTH
+            && message1.first() == Some(&HYBRID_MARKER)
+        {
+            HandshakeProtocol::Hybrid
+        } else {
+            HandshakeProtocol::Classical
+        };
+
+        if let Some(accepted_protocol) = accepted_protocol {
+            if protocol != accepted_protocol {
+                return Err(XXError::ProtocolNotAccepted.into());
+            }
+        }
+
+        self.protocol = protocol;
+        self.protocol_name = Self::protocol_name_for(protocol);
+        Ok(())
+    }
+
+    /// Start a new handshake with the same static key and new ephemeral keys,
+    /// after a session resumption was rejected by the responder
+    pub(super) async fn restart(&mut self, protocol: HandshakeProtocol) -> Result<()> {
+        if let Some(ck) = self.state.ck.take() {
+            self.vault.delete_secret_buffer(ck).await?;
+        }
+        if let Some(k) = self.state.k.take() {
+            self.vault.delete_aead_secret_key(k).await?;
+        }
+        self.delete_ephemeral_keys().await?;
+
+        *self = Handshake::new(self.vault.clone(), self.state.s()?.clone(), protocol).await?;
+        Ok(())
+    }
+
+    /// Return true if a message 1 resumes a session, in which case it starts with a marker and
+    /// contains a ticket after the public key. A classical message 1 only contains a public key
+    pub(super) fn is_resumption_message1(message: &[u8]) -> bool {
+        message.len() > MARKER_LENGTH + X25519_PUBLIC_KEY_LENGTH
+            && message.first() == Some(&RESUMPTION_MARKER)
+    }
+
+    /// Read the ticket of a session resumption message 1, which is present after the public key
+    pub(super) fn read_resumption_ticket(message: &[u8]) -> Result<&[u8]> {
+        Self::read_bytes_from(message, MARKER_LENGTH + X25519_PUBLIC_KEY_LENGTH)
+    }
+
     /// Import the ck secret
     async fn import_ck_secret(&self, content: Vec<u8>) -> Result<SecretBufferHandle> {
         self.vault.import_secret_buffer(content).await
@@ -348,6 +572,10 @@ impl Handshake {
             .delete_ephemeral_x25519_secret_key(self.state.take_e()?)
             .await?;
 
+        if let Some(e1) = self.state.e1.take() {
+            _ = self.vault.delete_ephemeral_ml_kem_secret_key(e1).await?;
+        }
+
         Ok(())
     }
 }
@@ -362,6 +590,19 @@ cfg_if! {
     }
 }
 
+/// Name of the hybrid protocol. It is longer than 32 bytes so its hash is used instead
+pub const HYBRID_PROTOCOL_NAME: &[u8] = b"Noise_XXhfs_25519+MLKEM768_AESGCM_SHA256";
+
+/// Name of the protocol used to resume a session with the secret of a resumption ticket
+pub const RESUMPTION_PROTOCOL_NAME: &[u8; 32] = b"OCKAM_NNpsk0_25519_AESGCM_SHA256";
+
+/// The number of bytes of the marker starting the hybrid and resumption messages 1
+const MARKER_LENGTH: usize = 1;
+/// Marker of the hybrid protocol message 1
+const HYBRID_MARKER: u8 = 1;
+/// Marker of the session resumption message 1
+const RESUMPTION_MARKER: u8 = 2;
+
 /// Static functions
 impl Handshake {
     /// Protocol name, used as a secret during the handshake initialization, padded to 32 bytes
@@ -369,6 +610,41 @@ impl Handshake {
         self.protocol_name
     }
 
+    /// Name of a protocol, padded to 32 bytes or hashed if it is longer
+    fn protocol_name_for(protocol: HandshakeProtocol) -> [u8; 32] {
+        match protocol {
+            HandshakeProtocol::Classical => *PROTOCOL_NAME,
+            HandshakeProtocol::Hybrid => HandshakeState::sha256(HYBRID_PROTOCOL_NAME),
+        }
+    }
+
+    /// Length of the marker starting message 1, for the hybrid protocol
+    fn message1_marker_length(&self) -> usize {
+        if self.protocol.is_hybrid() {
+            MARKER_LENGTH
+        } else {
+            0
+        }
+    }
+
+    /// Length of the KEM public key sent in message 1, for the hybrid protocol
+    fn message1_kem_length(&self) -> usize {
+        if self.protocol.is_hybrid() {
+            ML_KEM_PUBLIC_KEY_LENGTH
+        } else {
+            0
+        }
+    }
+
+    /// Length of the encrypted KEM ciphertext sent in message 2, for the hybrid protocol
+    fn message2_kem_length(&self) -> usize {
+        if self.protocol.is_hybrid() {
+            ML_KEM_CIPHERTEXT_LENGTH + AES_GCM_TAGSIZE
+        } else {
+            0
+        }
+    }
+
     /// Generate an ephemeral key for the key exchange
     async fn generate_ephemeral_key(
         vault: Arc<dyn VaultForSecureChannels>,
@@ -376,21 +652,40 @@ impl Handshake {
         vault.generate_ephemeral_x25519_secret_key().await
     }
 
-    /// Read the message 1 payload which is present after the public key
-    fn read_message1_payload(message: &[u8]) -> Result<&[u8]> {
-        Self::read_end::<X25519_PUBLIC_KEY_LENGTH>(message)
+    /// Read the message 1 KEM public key, which is present after the public key
+    fn read_message1_kem_public_key(message: &[u8]) -> Result<&[u8]> {
+        Self::read_bytes(message, X25519_PUBLIC_KEY_LENGTH, ML_KEM_PUBLIC_KEY_LENGTH)
+    }
+
+    /// Read the message 1 payload which is present after the public keys
+    fn read_message1_payload<'a>(&self, message: &'a [u8]) -> Result<&'a [u8]> {
+        Self::read_bytes_from(
+            message,
+            X25519_PUBLIC_KEY_LENGTH + self.message1_kem_length(),
+        )
+    }
+
+    /// Read the message 2 encrypted KEM ciphertext, which is present after the public key
+    fn read_message2_kem_ciphertext(message: &[u8]) -> Result<&[u8]> {
+        const L: usize = ML_KEM_CIPHERTEXT_LENGTH + AES_GCM_TAGSIZE;
+        Self::read_bytes(message, X25519_PUBLIC_KEY_LENGTH, L)
     }
 
     /// Read the message 2 encrypted key, which is present after the public key
-    fn read_message2_encrypted_key(message: &[u8]) -> Result<&[u8]> {
+    /// and the encrypted KEM ciphertext if any
+    fn read_message2_encrypted_key<'a>(&self, message: &'a [u8]) -> Result<&'a [u8]> {
         const L: usize = X25519_PUBLIC_KEY_LENGTH + AES_GCM_TAGSIZE;
-        Self::read_middle::<X25519_PUBLIC_KEY_LENGTH, L>(message)
+        Self::read_bytes(
+            message,
+            X25519_PUBLIC_KEY_LENGTH + self.message2_kem_length(),
+            L,
+        )
     }
 
     /// Read the message 2 encrypted payload, which is present after the encrypted key
-    fn read_message2_payload(message: &[u8]) -> Result<&[u8]> {
+    fn read_message2_payload<'a>(&self, message: &'a [u8]) -> Result<&'a [u8]> {
         const L: usize = 2 * X25519_PUBLIC_KEY_LENGTH + AES_GCM_TAGSIZE;
-        Self::read_end::<L>(message)
+        Self::read_bytes_from(message, L + self.message2_kem_length())
     }
 
     /// Read the message 3 encrypted key at the beginning of the message
@@ -419,11 +714,18 @@ impl Handshake {
             .map_err(|_| XXError::MessageLenMismatch.into())
     }
 
-    /// Read 'length' bytes of the message after the first 'drop_length' bytes
-    fn read_middle<const N: usize, const L: usize>(message: &[u8]) -> Result<&[u8]> {
-        message[N..(N + L)]
-            .try_into()
-            .map_err(|_| XXError::MessageLenMismatch.into())
+    /// Read 'length' bytes of the message after the first 'start' bytes
+    fn read_bytes(message: &[u8], start: usize, length: usize) -> Result<&[u8]> {
+        message
+            .get(start..(start + length))
+            .ok_or_else(|| XXError::MessageLenMismatch.into())
+    }
+
+    /// Read the bytes of the message after the first 'start' bytes
+    fn read_bytes_from(message: &[u8], start: usize) -> Result<&[u8]> {
+        message
+            .get(start..)
+            .ok_or_else(|| XXError::MessageLenMismatch.into())
     }
 
     /// Read the bytes of a key at the beginning of a message
@@ -437,8 +739,10 @@ impl Handshake {
 pub(super) struct HandshakeState {
     pub(super) s: Option<X25519SecretKeyHandle>,
     e: Option<X25519SecretKeyHandle>,
+    e1: Option<MlKemSecretKeyHandle>,
     k: Option<AeadSecretKeyHandle>,
     re: Option<X25519PublicKey>,
+    re1: Option<MlKemPublicKey>,
     pub(super) rs: Option<X25519PublicKey>,
     n: u64,
     h: [u8; SHA256_SIZE],
@@ -455,8 +759,10 @@ impl HandshakeState {
         HandshakeState {
             s: Some(s),
             e: Some(e),
+            e1: None,
             k: None,
             re: None,
+            re1: None,
             rs: None,
             n: 0,
             h: [0u8; SHA256_SIZE],
@@ -528,6 +834,16 @@ impl HandshakeState {
         })
     }
 
+    pub(super) fn e1(&self) -> Result<&MlKemSecretKeyHandle> {
+        self.e1.as_ref().ok_or_else(|| {
+            Error::new(
+                Origin::KeyExchange,
+                Kind::Invalid,
+                "key id e1 should have been set",
+            )
+        })
+    }
+
     pub(super) fn k(&self) -> Result<&AeadSecretKeyHandle> {
         self.k.as_ref().ok_or_else(|| {
             Error::new(
@@ -558,6 +874,16 @@ impl HandshakeState {
         })
     }
 
+    pub(super) fn re1(&self) -> Result<&MlKemPublicKey> {
+        self.re1.as_ref().ok_or_else(|| {
+            Error::new(
+                Origin::KeyExchange,
+                Kind::Invalid,
+                "public key re1 should have been set",
+            )
+        })
+    }
+
     pub(super) fn rs(&self) -> Result<&X25519PublicKey> {
         self.rs.as_ref().ok_or_else(|| {
             Error::new(
@@ -644,6 +970,136 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_full_hybrid_handshake() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create();
+
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut initiator = Handshake::new(
+            vault.clone(),
+            initiator_static_key,
+            HandshakeProtocol::Hybrid,
+        )
+        .await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut responder = Handshake::new(
+            vault.clone(),
+            responder_static_key,
+            HandshakeProtocol::Classical,
+        )
+        .await?;
+
+        initiator.initialize().await?;
+        let message1 = initiator.encode_message1(&[]).await?;
+        assert_eq!(message1[0], HYBRID_MARKER);
+        assert_eq!(
+            message1.len(),
+            MARKER_LENGTH + X25519_PUBLIC_KEY_LENGTH + ML_KEM_PUBLIC_KEY_LENGTH
+        );
+
+        responder.select_protocol(&message1, None)?;
+        assert_eq!(responder.protocol, HandshakeProtocol::Hybrid);
+        responder.initialize().await?;
+        responder.decode_message1(&message1).await?;
+
+        let message2 = responder.encode_message2(b"responder").await?;
+        assert_eq!(initiator.decode_message2(&message2).await?, b"responder");
+        let message3 = initiator.encode_message3(b"initiator").await?;
+        assert_eq!(responder.decode_message3(&message3).await?, b"initiator");
+
+        initiator.set_final_state(Role::Initiator).await?;
+        responder.set_final_state(Role::Responder).await?;
+        assert_eq!(vault.number_of_ephemeral_ml_kem_secrets(), 0);
+
+        // both parties derived the same keys
+        let initiator_keys = initiator.get_handshake_keys().unwrap();
+        let responder_keys = responder.get_handshake_keys().unwrap();
+        let nonce = [0u8; 12];
+        let c = vault
+            .aead_encrypt(&initiator_keys.encryption_key, b"hello", &nonce, &[])
+            .await?;
+        let decrypted = vault
+            .aead_decrypt(&responder_keys.decryption_key, &c, &nonce, &[])
+            .await?;
+        assert_eq!(decrypted, b"hello");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unprefixed_classical_message1() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create();
+
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut initiator = Handshake::new(
+            vault.clone(),
+            initiator_static_key,
+            HandshakeProtocol::Classical,
+        )
+        .await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut responder = Handshake::new(
+            vault.clone(),
+            responder_static_key,
+            HandshakeProtocol::Classical,
+        )
+        .await?;
+
+        // the classical message 1 only contains the ephemeral public key,
+        // as sent by the initiators which only support the classical protocol
+        initiator.initialize().await?;
+        let message1 = initiator.encode_message1(&[]).await?;
+        let e_pub_key = vault.get_x25519_public_key(initiator.state.e()?).await?;
+        assert_eq!(message1, e_pub_key.0.to_vec());
+
+        // whatever its first byte, it is processed as a classical message 1
+        for first_byte in [HYBRID_MARKER, RESUMPTION_MARKER] {
+            let mut message = message1.clone();
+            message[0] = first_byte;
+            assert!(!Handshake::is_resumption_message1(&message));
+            assert!(responder
+                .select_protocol(&message, Some(HandshakeProtocol::Hybrid))
+                .is_err());
+        }
+        assert!(!Handshake::is_resumption_message1(&message1));
+        responder.select_protocol(&message1, Some(HandshakeProtocol::Classical))?;
+        assert_eq!(responder.protocol, HandshakeProtocol::Classical);
+        responder.initialize().await?;
+        responder.decode_message1(&message1).await?;
+
+        let message2 = responder.encode_message2(b"responder").await?;
+        assert_eq!(initiator.decode_message2(&message2).await?, b"responder");
+        let message3 = initiator.encode_message3(b"initiator").await?;
+        assert_eq!(responder.decode_message3(&message3).await?, b"initiator");
+
+        initiator.set_final_state(Role::Initiator).await?;
+        responder.set_final_state(Role::Responder).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_protocol_not_accepted() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create();
+
+        for (protocol, accepted) in [
+            (HandshakeProtocol::Hybrid, HandshakeProtocol::Classical),
+            (HandshakeProtocol::Classical, HandshakeProtocol::Hybrid),
+        ] {
+            let static_key = vault.generate_static_x25519_secret_key().await?;
+            let mut initiator = Handshake::new(vault.clone(), static_key, protocol).await?;
+            initiator.initialize().await?;
+            let message1 = initiator.encode_message1(&[]).await?;
+
+            let static_key = vault.generate_static_x25519_secret_key().await?;
+            let mut responder =
+                Handshake::new(vault.clone(), static_key, HandshakeProtocol::Classical).await?;
+            assert!(responder
+                .select_protocol(&message1, Some(accepted))
+                .is_err());
+            assert!(responder.select_protocol(&message1, Some(protocol)).is_ok());
+        }
+        Ok(())
+    }
+
     // --------------------
     // TESTS IMPLEMENTATION
     // --------------------
@@ -735,6 +1191,7 @@ mod tests {
         ) -> Result<Handshake> {
             Ok(Handshake {
                 vault,
+                protocol: HandshakeProtocol::Classical,
                 protocol_name,
                 state: HandshakeState::new(static_key, ephemeral_key),
             })
@@ -753,6 +1210,7 @@ mod tests {
             // We currently don't use any payload for message 1
             Ok(Handshake {
                 vault,
+                protocol: HandshakeProtocol::Classical,
                 protocol_name,
                 state: HandshakeState::new(static_key, ephemeral_key),
             })
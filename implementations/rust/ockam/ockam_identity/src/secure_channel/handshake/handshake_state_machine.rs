@@ -8,9 +8,12 @@ use tracing::{debug, warn};
 
 use crate::models::{
     ChangeHistory, CredentialAndPurposeKey, Identifier, PurposeKeyAttestation, PurposePublicKey,
+    TimestampInSeconds,
 };
+use crate::secure_channel::credentials_expiration;
 use crate::{
-    Identities, Identity, IdentityError, SecureChannelTrustInfo, TrustContext, TrustPolicy,
+    Identities, Identity, IdentityError, RekeyPolicy, SecureChannelTrustInfo, TrustContext,
+    TrustPolicy,
 };
 
 /// Interface for a state machine in a key exchange protocol
@@ -42,6 +45,7 @@ pub(super) enum Status {
     WaitingForMessage1,
     WaitingForMessage2,
     WaitingForMessage3,
+    WaitingForResumptionMessage2,
     Ready(HandshakeKeys),
 }
 
@@ -54,10 +58,22 @@ pub(super) struct HandshakeKeys {
 
 /// The end result of a handshake with identity/credentials exchange is
 /// a pair of encryption/decryption keys + the identity of the other party
+/// + the rekey policy negotiated with the other party
+/// + the expiration of the credentials presented by the other party
+/// + whether both parties can refresh their credentials over the channel
+/// + whether the other party accepts keepalives
+/// + whether the other party accepts resumption tickets
+/// + the expiration of the resumed session if the channel was resumed with a ticket
 #[derive(Debug, Clone)]
 pub(super) struct HandshakeResults {
     pub(super) handshake_keys: HandshakeKeys,
     pub(super) their_identifier: Identifier,
+    pub(super) rekey_policy: RekeyPolicy,
+    pub(super) their_credentials_expiration: Option<TimestampInSeconds>,
+    pub(super) credentials_refresh: bool,
+    pub(super) keepalive: bool,
+    pub(super) resumption: bool,
+    pub(super) resumed_session_expiration: Option<TimestampInSeconds>,
 }
 
 /// This struct implements functions common to both initiator and the responder state machines
@@ -68,7 +84,14 @@ pub(super) struct CommonStateMachine {
     pub(super) credentials: Vec<CredentialAndPurposeKey>,
     pub(super) trust_policy: Arc<dyn TrustPolicy>,
     pub(super) trust_context: Option<TrustContext>,
+    pub(super) rekey_policy: RekeyPolicy,
+    pub(super) resumption: bool,
     their_identifier: Option<Identifier>,
+    their_credentials_expiration: Option<TimestampInSeconds>,
+    credentials_refresh: bool,
+    keepalive: bool,
+    their_resumption: bool,
+    resumed_session_expiration: Option<TimestampInSeconds>,
 }
 
 impl CommonStateMachine {
@@ -79,6 +102,8 @@ impl CommonStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        rekey_policy: RekeyPolicy,
+        resumption: bool,
     ) -> Self {
         Self {
             identities,
@@ -87,7 +112,14 @@ impl CommonStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            rekey_policy,
+            resumption,
             their_identifier: None,
+            their_credentials_expiration: None,
+            credentials_refresh: false,
+            keepalive: false,
+            their_resumption: false,
+            resumed_session_expiration: None,
         }
     }
 
@@ -97,6 +129,9 @@ impl CommonStateMachine {
     ///  - the current Identity Change History
     ///  - the current Secure Channel Purpose Key Attestation
     ///  - the Identity Credentials and corresponding Credentials Purpose Key Attestations
+    ///  - the rekey policy of the current party
+    ///  - the support of credentials refresh and keepalives over the channel
+    ///  - the support of resumption tickets
     ///
     pub(super) async fn make_identity_payload(&self) -> Result<Vec<u8>> {
         // prepare the payload that will be sent either in message 2 or message 3
@@ -109,13 +144,23 @@ impl CommonStateMachine {
             change_history,
             purpose_key_attestation: self.purpose_key_attestation.clone(),
             credentials: self.credentials.clone(),
+            rekey_message_interval: Some(self.rekey_policy.message_interval()),
+            rekey_time_interval: self
+                .rekey_policy
+                .time_interval()
+                .map(|interval| interval.as_secs()),
+            credentials_refresh: Some(true),
+            keepalive: Some(true),
+            resumption: Some(self.resumption),
         };
         Ok(minicbor::to_vec(payload)?)
     }
 
     /// Verify the identity sent by the other party: the Purpose Key and the credentials must be valid
     /// If everything is valid, store the identity identifier which will used to make the
-    /// final state machine result
+    /// final state machine result, and use the strictest rekey policy between ours and theirs.
+    /// Credentials are refreshed, keepalives and resumption tickets are sent over the channel
+    /// only if the other party supports it
     pub(super) async fn verify_identity(
         &mut self,
         peer: IdentityAndCredentials,
@@ -154,30 +199,29 @@ impl CommonStateMachine {
             }
         }
 
-        self.verify_credentials(identity.identifier(), peer.credentials)
+        self.their_credentials_expiration = self
+            .verify_credentials(identity.identifier(), peer.credentials)
             .await?;
+        self.credentials_refresh = peer.credentials_refresh == Some(true);
+        // keepalives are sent as `SecureChannelMessage`s, which requires the support of credentials refresh
+        self.keepalive = self.credentials_refresh && peer.keepalive == Some(true);
+        self.their_resumption = self.credentials_refresh && peer.resumption == Some(true);
+        self.rekey_policy = self.rekey_policy.negotiate(&RekeyPolicy::from_handshake(
+            peer.rekey_message_interval,
+            peer.rekey_time_interval,
+        ));
         self.their_identifier = Some(identity.identifier().clone());
         Ok(())
     }
 
     /// Verify that the credentials sent by the other party are valid using a trust context
-    /// and store them
+    /// and store them. Return the earliest expiration of these credentials, if any
     async fn verify_credentials(
         &self,
         their_identifier: &Identifier,
         credentials: Vec<CredentialAndPurposeKey>,
-    ) -> Result<()> {
-        // check our TrustPolicy
-        let trust_info = SecureChannelTrustInfo::new(their_identifier.clone());
-        let trusted = self.trust_policy.check(&trust_info).await?;
-        if !trusted {
-            // TODO: Shutdown? Communicate error?
-            return Err(IdentityError::SecureChannelTrustCheckFailed.into());
-        }
-        debug!(
-            "Initiator checked trust policy for SecureChannel from: {}",
-            their_identifier
-        );
+    ) -> Result<Option<TimestampInSeconds>> {
+        self.check_trust_policy(their_identifier).await?;
 
         if let Some(trust_context) = &self.trust_context {
             debug!(
@@ -189,9 +233,9 @@ impl CommonStateMachine {
                     .identities
                     .credentials()
                     .credentials_verification()
-                    .receive_presented_credential(
+                    .receive_presented_credential_for_trust_context(
                         their_identifier,
-                        &[trust_context.authority()?.identifier().clone()],
+                        trust_context,
                         credential,
                     )
                     .await;
@@ -210,12 +254,51 @@ impl CommonStateMachine {
             return Err(IdentityError::SecureChannelVerificationFailedMissingTrustContext.into());
         };
 
+        credentials_expiration(&credentials)
+    }
+
+    /// Check that the other party is trusted according to our trust policy
+    pub(super) async fn check_trust_policy(&self, their_identifier: &Identifier) -> Result<()> {
+        let trust_info = SecureChannelTrustInfo::new(their_identifier.clone());
+        let trusted = self.trust_policy.check(&trust_info).await?;
+        if !trusted {
+            // TODO: Shutdown? Communicate error?
+            return Err(IdentityError::SecureChannelTrustCheckFailed.into());
+        }
+        debug!(
+            "Initiator checked trust policy for SecureChannel from: {}",
+            their_identifier
+        );
         Ok(())
     }
 
+    /// Resume a session with a ticket instead of verifying the identity of the other party.
+    /// The ticket contains what was negotiated when the session was first established
+    pub(super) fn resume_session(
+        &mut self,
+        their_identifier: Identifier,
+        rekey_policy: RekeyPolicy,
+        their_credentials_expiration: Option<TimestampInSeconds>,
+        keepalive: bool,
+        expires_at: TimestampInSeconds,
+    ) {
+        self.their_credentials_expiration = their_credentials_expiration;
+        // tickets are only sent when `SecureChannelMessage`s are used
+        self.credentials_refresh = true;
+        self.keepalive = keepalive;
+        self.their_resumption = true;
+        self.resumed_session_expiration = Some(expires_at);
+        self.rekey_policy = self.rekey_policy.negotiate(&rekey_policy);
+        self.their_identifier = Some(their_identifier);
+    }
+
     /// Return the results of the full handshake
     ///  - the other party identity
     ///  - the encryption and decryption keys to use on the next messages to exchange
+    ///  - the negotiated rekey policy
+    ///  - the expiration of the other party credentials and the support of their refresh
+    ///  - the support of keepalives and resumption tickets by the other party
+    ///  - the expiration of the resumed session, if any
     pub(super) fn make_handshake_results(
         &self,
         handshake_keys: Option<HandshakeKeys>,
@@ -224,6 +307,12 @@ impl CommonStateMachine {
             (Some(their_identifier), Some(handshake_keys)) => Some(HandshakeResults {
                 their_identifier,
                 handshake_keys,
+                rekey_policy: self.rekey_policy,
+                their_credentials_expiration: self.their_credentials_expiration,
+                credentials_refresh: self.credentials_refresh,
+                keepalive: self.keepalive,
+                resumption: self.their_resumption,
+                resumed_session_expiration: self.resumed_session_expiration,
             }),
             _ => None,
         }
@@ -244,4 +333,16 @@ pub(super) struct IdentityAndCredentials {
     /// Credentials associated to the identity along with corresponding Credentials Purpose Keys
     /// to verify those Credentials
     #[n(3)] pub(super) credentials: Vec<CredentialAndPurposeKey>,
+    /// Number of messages after which keys are renewed, [`KEY_RENEWAL_INTERVAL`] if absent
+    ///
+    /// [`KEY_RENEWAL_INTERVAL`]: crate::secure_channel::encryptor::KEY_RENEWAL_INTERVAL
+    #[n(4)] pub(super) rekey_message_interval: Option<u64>,
+    /// Number of seconds after which keys are renewed, if any
+    #[n(5)] pub(super) rekey_time_interval: Option<u64>,
+    /// True if credentials can be refreshed over the channel once it is established
+    #[n(6)] pub(super) credentials_refresh: Option<bool>,
+    /// True if keepalives are accepted over the channel once it is established
+    #[n(7)] pub(super) keepalive: Option<bool>,
+    /// True if resumption tickets are accepted over the channel once it is established
+    #[n(8)] pub(super) resumption: Option<bool>,
 }
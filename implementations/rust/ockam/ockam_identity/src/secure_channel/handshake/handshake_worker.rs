@@ -3,18 +3,19 @@ use core::time::Duration;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{
-    AllowAll, Any, Decodable, DenyAll, Error, Mailbox, Mailboxes, OutgoingAccessControl, Route,
-    Routed,
+    Address, AllowAll, AllowSourceAddresses, Any, Decodable, DenyAll, Error, IncomingAccessControl,
+    Mailbox, Mailboxes, OutgoingAccessControl, Route, Routed,
 };
 use ockam_core::{AllowOnwardAddress, Result, Worker};
 use ockam_node::callback::CallbackSender;
 use ockam_node::{Context, WorkerBuilder};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::models::{CredentialAndPurposeKey, Identifier};
 use crate::secure_channel::decryptor::DecryptorHandler;
 use crate::secure_channel::encryptor::Encryptor;
 use crate::secure_channel::encryptor_worker::EncryptorWorker;
+use crate::secure_channel::event::SecureChannelEvent;
 use crate::secure_channel::handshake::handshake_state_machine::Action::SendMessage;
 use crate::secure_channel::handshake::handshake_state_machine::Event::{
     Initialize, ReceivedMessage,
@@ -24,10 +25,14 @@ use crate::secure_channel::handshake::handshake_state_machine::{
 };
 use crate::secure_channel::handshake::initiator_state_machine::InitiatorStateMachine;
 use crate::secure_channel::handshake::responder_state_machine::ResponderStateMachine;
-use crate::secure_channel::{Addresses, Role};
+use crate::secure_channel::{
+    credentials_expiration, Addresses, CredentialsExpiration, CredentialsRefresh,
+    CredentialsRefresher, IdleTimeout, KeepAliveTimer, LastActivity, ResumptionTicketIssuer,
+    ResumptionTicketReceiver, ResumptionTickets, Role, SessionResumption,
+};
 use crate::{
-    IdentityError, SecureChannelPurposeKey, SecureChannelRegistryEntry, SecureChannels,
-    TrustContext, TrustPolicy,
+    HandshakeProtocol, IdentityError, RekeyPolicy, SecureChannelPurposeKey,
+    SecureChannelRegistryEntry, SecureChannels, TrustContext, TrustPolicy,
 };
 
 /// This struct implements a Worker receiving and sending messages
@@ -41,6 +46,12 @@ pub(crate) struct HandshakeWorker {
     role: Role,
     remote_route: Option<Route>,
     decryptor_handler: Option<DecryptorHandler>,
+    credentials_refresher: Option<CredentialsRefresher>,
+    credentials_expiration: Option<CredentialsExpiration>,
+    idle_timeout: Option<IdleTimeout>,
+    keepalive: Option<KeepAliveTimer>,
+    ticket_issuer: Option<Arc<ResumptionTicketIssuer>>,
+    resumption_tickets: Option<ResumptionTickets>,
 }
 
 #[ockam_core::worker]
@@ -91,6 +102,9 @@ impl Worker for HandshakeWorker {
                 decryptor_handler.handle_decrypt(context, message).await
             } else if msg_addr == self.addresses.decryptor_api {
                 decryptor_handler.handle_decrypt_api(context, message).await
+            } else if msg_addr == self.addresses.decryptor_internal {
+                let event = SecureChannelEvent::decode(message.payload())?;
+                decryptor_handler.handle_event(context, event).await
             } else {
                 Err(IdentityError::UnknownChannelMsgDestination.into())
             };
@@ -98,13 +112,18 @@ impl Worker for HandshakeWorker {
         };
 
         let transport_message = message.into_transport_message();
-        if let SendMessage(message) = self
+        let action = self
             .state_machine
             .on_event(ReceivedMessage(Vec::<u8>::decode(
                 &transport_message.payload,
             )?))
-            .await?
-        {
+            .await;
+        if action.is_err() {
+            context
+                .metrics()
+                .record_secure_channel_handshake(self.role.str(), false);
+        }
+        if let SendMessage(message) = action? {
             // set the remote route by taking the most up to date message return route
             // In the case of the initiator the first return route mentions the secure channel listener
             // address so we need to wait for the return route corresponding to the remote handshake worker
@@ -124,6 +143,9 @@ impl Worker for HandshakeWorker {
         if let Some(final_state) = self.state_machine.get_handshake_results() {
             // start the encryptor worker and return the decryptor
             self.decryptor_handler = Some(self.finalize(context, final_state).await?);
+            context
+                .metrics()
+                .record_secure_channel_handshake(self.role.str(), true);
             if let Some(callback_sender) = self.callback_sender.take() {
                 callback_sender.send(())?;
             }
@@ -158,13 +180,87 @@ impl HandshakeWorker {
         trust_policy: Arc<dyn TrustPolicy>,
         decryptor_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
         credentials: Vec<CredentialAndPurposeKey>,
+        credentials_refresh: Option<CredentialsRefresh>,
         trust_context: Option<TrustContext>,
+        rekey_policy: RekeyPolicy,
+        idle_timeout: Option<Duration>,
+        keepalive_interval: Option<Duration>,
+        handshake_protocol: Option<HandshakeProtocol>,
+        resumption: Option<SessionResumption>,
         remote_route: Option<Route>,
         timeout: Option<Duration>,
         role: Role,
     ) -> Result<()> {
         let vault = secure_channels.identities.vault().secure_channel_vault;
         let identities = secure_channels.identities();
+
+        // our credentials are refreshed before they expire, if a retriever is available
+        let credentials_refresher =
+            match (credentials_refresh, credentials_expiration(&credentials)?) {
+                (Some(credentials_refresh), Some(expires_at)) => Some(
+                    CredentialsRefresher::create(
+                        context,
+                        identities.clone(),
+                        identifier.clone(),
+                        credentials_refresh,
+                        expires_at,
+                        addresses.encryptor_internal.clone(),
+                    )
+                    .await?,
+                ),
+                _ => None,
+            };
+
+        // the credentials of the other party can only be verified with a trust context
+        let credentials_expiration = match &trust_context {
+            Some(trust_context) => Some(
+                CredentialsExpiration::create(
+                    context,
+                    identities.clone(),
+                    trust_context.clone(),
+                    addresses.decryptor_internal.clone(),
+                )
+                .await?,
+            ),
+            None => None,
+        };
+
+        // the channel is stopped if the other party stays silent for too long
+        let idle_timeout = match idle_timeout {
+            Some(idle_timeout) => Some(
+                IdleTimeout::create(context, idle_timeout, addresses.decryptor_internal.clone())
+                    .await?,
+            ),
+            None => None,
+        };
+
+        // keepalives are sent when nothing else was sent to the other party for some time
+        let keepalive = match keepalive_interval {
+            Some(keepalive_interval) => Some(
+                KeepAliveTimer::create(
+                    context,
+                    keepalive_interval,
+                    addresses.encryptor_internal.clone(),
+                )
+                .await?,
+            ),
+            None => None,
+        };
+
+        let mut internal_event_addresses = vec![];
+        if let Some(credentials_expiration) = &credentials_expiration {
+            internal_event_addresses.push(credentials_expiration.address());
+        }
+        if let Some(idle_timeout) = &idle_timeout {
+            internal_event_addresses.push(idle_timeout.address());
+        }
+        // the initiator stores the tickets it receives and the responder issues them
+        let (resumption_ticket, resumption_tickets, ticket_issuer) = match resumption {
+            Some(SessionResumption::Initiator { ticket, tickets }) => (ticket, Some(tickets), None),
+            Some(SessionResumption::Responder(ticket_issuer)) => (None, None, Some(ticket_issuer)),
+            None => (None, None, None),
+        };
+
         let state_machine: Box<dyn StateMachine> = if role.is_initiator() {
             Box::new(
                 InitiatorStateMachine::new(
@@ -175,6 +271,10 @@ impl HandshakeWorker {
                     credentials,
                     trust_policy,
                     trust_context,
+                    rekey_policy,
+                    handshake_protocol.unwrap_or_default(),
+                    resumption_tickets.is_some(),
+                    resumption_ticket,
                 )
                 .await?,
             )
@@ -188,6 +288,9 @@ impl HandshakeWorker {
                     credentials,
                     trust_policy,
                     trust_context,
+                    rekey_policy,
+                    handshake_protocol,
+                    ticket_issuer.clone(),
                 )
                 .await?,
             )
@@ -209,12 +312,19 @@ impl HandshakeWorker {
             remote_route: remote_route.clone(),
             addresses: addresses.clone(),
             decryptor_handler: None,
+            credentials_refresher,
+            credentials_expiration,
+            idle_timeout,
+            keepalive,
+            ticket_issuer,
+            resumption_tickets,
         };
 
         WorkerBuilder::new(worker)
             .with_mailboxes(Self::create_mailboxes(
                 &addresses,
                 decryptor_outgoing_access_control,
+                internal_event_addresses,
             ))
             .start(context)
             .await?;
@@ -253,9 +363,12 @@ impl HandshakeWorker {
     }
 
     /// Create mailboxes and access rights for the workers involved in the secure channel creation
+    /// The internal mailbox only receives the events checking the expiration of the other party
+    /// credentials and its activity
     pub(crate) fn create_mailboxes(
         addresses: &Addresses,
         decryptor_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+        internal_event_addresses: Vec<Address>,
     ) -> Mailboxes {
         let remote_mailbox = Mailbox::new(
             addresses.decryptor_remote.clone(),
@@ -268,7 +381,7 @@ impl HandshakeWorker {
         );
         let internal_mailbox = Mailbox::new(
             addresses.decryptor_internal.clone(),
-            Arc::new(DenyAll),
+            Self::internal_incoming_access_control(internal_event_addresses),
             decryptor_outgoing_access_control,
         );
         let api_mailbox = Mailbox::new(
@@ -280,25 +393,107 @@ impl HandshakeWorker {
         Mailboxes::new(remote_mailbox, vec![internal_mailbox, api_mailbox])
     }
 
+    /// Internal addresses only accept the events scheduled by the channel itself
+    fn internal_incoming_access_control(
+        event_addresses: Vec<Address>,
+    ) -> Arc<dyn IncomingAccessControl> {
+        if event_addresses.is_empty() {
+            Arc::new(DenyAll)
+        } else {
+            Arc::new(AllowSourceAddresses(event_addresses))
+        }
+    }
+
     /// Finalize the handshake by creating a `Decryptor` and an `EncryptorWorker`
     /// Note that `EncryptorWorker` is actually started as an independent worker while
     /// the `Decryptor` is directly used by this worker to delegate the decryption of messages
     async fn finalize(
-        &self,
+        &mut self,
         context: &Context,
         handshake_results: HandshakeResults,
     ) -> Result<DecryptorHandler> {
+        // the channel is stopped if the credentials of the other party expire
+        let mut credentials_expiration = self.credentials_expiration.take();
+        if let Some(credentials_expiration) = credentials_expiration.as_mut() {
+            credentials_expiration
+                .track(handshake_results.their_credentials_expiration)
+                .await?;
+        }
+
+        // new credentials can only be sent if the other party supports it
+        let credentials_refresher = self
+            .credentials_refresher
+            .take()
+            .filter(|_| handshake_results.credentials_refresh);
+
+        // keepalives can only be sent if the other party supports them
+        let keepalive = self
+            .keepalive
+            .take()
+            .filter(|_| handshake_results.keepalive);
+
+        // the responder issues a ticket to resume this session later, if the initiator accepts it
+        let resumption_ticket = match &self.ticket_issuer {
+            Some(ticket_issuer) if handshake_results.resumption => {
+                match ticket_issuer
+                    .issue(
+                        handshake_results.their_identifier.clone(),
+                        self.identifier.clone(),
+                        handshake_results.rekey_policy,
+                        handshake_results.their_credentials_expiration,
+                        handshake_results.keepalive,
+                        handshake_results.resumed_session_expiration,
+                    )
+                    .await
+                {
+                    Ok(ticket) => Some(ticket),
+                    Err(e) => {
+                        warn!("failed to issue a resumption ticket: {}", e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        // the initiator stores the tickets it receives
+        let resumption_tickets = self
+            .resumption_tickets
+            .take()
+            .map(|tickets| ResumptionTicketReceiver::new(tickets, self.identifier.clone()));
+
+        // the idle timeout starts once the handshake is done
+        let last_activity = LastActivity::new();
+        let mut idle_timeout = self.idle_timeout.take();
+        if let Some(idle_timeout) = idle_timeout.as_mut() {
+            idle_timeout.start().await?;
+        }
+
         // create a decryptor to delegate the processing of all messages after the handshake
         let decryptor = DecryptorHandler::new(
             self.role.str(),
             self.addresses.clone(),
             handshake_results.handshake_keys.decryption_key,
+            handshake_results.rekey_policy.message_interval(),
             self.secure_channels.identities.vault().secure_channel_vault,
             handshake_results.their_identifier.clone(),
+            handshake_results.credentials_refresh,
+            credentials_expiration,
+            last_activity.clone(),
+            idle_timeout,
+            resumption_tickets,
         );
 
         // create a separate encryptor worker which will be started independently
         {
+            let mut internal_event_addresses = vec![];
+            if let Some(credentials_refresher) = &credentials_refresher {
+                internal_event_addresses.push(credentials_refresher.address());
+            }
+            if let Some(keepalive) = &keepalive {
+                internal_event_addresses.push(keepalive.address());
+            }
+
             let encryptor = EncryptorWorker::new(
                 self.role.str(),
                 self.addresses.clone(),
@@ -306,8 +501,13 @@ impl HandshakeWorker {
                 Encryptor::new(
                     handshake_results.handshake_keys.encryption_key,
                     0,
+                    handshake_results.rekey_policy,
                     self.secure_channels.identities.vault().secure_channel_vault,
-                ),
+                )?,
+                handshake_results.credentials_refresh,
+                credentials_refresher,
+                keepalive,
+                resumption_ticket,
             );
 
             let next_hop = self.remote_route()?.next()?.clone();
@@ -322,8 +522,17 @@ impl HandshakeWorker {
                 Arc::new(AllowAll),
             );
 
+            let internal_mailbox = Mailbox::new(
+                self.addresses.encryptor_internal.clone(),
+                Self::internal_incoming_access_control(internal_event_addresses),
+                Arc::new(DenyAll),
+            );
+
             WorkerBuilder::new(encryptor)
-                .with_mailboxes(Mailboxes::new(main_mailbox, vec![api_mailbox]))
+                .with_mailboxes(Mailboxes::new(
+                    main_mailbox,
+                    vec![api_mailbox, internal_mailbox],
+                ))
                 .start(context)
                 .await?;
         }
@@ -351,7 +560,9 @@ impl HandshakeWorker {
             self.identifier.clone(),
             handshake_results.their_identifier,
             their_decryptor_address,
-        );
+        )
+        .with_last_activity(last_activity)
+        .with_resumed(handshake_results.resumed_session_expiration.is_some());
 
         self.secure_channels
             .secure_channel_registry()
@@ -5,6 +5,7 @@ use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{VaultForSecureChannels, X25519PublicKey};
+use tracing::debug;
 use Action::*;
 use Event::*;
 use Role::*;
@@ -17,7 +18,10 @@ use crate::secure_channel::handshake::handshake_state_machine::{
     Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
     StateMachine, Status,
 };
-use crate::{Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
+use crate::{
+    HandshakeProtocol, Identities, RekeyPolicy, ResumptionTicket, Role, SecureChannelPurposeKey,
+    TrustContext, TrustPolicy,
+};
 
 /// Implementation of a state machine for the key exchange on the initiator side
 #[async_trait]
@@ -25,6 +29,20 @@ impl StateMachine for InitiatorStateMachine {
     async fn on_event(&mut self, event: Event) -> Result<Action> {
         let state = self.handshake.state.clone();
         match (state.status, event) {
+            // Resume a previous session with a ticket and send the resumption message 1
+            (Initial, Initialize) if self.resumption_ticket.is_some() => {
+                let ticket = self
+                    .resumption_ticket
+                    .clone()
+                    .ok_or(XXError::InvalidInternalState)?;
+                self.initialize_resumption(&ticket.ticket, &ticket.secret)
+                    .await?;
+                let message1 = self.encode_resumption_message1(&ticket.ticket).await?;
+
+                // Send the resumption message 1 and wait for the resumption message 2
+                self.handshake.state.status = WaitingForResumptionMessage2;
+                Ok(SendMessage(message1))
+            }
             // Initialize the handshake and send message 1
             (Initial, Initialize) => {
                 self.initialize_handshake().await?;
@@ -49,6 +67,39 @@ impl StateMachine for InitiatorStateMachine {
                 self.set_final_state(Initiator).await?;
                 Ok(SendMessage(message3))
             }
+            // Process the resumption message 2, or fall back to a full handshake
+            // if the responder rejected the ticket
+            (WaitingForResumptionMessage2, ReceivedMessage(message)) => {
+                let ticket = self
+                    .resumption_ticket
+                    .take()
+                    .ok_or(XXError::InvalidInternalState)?;
+
+                if message.is_empty() {
+                    debug!(
+                        "the resumption ticket was rejected by {}, starting a full handshake",
+                        ticket.their_identifier
+                    );
+                    self.handshake.restart(self.handshake_protocol).await?;
+                    self.initialize_handshake().await?;
+                    let message1 = self.encode_message1(&[]).await?;
+
+                    // Send message 1 and wait for message 2
+                    self.handshake.state.status = WaitingForMessage2;
+                    return Ok(SendMessage(message1));
+                }
+
+                self.decode_resumption_message2(&message).await?;
+                self.set_final_state(Initiator).await?;
+                self.common.resume_session(
+                    ticket.their_identifier,
+                    ticket.rekey_policy,
+                    ticket.their_credentials_expiration,
+                    ticket.keepalive,
+                    ticket.expires_at,
+                );
+                Ok(NoAction)
+            }
             // incorrect state / event
             (s, e) => Err(Error::new(
                 Origin::Channel,
@@ -72,6 +123,10 @@ pub(super) struct InitiatorStateMachine {
     pub(super) handshake: Handshake,
     /// this serialized payload contains an identity, its credentials and a signature of its static key
     pub(super) identity_payload: Option<Vec<u8>>,
+    /// protocol used for a full handshake
+    pub(super) handshake_protocol: HandshakeProtocol,
+    /// ticket used to resume a previous session instead of running a full handshake
+    pub(super) resumption_ticket: Option<ResumptionTicket>,
 }
 
 impl InitiatorStateMachine {
@@ -86,6 +141,9 @@ impl InitiatorStateMachine {
             #[call(initialize)]
             async fn initialize_handshake(&mut self) -> Result<()>;
             async fn encode_message1(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
+            async fn initialize_resumption(&mut self, ticket: &[u8], secret: &[u8]) -> Result<()>;
+            async fn encode_resumption_message1(&mut self, ticket: &[u8]) -> Result<Vec<u8>>;
+            async fn decode_resumption_message2(&mut self, message: &[u8]) -> Result<()>;
             async fn decode_message2(&mut self, message: &[u8]) -> Result<Vec<u8>>;
             async fn encode_message3(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
             async fn set_final_state(&mut self, role: Role) -> Result<()>;
@@ -95,6 +153,7 @@ impl InitiatorStateMachine {
 }
 
 impl InitiatorStateMachine {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         vault: Arc<dyn VaultForSecureChannels>,
         identities: Arc<Identities>,
@@ -103,6 +162,10 @@ impl InitiatorStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        rekey_policy: RekeyPolicy,
+        handshake_protocol: HandshakeProtocol,
+        resumption: bool,
+        resumption_ticket: Option<ResumptionTicket>,
     ) -> Result<InitiatorStateMachine> {
         let common = CommonStateMachine::new(
             identities,
@@ -111,13 +174,37 @@ impl InitiatorStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            rekey_policy,
+            resumption,
         );
         let identity_payload = common.make_identity_payload().await?;
 
+        // a session is only resumed with a party which is still trusted
+        let resumption_ticket = match resumption_ticket {
+            Some(ticket)
+                if common
+                    .check_trust_policy(&ticket.their_identifier)
+                    .await
+                    .is_ok() =>
+            {
+                Some(ticket)
+            }
+            _ => None,
+        };
+
+        // the handshake protocol is only used if the session is not resumed
+        let protocol = if resumption_ticket.is_some() {
+            HandshakeProtocol::Classical
+        } else {
+            handshake_protocol
+        };
+
         Ok(InitiatorStateMachine {
             common,
-            handshake: Handshake::new(vault, purpose_key.key().clone()).await?,
+            handshake: Handshake::new(vault, purpose_key.key().clone(), protocol).await?,
             identity_payload: Some(identity_payload),
+            handshake_protocol,
+            resumption_ticket,
         })
     }
 }
@@ -5,6 +5,7 @@ use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{VaultForSecureChannels, X25519PublicKey};
+use tracing::warn;
 use Action::*;
 use Event::*;
 use Role::*;
@@ -17,7 +18,11 @@ use crate::secure_channel::handshake::handshake_state_machine::{
     Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
     StateMachine, Status,
 };
-use crate::{Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
+use crate::secure_channel::{ResumptionTicketContent, ResumptionTicketIssuer};
+use crate::{
+    HandshakeProtocol, Identities, IdentityError, RekeyPolicy, Role, SecureChannelPurposeKey,
+    TrustContext, TrustPolicy,
+};
 
 /// Implementation of a state machine for the key exchange on the responder side
 #[async_trait]
@@ -25,14 +30,46 @@ impl StateMachine for ResponderStateMachine {
     async fn on_event(&mut self, event: Event) -> Result<Action> {
         let state = self.handshake.state.clone();
         match (state.status, event) {
-            // Initialize the handshake and wait for message 1
+            // Wait for message 1
             (Initial, Initialize) => {
-                self.initialize_handshake().await?;
                 self.handshake.state.status = WaitingForMessage1;
                 Ok(NoAction)
             }
-            // Process message 1 and send message 2
+            // Resume a previous session with the ticket presented by the initiator, or reject the
+            // ticket with an empty message so that the initiator starts a full handshake instead
+            (WaitingForMessage1, ReceivedMessage(message))
+                if Handshake::is_resumption_message1(&message) =>
+            {
+                let ticket = match self.open_resumption_ticket(&message).await {
+                    Ok(ticket) => ticket,
+                    Err(e) => {
+                        warn!("rejecting a session resumption: {}", e);
+                        return Ok(SendMessage(vec![]));
+                    }
+                };
+
+                self.initialize_resumption(
+                    Handshake::read_resumption_ticket(&message)?,
+                    &ticket.secret,
+                )
+                .await?;
+                self.decode_resumption_message1(&message).await?;
+                let message2 = self.encode_resumption_message2().await?;
+                self.set_final_state(Responder).await?;
+                self.common.resume_session(
+                    ticket.initiator.clone(),
+                    ticket.rekey_policy(),
+                    ticket.initiator_credentials_expiration,
+                    ticket.keepalive,
+                    ticket.expires_at,
+                );
+                Ok(SendMessage(message2))
+            }
+            // Initialize the handshake with the protocol selected by the initiator,
+            // then process message 1 and send message 2
             (WaitingForMessage1, ReceivedMessage(message)) => {
+                self.select_protocol(&message, self.accepted_protocol)?;
+                self.initialize_handshake().await?;
                 self.decode_message1(&message).await?;
                 let identity_payload = self
                     .identity_payload
@@ -75,6 +112,10 @@ pub struct ResponderStateMachine {
     handshake: Handshake,
     /// this serialized payload contains an identity, its credentials and a signature of its static key
     identity_payload: Option<Vec<u8>>,
+    /// if set, the only protocol accepted for the key exchange
+    accepted_protocol: Option<HandshakeProtocol>,
+    /// if set, sessions can be resumed with the tickets issued by the listener
+    ticket_issuer: Option<Arc<ResumptionTicketIssuer>>,
 }
 
 impl ResponderStateMachine {
@@ -88,7 +129,11 @@ impl ResponderStateMachine {
         to self.handshake {
             #[call(initialize)]
             async fn initialize_handshake(&mut self) -> Result<()>;
+            fn select_protocol(&mut self, message1: &[u8], accepted_protocol: Option<HandshakeProtocol>) -> Result<()>;
             async fn decode_message1(&mut self, message: &[u8]) -> Result<Vec<u8>>;
+            async fn initialize_resumption(&mut self, ticket: &[u8], secret: &[u8]) -> Result<()>;
+            async fn decode_resumption_message1(&mut self, message: &[u8]) -> Result<()>;
+            async fn encode_resumption_message2(&mut self) -> Result<Vec<u8>>;
             async fn encode_message2(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
             async fn decode_message3(&mut self, message: &[u8]) -> Result<Vec<u8>>;
             async fn set_final_state(&mut self, role: Role) -> Result<()>;
@@ -98,6 +143,7 @@ impl ResponderStateMachine {
 }
 
 impl ResponderStateMachine {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         vault: Arc<dyn VaultForSecureChannels>,
         identities: Arc<Identities>,
@@ -106,6 +152,9 @@ impl ResponderStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        rekey_policy: RekeyPolicy,
+        accepted_protocol: Option<HandshakeProtocol>,
+        ticket_issuer: Option<Arc<ResumptionTicketIssuer>>,
     ) -> Result<ResponderStateMachine> {
         let common = CommonStateMachine::new(
             identities,
@@ -114,13 +163,41 @@ impl ResponderStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            rekey_policy,
+            ticket_issuer.is_some(),
         );
         let identity_payload = common.make_identity_payload().await?;
 
         Ok(ResponderStateMachine {
             common,
-            handshake: Handshake::new(vault, purpose_key.key().clone()).await?,
+            // the responder starts with the classical protocol until it receives message 1
+            handshake: Handshake::new(
+                vault,
+                purpose_key.key().clone(),
+                HandshakeProtocol::Classical,
+            )
+            .await?,
             identity_payload: Some(identity_payload),
+            accepted_protocol,
+            ticket_issuer,
         })
     }
+
+    /// Open the ticket presented in a session resumption message 1. The session is only resumed
+    /// if the ticket was issued by this listener and is still valid, and if the initiator is
+    /// still trusted
+    async fn open_resumption_ticket(&self, message: &[u8]) -> Result<ResumptionTicketContent> {
+        let ticket_issuer = self
+            .ticket_issuer
+            .as_ref()
+            .ok_or(IdentityError::ResumptionTicketRejected)?;
+        let ticket = ticket_issuer
+            .open(
+                Handshake::read_resumption_ticket(message)?,
+                &self.common.identifier,
+            )
+            .await?;
+        self.common.check_trust_policy(&ticket.initiator).await?;
+        Ok(ticket)
+    }
 }
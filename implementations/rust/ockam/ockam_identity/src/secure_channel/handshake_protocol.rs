@@ -0,0 +1,21 @@
+/// Key exchange protocol used by the handshake of a secure channel.
+///
+/// The hybrid protocol mixes an ML-KEM-768 encapsulation into the Noise XX key exchange,
+/// alongside X25519. The channel keys then remain confidential as long as one of the two
+/// algorithms is not broken, which protects recorded traffic against a future quantum computer.
+/// Its messages are larger, and the other party must support it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HandshakeProtocol {
+    /// Noise XX over X25519
+    #[default]
+    Classical,
+    /// Noise XX over X25519 and ML-KEM-768, using the `hfs` modifier of the Noise protocol
+    Hybrid,
+}
+
+impl HandshakeProtocol {
+    /// Return true for the hybrid post-quantum protocol
+    pub fn is_hybrid(&self) -> bool {
+        matches!(self, HandshakeProtocol::Hybrid)
+    }
+}
@@ -9,12 +9,15 @@ use crate::secure_channel::addresses::Addresses;
 use crate::secure_channel::handshake_worker::HandshakeWorker;
 use crate::secure_channel::options::SecureChannelListenerOptions;
 use crate::secure_channel::role::Role;
+use crate::secure_channel::{ResumptionTicketIssuer, SessionResumption};
 use crate::secure_channels::secure_channels::SecureChannels;
+use crate::HandshakeProtocol;
 
 pub(crate) struct IdentityChannelListener {
     secure_channels: Arc<SecureChannels>,
     identifier: Identifier,
     options: SecureChannelListenerOptions,
+    ticket_issuer: Option<Arc<ResumptionTicketIssuer>>,
 }
 
 impl IdentityChannelListener {
@@ -22,11 +25,13 @@ impl IdentityChannelListener {
         secure_channels: Arc<SecureChannels>,
         identifier: Identifier,
         options: SecureChannelListenerOptions,
+        ticket_issuer: Option<Arc<ResumptionTicketIssuer>>,
     ) -> Self {
         Self {
             secure_channels,
             identifier,
             options,
+            ticket_issuer,
         }
     }
 
@@ -39,7 +44,27 @@ impl IdentityChannelListener {
     ) -> Result<()> {
         options.setup_flow_control_for_listener(ctx.flow_controls(), &address);
 
-        let listener = Self::new(secure_channels.clone(), identifier.clone(), options);
+        // resumption tickets are only issued if they have a lifetime, and sessions are not resumed
+        // when the hybrid protocol is required since a resumption only relies on X25519
+        let ticket_issuer = match options.resumption_ticket_lifetime {
+            Some(lifetime) if options.handshake_protocol != Some(HandshakeProtocol::Hybrid) => {
+                Some(Arc::new(
+                    ResumptionTicketIssuer::create(
+                        secure_channels.identities.vault().secure_channel_vault,
+                        lifetime,
+                    )
+                    .await?,
+                ))
+            }
+            _ => None,
+        };
+
+        let listener = Self::new(
+            secure_channels.clone(),
+            identifier.clone(),
+            options,
+            ticket_issuer,
+        );
 
         ctx.start_worker(address, listener).await?;
 
@@ -107,7 +132,13 @@ impl Worker for IdentityChannelListener {
             self.options.trust_policy.clone(),
             access_control.decryptor_outgoing_access_control,
             credentials,
+            self.options.credentials_refresh(),
             self.options.trust_context.clone(),
+            self.options.rekey_policy,
+            self.options.idle_timeout,
+            self.options.keepalive_interval,
+            self.options.handshake_protocol,
+            self.ticket_issuer.clone().map(SessionResumption::Responder),
             None,
             None,
             Role::Responder,
@@ -123,4 +154,11 @@ impl Worker for IdentityChannelListener {
 
         ctx.forward(local_message).await
     }
+
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        if let Some(ticket_issuer) = &self.ticket_issuer {
+            ticket_issuer.shutdown().await?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,124 @@
+use core::time::Duration;
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::{Address, Result};
+use ockam_node::{Context, DelayedEvent};
+
+use crate::models::TimestampInSeconds;
+use crate::secure_channel::event::SecureChannelEvent;
+use crate::utils::now;
+
+/// Time at which a message was last received from the other party of a secure channel.
+/// It is updated by the decryptor and can be read from the secure channel registry
+#[derive(Clone, Debug)]
+pub(crate) struct LastActivity(Arc<RwLock<TimestampInSeconds>>);
+
+impl LastActivity {
+    /// Start tracking the activity of a channel, from now on
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(RwLock::new(
+            now().unwrap_or(TimestampInSeconds(0)),
+        )))
+    }
+
+    /// Record that a message was just received
+    pub(crate) fn update(&self) {
+        if let Ok(now) = now() {
+            *self.0.write().unwrap() = now;
+        }
+    }
+
+    /// Time of the last received message
+    pub(crate) fn get(&self) -> TimestampInSeconds {
+        *self.0.read().unwrap()
+    }
+}
+
+/// Check that the other party of a secure channel is not silent for longer than a timeout
+pub(crate) struct IdleTimeout {
+    timeout: Duration,
+    event: DelayedEvent<SecureChannelEvent>,
+}
+
+impl IdleTimeout {
+    /// Create a timeout sending idle check events to the `destination` address
+    pub(crate) async fn create(
+        ctx: &Context,
+        timeout: Duration,
+        destination: Address,
+    ) -> Result<Self> {
+        let event = DelayedEvent::create(ctx, destination, SecureChannelEvent::IdleCheck).await?;
+        Ok(Self { timeout, event })
+    }
+
+    /// Address sending the idle check events
+    pub(crate) fn address(&self) -> Address {
+        self.event.address()
+    }
+
+    /// Schedule the first check, one timeout from now
+    pub(crate) async fn start(&mut self) -> Result<()> {
+        self.event.schedule(self.timeout).await
+    }
+
+    /// Return true if nothing was received for the duration of the timeout.
+    /// Otherwise the check is scheduled again, one timeout after the last activity
+    pub(crate) async fn check(&mut self, last_activity: TimestampInSeconds) -> Result<bool> {
+        let idle = now()?.0.saturating_sub(last_activity.0);
+        let timeout = self.timeout.as_secs();
+        if idle >= timeout {
+            return Ok(true);
+        }
+        self.event
+            .schedule(Duration::from_secs(timeout - idle))
+            .await?;
+        Ok(false)
+    }
+}
+
+/// Send keepalives to the other party of a secure channel when no other message
+/// was sent during an interval, so that the channel is not considered idle
+pub(crate) struct KeepAliveTimer {
+    interval: Duration,
+    sent: bool,
+    event: DelayedEvent<SecureChannelEvent>,
+}
+
+impl KeepAliveTimer {
+    /// Create a timer sending keepalive events to the `destination` address
+    pub(crate) async fn create(
+        ctx: &Context,
+        interval: Duration,
+        destination: Address,
+    ) -> Result<Self> {
+        let event = DelayedEvent::create(ctx, destination, SecureChannelEvent::KeepAlive).await?;
+        Ok(Self {
+            interval,
+            sent: false,
+            event,
+        })
+    }
+
+    /// Address sending the keepalive events
+    pub(crate) fn address(&self) -> Address {
+        self.event.address()
+    }
+
+    /// Schedule the next keepalive event, one interval from now
+    pub(crate) async fn schedule(&mut self) -> Result<()> {
+        self.event.schedule(self.interval).await
+    }
+
+    /// Record that a message was sent to the other party
+    pub(crate) fn sent(&mut self) {
+        self.sent = true;
+    }
+
+    /// Return true if nothing was sent since the previous keepalive event,
+    /// in which case a keepalive must be sent. The next event is scheduled
+    pub(crate) async fn check(&mut self) -> Result<bool> {
+        let send_keepalive = !self.sent;
+        self.sent = false;
+        self.schedule().await?;
+        Ok(send_keepalive)
+    }
+}
@@ -0,0 +1,53 @@
+use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
+
+use crate::models::{ChangeHistory, CredentialAndPurposeKey, TimestampInSeconds};
+use crate::secure_channel::resumption::RESUMPTION_SECRET_LENGTH;
+
+/// Plaintext of the messages exchanged over a secure channel when both parties
+/// agreed during the handshake to refresh their credentials or to send keepalives.
+/// Otherwise the plaintext is directly an encoded [`ockam_core::TransportMessage`]
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+pub(crate) enum SecureChannelMessage {
+    /// Encoded [`ockam_core::TransportMessage`] sent by a user of the channel
+    #[n(0)] Payload(#[cbor(n(0), with = "minicbor::bytes")] Vec<u8>),
+    /// New credentials of the sender, presented before the previous ones expire
+    #[n(1)] RefreshCredentials(#[n(0)] RefreshCredentials),
+    /// Sent when nothing else was sent for some time, to show that the sender is still there
+    #[n(2)] KeepAlive,
+    /// Ticket issued by the responder to resume the session later
+    #[n(3)] NewResumptionTicket(#[n(0)] NewResumptionTicket),
+}
+
+/// Credentials presented over an established secure channel
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct RefreshCredentials {
+    /// Latest Change History of the sender, in case its keys were rotated since the handshake
+    #[n(1)] pub(crate) change_history: ChangeHistory,
+    /// Credentials associated to the identity along with corresponding Credentials Purpose Keys
+    /// to verify those Credentials
+    #[n(2)] pub(crate) credentials: Vec<CredentialAndPurposeKey>,
+}
+
+/// Resumption ticket sent by the responder over an established secure channel,
+/// along with the parameters of the session it resumes
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct NewResumptionTicket {
+    /// Ticket encrypted by the responder, presented as it is to resume the session
+    #[cbor(n(1), with = "minicbor::bytes")] pub(crate) ticket: Vec<u8>,
+    /// Secret contained in the ticket, used as a pre-shared key to resume the session
+    #[cbor(n(2), with = "minicbor::bytes")] pub(crate) secret: [u8; RESUMPTION_SECRET_LENGTH],
+    /// Expiration of the ticket
+    #[n(3)] pub(crate) expires_at: TimestampInSeconds,
+    /// Number of messages after which keys are renewed
+    #[n(4)] pub(crate) rekey_message_interval: u64,
+    /// Number of seconds after which keys are renewed, if any
+    #[n(5)] pub(crate) rekey_time_interval: Option<u64>,
+    /// True if keepalives are accepted by the responder
+    #[n(6)] pub(crate) keepalive: bool,
+}
@@ -2,16 +2,23 @@
 pub mod access_control;
 mod addresses;
 mod api;
+mod credentials_refresh;
 mod decryptor;
 mod encryptor;
 mod encryptor_worker;
+mod event;
 mod handshake;
+mod handshake_protocol;
 mod key_tracker;
 mod listener;
+mod liveness;
 mod local_info;
+mod message;
 mod nonce_tracker;
 mod options;
 mod registry;
+mod rekey_policy;
+mod resumption;
 mod role;
 /// List of trust policies to setup ABAC controls
 pub mod trust_policy;
@@ -19,17 +26,24 @@ pub mod trust_policy;
 pub use access_control::*;
 pub(crate) use addresses::*;
 pub use api::*;
+pub(crate) use credentials_refresh::*;
 pub(crate) use handshake::*;
+pub use handshake_protocol::*;
 pub(crate) use listener::*;
+pub(crate) use liveness::*;
 pub use local_info::*;
 pub use options::*;
 pub use registry::*;
+pub use rekey_policy::*;
+pub use resumption::*;
 pub(crate) use role::*;
 pub use trust_policy::*;
 
 #[cfg(test)]
 mod tests {
     use crate::secure_channel::{decryptor::Decryptor, encryptor::Encryptor};
+    use crate::RekeyPolicy;
+    use core::time::Duration;
     use ockam_core::compat::rand::RngCore;
     use ockam_core::Result;
     use ockam_vault::{SoftwareVaultForSecureChannels, VaultForSecureChannels};
@@ -105,6 +119,48 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_encrypt_decrypt_with_large_message_interval() {
+        let policy = RekeyPolicy::new().with_message_interval(1000);
+        let (mut encryptor, mut decryptor) = create_encryptor_decryptor_with_policy(policy)
+            .await
+            .unwrap();
+
+        let mut ciphertexts = Vec::new();
+        for n in 0..3000u32 {
+            ciphertexts.push((n, encryptor.encrypt(&n.to_be_bytes()).await.unwrap()));
+        }
+        // messages are accepted out of order within the whole interval
+        ciphertexts[..1000].reverse();
+        for (n, ciphertext) in ciphertexts.iter() {
+            assert_eq!(
+                n.to_be_bytes().to_vec(),
+                decryptor.decrypt(ciphertext).await.unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_decrypt_with_time_interval() {
+        // a 0 time interval renews the key on each message
+        let policy = RekeyPolicy::new()
+            .with_message_interval(100)
+            .with_time_interval(Duration::from_secs(0));
+        let (mut encryptor, mut decryptor) = create_encryptor_decryptor_with_policy(policy)
+            .await
+            .unwrap();
+
+        for n in 0..10 {
+            let msg = vec![n];
+            let ciphertext = encryptor.encrypt(&msg).await.unwrap();
+            assert_eq!(
+                u64::from_be_bytes(ciphertext[..8].try_into().unwrap()),
+                n as u64 * 100
+            );
+            assert_eq!(msg, decryptor.decrypt(&ciphertext).await.unwrap());
+        }
+    }
+
     #[tokio::test]
     async fn test_attack_nonce() {
         let (mut encryptor, mut decryptor) = create_encryptor_decryptor().await.unwrap();
@@ -132,6 +188,12 @@ mod tests {
     }
 
     async fn create_encryptor_decryptor() -> Result<(Encryptor, Decryptor)> {
+        create_encryptor_decryptor_with_policy(RekeyPolicy::default()).await
+    }
+
+    async fn create_encryptor_decryptor_with_policy(
+        policy: RekeyPolicy,
+    ) -> Result<(Encryptor, Decryptor)> {
         let vault1 = SoftwareVaultForSecureChannels::create();
         let vault2 = SoftwareVaultForSecureChannels::create();
 
@@ -146,8 +208,8 @@ mod tests {
         let key_on_v2 = vault2.convert_secret_buffer_to_aead_key(key_on_v2).await?;
 
         Ok((
-            Encryptor::new(key_on_v1, 0, vault1),
-            Decryptor::new(key_on_v2, vault2),
+            Encryptor::new(key_on_v1, 0, policy, vault1)?,
+            Decryptor::new(key_on_v2, policy.message_interval(), vault2),
         ))
     }
 }
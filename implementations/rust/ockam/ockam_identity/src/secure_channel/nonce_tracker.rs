@@ -1,83 +1,139 @@
-use crate::secure_channel::encryptor::KEY_RENEWAL_INTERVAL;
+use ockam_core::compat::vec::Vec;
+
 use crate::IdentityError;
 
-/// fails compilation if [`KEY_RENEWAL_INTERVAL`] + 1 is bigger than [`BitmapType::BITS`].
-///
-/// the +1 is needed since the current nonce is also marked as received, taking an extra bit
-/// even though we could check `current_nonce`, this compromise is for the sake of simplicity
-const _: [(); (KEY_RENEWAL_INTERVAL + 1 > BitmapType::BITS as u64) as usize] = [];
 type BitmapType = u64;
 
+/// Keep track of the nonces received during the last `window` messages in order to
+/// reject replayed messages.
+///
+/// The window has the size of the key renewal interval: received nonces are stored in a
+/// ring bitmap of at least `window + 1` bits. The +1 is needed since the current nonce is
+/// also marked as received, taking an extra bit even though we could check `current_nonce`,
+/// this compromise is for the sake of simplicity
 #[derive(Debug)]
 pub(crate) struct NonceTracker {
-    nonce_bitmap: BitmapType,
+    nonce_bitmap: Vec<BitmapType>,
     current_nonce: u64,
+    window: u64,
 }
 
 impl NonceTracker {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(window: u64) -> Self {
+        let words = window / BitmapType::BITS as u64 + 1;
         Self {
-            nonce_bitmap: 0,
+            nonce_bitmap: vec![0; words as usize],
             current_nonce: 0,
+            window,
         }
     }
 
-    /// Mark a nonce as received, reject all invalid nonce values
-    pub(crate) fn mark(&self, nonce: u64) -> ockam_core::Result<NonceTracker> {
-        let new_tracker = if nonce > self.current_nonce {
-            // normal case, we increase the nonce and move the window
-            let relative_shift: u64 = nonce - self.current_nonce;
-            if relative_shift > KEY_RENEWAL_INTERVAL {
+    /// Check that a nonce can be accepted, without marking it as received
+    pub(crate) fn check(&self, nonce: u64) -> ockam_core::Result<()> {
+        if nonce > self.current_nonce {
+            // normal case, the window will move forward
+            if nonce - self.current_nonce > self.window {
                 return Err(IdentityError::InvalidNonce.into());
             }
-            NonceTracker {
-                nonce_bitmap: self.nonce_bitmap.overflowing_shl(relative_shift as u32).0 | 1,
-                current_nonce: nonce,
-            }
         } else {
             // first message or an out of order message
-            let relative: u64 = self.current_nonce - nonce;
-            if relative > KEY_RENEWAL_INTERVAL {
+            if self.current_nonce - nonce > self.window || self.is_marked(nonce) {
                 return Err(IdentityError::InvalidNonce.into());
             }
+        }
+        Ok(())
+    }
 
-            #[allow(trivial_numeric_casts)]
-            let bit = (1 as BitmapType).overflowing_shl(relative as u32).0;
-            if self.nonce_bitmap & bit != 0 {
-                // we already processed this nonce
-                return Err(IdentityError::InvalidNonce.into());
-            }
-            NonceTracker {
-                nonce_bitmap: self.nonce_bitmap | bit,
-                current_nonce: self.current_nonce,
+    /// Mark a nonce as received, reject all invalid nonce values
+    pub(crate) fn mark(&mut self, nonce: u64) -> ockam_core::Result<()> {
+        self.check(nonce)?;
+
+        if nonce > self.current_nonce {
+            // forget the nonces which are now outside of the window
+            for skipped in self.current_nonce + 1..nonce {
+                self.set_bit(skipped, false);
             }
-        };
+            self.current_nonce = nonce;
+        }
+        self.set_bit(nonce, true);
+        Ok(())
+    }
 
-        Ok(new_tracker)
+    fn bit_position(&self, nonce: u64) -> (usize, BitmapType) {
+        let bits = self.nonce_bitmap.len() as u64 * BitmapType::BITS as u64;
+        let index = nonce % bits;
+        (
+            (index / BitmapType::BITS as u64) as usize,
+            1 << (index % BitmapType::BITS as u64),
+        )
+    }
+
+    fn is_marked(&self, nonce: u64) -> bool {
+        let (word, bit) = self.bit_position(nonce);
+        self.nonce_bitmap[word] & bit != 0
+    }
+
+    fn set_bit(&mut self, nonce: u64, value: bool) {
+        let (word, bit) = self.bit_position(nonce);
+        if value {
+            self.nonce_bitmap[word] |= bit;
+        } else {
+            self.nonce_bitmap[word] &= !bit;
+        }
     }
 }
 
-#[test]
-pub fn check_nonce_tracker() {
-    let mut tracker = NonceTracker::new();
-    tracker = tracker.mark(0).unwrap();
-    tracker = tracker.mark(1).unwrap();
-    tracker.mark(0).unwrap_err();
-    tracker.mark(KEY_RENEWAL_INTERVAL + 2).unwrap_err();
-    tracker = tracker.mark(KEY_RENEWAL_INTERVAL + 1).unwrap();
-    tracker.mark(1).unwrap_err();
-    tracker = tracker.mark(KEY_RENEWAL_INTERVAL + 2).unwrap();
-    tracker = tracker.mark(KEY_RENEWAL_INTERVAL + 3).unwrap();
-    tracker.mark(KEY_RENEWAL_INTERVAL + 1).unwrap_err();
-    tracker.mark(KEY_RENEWAL_INTERVAL + 2).unwrap_err();
-    tracker = tracker.mark(2 * KEY_RENEWAL_INTERVAL).unwrap();
-    tracker.mark(KEY_RENEWAL_INTERVAL - 1).unwrap_err();
-    tracker = tracker.mark(3 * KEY_RENEWAL_INTERVAL).unwrap();
-    tracker = tracker.mark(4 * KEY_RENEWAL_INTERVAL).unwrap();
-    for n in 3 * KEY_RENEWAL_INTERVAL + 1..4 * KEY_RENEWAL_INTERVAL {
-        tracker = tracker.mark(n).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure_channel::encryptor::KEY_RENEWAL_INTERVAL;
+
+    #[test]
+    pub fn check_nonce_tracker() {
+        let mut tracker = NonceTracker::new(KEY_RENEWAL_INTERVAL);
+        tracker.mark(0).unwrap();
+        tracker.mark(1).unwrap();
+        tracker.mark(0).unwrap_err();
+        tracker.mark(KEY_RENEWAL_INTERVAL + 2).unwrap_err();
+        tracker.mark(KEY_RENEWAL_INTERVAL + 1).unwrap();
+        tracker.mark(1).unwrap_err();
+        tracker.mark(KEY_RENEWAL_INTERVAL + 2).unwrap();
+        tracker.mark(KEY_RENEWAL_INTERVAL + 3).unwrap();
+        tracker.mark(KEY_RENEWAL_INTERVAL + 1).unwrap_err();
+        tracker.mark(KEY_RENEWAL_INTERVAL + 2).unwrap_err();
+        tracker.mark(2 * KEY_RENEWAL_INTERVAL).unwrap();
+        tracker.mark(KEY_RENEWAL_INTERVAL - 1).unwrap_err();
+        tracker.mark(3 * KEY_RENEWAL_INTERVAL).unwrap();
+        tracker.mark(4 * KEY_RENEWAL_INTERVAL).unwrap();
+        for n in 3 * KEY_RENEWAL_INTERVAL + 1..4 * KEY_RENEWAL_INTERVAL {
+            tracker.mark(n).unwrap();
+        }
+        for n in 4 * KEY_RENEWAL_INTERVAL + 1..5 * KEY_RENEWAL_INTERVAL + 1 {
+            tracker.mark(n).unwrap();
+        }
     }
-    for n in 4 * KEY_RENEWAL_INTERVAL + 1..5 * KEY_RENEWAL_INTERVAL + 1 {
-        tracker = tracker.mark(n).unwrap();
+
+    #[test]
+    pub fn check_nonce_tracker_with_large_window() {
+        let window = 1000;
+        let mut tracker = NonceTracker::new(window);
+        tracker.mark(window + 1).unwrap_err();
+        tracker.mark(window).unwrap();
+        for n in (0..window).rev() {
+            tracker.check(n).unwrap();
+            tracker.mark(n).unwrap();
+            tracker.mark(n).unwrap_err();
+        }
+        tracker.mark(2 * window + 1).unwrap_err();
+        tracker.mark(2 * window).unwrap();
+        // the nonces of the first interval are now outside of the window
+        tracker.mark(window - 1).unwrap_err();
+        tracker.mark(window).unwrap_err();
+        for n in window + 1..2 * window {
+            tracker.check(n).unwrap();
+        }
+        tracker.mark(window + 1).unwrap();
+        tracker.mark(window + 1).unwrap_err();
+        tracker.mark(2 * window).unwrap_err();
     }
 }
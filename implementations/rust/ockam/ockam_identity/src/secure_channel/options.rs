@@ -3,9 +3,12 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
 use ockam_core::{Address, OutgoingAccessControl, Result};
 
-use crate::models::CredentialAndPurposeKey;
-use crate::secure_channel::Addresses;
-use crate::{TrustContext, TrustEveryonePolicy, TrustPolicy};
+use crate::models::{CredentialAndPurposeKey, Identifier};
+use crate::secure_channel::{Addresses, CredentialsRefresh};
+use crate::{
+    CredentialsRetriever, HandshakeProtocol, RekeyPolicy, TrustContext, TrustEveryonePolicy,
+    TrustPolicy,
+};
 
 use core::fmt;
 use core::fmt::Formatter;
@@ -14,13 +17,24 @@ use core::time::Duration;
 /// This is the default timeout for creating a secure channel
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
 
+/// This is the default duration before the expiration of the credentials presented
+/// over a secure channel at which new credentials are sent to the other party
+pub const DEFAULT_CREDENTIALS_REFRESH_TIME_GAP: Duration = Duration::from_secs(120);
+
 /// Trust options for a Secure Channel
 pub struct SecureChannelOptions {
     pub(crate) flow_control_id: FlowControlId,
     pub(crate) trust_policy: Arc<dyn TrustPolicy>,
     pub(crate) trust_context: Option<TrustContext>,
     pub(crate) credentials: Vec<CredentialAndPurposeKey>,
+    pub(crate) credentials_retriever: Option<Arc<dyn CredentialsRetriever>>,
+    pub(crate) credentials_refresh_time_gap: Duration,
     pub(crate) timeout: Duration,
+    pub(crate) rekey_policy: RekeyPolicy,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) keepalive_interval: Option<Duration>,
+    pub(crate) handshake_protocol: HandshakeProtocol,
+    pub(crate) resumption: Option<Identifier>,
 }
 
 impl fmt::Debug for SecureChannelOptions {
@@ -42,7 +56,14 @@ impl SecureChannelOptions {
             trust_policy: Arc::new(TrustEveryonePolicy),
             trust_context: None,
             credentials: vec![],
+            credentials_retriever: None,
+            credentials_refresh_time_gap: DEFAULT_CREDENTIALS_REFRESH_TIME_GAP,
             timeout: DEFAULT_TIMEOUT,
+            rekey_policy: RekeyPolicy::default(),
+            idle_timeout: None,
+            keepalive_interval: None,
+            handshake_protocol: HandshakeProtocol::default(),
+            resumption: None,
         }
     }
 
@@ -64,6 +85,23 @@ impl SecureChannelOptions {
         self
     }
 
+    /// Set the retriever used to get new credentials before the presented ones expire.
+    /// If the credentials are taken from the trust context, its authority is used by default
+    pub fn with_credentials_retriever(
+        mut self,
+        credentials_retriever: Arc<dyn CredentialsRetriever>,
+    ) -> Self {
+        self.credentials_retriever = Some(credentials_retriever);
+        self
+    }
+
+    /// Send new credentials this duration before the presented ones expire,
+    /// instead of [`DEFAULT_CREDENTIALS_REFRESH_TIME_GAP`]
+    pub fn with_credentials_refresh_time_gap(mut self, time_gap: Duration) -> Self {
+        self.credentials_refresh_time_gap = time_gap;
+        self
+    }
+
     /// Sets trust context
     pub fn with_trust_context(mut self, trust_context: TrustContext) -> Self {
         self.trust_context = Some(trust_context);
@@ -76,6 +114,43 @@ impl SecureChannelOptions {
         self
     }
 
+    /// Set the policy used to renew the channel keys.
+    /// The strictest combination of this policy and the other party's one is used
+    pub fn with_rekey_policy(mut self, rekey_policy: RekeyPolicy) -> Self {
+        self.rekey_policy = rekey_policy;
+        self
+    }
+
+    /// Stop the channel when nothing was received from the other party for this duration.
+    /// The timeout is measured in seconds
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Send a keepalive to the other party when nothing else was sent during this interval,
+    /// so that the channel stays open if the other party has an idle timeout
+    pub fn with_keepalive_interval(mut self, keepalive_interval: Duration) -> Self {
+        self.keepalive_interval = Some(keepalive_interval);
+        self
+    }
+
+    /// Set the key exchange protocol used by the handshake, [`HandshakeProtocol::Classical`]
+    /// by default. The hybrid protocol requires a listener supporting it
+    pub fn with_handshake_protocol(mut self, handshake_protocol: HandshakeProtocol) -> Self {
+        self.handshake_protocol = handshake_protocol;
+        self
+    }
+
+    /// Resume the session previously established with `their_identifier` in one round trip,
+    /// if a valid resumption ticket was received from that identity. Otherwise, or if the
+    /// listener rejects the ticket, a full handshake is performed.
+    /// The tickets received on this channel are kept to resume the next sessions
+    pub fn with_session_resumption(mut self, their_identifier: &Identifier) -> Self {
+        self.resumption = Some(their_identifier.clone());
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn producer_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -83,6 +158,15 @@ impl SecureChannelOptions {
 }
 
 impl SecureChannelOptions {
+    pub(crate) fn credentials_refresh(&self) -> Option<CredentialsRefresh> {
+        credentials_refresh(
+            &self.credentials,
+            &self.credentials_retriever,
+            &self.trust_context,
+            self.credentials_refresh_time_gap,
+        )
+    }
+
     pub(crate) fn setup_flow_control(
         &self,
         flow_controls: &FlowControls,
@@ -130,6 +214,13 @@ pub struct SecureChannelListenerOptions {
     pub(crate) trust_policy: Arc<dyn TrustPolicy>,
     pub(crate) trust_context: Option<TrustContext>,
     pub(crate) credentials: Vec<CredentialAndPurposeKey>,
+    pub(crate) credentials_retriever: Option<Arc<dyn CredentialsRetriever>>,
+    pub(crate) credentials_refresh_time_gap: Duration,
+    pub(crate) rekey_policy: RekeyPolicy,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) keepalive_interval: Option<Duration>,
+    pub(crate) handshake_protocol: Option<HandshakeProtocol>,
+    pub(crate) resumption_ticket_lifetime: Option<Duration>,
 }
 
 impl fmt::Debug for SecureChannelListenerOptions {
@@ -150,6 +241,13 @@ impl SecureChannelListenerOptions {
             trust_policy: Arc::new(TrustEveryonePolicy),
             trust_context: None,
             credentials: vec![],
+            credentials_retriever: None,
+            credentials_refresh_time_gap: DEFAULT_CREDENTIALS_REFRESH_TIME_GAP,
+            rekey_policy: RekeyPolicy::default(),
+            idle_timeout: None,
+            keepalive_interval: None,
+            handshake_protocol: None,
+            resumption_ticket_lifetime: None,
         }
     }
 
@@ -174,6 +272,23 @@ impl SecureChannelListenerOptions {
         self
     }
 
+    /// Set the retriever used to get new credentials before the presented ones expire.
+    /// If the credentials are taken from the trust context, its authority is used by default
+    pub fn with_credentials_retriever(
+        mut self,
+        credentials_retriever: Arc<dyn CredentialsRetriever>,
+    ) -> Self {
+        self.credentials_retriever = Some(credentials_retriever);
+        self
+    }
+
+    /// Send new credentials this duration before the presented ones expire,
+    /// instead of [`DEFAULT_CREDENTIALS_REFRESH_TIME_GAP`]
+    pub fn with_credentials_refresh_time_gap(mut self, time_gap: Duration) -> Self {
+        self.credentials_refresh_time_gap = time_gap;
+        self
+    }
+
     /// Sets trust context
     pub fn with_trust_context(mut self, trust_context: TrustContext) -> Self {
         self.trust_context = Some(trust_context);
@@ -186,6 +301,43 @@ impl SecureChannelListenerOptions {
         self
     }
 
+    /// Set the policy used to renew the channel keys.
+    /// The strictest combination of this policy and the other party's one is used
+    pub fn with_rekey_policy(mut self, rekey_policy: RekeyPolicy) -> Self {
+        self.rekey_policy = rekey_policy;
+        self
+    }
+
+    /// Stop the channel when nothing was received from the other party for this duration.
+    /// The timeout is measured in seconds
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Send a keepalive to the other party when nothing else was sent during this interval,
+    /// so that the channel stays open if the other party has an idle timeout
+    pub fn with_keepalive_interval(mut self, keepalive_interval: Duration) -> Self {
+        self.keepalive_interval = Some(keepalive_interval);
+        self
+    }
+
+    /// Only accept handshakes using this key exchange protocol.
+    /// By default, both the classical and the hybrid protocols are accepted
+    pub fn with_handshake_protocol(mut self, handshake_protocol: HandshakeProtocol) -> Self {
+        self.handshake_protocol = Some(handshake_protocol);
+        self
+    }
+
+    /// Issue resumption tickets valid for this duration to the initiators accepting them.
+    /// A ticket lets an initiator establish a new channel with this listener in one round trip,
+    /// without verifying identities and credentials again. No tickets are issued by default,
+    /// nor when the listener requires the [`HandshakeProtocol::Hybrid`] protocol
+    pub fn with_resumption_ticket_lifetime(mut self, lifetime: Duration) -> Self {
+        self.resumption_ticket_lifetime = Some(lifetime);
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -193,6 +345,15 @@ impl SecureChannelListenerOptions {
 }
 
 impl SecureChannelListenerOptions {
+    pub(crate) fn credentials_refresh(&self) -> Option<CredentialsRefresh> {
+        credentials_refresh(
+            &self.credentials,
+            &self.credentials_retriever,
+            &self.trust_context,
+            self.credentials_refresh_time_gap,
+        )
+    }
+
     pub(crate) fn setup_flow_control_for_listener(
         &self,
         flow_controls: &FlowControls,
@@ -252,3 +413,25 @@ impl SecureChannelListenerOptions {
         }
     }
 }
+
+/// The credentials presented over a secure channel are refreshed with the configured retriever.
+/// When no credentials were provided, they are taken from the authority of the trust context
+/// which is then also used to refresh them
+fn credentials_refresh(
+    credentials: &[CredentialAndPurposeKey],
+    credentials_retriever: &Option<Arc<dyn CredentialsRetriever>>,
+    trust_context: &Option<TrustContext>,
+    time_gap: Duration,
+) -> Option<CredentialsRefresh> {
+    let retriever: Arc<dyn CredentialsRetriever> = match (credentials_retriever, trust_context) {
+        (Some(retriever), _) => retriever.clone(),
+        (None, Some(trust_context)) if credentials.is_empty() => {
+            Arc::new(trust_context.authority().ok()?.clone())
+        }
+        _ => return None,
+    };
+    Some(CredentialsRefresh {
+        retriever,
+        time_gap,
+    })
+}
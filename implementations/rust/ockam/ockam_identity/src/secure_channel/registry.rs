@@ -3,7 +3,8 @@ use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::compat::vec::Vec;
 use ockam_core::{Address, Result};
 
-use crate::models::Identifier;
+use crate::models::{Identifier, TimestampInSeconds};
+use crate::secure_channel::LastActivity;
 use crate::IdentityError;
 
 /// Known information about particular SecureChannel
@@ -17,6 +18,8 @@ pub struct SecureChannelRegistryEntry {
     my_id: Identifier,
     their_id: Identifier,
     their_decryptor_address: Address,
+    last_activity: LastActivity,
+    is_resumed: bool,
 }
 
 impl SecureChannelRegistryEntry {
@@ -41,9 +44,23 @@ impl SecureChannelRegistryEntry {
             my_id,
             their_id,
             their_decryptor_address,
+            last_activity: LastActivity::new(),
+            is_resumed: false,
         }
     }
 
+    /// Share the last activity time updated by the channel decryptor
+    pub(crate) fn with_last_activity(mut self, last_activity: LastActivity) -> Self {
+        self.last_activity = last_activity;
+        self
+    }
+
+    /// Mark the channel as resumed with a ticket
+    pub(crate) fn with_resumed(mut self, is_resumed: bool) -> Self {
+        self.is_resumed = is_resumed;
+        self
+    }
+
     /// Encryptor messaging address
     pub fn encryptor_messaging_address(&self) -> &Address {
         &self.encryptor_messaging_address
@@ -69,6 +86,11 @@ impl SecureChannelRegistryEntry {
         self.clone().is_initiator
     }
 
+    /// If this channel resumed a previous session with a ticket instead of a full handshake
+    pub fn is_resumed(&self) -> bool {
+        self.is_resumed
+    }
+
     /// Our `Identifier`
     pub fn my_id(&self) -> &Identifier {
         &self.my_id
@@ -83,6 +105,11 @@ impl SecureChannelRegistryEntry {
     pub fn their_decryptor_address(&self) -> Address {
         self.their_decryptor_address.clone()
     }
+
+    /// Last time a message was received from the other party
+    pub fn last_activity(&self) -> TimestampInSeconds {
+        self.last_activity.get()
+    }
 }
 
 /// Registry of all known Secure Channels
@@ -0,0 +1,134 @@
+use core::cmp::min;
+use core::time::Duration;
+
+use crate::secure_channel::encryptor::KEY_RENEWAL_INTERVAL;
+
+/// Maximum number of messages which can be encrypted with the same key.
+///
+/// The receiving side keeps a bitmap of the nonces received in the current and previous
+/// intervals, so this value also bounds the memory used by each secure channel
+pub const MAX_KEY_RENEWAL_INTERVAL: u64 = 1 << 16;
+
+/// Policy used to decide when the keys of a secure channel must be renewed.
+///
+/// A key is renewed after a given number of messages, and optionally once some time has
+/// elapsed since the last renewal, so that channels with little traffic still rotate keys.
+/// Both sides of a channel exchange their policy during the handshake and use the
+/// strictest combination of both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RekeyPolicy {
+    message_interval: u64,
+    time_interval: Option<Duration>,
+}
+
+impl Default for RekeyPolicy {
+    fn default() -> Self {
+        Self {
+            message_interval: KEY_RENEWAL_INTERVAL,
+            time_interval: None,
+        }
+    }
+}
+
+impl RekeyPolicy {
+    /// Renew keys every [`KEY_RENEWAL_INTERVAL`] messages
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renew keys after the given number of messages.
+    /// The value is capped between 1 and [`MAX_KEY_RENEWAL_INTERVAL`]
+    pub fn with_message_interval(mut self, message_interval: u64) -> Self {
+        self.message_interval = message_interval.clamp(1, MAX_KEY_RENEWAL_INTERVAL);
+        self
+    }
+
+    /// Also renew keys when a message is sent at least `time_interval` after the last renewal.
+    /// The interval is measured in seconds
+    pub fn with_time_interval(mut self, time_interval: Duration) -> Self {
+        self.time_interval = Some(time_interval);
+        self
+    }
+
+    /// Number of messages encrypted with the same key
+    pub fn message_interval(&self) -> u64 {
+        self.message_interval
+    }
+
+    /// Maximum time during which a key is used, if any
+    pub fn time_interval(&self) -> Option<Duration> {
+        self.time_interval
+    }
+
+    /// Return the strictest policy satisfying both this policy and the other party's one
+    pub(crate) fn negotiate(&self, other: &RekeyPolicy) -> RekeyPolicy {
+        let time_interval = match (self.time_interval, other.time_interval) {
+            (Some(t1), Some(t2)) => Some(min(t1, t2)),
+            (t1, t2) => t1.or(t2),
+        };
+        RekeyPolicy {
+            message_interval: min(self.message_interval, other.message_interval),
+            time_interval,
+        }
+    }
+
+    /// Create a policy from the values sent by the other party during the handshake.
+    /// Values which were not sent default to the ones of [`RekeyPolicy::default`]
+    pub(crate) fn from_handshake(
+        message_interval: Option<u64>,
+        time_interval_secs: Option<u64>,
+    ) -> RekeyPolicy {
+        RekeyPolicy {
+            message_interval: message_interval
+                .unwrap_or(KEY_RENEWAL_INTERVAL)
+                .clamp(1, MAX_KEY_RENEWAL_INTERVAL),
+            time_interval: time_interval_secs.map(Duration::from_secs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_interval_is_bounded() {
+        assert_eq!(
+            RekeyPolicy::new()
+                .with_message_interval(0)
+                .message_interval(),
+            1
+        );
+        assert_eq!(
+            RekeyPolicy::new()
+                .with_message_interval(u64::MAX)
+                .message_interval(),
+            MAX_KEY_RENEWAL_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_negotiate_uses_strictest_values() {
+        let p1 = RekeyPolicy::new()
+            .with_message_interval(1000)
+            .with_time_interval(Duration::from_secs(60));
+        let p2 = RekeyPolicy::new().with_message_interval(100);
+        let p3 = RekeyPolicy::new()
+            .with_message_interval(10_000)
+            .with_time_interval(Duration::from_secs(30));
+
+        let negotiated = p1.negotiate(&p2);
+        assert_eq!(negotiated.message_interval(), 100);
+        assert_eq!(negotiated.time_interval(), Some(Duration::from_secs(60)));
+        assert_eq!(negotiated, p2.negotiate(&p1));
+
+        let negotiated = p1.negotiate(&p3);
+        assert_eq!(negotiated.message_interval(), 1000);
+        assert_eq!(negotiated.time_interval(), Some(Duration::from_secs(30)));
+
+        assert_eq!(
+            RekeyPolicy::from_handshake(None, None).negotiate(&RekeyPolicy::default()),
+            RekeyPolicy::default()
+        );
+    }
+}
@@ -0,0 +1,458 @@
+use core::time::Duration;
+use minicbor::{Decode, Encode};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_vault::{AeadSecretKeyHandle, VaultForSecureChannels};
+
+use crate::models::{Identifier, TimestampInSeconds};
+use crate::secure_channel::message::NewResumptionTicket;
+use crate::utils::now;
+use crate::{IdentityError, RekeyPolicy};
+
+/// Length of the secret shared by both parties to resume a session
+pub(crate) const RESUMPTION_SECRET_LENGTH: usize = 32;
+
+/// Length of the random nonce used to encrypt a ticket
+const TICKET_NONCE_LENGTH: usize = 12;
+
+/// Maximum length of a ticket, to keep session resumption messages small
+const MAX_TICKET_LENGTH: usize = 1024;
+
+/// Ticket received by the initiator of a secure channel at the end of a full handshake.
+/// It can be presented to the same listener to establish a new channel in one round trip,
+/// without verifying the identities and credentials again, until the ticket expires
+#[derive(Clone)]
+pub struct ResumptionTicket {
+    pub(crate) their_identifier: Identifier,
+    pub(crate) ticket: Vec<u8>,
+    pub(crate) secret: [u8; RESUMPTION_SECRET_LENGTH],
+    pub(crate) expires_at: TimestampInSeconds,
+    pub(crate) rekey_policy: RekeyPolicy,
+    pub(crate) their_credentials_expiration: Option<TimestampInSeconds>,
+    pub(crate) keepalive: bool,
+}
+
+impl ResumptionTicket {
+    /// Identifier of the responder which issued the ticket
+    pub fn their_identifier(&self) -> &Identifier {
+        &self.their_identifier
+    }
+
+    /// Expiration of the ticket
+    pub fn expires_at(&self) -> TimestampInSeconds {
+        self.expires_at
+    }
+
+    /// A ticket can't be used once it expired or once the credentials of the responder expired
+    fn is_valid(&self) -> bool {
+        let now = match now() {
+            Ok(now) => now,
+            Err(_) => return false,
+        };
+        self.expires_at > now && self.their_credentials_expiration.map_or(true, |e| e > now)
+    }
+}
+
+/// Resumption tickets received by the initiators of secure channels,
+/// for each pair of initiator and responder identifiers
+#[derive(Clone, Default)]
+pub struct ResumptionTickets {
+    tickets: Arc<RwLock<BTreeMap<(Identifier, Identifier), ResumptionTicket>>>,
+}
+
+impl ResumptionTickets {
+    /// Return a valid ticket which can be used by `identifier` to resume a session
+    /// with `their_identifier`
+    pub fn get(
+        &self,
+        identifier: &Identifier,
+        their_identifier: &Identifier,
+    ) -> Option<ResumptionTicket> {
+        self.tickets
+            .read()
+            .unwrap()
+            .get(&(identifier.clone(), their_identifier.clone()))
+            .filter(|ticket| ticket.is_valid())
+            .cloned()
+    }
+
+    /// Remove the ticket used by `identifier` to resume a session with `their_identifier`
+    pub fn remove(&self, identifier: &Identifier, their_identifier: &Identifier) {
+        self.tickets
+            .write()
+            .unwrap()
+            .remove(&(identifier.clone(), their_identifier.clone()));
+    }
+
+    /// Remove and return a valid ticket. A ticket is only used once,
+    /// a new one is issued over the resumed channel
+    pub(crate) fn take(
+        &self,
+        identifier: &Identifier,
+        their_identifier: &Identifier,
+    ) -> Option<ResumptionTicket> {
+        self.tickets
+            .write()
+            .unwrap()
+            .remove(&(identifier.clone(), their_identifier.clone()))
+            .filter(|ticket| ticket.is_valid())
+    }
+
+    /// Store a ticket, replacing the previous one for the same identifiers
+    pub(crate) fn insert(&self, identifier: Identifier, ticket: ResumptionTicket) {
+        self.tickets
+            .write()
+            .unwrap()
+            .insert((identifier, ticket.their_identifier.clone()), ticket);
+    }
+}
+
+/// Store the resumption tickets received by the initiator of a secure channel
+pub(crate) struct ResumptionTicketReceiver {
+    tickets: ResumptionTickets,
+    identifier: Identifier,
+}
+
+impl ResumptionTicketReceiver {
+    pub(crate) fn new(tickets: ResumptionTickets, identifier: Identifier) -> Self {
+        Self {
+            tickets,
+            identifier,
+        }
+    }
+
+    /// Store a ticket received from `their_identifier`, whose credentials expire
+    /// at `their_credentials_expiration`
+    pub(crate) fn receive(
+        &self,
+        their_identifier: &Identifier,
+        ticket: NewResumptionTicket,
+        their_credentials_expiration: Option<TimestampInSeconds>,
+    ) {
+        self.tickets.insert(
+            self.identifier.clone(),
+            ResumptionTicket {
+                their_identifier: their_identifier.clone(),
+                ticket: ticket.ticket,
+                secret: ticket.secret,
+                expires_at: ticket.expires_at,
+                rekey_policy: RekeyPolicy::from_handshake(
+                    Some(ticket.rekey_message_interval),
+                    ticket.rekey_time_interval,
+                ),
+                their_credentials_expiration,
+                keepalive: ticket.keepalive,
+            },
+        )
+    }
+}
+
+/// Content of a resumption ticket, only readable by the listener which issued it
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct ResumptionTicketContent {
+    /// Identifier of the initiator which received the ticket
+    #[n(1)] pub(crate) initiator: Identifier,
+    /// Identifier of the responder which issued the ticket
+    #[n(2)] pub(crate) responder: Identifier,
+    /// Secret shared with the initiator, used as a pre-shared key to resume the session
+    #[cbor(n(3), with = "minicbor::bytes")] pub(crate) secret: [u8; RESUMPTION_SECRET_LENGTH],
+    /// Expiration of the ticket
+    #[n(4)] pub(crate) expires_at: TimestampInSeconds,
+    /// Number of messages after which keys are renewed
+    #[n(5)] pub(crate) rekey_message_interval: u64,
+    /// Number of seconds after which keys are renewed, if any
+    #[n(6)] pub(crate) rekey_time_interval: Option<u64>,
+    /// Expiration of the credentials presented by the initiator, if any
+    #[n(7)] pub(crate) initiator_credentials_expiration: Option<TimestampInSeconds>,
+    /// True if keepalives are accepted by the initiator
+    #[n(8)] pub(crate) keepalive: bool,
+}
+
+impl ResumptionTicketContent {
+    /// Rekey policy negotiated during the full handshake
+    pub(crate) fn rekey_policy(&self) -> RekeyPolicy {
+        RekeyPolicy::from_handshake(Some(self.rekey_message_interval), self.rekey_time_interval)
+    }
+}
+
+/// Issue and open the resumption tickets of a secure channel listener.
+/// Tickets are encrypted with a key which only exists in the memory of the listener,
+/// so they can't be used anymore once the listener is stopped
+pub(crate) struct ResumptionTicketIssuer {
+    vault: Arc<dyn VaultForSecureChannels>,
+    key: AeadSecretKeyHandle,
+    lifetime: Duration,
+    /// Nonces of the tickets already used to resume a session, kept until the tickets expire
+    used_tickets: RwLock<BTreeMap<[u8; TICKET_NONCE_LENGTH], TimestampInSeconds>>,
+}
+
+impl ResumptionTicketIssuer {
+    /// Create an issuer of tickets valid for `lifetime`
+    pub(crate) async fn create(
+        vault: Arc<dyn VaultForSecureChannels>,
+        lifetime: Duration,
+    ) -> Result<Self> {
+        let mut key = [0u8; 32];
+        thread_rng().fill_bytes(&mut key);
+        let key = vault.import_secret_buffer(key.to_vec()).await?;
+        let key = vault.convert_secret_buffer_to_aead_key(key).await?;
+        Ok(Self {
+            vault,
+            key,
+            lifetime,
+            used_tickets: Default::default(),
+        })
+    }
+
+    /// Issue a ticket to resume the session of a secure channel. A ticket issued after a full
+    /// handshake expires after the ticket lifetime, while a ticket issued over a resumed channel
+    /// keeps the expiration of the ticket used to resume it
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn issue(
+        &self,
+        initiator: Identifier,
+        responder: Identifier,
+        rekey_policy: RekeyPolicy,
+        initiator_credentials_expiration: Option<TimestampInSeconds>,
+        keepalive: bool,
+        expires_at: Option<TimestampInSeconds>,
+    ) -> Result<NewResumptionTicket> {
+        let expires_at = match expires_at {
+            Some(expires_at) => expires_at,
+            None => TimestampInSeconds(now()?.0 + self.lifetime.as_secs()),
+        };
+
+        let mut secret = [0u8; RESUMPTION_SECRET_LENGTH];
+        thread_rng().fill_bytes(&mut secret);
+
+        let content = ResumptionTicketContent {
+            initiator,
+            responder,
+            secret,
+            expires_at,
+            rekey_message_interval: rekey_policy.message_interval(),
+            rekey_time_interval: rekey_policy.time_interval().map(|t| t.as_secs()),
+            initiator_credentials_expiration,
+            keepalive,
+        };
+
+        let mut nonce = [0u8; TICKET_NONCE_LENGTH];
+        thread_rng().fill_bytes(&mut nonce);
+        let mut ticket = nonce.to_vec();
+        ticket.extend(
+            self.vault
+                .aead_encrypt(&self.key, &minicbor::to_vec(&content)?, &nonce, &[])
+                .await?,
+        );
+        if ticket.len() > MAX_TICKET_LENGTH {
+            return Err(IdentityError::ResumptionTicketRejected.into());
+        }
+
+        Ok(NewResumptionTicket {
+            ticket,
+            secret,
+            expires_at,
+            rekey_message_interval: content.rekey_message_interval,
+            rekey_time_interval: content.rekey_time_interval,
+            keepalive,
+        })
+    }
+
+    /// Decrypt a ticket presented to the `responder`. The ticket is rejected if it was not
+    /// issued by this listener for that responder, or if it expired, or if the credentials
+    /// of the initiator expired, or if it was already used, so that a captured session
+    /// resumption message can't be replayed
+    pub(crate) async fn open(
+        &self,
+        ticket: &[u8],
+        responder: &Identifier,
+    ) -> Result<ResumptionTicketContent> {
+        if ticket.len() <= TICKET_NONCE_LENGTH {
+            return Err(IdentityError::ResumptionTicketRejected.into());
+        }
+        let (nonce, ciphertext) = ticket.split_at(TICKET_NONCE_LENGTH);
+        let content = self
+            .vault
+            .aead_decrypt(&self.key, ciphertext, nonce, &[])
+            .await
+            .map_err(|_| IdentityError::ResumptionTicketRejected)?;
+        let content: ResumptionTicketContent = minicbor::decode(&content)?;
+
+        let now = now()?;
+        if &content.responder != responder
+            || content.expires_at <= now
+            || content
+                .initiator_credentials_expiration
+                .map_or(false, |e| e <= now)
+        {
+            return Err(IdentityError::ResumptionTicketRejected.into());
+        }
+
+        let nonce: [u8; TICKET_NONCE_LENGTH] = nonce
+            .try_into()
+            .map_err(|_| IdentityError::ResumptionTicketRejected)?;
+        let mut used_tickets = self.used_tickets.write().unwrap();
+        used_tickets.retain(|_, expires_at| *expires_at > now);
+        if used_tickets.insert(nonce, content.expires_at).is_some() {
+            return Err(IdentityError::ResumptionTicketRejected.into());
+        }
+
+        Ok(content)
+    }
+
+    /// Remove the ticket key when the listener is stopped
+    pub(crate) async fn shutdown(&self) -> Result<()> {
+        self.vault.delete_aead_secret_key(self.key.clone()).await?;
+        Ok(())
+    }
+}
+
+/// Resumption of secure channel sessions, on the initiator or on the responder side
+#[derive(Clone)]
+pub(crate) enum SessionResumption {
+    /// The initiator presents a ticket if it has a valid one,
+    /// and stores the tickets it receives to resume its next sessions
+    Initiator {
+        ticket: Option<ResumptionTicket>,
+        tickets: ResumptionTickets,
+    },
+    /// The responder accepts the tickets it issued and issues new ones
+    Responder(Arc<ResumptionTicketIssuer>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_vault::SoftwareVaultForSecureChannels;
+
+    #[tokio::test]
+    async fn test_open_issued_ticket() -> Result<()> {
+        let issuer = ResumptionTicketIssuer::create(
+            SoftwareVaultForSecureChannels::create(),
+            Duration::from_secs(60),
+        )
+        .await?;
+        let (initiator, responder) = identifiers();
+
+        let ticket = issuer
+            .issue(
+                initiator.clone(),
+                responder.clone(),
+                RekeyPolicy::default(),
+                None,
+                true,
+                None,
+            )
+            .await?;
+        let content = issuer.open(&ticket.ticket, &responder).await?;
+        assert_eq!(content.initiator, initiator);
+        assert_eq!(content.secret, ticket.secret);
+        assert_eq!(content.expires_at, ticket.expires_at);
+        assert!(content.keepalive);
+
+        // a ticket can only be presented to the responder which issued it
+        assert!(issuer.open(&ticket.ticket, &initiator).await.is_err());
+
+        // a modified ticket is rejected
+        let mut modified = ticket.ticket.clone();
+        modified[TICKET_NONCE_LENGTH] ^= 1;
+        assert!(issuer.open(&modified, &responder).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_used_ticket() -> Result<()> {
+        let issuer = ResumptionTicketIssuer::create(
+            SoftwareVaultForSecureChannels::create(),
+            Duration::from_secs(60),
+        )
+        .await?;
+        let (initiator, responder) = identifiers();
+
+        let ticket = issuer
+            .issue(
+                initiator,
+                responder.clone(),
+                RekeyPolicy::default(),
+                None,
+                true,
+                None,
+            )
+            .await?;
+        assert!(issuer.open(&ticket.ticket, &responder).await.is_ok());
+
+        // a ticket can't be replayed
+        assert!(issuer.open(&ticket.ticket, &responder).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_expired_ticket() -> Result<()> {
+        let issuer = ResumptionTicketIssuer::create(
+            SoftwareVaultForSecureChannels::create(),
+            Duration::from_secs(60),
+        )
+        .await?;
+        let (initiator, responder) = identifiers();
+
+        let expired = TimestampInSeconds(now()?.0 - 1);
+        let ticket = issuer
+            .issue(
+                initiator.clone(),
+                responder.clone(),
+                RekeyPolicy::default(),
+                None,
+                true,
+                Some(expired),
+            )
+            .await?;
+        assert!(issuer.open(&ticket.ticket, &responder).await.is_err());
+
+        let ticket = issuer
+            .issue(
+                initiator,
+                responder.clone(),
+                RekeyPolicy::default(),
+                Some(expired),
+                true,
+                None,
+            )
+            .await?;
+        assert!(issuer.open(&ticket.ticket, &responder).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_ticket_from_other_listener() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create();
+        let issuer1 =
+            ResumptionTicketIssuer::create(vault.clone(), Duration::from_secs(60)).await?;
+        let issuer2 = ResumptionTicketIssuer::create(vault, Duration::from_secs(60)).await?;
+        let (initiator, responder) = identifiers();
+
+        let ticket = issuer1
+            .issue(
+                initiator,
+                responder.clone(),
+                RekeyPolicy::default(),
+                None,
+                true,
+                None,
+            )
+            .await?;
+        assert!(issuer2.open(&ticket.ticket, &responder).await.is_err());
+        Ok(())
+    }
+
+    fn identifiers() -> (Identifier, Identifier) {
+        (
+            Identifier::try_from("I0000000000000000000000000000000000000000").unwrap(),
+            Identifier::try_from("I1111111111111111111111111111111111111111").unwrap(),
+        )
+    }
+}
@@ -7,8 +7,8 @@ use crate::identities::Identities;
 use crate::models::Identifier;
 use crate::secure_channel::handshake_worker::HandshakeWorker;
 use crate::secure_channel::{
-    Addresses, IdentityChannelListener, Role, SecureChannelListenerOptions, SecureChannelOptions,
-    SecureChannelRegistry,
+    Addresses, IdentityChannelListener, ResumptionTickets, Role, SecureChannelListenerOptions,
+    SecureChannelOptions, SecureChannelRegistry, SessionResumption,
 };
 use crate::{SecureChannel, SecureChannelListener, SecureChannelsBuilder, Vault};
 
@@ -17,6 +17,7 @@ use crate::{SecureChannel, SecureChannelListener, SecureChannelsBuilder, Vault};
 pub struct SecureChannels {
     pub(crate) identities: Arc<Identities>,
     pub(crate) secure_channel_registry: SecureChannelRegistry,
+    pub(crate) resumption_tickets: ResumptionTickets,
 }
 
 impl SecureChannels {
@@ -28,6 +29,7 @@ impl SecureChannels {
         Self {
             identities,
             secure_channel_registry,
+            resumption_tickets: ResumptionTickets::default(),
         }
     }
 
@@ -46,6 +48,11 @@ impl SecureChannels {
         self.secure_channel_registry.clone()
     }
 
+    /// Return the resumption tickets received by the initiators of secure channels
+    pub fn resumption_tickets(&self) -> ResumptionTickets {
+        self.resumption_tickets.clone()
+    }
+
     /// Create a builder for secure channels
     pub fn builder() -> SecureChannelsBuilder {
         SecureChannelsBuilder {
@@ -96,6 +103,7 @@ impl SecureChannels {
         let next = route.next()?;
         options.setup_flow_control(ctx.flow_controls(), &addresses, next)?;
         let access_control = options.create_access_control(ctx.flow_controls());
+        let credentials_refresh = options.credentials_refresh();
 
         // TODO: Allow manual PurposeKey management
         let purpose_key = self
@@ -105,6 +113,16 @@ impl SecureChannels {
             .get_or_create_secure_channel_purpose_key(identifier)
             .await?;
 
+        // a ticket is used at most once, a new one is received on the resumed channel
+        let resumption =
+            options
+                .resumption
+                .as_ref()
+                .map(|their_identifier| SessionResumption::Initiator {
+                    ticket: self.resumption_tickets.take(identifier, their_identifier),
+                    tickets: self.resumption_tickets.clone(),
+                });
+
         HandshakeWorker::create(
             ctx,
             Arc::new(self.clone()),
@@ -114,7 +132,13 @@ impl SecureChannels {
             options.trust_policy,
             access_control.decryptor_outgoing_access_control,
             options.credentials,
+            credentials_refresh,
             options.trust_context,
+            options.rekey_policy,
+            options.idle_timeout,
+            options.keepalive_interval,
+            Some(options.handshake_protocol),
+            resumption,
             Some(route),
             Some(options.timeout),
             Role::Initiator,
@@ -85,6 +85,18 @@ impl Vault {
         Ok(Self::create_with_persistent_storage(storage))
     }
 
+    /// Create Software Vaults with [`PersistentStorage`] with a given path, the storage file
+    /// being encrypted with a key derived from the given passphrase
+    #[cfg(feature = "std")]
+    pub async fn create_with_encrypted_storage_path(
+        path: &std::path::Path,
+        passphrase: &[u8],
+    ) -> ockam_core::Result<Vault> {
+        let storage =
+            ockam_vault::storage::PersistentStorage::create_encrypted(path, passphrase).await?;
+        Ok(Self::create_with_persistent_storage(storage))
+    }
+
     /// Create Software Vaults with a given [`VaultStorage`]r
     pub fn create_with_persistent_storage(storage: VaultStorage) -> Vault {
         Self::new(
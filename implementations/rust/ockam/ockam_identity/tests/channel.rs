@@ -1,12 +1,13 @@
 use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{route, Address, AllowAll, Any, DenyAll, Mailboxes, Result, Routed, Worker};
-use ockam_identity::models::{CredentialSchemaIdentifier, Identifier};
+use ockam_identity::models::{CredentialAndPurposeKey, CredentialSchemaIdentifier, Identifier};
 use ockam_identity::secure_channels::secure_channels;
-use ockam_identity::utils::AttributesBuilder;
+use ockam_identity::utils::{now, AttributesBuilder};
 use ockam_identity::{
-    AuthorityService, DecryptionResponse, EncryptionRequest, EncryptionResponse,
-    IdentityAccessControlBuilder, IdentitySecureChannelLocalInfo, SecureChannelListenerOptions,
+    AuthorityService, CredentialsMemoryRetriever, DecryptionResponse, EncryptionRequest,
+    EncryptionResponse, HandshakeProtocol, IdentityAccessControlBuilder,
+    IdentitySecureChannelLocalInfo, RekeyPolicy, SecureChannelListenerOptions,
     SecureChannelOptions, SecureChannels, TrustContext, TrustEveryonePolicy, TrustIdentifierPolicy,
     Vault,
 };
@@ -226,6 +227,478 @@ async fn test_channel_send_credentials(context: &mut Context) -> Result<()> {
     context.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_refresh_credentials(context: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let authority = identities_creation.create_identity().await?;
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let trust_context = TrustContext::new(
+        "test".to_string(),
+        Some(AuthorityService::new(
+            secure_channels.identities().credentials(),
+            authority.identifier().clone(),
+            None,
+        )),
+    );
+
+    secure_channels
+        .create_secure_channel_listener(
+            context,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new().with_trust_context(trust_context.clone()),
+        )
+        .await?;
+
+    // the first credential expires before the default refresh time gap,
+    // so the second one is sent as soon as the channel is established
+    let alice_credential_1 = issue_credential(
+        &secure_channels,
+        authority.identifier(),
+        alice.identifier(),
+        "alice_1",
+        Duration::from_secs(2),
+    )
+    .await?;
+    let alice_credential_2 = issue_credential(
+        &secure_channels,
+        authority.identifier(),
+        alice.identifier(),
+        "alice_2",
+        Duration::from_secs(60),
+    )
+    .await?;
+
+    secure_channels
+        .create_secure_channel(
+            context,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new()
+                .with_trust_context(trust_context)
+                .with_credential(alice_credential_1)
+                .with_credentials_retriever(Arc::new(CredentialsMemoryRetriever::new(
+                    alice_credential_2,
+                ))),
+        )
+        .await?;
+
+    context.sleep(Duration::from_millis(250)).await;
+
+    let alice_attributes = secure_channels
+        .identities()
+        .repository()
+        .get_attributes(alice.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(
+        "true".as_bytes(),
+        alice_attributes.attrs().get("alice_2".as_bytes()).unwrap()
+    );
+
+    // the channel is kept open after the expiration of the first credential
+    context.sleep(Duration::from_secs(3)).await;
+    assert_eq!(
+        secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+            .len(),
+        2
+    );
+
+    context.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_stopped_when_credentials_expire(context: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let authority = identities_creation.create_identity().await?;
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let trust_context = TrustContext::new(
+        "test".to_string(),
+        Some(AuthorityService::new(
+            secure_channels.identities().credentials(),
+            authority.identifier().clone(),
+            None,
+        )),
+    );
+
+    secure_channels
+        .create_secure_channel_listener(
+            context,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new().with_trust_context(trust_context.clone()),
+        )
+        .await?;
+
+    // alice has no way to retrieve a new credential
+    let alice_credential = issue_credential(
+        &secure_channels,
+        authority.identifier(),
+        alice.identifier(),
+        "alice",
+        Duration::from_secs(2),
+    )
+    .await?;
+
+    secure_channels
+        .create_secure_channel(
+            context,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new()
+                .with_trust_context(trust_context)
+                .with_credential(alice_credential),
+        )
+        .await?;
+
+    context.sleep(Duration::from_millis(250)).await;
+    assert_eq!(
+        secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+            .len(),
+        2
+    );
+
+    // bob stops its side of the channel once the credential of alice expired
+    context.sleep(Duration::from_secs(4)).await;
+    let channels = secure_channels.secure_channel_registry().get_channel_list();
+    assert_eq!(channels.len(), 1);
+    assert!(channels[0].is_initiator());
+
+    context.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_stopped_when_idle(context: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            context,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new().with_idle_timeout(Duration::from_secs(2)),
+        )
+        .await?;
+
+    secure_channels
+        .create_secure_channel(
+            context,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+
+    context.sleep(Duration::from_millis(250)).await;
+    assert_eq!(
+        secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+            .len(),
+        2
+    );
+
+    // bob stops its side of the channel since alice didn't send anything
+    context.sleep(Duration::from_secs(3)).await;
+    let channels = secure_channels.secure_channel_registry().get_channel_list();
+    assert_eq!(channels.len(), 1);
+    assert!(channels[0].is_initiator());
+
+    context.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_kept_open_by_keepalives(context: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            context,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new().with_idle_timeout(Duration::from_secs(2)),
+        )
+        .await?;
+
+    secure_channels
+        .create_secure_channel(
+            context,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_keepalive_interval(Duration::from_secs(1)),
+        )
+        .await?;
+
+    context.sleep(Duration::from_secs(5)).await;
+    let channels = secure_channels.secure_channel_registry().get_channel_list();
+    assert_eq!(channels.len(), 2);
+
+    // the keepalives of alice are recorded as activity on the side of bob
+    let bob_channel = channels
+        .iter()
+        .find(|channel| !channel.is_initiator())
+        .unwrap();
+    assert!(now()?.0 - bob_channel.last_activity().0 <= 2);
+
+    context.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_session_resumption(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new()
+                .with_resumption_ticket_lifetime(Duration::from_secs(60)),
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", bob_listener.flow_control_id());
+
+    // the first channel is established with a full handshake and a ticket is received
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_session_resumption(bob.identifier()),
+        )
+        .await?;
+    ctx.sleep(Duration::from_millis(250)).await;
+
+    let registry = secure_channels.secure_channel_registry();
+    let entry = registry
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .unwrap();
+    assert!(!entry.is_resumed());
+    assert!(secure_channels
+        .resumption_tickets()
+        .get(alice.identifier(), bob.identifier())
+        .is_some());
+
+    // the second channel resumes the session with that ticket
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_session_resumption(bob.identifier()),
+        )
+        .await?;
+    let entry = registry
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .unwrap();
+    assert!(entry.is_resumed());
+
+    child_ctx
+        .send(
+            route![alice_channel, child_ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+    let msg = child_ctx.receive::<String>().await?;
+    let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+    assert_eq!(&local_info.their_identity_id(), alice.identifier());
+    assert_eq!("Hello, Bob!", msg.body());
+
+    ctx.flow_controls()
+        .add_consumer("child", alice_channel.flow_control_id());
+    child_ctx
+        .send(msg.return_route(), "Hello, Alice!".to_string())
+        .await?;
+    let msg = child_ctx.receive::<String>().await?;
+    let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+    assert_eq!(&local_info.their_identity_id(), bob.identifier());
+    assert_eq!("Hello, Alice!", msg.body());
+
+    // a new ticket is received on the resumed channel
+    ctx.sleep(Duration::from_millis(250)).await;
+    assert!(secure_channels
+        .resumption_tickets()
+        .get(alice.identifier(), bob.identifier())
+        .is_some());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_rejected_resumption_ticket(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new()
+                .with_resumption_ticket_lifetime(Duration::from_secs(60)),
+        )
+        .await?;
+    // this listener did not issue the ticket and cannot open it
+    let bob_other_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_other_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", bob_other_listener.flow_control_id());
+
+    secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_session_resumption(bob.identifier()),
+        )
+        .await?;
+    ctx.sleep(Duration::from_millis(250)).await;
+    assert!(secure_channels
+        .resumption_tickets()
+        .get(alice.identifier(), bob.identifier())
+        .is_some());
+
+    // the ticket is rejected and a full handshake is performed instead
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_other_listener"],
+            SecureChannelOptions::new().with_session_resumption(bob.identifier()),
+        )
+        .await?;
+    let entry = secure_channels
+        .secure_channel_registry()
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .unwrap();
+    assert!(!entry.is_resumed());
+
+    child_ctx
+        .send(
+            route![alice_channel, child_ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+    let msg = child_ctx.receive::<String>().await?;
+    let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+    assert_eq!(&local_info.their_identity_id(), alice.identifier());
+    assert_eq!("Hello, Bob!", msg.body());
+
+    ctx.stop().await
+}
+
+async fn issue_credential(
+    secure_channels: &SecureChannels,
+    authority: &Identifier,
+    subject: &Identifier,
+    attribute: &str,
+    ttl: Duration,
+) -> Result<CredentialAndPurposeKey> {
+    secure_channels
+        .identities()
+        .credentials()
+        .credentials_creation()
+        .issue_credential(
+            authority,
+            subject,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute(attribute, "true")
+                .build(),
+            ttl,
+        )
+        .await
+}
+
+#[ockam_macros::test]
+async fn test_channel_no_resumption_with_hybrid_listener(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new()
+                .with_handshake_protocol(HandshakeProtocol::Hybrid)
+                .with_resumption_ticket_lifetime(Duration::from_secs(60)),
+        )
+        .await?;
+
+    // a listener requiring the hybrid protocol doesn't issue resumption tickets
+    secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new()
+                .with_handshake_protocol(HandshakeProtocol::Hybrid)
+                .with_session_resumption(bob.identifier()),
+        )
+        .await?;
+    ctx.sleep(Duration::from_millis(250)).await;
+
+    assert!(secure_channels
+        .resumption_tickets()
+        .get(alice.identifier(), bob.identifier())
+        .is_none());
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_channel_rejected_trust_policy(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels();
@@ -282,6 +755,134 @@ async fn test_channel_rejected_trust_policy(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_hybrid_handshake(ctx: &mut Context) -> Result<()> {
+    let alice_sc_vault = SoftwareVaultForSecureChannels::create();
+    let mut alice_vault = Vault::create();
+    alice_vault.secure_channel_vault = alice_sc_vault.clone();
+    let bob_sc_vault = SoftwareVaultForSecureChannels::create();
+    let mut bob_vault = Vault::create();
+    bob_vault.secure_channel_vault = bob_sc_vault.clone();
+
+    let secure_channels_alice = SecureChannels::builder().with_vault(alice_vault).build();
+    let secure_channels_bob = SecureChannels::builder().with_vault(bob_vault).build();
+
+    let alice = secure_channels_alice
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let bob = secure_channels_bob
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+
+    // a listener accepting both protocols and a listener only accepting the hybrid protocol
+    let bob_listener = secure_channels_bob
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+    let bob_hybrid_listener = secure_channels_bob
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_hybrid_listener",
+            SecureChannelListenerOptions::new().with_handshake_protocol(HandshakeProtocol::Hybrid),
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", bob_listener.flow_control_id());
+    ctx.flow_controls()
+        .add_consumer("child", bob_hybrid_listener.flow_control_id());
+
+    for listener in ["bob_listener", "bob_hybrid_listener"] {
+        let alice_channel = secure_channels_alice
+            .create_secure_channel(
+                ctx,
+                alice.identifier(),
+                route![listener],
+                SecureChannelOptions::new().with_handshake_protocol(HandshakeProtocol::Hybrid),
+            )
+            .await?;
+
+        child_ctx
+            .send(
+                route![alice_channel, child_ctx.address()],
+                "Hello, Bob!".to_string(),
+            )
+            .await?;
+        let msg = child_ctx.receive::<String>().await?;
+        let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+        assert_eq!(&local_info.their_identity_id(), alice.identifier());
+        assert_eq!("Hello, Bob!", msg.body());
+    }
+
+    // the ephemeral ML-KEM keys are deleted once the handshakes are done
+    assert_eq!(alice_sc_vault.number_of_ephemeral_ml_kem_secrets(), 0);
+    assert_eq!(bob_sc_vault.number_of_ephemeral_ml_kem_secrets(), 0);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_rejected_handshake_protocol(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_hybrid_listener",
+            SecureChannelListenerOptions::new().with_handshake_protocol(HandshakeProtocol::Hybrid),
+        )
+        .await?;
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_classical_listener",
+            SecureChannelListenerOptions::new()
+                .with_handshake_protocol(HandshakeProtocol::Classical),
+        )
+        .await?;
+
+    for (listener, protocol) in [
+        ("bob_hybrid_listener", HandshakeProtocol::Classical),
+        ("bob_classical_listener", HandshakeProtocol::Hybrid),
+    ] {
+        let result = secure_channels
+            .create_secure_channel(
+                ctx,
+                alice.identifier(),
+                route![listener],
+                SecureChannelOptions::new()
+                    .with_handshake_protocol(protocol)
+                    .with_timeout(Duration::from_millis(500)),
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_channel_send_multiple_messages_both_directions(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels();
@@ -348,6 +949,76 @@ async fn test_channel_send_multiple_messages_both_directions(ctx: &mut Context)
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_negotiated_rekey_policy(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    // bob renews keys more often, alice renews keys on every message by using
+    // a 0 time interval. Both sides must use the strictest values
+    let bob_options = SecureChannelListenerOptions::new()
+        .with_rekey_policy(RekeyPolicy::new().with_message_interval(10));
+    let sc_listener_flow_control_id = bob_options.spawner_flow_control_id();
+    secure_channels
+        .create_secure_channel_listener(ctx, bob.identifier(), "bob_listener", bob_options)
+        .await?;
+
+    let alice_options = SecureChannelOptions::new().with_rekey_policy(
+        RekeyPolicy::new()
+            .with_message_interval(1000)
+            .with_time_interval(Duration::from_secs(0)),
+    );
+    let sc_flow_control_id = alice_options.producer_flow_control_id();
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            alice_options,
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+
+    for n in 0..50 {
+        child_ctx
+            .flow_controls()
+            .add_consumer(child_ctx.address(), &sc_listener_flow_control_id);
+        let payload = format!("Hello, Bob! {}", n);
+        child_ctx
+            .send(
+                route![alice_channel.clone(), child_ctx.address()],
+                payload.clone(),
+            )
+            .await?;
+
+        let message = child_ctx.receive::<String>().await?;
+        assert_eq!(&payload, message.as_body());
+
+        child_ctx
+            .flow_controls()
+            .add_consumer(child_ctx.address(), &sc_flow_control_id);
+        let payload = format!("Hello, Alice! {}", n);
+        child_ctx
+            .send(message.return_route(), payload.clone())
+            .await?;
+
+        let message = child_ctx.receive::<String>().await?;
+        assert_eq!(&payload, message.as_body());
+    }
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_channel_registry(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels();
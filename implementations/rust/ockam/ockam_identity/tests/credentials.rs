@@ -4,12 +4,17 @@ use std::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, Any, DenyAll};
 use ockam_core::{route, Result, Routed, Worker};
-use ockam_identity::models::CredentialSchemaIdentifier;
+use ockam_identity::models::{
+    Credential, CredentialAndPurposeKey, CredentialSchemaIdentifier, Identifier, RevocationList,
+    RevocationListAndPurposeKey,
+};
 use ockam_identity::secure_channels::secure_channels;
+use ockam_identity::storage::InMemoryStorage;
 use ockam_identity::utils::AttributesBuilder;
 use ockam_identity::{
-    AuthorityService, CredentialAccessControl, CredentialsMemoryRetriever,
-    SecureChannelListenerOptions, SecureChannelOptions, TrustContext, TrustIdentifierPolicy,
+    AuthorityService, CredentialAccessControl, Credentials, CredentialsMemoryRetriever,
+    RevocationListIssuer, SecureChannelListenerOptions, SecureChannelOptions, TrustContext,
+    TrustIdentifierPolicy,
 };
 use ockam_node::{Context, WorkerBuilder};
 
@@ -94,6 +99,220 @@ async fn full_flow_oneway(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn full_flow_multiple_authorities(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let identities_repository = identities.repository();
+    let credentials = identities.credentials();
+    let credentials_service = identities.credentials_server();
+
+    let authority = identities_creation.create_identity().await?;
+    let partner_authority = identities_creation.create_identity().await?;
+    let unknown_authority = identities_creation.create_identity().await?;
+    let server = identities_creation.create_identity().await?;
+    let client1 = identities_creation.create_identity().await?;
+    let client2 = identities_creation.create_identity().await?;
+
+    let listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            server.identifier(),
+            "listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    let trust_context = TrustContext::new_with_authorities(
+        "test_trust_context_id".to_string(),
+        vec![
+            AuthorityService::new(credentials.clone(), authority.identifier().clone(), None),
+            AuthorityService::new(
+                credentials.clone(),
+                partner_authority.identifier().clone(),
+                None,
+            )
+            .with_namespace("partner"),
+        ],
+    );
+
+    ctx.flow_controls()
+        .add_consumer("credential_exchange", listener.flow_control_id());
+    credentials_service
+        .start(
+            ctx,
+            trust_context,
+            server.identifier().clone(),
+            "credential_exchange".into(),
+            false,
+        )
+        .await?;
+
+    async fn issue(
+        credentials: &Credentials,
+        issuer: &Identifier,
+        subject: &Identifier,
+    ) -> Result<CredentialAndPurposeKey> {
+        credentials
+            .credentials_creation()
+            .issue_credential(
+                issuer,
+                subject,
+                AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                    .with_attribute("is_superuser", "true")
+                    .with_attribute("trust_context_id", "partner_trust_context_id")
+                    .build(),
+                Duration::from_secs(60),
+            )
+            .await
+    }
+
+    // the attributes attested by the main authority are not namespaced
+    let channel1 = secure_channels
+        .create_secure_channel(
+            ctx,
+            client1.identifier(),
+            route!["listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    credentials_service
+        .present_credential(
+            ctx,
+            route![channel1.clone(), "credential_exchange"],
+            issue(&credentials, authority.identifier(), client1.identifier()).await?,
+        )
+        .await?;
+
+    let attrs1 = identities_repository
+        .get_attributes(client1.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(
+        attrs1.attrs().get("is_superuser".as_bytes()).unwrap(),
+        b"true"
+    );
+
+    // the attributes attested by the partner authority are namespaced
+    let channel2 = secure_channels
+        .create_secure_channel(
+            ctx,
+            client2.identifier(),
+            route!["listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    credentials_service
+        .present_credential(
+            ctx,
+            route![channel2.clone(), "credential_exchange"],
+            issue(
+                &credentials,
+                partner_authority.identifier(),
+                client2.identifier(),
+            )
+            .await?,
+        )
+        .await?;
+
+    let attrs2 = identities_repository
+        .get_attributes(client2.identifier())
+        .await?
+        .unwrap();
+    assert!(attrs2.attrs().get("is_superuser".as_bytes()).is_none());
+    assert_eq!(
+        attrs2
+            .attrs()
+            .get("partner.is_superuser".as_bytes())
+            .unwrap(),
+        b"true"
+    );
+    assert_eq!(
+        attrs2
+            .attrs()
+            .get("partner.trust_context_id".as_bytes())
+            .unwrap(),
+        b"partner_trust_context_id"
+    );
+    // the partner authority can't attest the unprefixed trust context id checked by default policies
+    assert!(attrs2.attrs().get("trust_context_id".as_bytes()).is_none());
+    assert_eq!(
+        attrs2.attested_by().as_ref(),
+        Some(partner_authority.identifier())
+    );
+
+    // the attributes attested by both authorities are kept side by side
+    credentials_service
+        .present_credential(
+            ctx,
+            route![channel1.clone(), "credential_exchange"],
+            issue(
+                &credentials,
+                partner_authority.identifier(),
+                client1.identifier(),
+            )
+            .await?,
+        )
+        .await?;
+    let attrs1 = identities_repository
+        .get_attributes(client1.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(
+        attrs1.attrs().get("trust_context_id".as_bytes()).unwrap(),
+        b"partner_trust_context_id"
+    );
+    assert_eq!(
+        attrs1.attrs().get("is_superuser".as_bytes()).unwrap(),
+        b"true"
+    );
+    assert_eq!(
+        attrs1
+            .attrs()
+            .get("partner.is_superuser".as_bytes())
+            .unwrap(),
+        b"true"
+    );
+
+    // the main authority can't attest attributes in the namespace of the partner authority
+    let res = credentials_service
+        .present_credential(
+            ctx,
+            route![channel1, "credential_exchange"],
+            credentials
+                .credentials_creation()
+                .issue_credential(
+                    authority.identifier(),
+                    client1.identifier(),
+                    AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                        .with_attribute("partner.is_superuser", "true")
+                        .build(),
+                    Duration::from_secs(60),
+                )
+                .await?,
+        )
+        .await;
+    assert!(res.is_err());
+
+    // credentials issued by other authorities are rejected
+    let res = credentials_service
+        .present_credential(
+            ctx,
+            route![channel2, "credential_exchange"],
+            issue(
+                &credentials,
+                unknown_authority.identifier(),
+                client2.identifier(),
+            )
+            .await?,
+        )
+        .await;
+    assert!(res.is_err());
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn full_flow_twoway(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels();
@@ -310,6 +529,150 @@ async fn access_control(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn revocation(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let identities_repository = identities.repository();
+    let credentials = identities.credentials();
+    let credentials_service = identities.credentials_server();
+
+    let authority = identities_creation.create_identity().await?;
+    let server = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let options = SecureChannelListenerOptions::new();
+    let listener = secure_channels
+        .create_secure_channel_listener(ctx, server.identifier(), "listener", options)
+        .await?;
+
+    let trust_context = TrustContext::new(
+        "test_trust_context_id".to_string(),
+        Some(AuthorityService::new(
+            credentials.clone(),
+            authority.identifier().clone(),
+            None,
+        )),
+    );
+
+    ctx.flow_controls()
+        .add_consumer("credential_exchange", listener.flow_control_id());
+
+    credentials_service
+        .start(
+            ctx,
+            trust_context,
+            server.identifier().clone(),
+            "credential_exchange".into(),
+            false,
+        )
+        .await?;
+
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            client.identifier(),
+            route!["listener"],
+            SecureChannelOptions::new()
+                .with_trust_policy(TrustIdentifierPolicy::new(server.identifier().clone())),
+        )
+        .await?;
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            authority.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_superuser", "true")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+
+    let counter = Arc::new(AtomicI8::new(0));
+
+    let worker = CountingWorker {
+        msgs_count: counter.clone(),
+    };
+
+    let required_attributes = vec![(b"is_superuser".to_vec(), b"true".to_vec())];
+    let access_control =
+        CredentialAccessControl::new(&required_attributes, identities_repository.clone())
+            .with_revocation_lists(credentials.revocation_lists());
+
+    ctx.flow_controls()
+        .add_consumer("counter", listener.flow_control_id());
+
+    WorkerBuilder::new(worker)
+        .with_address("counter")
+        .with_incoming_access_control(access_control)
+        .with_outgoing_access_control(DenyAll)
+        .start(ctx)
+        .await?;
+
+    credentials_service
+        .present_credential(
+            ctx,
+            route![channel.clone(), "credential_exchange"],
+            credential.clone(),
+        )
+        .await?;
+
+    ctx.send(route![channel.clone(), "counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    // the authority revokes the client and the server receives the new revocation list
+    let revocation_list_issuer = RevocationListIssuer::new(
+        credentials.clone(),
+        authority.identifier(),
+        InMemoryStorage::create(),
+    );
+    revocation_list_issuer.revoke(client.identifier()).await?;
+    let revocation_list = revocation_list_issuer.issue_revocation_list().await?;
+
+    let verification = credentials.credentials_verification();
+
+    // the revocation list must be issued by a trusted authority
+    assert!(verification
+        .receive_revocation_list(&[server.identifier().clone()], &revocation_list)
+        .await
+        .is_err());
+
+    verification
+        .receive_revocation_list(&[authority.identifier().clone()], &revocation_list)
+        .await?;
+
+    assert!(identities_repository
+        .get_attributes(client.identifier())
+        .await?
+        .is_none());
+
+    ctx.send(route![channel.clone(), "counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    // the revoked credential can't be presented again
+    assert!(verification
+        .verify_credential(
+            Some(client.identifier()),
+            &[authority.identifier().clone()],
+            &credential,
+        )
+        .await
+        .is_err());
+    assert!(credentials_service
+        .present_credential(ctx, route![channel, "credential_exchange"], credential)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
+
 struct CountingWorker {
     msgs_count: Arc<AtomicI8>,
 }
@@ -329,3 +692,283 @@ impl Worker for CountingWorker {
         Ok(())
     }
 }
+
+#[ockam_macros::test]
+async fn revocation_list_freshness(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+    let verification = credentials.credentials_verification();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            authority.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_superuser", "true")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+
+    // the credentials of the authority are rejected until its revocation list is retrieved
+    credentials
+        .revocation_lists()
+        .require_freshness(authority.identifier(), Duration::from_secs(60));
+    assert!(verification
+        .verify_credential(
+            Some(client.identifier()),
+            &[authority.identifier().clone()],
+            &credential,
+        )
+        .await
+        .is_err());
+
+    let revocation_list_issuer = RevocationListIssuer::new(
+        credentials.clone(),
+        authority.identifier(),
+        InMemoryStorage::create(),
+    );
+    let revocation_list = revocation_list_issuer.issue_revocation_list().await?;
+    verification
+        .receive_revocation_list(&[authority.identifier().clone()], &revocation_list)
+        .await?;
+    verification
+        .receive_presented_credential(
+            client.identifier(),
+            &[authority.identifier().clone()],
+            &credential,
+        )
+        .await?;
+
+    // the attributes keep the creation time of their credential, which is compared to the
+    // revocation time: a credential issued after a revocation is accepted
+    revocation_list_issuer.revoke(client.identifier()).await?;
+    let revocation_list = revocation_list_issuer.issue_revocation_list().await?;
+    verification
+        .receive_revocation_list(&[authority.identifier().clone()], &revocation_list)
+        .await?;
+    assert!(identities
+        .repository()
+        .get_attributes(client.identifier())
+        .await?
+        .is_none());
+
+    ctx.sleep(Duration::from_millis(1100)).await;
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            authority.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_superuser", "true")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    verification
+        .receive_presented_credential(
+            client.identifier(),
+            &[authority.identifier().clone()],
+            &credential,
+        )
+        .await?;
+    let entry = identities
+        .repository()
+        .get_attributes(client.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(
+        entry.credential_created_at(),
+        Some(credential.get_credential_data()?.created_at)
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn revocation_list_staleness_per_authority(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let identities_repository = identities.repository();
+    let credentials = identities.credentials();
+    let credentials_service = identities.credentials_server();
+
+    let authority = identities_creation.create_identity().await?;
+    let partner_authority = identities_creation.create_identity().await?;
+    let server = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            server.identifier(),
+            "listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    let trust_context = TrustContext::new_with_authorities(
+        "test_trust_context_id".to_string(),
+        vec![
+            AuthorityService::new(credentials.clone(), authority.identifier().clone(), None),
+            AuthorityService::new(
+                credentials.clone(),
+                partner_authority.identifier().clone(),
+                None,
+            )
+            .with_namespace("partner"),
+        ],
+    );
+
+    ctx.flow_controls()
+        .add_consumer("credential_exchange", listener.flow_control_id());
+    credentials_service
+        .start(
+            ctx,
+            trust_context,
+            server.identifier().clone(),
+            "credential_exchange".into(),
+            false,
+        )
+        .await?;
+
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            client.identifier(),
+            route!["listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+
+    // the client presents a credential from each authority
+    for issuer in [authority.identifier(), partner_authority.identifier()] {
+        let credential = credentials
+            .credentials_creation()
+            .issue_credential(
+                issuer,
+                client.identifier(),
+                AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                    .with_attribute("is_superuser", "true")
+                    .build(),
+                Duration::from_secs(60),
+            )
+            .await?;
+        credentials_service
+            .present_credential(
+                ctx,
+                route![channel.clone(), "credential_exchange"],
+                credential,
+            )
+            .await?;
+    }
+
+    // each worker requires the attributes attested by one of the authorities
+    let counter = Arc::new(AtomicI8::new(0));
+    let partner_counter = Arc::new(AtomicI8::new(0));
+    for (address, attribute_name, msgs_count) in [
+        ("counter", "is_superuser", counter.clone()),
+        (
+            "partner_counter",
+            "partner.is_superuser",
+            partner_counter.clone(),
+        ),
+    ] {
+        let required_attributes = vec![(attribute_name.as_bytes().to_vec(), b"true".to_vec())];
+        let access_control =
+            CredentialAccessControl::new(&required_attributes, identities_repository.clone())
+                .with_revocation_lists(credentials.revocation_lists());
+        ctx.flow_controls()
+            .add_consumer(address, listener.flow_control_id());
+        WorkerBuilder::new(CountingWorker { msgs_count })
+            .with_address(address)
+            .with_incoming_access_control(access_control)
+            .with_outgoing_access_control(DenyAll)
+            .start(ctx)
+            .await?;
+    }
+
+    // only the revocation list of the first authority becomes stale: the attributes it attested
+    // are rejected even though the last credential was issued by the partner authority
+    credentials
+        .revocation_lists()
+        .require_freshness(authority.identifier(), Duration::from_secs(60));
+
+    ctx.send(route![channel.clone(), "counter"], "Hello".to_string())
+        .await?;
+    ctx.send(route![channel, "partner_counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 0);
+    assert_eq!(partner_counter.load(Ordering::Relaxed), 1);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn revocation_list_and_credential_signatures_are_not_interchangeable(
+    ctx: &mut Context,
+) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+    let verification = credentials.credentials_verification();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            authority.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_superuser", "true")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    let revocation_list = RevocationListIssuer::new(
+        credentials.clone(),
+        authority.identifier(),
+        InMemoryStorage::create(),
+    )
+    .issue_revocation_list()
+    .await?;
+
+    // a signed revocation list can't be presented as a credential
+    let fake_credential = CredentialAndPurposeKey {
+        credential: Credential {
+            data: revocation_list.revocation_list.data.clone(),
+            signature: revocation_list.revocation_list.signature.clone(),
+        },
+        purpose_key_attestation: revocation_list.purpose_key_attestation.clone(),
+    };
+    assert!(verification
+        .verify_credential(None, &[authority.identifier().clone()], &fake_credential)
+        .await
+        .is_err());
+
+    // a signed credential can't be received as a revocation list
+    let fake_revocation_list = RevocationListAndPurposeKey {
+        revocation_list: RevocationList {
+            data: credential.credential.data.clone(),
+            signature: credential.credential.signature.clone(),
+        },
+        purpose_key_attestation: credential.purpose_key_attestation.clone(),
+    };
+    assert!(verification
+        .verify_revocation_list(&[authority.identifier().clone()], &fake_revocation_list)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
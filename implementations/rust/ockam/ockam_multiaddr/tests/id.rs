@@ -1,5 +1,7 @@
 use core::fmt;
-use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp};
+use ockam_multiaddr::proto::{
+    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Udp, Unix, Ws,
+};
 use ockam_multiaddr::{Code, Match, MultiAddr, Protocol};
 use quickcheck::{quickcheck, Arbitrary, Gen};
 use rand::distributions::{Alphanumeric, DistString};
@@ -132,6 +134,18 @@ quickcheck! {
                         addr.push_back(Space::new("space")).unwrap();
                         prot.push_back(Space::CODE);
                     }
+                    Udp::CODE => {
+                        addr.push_back(Udp::new(0)).unwrap();
+                        prot.push_back(Udp::CODE);
+                    }
+                    Ws::CODE => {
+                        addr.push_back(Ws).unwrap();
+                        prot.push_back(Ws::CODE);
+                    }
+                    Unix::CODE => {
+                        addr.push_back(Unix::new("/tmp/ockam.sock")).unwrap();
+                        prot.push_back(Unix::CODE);
+                    }
                     _ => unreachable!()
                 }
             }
@@ -153,6 +167,9 @@ const PROTOS: &[Code] = &[
     Node::CODE,
     Project::CODE,
     Space::CODE,
+    Udp::CODE,
+    Ws::CODE,
+    Unix::CODE,
 ];
 
 impl Arbitrary for Addr {
@@ -169,6 +186,9 @@ impl Arbitrary for Addr {
                 Project::CODE => a.push_back(Project::new(gen_string())).unwrap(),
                 Space::CODE => a.push_back(Space::new(gen_string())).unwrap(),
                 Node::CODE => a.push_back(Node::new(gen_string())).unwrap(),
+                Udp::CODE => a.push_back(Udp::new(u16::arbitrary(g))).unwrap(),
+                Ws::CODE => a.push_back(Ws).unwrap(),
+                Unix::CODE => a.push_back(Unix::new(gen_path())).unwrap(),
                 _ => unreachable!(),
             }
         }
@@ -176,6 +196,27 @@ impl Arbitrary for Addr {
     }
 }
 
+#[test]
+fn transport_protocols() {
+    let a = MultiAddr::from_str("/ip4/127.0.0.1/udp/4000/service/api").unwrap();
+    let codes: Vec<Code> = a.iter().map(|p| p.code()).collect();
+    assert_eq!(codes, [Ip4::CODE, Udp::CODE, Service::CODE]);
+    assert_eq!(a.iter().nth(1).unwrap().cast::<Udp>(), Some(Udp::new(4000)));
+
+    let a = MultiAddr::from_str("/dnsaddr/localhost/tcp/4000/ws/service/api").unwrap();
+    let codes: Vec<Code> = a.iter().map(|p| p.code()).collect();
+    assert_eq!(codes, [DnsAddr::CODE, Tcp::CODE, Ws::CODE, Service::CODE]);
+    assert_eq!(a.to_string(), "/dnsaddr/localhost/tcp/4000/ws/service/api");
+
+    let a = MultiAddr::from_str("/unix/%2Ftmp%2Fockam%25.sock/service/api").unwrap();
+    let unix = a.first().unwrap();
+    assert_eq!(&*unix.cast::<Unix>().unwrap(), "/tmp/ockam%.sock");
+    assert_eq!(a.to_string(), "/unix/%2Ftmp%2Fockam%25.sock/service/api");
+
+    assert!(MultiAddr::from_str("/unix/%2").is_err());
+    assert!(MultiAddr::from_str("/ip4/127.0.0.1/udp/port").is_err());
+}
+
 /// An operation to perform on a MultiAddr.
 #[derive(Debug, Copy, Clone)]
 enum Op {
@@ -220,3 +261,12 @@ fn gen_string() -> String {
     s.retain(|c| c != '/');
     s
 }
+
+fn gen_path() -> String {
+    let mut g = rand::thread_rng();
+    let mut v = vec![String::new()];
+    for _ in 1..=g.gen_range(1..5) {
+        v.push(Alphanumeric.sample_string(&mut g, 10))
+    }
+    format!("{}%.sock", v.join("/"))
+}
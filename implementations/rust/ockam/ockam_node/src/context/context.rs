@@ -1,4 +1,5 @@
 use crate::channel_types::{SmallReceiver, SmallSender};
+use crate::metrics::Metrics;
 use crate::tokio::runtime::Handle;
 use crate::{error::*, AsyncDropSender, NodeMessage};
 use core::sync::atomic::AtomicUsize;
@@ -27,6 +28,7 @@ pub struct Context {
     /// List of transports used to resolve external addresses to local workers in routes
     pub(super) transports: Arc<RwLock<HashMap<TransportType, Arc<dyn Transport>>>>,
     pub(super) flow_controls: FlowControls,
+    pub(super) metrics: Metrics,
 }
 
 /// This trait can be used to integrate transports into a node
@@ -82,6 +84,11 @@ impl Context {
     pub fn flow_controls(&self) -> &FlowControls {
         &self.flow_controls
     }
+
+    /// Shared [`Metrics`] instance
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
 }
 
 impl Context {
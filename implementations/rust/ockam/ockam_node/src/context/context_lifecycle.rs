@@ -12,6 +12,7 @@ use ockam_transport_core::Transport;
 
 use crate::async_drop::AsyncDrop;
 use crate::channel_types::{message_channel, small_channel, SmallReceiver, SmallSender};
+use crate::metrics::Metrics;
 use crate::tokio::{self, runtime::Handle};
 use crate::{debugger, Context};
 use crate::{error::*, relay::CtrlSignal, router::SenderPair, NodeMessage};
@@ -62,6 +63,7 @@ impl Context {
         async_drop_sender: Option<AsyncDropSender>,
         transports: Arc<RwLock<HashMap<TransportType, Arc<dyn Transport>>>>,
         flow_controls: &FlowControls,
+        metrics: &Metrics,
     ) -> (Self, SenderPair, SmallReceiver<CtrlSignal>) {
         let (mailbox_tx, receiver) = message_channel();
         let (ctrl_tx, ctrl_rx) = small_channel();
@@ -75,6 +77,7 @@ impl Context {
                 mailbox_count: Arc::new(0.into()),
                 transports,
                 flow_controls: flow_controls.clone(),
+                metrics: metrics.clone(),
             },
             SenderPair {
                 msgs: mailbox_tx,
@@ -95,6 +98,7 @@ impl Context {
             None,
             self.transports.clone(),
             &self.flow_controls,
+            &self.metrics,
         )
     }
 
@@ -110,6 +114,7 @@ impl Context {
             Some(drop_sender),
             self.transports.clone(),
             &self.flow_controls,
+            &self.metrics,
         )
     }
 
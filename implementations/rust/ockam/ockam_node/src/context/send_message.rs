@@ -272,10 +272,12 @@ impl Context {
         }
 
         // Send the packed user message with associated route
+        let payload_size = relay_msg.local_message().transport().payload.len();
         sender
             .send(relay_msg)
             .await
             .map_err(NodeError::from_send_err)?;
+        self.metrics.record_message_routed(payload_size);
 
         Ok(())
     }
@@ -357,10 +359,12 @@ impl Context {
         }
 
         // Forward the message
+        let payload_size = relay_msg.local_message().transport().payload.len();
         sender
             .send(relay_msg)
             .await
             .map_err(NodeError::from_send_err)?;
+        self.metrics.record_message_routed(payload_size);
 
         Ok(())
     }
@@ -9,8 +9,9 @@ use crate::{
 use core::future::Future;
 use ockam_core::{Address, Result};
 
-#[cfg(feature = "metrics")]
 use crate::metrics::Metrics;
+#[cfg(feature = "metrics")]
+use crate::metrics::RuntimeMetrics;
 
 // This import is available on emebedded but we don't use the metrics
 // collector, thus don't need it in scope.
@@ -36,21 +37,21 @@ pub struct Executor {
     router: Router,
     /// Metrics collection endpoint
     #[cfg(feature = "metrics")]
-    metrics: Arc<Metrics>,
+    runtime_metrics: Arc<RuntimeMetrics>,
 }
 
 impl Executor {
     /// Create a new Ockam node [`Executor`] instance
-    pub fn new(flow_controls: &FlowControls) -> Self {
+    pub fn new(flow_controls: &FlowControls, metrics: &Metrics) -> Self {
         let rt = Runtime::new().unwrap();
-        let router = Router::new(flow_controls);
+        let router = Router::new(flow_controls, metrics);
         #[cfg(feature = "metrics")]
-        let metrics = Metrics::new(&rt, router.get_metrics_readout());
+        let runtime_metrics = RuntimeMetrics::new(&rt, router.get_metrics_readout());
         Self {
             rt,
             router,
             #[cfg(feature = "metrics")]
-            metrics,
+            runtime_metrics,
         }
     }
 
@@ -92,7 +93,8 @@ impl Executor {
         #[cfg(feature = "metrics")]
         let alive = Arc::new(AtomicBool::from(true));
         #[cfg(feature = "metrics")]
-        self.rt.spawn(self.metrics.clone().run(alive.clone()));
+        self.rt
+            .spawn(self.runtime_metrics.clone().run(alive.clone()));
 
         // Spawn user code second
         let join_body = self.rt.spawn(future);
@@ -37,8 +37,8 @@ pub mod compat;
 /// MPSC channel type aliases
 pub mod channel_types;
 
-#[cfg(feature = "metrics")]
-mod metrics;
+/// Node metrics
+pub mod metrics;
 
 /// Api helpers
 pub mod api;
@@ -0,0 +1,423 @@
+#[cfg(feature = "metrics")]
+mod runtime;
+
+#[cfg(feature = "metrics")]
+pub(crate) use runtime::RuntimeMetrics;
+
+use core::fmt::Write;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Address;
+
+/// Upper bounds of the buckets used for the size of routed messages, in bytes
+const MESSAGE_SIZE_BUCKETS: &[usize] = &[64, 256, 1024, 4096, 16384, 65536];
+
+/// Metrics collected by a node: routed messages, workers mailboxes, secure channel
+/// handshakes, portals and relays.
+///
+/// A shared instance is created for each node and can be accessed by workers with
+/// [`Context::metrics`](crate::Context::metrics). The metrics can be exported with
+/// [`Metrics::render`] using the OpenMetrics text format
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    state: Arc<MetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    messages_routed: AtomicUsize,
+    message_sizes: Histogram,
+    mailbox_depths: RwLock<BTreeMap<Address, Arc<AtomicUsize>>>,
+    /// Number of handshakes per (role, result)
+    handshakes: RwLock<BTreeMap<(String, String), usize>>,
+    portal_bytes_in: AtomicUsize,
+    portal_bytes_out: AtomicUsize,
+    relays: AtomicUsize,
+    relays_created: AtomicUsize,
+    /// Number of in-flight messages for each windowed pipe sender
+    pipe_windows: RwLock<BTreeMap<Address, usize>>,
+    pipe_retransmissions: AtomicUsize,
+}
+
+impl Metrics {
+    /// Create a new, empty, set of metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a message sent to a worker, with the size of its payload
+    pub fn record_message_routed(&self, payload_size: usize) {
+        self.state.messages_routed.fetch_add(1, Ordering::Relaxed);
+        self.state.message_sizes.observe(payload_size);
+    }
+
+    /// Record the end of a secure channel handshake for a given role
+    pub fn record_secure_channel_handshake(&self, role: &str, success: bool) {
+        let result = if success { "success" } else { "failure" };
+        let mut handshakes = self.state.handshakes.write().unwrap();
+        *handshakes.entry((role.into(), result.into())).or_default() += 1;
+    }
+
+    /// Record bytes received by a portal from its TCP connection
+    pub fn record_portal_bytes_in(&self, bytes: usize) {
+        self.state
+            .portal_bytes_in
+            .fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Record bytes written by a portal to its TCP connection
+    pub fn record_portal_bytes_out(&self, bytes: usize) {
+        self.state
+            .portal_bytes_out
+            .fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Record the creation of a relay on this node
+    pub fn record_relay_created(&self) {
+        self.state.relays.fetch_add(1, Ordering::Relaxed);
+        self.state.relays_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the removal of a relay from this node
+    pub fn record_relay_removed(&self) {
+        // the update can't fail since the closure always returns a value
+        let _ = self
+            .state
+            .relays
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                Some(n.saturating_sub(1))
+            });
+    }
+
+    /// Record the number of messages sent but not yet credited by a windowed pipe sender
+    pub fn record_pipe_window_occupancy(&self, address: &Address, in_flight: usize) {
+        self.state
+            .pipe_windows
+            .write()
+            .unwrap()
+            .insert(address.clone(), in_flight);
+    }
+
+    /// Stop tracking the window of a pipe sender, when it is stopped
+    pub fn remove_pipe_window(&self, address: &Address) {
+        self.state.pipe_windows.write().unwrap().remove(address);
+    }
+
+    /// Record messages re-sent by a pipe sender
+    pub fn record_pipe_retransmissions(&self, count: usize) {
+        self.state
+            .pipe_retransmissions
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Start tracking the number of messages waiting in the mailbox of a worker
+    pub(crate) fn register_mailbox(&self, address: Address, depth: Arc<AtomicUsize>) {
+        self.state
+            .mailbox_depths
+            .write()
+            .unwrap()
+            .insert(address, depth);
+    }
+
+    /// Stop tracking the mailbox of a worker
+    pub(crate) fn unregister_mailbox(&self, address: &Address) {
+        self.state.mailbox_depths.write().unwrap().remove(address);
+    }
+
+    /// Number of messages routed so far
+    pub fn messages_routed(&self) -> usize {
+        self.state.messages_routed.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages sent but not yet credited by a windowed pipe sender
+    pub fn pipe_window_occupancy(&self, address: &Address) -> Option<usize> {
+        self.state
+            .pipe_windows
+            .read()
+            .unwrap()
+            .get(address)
+            .cloned()
+    }
+
+    /// Number of messages re-sent by pipe senders so far
+    pub fn pipe_retransmissions(&self) -> usize {
+        self.state.pipe_retransmissions.load(Ordering::Relaxed)
+    }
+
+    /// Number of relays currently running on this node
+    pub fn relays(&self) -> usize {
+        self.state.relays.load(Ordering::Relaxed)
+    }
+
+    /// Render all the metrics using the OpenMetrics text format
+    pub fn render(&self) -> String {
+        let state = &self.state;
+        let mut out = String::new();
+
+        write_family(
+            &mut out,
+            "ockam_messages_routed",
+            "counter",
+            "Number of messages sent to workers",
+        );
+        write_sample(
+            &mut out,
+            "ockam_messages_routed_total",
+            &[],
+            state.messages_routed.load(Ordering::Relaxed),
+        );
+
+        write_family(
+            &mut out,
+            "ockam_message_size_bytes",
+            "histogram",
+            "Size of the payload of the messages sent to workers",
+        );
+        state
+            .message_sizes
+            .render(&mut out, "ockam_message_size_bytes");
+
+        write_family(
+            &mut out,
+            "ockam_worker_mailbox_depth",
+            "gauge",
+            "Number of messages waiting to be processed by a worker",
+        );
+        for (address, depth) in state.mailbox_depths.read().unwrap().iter() {
+            write_sample(
+                &mut out,
+                "ockam_worker_mailbox_depth",
+                &[("address", &address.to_string())],
+                depth.load(Ordering::Relaxed),
+            );
+        }
+
+        write_family(
+            &mut out,
+            "ockam_secure_channel_handshakes",
+            "counter",
+            "Number of completed secure channel handshakes",
+        );
+        for ((role, result), count) in state.handshakes.read().unwrap().iter() {
+            write_sample(
+                &mut out,
+                "ockam_secure_channel_handshakes_total",
+                &[("role", role), ("result", result)],
+                *count,
+            );
+        }
+
+        write_family(
+            &mut out,
+            "ockam_portal_bytes",
+            "counter",
+            "Number of bytes transferred by portals",
+        );
+        write_sample(
+            &mut out,
+            "ockam_portal_bytes_total",
+            &[("direction", "in")],
+            state.portal_bytes_in.load(Ordering::Relaxed),
+        );
+        write_sample(
+            &mut out,
+            "ockam_portal_bytes_total",
+            &[("direction", "out")],
+            state.portal_bytes_out.load(Ordering::Relaxed),
+        );
+
+        write_family(
+            &mut out,
+            "ockam_relays",
+            "gauge",
+            "Number of relays running on the node",
+        );
+        write_sample(
+            &mut out,
+            "ockam_relays",
+            &[],
+            state.relays.load(Ordering::Relaxed),
+        );
+
+        write_family(
+            &mut out,
+            "ockam_relays_created",
+            "counter",
+            "Number of relays created on the node",
+        );
+        write_sample(
+            &mut out,
+            "ockam_relays_created_total",
+            &[],
+            state.relays_created.load(Ordering::Relaxed),
+        );
+
+        write_family(
+            &mut out,
+            "ockam_pipe_window_occupancy",
+            "gauge",
+            "Number of messages sent but not yet credited by a windowed pipe sender",
+        );
+        for (address, in_flight) in state.pipe_windows.read().unwrap().iter() {
+            write_sample(
+                &mut out,
+                "ockam_pipe_window_occupancy",
+                &[("address", &address.to_string())],
+                *in_flight,
+            );
+        }
+
+        write_family(
+            &mut out,
+            "ockam_pipe_retransmissions",
+            "counter",
+            "Number of messages re-sent by pipe senders",
+        );
+        write_sample(
+            &mut out,
+            "ockam_pipe_retransmissions_total",
+            &[],
+            state.pipe_retransmissions.load(Ordering::Relaxed),
+        );
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+/// Histogram with fixed buckets
+#[derive(Debug)]
+struct Histogram {
+    bounds: &'static [usize],
+    /// One count per bucket, the last one is the `+Inf` bucket
+    buckets: Vec<AtomicUsize>,
+    sum: AtomicUsize,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            bounds: MESSAGE_SIZE_BUCKETS,
+            buckets: (0..=MESSAGE_SIZE_BUCKETS.len())
+                .map(|_| AtomicUsize::new(0))
+                .collect(),
+            sum: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&self, value: usize) {
+        let index = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// OpenMetrics buckets are cumulative
+    fn render(&self, out: &mut String, name: &str) {
+        let bucket_name = format!("{name}_bucket");
+        let mut count = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            count += bucket.load(Ordering::Relaxed);
+            let bound = match self.bounds.get(index) {
+                Some(bound) => bound.to_string(),
+                None => "+Inf".into(),
+            };
+            write_sample(out, &bucket_name, &[("le", &bound)], count);
+        }
+        write_sample(
+            out,
+            &format!("{name}_sum"),
+            &[],
+            self.sum.load(Ordering::Relaxed),
+        );
+        write_sample(out, &format!("{name}_count"), &[], count);
+    }
+}
+
+fn write_family(out: &mut String, name: &str, metric_type: &str, help: &str) {
+    let _ = writeln!(out, "# TYPE {name} {metric_type}");
+    let _ = writeln!(out, "# HELP {name} {help}");
+}
+
+fn write_sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: usize) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(label, value)| format!("{label}=\"{}\"", escape_label_value(value)))
+            .collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {value}");
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_open_metrics() {
+        let metrics = Metrics::new();
+        metrics.record_message_routed(10);
+        metrics.record_message_routed(100_000);
+        metrics.register_mailbox("worker".into(), Arc::new(AtomicUsize::new(3)));
+        metrics.record_secure_channel_handshake("initiator", true);
+        metrics.record_secure_channel_handshake("initiator", true);
+        metrics.record_portal_bytes_in(5);
+        metrics.record_relay_created();
+        metrics.record_relay_created();
+        metrics.record_relay_removed();
+        metrics.record_pipe_window_occupancy(&"pipe".into(), 4);
+        metrics.record_pipe_retransmissions(2);
+
+        let rendered = metrics.render();
+        for line in [
+            "ockam_messages_routed_total 2",
+            "ockam_message_size_bytes_bucket{le=\"64\"} 1",
+            "ockam_message_size_bytes_bucket{le=\"65536\"} 1",
+            "ockam_message_size_bytes_bucket{le=\"+Inf\"} 2",
+            "ockam_message_size_bytes_sum 100010",
+            "ockam_message_size_bytes_count 2",
+            "ockam_worker_mailbox_depth{address=\"0#worker\"} 3",
+            "ockam_secure_channel_handshakes_total{role=\"initiator\",result=\"success\"} 2",
+            "ockam_portal_bytes_total{direction=\"in\"} 5",
+            "ockam_portal_bytes_total{direction=\"out\"} 0",
+            "ockam_relays 1",
+            "ockam_relays_created_total 2",
+            "ockam_pipe_window_occupancy{address=\"0#pipe\"} 4",
+            "ockam_pipe_retransmissions_total 2",
+        ] {
+            assert!(
+                rendered.lines().any(|l| l == line),
+                "missing {line} in\n{rendered}"
+            );
+        }
+        assert!(rendered.ends_with("# EOF\n"));
+
+        metrics.unregister_mailbox(&"worker".into());
+        assert!(!metrics.render().contains("ockam_worker_mailbox_depth{"));
+
+        metrics.remove_pipe_window(&"pipe".into());
+        assert!(!metrics.render().contains("ockam_pipe_window_occupancy{"));
+    }
+
+    #[test]
+    fn escape_label_values() {
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -7,13 +7,13 @@ use ockam_core::compat::{collections::BTreeMap, sync::Arc};
 use ockam_core::env::get_env;
 use std::{fs::OpenOptions, io::Write};
 
-pub struct Metrics {
+pub struct RuntimeMetrics {
     rt: Arc<Runtime>,
     router: (Arc<AtomicUsize>, Arc<AtomicUsize>),
 }
 
-impl Metrics {
-    /// Create a new Metrics collector with access to the runtime
+impl RuntimeMetrics {
+    /// Create a new runtime metrics collector with access to the runtime
     pub(crate) fn new(
         rt: &Arc<Runtime>,
         router: (Arc<AtomicUsize>, Arc<AtomicUsize>),
@@ -2,6 +2,7 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::FlowControls;
 use ockam_core::{Address, AllowAll, Mailbox, Mailboxes};
 
+use crate::metrics::Metrics;
 use crate::{debugger, Context, Executor};
 
 /// A minimal worker implementation that does nothing
@@ -50,7 +51,10 @@ impl NodeBuilder {
         // Shared instance of FlowControls
         let flow_controls = FlowControls::new();
 
-        let mut exe = Executor::new(&flow_controls);
+        // Shared instance of Metrics
+        let metrics = Metrics::new();
+
+        let mut exe = Executor::new(&flow_controls, &metrics);
         let addr: Address = "app".into();
 
         // The root application worker needs a mailbox and relay to accept
@@ -65,6 +69,7 @@ impl NodeBuilder {
             None,
             Default::default(),
             &flow_controls,
+            &metrics,
         );
 
         debugger::log_inherit_context("NODE", &ctx, &ctx);
@@ -14,6 +14,7 @@ use record::{AddressMeta, AddressRecord, InternalMap};
 use state::{NodeState, RouterState};
 
 use crate::channel_types::{router_channel, MessageSender, RouterReceiver, SmallSender};
+use crate::metrics::Metrics;
 use crate::{
     error::{NodeError, NodeReason},
     relay::CtrlSignal,
@@ -64,11 +65,11 @@ fn determine_type(next: &Address) -> RouteType {
 }
 
 impl Router {
-    pub fn new(flow_controls: &FlowControls) -> Self {
+    pub fn new(flow_controls: &FlowControls, metrics: &Metrics) -> Self {
         let (sender, receiver) = router_channel();
         Self {
             state: RouterState::new(sender),
-            map: InternalMap::new(flow_controls),
+            map: InternalMap::new(flow_controls, metrics),
             external: BTreeMap::new(),
             receiver: Some(receiver),
         }
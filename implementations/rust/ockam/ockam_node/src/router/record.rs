@@ -1,4 +1,5 @@
 use crate::channel_types::{MessageSender, SmallSender};
+use crate::metrics::Metrics;
 use crate::relay::CtrlSignal;
 use crate::{
     error::{NodeError, NodeReason},
@@ -30,13 +31,15 @@ pub struct InternalMap {
     stopping: BTreeSet<Address>,
     /// Access to [`FlowControls`] to clean resources
     flow_controls: FlowControls,
+    /// Access to [`Metrics`] to track workers mailboxes
+    metrics: Metrics,
     /// Metrics collection and sharing
     #[cfg(feature = "metrics")]
-    metrics: (Arc<AtomicUsize>, Arc<AtomicUsize>),
+    runtime_metrics: (Arc<AtomicUsize>, Arc<AtomicUsize>),
 }
 
 impl InternalMap {
-    pub(super) fn new(flow_controls: &FlowControls) -> Self {
+    pub(super) fn new(flow_controls: &FlowControls, metrics: &Metrics) -> Self {
         Self {
             address_records_map: Default::default(),
             alias_map: Default::default(),
@@ -44,8 +47,9 @@ impl InternalMap {
             clusters: Default::default(),
             stopping: Default::default(),
             flow_controls: flow_controls.clone(),
+            metrics: metrics.clone(),
             #[cfg(feature = "metrics")]
-            metrics: Default::default(),
+            runtime_metrics: Default::default(),
         }
     }
 }
@@ -75,6 +79,7 @@ impl InternalMap {
         primary_address: &Address,
     ) -> Option<AddressRecord> {
         self.flow_controls.cleanup_address(primary_address);
+        self.metrics.unregister_mailbox(primary_address);
         self.address_records_map.remove(primary_address)
     }
 
@@ -83,6 +88,10 @@ impl InternalMap {
         primary_address: Address,
         record: AddressRecord,
     ) -> Option<AddressRecord> {
+        if !record.meta.processor {
+            self.metrics
+                .register_mailbox(primary_address.clone(), record.msg_count.clone());
+        }
         self.address_records_map.insert(primary_address, record)
     }
 
@@ -105,20 +114,25 @@ impl InternalMap {
 impl InternalMap {
     #[cfg(feature = "metrics")]
     pub(super) fn update_metrics(&self) {
-        self.metrics
+        self.runtime_metrics
             .0
             .store(self.address_records_map.len(), Ordering::Release);
-        self.metrics.1.store(self.clusters.len(), Ordering::Release);
+        self.runtime_metrics
+            .1
+            .store(self.clusters.len(), Ordering::Release);
     }
 
     #[cfg(feature = "metrics")]
     pub(super) fn get_metrics(&self) -> (Arc<AtomicUsize>, Arc<AtomicUsize>) {
-        (Arc::clone(&self.metrics.0), Arc::clone(&self.metrics.1))
+        (
+            Arc::clone(&self.runtime_metrics.0),
+            Arc::clone(&self.runtime_metrics.1),
+        )
     }
 
     #[cfg(feature = "metrics")]
     pub(super) fn get_addr_count(&self) -> usize {
-        self.metrics.0.load(Ordering::Acquire)
+        self.runtime_metrics.0.load(Ordering::Acquire)
     }
 
     /// Add an address to a particular cluster
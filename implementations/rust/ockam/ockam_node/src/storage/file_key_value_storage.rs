@@ -15,7 +15,6 @@ use crate::{FileValueStorage, InMemoryKeyValueStorage, KeyValueStorage, ValueSto
 /// WARNING: This implementation provides limited consistency if the same file is reused from
 /// multiple instances and/or processes. For example, if one process deletes a value, the other
 /// process will still have it in its cache and return it on a Get query.
-/// NOTE: Currently, unused
 pub struct FileKeyValueStorage<K, V> {
     file_storage: FileValueStorage<BTreeMap<String, V>>,
     cache: InMemoryKeyValueStorage<K, V>,
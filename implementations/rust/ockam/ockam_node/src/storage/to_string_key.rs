@@ -7,3 +7,9 @@ pub trait ToStringKey {
     /// Return a string representation to be used as a key in a JSON map
     fn to_string_key(&self) -> String;
 }
+
+impl ToStringKey for String {
+    fn to_string_key(&self) -> String {
+        self.clone()
+    }
+}
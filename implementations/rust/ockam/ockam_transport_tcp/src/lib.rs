@@ -25,7 +25,10 @@ mod transport;
 
 use ockam_core::TransportType;
 pub use options::{TcpConnectionOptions, TcpListenerOptions};
-pub use portal::{PortalInternalMessage, PortalMessage, MAX_PAYLOAD_SIZE};
+pub use portal::{
+    PortalAddresses, PortalInternalMessage, PortalMessage, PortalStream, PortalType, PortalWorker,
+    TcpOutletStrategy, TcpOutletTargetStatus, TcpOutletTargets, MAX_PAYLOAD_SIZE,
+};
 pub use registry::*;
 pub use transport::common::*;
 pub use transport::*;
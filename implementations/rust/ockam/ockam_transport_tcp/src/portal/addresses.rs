@@ -1,13 +1,17 @@
+use crate::portal::PortalStream;
 use ockam_core::Address;
 
 /// Enumerate all portal types
 #[derive(Debug, Clone)]
-pub(super) enum PortalType {
+pub enum PortalType {
+    /// Inlet side of a portal, accepting connections
     Inlet,
+    /// Outlet side of a portal, connecting to a target
     Outlet,
 }
 
 impl PortalType {
+    /// Name of the portal type
     pub fn str(&self) -> &'static str {
         match self {
             PortalType::Inlet => "inlet",
@@ -16,19 +20,24 @@ impl PortalType {
     }
 }
 
+/// Addresses used by the worker and the receiver processor of a portal connection
 #[derive(Clone, Debug)]
-pub(super) struct Addresses {
-    pub(super) internal: Address,
-    pub(super) remote: Address,
-    pub(super) receiver: Address,
+pub struct PortalAddresses {
+    pub(crate) internal: Address,
+    pub(crate) remote: Address,
+    pub(crate) receiver: Address,
 }
 
-impl Addresses {
-    pub(super) fn generate(portal_type: PortalType) -> Self {
+impl PortalAddresses {
+    /// Generate the addresses of a new portal connection
+    pub fn generate<S: PortalStream>(portal_type: PortalType) -> Self {
         let type_name = portal_type.str();
-        let internal = Address::random_tagged(&format!("TcpPortalWorker.{}.internal", type_name));
-        let remote = Address::random_tagged(&format!("TcpPortalWorker.{}.remote", type_name));
-        let receiver = Address::random_tagged(&format!("TcpPortalRecvProcessor.{}", type_name));
+        let internal =
+            Address::random_tagged(&format!("{}PortalWorker.{}.internal", S::NAME, type_name));
+        let remote =
+            Address::random_tagged(&format!("{}PortalWorker.{}.remote", S::NAME, type_name));
+        let receiver =
+            Address::random_tagged(&format!("{}PortalRecvProcessor.{}", S::NAME, type_name));
 
         Self {
             internal,
@@ -36,4 +45,9 @@ impl Addresses {
             receiver,
         }
     }
+
+    /// Address receiving the messages sent from the other side of the portal
+    pub fn remote(&self) -> &Address {
+        &self.remote
+    }
 }
@@ -0,0 +1,67 @@
+use crate::portal::TcpOutletTargets;
+use core::time::Duration;
+use ockam_core::{async_trait, Address, Processor, Result};
+use ockam_node::Context;
+use tokio::net::TcpStream;
+use tracing::debug;
+
+/// A TCP Outlet health check processor
+///
+/// Periodically opens a TCP connection to each target of a
+/// load-balanced Outlet and marks the targets that can't be reached in
+/// time as unhealthy. It is started by `TcpOutletListenWorker` when a
+/// health check interval is set with
+/// [`TcpOutletOptions::with_health_check`](crate::TcpOutletOptions::with_health_check)
+pub(crate) struct TcpOutletHealthCheckProcessor {
+    targets: TcpOutletTargets,
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl TcpOutletHealthCheckProcessor {
+    /// Start a new `TcpOutletHealthCheckProcessor`
+    pub(crate) async fn start(
+        ctx: &Context,
+        targets: TcpOutletTargets,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Address> {
+        let address = Address::random_tagged("TcpOutletHealthCheckProcessor");
+        let processor = Self {
+            targets,
+            interval,
+            timeout,
+        };
+
+        ctx.start_processor(address.clone(), processor).await?;
+
+        Ok(address)
+    }
+}
+
+#[async_trait]
+impl Processor for TcpOutletHealthCheckProcessor {
+    type Context = Context;
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        for socket_addr in self.targets.socket_addrs() {
+            let healthy =
+                match tokio::time::timeout(self.timeout, TcpStream::connect(socket_addr)).await {
+                    Ok(Ok(_)) => true,
+                    Ok(Err(err)) => {
+                        debug!("Health check of {} failed: {}", socket_addr, err);
+                        false
+                    }
+                    Err(_) => {
+                        debug!("Health check of {} timed out", socket_addr);
+                        false
+                    }
+                };
+            self.targets.set_healthy(&socket_addr, healthy);
+        }
+
+        ctx.sleep(self.interval).await;
+
+        Ok(true)
+    }
+}
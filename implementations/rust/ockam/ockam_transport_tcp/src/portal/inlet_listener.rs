@@ -1,11 +1,11 @@
-use crate::portal::addresses::{Addresses, PortalType};
+use crate::portal::{PortalAddresses, PortalType};
 use crate::{portal::TcpPortalWorker, TcpInletOptions, TcpRegistry};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::{async_trait, compat::boxed::Box};
 use ockam_core::{Address, Processor, Result, Route};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
 use tracing::{debug, error};
 
 /// A TCP Portal Inlet listen processor
@@ -81,7 +81,7 @@ impl Processor for TcpInletListenProcessor {
     }
 
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
-        let addresses = Addresses::generate(PortalType::Inlet);
+        let addresses = PortalAddresses::generate::<TcpStream>(PortalType::Inlet);
         let outlet_listener_route = self.outlet_listener_route.clone();
 
         self.options.setup_flow_control(
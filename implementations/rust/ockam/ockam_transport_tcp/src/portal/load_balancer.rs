@@ -0,0 +1,256 @@
+use core::fmt;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Strategy used by an Outlet to choose the target of a new portal connection
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TcpOutletStrategy {
+    /// Use each healthy target in turn
+    #[default]
+    RoundRobin,
+    /// Use the healthy target with the fewest open portal connections
+    LeastConnections,
+}
+
+impl fmt::Display for TcpOutletStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TcpOutletStrategy::RoundRobin => "round-robin",
+            TcpOutletStrategy::LeastConnections => "least-connections",
+        })
+    }
+}
+
+/// Current state of one of the targets of an Outlet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpOutletTargetStatus {
+    /// Address of the target
+    pub socket_addr: SocketAddr,
+    /// `false` if the last health check of this target failed. Unhealthy
+    /// targets don't receive new portal connections
+    pub healthy: bool,
+    /// Number of open portal connections to this target
+    pub connections: usize,
+}
+
+struct Target {
+    socket_addr: SocketAddr,
+    healthy: bool,
+    connections: usize,
+}
+
+struct TargetsState {
+    targets: Vec<Target>,
+    strategy: TcpOutletStrategy,
+    /// Index of the next target to use with the round-robin strategy
+    next: usize,
+    /// Whether a health check runs for these targets. Without it, nothing
+    /// would mark a target healthy again
+    health_checked: bool,
+}
+
+/// The set of targets of an Outlet
+///
+/// The Outlet uses it to choose the target of each new portal
+/// connection. It can be shared to inspect the health of the targets.
+#[derive(Clone)]
+pub struct TcpOutletTargets {
+    state: Arc<Mutex<TargetsState>>,
+}
+
+impl TcpOutletTargets {
+    pub(crate) fn new(
+        socket_addrs: Vec<SocketAddr>,
+        strategy: TcpOutletStrategy,
+        health_checked: bool,
+    ) -> Self {
+        let targets = socket_addrs
+            .into_iter()
+            .map(|socket_addr| Target {
+                socket_addr,
+                healthy: true,
+                connections: 0,
+            })
+            .collect();
+
+        Self {
+            state: Arc::new(Mutex::new(TargetsState {
+                targets,
+                strategy,
+                next: 0,
+                health_checked,
+            })),
+        }
+    }
+
+    /// Strategy used to choose a target
+    pub fn strategy(&self) -> TcpOutletStrategy {
+        self.state.lock().unwrap().strategy
+    }
+
+    /// Addresses of all the targets, healthy or not
+    pub fn socket_addrs(&self) -> Vec<SocketAddr> {
+        let state = self.state.lock().unwrap();
+        state.targets.iter().map(|t| t.socket_addr).collect()
+    }
+
+    /// Current state of all the targets
+    pub fn status(&self) -> Vec<TcpOutletTargetStatus> {
+        let state = self.state.lock().unwrap();
+        state
+            .targets
+            .iter()
+            .map(|t| TcpOutletTargetStatus {
+                socket_addr: t.socket_addr,
+                healthy: t.healthy,
+                connections: t.connections,
+            })
+            .collect()
+    }
+
+    /// Choose a healthy target for a new portal connection
+    ///
+    /// The connection is accounted to the target until the returned
+    /// [`TargetConnection`] is dropped.
+    pub(crate) fn acquire(&self) -> Option<TargetConnection> {
+        let mut state = self.state.lock().unwrap();
+        let len = state.targets.len();
+
+        let index = match state.strategy {
+            TcpOutletStrategy::RoundRobin => {
+                let start = state.next;
+                let index = (0..len)
+                    .map(|i| (start + i) % len)
+                    .find(|i| state.targets[*i].healthy)?;
+                state.next = (index + 1) % len;
+                index
+            }
+            TcpOutletStrategy::LeastConnections => state
+                .targets
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.healthy)
+                .min_by_key(|(_, t)| t.connections)
+                .map(|(i, _)| i)?,
+        };
+
+        let target = &mut state.targets[index];
+        target.connections += 1;
+
+        Some(TargetConnection {
+            targets: self.clone(),
+            socket_addr: target.socket_addr,
+        })
+    }
+
+    pub(crate) fn set_healthy(&self, socket_addr: &SocketAddr, healthy: bool) {
+        let mut state = self.state.lock().unwrap();
+        for target in state.targets.iter_mut() {
+            if &target.socket_addr == socket_addr && target.healthy != healthy {
+                if healthy {
+                    info!("Outlet target {} is healthy again", socket_addr);
+                } else {
+                    warn!(
+                        "Outlet target {} is unhealthy and won't receive new connections",
+                        socket_addr
+                    );
+                }
+                target.healthy = healthy;
+            }
+        }
+    }
+
+    fn release(&self, socket_addr: &SocketAddr) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(target) = state
+            .targets
+            .iter_mut()
+            .find(|t| &t.socket_addr == socket_addr)
+        {
+            target.connections = target.connections.saturating_sub(1);
+        }
+    }
+}
+
+/// A portal connection to one of the targets of an Outlet
+///
+/// Dropping it releases the connection from the target
+pub(crate) struct TargetConnection {
+    targets: TcpOutletTargets,
+    socket_addr: SocketAddr,
+}
+
+impl TargetConnection {
+    pub(crate) fn socket_addr(&self) -> SocketAddr {
+        self.socket_addr
+    }
+
+    /// Stop sending new connections to this target until a health
+    /// check succeeds. Does nothing if the targets are not health checked
+    pub(crate) fn mark_unhealthy(&self) {
+        let health_checked = self.targets.state.lock().unwrap().health_checked;
+        if health_checked {
+            self.targets.set_healthy(&self.socket_addr, false)
+        }
+    }
+}
+
+impl Drop for TargetConnection {
+    fn drop(&mut self) {
+        self.targets.release(&self.socket_addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addrs() -> Vec<SocketAddr> {
+        vec![
+            "127.0.0.1:5001".parse().unwrap(),
+            "127.0.0.1:5002".parse().unwrap(),
+            "127.0.0.1:5003".parse().unwrap(),
+        ]
+    }
+
+    #[test]
+    fn round_robin_skips_unhealthy_targets() {
+        let addrs = addrs();
+        let targets = TcpOutletTargets::new(addrs.clone(), TcpOutletStrategy::RoundRobin, true);
+        targets.set_healthy(&addrs[1], false);
+
+        let chosen: Vec<SocketAddr> = (0..4)
+            .map(|_| targets.acquire().unwrap().socket_addr())
+            .collect();
+        assert_eq!(chosen, vec![addrs[0], addrs[2], addrs[0], addrs[2]]);
+
+        targets.set_healthy(&addrs[0], false);
+        targets.set_healthy(&addrs[2], false);
+        assert!(targets.acquire().is_none());
+    }
+
+    #[test]
+    fn least_connections_uses_the_least_loaded_target() {
+        let addrs = addrs();
+        let targets =
+            TcpOutletTargets::new(addrs.clone(), TcpOutletStrategy::LeastConnections, false);
+
+        let c1 = targets.acquire().unwrap();
+        let c2 = targets.acquire().unwrap();
+        let c3 = targets.acquire().unwrap();
+        assert_eq!(
+            vec![c1.socket_addr(), c2.socket_addr(), c3.socket_addr()],
+            addrs
+        );
+
+        drop(c2);
+        let c4 = targets.acquire().unwrap();
+        assert_eq!(c4.socket_addr(), addrs[1]);
+
+        let connections: Vec<usize> = targets.status().iter().map(|t| t.connections).collect();
+        assert_eq!(connections, vec![1, 1, 1]);
+    }
+}
@@ -1,13 +1,20 @@
 mod addresses;
+mod health_check;
 mod inlet_listener;
+mod load_balancer;
 pub mod options;
 mod outlet_listener;
 mod portal_message;
 mod portal_receiver;
+mod portal_stream;
 mod portal_worker;
 
+pub use addresses::*;
+pub(crate) use health_check::*;
 pub(crate) use inlet_listener::*;
+pub use load_balancer::*;
 pub(crate) use outlet_listener::*;
 pub use portal_message::*;
 pub(crate) use portal_receiver::*;
-pub(crate) use portal_worker::*;
+pub use portal_stream::*;
+pub use portal_worker::*;
@@ -1,4 +1,6 @@
-use crate::portal::addresses::Addresses;
+use crate::portal::PortalAddresses;
+use crate::TcpOutletStrategy;
+use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::{FlowControlId, FlowControls};
 use ockam_core::{Address, AllowAll, IncomingAccessControl};
@@ -38,7 +40,7 @@ impl TcpInletOptions {
     pub(super) fn setup_flow_control(
         &self,
         flow_controls: &FlowControls,
-        addresses: &Addresses,
+        addresses: &PortalAddresses,
         next: &Address,
     ) {
         if let Some(flow_control_id) = flow_controls
@@ -62,6 +64,15 @@ impl Default for TcpInletOptions {
 pub struct TcpOutletOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(super) incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub(crate) strategy: TcpOutletStrategy,
+    pub(crate) health_check: Option<TcpOutletHealthCheck>,
+}
+
+/// Health check settings of an Outlet with several targets
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TcpOutletHealthCheck {
+    pub(crate) interval: Duration,
+    pub(crate) timeout: Duration,
 }
 
 impl TcpOutletOptions {
@@ -70,6 +81,8 @@ impl TcpOutletOptions {
         Self {
             consumer: vec![],
             incoming_access_control: Arc::new(AllowAll),
+            strategy: TcpOutletStrategy::default(),
+            health_check: None,
         }
     }
 
@@ -100,6 +113,21 @@ impl TcpOutletOptions {
         self
     }
 
+    /// Set the strategy used to choose the target of each new connection
+    /// when the Outlet has several targets
+    pub fn with_strategy(mut self, strategy: TcpOutletStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Periodically check that the targets accept TCP connections within
+    /// `timeout`. Targets failing the check don't receive new connections
+    /// until they pass it again
+    pub fn with_health_check(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.health_check = Some(TcpOutletHealthCheck { interval, timeout });
+        self
+    }
+
     pub(super) fn setup_flow_control_for_outlet_listener(
         &self,
         flow_controls: &FlowControls,
@@ -113,7 +141,7 @@ impl TcpOutletOptions {
     pub(super) fn setup_flow_control_for_outlet(
         &self,
         flow_controls: &FlowControls,
-        addresses: &Addresses,
+        addresses: &PortalAddresses,
         src_addr: &Address,
     ) {
         // Check if the Worker that send us this message is a Producer
@@ -1,10 +1,13 @@
-use crate::portal::addresses::{Addresses, PortalType};
-use crate::{portal::TcpPortalWorker, PortalMessage, TcpOutletOptions, TcpRegistry};
+use crate::portal::TcpOutletHealthCheckProcessor;
+use crate::portal::{PortalAddresses, PortalType};
+use crate::{
+    portal::TcpPortalWorker, PortalMessage, TcpOutletOptions, TcpOutletTargets, TcpRegistry,
+};
 use ockam_core::{async_trait, Address, DenyAll, Result, Routed, Worker};
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
-use std::net::SocketAddr;
-use tracing::debug;
+use tokio::net::TcpStream;
+use tracing::{debug, warn};
 
 /// A TCP Portal Outlet listen worker
 ///
@@ -13,16 +16,18 @@ use tracing::debug;
 /// [`TcpTransport::create_outlet`](crate::TcpTransport::create_outlet).
 pub(crate) struct TcpOutletListenWorker {
     registry: TcpRegistry,
-    peer: SocketAddr,
+    targets: TcpOutletTargets,
+    health_check_processor: Option<Address>,
     options: TcpOutletOptions,
 }
 
 impl TcpOutletListenWorker {
     /// Create a new `TcpOutletListenWorker`
-    fn new(registry: TcpRegistry, peer: SocketAddr, options: TcpOutletOptions) -> Self {
+    fn new(registry: TcpRegistry, targets: TcpOutletTargets, options: TcpOutletOptions) -> Self {
         Self {
             registry,
-            peer,
+            targets,
+            health_check_processor: None,
             options,
         }
     }
@@ -31,14 +36,14 @@ impl TcpOutletListenWorker {
         ctx: &Context,
         registry: TcpRegistry,
         address: Address,
-        peer: SocketAddr,
+        targets: TcpOutletTargets,
         options: TcpOutletOptions,
     ) -> Result<()> {
         let access_control = options.incoming_access_control.clone();
 
         options.setup_flow_control_for_outlet_listener(ctx.flow_controls(), &address);
 
-        let worker = Self::new(registry, peer, options);
+        let worker = Self::new(registry, targets, options);
         WorkerBuilder::new(worker)
             .with_address(address)
             .with_incoming_access_control_arc(access_control)
@@ -58,12 +63,27 @@ impl Worker for TcpOutletListenWorker {
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry.add_outlet_listener_worker(&ctx.address());
 
+        if let Some(health_check) = self.options.health_check {
+            let address = TcpOutletHealthCheckProcessor::start(
+                ctx,
+                self.targets.clone(),
+                health_check.interval,
+                health_check.timeout,
+            )
+            .await?;
+            self.health_check_processor = Some(address);
+        }
+
         Ok(())
     }
 
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry.remove_outlet_listener_worker(&ctx.address());
 
+        if let Some(address) = self.health_check_processor.take() {
+            let _ = ctx.stop_processor(address).await;
+        }
+
         Ok(())
     }
 
@@ -80,7 +100,18 @@ impl Worker for TcpOutletListenWorker {
             return Err(TransportError::Protocol.into());
         }
 
-        let addresses = Addresses::generate(PortalType::Outlet);
+        let target = match self.targets.acquire() {
+            Some(target) => target,
+            None => {
+                warn!(
+                    "Outlet at: {} has no healthy target to connect to",
+                    ctx.address()
+                );
+                return Err(TransportError::PeerNotFound.into());
+            }
+        };
+
+        let addresses = PortalAddresses::generate::<TcpStream>(PortalType::Outlet);
 
         self.options
             .setup_flow_control_for_outlet(ctx.flow_controls(), &addresses, &src_addr);
@@ -88,7 +119,7 @@ impl Worker for TcpOutletListenWorker {
         TcpPortalWorker::start_new_outlet(
             ctx,
             self.registry.clone(),
-            self.peer,
+            target,
             return_route.clone(),
             addresses.clone(),
             self.options.incoming_access_control.clone(),
@@ -1,30 +1,32 @@
 use crate::portal::portal_message::MAX_PAYLOAD_SIZE;
+use crate::portal::PortalStream;
 use crate::{PortalInternalMessage, PortalMessage, TcpRegistry};
 use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, Encodable, LocalMessage, Route, TransportMessage};
 use ockam_core::{route, Address, Processor, Result};
 use ockam_node::Context;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
+use tokio::io::AsyncReadExt;
 use tracing::{error, warn};
 
-/// A TCP Portal receiving message processor
+/// A Portal receiving message processor
 ///
-/// TCP Portal receiving message processor are created by
-/// `TcpPortalWorker` after a call is made to
-/// [`TcpPortalWorker::start_receiver`](crate::TcpPortalWorker::start_receiver)
-pub(crate) struct TcpPortalRecvProcessor {
-    registry: TcpRegistry,
+/// Portal receiving message processor are created by
+/// `PortalWorker` after a call is made to
+/// [`PortalWorker::start_receiver`](crate::PortalWorker::start_receiver)
+pub(crate) struct PortalRecvProcessor<S: PortalStream> {
+    /// Registry of the TCP transport, not used by the portals of other transports
+    registry: Option<TcpRegistry>,
     buf: Vec<u8>,
-    read_half: OwnedReadHalf,
+    read_half: S::ReadHalf,
     sender_address: Address,
     onward_route: Route,
 }
 
-impl TcpPortalRecvProcessor {
-    /// Create a new `TcpPortalRecvProcessor`
+impl<S: PortalStream> PortalRecvProcessor<S> {
+    /// Create a new `PortalRecvProcessor`
     pub fn new(
-        registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        registry: Option<TcpRegistry>,
+        read_half: S::ReadHalf,
         sender_address: Address,
         onward_route: Route,
     ) -> Self {
@@ -39,18 +41,21 @@ impl TcpPortalRecvProcessor {
 }
 
 #[async_trait]
-impl Processor for TcpPortalRecvProcessor {
+impl<S: PortalStream> Processor for PortalRecvProcessor<S> {
     type Context = Context;
 
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.registry.add_portal_receiver_processor(&ctx.address());
+        if let Some(registry) = &self.registry {
+            registry.add_portal_receiver_processor(&ctx.address());
+        }
 
         Ok(())
     }
 
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.registry
-            .remove_portal_receiver_processor(&ctx.address());
+        if let Some(registry) = &self.registry {
+            registry.remove_portal_receiver_processor(&ctx.address());
+        }
 
         Ok(())
     }
@@ -58,10 +63,14 @@ impl Processor for TcpPortalRecvProcessor {
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
         self.buf.clear();
 
-        let _len = match self.read_half.read_buf(&mut self.buf).await {
+        let len = match self.read_half.read_buf(&mut self.buf).await {
             Ok(len) => len,
             Err(err) => {
-                error!("Tcp Portal connection read failed with error: {}", err);
+                error!(
+                    "{} Portal connection read failed with error: {}",
+                    S::NAME,
+                    err
+                );
                 return Ok(false);
             }
         };
@@ -76,7 +85,8 @@ impl Processor for TcpPortalRecvProcessor {
                 .await
             {
                 warn!(
-                    "Error notifying Tcp Portal Sender about dropped connection {}",
+                    "Error notifying {} Portal Sender about dropped connection {}",
+                    S::NAME,
                     err
                 );
             }
@@ -91,6 +101,8 @@ impl Processor for TcpPortalRecvProcessor {
             return Ok(false);
         }
 
+        ctx.metrics().record_portal_bytes_in(len);
+
         // Loop just in case buf was extended (should not happen though)
         for chunk in self.buf.chunks(MAX_PAYLOAD_SIZE) {
             let msg = TransportMessage::v1(
@@ -0,0 +1,47 @@
+use core::fmt::Debug;
+use ockam_core::async_trait;
+use ockam_core::compat::net::SocketAddr;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+
+/// A stream connecting a portal to its peer
+///
+/// The stream is split once connected: its read half is read by the portal
+/// receiver processor and its write half is written by the [`PortalWorker`](crate::PortalWorker).
+/// This allows portals to be used over other transports than TCP, a Unix domain socket for example.
+#[async_trait]
+pub trait PortalStream: Sized + Send + 'static {
+    /// Read half of the stream
+    type ReadHalf: AsyncRead + Unpin + Send + 'static;
+    /// Write half of the stream
+    type WriteHalf: AsyncWrite + Unpin + Send + 'static;
+    /// Address of the peer of the stream
+    type Peer: Debug + Clone + Send + Sync + 'static;
+
+    /// Name of the transport, used in the addresses and the logs of the portal workers
+    const NAME: &'static str;
+
+    /// Connect to the target of an outlet
+    async fn connect(peer: &Self::Peer) -> std::io::Result<Self>;
+
+    /// Split the stream into its read and write halves
+    fn into_split(self) -> (Self::ReadHalf, Self::WriteHalf);
+}
+
+#[async_trait]
+impl PortalStream for TcpStream {
+    type ReadHalf = OwnedReadHalf;
+    type WriteHalf = OwnedWriteHalf;
+    type Peer = SocketAddr;
+
+    const NAME: &'static str = "Tcp";
+
+    async fn connect(peer: &Self::Peer) -> std::io::Result<Self> {
+        TcpStream::connect(*peer).await
+    }
+
+    fn into_split(self) -> (Self::ReadHalf, Self::WriteHalf) {
+        TcpStream::into_split(self)
+    }
+}
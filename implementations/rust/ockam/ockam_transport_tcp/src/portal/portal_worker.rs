@@ -1,5 +1,6 @@
-use crate::portal::addresses::{Addresses, PortalType};
-use crate::{portal::TcpPortalRecvProcessor, PortalInternalMessage, PortalMessage, TcpRegistry};
+use crate::portal::TargetConnection;
+use crate::portal::{PortalAddresses, PortalRecvProcessor, PortalStream, PortalType};
+use crate::{PortalInternalMessage, PortalMessage, TcpRegistry};
 use core::time::Duration;
 use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc};
 use ockam_core::{
@@ -10,11 +11,10 @@ use ockam_core::{Any, Result, Route, Routed, Worker};
 use ockam_node::{Context, ProcessorBuilder, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
 use tracing::{debug, info, trace, warn};
 
-/// Enumerate all `TcpPortalWorker` states
+/// Enumerate all `PortalWorker` states
 ///
 /// Possible state transitions are:
 ///
@@ -28,39 +28,50 @@ enum State {
     Initialized,
 }
 
-/// A TCP Portal worker
+/// A Portal worker
 ///
-/// A TCP Portal worker is responsible for managing the life-cycle of
+/// A Portal worker is responsible for managing the life-cycle of
 /// a portal connection and is created by
 /// [`TcpInletListenProcessor::process`](crate::TcpInletListenProcessor)
 /// after a new connection has been accepted.
-pub(crate) struct TcpPortalWorker {
-    registry: TcpRegistry,
+///
+/// The worker is generic over the [`PortalStream`] connecting it to its peer,
+/// so that other transports can reuse the TCP portals.
+pub struct PortalWorker<S: PortalStream> {
+    /// Registry of the TCP transport, not used by the portals of other transports
+    registry: Option<TcpRegistry>,
     state: State,
-    write_half: Option<OwnedWriteHalf>,
-    read_half: Option<OwnedReadHalf>,
-    peer: SocketAddr,
-    addresses: Addresses,
+    write_half: Option<S::WriteHalf>,
+    read_half: Option<S::ReadHalf>,
+    peer: S::Peer,
+    /// Outlet target this portal is connected to, released when the
+    /// worker is dropped
+    target: Option<TargetConnection>,
+    addresses: PortalAddresses,
     remote_route: Option<Route>,
     is_disconnecting: bool,
     portal_type: PortalType,
 }
 
+/// A TCP Portal worker
+pub(crate) type TcpPortalWorker = PortalWorker<TcpStream>;
+
 impl TcpPortalWorker {
-    /// Start a new `TcpPortalWorker` of type [`TypeName::Inlet`]
+    /// Start a new `TcpPortalWorker` of type [`PortalType::Inlet`]
     pub(super) async fn start_new_inlet(
         ctx: &Context,
         registry: TcpRegistry,
         stream: TcpStream,
         peer: SocketAddr,
         ping_route: Route,
-        addresses: Addresses,
+        addresses: PortalAddresses,
         access_control: Arc<dyn IncomingAccessControl>,
     ) -> Result<()> {
         Self::start(
             ctx,
-            registry,
+            Some(registry),
             peer,
+            None,
             State::SendPing { ping_route },
             Some(stream),
             addresses,
@@ -70,19 +81,67 @@ impl TcpPortalWorker {
         .await
     }
 
-    /// Start a new `TcpPortalWorker` of type [`TypeName::Outlet`]
+    /// Start a new `TcpPortalWorker` of type [`PortalType::Outlet`]
     pub(super) async fn start_new_outlet(
         ctx: &Context,
         registry: TcpRegistry,
-        peer: SocketAddr,
+        target: TargetConnection,
         pong_route: Route,
-        addresses: Addresses,
+        addresses: PortalAddresses,
         access_control: Arc<dyn IncomingAccessControl>,
     ) -> Result<()> {
         Self::start(
             ctx,
-            registry,
+            Some(registry),
+            target.socket_addr(),
+            Some(target),
+            State::SendPong { pong_route },
+            None,
+            addresses,
+            PortalType::Outlet,
+            access_control,
+        )
+        .await
+    }
+}
+
+impl<S: PortalStream> PortalWorker<S> {
+    /// Start a new `PortalWorker` of type [`PortalType::Inlet`] for an accepted stream
+    pub async fn start_inlet(
+        ctx: &Context,
+        stream: S,
+        peer: S::Peer,
+        ping_route: Route,
+        addresses: PortalAddresses,
+        access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Result<()> {
+        Self::start(
+            ctx,
+            None,
             peer,
+            None,
+            State::SendPing { ping_route },
+            Some(stream),
+            addresses,
+            PortalType::Inlet,
+            access_control,
+        )
+        .await
+    }
+
+    /// Start a new `PortalWorker` of type [`PortalType::Outlet`] connecting to `peer`
+    pub async fn start_outlet(
+        ctx: &Context,
+        peer: S::Peer,
+        pong_route: Route,
+        addresses: PortalAddresses,
+        access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Result<()> {
+        Self::start(
+            ctx,
+            None,
+            peer,
+            None,
             State::SendPong { pong_route },
             None,
             addresses,
@@ -92,20 +151,22 @@ impl TcpPortalWorker {
         .await
     }
 
-    /// Start a new `TcpPortalWorker`
+    /// Start a new `PortalWorker`
     #[allow(clippy::too_many_arguments)]
     async fn start(
         ctx: &Context,
-        registry: TcpRegistry,
-        peer: SocketAddr,
+        registry: Option<TcpRegistry>,
+        peer: S::Peer,
+        target: Option<TargetConnection>,
         state: State,
-        stream: Option<TcpStream>,
-        addresses: Addresses,
+        stream: Option<S>,
+        addresses: PortalAddresses,
         portal_type: PortalType,
         access_control: Arc<dyn IncomingAccessControl>,
     ) -> Result<()> {
         info!(
-            "Creating new {:?} at internal: {}, remote: {}",
+            "Creating new {} {:?} at internal: {}, remote: {}",
+            S::NAME,
             portal_type.str(),
             addresses.internal,
             addresses.remote
@@ -125,6 +186,7 @@ impl TcpPortalWorker {
             write_half: tx,
             read_half: rx,
             peer,
+            target,
             addresses: addresses.clone(),
             remote_route: None,
             is_disconnecting: false,
@@ -159,16 +221,16 @@ enum DisconnectionReason {
     Remote,
 }
 
-impl TcpPortalWorker {
+impl<S: PortalStream> PortalWorker<S> {
     fn clone_state(&self) -> State {
         self.state.clone()
     }
 
-    /// Start a `TcpPortalRecvProcessor`
+    /// Start a `PortalRecvProcessor`
     async fn start_receiver(&mut self, ctx: &Context, onward_route: Route) -> Result<()> {
         if let Some(rx) = self.read_half.take() {
             let next_hop = onward_route.next()?.clone();
-            let receiver = TcpPortalRecvProcessor::new(
+            let receiver = PortalRecvProcessor::<S>::new(
                 self.registry.clone(),
                 rx,
                 self.addresses.internal.clone(),
@@ -296,9 +358,15 @@ impl TcpPortalWorker {
         .await?;
 
         if self.write_half.is_none() {
-            let stream = TcpStream::connect(self.peer)
-                .await
-                .map_err(TransportError::from)?;
+            let stream = match S::connect(&self.peer).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    if let Some(target) = &self.target {
+                        target.mark_unhealthy();
+                    }
+                    return Err(TransportError::from(err).into());
+                }
+            };
             let (rx, tx) = stream.into_split();
             self.write_half = Some(tx);
             self.read_half = Some(rx);
@@ -319,7 +387,7 @@ impl TcpPortalWorker {
 }
 
 #[async_trait]
-impl Worker for TcpPortalWorker {
+impl<S: PortalStream> Worker for PortalWorker<S> {
     type Context = Context;
     type Message = Any;
 
@@ -338,19 +406,23 @@ impl Worker for TcpPortalWorker {
             }
         }
 
-        self.registry.add_portal_worker(&self.addresses.remote);
+        if let Some(registry) = &self.registry {
+            registry.add_portal_worker(&self.addresses.remote);
+        }
 
         Ok(())
     }
 
     async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
-        self.registry.remove_portal_worker(&self.addresses.remote);
+        if let Some(registry) = &self.registry {
+            registry.remove_portal_worker(&self.addresses.remote);
+        }
 
         Ok(())
     }
 
-    // TcpSendWorker will receive messages from the TcpRouter to send
-    // across the TcpStream to our friend
+    // PortalWorker will receive messages from the other side of the portal
+    // to send across the stream to our friend
     async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
         if self.is_disconnecting {
             return Ok(());
@@ -392,9 +464,10 @@ impl Worker for TcpPortalWorker {
             State::Initialized => {
                 if recipient == self.addresses.internal {
                     trace!(
-                        "{:?} at: {} received internal tcp packet",
+                        "{:?} at: {} received internal {} packet",
                         self.portal_type.str(),
-                        self.addresses.internal
+                        self.addresses.internal,
+                        S::NAME
                     );
 
                     let msg = PortalInternalMessage::decode(msg.payload())?;
@@ -402,7 +475,8 @@ impl Worker for TcpPortalWorker {
                     match msg {
                         PortalInternalMessage::Disconnect => {
                             info!(
-                                "Tcp stream was dropped for {:?} at: {}",
+                                "{} stream was dropped for {:?} at: {}",
+                                S::NAME,
                                 self.portal_type.str(),
                                 self.addresses.internal
                             );
@@ -412,22 +486,23 @@ impl Worker for TcpPortalWorker {
                     }
                 } else {
                     trace!(
-                        "{:?} at: {} received remote tcp packet",
+                        "{:?} at: {} received remote {} packet",
                         self.portal_type.str(),
-                        self.addresses.internal
+                        self.addresses.internal,
+                        S::NAME
                     );
 
-                    // Send to Tcp stream
+                    // Send to the stream
                     let msg = PortalMessage::decode(msg.payload())?;
 
                     match msg {
                         PortalMessage::Payload(payload) => {
                             if let Some(tx) = &mut self.write_half {
                                 match tx.write_all(&payload).await {
-                                    Ok(()) => {}
+                                    Ok(()) => ctx.metrics().record_portal_bytes_out(payload.len()),
                                     Err(err) => {
                                         warn!(
-                                            "Failed to send message to peer {} with error: {}",
+                                            "Failed to send message to peer {:?} with error: {}",
                                             self.peer, err
                                         );
                                         self.start_disconnection(
@@ -1,8 +1,13 @@
 use crate::portal::TcpInletListenProcessor;
 use crate::transport::common::{parse_socket_addr, resolve_peer};
-use crate::{portal::TcpOutletListenWorker, TcpInletOptions, TcpOutletOptions, TcpTransport};
+use crate::{
+    portal::TcpOutletListenWorker, TcpInletOptions, TcpOutletOptions, TcpOutletTargets,
+    TcpTransport,
+};
 use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::vec::Vec;
 use ockam_core::{Address, Result, Route};
+use ockam_transport_core::TransportError;
 
 impl TcpTransport {
     /// Create Tcp Inlet that listens on bind_addr, transforms Tcp stream into Ockam Routable
@@ -84,16 +89,8 @@ impl TcpTransport {
     ) -> Result<()> {
         // Resolve peer address
         let peer_addr = resolve_peer(peer.into())?;
-        TcpOutletListenWorker::start(
-            &self.ctx,
-            self.registry.clone(),
-            address.into(),
-            peer_addr,
-            options,
-        )
-        .await?;
-
-        Ok(())
+        self.create_tcp_outlet(address.into(), peer_addr, options)
+            .await
     }
 
     /// Create Tcp Outlet Listener at address, that connects to peer using Tcp
@@ -103,12 +100,58 @@ impl TcpTransport {
         peer: SocketAddr,
         options: TcpOutletOptions,
     ) -> Result<()> {
-        TcpOutletListenWorker::start(&self.ctx, self.registry.clone(), address, peer, options)
+        self.create_tcp_outlet_with_targets(address, vec![peer], options)
             .await?;
 
         Ok(())
     }
 
+    /// Create Tcp Outlet Listener at address, that distributes new portal connections
+    /// across several targets, following the strategy and health check set in `options`.
+    /// The returned [`TcpOutletTargets`] reports the current state of each target.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpOutletOptions, TcpOutletStrategy, TcpTransport};
+    /// # use core::time::Duration;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let targets = vec!["127.0.0.1:5000".parse().unwrap(), "127.0.0.1:5001".parse().unwrap()];
+    /// let options = TcpOutletOptions::new()
+    ///     .with_strategy(TcpOutletStrategy::LeastConnections)
+    ///     .with_health_check(Duration::from_secs(10), Duration::from_secs(2));
+    ///
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let targets = tcp
+    ///     .create_tcp_outlet_with_targets("outlet".into(), targets, options)
+    ///     .await?;
+    /// # tcp.stop_outlet("outlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_tcp_outlet_with_targets(
+        &self,
+        address: Address,
+        targets: Vec<SocketAddr>,
+        options: TcpOutletOptions,
+    ) -> Result<TcpOutletTargets> {
+        if targets.is_empty() {
+            return Err(TransportError::InvalidAddress.into());
+        }
+
+        let targets =
+            TcpOutletTargets::new(targets, options.strategy, options.health_check.is_some());
+        TcpOutletListenWorker::start(
+            &self.ctx,
+            self.registry.clone(),
+            address,
+            targets.clone(),
+            options,
+        )
+        .await?;
+
+        Ok(targets)
+    }
+
     /// Stop outlet at addr
     /// ```rust
     /// use ockam_transport_tcp::{TcpOutletOptions, TcpTransport};
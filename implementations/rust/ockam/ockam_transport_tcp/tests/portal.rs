@@ -7,7 +7,8 @@ use ockam_core::compat::rand::random;
 use ockam_core::{route, Result};
 use ockam_node::Context;
 use ockam_transport_tcp::{
-    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpTransport,
+    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpOutletStrategy,
+    TcpTransport,
 };
 
 const LENGTH: usize = 32;
@@ -238,3 +239,58 @@ async fn portal__tcp_connection_with_invalid_message_flow__should_not_succeed(
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__several_targets__should_skip_unhealthy_target(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let listener1 = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let listener2 = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unreachable = {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    };
+
+    let options = TcpOutletOptions::new()
+        .with_strategy(TcpOutletStrategy::RoundRobin)
+        .with_health_check(Duration::from_millis(100), Duration::from_millis(100));
+    let targets = tcp
+        .create_tcp_outlet_with_targets(
+            "outlet".into(),
+            vec![
+                listener1.local_addr().unwrap(),
+                unreachable,
+                listener2.local_addr().unwrap(),
+            ],
+            options,
+        )
+        .await?;
+
+    let (inlet_saddr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    let healthy: Vec<bool> = targets.status().iter().map(|t| t.healthy).collect();
+    assert_eq!(healthy, vec![true, false, true]);
+
+    // The health check connections were accepted by the listeners as well,
+    // only count the connections that carry a payload
+    for listener in [listener1, listener2] {
+        let payload = generate_binary();
+        let mut stream = TcpStream::connect(&inlet_saddr).await.unwrap();
+        write_binary(&mut stream, payload).await;
+
+        loop {
+            let (mut accepted, _) = listener.accept().await.unwrap();
+            let mut received = [0u8; LENGTH];
+            if accepted.read_exact(&mut received).await.is_ok() {
+                assert_eq!(received, payload);
+                break;
+            }
+        }
+    }
+
+    ctx.stop().await
+}
@@ -10,11 +10,16 @@
 use ockam_core::TransportType;
 
 pub use hole_puncher::{PunchError, UdpHolePuncher};
+pub use options::*;
+pub use portal::options::*;
+pub use portal::{UdpPortalMessage, MAX_DATAGRAM_SIZE};
 pub use rendezvous_service::UdpRendezvousService;
 pub use transport::UdpTransport;
 pub use transport::UdpTransportExtension;
 
 mod hole_puncher;
+mod options;
+mod portal;
 mod rendezvous_service;
 mod router;
 mod transport;
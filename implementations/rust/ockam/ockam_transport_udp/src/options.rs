@@ -0,0 +1,134 @@
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
+use ockam_core::{Address, AllowAll, OutgoingAccessControl};
+
+/// Default maximum size of the datagrams sent by the transport. Small enough
+/// to fit into the path MTU of most networks, including tunnels
+pub const DEFAULT_UDP_DATAGRAM_SIZE: usize = 1200;
+
+/// Default duration after which a message which was only partially received is dropped
+pub const DEFAULT_UDP_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default duration after which a message which was not acknowledged is sent again
+pub const DEFAULT_UDP_RETRANSMISSION_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Default number of times a message which was not acknowledged is sent again
+pub const DEFAULT_UDP_MAX_RETRANSMISSIONS: u8 = 3;
+
+/// Options for a UDP transport
+///
+/// Messages larger than the maximum datagram size are split into several
+/// datagrams and reassembled by the receiver. Unless retransmission is
+/// disabled, the receiver acknowledges each reassembled message, and the
+/// messages which are not acknowledged in time are sent again.
+///
+/// By default the received messages can be delivered to any worker, unless the
+/// listeners of the transport are marked as Producers with [`UdpTransportOptions::as_producer`].
+#[derive(Clone, Debug)]
+pub struct UdpTransportOptions {
+    pub(crate) max_datagram_size: usize,
+    pub(crate) reassembly_timeout: Duration,
+    pub(crate) retransmission: Option<UdpRetransmission>,
+    pub(crate) flow_control_id: Option<FlowControlId>,
+}
+
+/// Retransmission settings of a UDP transport
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct UdpRetransmission {
+    pub(crate) timeout: Duration,
+    pub(crate) max_retransmissions: u8,
+}
+
+impl UdpTransportOptions {
+    /// Default constructor, with retransmission enabled
+    pub fn new() -> Self {
+        Self {
+            max_datagram_size: DEFAULT_UDP_DATAGRAM_SIZE,
+            reassembly_timeout: DEFAULT_UDP_REASSEMBLY_TIMEOUT,
+            retransmission: Some(UdpRetransmission {
+                timeout: DEFAULT_UDP_RETRANSMISSION_TIMEOUT,
+                max_retransmissions: DEFAULT_UDP_MAX_RETRANSMISSIONS,
+            }),
+            flow_control_id: None,
+        }
+    }
+
+    /// Set the maximum size of the datagrams sent by the transport
+    ///
+    /// The messages received from peers using smaller datagrams are dropped,
+    /// since they are split into more fragments than expected
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.max_datagram_size = max_datagram_size;
+        self
+    }
+
+    /// Drop a partially received message after this duration
+    pub fn with_reassembly_timeout(mut self, reassembly_timeout: Duration) -> Self {
+        self.reassembly_timeout = reassembly_timeout;
+        self
+    }
+
+    /// Send a message again if it was not acknowledged after `timeout`,
+    /// at most `max_retransmissions` times
+    pub fn with_retransmission(mut self, timeout: Duration, max_retransmissions: u8) -> Self {
+        self.retransmission = Some(UdpRetransmission {
+            timeout,
+            max_retransmissions,
+        });
+        self
+    }
+
+    /// Send each message once, without asking the receiver for an acknowledgment
+    pub fn without_retransmission(mut self) -> Self {
+        self.retransmission = None;
+        self
+    }
+
+    /// Mark the listeners of this transport as Producers for the given [`FlowControlId`].
+    /// The messages they receive are then only delivered to the Consumers of that [`FlowControlId`]
+    pub fn as_producer(mut self, flow_control_id: &FlowControlId) -> Self {
+        self.flow_control_id = Some(flow_control_id.clone());
+        self
+    }
+
+    /// [`FlowControlId`] of the listeners of this transport, if they are Producers
+    pub fn flow_control_id(&self) -> Option<&FlowControlId> {
+        self.flow_control_id.as_ref()
+    }
+}
+
+impl UdpTransportOptions {
+    pub(crate) fn setup_flow_control(
+        &self,
+        flow_controls: &FlowControls,
+        listener_address: &Address,
+        sender_internal_address: &Address,
+    ) {
+        if let Some(flow_control_id) = &self.flow_control_id {
+            flow_controls.add_producer(listener_address.clone(), flow_control_id, None, vec![]);
+            // The listener reports the received acknowledgments to its sender
+            flow_controls.add_consumer(sender_internal_address.clone(), flow_control_id);
+        }
+    }
+
+    pub(crate) fn create_listener_access_control(
+        &self,
+        flow_controls: &FlowControls,
+    ) -> Arc<dyn OutgoingAccessControl> {
+        match &self.flow_control_id {
+            Some(flow_control_id) => Arc::new(FlowControlOutgoingAccessControl::new(
+                flow_controls,
+                flow_control_id.clone(),
+                None,
+            )),
+            None => Arc::new(AllowAll),
+        }
+    }
+}
+
+impl Default for UdpTransportOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,156 @@
+use crate::portal::addresses::{Addresses, PortalType};
+use crate::portal::{UdpPortalInternalMessage, UdpPortalWorker, MAX_DATAGRAM_SIZE};
+use crate::UdpInletOptions;
+use ockam_core::{async_trait, Address, Processor, Result, Route};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::UdpSocket;
+use tracing::{debug, error, warn};
+
+/// Sessions of an inlet, indexed by the address of the UDP peer
+pub(crate) type UdpInletSessions = Arc<Mutex<HashMap<SocketAddr, Address>>>;
+
+/// A UDP Portal Inlet listen processor
+///
+/// UDP Portal Inlet listen processors are created by `UdpTransport`
+/// after a call is made to
+/// [`UdpTransport::create_inlet`](crate::UdpTransport::create_inlet).
+///
+/// Every UDP peer sending datagrams to the inlet socket gets its own
+/// session, handled by a [`UdpPortalWorker`], which is connected to
+/// its own outlet session on the other side of the portal. The number
+/// of sessions is bounded by [`UdpInletOptions::with_max_sessions`].
+pub(crate) struct UdpInletListenProcessor {
+    socket: Arc<UdpSocket>,
+    buf: Vec<u8>,
+    outlet_listener_route: Route,
+    options: UdpInletOptions,
+    sessions: UdpInletSessions,
+}
+
+impl UdpInletListenProcessor {
+    pub fn new(socket: UdpSocket, outlet_listener_route: Route, options: UdpInletOptions) -> Self {
+        Self {
+            socket: Arc::new(socket),
+            buf: vec![0; MAX_DATAGRAM_SIZE],
+            outlet_listener_route,
+            options,
+            sessions: Default::default(),
+        }
+    }
+
+    /// Start a new `UdpInletListenProcessor`
+    pub(crate) async fn start(
+        ctx: &Context,
+        outlet_listener_route: Route,
+        addr: SocketAddr,
+        options: UdpInletOptions,
+    ) -> Result<(SocketAddr, Address)> {
+        let processor_address = Address::random_tagged("UdpInletListenProcessor");
+
+        debug!("Binding UdpInletListenProcessor to {}", addr);
+        let socket = match UdpSocket::bind(addr).await {
+            Ok(socket) => socket,
+            Err(err) => {
+                error!(%addr, %err, "could not bind to address");
+                return Err(TransportError::from(err).into());
+            }
+        };
+        let socket_addr = socket.local_addr().map_err(TransportError::from)?;
+        let processor = Self::new(socket, outlet_listener_route, options);
+
+        ctx.start_processor(processor_address.clone(), processor)
+            .await?;
+
+        Ok((socket_addr, processor_address))
+    }
+
+    /// Return the session handling the datagrams of a peer, creating it if needed
+    async fn session(&self, ctx: &Context, peer: SocketAddr) -> Result<Address> {
+        {
+            let sessions = self.sessions.lock().unwrap();
+            if let Some(internal) = sessions.get(&peer) {
+                return Ok(internal.clone());
+            }
+            if sessions.len() >= self.options.max_sessions {
+                return Err(TransportError::Capacity.into());
+            }
+        }
+
+        let addresses = Addresses::generate(PortalType::Inlet);
+        let outlet_listener_route = self.outlet_listener_route.clone();
+
+        self.options.setup_flow_control(
+            ctx.flow_controls(),
+            &addresses,
+            outlet_listener_route.next()?,
+        );
+
+        UdpPortalWorker::start_new_inlet(
+            ctx,
+            self.socket.clone(),
+            peer,
+            outlet_listener_route,
+            addresses.clone(),
+            self.options.incoming_access_control.clone(),
+            self.options.idle_timeout,
+            self.sessions.clone(),
+        )
+        .await?;
+
+        debug!("Created a new UDP Inlet session for {}", peer);
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(peer, addresses.internal.clone());
+
+        Ok(addresses.internal)
+    }
+}
+
+#[async_trait]
+impl Processor for UdpInletListenProcessor {
+    type Context = Context;
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let sessions: Vec<Address> = self.sessions.lock().unwrap().values().cloned().collect();
+        for session in sessions {
+            // The session may have already stopped itself
+            let _ = ctx.stop_worker(session).await;
+        }
+
+        Ok(())
+    }
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        let (len, peer) = match self.socket.recv_from(&mut self.buf).await {
+            Ok(res) => res,
+            Err(err) => {
+                warn!("Udp Inlet failed to receive a datagram: {}", err);
+                return Ok(true);
+            }
+        };
+
+        // A datagram which can't be forwarded is dropped, as if it was lost,
+        // and the inlet keeps serving the other peers
+        let session = match self.session(ctx, peer).await {
+            Ok(session) => session,
+            Err(err) => {
+                warn!("Dropping a datagram from {}: {}", peer, err);
+                return Ok(true);
+            }
+        };
+        let datagram = UdpPortalInternalMessage::Datagram(self.buf[..len].to_vec());
+        if let Err(err) = ctx.send(session, datagram).await {
+            // The session was closed in the meantime, the next datagram
+            // from this peer will create a new one
+            warn!("Dropping a datagram from {}: {}", peer, err);
+            self.sessions.lock().unwrap().remove(&peer);
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,13 @@
+mod addresses;
+mod inlet_listener;
+pub mod options;
+mod outlet_listener;
+mod portal_message;
+mod portal_receiver;
+mod portal_worker;
+
+pub(crate) use inlet_listener::*;
+pub(crate) use outlet_listener::*;
+pub use portal_message::*;
+pub(crate) use portal_receiver::*;
+pub(crate) use portal_worker::*;
@@ -0,0 +1,29 @@
+use ockam_core::compat::vec::Vec;
+use ockam_core::Message;
+use serde::{Deserialize, Serialize};
+
+/// A command message type for a UDP Portal
+#[derive(Serialize, Deserialize, Message, Debug)]
+pub enum UdpPortalMessage {
+    /// First message that Inlet sends to the Outlet
+    Ping,
+    /// First message that Outlet sends to the Inlet
+    Pong,
+    /// Message to indicate that the session between the Inlet and the
+    /// Outlet was closed after being idle
+    Disconnect,
+    /// Message with a single datagram
+    Payload(Vec<u8>),
+}
+
+/// An internal message type for a UDP Portal
+#[derive(Serialize, Deserialize, Message, Clone)]
+pub(crate) enum UdpPortalInternalMessage {
+    /// A datagram was received from the peer
+    Datagram(Vec<u8>),
+    /// Check if the session has been idle for too long
+    IdleCheck,
+}
+
+/// Maximum size of a received datagram
+pub const MAX_DATAGRAM_SIZE: usize = 64 * 1024;
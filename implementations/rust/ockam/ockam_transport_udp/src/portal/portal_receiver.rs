@@ -0,0 +1,59 @@
+use crate::portal::{UdpPortalInternalMessage, MAX_DATAGRAM_SIZE};
+use ockam_core::{async_trait, Address, Processor, Result};
+use ockam_node::Context;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+use tracing::{trace, warn};
+
+/// A UDP Portal receiving processor
+///
+/// UDP Portal receiving processors are created by an outlet
+/// `UdpPortalWorker` to receive the datagrams sent back by the
+/// target on the socket of the outlet session.
+pub(crate) struct UdpPortalRecvProcessor {
+    buf: Vec<u8>,
+    socket: Arc<UdpSocket>,
+    peer: SocketAddr,
+    sender_address: Address,
+}
+
+impl UdpPortalRecvProcessor {
+    /// Create a new `UdpPortalRecvProcessor`
+    pub fn new(socket: Arc<UdpSocket>, peer: SocketAddr, sender_address: Address) -> Self {
+        Self {
+            buf: vec![0; MAX_DATAGRAM_SIZE],
+            socket,
+            peer,
+            sender_address,
+        }
+    }
+}
+
+#[async_trait]
+impl Processor for UdpPortalRecvProcessor {
+    type Context = Context;
+
+    async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+        let (len, from) = match self.socket.recv_from(&mut self.buf).await {
+            Ok(res) => res,
+            Err(err) => {
+                warn!("Udp Outlet failed to receive a datagram: {}", err);
+                return Ok(true);
+            }
+        };
+
+        if from != self.peer {
+            trace!("Ignoring a datagram received from {}", from);
+            return Ok(true);
+        }
+
+        ctx.send(
+            self.sender_address.clone(),
+            UdpPortalInternalMessage::Datagram(self.buf[..len].to_vec()),
+        )
+        .await?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,448 @@
+use crate::portal::addresses::{Addresses, PortalType};
+use crate::portal::{
+    UdpInletSessions, UdpPortalInternalMessage, UdpPortalMessage, UdpPortalRecvProcessor,
+};
+use core::time::Duration;
+use ockam_core::{
+    async_trait, Address, AllowAll, AllowOnwardAddress, AllowSourceAddresses, Any, Decodable,
+    DenyAll, IncomingAccessControl, Mailbox, Mailboxes, Result, Route, Routed, Worker,
+};
+use ockam_node::{Context, DelayedEvent, ProcessorBuilder, WorkerBuilder};
+use ockam_transport_core::TransportError;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::net::UdpSocket;
+use tracing::{debug, info, trace, warn};
+
+/// Maximum number of datagrams kept by an inlet session while waiting
+/// for the outlet session to be created
+const MAX_PENDING_DATAGRAMS: usize = 64;
+
+/// Enumerate all `UdpPortalWorker` states
+///
+/// Possible state transitions are:
+///
+/// `Outlet`: `SendPong` -> `Initialized`
+/// `Inlet`: `SendPing` -> `ReceivePong` -> `Initialized`
+#[derive(Clone)]
+enum State {
+    SendPing { ping_route: Route },
+    SendPong { pong_route: Route },
+    ReceivePong,
+    Initialized,
+}
+
+/// A UDP Portal worker
+///
+/// A UDP Portal worker manages a single session of a portal, which
+/// is the exchange of datagrams with one UDP peer.  The session is
+/// closed, on both sides of the portal, when no datagram was exchanged
+/// during the idle timeout.
+pub(crate) struct UdpPortalWorker {
+    state: State,
+    socket: Option<Arc<UdpSocket>>,
+    peer: SocketAddr,
+    addresses: Addresses,
+    remote_route: Option<Route>,
+    /// Datagrams received before the outlet session was created
+    pending: Vec<Vec<u8>>,
+    last_activity: Instant,
+    idle_timeout: Duration,
+    idle_check: DelayedEvent<UdpPortalInternalMessage>,
+    /// Sessions of the inlet, `None` for an outlet
+    sessions: Option<UdpInletSessions>,
+    portal_type: PortalType,
+}
+
+impl UdpPortalWorker {
+    /// Start a new `UdpPortalWorker` of type [`PortalType::Inlet`]
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn start_new_inlet(
+        ctx: &Context,
+        socket: Arc<UdpSocket>,
+        peer: SocketAddr,
+        ping_route: Route,
+        addresses: Addresses,
+        access_control: Arc<dyn IncomingAccessControl>,
+        idle_timeout: Duration,
+        sessions: UdpInletSessions,
+    ) -> Result<()> {
+        // Datagrams are sent to the session by the inlet listener
+        let source = ctx.address();
+        Self::start(
+            ctx,
+            peer,
+            State::SendPing { ping_route },
+            Some(socket),
+            addresses,
+            PortalType::Inlet,
+            access_control,
+            idle_timeout,
+            Some(sessions),
+            source,
+        )
+        .await
+    }
+
+    /// Start a new `UdpPortalWorker` of type [`PortalType::Outlet`]
+    pub(super) async fn start_new_outlet(
+        ctx: &Context,
+        peer: SocketAddr,
+        pong_route: Route,
+        addresses: Addresses,
+        access_control: Arc<dyn IncomingAccessControl>,
+        idle_timeout: Duration,
+    ) -> Result<()> {
+        // Datagrams are sent to the session by its receiver
+        let source = addresses.receiver.clone();
+        Self::start(
+            ctx,
+            peer,
+            State::SendPong { pong_route },
+            None,
+            addresses,
+            PortalType::Outlet,
+            access_control,
+            idle_timeout,
+            None,
+            source,
+        )
+        .await
+    }
+
+    /// Start a new `UdpPortalWorker`
+    #[allow(clippy::too_many_arguments)]
+    async fn start(
+        ctx: &Context,
+        peer: SocketAddr,
+        state: State,
+        socket: Option<Arc<UdpSocket>>,
+        addresses: Addresses,
+        portal_type: PortalType,
+        access_control: Arc<dyn IncomingAccessControl>,
+        idle_timeout: Duration,
+        sessions: Option<UdpInletSessions>,
+        source: Address,
+    ) -> Result<()> {
+        info!(
+            "Creating new {:?} session for {} at internal: {}, remote: {}",
+            portal_type.str(),
+            peer,
+            addresses.internal,
+            addresses.remote
+        );
+
+        let idle_check = DelayedEvent::create(
+            ctx,
+            addresses.internal.clone(),
+            UdpPortalInternalMessage::IdleCheck,
+        )
+        .await?;
+
+        let internal_mailbox = Mailbox::new(
+            addresses.internal.clone(),
+            Arc::new(AllowSourceAddresses(vec![source, idle_check.address()])),
+            Arc::new(DenyAll),
+        );
+
+        let remote_mailbox = Mailbox::new(
+            addresses.remote.clone(),
+            access_control,
+            Arc::new(AllowAll), // FIXME: @ac Allow to respond anywhere using return_route
+        );
+
+        let worker = Self {
+            state,
+            socket,
+            peer,
+            addresses,
+            remote_route: None,
+            pending: vec![],
+            last_activity: Instant::now(),
+            idle_timeout,
+            idle_check,
+            sessions,
+            portal_type,
+        };
+
+        WorkerBuilder::new(worker)
+            .with_mailboxes(Mailboxes::new(internal_mailbox, vec![remote_mailbox]))
+            .start(ctx)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl UdpPortalWorker {
+    fn clone_state(&self) -> State {
+        self.state.clone()
+    }
+
+    /// Bind the socket of an outlet session and start its `UdpPortalRecvProcessor`
+    async fn start_receiver(&mut self, ctx: &Context) -> Result<()> {
+        let bind_addr = match self.peer {
+            SocketAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+            SocketAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+        };
+        let socket = Arc::new(
+            UdpSocket::bind(bind_addr)
+                .await
+                .map_err(TransportError::from)?,
+        );
+        self.socket = Some(socket.clone());
+
+        let receiver =
+            UdpPortalRecvProcessor::new(socket, self.peer, self.addresses.internal.clone());
+
+        ProcessorBuilder::new(receiver)
+            .with_address(self.addresses.receiver.clone())
+            .with_outgoing_access_control(AllowOnwardAddress(self.addresses.internal.clone()))
+            .start(ctx)
+            .await
+    }
+
+    /// Send a datagram received from the peer to the other side of the portal
+    async fn send_to_remote(
+        &self,
+        ctx: &Context,
+        remote_route: Route,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let len = data.len();
+        ctx.send_from_address(
+            remote_route,
+            UdpPortalMessage::Payload(data),
+            self.addresses.remote.clone(),
+        )
+        .await?;
+        ctx.metrics().record_portal_bytes_in(len);
+
+        Ok(())
+    }
+
+    /// Send a datagram received from the other side of the portal to the peer
+    async fn send_to_peer(&self, ctx: &Context, data: &[u8]) -> Result<()> {
+        let socket = self
+            .socket
+            .as_ref()
+            .ok_or(TransportError::PortalInvalidState)?;
+        match socket.send_to(data, self.peer).await {
+            Ok(len) => ctx.metrics().record_portal_bytes_out(len),
+            // UDP is unreliable anyway, the session is kept open
+            Err(err) => warn!("Failed to send datagram to peer {}: {}", self.peer, err),
+        }
+
+        Ok(())
+    }
+
+    /// Close the session, notifying the other side of the portal if needed
+    async fn close(&mut self, ctx: &Context, notify_remote: bool) -> Result<()> {
+        if notify_remote {
+            if let Some(remote_route) = self.remote_route.take() {
+                // The other side may have already closed its own session
+                if let Err(err) = ctx
+                    .send_from_address(
+                        remote_route,
+                        UdpPortalMessage::Disconnect,
+                        self.addresses.remote.clone(),
+                    )
+                    .await
+                {
+                    debug!(
+                        "Could not notify the other side about the session closing: {}",
+                        err
+                    );
+                }
+            }
+        }
+
+        if let PortalType::Outlet = self.portal_type {
+            // The receiver may not have been started yet
+            let _ = ctx.stop_processor(self.addresses.receiver.clone()).await;
+        }
+
+        ctx.stop_worker(self.addresses.internal.clone()).await?;
+
+        info!(
+            "{:?} session for {} at: {} was closed",
+            self.portal_type.str(),
+            self.peer,
+            self.addresses.internal
+        );
+
+        Ok(())
+    }
+
+    async fn handle_send_ping(&self, ctx: &Context, ping_route: Route) -> Result<State> {
+        // Force creation of an Outlet session on the other side
+        ctx.send_from_address(
+            ping_route,
+            UdpPortalMessage::Ping,
+            self.addresses.remote.clone(),
+        )
+        .await?;
+
+        debug!("Inlet at: {} sent ping", self.addresses.internal);
+
+        Ok(State::ReceivePong)
+    }
+
+    async fn handle_send_pong(&mut self, ctx: &Context, pong_route: Route) -> Result<State> {
+        self.start_receiver(ctx).await?;
+
+        // Respond to Inlet
+        ctx.send_from_address(
+            pong_route.clone(),
+            UdpPortalMessage::Pong,
+            self.addresses.remote.clone(),
+        )
+        .await?;
+
+        debug!("Outlet at: {} sent pong", self.addresses.internal);
+
+        self.remote_route = Some(pong_route);
+        Ok(State::Initialized)
+    }
+
+    async fn handle_internal(
+        &mut self,
+        ctx: &Context,
+        msg: UdpPortalInternalMessage,
+    ) -> Result<()> {
+        match msg {
+            UdpPortalInternalMessage::Datagram(data) => {
+                self.last_activity = Instant::now();
+                match (&self.state, self.remote_route.clone()) {
+                    (State::Initialized, Some(remote_route)) => {
+                        self.send_to_remote(ctx, remote_route, data).await?;
+                    }
+                    _ if self.pending.len() < MAX_PENDING_DATAGRAMS => {
+                        self.pending.push(data);
+                    }
+                    _ => {
+                        warn!(
+                            "Dropping a datagram from {}, the outlet session is not ready",
+                            self.peer
+                        );
+                    }
+                }
+            }
+            UdpPortalInternalMessage::IdleCheck => {
+                let idle = self.last_activity.elapsed();
+                if idle >= self.idle_timeout {
+                    debug!(
+                        "{:?} session for {} was idle for {:?}",
+                        self.portal_type.str(),
+                        self.peer,
+                        idle
+                    );
+                    self.close(ctx, true).await?;
+                } else {
+                    self.idle_check.schedule(self.idle_timeout - idle).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for UdpPortalWorker {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let state = self.clone_state();
+
+        match state {
+            State::SendPing { ping_route } => {
+                self.state = self.handle_send_ping(ctx, ping_route).await?;
+            }
+            State::SendPong { pong_route } => {
+                self.state = self.handle_send_pong(ctx, pong_route).await?;
+            }
+            State::ReceivePong | State::Initialized => {
+                return Err(TransportError::PortalInvalidState.into())
+            }
+        }
+
+        self.idle_check.schedule(self.idle_timeout).await?;
+
+        Ok(())
+    }
+
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        self.idle_check.cancel();
+
+        // Let the inlet create a new session for the next datagram of this peer
+        if let Some(sessions) = &self.sessions {
+            let mut sessions = sessions.lock().unwrap();
+            if sessions.get(&self.peer) == Some(&self.addresses.internal) {
+                sessions.remove(&self.peer);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        // Remove our own address from the route so the other end
+        // knows what to do with the incoming message
+        let mut onward_route = msg.onward_route();
+        let recipient = onward_route.step()?;
+
+        let return_route = msg.return_route();
+
+        if onward_route.next().is_ok() {
+            return Err(TransportError::UnknownRoute.into());
+        }
+
+        if recipient == self.addresses.internal {
+            trace!(
+                "{:?} at: {} received internal udp message",
+                self.portal_type.str(),
+                self.addresses.internal
+            );
+            let msg = UdpPortalInternalMessage::decode(msg.payload())?;
+            return self.handle_internal(ctx, msg).await;
+        }
+
+        trace!(
+            "{:?} at: {} received remote udp message",
+            self.portal_type.str(),
+            self.addresses.internal
+        );
+        let msg = UdpPortalMessage::decode(msg.payload())?;
+
+        match (self.clone_state(), msg) {
+            (State::ReceivePong, UdpPortalMessage::Pong) => {
+                debug!("Inlet at: {} received pong", self.addresses.internal);
+
+                self.remote_route = Some(return_route.clone());
+                self.state = State::Initialized;
+
+                for data in core::mem::take(&mut self.pending) {
+                    self.send_to_remote(ctx, return_route.clone(), data).await?;
+                }
+            }
+            (State::Initialized, UdpPortalMessage::Payload(data)) => {
+                self.last_activity = Instant::now();
+                self.send_to_peer(ctx, &data).await?;
+            }
+            (State::ReceivePong | State::Initialized, UdpPortalMessage::Disconnect) => {
+                self.close(ctx, false).await?;
+            }
+            (State::ReceivePong | State::Initialized, _) => {
+                return Err(TransportError::Protocol.into());
+            }
+            (State::SendPing { .. } | State::SendPong { .. }, _) => {
+                return Err(TransportError::PortalInvalidState.into());
+            }
+        }
+
+        Ok(())
+    }
+}
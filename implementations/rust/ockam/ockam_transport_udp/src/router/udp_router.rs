@@ -1,6 +1,7 @@
 use crate::router::messages::{UdpRouterRequest, UdpRouterResponse};
 use crate::router::UdpRouterHandle;
-use crate::workers::{TransportMessageCodec, UdpListenProcessor, UdpSendWorker};
+use crate::workers::{UdpDatagramCodec, UdpListenProcessor, UdpSendWorker};
+use crate::UdpTransportOptions;
 use futures_util::StreamExt;
 use ockam_core::{
     async_trait, Address, AllowAll, Any, Decodable, DenyAll, LocalMessage, Mailbox, Mailboxes,
@@ -38,11 +39,15 @@ pub(crate) struct UdpRouter {
     api_addr: Address,
     /// Sender for 'client' messages
     client_sender: Address,
+    options: UdpTransportOptions,
 }
 
 impl UdpRouter {
     /// Create and register a new UDP router with the node context
-    pub(crate) async fn register(ctx: &Context) -> Result<UdpRouterHandle> {
+    pub(crate) async fn register(
+        ctx: &Context,
+        options: UdpTransportOptions,
+    ) -> Result<UdpRouterHandle> {
         // This context is only used to start workers, doesn't need to send nor receive messages
         let child_ctx = ctx
             .new_detached(
@@ -62,6 +67,7 @@ impl UdpRouter {
         let client_sender = Self::create_sender_listener(
             &child_ctx,
             SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 0),
+            &options,
         )
         .await?;
 
@@ -70,6 +76,7 @@ impl UdpRouter {
             main_addr: main_addr.clone(),
             api_addr: api_addr.clone(),
             client_sender,
+            options,
         };
 
         let main_mailbox = Mailbox::new(
@@ -104,7 +111,11 @@ impl UdpRouter {
     /// Create a sender, listener pair for the given socket address.
     ///
     /// Returns the address of the created sender.
-    async fn create_sender_listener(ctx: &Context, local_addr: SocketAddr) -> Result<Address> {
+    async fn create_sender_listener(
+        ctx: &Context,
+        local_addr: SocketAddr,
+        options: &UdpTransportOptions,
+    ) -> Result<Address> {
         // This transport only supports IPv4
         if !local_addr.is_ipv4() {
             error!(local_addr = %local_addr, "This transport only supprts IPv4");
@@ -117,18 +128,35 @@ impl UdpRouter {
             .map_err(|_| TransportError::InvalidAddress)?;
 
         // Split socket into sink and stream
-        let (sink, stream) = UdpFramed::new(socket, TransportMessageCodec).split();
+        let (sink, stream) = UdpFramed::new(socket, UdpDatagramCodec).split();
 
         debug!("Creating new sender and listener for {}", local_addr);
 
-        // Create sender
         let sender_addr = Address::random_tagged("UdpSendWorker");
-        let sender = UdpSendWorker::new(sink);
-        // FIXME: @ac
-        ctx.start_worker(sender_addr.clone(), sender).await?;
+        let sender_internal_addr = Address::random_tagged("UdpSendWorker.internal");
+        let listener_addr = Address::random_tagged("UdpListenProcessor");
+
+        // Create sender
+        UdpSendWorker::start(
+            ctx,
+            sink,
+            sender_addr.clone(),
+            sender_internal_addr.clone(),
+            listener_addr.clone(),
+            options.clone(),
+        )
+        .await?;
 
         // Create listener
-        UdpListenProcessor::start(ctx, stream, sender_addr.clone()).await?;
+        UdpListenProcessor::start(
+            ctx,
+            stream,
+            listener_addr,
+            sender_addr.clone(),
+            sender_internal_addr,
+            options,
+        )
+        .await?;
 
         Ok(sender_addr)
     }
@@ -163,7 +191,8 @@ impl Worker for UdpRouter {
             trace!("handle_message() API_ADDR: msg = {:?}", msg);
             match msg {
                 UdpRouterRequest::Listen { local_addr } => {
-                    let res = Self::create_sender_listener(&self.ctx, local_addr).await;
+                    let res =
+                        Self::create_sender_listener(&self.ctx, local_addr, &self.options).await;
                     let res = res.map(|_| ());
                     ctx.send_from_address(return_route, UdpRouterResponse::Listen(res), msg_addr)
                         .await?;
@@ -1,7 +1,11 @@
+use crate::portal::{UdpInletListenProcessor, UdpOutletListenWorker};
 use crate::router::{UdpRouter, UdpRouterHandle};
-use ockam_core::{async_trait, Result};
+use crate::{UdpInletOptions, UdpOutletOptions, UdpTransportOptions};
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::{async_trait, Address, AsyncTryClone, Result, Route};
 use ockam_node::{Context, HasContext};
 use ockam_transport_core::TransportError;
+use std::net::{SocketAddr, ToSocketAddrs};
 
 /// High level management interface for UDP transport
 ///
@@ -9,14 +13,49 @@ use ockam_transport_core::TransportError;
 ///
 /// This transport only supports IPv4.
 pub struct UdpTransport {
+    ctx: Context,
     router_handle: UdpRouterHandle,
+    flow_control_id: Option<FlowControlId>,
 }
 
 impl UdpTransport {
     /// Create a new UDP transport for the current node
     pub async fn create(ctx: &Context) -> Result<UdpTransport> {
-        let router_handle = UdpRouter::register(ctx).await?;
-        Ok(Self { router_handle })
+        Self::create_with_options(ctx, UdpTransportOptions::new()).await
+    }
+
+    /// Create a new UDP transport for the current node, with custom fragmentation
+    /// and retransmission settings
+    ///
+    /// ```rust
+    /// use ockam_transport_udp::{UdpTransport, UdpTransportOptions};
+    /// # use core::time::Duration;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let options = UdpTransportOptions::new()
+    ///     .with_max_datagram_size(1400)
+    ///     .with_retransmission(Duration::from_millis(200), 5);
+    /// let udp = UdpTransport::create_with_options(&ctx, options).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_with_options(
+        ctx: &Context,
+        options: UdpTransportOptions,
+    ) -> Result<UdpTransport> {
+        let flow_control_id = options.flow_control_id().cloned();
+        let router_handle = UdpRouter::register(ctx, options).await?;
+        Ok(Self {
+            ctx: ctx.async_try_clone().await?,
+            router_handle,
+            flow_control_id,
+        })
+    }
+
+    /// [`FlowControlId`] of the listeners of this transport, if they were marked
+    /// as Producers with [`UdpTransportOptions::as_producer`]
+    pub fn flow_control_id(&self) -> Option<&FlowControlId> {
+        self.flow_control_id.as_ref()
     }
 
     /// Start listening to incoming datagrams on a specified local address
@@ -27,6 +66,94 @@ impl UdpTransport {
             .map_err(|_| TransportError::InvalidAddress)?;
         self.router_handle.listen(bind_addr).await
     }
+
+    /// Create a UDP Inlet that listens on bind_addr, and forwards the datagrams of each UDP peer
+    /// to its own session on the Outlet, using outlet_route. Inlet is bidirectional: datagrams
+    /// sent back by the Outlet are sent to the UDP peer of the session.
+    /// A session is closed when no datagram was exchanged during the idle timeout set in the
+    /// options.
+    ///
+    /// ```rust
+    /// use ockam_transport_udp::{UdpInletOptions, UdpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let route_path = route!["outlet"];
+    ///
+    /// let udp = UdpTransport::create(&ctx).await?;
+    /// let (bind_addr, inlet) = udp.create_inlet("127.0.0.1:5353", route_path, UdpInletOptions::new()).await?;
+    /// # udp.stop_inlet(inlet).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_inlet(
+        &self,
+        bind_addr: impl Into<String>,
+        outlet_route: impl Into<Route>,
+        options: UdpInletOptions,
+    ) -> Result<(SocketAddr, Address)> {
+        let socket_addr = bind_addr
+            .into()
+            .parse()
+            .map_err(|_| TransportError::InvalidAddress)?;
+        UdpInletListenProcessor::start(&self.ctx, outlet_route.into(), socket_addr, options).await
+    }
+
+    /// Stop the UDP Inlet at addr, and all its sessions
+    pub async fn stop_inlet(&self, addr: impl Into<Address>) -> Result<()> {
+        self.ctx.stop_processor(addr).await?;
+
+        Ok(())
+    }
+
+    /// Create a UDP Outlet Listener at address, which creates a session for each session of an
+    /// Inlet. Each session sends the datagrams received from the Inlet to peer, from its own UDP
+    /// socket, and sends the datagrams received from peer back to the Inlet.
+    ///
+    /// ```rust
+    /// use ockam_transport_udp::{UdpOutletOptions, UdpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    ///
+    /// let udp = UdpTransport::create(&ctx).await?;
+    /// udp.create_outlet("outlet", "localhost:53", UdpOutletOptions::new()).await?;
+    /// # udp.stop_outlet("outlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_outlet(
+        &self,
+        address: impl Into<Address>,
+        peer: impl Into<String>,
+        options: UdpOutletOptions,
+    ) -> Result<()> {
+        let peer_addr = resolve_peer(peer.into())?;
+        UdpOutletListenWorker::start(&self.ctx, address.into(), peer_addr, options).await
+    }
+
+    /// Stop the UDP Outlet Listener at addr
+    pub async fn stop_outlet(&self, addr: impl Into<Address>) -> Result<()> {
+        self.ctx.stop_worker(addr).await?;
+
+        Ok(())
+    }
+}
+
+/// Resolve a peer address, preferring an IPv4 address
+fn resolve_peer(peer: String) -> Result<SocketAddr> {
+    if let Ok(p) = peer.parse() {
+        return Ok(p);
+    }
+
+    let addrs: Vec<SocketAddr> = peer
+        .to_socket_addrs()
+        .map_err(|_| TransportError::InvalidAddress)?
+        .collect();
+    addrs
+        .iter()
+        .find(|x| x.is_ipv4())
+        .or_else(|| addrs.first())
+        .cloned()
+        .ok_or_else(|| TransportError::InvalidAddress.into())
 }
 
 /// This trait adds a `create_udp_transport` method to any struct returning a Context.
@@ -1,34 +1,141 @@
+use super::{Fragment, UdpDatagram, FRAGMENT_HEADER_SIZE};
 use bytes::{Buf, BufMut, BytesMut};
-use ockam_core::TransportMessage;
-use ockam_core::{Decodable, Encodable};
 use ockam_transport_core::TransportError;
 use tokio_util::codec::{Decoder, Encoder};
 
-pub(crate) struct TransportMessageCodec;
+/// First bytes of every datagram
+///
+/// Datagrams of the previous format started with the length of their message,
+/// which can't be as large as this in a UDP datagram, so they are never
+/// mistaken for datagrams of this format.
+const MAGIC: [u8; 2] = [0xff, 0xfe];
 
-impl Encoder<TransportMessage> for TransportMessageCodec {
+/// Version of the datagram format, following the magic bytes
+const VERSION: u8 = 1;
+
+/// Size of the magic bytes and the version starting every datagram
+pub(crate) const PREFIX_SIZE: usize = MAGIC.len() + 1;
+
+/// Kind of a datagram carrying a [`Fragment`]
+const KIND_FRAGMENT: u8 = 0;
+/// Kind of a datagram carrying an acknowledgment
+const KIND_ACK: u8 = 1;
+
+/// Flag set on the fragments of a message which must be acknowledged
+const FLAG_ACK_REQUESTED: u8 = 0b0000_0001;
+
+/// Encode and decode one [`UdpDatagram`] per UDP datagram
+///
+/// Datagrams which don't start with the expected magic bytes and version, sent
+/// by a peer using another version of the transport, are rejected with
+/// [`TransportError::Protocol`].
+pub(crate) struct UdpDatagramCodec;
+
+impl Encoder<UdpDatagram> for UdpDatagramCodec {
     type Error = TransportError;
-    fn encode(&mut self, item: TransportMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        let msg_buf = item.encode().map_err(|_| TransportError::SendBadMessage)?;
-        let len = msg_buf.len();
-        dst.put_u16(len as u16);
-        dst.put(&msg_buf[..]);
+    fn encode(&mut self, item: UdpDatagram, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        match item {
+            UdpDatagram::Fragment(fragment) => {
+                dst.reserve(FRAGMENT_HEADER_SIZE + fragment.payload.len());
+                dst.put(&MAGIC[..]);
+                dst.put_u8(VERSION);
+                dst.put_u8(KIND_FRAGMENT);
+                dst.put_u8(if fragment.ack_requested {
+                    FLAG_ACK_REQUESTED
+                } else {
+                    0
+                });
+                dst.put_u32(fragment.message_id);
+                dst.put_u16(fragment.index);
+                dst.put_u16(fragment.count);
+                dst.put(&fragment.payload[..]);
+            }
+            UdpDatagram::Ack { message_id } => {
+                dst.reserve(PREFIX_SIZE + 5);
+                dst.put(&MAGIC[..]);
+                dst.put_u8(VERSION);
+                dst.put_u8(KIND_ACK);
+                dst.put_u32(message_id);
+            }
+        }
         Ok(())
     }
 }
 
-impl Decoder for TransportMessageCodec {
-    type Item = TransportMessage;
+impl Decoder for UdpDatagramCodec {
+    type Item = UdpDatagram;
     type Error = TransportError;
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
         if src.is_empty() {
             return Ok(None);
         }
 
-        let len = src.get_u16() as usize;
-        let msg = TransportMessage::decode(&src.split_to(len)[..])
-            .map_err(|_| TransportError::RecvBadMessage)?;
+        // A datagram is always decoded as a whole
+        let mut src = src.split();
+        if src.len() <= PREFIX_SIZE || src[..MAGIC.len()] != MAGIC || src[MAGIC.len()] != VERSION {
+            return Err(TransportError::Protocol);
+        }
+        src.advance(PREFIX_SIZE);
+
+        let datagram = match src.get_u8() {
+            KIND_FRAGMENT if src.len() >= FRAGMENT_HEADER_SIZE - PREFIX_SIZE - 1 => {
+                let flags = src.get_u8();
+                UdpDatagram::Fragment(Fragment {
+                    ack_requested: flags & FLAG_ACK_REQUESTED != 0,
+                    message_id: src.get_u32(),
+                    index: src.get_u16(),
+                    count: src.get_u16(),
+                    payload: src.to_vec(),
+                })
+            }
+            KIND_ACK if src.len() == 4 => UdpDatagram::Ack {
+                message_id: src.get_u32(),
+            },
+            _ => return Err(TransportError::RecvBadMessage),
+        };
+
+        Ok(Some(datagram))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn datagrams_are_decoded() {
+        let datagrams = vec![
+            UdpDatagram::Fragment(Fragment {
+                message_id: 3,
+                index: 1,
+                count: 2,
+                ack_requested: true,
+                payload: vec![1, 2, 3],
+            }),
+            UdpDatagram::Ack { message_id: 3 },
+        ];
+
+        for datagram in datagrams {
+            let mut buf = BytesMut::new();
+            UdpDatagramCodec.encode(datagram.clone(), &mut buf).unwrap();
+            assert_eq!(UdpDatagramCodec.decode(&mut buf).unwrap(), Some(datagram));
+        }
+    }
+
+    #[test]
+    fn datagrams_of_another_version_are_rejected() {
+        // A datagram of the previous format: message length followed by the message
+        let mut buf = BytesMut::from(&[0, 3, 1, 2, 3][..]);
+        assert_eq!(
+            UdpDatagramCodec.decode(&mut buf),
+            Err(TransportError::Protocol)
+        );
+        assert!(buf.is_empty());
 
-        Ok(Some(msg))
+        let mut buf = BytesMut::from(&[0xff, 0xfe, VERSION + 1, KIND_ACK, 0, 0, 0, 3][..]);
+        assert_eq!(
+            UdpDatagramCodec.decode(&mut buf),
+            Err(TransportError::Protocol)
+        );
     }
 }
@@ -0,0 +1,328 @@
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// Size of the header of a datagram carrying a fragment: magic bytes and
+/// version (3), kind (1), flags (1), message id (4), fragment index (2), fragment count (2)
+pub(crate) const FRAGMENT_HEADER_SIZE: usize = 13;
+
+/// Maximum size of an encoded message, the same as for the TCP transport
+pub(crate) const MAX_MESSAGE_SIZE: usize = u16::MAX as usize;
+
+/// Maximum number of messages being reassembled at the same time
+const MAX_PARTIAL_MESSAGES: usize = 1024;
+
+/// Maximum number of bytes buffered for the messages being reassembled
+const MAX_BUFFERED_BYTES: usize = 4 * 1024 * 1024;
+
+/// Number of delivered messages remembered to drop retransmitted duplicates
+const MAX_DELIVERED_MESSAGES: usize = 4096;
+
+/// A fragment of an encoded `TransportMessage`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Fragment {
+    pub(crate) message_id: u32,
+    pub(crate) index: u16,
+    pub(crate) count: u16,
+    /// The receiver must acknowledge the message once it is reassembled
+    pub(crate) ack_requested: bool,
+    pub(crate) payload: Vec<u8>,
+}
+
+/// A datagram exchanged by the UDP transport
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum UdpDatagram {
+    /// A fragment of a message
+    Fragment(Fragment),
+    /// Acknowledgment of a reassembled message
+    Ack { message_id: u32 },
+}
+
+/// Split an encoded message into fragments fitting into `max_datagram_size`
+pub(crate) fn fragment(
+    message_id: u32,
+    payload: &[u8],
+    max_datagram_size: usize,
+    ack_requested: bool,
+) -> Result<Vec<Fragment>> {
+    let chunk_size = max_datagram_size.saturating_sub(FRAGMENT_HEADER_SIZE);
+    if chunk_size == 0 || payload.len() > MAX_MESSAGE_SIZE {
+        return Err(TransportError::Capacity.into());
+    }
+
+    let count = ((payload.len() + chunk_size - 1) / chunk_size).max(1);
+    let count = u16::try_from(count).map_err(|_| TransportError::Capacity)?;
+
+    let fragments = (0..count)
+        .map(|index| {
+            let start = index as usize * chunk_size;
+            let end = (start + chunk_size).min(payload.len());
+            Fragment {
+                message_id,
+                index,
+                count,
+                ack_requested,
+                payload: payload[start..end].to_vec(),
+            }
+        })
+        .collect();
+
+    Ok(fragments)
+}
+
+/// Result of adding a fragment to a [`Reassembler`]
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Reassembled {
+    /// All the fragments of the message were received
+    Complete(Vec<u8>),
+    /// The message was already delivered, the fragment was retransmitted
+    Duplicate,
+    /// Some fragments of the message are still missing
+    Incomplete,
+}
+
+struct PartialMessage {
+    fragments: Vec<Option<Vec<u8>>>,
+    missing: usize,
+    /// Number of payload bytes received so far
+    size: usize,
+    started_at: Instant,
+}
+
+/// Reassemble the messages received from each peer
+///
+/// Messages which are not complete after the reassembly timeout are
+/// considered lost and dropped.
+///
+/// The fragments are expected to fit into datagrams of at most the maximum
+/// datagram size of the receiver, so that the number of fragments of a message
+/// and the number of bytes buffered for the messages being reassembled stay bounded.
+pub(crate) struct Reassembler {
+    timeout: Duration,
+    max_fragment_size: usize,
+    max_fragments: usize,
+    buffered: usize,
+    partial: HashMap<(SocketAddr, u32), PartialMessage>,
+    delivered: HashSet<(SocketAddr, u32)>,
+    delivered_order: VecDeque<(SocketAddr, u32)>,
+}
+
+impl Reassembler {
+    pub(crate) fn new(timeout: Duration, max_datagram_size: usize) -> Self {
+        let max_fragment_size = max_datagram_size
+            .saturating_sub(FRAGMENT_HEADER_SIZE)
+            .max(1);
+        Self {
+            timeout,
+            max_fragment_size,
+            max_fragments: (MAX_MESSAGE_SIZE + max_fragment_size - 1) / max_fragment_size,
+            buffered: 0,
+            partial: HashMap::new(),
+            delivered: HashSet::new(),
+            delivered_order: VecDeque::new(),
+        }
+    }
+
+    /// Add a fragment received from `peer`
+    pub(crate) fn insert(
+        &mut self,
+        peer: SocketAddr,
+        fragment: Fragment,
+        now: Instant,
+    ) -> Result<Reassembled> {
+        let key = (peer, fragment.message_id);
+        if self.delivered.contains(&key) {
+            return Ok(Reassembled::Duplicate);
+        }
+
+        let count = fragment.count as usize;
+        let index = fragment.index as usize;
+        if count == 0
+            || count > self.max_fragments
+            || index >= count
+            || fragment.payload.len() > self.max_fragment_size
+        {
+            return Err(TransportError::RecvBadMessage.into());
+        }
+
+        let is_new = !self.partial.contains_key(&key);
+        if (is_new && self.partial.len() >= MAX_PARTIAL_MESSAGES)
+            || self.buffered + fragment.payload.len() > MAX_BUFFERED_BYTES
+        {
+            self.expire(now);
+            if (is_new && self.partial.len() >= MAX_PARTIAL_MESSAGES)
+                || self.buffered + fragment.payload.len() > MAX_BUFFERED_BYTES
+            {
+                return Err(TransportError::Capacity.into());
+            }
+        }
+
+        let partial = self.partial.entry(key).or_insert_with(|| PartialMessage {
+            fragments: vec![None; count],
+            missing: count,
+            size: 0,
+            started_at: now,
+        });
+
+        if partial.fragments.len() != count {
+            return Err(TransportError::RecvBadMessage.into());
+        }
+
+        if partial.fragments[index].is_none() {
+            partial.size += fragment.payload.len();
+            self.buffered += fragment.payload.len();
+            partial.fragments[index] = Some(fragment.payload);
+            partial.missing -= 1;
+        }
+
+        if partial.missing > 0 {
+            return Ok(Reassembled::Incomplete);
+        }
+
+        let partial = self
+            .partial
+            .remove(&key)
+            .ok_or(TransportError::RecvBadMessage)?;
+        self.buffered -= partial.size;
+        self.remember_delivered(key);
+
+        Ok(Reassembled::Complete(
+            partial.fragments.into_iter().flatten().flatten().collect(),
+        ))
+    }
+
+    /// Drop the messages which could not be reassembled in time,
+    /// and return how many were dropped
+    pub(crate) fn expire(&mut self, now: Instant) -> usize {
+        let timeout = self.timeout;
+        let before = self.partial.len();
+        let mut dropped_bytes = 0;
+        self.partial.retain(|_, partial| {
+            let keep = now.duration_since(partial.started_at) < timeout;
+            if !keep {
+                dropped_bytes += partial.size;
+            }
+            keep
+        });
+        self.buffered -= dropped_bytes;
+        before - self.partial.len()
+    }
+
+    fn remember_delivered(&mut self, key: (SocketAddr, u32)) {
+        if self.delivered_order.len() >= MAX_DELIVERED_MESSAGES {
+            if let Some(oldest) = self.delivered_order.pop_front() {
+                self.delivered.remove(&oldest);
+            }
+        }
+        self.delivered.insert(key);
+        self.delivered_order.push_back(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer() -> SocketAddr {
+        "127.0.0.1:4000".parse().unwrap()
+    }
+
+    #[test]
+    fn fragments_are_reassembled_in_any_order() {
+        let payload: Vec<u8> = (0..5000).map(|i| i as u8).collect();
+        let mut fragments = fragment(7, &payload, 1200, false).unwrap();
+        assert_eq!(fragments.len(), 5);
+        assert!(fragments
+            .iter()
+            .all(|f| f.payload.len() + FRAGMENT_HEADER_SIZE <= 1200));
+
+        fragments.reverse();
+        let last = fragments.pop().unwrap();
+
+        let now = Instant::now();
+        let mut reassembler = Reassembler::new(Duration::from_secs(1), 1200);
+        for f in fragments {
+            assert_eq!(
+                reassembler.insert(peer(), f, now).unwrap(),
+                Reassembled::Incomplete
+            );
+        }
+        assert_eq!(
+            reassembler.insert(peer(), last.clone(), now).unwrap(),
+            Reassembled::Complete(payload)
+        );
+        assert_eq!(
+            reassembler.insert(peer(), last, now).unwrap(),
+            Reassembled::Duplicate
+        );
+    }
+
+    #[test]
+    fn incomplete_messages_expire() {
+        let payload = vec![1u8; 3000];
+        let mut fragments = fragment(1, &payload, 1200, false).unwrap();
+        fragments.pop();
+
+        let now = Instant::now();
+        let mut reassembler = Reassembler::new(Duration::from_secs(1), 1200);
+        for f in fragments {
+            reassembler.insert(peer(), f, now).unwrap();
+        }
+
+        assert_eq!(reassembler.expire(now), 0);
+        assert_eq!(reassembler.expire(now + Duration::from_secs(2)), 1);
+    }
+
+    #[test]
+    fn fragment_counts_and_sizes_are_bounded() {
+        let now = Instant::now();
+        let mut reassembler = Reassembler::new(Duration::from_secs(1), 1200);
+
+        // More fragments than needed for the largest message
+        let too_many = Fragment {
+            message_id: 1,
+            index: 0,
+            count: u16::MAX,
+            ack_requested: false,
+            payload: vec![0; 10],
+        };
+        assert!(reassembler.insert(peer(), too_many, now).is_err());
+
+        // A fragment larger than a datagram
+        let too_large = Fragment {
+            message_id: 2,
+            index: 0,
+            count: 2,
+            ack_requested: false,
+            payload: vec![0; 1200],
+        };
+        assert!(reassembler.insert(peer(), too_large, now).is_err());
+
+        // The buffered bytes are bounded, until incomplete messages expire
+        let mut message_id = 0;
+        loop {
+            message_id += 1;
+            let mut fragments = fragment(message_id, &[1; 10_000], 1200, false).unwrap();
+            fragments.pop();
+            if fragments
+                .into_iter()
+                .any(|f| reassembler.insert(peer(), f, now).is_err())
+            {
+                break;
+            }
+        }
+        assert!(reassembler.buffered <= MAX_BUFFERED_BYTES);
+        assert!(reassembler.buffered > MAX_BUFFERED_BYTES - 10_000);
+
+        reassembler.expire(now + Duration::from_secs(2));
+        assert_eq!(reassembler.buffered, 0);
+    }
+
+    #[test]
+    fn messages_larger_than_the_maximum_size_are_not_fragmented() {
+        assert!(fragment(1, &vec![0; MAX_MESSAGE_SIZE], 1200, false).is_ok());
+        assert!(fragment(1, &vec![0; MAX_MESSAGE_SIZE + 1], 1200, false).is_err());
+    }
+}
@@ -1,9 +1,16 @@
-use super::TransportMessageCodec;
-use crate::UDP;
+use super::{Reassembled, Reassembler, UdpDatagram, UdpDatagramCodec, UdpSenderInternalMessage};
+use crate::{UdpTransportOptions, UDP};
 use futures_util::stream::SplitStream;
 use futures_util::StreamExt;
-use ockam_core::{async_trait, route, Address, AllowAll, LocalMessage, Processor, Result};
-use ockam_node::Context;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{
+    async_trait, route, Address, AllowAll, Decodable, LocalMessage, Mailbox, Mailboxes, Processor,
+    Result, TransportMessage,
+};
+use ockam_node::{Context, ProcessorBuilder};
+use ockam_transport_core::TransportError;
+use std::net::SocketAddr;
+use std::time::Instant;
 use tokio_util::udp::UdpFramed;
 use tracing::{debug, warn};
 
@@ -15,31 +22,81 @@ use tracing::{debug, warn};
 /// When a message is received, the address of the paired sender
 /// ([`UdpSendWorker`](crate::workers::UdpSendWorker)) is injected into the message's
 /// return route so that replies are sent to the sender.
+///
+/// Messages are reassembled from their fragments before being forwarded.
+/// Acknowledgments are sent, and received acknowledgments are reported, by
+/// the paired sender.
 pub(crate) struct UdpListenProcessor {
-    /// The read half of the udnerlying UDP socket.
-    stream: SplitStream<UdpFramed<TransportMessageCodec>>,
+    /// The read half of the underlying UDP socket.
+    stream: SplitStream<UdpFramed<UdpDatagramCodec>>,
     /// Address of our sender counterpart
     sender_addr: Address,
+    /// Internal address of our sender counterpart
+    sender_internal_addr: Address,
+    reassembler: Reassembler,
 }
 
 impl UdpListenProcessor {
     pub(crate) async fn start(
         ctx: &Context,
-        stream: SplitStream<UdpFramed<TransportMessageCodec>>,
+        stream: SplitStream<UdpFramed<UdpDatagramCodec>>,
+        address: Address,
         sender_addr: Address,
+        sender_internal_addr: Address,
+        options: &UdpTransportOptions,
     ) -> Result<()> {
         let processor = Self {
             stream,
             sender_addr,
+            sender_internal_addr,
+            reassembler: Reassembler::new(options.reassembly_timeout, options.max_datagram_size),
         };
-        let addr = Address::random_tagged("UdpListenProcessor");
+
+        options.setup_flow_control(
+            ctx.flow_controls(),
+            &address,
+            &processor.sender_internal_addr,
+        );
 
         // FIXME: @ac
-        ctx.start_processor_with_access_control(addr.clone(), processor, AllowAll, AllowAll)
+        let mailbox = Mailbox::new(
+            address,
+            Arc::new(AllowAll),
+            options.create_listener_access_control(ctx.flow_controls()),
+        );
+        ProcessorBuilder::new(processor)
+            .with_mailboxes(Mailboxes::new(mailbox, vec![]))
+            .start(ctx)
             .await?;
 
         Ok(())
     }
+
+    async fn notify_sender(&self, ctx: &Context, msg: UdpSenderInternalMessage) -> Result<()> {
+        ctx.send(self.sender_internal_addr.clone(), msg).await
+    }
+
+    async fn forward(&self, ctx: &Context, payload: &[u8], addr: SocketAddr) -> Result<()> {
+        let mut msg = match TransportMessage::decode(payload) {
+            Ok(msg) => msg,
+            Err(e) => {
+                warn!("Failed to decode message from {}: {:?}", addr, e);
+                return Ok(());
+            }
+        };
+
+        // Set return route to go directly to paired sender, skipping the UDP router
+        msg.return_route = route![
+            self.sender_addr.clone(),
+            Address::new(UDP, addr.to_string()),
+            msg.return_route
+        ];
+
+        debug!(onward_route = %msg.onward_route,
+            return_route = %msg.return_route,
+            "Forwarding UDP message");
+        ctx.forward(LocalMessage::new(msg, vec![])).await
+    }
 }
 
 #[async_trait]
@@ -52,9 +109,13 @@ impl Processor for UdpListenProcessor {
 
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
         debug!("Waiting for incoming UDP datagram...");
-        let (mut msg, addr) = match self.stream.next().await {
+        let (datagram, addr) = match self.stream.next().await {
             Some(res) => match res {
-                Ok((msg, addr)) => (msg, addr),
+                Ok((datagram, addr)) => (datagram, addr),
+                Err(TransportError::Protocol) => {
+                    warn!("Dropped a datagram sent by a peer using another version of the UDP transport");
+                    return Ok(true);
+                }
                 Err(e) => {
                     warn!(
                         "Failed to read message, will wait for next message: {:?}",
@@ -69,17 +130,57 @@ impl Processor for UdpListenProcessor {
             }
         };
 
-        // Set return route to go directly to paired sender, skipping the UDP router
-        msg.return_route = route![
-            self.sender_addr.clone(),
-            Address::new(UDP, addr.to_string()),
-            msg.return_route
-        ];
+        let now = Instant::now();
+        let lost = self.reassembler.expire(now);
+        if lost > 0 {
+            warn!(
+                "Dropped {} UDP messages which were not fully received in time",
+                lost
+            );
+        }
 
-        debug!(onward_route = %msg.onward_route,
-            return_route = %msg.return_route,
-            "Forwarding UDP message");
-        ctx.forward(LocalMessage::new(msg, vec![])).await?;
+        let fragment = match datagram {
+            UdpDatagram::Fragment(fragment) => fragment,
+            UdpDatagram::Ack { message_id } => {
+                self.notify_sender(
+                    ctx,
+                    UdpSenderInternalMessage::AckReceived {
+                        peer: addr,
+                        message_id,
+                    },
+                )
+                .await?;
+                return Ok(true);
+            }
+        };
+
+        let message_id = fragment.message_id;
+        let ack_requested = fragment.ack_requested;
+        let payload = match self.reassembler.insert(addr, fragment, now) {
+            Ok(Reassembled::Complete(payload)) => Some(payload),
+            // The acknowledgment of a duplicate message was probably lost
+            Ok(Reassembled::Duplicate) => None,
+            Ok(Reassembled::Incomplete) => return Ok(true),
+            Err(e) => {
+                warn!("Dropped a fragment from {}: {:?}", addr, e);
+                return Ok(true);
+            }
+        };
+
+        if ack_requested {
+            self.notify_sender(
+                ctx,
+                UdpSenderInternalMessage::SendAck {
+                    peer: addr,
+                    message_id,
+                },
+            )
+            .await?;
+        }
+
+        if let Some(payload) = payload {
+            self.forward(ctx, &payload, addr).await?;
+        }
 
         Ok(true)
     }
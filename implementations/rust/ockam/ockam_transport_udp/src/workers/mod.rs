@@ -1,9 +1,11 @@
 // TODO: Would it be logical to move this `workers` directory into the `router` directory?
 
 pub(crate) use codec::*;
+pub(crate) use fragmentation::*;
 pub(crate) use listener::*;
 pub(crate) use sender::*;
 
 mod codec;
+mod fragmentation;
 mod listener;
 mod sender;
@@ -1,28 +1,204 @@
-use super::TransportMessageCodec;
-use crate::UDP;
+use super::{fragment, Fragment, UdpDatagram, UdpDatagramCodec};
+use crate::{UdpTransportOptions, UDP};
 use futures_util::{stream::SplitSink, SinkExt};
-use ockam_core::{async_trait, Any, Result, Routed, TransportMessage, Worker};
-use ockam_node::Context;
+use ockam_core::{
+    async_trait, Address, AllowAll, AllowSourceAddresses, Any, Decodable, DenyAll, Encodable,
+    Mailbox, Mailboxes, Message, Result, Routed, Worker,
+};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
 use ockam_transport_core::TransportError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Instant;
 use tokio_util::udp::UdpFramed;
-use tracing::{error, trace, warn};
+use tracing::{debug, error, trace, warn};
+
+/// Maximum number of messages waiting for an acknowledgment
+const MAX_PENDING_MESSAGES: usize = 1024;
+
+/// Messages sent to the internal address of a [`UdpSendWorker`]
+#[derive(Serialize, Deserialize, Clone, Debug, Message)]
+pub(crate) enum UdpSenderInternalMessage {
+    /// Acknowledge a message reassembled by the paired listener
+    SendAck { peer: SocketAddr, message_id: u32 },
+    /// A peer acknowledged one of our messages
+    AckReceived { peer: SocketAddr, message_id: u32 },
+    /// Send again the messages which were not acknowledged in time
+    Retransmit,
+}
+
+struct PendingMessage {
+    fragments: Vec<Fragment>,
+    retransmissions: u8,
+    sent_at: Instant,
+}
 
 /// A sender for the UDP transport
 ///
 /// This worker handles the sending of messages on a
 /// local socket. See [`UdpRouter`](crate::router::UdpRouter) for more details.
+///
+/// Messages are split into fragments fitting into a datagram. Unless
+/// retransmission is disabled, the fragments of a message are sent again
+/// until the message is acknowledged by the receiver.
 pub(crate) struct UdpSendWorker {
-    /// The read half of the udnerlying UDP socket.
-    sink: SplitSink<UdpFramed<TransportMessageCodec>, (TransportMessage, SocketAddr)>,
+    /// The write half of the underlying UDP socket.
+    sink: SplitSink<UdpFramed<UdpDatagramCodec>, (UdpDatagram, SocketAddr)>,
+    internal_addr: Address,
+    options: UdpTransportOptions,
+    next_message_id: u32,
+    pending: HashMap<(SocketAddr, u32), PendingMessage>,
+    retransmit_event: DelayedEvent<UdpSenderInternalMessage>,
 }
 
 impl UdpSendWorker {
-    /// Create a new `UdpSendWorker`
-    pub(crate) fn new(
-        sink: SplitSink<UdpFramed<TransportMessageCodec>, (TransportMessage, SocketAddr)>,
-    ) -> Self {
-        Self { sink }
+    /// Start a new `UdpSendWorker`, which only accepts internal messages from
+    /// the paired listener
+    pub(crate) async fn start(
+        ctx: &Context,
+        sink: SplitSink<UdpFramed<UdpDatagramCodec>, (UdpDatagram, SocketAddr)>,
+        address: Address,
+        internal_addr: Address,
+        listener_addr: Address,
+        options: UdpTransportOptions,
+    ) -> Result<()> {
+        let retransmit_event = DelayedEvent::create(
+            ctx,
+            internal_addr.clone(),
+            UdpSenderInternalMessage::Retransmit,
+        )
+        .await?;
+
+        let main_mailbox = Mailbox::new(
+            address,
+            Arc::new(AllowAll), // FIXME: @ac
+            Arc::new(DenyAll),
+        );
+        let internal_mailbox = Mailbox::new(
+            internal_addr.clone(),
+            Arc::new(AllowSourceAddresses(vec![
+                listener_addr,
+                retransmit_event.address(),
+            ])),
+            Arc::new(DenyAll),
+        );
+
+        let worker = Self {
+            sink,
+            internal_addr,
+            options,
+            next_message_id: rand::random(),
+            pending: HashMap::new(),
+            retransmit_event,
+        };
+
+        WorkerBuilder::new(worker)
+            .with_mailboxes(Mailboxes::new(main_mailbox, vec![internal_mailbox]))
+            .start(ctx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn send_datagram(&mut self, datagram: UdpDatagram, addr: SocketAddr) -> Result<()> {
+        match self.sink.send((datagram, addr)).await {
+            Ok(()) => {
+                trace!("Successful send to {}", addr);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed send to {}: {:?}", addr, e);
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn send_fragments(&mut self, fragments: &[Fragment], addr: SocketAddr) -> Result<()> {
+        for fragment in fragments {
+            self.send_datagram(UdpDatagram::Fragment(fragment.clone()), addr)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_internal_message(&mut self, msg: UdpSenderInternalMessage) -> Result<()> {
+        match msg {
+            UdpSenderInternalMessage::SendAck { peer, message_id } => {
+                if let Err(e) = self
+                    .send_datagram(UdpDatagram::Ack { message_id }, peer)
+                    .await
+                {
+                    warn!(
+                        "Failed to acknowledge message {} to {}: {}",
+                        message_id, peer, e
+                    );
+                }
+            }
+            UdpSenderInternalMessage::AckReceived { peer, message_id } => {
+                if self.pending.remove(&(peer, message_id)).is_some() {
+                    trace!("Message {} was acknowledged by {}", message_id, peer);
+                }
+            }
+            UdpSenderInternalMessage::Retransmit => self.retransmit().await?,
+        }
+
+        Ok(())
+    }
+
+    async fn retransmit(&mut self) -> Result<()> {
+        let retransmission = match self.options.retransmission {
+            Some(retransmission) => retransmission,
+            None => return Ok(()),
+        };
+
+        let now = Instant::now();
+        let expired: Vec<(SocketAddr, u32)> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now.duration_since(pending.sent_at) >= retransmission.timeout)
+            .map(|(key, _)| *key)
+            .collect();
+
+        for (peer, message_id) in expired {
+            let pending = match self.pending.remove(&(peer, message_id)) {
+                Some(pending) => pending,
+                None => continue,
+            };
+
+            if pending.retransmissions >= retransmission.max_retransmissions {
+                warn!(
+                    "Message {} to {} was not acknowledged after {} retransmissions, dropping it",
+                    message_id, peer, pending.retransmissions
+                );
+                continue;
+            }
+
+            debug!("Retransmitting message {} to {}", message_id, peer);
+            if let Err(e) = self.send_fragments(&pending.fragments, peer).await {
+                warn!(
+                    "Failed to retransmit message {} to {}: {}",
+                    message_id, peer, e
+                );
+            }
+            self.pending.insert(
+                (peer, message_id),
+                PendingMessage {
+                    retransmissions: pending.retransmissions + 1,
+                    sent_at: now,
+                    ..pending
+                },
+            );
+        }
+
+        if !self.pending.is_empty() {
+            self.retransmit_event
+                .schedule(retransmission.timeout)
+                .await?;
+        }
+
+        Ok(())
     }
 }
 
@@ -36,6 +212,11 @@ impl Worker for UdpSendWorker {
         _ctx: &mut Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
+        if msg.msg_addr() == self.internal_addr {
+            let msg = UdpSenderInternalMessage::decode(msg.payload())?;
+            return self.handle_internal_message(msg).await;
+        }
+
         // Parse message and remove our address from its routing
         let mut msg = msg.into_transport_message();
         msg.onward_route.step()?;
@@ -73,16 +254,45 @@ impl Worker for UdpSendWorker {
             return Err(TransportError::InvalidAddress.into());
         }
 
+        let payload = msg.encode().map_err(|_| TransportError::SendBadMessage)?;
+        let message_id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+
+        let retransmission = self.options.retransmission;
+        let fragments = fragment(
+            message_id,
+            &payload,
+            self.options.max_datagram_size,
+            retransmission.is_some(),
+        )?;
+
         // Send
-        match self.sink.send((msg.clone(), addr)).await {
-            Ok(()) => {
-                trace!("Successful send to {}", addr);
-                Ok(())
+        self.send_fragments(&fragments, addr).await?;
+
+        if let Some(retransmission) = retransmission {
+            if self.pending.len() >= MAX_PENDING_MESSAGES {
+                warn!(
+                    "Too many messages waiting for an acknowledgment, message {} to {} won't be retransmitted",
+                    message_id, addr
+                );
+                return Ok(());
             }
-            Err(e) => {
-                error!("Failed send to {}: {:?}", addr, e);
-                Err(e.into())
+
+            if self.pending.is_empty() {
+                self.retransmit_event
+                    .schedule(retransmission.timeout)
+                    .await?;
             }
+            self.pending.insert(
+                (addr, message_id),
+                PendingMessage {
+                    fragments,
+                    retransmissions: 0,
+                    sent_at: Instant::now(),
+                },
+            );
         }
+
+        Ok(())
     }
 }
@@ -0,0 +1,143 @@
+use ockam::{errcode::Origin, Error};
+use ockam_core::{route, Result};
+use ockam_node::Context;
+use ockam_transport_udp::{UdpInletOptions, UdpOutletOptions, UdpTransport};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn to_error(e: impl std::error::Error + Send + Sync + 'static) -> Error {
+    Error::new_unknown(Origin::Unknown, e)
+}
+
+/// Start a UDP server echoing all the datagrams it receives
+async fn start_echo_server() -> Result<SocketAddr> {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.map_err(to_error)?;
+    let addr = socket.local_addr().map_err(to_error)?;
+    tokio::spawn(async move {
+        let mut buf = vec![0; 1024];
+        while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+            let _ = socket.send_to(&buf[..len], peer).await;
+        }
+    });
+    Ok(addr)
+}
+
+async fn send_and_receive(client: &UdpSocket, inlet: SocketAddr, msg: &[u8]) -> Result<Vec<u8>> {
+    client.send_to(msg, inlet).await.map_err(to_error)?;
+    let mut buf = vec![0; 1024];
+    let (len, _) = timeout(TIMEOUT, client.recv_from(&mut buf))
+        .await
+        .map_err(to_error)?
+        .map_err(to_error)?;
+    Ok(buf[..len].to_vec())
+}
+
+/// Datagrams of several UDP peers are forwarded through the portal and
+/// the replies are sent back to the right peer
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__two_peers__replies_are_received(ctx: &mut Context) -> Result<()> {
+    let echo_addr = start_echo_server().await?;
+    let udp = UdpTransport::create(ctx).await?;
+
+    udp.create_outlet("outlet", echo_addr.to_string(), UdpOutletOptions::new())
+        .await?;
+    let (inlet_addr, _) = udp
+        .create_inlet("127.0.0.1:0", route!["outlet"], UdpInletOptions::new())
+        .await?;
+
+    let client1 = UdpSocket::bind("127.0.0.1:0").await.map_err(to_error)?;
+    let client2 = UdpSocket::bind("127.0.0.1:0").await.map_err(to_error)?;
+
+    for i in 0..3 {
+        let msg1 = format!("Hello from client 1, #{i}");
+        let msg2 = format!("Hello from client 2, #{i}");
+        assert_eq!(
+            send_and_receive(&client1, inlet_addr, msg1.as_bytes()).await?,
+            msg1.as_bytes()
+        );
+        assert_eq!(
+            send_and_receive(&client2, inlet_addr, msg2.as_bytes()).await?,
+            msg2.as_bytes()
+        );
+    }
+
+    ctx.stop().await
+}
+
+/// A session which was closed after being idle is created again for
+/// the next datagram of the same peer
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__idle_session__is_recreated(ctx: &mut Context) -> Result<()> {
+    let echo_addr = start_echo_server().await?;
+    let udp = UdpTransport::create(ctx).await?;
+
+    let idle_timeout = Duration::from_millis(200);
+    udp.create_outlet(
+        "outlet",
+        echo_addr.to_string(),
+        UdpOutletOptions::new().with_idle_timeout(idle_timeout),
+    )
+    .await?;
+    let (inlet_addr, _) = udp
+        .create_inlet(
+            "127.0.0.1:0",
+            route!["outlet"],
+            UdpInletOptions::new().with_idle_timeout(idle_timeout),
+        )
+        .await?;
+
+    let client = UdpSocket::bind("127.0.0.1:0").await.map_err(to_error)?;
+    assert_eq!(
+        send_and_receive(&client, inlet_addr, b"first").await?,
+        b"first"
+    );
+
+    ctx.sleep(Duration::from_secs(1)).await;
+    assert_eq!(
+        send_and_receive(&client, inlet_addr, b"second").await?,
+        b"second"
+    );
+
+    ctx.stop().await
+}
+
+/// The datagrams of new peers are dropped while the maximum number of
+/// sessions is reached, the existing sessions are still served
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__max_sessions__new_peers_are_dropped(ctx: &mut Context) -> Result<()> {
+    let echo_addr = start_echo_server().await?;
+    let udp = UdpTransport::create(ctx).await?;
+
+    udp.create_outlet("outlet", echo_addr.to_string(), UdpOutletOptions::new())
+        .await?;
+    let (inlet_addr, _) = udp
+        .create_inlet(
+            "127.0.0.1:0",
+            route!["outlet"],
+            UdpInletOptions::new().with_max_sessions(1),
+        )
+        .await?;
+
+    let client1 = UdpSocket::bind("127.0.0.1:0").await.map_err(to_error)?;
+    let client2 = UdpSocket::bind("127.0.0.1:0").await.map_err(to_error)?;
+    assert_eq!(
+        send_and_receive(&client1, inlet_addr, b"first").await?,
+        b"first"
+    );
+    assert!(send_and_receive(&client2, inlet_addr, b"dropped")
+        .await
+        .is_err());
+    assert_eq!(
+        send_and_receive(&client1, inlet_addr, b"second").await?,
+        b"second"
+    );
+
+    ctx.stop().await
+}
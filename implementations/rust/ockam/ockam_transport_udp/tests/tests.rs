@@ -1,9 +1,10 @@
 use ockam_core::compat::rand::{self, Rng};
 use ockam_core::{route, Address, AllowAll, Result, Routed, Worker};
 use ockam_node::{Context, MessageReceiveOptions, MessageSendReceiveOptions};
-use ockam_transport_udp::{UdpTransport, UDP};
+use ockam_transport_udp::{UdpTransport, UdpTransportOptions, UDP};
 use std::net::SocketAddr;
 use std::time::Duration;
+use tokio::net::UdpSocket;
 use tracing::{debug, error, trace};
 
 mod utils;
@@ -192,6 +193,99 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+/// Messages larger than a datagram are fragmented and reassembled
+#[ockam_macros::test]
+async fn send_receive_large_message(ctx: &mut Context) -> Result<()> {
+    let bind_addr = utils::available_local_ports(1)
+        .await?
+        .first()
+        .unwrap()
+        .to_string();
+
+    let options = UdpTransportOptions::new().with_max_datagram_size(512);
+    let transport = UdpTransport::create_with_options(ctx, options).await?;
+
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    transport.listen(bind_addr.clone()).await?;
+
+    let msg: String = rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(100_000)
+        .map(char::from)
+        .collect();
+    let r = route![(UDP, bind_addr), "echoer"];
+    let reply = ctx
+        .send_and_receive_extended::<String>(
+            r,
+            msg.clone(),
+            MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+        )
+        .await?
+        .body();
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    ctx.stop().await?;
+    Ok(())
+}
+
+/// A message with a lost fragment is retransmitted until it is acknowledged
+#[ockam_macros::test]
+async fn retransmit_lost_fragment(ctx: &mut Context) -> Result<()> {
+    let bind_addr = *utils::available_local_ports(1).await?.first().unwrap();
+
+    let options = UdpTransportOptions::new()
+        .with_max_datagram_size(512)
+        .with_retransmission(Duration::from_millis(100), 3);
+    let transport = UdpTransport::create_with_options(ctx, options).await?;
+
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    transport.listen(bind_addr.to_string()).await?;
+
+    // Forward datagrams between the client and the listener, dropping the
+    // first datagram sent by the client
+    let proxy = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let proxy_addr = proxy.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut buf = vec![0; 65535];
+        let mut client_addr = None;
+        let mut dropped = false;
+        loop {
+            let (len, src) = proxy.recv_from(&mut buf).await.unwrap();
+            if src == bind_addr {
+                if let Some(client_addr) = client_addr {
+                    proxy.send_to(&buf[..len], client_addr).await.unwrap();
+                }
+            } else {
+                client_addr = Some(src);
+                if !dropped {
+                    dropped = true;
+                    continue;
+                }
+                proxy.send_to(&buf[..len], bind_addr).await.unwrap();
+            }
+        }
+    });
+
+    let msg: String = rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(2_000)
+        .map(char::from)
+        .collect();
+    let r = route![(UDP, proxy_addr.to_string()), "echoer"];
+    let reply = ctx
+        .send_and_receive_extended::<String>(
+            r,
+            msg.clone(),
+            MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+        )
+        .await?
+        .body();
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    ctx.stop().await?;
+    Ok(())
+}
+
 pub struct Echoer {
     prev_src_addr: Option<String>,
 }
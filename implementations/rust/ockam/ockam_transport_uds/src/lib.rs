@@ -8,9 +8,11 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate core;
 
+mod portal;
 mod router;
 mod transport;
 mod workers;
+pub use portal::options::*;
 use tokio::net::unix::SocketAddr as TokioSocketAddr;
 use tracing::error;
 pub use transport::*;
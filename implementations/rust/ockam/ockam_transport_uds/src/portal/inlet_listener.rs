@@ -0,0 +1,108 @@
+use crate::portal::{UdsPortalWorker, UdsStream};
+use crate::{std_socket_addr_from_tokio, UdsInletOptions};
+use ockam_core::{async_trait, Address, Processor, Result, Route};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+use ockam_transport_tcp::{PortalAddresses, PortalType};
+use std::os::unix::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::net::UnixListener;
+use tracing::{debug, error, warn};
+
+/// A UDS Portal Inlet listen processor
+///
+/// UDS Portal Inlet listen processors are created by `UdsTransport`
+/// after a call is made to
+/// [`UdsTransport::create_inlet`](crate::UdsTransport::create_inlet).
+pub(crate) struct UdsInletListenProcessor {
+    inner: UnixListener,
+    path: PathBuf,
+    outlet_listener_route: Route,
+    options: UdsInletOptions,
+}
+
+impl UdsInletListenProcessor {
+    /// Start a new `UdsInletListenProcessor`
+    pub(crate) async fn start(
+        ctx: &Context,
+        outlet_listener_route: Route,
+        addr: SocketAddr,
+        options: UdsInletOptions,
+    ) -> Result<(SocketAddr, Address)> {
+        let processor_address = Address::random_tagged("UdsInletListenProcessor");
+
+        let path = match addr.as_pathname() {
+            Some(p) => p.to_path_buf(),
+            None => {
+                error!("Error binding to socket address {:?}", addr);
+                return Err(TransportError::InvalidAddress.into());
+            }
+        };
+
+        debug!("Binding UdsInletListenProcessor to {}", path.display());
+        let inner = match UnixListener::bind(&path) {
+            Ok(inner) => inner,
+            Err(err) => {
+                error!(path = %path.display(), %err, "could not bind to socket");
+                return Err(TransportError::from(err).into());
+            }
+        };
+        let tokio_sock_addr = inner.local_addr().map_err(TransportError::from)?;
+        let socket_addr = std_socket_addr_from_tokio(&tokio_sock_addr)?;
+
+        let processor = Self {
+            inner,
+            path,
+            outlet_listener_route,
+            options,
+        };
+
+        ctx.start_processor(processor_address.clone(), processor)
+            .await?;
+
+        Ok((socket_addr, processor_address))
+    }
+}
+
+#[async_trait]
+impl Processor for UdsInletListenProcessor {
+    type Context = Context;
+
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        // Unlike TCP ports, a socket file outlives its listener and
+        // would prevent binding the same path again
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            warn!(
+                "Failed to remove the inlet socket {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        let addresses = PortalAddresses::generate::<UdsStream>(PortalType::Inlet);
+        let outlet_listener_route = self.outlet_listener_route.clone();
+
+        self.options.setup_flow_control(
+            ctx.flow_controls(),
+            &addresses,
+            outlet_listener_route.next()?,
+        );
+
+        let (stream, _) = self.inner.accept().await.map_err(TransportError::from)?;
+        UdsPortalWorker::start_inlet(
+            ctx,
+            UdsStream(stream),
+            self.path.clone(),
+            outlet_listener_route,
+            addresses,
+            self.options.incoming_access_control.clone(),
+        )
+        .await?;
+
+        Ok(true)
+    }
+}
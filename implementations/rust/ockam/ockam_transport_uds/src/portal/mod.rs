@@ -0,0 +1,8 @@
+mod inlet_listener;
+pub mod options;
+mod outlet_listener;
+mod stream;
+
+pub(crate) use inlet_listener::*;
+pub(crate) use outlet_listener::*;
+pub(crate) use stream::*;
@@ -0,0 +1,84 @@
+use crate::portal::{UdsPortalWorker, UdsStream};
+use crate::UdsOutletOptions;
+use ockam_core::{async_trait, Address, DenyAll, Result, Routed, Worker};
+use ockam_node::{Context, WorkerBuilder};
+use ockam_transport_core::TransportError;
+use ockam_transport_tcp::{PortalAddresses, PortalMessage, PortalType};
+use std::path::PathBuf;
+use tracing::debug;
+
+/// A UDS Portal Outlet listen worker
+///
+/// UDS Portal Outlet listen workers are created by `UdsTransport`
+/// after a call is made to
+/// [`UdsTransport::create_outlet`](crate::UdsTransport::create_outlet).
+pub(crate) struct UdsOutletListenWorker {
+    peer: PathBuf,
+    options: UdsOutletOptions,
+}
+
+impl UdsOutletListenWorker {
+    /// Create a new `UdsOutletListenWorker`
+    fn new(peer: PathBuf, options: UdsOutletOptions) -> Self {
+        Self { peer, options }
+    }
+
+    pub(crate) async fn start(
+        ctx: &Context,
+        address: Address,
+        peer: PathBuf,
+        options: UdsOutletOptions,
+    ) -> Result<()> {
+        let access_control = options.incoming_access_control.clone();
+
+        options.setup_flow_control_for_outlet_listener(ctx.flow_controls(), &address);
+
+        let worker = Self::new(peer, options);
+        WorkerBuilder::new(worker)
+            .with_address(address)
+            .with_incoming_access_control_arc(access_control)
+            .with_outgoing_access_control(DenyAll)
+            .start(ctx)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for UdsOutletListenWorker {
+    type Context = Context;
+    type Message = PortalMessage;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let return_route = msg.return_route();
+        let src_addr = msg.src_addr();
+
+        if let PortalMessage::Ping = msg.body() {
+        } else {
+            return Err(TransportError::Protocol.into());
+        }
+
+        let addresses = PortalAddresses::generate::<UdsStream>(PortalType::Outlet);
+
+        self.options
+            .setup_flow_control_for_outlet(ctx.flow_controls(), &addresses, &src_addr);
+
+        UdsPortalWorker::start_outlet(
+            ctx,
+            self.peer.clone(),
+            return_route.clone(),
+            addresses.clone(),
+            self.options.incoming_access_control.clone(),
+        )
+        .await?;
+
+        debug!("Created Uds Outlet at {}", addresses.remote());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use ockam_core::async_trait;
+use ockam_transport_tcp::{PortalStream, PortalWorker};
+use std::path::PathBuf;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::UnixStream;
+
+/// A Unix domain socket connecting a portal to its peer
+pub(crate) struct UdsStream(pub(crate) UnixStream);
+
+#[async_trait]
+impl PortalStream for UdsStream {
+    type ReadHalf = OwnedReadHalf;
+    type WriteHalf = OwnedWriteHalf;
+    type Peer = PathBuf;
+
+    const NAME: &'static str = "Uds";
+
+    async fn connect(peer: &Self::Peer) -> std::io::Result<Self> {
+        UnixStream::connect(peer).await.map(UdsStream)
+    }
+
+    fn into_split(self) -> (Self::ReadHalf, Self::WriteHalf) {
+        self.0.into_split()
+    }
+}
+
+/// A UDS Portal worker, reusing the TCP portal worker over a Unix domain socket
+pub(crate) type UdsPortalWorker = PortalWorker<UdsStream>;
@@ -1,5 +1,6 @@
 use std::os::unix::net::SocketAddr;
 
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     async_trait, compat::sync::Arc, Address, AsyncTryClone, DenyAll, Mailbox, Mailboxes, Result,
 };
@@ -21,6 +22,7 @@ pub(crate) struct UdsRouterHandle {
     ctx: Context,
     main_addr: Address,
     api_addr: Address,
+    flow_control_id: Option<FlowControlId>,
 }
 
 #[async_trait]
@@ -40,17 +42,24 @@ impl AsyncTryClone for UdsRouterHandle {
             child_ctx,
             self.main_addr.clone(),
             self.api_addr.clone(),
+            self.flow_control_id.clone(),
         ))
     }
 }
 
 impl UdsRouterHandle {
     /// Create a new [`UdsRouterHandle`] with the given address
-    pub(crate) fn new(ctx: Context, main_addr: Address, api_addr: Address) -> Self {
+    pub(crate) fn new(
+        ctx: Context,
+        main_addr: Address,
+        api_addr: Address,
+        flow_control_id: Option<FlowControlId>,
+    ) -> Self {
         UdsRouterHandle {
             ctx,
             main_addr,
             api_addr,
+            flow_control_id,
         }
     }
 
@@ -63,6 +72,11 @@ impl UdsRouterHandle {
     pub(crate) fn main_addr(&self) -> &Address {
         &self.main_addr
     }
+
+    /// Return the [`FlowControlId`] of the connection receivers, if they are Producers
+    pub(crate) fn flow_control_id(&self) -> Option<&FlowControlId> {
+        self.flow_control_id.as_ref()
+    }
 }
 
 impl UdsRouterHandle {
@@ -1,4 +1,5 @@
 use core::ops::Deref;
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     async_trait, compat::sync::Arc, Address, AllowAll, Any, Decodable, LocalMessage, Mailbox,
     Mailboxes, Result, Routed, Worker,
@@ -24,12 +25,19 @@ pub(crate) struct UdsRouter {
     api_addr: Address,
     map: BTreeMap<Address, Address>,
     allow_auto_connection: bool,
+    flow_control_id: Option<FlowControlId>,
 }
 
 /// Public Implementations to instantiate a UDS Router and UDS Router Handler
 impl UdsRouter {
     /// Create and register a new UDS router with the given node context
-    pub async fn register(ctx: &Context) -> Result<UdsRouterHandle> {
+    ///
+    /// When a [`FlowControlId`] is given, the receivers of the connections are marked
+    /// as Producers for that [`FlowControlId`]
+    pub async fn register(
+        ctx: &Context,
+        flow_control_id: Option<FlowControlId>,
+    ) -> Result<UdsRouterHandle> {
         // This context is only used to start workers, doesn't need to send nor receive messages
         let mailboxes = Mailboxes::new(
             Mailbox::deny_all(Address::random_tagged("UdsRouter.detached")),
@@ -48,6 +56,7 @@ impl UdsRouter {
             api_addr: api_addr.clone(),
             map: BTreeMap::new(),
             allow_auto_connection: true,
+            flow_control_id,
         };
 
         let handle = router.create_self_handle().await?;
@@ -74,8 +83,12 @@ impl UdsRouter {
 
         let handle_ctx = self.ctx.new_detached_with_mailboxes(mailboxes).await?;
 
-        let handle =
-            UdsRouterHandle::new(handle_ctx, self.main_addr.clone(), self.api_addr.clone());
+        let handle = UdsRouterHandle::new(
+            handle_ctx,
+            self.main_addr.clone(),
+            self.api_addr.clone(),
+            self.flow_control_id.clone(),
+        );
 
         Ok(handle)
     }
@@ -1,11 +1,15 @@
 use std::os::unix::net::SocketAddr;
 
-use ockam_core::{async_trait, Address, AsyncTryClone, Result};
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::{async_trait, Address, AsyncTryClone, Result, Route};
 use ockam_node::{Context, HasContext};
+use ockam_transport_core::TransportError;
 
 use crate::{
     parse_socket_addr,
+    portal::{UdsInletListenProcessor, UdsOutletListenWorker},
     router::{UdsRouter, UdsRouterHandle},
+    UdsInletOptions, UdsOutletOptions,
 };
 
 /// High level management interface for UDS transports
@@ -55,13 +59,33 @@ pub struct UdsTransport {
 impl UdsTransport {
     /// Creates a a UDS Router and registers it with the given node [`Context`]
     pub async fn create(ctx: &Context) -> Result<Self> {
-        let router = UdsRouter::register(ctx).await?;
+        let router = UdsRouter::register(ctx, None).await?;
 
         Ok(Self {
             router_handle: router,
         })
     }
 
+    /// Creates a UDS Router whose connection receivers are marked as Producers for the
+    /// given [`FlowControlId`]. The messages they receive are then only delivered to the
+    /// Consumers of that [`FlowControlId`]
+    pub async fn create_as_producer(
+        ctx: &Context,
+        flow_control_id: &FlowControlId,
+    ) -> Result<Self> {
+        let router = UdsRouter::register(ctx, Some(flow_control_id.clone())).await?;
+
+        Ok(Self {
+            router_handle: router,
+        })
+    }
+
+    /// [`FlowControlId`] of the connection receivers, if they were marked as Producers
+    /// with [`UdsTransport::create_as_producer`]
+    pub fn flow_control_id(&self) -> Option<&FlowControlId> {
+        self.router_handle.flow_control_id()
+    }
+
     /// Connects the [`UdsTransport`] to the given socket peer.
     ///
     /// ```rust
@@ -109,6 +133,80 @@ impl UdsTransport {
         let sock_addr = parse_socket_addr(bind_addr.as_ref())?;
         self.router_handle.bind(sock_addr).await
     }
+
+    /// Create a UDS Inlet that listens on the socket at `bind_addr`, transforms the UDS stream
+    /// into Ockam Routable Messages and forwards them to the Outlet using `outlet_route`.
+    ///
+    /// The Outlet can be either a UDS or a TCP Outlet, since both use the same
+    /// [`PortalMessage`](ockam_transport_tcp::PortalMessage) protocol.
+    ///
+    /// ```rust
+    /// use ockam_transport_uds::{UdsInletOptions, UdsTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{route, Result};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let uds = UdsTransport::create(&ctx).await?;
+    /// let (_, inlet) = uds
+    ///     .create_inlet("/tmp/inlet-socket", route!["outlet"], UdsInletOptions::new())
+    ///     .await?;
+    /// # uds.stop_inlet(inlet).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_inlet<S: AsRef<str>>(
+        &self,
+        bind_addr: S,
+        outlet_route: impl Into<Route>,
+        options: UdsInletOptions,
+    ) -> Result<(SocketAddr, Address)> {
+        let sock_addr = parse_socket_addr(bind_addr.as_ref())?;
+        UdsInletListenProcessor::start(
+            self.router_handle.ctx(),
+            outlet_route.into(),
+            sock_addr,
+            options,
+        )
+        .await
+    }
+
+    /// Stop the inlet listening at `addr` and remove its socket file
+    pub async fn stop_inlet(&self, addr: impl Into<Address>) -> Result<()> {
+        self.router_handle.ctx().stop_processor(addr).await
+    }
+
+    /// Create a UDS Outlet listener at `address`. For every Inlet connection it connects
+    /// to the socket at `peer` and streams the data received from the Inlet to it.
+    ///
+    /// The Inlet can be either a UDS or a TCP Inlet.
+    ///
+    /// ```rust
+    /// use ockam_transport_uds::{UdsOutletOptions, UdsTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let uds = UdsTransport::create(&ctx).await?;
+    /// uds.create_outlet("outlet", "/var/run/postgresql/.s.PGSQL.5432", UdsOutletOptions::new())
+    ///     .await?;
+    /// # uds.stop_outlet("outlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_outlet<S: AsRef<str>>(
+        &self,
+        address: impl Into<Address>,
+        peer: S,
+        options: UdsOutletOptions,
+    ) -> Result<()> {
+        let peer = parse_socket_addr(peer.as_ref())?;
+        let path = peer
+            .as_pathname()
+            .ok_or(TransportError::InvalidAddress)?
+            .to_path_buf();
+        UdsOutletListenWorker::start(self.router_handle.ctx(), address.into(), path, options).await
+    }
+
+    /// Stop the outlet listening at `addr`
+    pub async fn stop_outlet(&self, addr: impl Into<Address>) -> Result<()> {
+        self.router_handle.ctx().stop_worker(addr).await
+    }
 }
 
 /// This trait adds a `create_uds_transport` method to any struct returning a Context.
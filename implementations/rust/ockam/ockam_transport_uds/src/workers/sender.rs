@@ -1,10 +1,12 @@
 use std::os::unix::net::SocketAddr;
 
+use ockam_core::flow_control::FlowControlOutgoingAccessControl;
 use ockam_core::{
     async_trait, compat::sync::Arc, Address, AllowAll, Any, Decodable, DenyAll, Encodable,
-    LocalMessage, Mailbox, Mailboxes, Message, Result, Routed, TransportMessage, Worker,
+    LocalMessage, Mailbox, Mailboxes, Message, OutgoingAccessControl, Result, Routed,
+    TransportMessage, Worker,
 };
-use ockam_node::{Context, WorkerBuilder};
+use ockam_node::{Context, ProcessorBuilder, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
 use socket2::SockRef;
@@ -228,7 +230,32 @@ impl Worker for UdsSendWorker {
             self.internal_addr.clone(),
         );
 
-        ctx.start_processor_with_access_control(self.rx_addr.clone(), receiver, DenyAll, AllowAll)
+        let outgoing_access_control: Arc<dyn OutgoingAccessControl> =
+            match self.router_handle.flow_control_id() {
+                Some(flow_control_id) => {
+                    let flow_controls = ctx.flow_controls();
+                    flow_controls.add_producer(self.rx_addr.clone(), flow_control_id, None, vec![]);
+                    // The receiver notifies its sender when the connection is closed
+                    flow_controls.add_consumer(self.internal_addr.clone(), flow_control_id);
+                    Arc::new(FlowControlOutgoingAccessControl::new(
+                        flow_controls,
+                        flow_control_id.clone(),
+                        None,
+                    ))
+                }
+                None => Arc::new(AllowAll),
+            };
+
+        ProcessorBuilder::new(receiver)
+            .with_mailboxes(Mailboxes::new(
+                Mailbox::new(
+                    self.rx_addr.clone(),
+                    Arc::new(DenyAll),
+                    outgoing_access_control,
+                ),
+                vec![],
+            ))
+            .start(ctx)
             .await?;
 
         Ok(())
@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, UnixListener, UnixStream};
+
+use ockam_core::compat::rand::random;
+use ockam_core::{route, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpInletOptions, TcpTransport};
+use ockam_transport_uds::{UdsInletOptions, UdsOutletOptions, UdsTransport};
+
+const LENGTH: usize = 32;
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{name}-{}.sock", random::<u32>()))
+}
+
+/// Start a UDS server answering to a single connection and a UDS outlet to it
+async fn setup_outlet(
+    uds: &UdsTransport,
+    payload1: [u8; LENGTH],
+    payload2: [u8; LENGTH],
+) -> Result<tokio::task::JoinHandle<()>> {
+    let target = socket_path("uds-portal-target");
+    let listener = UnixListener::bind(&target).unwrap();
+    uds.create_outlet("outlet", target.to_str().unwrap(), UdsOutletOptions::new())
+        .await?;
+
+    Ok(tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        read_assert_binary(&mut stream, payload1).await;
+        write_binary(&mut stream, payload2).await;
+        let _ = std::fs::remove_file(target);
+    }))
+}
+
+async fn write_binary(stream: &mut (impl AsyncWrite + Unpin), payload: [u8; LENGTH]) {
+    stream.write_all(&payload).await.unwrap();
+}
+
+async fn read_assert_binary(stream: &mut (impl AsyncRead + Unpin), expected_payload: [u8; LENGTH]) {
+    let mut payload = [0u8; LENGTH];
+    stream.read_exact(&mut payload).await.unwrap();
+    assert_eq!(payload, expected_payload);
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn uds_portal__standard_flow__should_succeed(ctx: &mut Context) -> Result<()> {
+    let payload1: [u8; LENGTH] = random();
+    let payload2: [u8; LENGTH] = random();
+
+    let uds = UdsTransport::create(ctx).await?;
+    let handle = setup_outlet(&uds, payload1, payload2).await?;
+
+    let inlet_path = socket_path("uds-portal-inlet");
+    let (_, inlet) = uds
+        .create_inlet(
+            inlet_path.to_str().unwrap(),
+            route!["outlet"],
+            UdsInletOptions::new(),
+        )
+        .await?;
+
+    let mut stream = UnixStream::connect(&inlet_path).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, payload2).await;
+
+    assert!(handle.await.is_ok());
+
+    uds.stop_inlet(inlet).await?;
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert!(!inlet_path.exists());
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn uds_portal__tcp_inlet__should_reach_uds_outlet(ctx: &mut Context) -> Result<()> {
+    let payload1: [u8; LENGTH] = random();
+    let payload2: [u8; LENGTH] = random();
+
+    let uds = UdsTransport::create(ctx).await?;
+    let handle = setup_outlet(&uds, payload1, payload2).await?;
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, payload2).await;
+
+    assert!(handle.await.is_ok());
+
+    ctx.stop().await
+}
@@ -1,6 +1,7 @@
 use core::str::FromStr;
 use std::net::{SocketAddr, ToSocketAddrs};
 
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{async_trait, Address, AsyncTryClone, DenyAll, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
@@ -15,6 +16,7 @@ use crate::{parse_socket_addr, WebSocketAddress};
 pub(crate) struct WebSocketRouterHandle {
     ctx: Context,
     api_addr: Address,
+    flow_control_id: Option<FlowControlId>,
 }
 
 #[async_trait]
@@ -28,13 +30,30 @@ impl AsyncTryClone for WebSocketRouterHandle {
                 DenyAll,
             )
             .await?;
-        Ok(Self::new(child_ctx, self.api_addr.clone()))
+        Ok(Self::new(
+            child_ctx,
+            self.api_addr.clone(),
+            self.flow_control_id.clone(),
+        ))
     }
 }
 
 impl WebSocketRouterHandle {
-    pub(crate) fn new(ctx: Context, api_addr: Address) -> Self {
-        Self { ctx, api_addr }
+    pub(crate) fn new(
+        ctx: Context,
+        api_addr: Address,
+        flow_control_id: Option<FlowControlId>,
+    ) -> Self {
+        Self {
+            ctx,
+            api_addr,
+            flow_control_id,
+        }
+    }
+
+    /// [`FlowControlId`] of the connection receivers, if they are Producers
+    pub(crate) fn flow_control_id(&self) -> Option<&FlowControlId> {
+        self.flow_control_id.as_ref()
     }
 
     /// Register a new connection worker with this router.
@@ -102,7 +121,13 @@ impl WebSocketRouterHandle {
 
         // Create a new `WorkerPair` for the given peer, initializing a new pair
         // of sender worker and receiver processor.
-        let pair = WorkerPair::from_client(&self.ctx, peer_addr, hostnames).await?;
+        let pair = WorkerPair::from_client(
+            &self.ctx,
+            peer_addr,
+            hostnames,
+            self.flow_control_id.clone(),
+        )
+        .await?;
 
         // Handle node's register request.
         self.register(&pair).await
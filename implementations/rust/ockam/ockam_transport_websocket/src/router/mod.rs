@@ -4,6 +4,7 @@ use std::ops::Deref;
 use std::sync::Arc;
 
 pub(crate) use handle::WebSocketRouterHandle;
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     async_trait, Address, AllowAll, Any, Decodable, LocalMessage, Mailbox, Mailboxes, Message,
     Result, Routed, Worker,
@@ -47,11 +48,18 @@ pub(crate) struct WebSocketRouter {
     api_addr: Address,
     map: BTreeMap<Address, Address>,
     allow_auto_connection: bool,
+    flow_control_id: Option<FlowControlId>,
 }
 
 impl WebSocketRouter {
     /// Create and register a new WebSocket router with the node context.
-    pub(crate) async fn register(ctx: &Context) -> Result<WebSocketRouterHandle> {
+    ///
+    /// When a [`FlowControlId`] is given, the receivers of the connections are marked
+    /// as Producers for that [`FlowControlId`].
+    pub(crate) async fn register(
+        ctx: &Context,
+        flow_control_id: Option<FlowControlId>,
+    ) -> Result<WebSocketRouterHandle> {
         let main_addr = Address::random_tagged("WebSocketRouter.main_addr");
         let api_addr = Address::random_tagged("WebSocketRouter.api_addr");
         debug!(
@@ -74,6 +82,7 @@ impl WebSocketRouter {
             api_addr: api_addr.clone(),
             map: BTreeMap::new(),
             allow_auto_connection: true,
+            flow_control_id,
         };
 
         let handle = router.create_self_handle(ctx).await?;
@@ -106,7 +115,11 @@ impl WebSocketRouter {
             vec![],
         );
         let handle_ctx = ctx.new_detached_with_mailboxes(mailboxes).await?;
-        let handle = WebSocketRouterHandle::new(handle_ctx, self.api_addr.clone());
+        let handle = WebSocketRouterHandle::new(
+            handle_ctx,
+            self.api_addr.clone(),
+            self.flow_control_id.clone(),
+        );
         Ok(handle)
     }
 }
@@ -225,7 +238,13 @@ impl WebSocketRouter {
 
         // Create a new `WorkerPair` for the given peer, initializing a new pair
         // of sender worker and receiver processor.
-        let pair = WorkerPair::from_client(&self.ctx, peer_addr, hostnames).await?;
+        let pair = WorkerPair::from_client(
+            &self.ctx,
+            peer_addr,
+            hostnames,
+            self.flow_control_id.clone(),
+        )
+        .await?;
 
         // Handle node's register request.
         let mut accepts = vec![pair.peer()];
@@ -2,6 +2,7 @@ use core::fmt;
 use std::net::SocketAddr;
 use std::str::FromStr;
 
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{async_trait, Address, Result};
 use ockam_node::{Context, HasContext};
 
@@ -61,10 +62,27 @@ impl WebSocketTransport {
     /// # Ok(()) }
     /// ```
     pub async fn create(ctx: &Context) -> Result<WebSocketTransport> {
-        let router_handle = WebSocketRouter::register(ctx).await?;
+        let router_handle = WebSocketRouter::register(ctx, None).await?;
         Ok(Self { router_handle })
     }
 
+    /// Create a new WebSocket transport whose connection receivers are marked as Producers
+    /// for the given [`FlowControlId`]. The messages they receive are then only delivered
+    /// to the Consumers of that [`FlowControlId`].
+    pub async fn create_as_producer(
+        ctx: &Context,
+        flow_control_id: &FlowControlId,
+    ) -> Result<WebSocketTransport> {
+        let router_handle = WebSocketRouter::register(ctx, Some(flow_control_id.clone())).await?;
+        Ok(Self { router_handle })
+    }
+
+    /// [`FlowControlId`] of the connection receivers, if they were marked as Producers
+    /// with [`WebSocketTransport::create_as_producer`]
+    pub fn flow_control_id(&self) -> Option<&FlowControlId> {
+        self.router_handle.flow_control_id()
+    }
+
     /// Establish an outgoing WebSocket connection on an existing transport.
     ///
     /// ```rust
@@ -7,11 +7,12 @@ use futures_util::{SinkExt, StreamExt};
 use tokio_tungstenite::tungstenite::protocol::Message as WebSocketMessage;
 
 use crate::error::WebSocketError;
+use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl};
 use ockam_core::{
     async_trait, route, Address, AllowAll, Any, Decodable, Encodable, LocalMessage, Mailbox,
-    Mailboxes, Result, Routed, TransportMessage, Worker,
+    Mailboxes, OutgoingAccessControl, Result, Routed, TransportMessage, Worker,
 };
-use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+use ockam_node::{Context, DelayedEvent, ProcessorBuilder, WorkerBuilder};
 use ockam_transport_core::TransportError;
 
 use crate::workers::{
@@ -46,6 +47,7 @@ impl WorkerPair {
         ctx: &Context,
         peer: SocketAddr,
         hostnames: Vec<String>,
+        flow_control_id: Option<FlowControlId>,
     ) -> Result<WorkerPair> {
         trace!("Creating new WS worker pair");
 
@@ -54,6 +56,7 @@ impl WorkerPair {
             peer,
             internal_addr.clone(),
             DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
+            flow_control_id,
         );
 
         let tx_addr = Address::random_tagged("WebSocketSender.tx_addr.from_client");
@@ -90,6 +93,7 @@ impl WorkerPair {
         stream: WebSocketStream<TcpServerStream>,
         peer: SocketAddr,
         hostnames: Vec<String>,
+        flow_control_id: Option<FlowControlId>,
     ) -> Result<WorkerPair> {
         trace!("Creating new WS worker pair");
 
@@ -99,6 +103,7 @@ impl WorkerPair {
             peer,
             internal_addr.clone(),
             DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
+            flow_control_id,
         );
 
         let tx_addr = Address::random_tagged("WebSocketSender.tx_addr.from_server");
@@ -143,6 +148,8 @@ where
     internal_addr: Address,
     heartbeat: DelayedEvent<Vec<u8>>,
     heartbeat_interval: Option<Duration>,
+    /// The receiver is a Producer for this [`FlowControlId`], if set
+    flow_control_id: Option<FlowControlId>,
 }
 
 impl<S> WebSocketSendWorker<S>
@@ -153,13 +160,34 @@ where
         if let Some(ws_stream) = self.ws_stream.take() {
             let rx_addr = Address::random_tagged("WebSocketSendWorker.rx_addr");
             let receiver = WebSocketRecvProcessor::new(ws_stream, self.peer);
-            ctx.start_processor_with_access_control(
-                rx_addr.clone(),
-                receiver,
-                AllowAll, // FIXME: @ac
-                AllowAll, // FIXME: @ac
-            )
-            .await?;
+            let outgoing_access_control: Arc<dyn OutgoingAccessControl> =
+                match &self.flow_control_id {
+                    Some(flow_control_id) => {
+                        ctx.flow_controls().add_producer(
+                            rx_addr.clone(),
+                            flow_control_id,
+                            None,
+                            vec![],
+                        );
+                        Arc::new(FlowControlOutgoingAccessControl::new(
+                            ctx.flow_controls(),
+                            flow_control_id.clone(),
+                            None,
+                        ))
+                    }
+                    None => Arc::new(AllowAll), // FIXME: @ac
+                };
+            ProcessorBuilder::new(receiver)
+                .with_mailboxes(Mailboxes::new(
+                    Mailbox::new(
+                        rx_addr,
+                        Arc::new(AllowAll), // FIXME: @ac
+                        outgoing_access_control,
+                    ),
+                    vec![],
+                ))
+                .start(ctx)
+                .await?;
         } else {
             return Err(TransportError::GenericIo.into());
         }
@@ -232,6 +260,7 @@ impl WebSocketSendWorker<TcpServerStream> {
         peer: SocketAddr,
         internal_addr: Address,
         heartbeat: DelayedEvent<Vec<u8>>,
+        flow_control_id: Option<FlowControlId>,
     ) -> Self {
         let (ws_sink, ws_stream) = stream.split();
         Self {
@@ -241,12 +270,18 @@ impl WebSocketSendWorker<TcpServerStream> {
             internal_addr,
             heartbeat,
             heartbeat_interval: None,
+            flow_control_id,
         }
     }
 }
 
 impl WebSocketSendWorker<TcpClientStream> {
-    fn new(peer: SocketAddr, internal_addr: Address, heartbeat: DelayedEvent<Vec<u8>>) -> Self {
+    fn new(
+        peer: SocketAddr,
+        internal_addr: Address,
+        heartbeat: DelayedEvent<Vec<u8>>,
+        flow_control_id: Option<FlowControlId>,
+    ) -> Self {
         Self {
             ws_stream: None,
             ws_sink: None,
@@ -254,6 +289,7 @@ impl WebSocketSendWorker<TcpClientStream> {
             internal_addr,
             heartbeat,
             heartbeat_interval: None,
+            flow_control_id,
         }
     }
 
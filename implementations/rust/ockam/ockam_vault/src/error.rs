@@ -33,6 +33,18 @@ pub enum VaultError {
     InvalidSha256Len,
     /// Invalid Signature Size
     InvalidSignatureSize,
+    /// The vault storage is encrypted and must be opened with a passphrase
+    StorageEncrypted,
+    /// The vault storage is not encrypted
+    StorageNotEncrypted,
+    /// The vault storage could not be decrypted with the given passphrase
+    InvalidStoragePassphrase,
+    /// The storage key could not be derived from the passphrase
+    StorageKeyDerivation,
+    /// Invalid KEM Ciphertext length
+    InvalidKemCiphertextLength,
+    /// ML-KEM is not supported, the "ml-kem" feature is disabled
+    MlKemNotSupported,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -54,6 +66,12 @@ impl core::fmt::Display for VaultError {
             Self::KeyNotFound => write!(f, "key not found"),
             Self::InvalidSha256Len => write!(f, "invalid sha256 len"),
             Self::InvalidSignatureSize => write!(f, "invalid signature len"),
+            Self::StorageEncrypted => write!(f, "the vault storage is encrypted"),
+            Self::StorageNotEncrypted => write!(f, "the vault storage is not encrypted"),
+            Self::InvalidStoragePassphrase => write!(f, "invalid vault storage passphrase"),
+            Self::StorageKeyDerivation => write!(f, "vault storage key derivation failed"),
+            Self::InvalidKemCiphertextLength => write!(f, "invalid kem ciphertext length"),
+            Self::MlKemNotSupported => write!(f, "ml-kem is not supported by this vault"),
         }
     }
 }
@@ -65,6 +83,8 @@ impl From<VaultError> for Error {
         let kind = match err {
             InvalidPublicKey | InvalidKeyType | InvalidHkdfOutputType => Kind::Misuse,
             UnknownEcdhKeyType => Kind::NotFound,
+            StorageEncrypted | StorageNotEncrypted => Kind::Misuse,
+            MlKemNotSupported => Kind::Unsupported,
             _ => Kind::Invalid,
         };
 
@@ -0,0 +1,72 @@
+use crate::{BufferSecret, MlKemCiphertext, MlKemPublicKey, MlKemSecretKey, VaultError};
+
+use ockam_core::Result;
+
+use cfg_if::cfg_if;
+
+cfg_if! {
+    if #[cfg(feature = "ml-kem")] {
+        use pqcrypto_mlkem::mlkem768;
+        use pqcrypto_traits::kem::{
+            Ciphertext as _, PublicKey as _, SecretKey as _, SharedSecret as _,
+        };
+
+        /// Generate a new ML-KEM-768 key pair
+        pub(super) fn generate_secret_key() -> Result<MlKemSecretKey> {
+            let (public_key, secret_key) = mlkem768::keypair();
+
+            Ok(MlKemSecretKey::new(
+                secret_key.as_bytes().to_vec(),
+                public_key.as_bytes().to_vec(),
+            ))
+        }
+
+        /// Encapsulate a new shared secret for the peer public key
+        pub(super) fn encapsulate(
+            peer_public_key: &MlKemPublicKey,
+        ) -> Result<(MlKemCiphertext, BufferSecret)> {
+            let public_key = mlkem768::PublicKey::from_bytes(&peer_public_key.0)
+                .map_err(|_| VaultError::InvalidPublicLength)?;
+            let (shared_secret, ciphertext) = mlkem768::encapsulate(&public_key);
+
+            Ok((
+                MlKemCiphertext(ciphertext.as_bytes().to_vec()),
+                BufferSecret::new(shared_secret.as_bytes().to_vec()),
+            ))
+        }
+
+        /// Decapsulate the shared secret contained in the ciphertext
+        pub(super) fn decapsulate(
+            secret: &MlKemSecretKey,
+            ciphertext: &MlKemCiphertext,
+        ) -> Result<BufferSecret> {
+            let secret_key = mlkem768::SecretKey::from_bytes(secret.secret_key())
+                .map_err(|_| VaultError::InvalidSecretLength)?;
+            let ciphertext = mlkem768::Ciphertext::from_bytes(&ciphertext.0)
+                .map_err(|_| VaultError::InvalidKemCiphertextLength)?;
+            let shared_secret = mlkem768::decapsulate(&ciphertext, &secret_key);
+
+            Ok(BufferSecret::new(shared_secret.as_bytes().to_vec()))
+        }
+    } else {
+        /// ML-KEM is not available without the "ml-kem" feature
+        pub(super) fn generate_secret_key() -> Result<MlKemSecretKey> {
+            Err(VaultError::MlKemNotSupported.into())
+        }
+
+        /// ML-KEM is not available without the "ml-kem" feature
+        pub(super) fn encapsulate(
+            _peer_public_key: &MlKemPublicKey,
+        ) -> Result<(MlKemCiphertext, BufferSecret)> {
+            Err(VaultError::MlKemNotSupported.into())
+        }
+
+        /// ML-KEM is not available without the "ml-kem" feature
+        pub(super) fn decapsulate(
+            _secret: &MlKemSecretKey,
+            _ciphertext: &MlKemCiphertext,
+        ) -> Result<BufferSecret> {
+            Err(VaultError::MlKemNotSupported.into())
+        }
+    }
+}
@@ -5,6 +5,7 @@
 ))]
 pub(crate) mod aes;
 
+mod ml_kem;
 mod types;
 #[allow(clippy::module_inception)]
 mod vault_for_secure_channels;
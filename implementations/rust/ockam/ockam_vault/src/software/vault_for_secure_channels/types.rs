@@ -21,6 +21,32 @@ impl X25519SecretKey {
     }
 }
 
+/// ML-KEM-768 Secret Key, stored with the corresponding public key.
+#[derive(Eq, PartialEq, Clone, Zeroize, ZeroizeOnDrop)]
+pub struct MlKemSecretKey {
+    secret_key: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+impl MlKemSecretKey {
+    /// Constructor.
+    pub fn new(secret_key: Vec<u8>, public_key: Vec<u8>) -> Self {
+        Self {
+            secret_key,
+            public_key,
+        }
+    }
+
+    #[cfg_attr(not(feature = "ml-kem"), allow(dead_code))]
+    pub(crate) fn secret_key(&self) -> &[u8] {
+        self.secret_key.as_slice()
+    }
+
+    pub(crate) fn public_key(&self) -> &[u8] {
+        self.public_key.as_slice()
+    }
+}
+
 /// Buffer with sensitive data, like HKDF output.
 #[derive(Eq, PartialEq, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct BufferSecret(Vec<u8>);
@@ -1,10 +1,11 @@
 use super::aes::make_aes;
+use super::ml_kem;
 
 use crate::{
     AeadSecret, AeadSecretKeyHandle, BufferSecret, HKDFNumberOfOutputs, HandleToSecret, HashOutput,
-    HkdfOutput, SecretBufferHandle, SoftwareVaultForVerifyingSignatures, VaultError,
-    VaultForSecureChannels, X25519PublicKey, X25519SecretKey, X25519SecretKeyHandle,
-    AEAD_SECRET_LENGTH,
+    HkdfOutput, MlKemCiphertext, MlKemPublicKey, MlKemSecretKey, MlKemSecretKeyHandle,
+    SecretBufferHandle, SoftwareVaultForVerifyingSignatures, VaultError, VaultForSecureChannels,
+    X25519PublicKey, X25519SecretKey, X25519SecretKeyHandle, AEAD_SECRET_LENGTH,
 };
 
 use ockam_core::compat::collections::BTreeMap;
@@ -22,6 +23,7 @@ pub struct SoftwareVaultForSecureChannels {
     ephemeral_buffer_secrets: Arc<RwLock<BTreeMap<SecretBufferHandle, BufferSecret>>>,
     ephemeral_aead_secrets: Arc<RwLock<BTreeMap<AeadSecretKeyHandle, AeadSecret>>>,
     ephemeral_x25519_secrets: Arc<RwLock<BTreeMap<X25519SecretKeyHandle, X25519SecretKey>>>,
+    ephemeral_ml_kem_secrets: Arc<RwLock<BTreeMap<MlKemSecretKeyHandle, MlKemSecretKey>>>,
     // Use String as a key for backwards compatibility
     static_x25519_secrets: Arc<dyn KeyValueStorage<KeyId, StoredSecret>>,
 }
@@ -33,6 +35,7 @@ impl SoftwareVaultForSecureChannels {
             ephemeral_buffer_secrets: Default::default(),
             ephemeral_aead_secrets: Default::default(),
             ephemeral_x25519_secrets: Default::default(),
+            ephemeral_ml_kem_secrets: Default::default(),
             static_x25519_secrets: storage,
         }
     }
@@ -91,6 +94,11 @@ impl SoftwareVaultForSecureChannels {
         self.ephemeral_x25519_secrets.read().unwrap().len()
     }
 
+    /// Return the total number of ephemeral ML-KEM secrets present in the Vault
+    pub fn number_of_ephemeral_ml_kem_secrets(&self) -> usize {
+        self.ephemeral_ml_kem_secrets.read().unwrap().len()
+    }
+
     /// Return the total number of ephemeral buffer secrets present in the Vault
     pub fn number_of_ephemeral_buffer_secrets(&self) -> usize {
         self.ephemeral_buffer_secrets.read().unwrap().len()
@@ -194,6 +202,13 @@ impl SoftwareVaultForSecureChannels {
         }
     }
 
+    async fn get_ml_kem_secret(&self, handle: &MlKemSecretKeyHandle) -> Result<MlKemSecretKey> {
+        match self.ephemeral_ml_kem_secrets.read().unwrap().get(handle) {
+            Some(secret) => Ok(secret.clone()),
+            None => Err(VaultError::KeyNotFound.into()),
+        }
+    }
+
     async fn get_aead_secret(&self, handle: &AeadSecretKeyHandle) -> Result<AeadSecret> {
         match self.ephemeral_aead_secrets.read().unwrap().get(handle) {
             Some(secret) => Ok(secret.clone()),
@@ -345,6 +360,59 @@ impl VaultForSecureChannels for SoftwareVaultForSecureChannels {
         Ok(Self::compute_handle_for_public_key(public_key))
     }
 
+    async fn generate_ephemeral_ml_kem_secret_key(&self) -> Result<MlKemSecretKeyHandle> {
+        let secret = ml_kem::generate_secret_key()?;
+        let handle = MlKemSecretKeyHandle(Self::generate_random_handle());
+
+        self.ephemeral_ml_kem_secrets
+            .write()
+            .unwrap()
+            .insert(handle.clone(), secret);
+
+        Ok(handle)
+    }
+
+    async fn delete_ephemeral_ml_kem_secret_key(
+        &self,
+        secret_key_handle: MlKemSecretKeyHandle,
+    ) -> Result<bool> {
+        Ok(self
+            .ephemeral_ml_kem_secrets
+            .write()
+            .unwrap()
+            .remove(&secret_key_handle)
+            .is_some())
+    }
+
+    async fn get_ml_kem_public_key(
+        &self,
+        secret_key_handle: &MlKemSecretKeyHandle,
+    ) -> Result<MlKemPublicKey> {
+        let secret = self.get_ml_kem_secret(secret_key_handle).await?;
+
+        Ok(MlKemPublicKey(secret.public_key().to_vec()))
+    }
+
+    async fn ml_kem_encapsulate(
+        &self,
+        peer_public_key: &MlKemPublicKey,
+    ) -> Result<(MlKemCiphertext, SecretBufferHandle)> {
+        let (ciphertext, shared_secret) = ml_kem::encapsulate(peer_public_key)?;
+
+        Ok((ciphertext, self.import_buffer_secret_impl(shared_secret)))
+    }
+
+    async fn ml_kem_decapsulate(
+        &self,
+        secret_key_handle: &MlKemSecretKeyHandle,
+        ciphertext: &MlKemCiphertext,
+    ) -> Result<SecretBufferHandle> {
+        let secret = self.get_ml_kem_secret(secret_key_handle).await?;
+        let shared_secret = ml_kem::decapsulate(&secret, ciphertext)?;
+
+        Ok(self.import_buffer_secret_impl(shared_secret))
+    }
+
     async fn import_secret_buffer(&self, buffer: Vec<u8>) -> Result<SecretBufferHandle> {
         Ok(self.import_buffer_secret_impl(BufferSecret::new(buffer)))
     }
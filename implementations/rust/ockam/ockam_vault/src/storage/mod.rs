@@ -2,3 +2,6 @@
 mod persistent_storage;
 
 pub use persistent_storage::*;
+
+/// Encryption of the secrets stored in a file
+mod storage_encryption;
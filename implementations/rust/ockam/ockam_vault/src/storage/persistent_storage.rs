@@ -4,6 +4,8 @@ use ockam_core::{async_trait, Result};
 use ockam_node::{FileValueStorage, InMemoryKeyValueStorage, KeyValueStorage, ValueStorage};
 
 use crate::legacy::{KeyId, Secret, SecretAttributes, StoredSecret};
+use crate::storage::storage_encryption::{EncryptedSecrets, StorageKey};
+use crate::VaultError;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::path::Path;
 
@@ -13,23 +15,126 @@ use std::path::Path;
 /// WARNING: This implementation provides limited consistency if the same file is reused from
 /// multiple instances and/or processes. For example, if one process deletes a value, the other
 /// process will still have it in its cache and return it on a Get query.
+///
+/// The file can optionally be encrypted with a key derived from a passphrase (Argon2id + AES-GCM)
 pub struct PersistentStorage {
-    storage: Arc<FileValueStorage<StoredSecrets>>,
+    storage: Arc<FileValueStorage<VaultFile>>,
+    key: Option<Arc<StorageKey>>,
     cache: InMemoryKeyValueStorage<KeyId, StoredSecret>,
 }
 
 impl PersistentStorage {
     /// Create a new file storage for a Vault
+    /// This fails if the existing file is encrypted
     pub async fn create(path: &Path) -> Result<Arc<dyn KeyValueStorage<KeyId, StoredSecret>>> {
         let storage = Arc::new(FileValueStorage::create(path).await?);
+        if storage
+            .read_value(|file: VaultFile| Ok(file.is_encrypted()))
+            .await?
+        {
+            return Err(VaultError::StorageEncrypted.into());
+        }
+        let cache = InMemoryKeyValueStorage::new();
+        Ok(Arc::new(PersistentStorage {
+            storage,
+            key: None,
+            cache,
+        }))
+    }
+
+    /// Create a new file storage for a Vault, encrypted with a key derived from a passphrase.
+    /// This fails if the passphrase is not the one used to encrypt an existing file.
+    ///
+    /// An existing unencrypted file must first be migrated with [`PersistentStorage::encrypt`]
+    pub async fn create_encrypted(
+        path: &Path,
+        passphrase: &[u8],
+    ) -> Result<Arc<dyn KeyValueStorage<KeyId, StoredSecret>>> {
+        let storage = Arc::new(FileValueStorage::create(path).await?);
+        let key = match storage.read_value(Ok::<VaultFile, _>).await? {
+            VaultFile::Encrypted(encrypted) => {
+                let key = StorageKey::derive(passphrase, encrypted.kdf().clone())?;
+                // make sure that the passphrase is correct
+                encrypted.decrypt(&key)?;
+                Arc::new(key)
+            }
+            VaultFile::Plain(secrets) if secrets.is_empty() => {
+                let key = Arc::new(StorageKey::generate(passphrase)?);
+                Self::write_encrypted(&storage, key.clone()).await?;
+                key
+            }
+            VaultFile::Plain(_) => return Err(VaultError::StorageNotEncrypted.into()),
+        };
         let cache = InMemoryKeyValueStorage::new();
-        Ok(Arc::new(PersistentStorage { storage, cache }))
+        Ok(Arc::new(PersistentStorage {
+            storage,
+            key: Some(key),
+            cache,
+        }))
+    }
+
+    /// Encrypt an existing unencrypted file storage with a key derived from a passphrase
+    pub async fn encrypt(path: &Path, passphrase: &[u8]) -> Result<()> {
+        let storage = FileValueStorage::create(path).await?;
+        let key = Arc::new(StorageKey::generate(passphrase)?);
+        Self::write_encrypted(&storage, key).await
+    }
+
+    /// Encrypt the secrets of an unencrypted file
+    async fn write_encrypted(
+        storage: &FileValueStorage<VaultFile>,
+        key: Arc<StorageKey>,
+    ) -> Result<()> {
+        storage
+            .update_value(move |file: VaultFile| match file {
+                VaultFile::Plain(secrets) => VaultFile::new(secrets, Some(&key)),
+                VaultFile::Encrypted(_) => Err(VaultError::StorageEncrypted.into()),
+            })
+            .await
+    }
+}
+
+/// Content of the storage file: either the secrets or the encrypted secrets
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum VaultFile {
+    Encrypted(EncryptedSecrets),
+    Plain(StoredSecrets),
+}
+
+impl Default for VaultFile {
+    fn default() -> Self {
+        VaultFile::Plain(StoredSecrets::default())
+    }
+}
+
+impl VaultFile {
+    fn new(secrets: StoredSecrets, key: Option<&StorageKey>) -> Result<VaultFile> {
+        match key {
+            Some(key) => Ok(VaultFile::Encrypted(EncryptedSecrets::encrypt(
+                key, &secrets,
+            )?)),
+            None => Ok(VaultFile::Plain(secrets)),
+        }
+    }
+
+    fn is_encrypted(&self) -> bool {
+        matches!(self, VaultFile::Encrypted(_))
+    }
+
+    fn into_secrets(self, key: Option<&StorageKey>) -> Result<StoredSecrets> {
+        match (self, key) {
+            (VaultFile::Plain(secrets), None) => Ok(secrets),
+            (VaultFile::Encrypted(encrypted), Some(key)) => encrypted.decrypt(key),
+            (VaultFile::Plain(_), Some(_)) => Err(VaultError::StorageNotEncrypted.into()),
+            (VaultFile::Encrypted(_), None) => Err(VaultError::StorageEncrypted.into()),
+        }
     }
 }
 
 /// This struct is serialized to a file in order to persist vault data
 #[derive(Debug, Clone, Default)]
-struct StoredSecrets {
+pub(crate) struct StoredSecrets {
     secrets: BTreeMap<KeyId, StoredSecret>,
 }
 
@@ -44,6 +149,10 @@ struct FileSecret {
 }
 
 impl StoredSecrets {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
     fn add_stored_secret(&mut self, key_id: KeyId, stored_secret: StoredSecret) {
         self.secrets.insert(key_id, stored_secret);
     }
@@ -117,9 +226,11 @@ impl KeyValueStorage<KeyId, StoredSecret> for PersistentStorage {
             .put(key_id.clone(), stored_secret.clone())
             .await?;
 
-        let t = move |mut v: StoredSecrets| {
+        let key = self.key.clone();
+        let t = move |file: VaultFile| {
+            let mut v = file.into_secrets(key.as_deref())?;
             v.add_stored_secret(key_id.clone(), stored_secret.clone());
-            Ok(v)
+            VaultFile::new(v, key.as_deref())
         };
         self.storage.update_value(t).await
     }
@@ -129,17 +240,21 @@ impl KeyValueStorage<KeyId, StoredSecret> for PersistentStorage {
             return Ok(Some(s));
         }
         let k = key_id.clone();
-        let t =
-            move |v: StoredSecrets| -> Result<Option<StoredSecret>> { Ok(v.get_stored_secret(&k)) };
+        let key = self.key.clone();
+        let t = move |file: VaultFile| -> Result<Option<StoredSecret>> {
+            Ok(file.into_secrets(key.as_deref())?.get_stored_secret(&k))
+        };
         self.storage.read_value(t).await
     }
 
     async fn delete(&self, key_id: &KeyId) -> Result<Option<StoredSecret>> {
         self.cache.delete(key_id).await?;
         let k = key_id.clone();
-        let t = move |mut v: StoredSecrets| -> Result<(StoredSecrets, Option<StoredSecret>)> {
+        let key = self.key.clone();
+        let t = move |file: VaultFile| -> Result<(VaultFile, Option<StoredSecret>)> {
+            let mut v = file.into_secrets(key.as_deref())?;
             let r = v.delete_stored_secret(&k);
-            Ok((v, r))
+            Ok((VaultFile::new(v, key.as_deref())?, r))
         };
         self.storage.modify_value(t).await
     }
@@ -184,4 +299,55 @@ pub(crate) mod tests {
         assert_eq!(actual, Some(stored_secret));
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_encrypted_persistent_storage() -> Result<()> {
+        let temp_file = NamedTempFile::new().unwrap();
+        let storage = PersistentStorage::create_encrypted(temp_file.path(), b"passphrase").await?;
+
+        let secret = Secret::new(vec![1; 32]);
+        let key_id: KeyId = "key-id".into();
+        let stored_secret = StoredSecret::new(secret, SecretAttributes::Ed25519);
+        storage.put(key_id.clone(), stored_secret.clone()).await?;
+
+        let file_contents = std::fs::read_to_string(temp_file.path()).unwrap();
+        assert!(file_contents.contains("argon2id"));
+        assert!(!file_contents.contains("key-id"));
+        assert!(!file_contents.contains(&hex::encode([1; 32])));
+
+        // the file can only be opened with the right passphrase
+        let storage = PersistentStorage::create_encrypted(temp_file.path(), b"passphrase").await?;
+        assert_eq!(storage.get(&key_id).await?, Some(stored_secret));
+        assert!(
+            PersistentStorage::create_encrypted(temp_file.path(), b"wrong passphrase")
+                .await
+                .is_err()
+        );
+        assert!(PersistentStorage::create(temp_file.path()).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_existing_persistent_storage() -> Result<()> {
+        let temp_file = NamedTempFile::new().unwrap();
+        let storage = PersistentStorage::create(temp_file.path()).await?;
+        let key_id: KeyId = "key-id".into();
+        let stored_secret = StoredSecret::new(Secret::new(vec![1; 32]), SecretAttributes::Ed25519);
+        storage.put(key_id.clone(), stored_secret.clone()).await?;
+
+        // an unencrypted file must be migrated explicitly
+        assert!(
+            PersistentStorage::create_encrypted(temp_file.path(), b"passphrase")
+                .await
+                .is_err()
+        );
+        PersistentStorage::encrypt(temp_file.path(), b"passphrase").await?;
+        assert!(PersistentStorage::encrypt(temp_file.path(), b"passphrase")
+            .await
+            .is_err());
+
+        let storage = PersistentStorage::create_encrypted(temp_file.path(), b"passphrase").await?;
+        assert_eq!(storage.get(&key_id).await?, Some(stored_secret));
+        Ok(())
+    }
 }
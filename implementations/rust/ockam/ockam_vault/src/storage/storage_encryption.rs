@@ -0,0 +1,201 @@
+use aes_gcm::aead::{Aead, NewAead, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::Result;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::storage::persistent_storage::StoredSecrets;
+use crate::VaultError;
+
+/// Name of the key derivation function used for the storage key
+const ARGON2ID: &str = "argon2id";
+
+/// Length of the salt used to derive the storage key
+const SALT_LENGTH: usize = 16;
+
+/// Length of the AES-GCM nonce used to encrypt the secrets
+const NONCE_LENGTH: usize = 12;
+
+/// Maximum Argon2 memory cost accepted from a storage file, in KiB (1 GiB).
+/// The costs are read from the file, a larger value could exhaust the memory of the node
+const MAX_MEMORY_COST: u32 = 1024 * 1024;
+
+/// Maximum Argon2 time cost accepted from a storage file
+const MAX_TIME_COST: u32 = 16;
+
+/// Maximum Argon2 parallelism accepted from a storage file
+const MAX_PARALLELISM: u32 = 16;
+
+/// Associated data authenticated with the encrypted secrets
+const STORAGE_AAD: &[u8] = b"ockam_vault_storage";
+
+/// Parameters used to derive the storage key from a passphrase.
+/// They are stored next to the encrypted secrets so that they can be changed
+/// for new vaults without breaking existing ones
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KdfParameters {
+    algorithm: String,
+    salt: String,
+    memory_cost: u32,
+    time_cost: u32,
+    parallelism: u32,
+}
+
+impl KdfParameters {
+    /// Create new parameters with a random salt and the default Argon2 costs
+    fn generate() -> Self {
+        Self::generate_with_costs(
+            Params::DEFAULT_M_COST,
+            Params::DEFAULT_T_COST,
+            Params::DEFAULT_P_COST,
+        )
+    }
+
+    fn generate_with_costs(memory_cost: u32, time_cost: u32, parallelism: u32) -> Self {
+        let mut salt = [0u8; SALT_LENGTH];
+        thread_rng().fill_bytes(&mut salt);
+        Self {
+            algorithm: ARGON2ID.to_string(),
+            salt: hex::encode(salt),
+            memory_cost,
+            time_cost,
+            parallelism,
+        }
+    }
+
+    /// Check that the costs are low enough to derive a key without exhausting
+    /// the resources of the node
+    fn is_bounded(&self) -> bool {
+        self.memory_cost <= MAX_MEMORY_COST
+            && self.time_cost <= MAX_TIME_COST
+            && self.parallelism <= MAX_PARALLELISM
+    }
+}
+
+/// Key used to encrypt the vault storage, derived from a passphrase
+pub(crate) struct StorageKey {
+    key: Zeroizing<[u8; 32]>,
+    kdf: KdfParameters,
+}
+
+impl StorageKey {
+    /// Derive a key for a new storage file, using a random salt
+    pub(crate) fn generate(passphrase: &[u8]) -> Result<Self> {
+        Self::derive(passphrase, KdfParameters::generate())
+    }
+
+    /// Derive a key from a passphrase and the parameters of an existing storage file
+    pub(crate) fn derive(passphrase: &[u8], kdf: KdfParameters) -> Result<Self> {
+        if kdf.algorithm != ARGON2ID || !kdf.is_bounded() {
+            return Err(VaultError::StorageKeyDerivation.into());
+        }
+        let salt = hex::decode(&kdf.salt).map_err(|_| VaultError::StorageKeyDerivation)?;
+        let params = Params::new(kdf.memory_cost, kdf.time_cost, kdf.parallelism, Some(32))
+            .map_err(|_| VaultError::StorageKeyDerivation)?;
+
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase, &salt, key.as_mut())
+            .map_err(|_| VaultError::StorageKeyDerivation)?;
+
+        Ok(Self { key, kdf })
+    }
+}
+
+/// Content of an encrypted storage file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct EncryptedSecrets {
+    kdf: KdfParameters,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl EncryptedSecrets {
+    /// Parameters needed to derive the key of this storage file
+    pub(crate) fn kdf(&self) -> &KdfParameters {
+        &self.kdf
+    }
+
+    /// Encrypt secrets with a fresh nonce
+    pub(crate) fn encrypt(key: &StorageKey, secrets: &StoredSecrets) -> Result<Self> {
+        let plaintext =
+            Zeroizing::new(serde_cbor::to_vec(secrets).map_err(|_| VaultError::AeadAesGcmEncrypt)?);
+        let mut nonce = [0u8; NONCE_LENGTH];
+        thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = Aes256Gcm::new(Key::from_slice(key.key.as_ref()))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: STORAGE_AAD,
+                },
+            )
+            .map_err(|_| VaultError::AeadAesGcmEncrypt)?;
+
+        Ok(Self {
+            kdf: key.kdf.clone(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the secrets. This fails if the key was not derived from the right passphrase
+    pub(crate) fn decrypt(&self, key: &StorageKey) -> Result<StoredSecrets> {
+        let nonce = hex::decode(&self.nonce).map_err(|_| VaultError::AeadAesGcmDecrypt)?;
+        let ciphertext =
+            hex::decode(&self.ciphertext).map_err(|_| VaultError::AeadAesGcmDecrypt)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(VaultError::AeadAesGcmDecrypt.into());
+        }
+
+        let plaintext = Zeroizing::new(
+            Aes256Gcm::new(Key::from_slice(key.key.as_ref()))
+                .decrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: &ciphertext,
+                        aad: STORAGE_AAD,
+                    },
+                )
+                .map_err(|_| VaultError::InvalidStoragePassphrase)?,
+        );
+
+        Ok(serde_cbor::from_slice(&plaintext).map_err(|_| VaultError::AeadAesGcmDecrypt)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_secrets() {
+        // use low costs to keep the test fast
+        let kdf = KdfParameters::generate_with_costs(64, 1, 1);
+        let key = StorageKey::derive(b"passphrase", kdf).unwrap();
+        let secrets = StoredSecrets::default();
+        let encrypted = EncryptedSecrets::encrypt(&key, &secrets).unwrap();
+        assert!(encrypted.decrypt(&key).unwrap().is_empty());
+
+        let same_key = StorageKey::derive(b"passphrase", encrypted.kdf().clone()).unwrap();
+        assert!(encrypted.decrypt(&same_key).is_ok());
+
+        let wrong_key = StorageKey::derive(b"wrong passphrase", encrypted.kdf().clone()).unwrap();
+        assert!(encrypted.decrypt(&wrong_key).is_err());
+    }
+
+    #[test]
+    fn test_reject_unbounded_costs() {
+        let kdf = KdfParameters::generate_with_costs(MAX_MEMORY_COST + 1, 1, 1);
+        assert!(StorageKey::derive(b"passphrase", kdf).is_err());
+
+        let kdf = KdfParameters::generate_with_costs(64, u32::MAX, 1);
+        assert!(StorageKey::derive(b"passphrase", kdf).is_err());
+
+        let kdf = KdfParameters::generate_with_costs(64, 1, MAX_PARALLELISM + 1);
+        assert!(StorageKey::derive(b"passphrase", kdf).is_err());
+    }
+}
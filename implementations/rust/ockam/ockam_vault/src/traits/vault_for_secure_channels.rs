@@ -1,6 +1,6 @@
 use crate::{
-    AeadSecretKeyHandle, HashOutput, HkdfOutput, SecretBufferHandle, X25519PublicKey,
-    X25519SecretKeyHandle,
+    AeadSecretKeyHandle, HashOutput, HkdfOutput, MlKemCiphertext, MlKemPublicKey,
+    MlKemSecretKeyHandle, SecretBufferHandle, X25519PublicKey, X25519SecretKeyHandle,
 };
 
 use ockam_core::compat::vec::Vec;
@@ -88,6 +88,37 @@ pub trait VaultForSecureChannels: Send + Sync + 'static {
         public_key: &X25519PublicKey,
     ) -> Result<X25519SecretKeyHandle>;
 
+    /// Generate a fresh ephemeral (not persisted) ML-KEM-768 Key.
+    async fn generate_ephemeral_ml_kem_secret_key(&self) -> Result<MlKemSecretKeyHandle>;
+
+    /// Delete ephemeral ML-KEM-768 Key.
+    async fn delete_ephemeral_ml_kem_secret_key(
+        &self,
+        secret_key_handle: MlKemSecretKeyHandle,
+    ) -> Result<bool>;
+
+    /// Get [`MlKemPublicKey`] of the corresponding ML-KEM-768 Secret Key given its Handle.
+    async fn get_ml_kem_public_key(
+        &self,
+        secret_key_handle: &MlKemSecretKeyHandle,
+    ) -> Result<MlKemPublicKey>;
+
+    /// Perform ML-KEM-768 encapsulation of a fresh shared secret for the given public key.
+    /// Return the ciphertext to send to the owner of the public key and the shared secret.
+    /// [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+    async fn ml_kem_encapsulate(
+        &self,
+        peer_public_key: &MlKemPublicKey,
+    ) -> Result<(MlKemCiphertext, SecretBufferHandle)>;
+
+    /// Perform ML-KEM-768 decapsulation of the shared secret contained in a ciphertext.
+    /// [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+    async fn ml_kem_decapsulate(
+        &self,
+        secret_key_handle: &MlKemSecretKeyHandle,
+        ciphertext: &MlKemCiphertext,
+    ) -> Result<SecretBufferHandle>;
+
     /// Import a Secret Buffer.
     async fn import_secret_buffer(&self, buffer: Vec<u8>) -> Result<SecretBufferHandle>;
 
@@ -0,0 +1,41 @@
+use ockam_core::errcode::{Kind, Origin};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("pkcs11 error loading module {module}: {error}")]
+    LoadModule { module: String, error: String },
+    #[error("pkcs11 error opening a session on slot {slot}: {error}")]
+    OpenSession { slot: u64, error: String },
+    #[error("pkcs11 error logging in to slot {slot}: {error}")]
+    Login { slot: u64, error: String },
+    #[error("pkcs11 error creating new key")]
+    Create(String),
+    #[error("pkcs11 error signing message with key {keyid}")]
+    Sign { keyid: String, error: String },
+    #[error("pkcs11 error exporting public key {keyid}")]
+    Export { keyid: String, error: String },
+    #[error("pkcs11 error deleting key {keyid}")]
+    Delete { keyid: String, error: String },
+    #[error("pkcs11 error listing keys")]
+    List(String),
+    #[error("slot {0} is not available")]
+    InvalidSlot(u64),
+    #[error("key type is not supported")]
+    UnsupportedKeyType,
+    #[error("ec point is incorrect")]
+    InvalidEcPoint,
+    #[error("signature is incorrect")]
+    InvalidSignature,
+    #[error("key was not found")]
+    KeyNotFound,
+    #[error("invalid handle")]
+    InvalidHandle,
+}
+
+impl From<Error> for ockam_core::Error {
+    fn from(e: Error) -> Self {
+        ockam_core::Error::new(Origin::Other, Kind::Io, e)
+    }
+}
@@ -0,0 +1,18 @@
+//! PKCS#11 implementation of the ockam_vault::VaultForSigning trait
+//!
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+mod error;
+mod pkcs11_config;
+mod pkcs11_signing_vault;
+
+pub use error::*;
+pub use pkcs11_config::*;
+pub use pkcs11_signing_vault::*;
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+/// PKCS#11 configuration.
+#[derive(Debug, Clone)]
+pub struct Pkcs11Config {
+    module: PathBuf,
+    slot: u64,
+    pin: Option<String>,
+}
+
+impl Pkcs11Config {
+    /// Create a new configuration for a PKCS#11 token
+    ///
+    /// `module` is the path to the PKCS#11 shared library provided by the
+    /// token vendor, for example `/usr/lib/softhsm/libsofthsm2.so`.
+    pub fn new(module: impl Into<PathBuf>, slot: u64) -> Self {
+        Self {
+            module: module.into(),
+            slot,
+            pin: None,
+        }
+    }
+
+    /// Log in to the token with a user PIN
+    pub fn with_pin(self, pin: impl Into<String>) -> Self {
+        Self {
+            pin: Some(pin.into()),
+            ..self
+        }
+    }
+
+    /// Path to the PKCS#11 module
+    pub fn module(&self) -> &PathBuf {
+        &self.module
+    }
+
+    /// Slot containing the token
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    /// User PIN of the token
+    pub fn pin(&self) -> Option<&String> {
+        self.pin.as_ref()
+    }
+}
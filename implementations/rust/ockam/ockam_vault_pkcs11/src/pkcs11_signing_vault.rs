@@ -0,0 +1,495 @@
+use crate::error::Error;
+use crate::pkcs11_config::Pkcs11Config;
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::error::RvError;
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use ockam_core::compat::sync::{Arc, Mutex, RwLock};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    ECDSASHA256CurveP256PublicKey, ECDSASHA256CurveP256Signature, EdDSACurve25519PublicKey,
+    EdDSACurve25519Signature, HandleToSecret, Signature, SigningKeyType, SigningSecretKeyHandle,
+    VaultForSigning, VerifyingPublicKey,
+};
+use rand::{thread_rng, RngCore};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing as log;
+
+/// DER encoding of the P-256 curve OID (1.2.840.10045.3.1.7)
+const P256_EC_PARAMS: [u8; 10] = [0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// DER encoding of the Ed25519 curve OID (1.3.101.112)
+const ED25519_EC_PARAMS: [u8; 5] = [0x06, 0x03, 0x2b, 0x65, 0x70];
+
+/// Label set on the keys created by this vault
+const KEY_LABEL: &str = "ockam";
+
+struct Pkcs11KeyPair {
+    key: SigningSecretKeyHandle,
+    public_key: VerifyingPublicKey,
+    private_key_object: ObjectHandle,
+}
+
+/// Security module implementation using a PKCS#11 token
+///
+/// Secret keys are generated on the token as non-extractable keys and all the
+/// signing operations are delegated to the token, so they never leave it.
+pub struct Pkcs11SigningVault {
+    // A PKCS#11 session can only be used by one thread at the time
+    session: Arc<Mutex<Session>>,
+    // Store mapping from PublicKey to the token objects in memory
+    // This is fetched at the Vault initialization
+    // and is updated locally during add/delete operations
+    // WARNING: The assumption is that there is no concurrent access to the same keys from
+    // different places.
+    keys: Arc<RwLock<Vec<Pkcs11KeyPair>>>,
+}
+
+impl Pkcs11SigningVault {
+    /// Create a new PKCS#11 security module
+    pub async fn create(config: Pkcs11Config) -> Result<Self> {
+        let pkcs11 = load_module(config.module())?;
+
+        let slot = pkcs11
+            .get_slots_with_token()
+            .map_err(|err| Error::OpenSession {
+                slot: config.slot(),
+                error: err.to_string(),
+            })?
+            .into_iter()
+            .find(|s| s.id() == config.slot())
+            .ok_or(Error::InvalidSlot(config.slot()))?;
+
+        let session = pkcs11
+            .open_rw_session(slot)
+            .map_err(|err| Error::OpenSession {
+                slot: config.slot(),
+                error: err.to_string(),
+            })?;
+
+        if let Some(pin) = config.pin() {
+            match session.login(UserType::User, Some(&AuthPin::new(pin.clone()))) {
+                // The login state is shared by all the sessions opened on the same token
+                Ok(()) | Err(cryptoki::error::Error::Pkcs11(RvError::UserAlreadyLoggedIn)) => {}
+                Err(err) => {
+                    return Err(Error::Login {
+                        slot: config.slot(),
+                        error: err.to_string(),
+                    }
+                    .into())
+                }
+            }
+        }
+
+        let key_pairs = Self::discover_keys(&session)?;
+        log::debug!(slot = config.slot(), "found {} keys", key_pairs.len());
+
+        Ok(Self {
+            session: Arc::new(Mutex::new(session)),
+            keys: Arc::new(RwLock::new(key_pairs)),
+        })
+    }
+
+    /// Return list of all keys
+    pub fn keys(&self) -> Vec<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .map(|x| x.key.clone())
+            .collect()
+    }
+
+    /// Return number of keys
+    pub async fn number_of_keys(&self) -> Result<usize> {
+        Ok(self.keys.read().unwrap().len())
+    }
+
+    /// List the signing keys created by this vault which are already present on the token.
+    /// Other keys of the token are only loaded when they are explicitly used, see [`Self::key_pair`]
+    fn discover_keys(session: &Session) -> Result<Vec<Pkcs11KeyPair>> {
+        let private_keys = session
+            .find_objects(&[
+                Attribute::Class(ObjectClass::PRIVATE_KEY),
+                Attribute::Sign(true),
+                Attribute::Label(KEY_LABEL.as_bytes().to_vec()),
+            ])
+            .map_err(|err| Error::List(err.to_string()))?;
+
+        let mut key_pairs = vec![];
+        for private_key_object in private_keys {
+            // There are different possible causes here, for example the key may not have an
+            // id or it may have an unsupported key type. Therefore, the best strategy is to
+            // just skip that key
+            match Self::load_key_pair(session, private_key_object) {
+                Ok(key_pair) => key_pairs.push(key_pair),
+                Err(err) => log::warn!("Skipping token key: {err}"),
+            }
+        }
+        Ok(key_pairs)
+    }
+
+    fn load_key_pair(session: &Session, private_key_object: ObjectHandle) -> Result<Pkcs11KeyPair> {
+        let attributes = session
+            .get_attributes(
+                private_key_object,
+                &[AttributeType::Id, AttributeType::KeyType],
+            )
+            .map_err(|err| Error::List(err.to_string()))?;
+
+        let mut id = None;
+        let mut key_type = None;
+        for attribute in attributes {
+            match attribute {
+                Attribute::Id(value) => id = Some(value),
+                Attribute::KeyType(value) => key_type = Some(value),
+                _ => {}
+            }
+        }
+        let id = id.ok_or(Error::InvalidHandle)?;
+        let key = match key_type {
+            Some(KeyType::EC) => {
+                SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(id))
+            }
+            Some(KeyType::EC_EDWARDS) => {
+                SigningSecretKeyHandle::EdDSACurve25519(HandleToSecret::new(id))
+            }
+            _ => return Err(Error::UnsupportedKeyType.into()),
+        };
+        let public_key = Self::public_key(session, &key)?;
+
+        Ok(Pkcs11KeyPair {
+            key,
+            public_key,
+            private_key_object,
+        })
+    }
+
+    /// Read the public key of a key-pair from the token
+    fn public_key(session: &Session, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey> {
+        let keyid = hex::encode(key.handle().value());
+        log::trace!(%keyid, "get public key");
+        let public_key_object = session
+            .find_objects(&[
+                Attribute::Class(ObjectClass::PUBLIC_KEY),
+                Attribute::Id(key.handle().value().clone()),
+            ])
+            .map_err(|err| Error::Export {
+                keyid: keyid.clone(),
+                error: err.to_string(),
+            })?
+            .into_iter()
+            .next()
+            .ok_or(Error::KeyNotFound)?;
+
+        let ec_point = session
+            .get_attributes(public_key_object, &[AttributeType::EcPoint])
+            .map_err(|err| Error::Export {
+                keyid: keyid.clone(),
+                error: err.to_string(),
+            })?
+            .into_iter()
+            .find_map(|attribute| match attribute {
+                Attribute::EcPoint(value) => Some(value),
+                _ => None,
+            })
+            .ok_or(Error::InvalidEcPoint)?;
+        let ec_point = decode_ec_point(&ec_point)?;
+
+        let public_key = match key {
+            SigningSecretKeyHandle::ECDSASHA256CurveP256(_) => {
+                VerifyingPublicKey::ECDSASHA256CurveP256(ECDSASHA256CurveP256PublicKey(
+                    ec_point.try_into().map_err(|_| Error::InvalidEcPoint)?,
+                ))
+            }
+            SigningSecretKeyHandle::EdDSACurve25519(_) => VerifyingPublicKey::EdDSACurve25519(
+                EdDSACurve25519PublicKey(ec_point.try_into().map_err(|_| Error::InvalidEcPoint)?),
+            ),
+        };
+        log::debug!(%keyid, "received public key");
+        Ok(public_key)
+    }
+
+    /// Return the key-pair of a key, loading it from the token if it was not created by this vault,
+    /// for example when an existing key of the token is attached to an identity
+    fn key_pair<T>(
+        &self,
+        key: &SigningSecretKeyHandle,
+        f: impl Fn(&Pkcs11KeyPair) -> T,
+    ) -> Result<T> {
+        if let Some(key_pair) = self.keys.read().unwrap().iter().find(|x| &x.key == key) {
+            return Ok(f(key_pair));
+        }
+
+        let session = self.session.lock().unwrap();
+        let private_key_object = session
+            .find_objects(&[
+                Attribute::Class(ObjectClass::PRIVATE_KEY),
+                Attribute::Sign(true),
+                Attribute::Id(key.handle().value().clone()),
+            ])
+            .map_err(|err| Error::List(err.to_string()))?
+            .into_iter()
+            .next()
+            .ok_or(Error::KeyNotFound)?;
+        let key_pair = Self::load_key_pair(&session, private_key_object)?;
+        if &key_pair.key != key {
+            return Err(Error::UnsupportedKeyType.into());
+        }
+        let result = f(&key_pair);
+        self.keys.write().unwrap().push(key_pair);
+        Ok(result)
+    }
+
+    /// Find the objects of a key-pair created by this vault
+    fn find_key_objects(
+        session: &Session,
+        key: &SigningSecretKeyHandle,
+    ) -> Result<Vec<ObjectHandle>> {
+        let keyid = hex::encode(key.handle().value());
+        let mut objects = vec![];
+        for class in [ObjectClass::PRIVATE_KEY, ObjectClass::PUBLIC_KEY] {
+            objects.extend(
+                session
+                    .find_objects(&[
+                        Attribute::Class(class),
+                        Attribute::Id(key.handle().value().clone()),
+                        Attribute::Label(KEY_LABEL.as_bytes().to_vec()),
+                    ])
+                    .map_err(|err| Error::Delete {
+                        keyid: keyid.clone(),
+                        error: err.to_string(),
+                    })?,
+            );
+        }
+        Ok(objects)
+    }
+}
+
+#[async_trait]
+impl VaultForSigning for Pkcs11SigningVault {
+    async fn sign(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+        data: &[u8],
+    ) -> Result<Signature> {
+        let keyid = hex::encode(signing_secret_key_handle.handle().value());
+        log::trace!(%keyid, "sign message");
+        let private_key_object =
+            self.key_pair(signing_secret_key_handle, |x| x.private_key_object)?;
+
+        let session = self.session.lock().unwrap();
+        let signature = match signing_secret_key_handle {
+            SigningSecretKeyHandle::ECDSASHA256CurveP256(_) => {
+                // CKM_ECDSA signs a pre-computed digest and returns the raw r || s values
+                let digest = Sha256::digest(data);
+                let signature = session
+                    .sign(&Mechanism::Ecdsa, private_key_object, digest.as_slice())
+                    .map_err(|err| Error::Sign {
+                        keyid: keyid.clone(),
+                        error: err.to_string(),
+                    })?;
+                Signature::ECDSASHA256CurveP256(ECDSASHA256CurveP256Signature(
+                    signature.try_into().map_err(|_| Error::InvalidSignature)?,
+                ))
+            }
+            SigningSecretKeyHandle::EdDSACurve25519(_) => {
+                let signature = session
+                    .sign(&Mechanism::Eddsa, private_key_object, data)
+                    .map_err(|err| Error::Sign {
+                        keyid: keyid.clone(),
+                        error: err.to_string(),
+                    })?;
+                Signature::EdDSACurve25519(EdDSACurve25519Signature(
+                    signature.try_into().map_err(|_| Error::InvalidSignature)?,
+                ))
+            }
+        };
+        log::debug!(%keyid, "signed message");
+
+        Ok(signature)
+    }
+
+    async fn generate_signing_secret_key(
+        &self,
+        signing_key_type: SigningKeyType,
+    ) -> Result<SigningSecretKeyHandle> {
+        log::trace!("create new key");
+        let mut id = vec![0u8; 16];
+        thread_rng().fill_bytes(&mut id);
+
+        let (mechanism, ec_params, key) = match signing_key_type {
+            SigningKeyType::ECDSASHA256CurveP256 => (
+                Mechanism::EccKeyPairGen,
+                P256_EC_PARAMS.to_vec(),
+                SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(id.clone())),
+            ),
+            SigningKeyType::EdDSACurve25519 => (
+                Mechanism::EccEdwardsKeyPairGen,
+                ED25519_EC_PARAMS.to_vec(),
+                SigningSecretKeyHandle::EdDSACurve25519(HandleToSecret::new(id.clone())),
+            ),
+        };
+
+        let public_key_template = vec![
+            Attribute::Token(true),
+            Attribute::Verify(true),
+            Attribute::EcParams(ec_params),
+            Attribute::Id(id.clone()),
+            Attribute::Label(KEY_LABEL.as_bytes().to_vec()),
+        ];
+        let private_key_template = vec![
+            Attribute::Token(true),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+            Attribute::Id(id),
+            Attribute::Label(KEY_LABEL.as_bytes().to_vec()),
+        ];
+
+        let session = self.session.lock().unwrap();
+        let (_, private_key_object) = session
+            .generate_key_pair(&mechanism, &public_key_template, &private_key_template)
+            .map_err(|err| {
+                log::error!(%err, "failed to create new key");
+                Error::Create(err.to_string())
+            })?;
+        let public_key = Self::public_key(&session, &key)?;
+        log::debug!(keyid = hex::encode(key.handle().value()), "created new key");
+
+        self.keys.write().unwrap().push(Pkcs11KeyPair {
+            key: key.clone(),
+            public_key,
+            private_key_object,
+        });
+
+        Ok(key)
+    }
+
+    async fn get_verifying_public_key(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<VerifyingPublicKey> {
+        self.key_pair(signing_secret_key_handle, |x| x.public_key.clone())
+    }
+
+    async fn get_secret_key_handle(
+        &self,
+        verifying_public_key: &VerifyingPublicKey,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.public_key == verifying_public_key {
+                    Some(x.key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn delete_signing_secret_key(
+        &self,
+        signing_secret_key_handle: SigningSecretKeyHandle,
+    ) -> Result<bool> {
+        let keyid = hex::encode(signing_secret_key_handle.handle().value());
+        log::trace!(%keyid, "delete key");
+        let session = self.session.lock().unwrap();
+        // Only the keys created by this vault are deleted, other objects of the token
+        // may share the same id
+        let objects = Self::find_key_objects(&session, &signing_secret_key_handle)?;
+        self.keys
+            .write()
+            .unwrap()
+            .retain(|x| x.key != signing_secret_key_handle);
+        if objects.is_empty() {
+            return Ok(false);
+        }
+
+        // Delete both the private and the public key objects
+        for object in objects {
+            session
+                .destroy_object(object)
+                .map_err(|err| Error::Delete {
+                    keyid: keyid.clone(),
+                    error: err.to_string(),
+                })?;
+        }
+        log::debug!(%keyid, "deleted key");
+
+        Ok(true)
+    }
+}
+
+/// Load and initialize a PKCS#11 module.
+///
+/// A module can only be initialized once per process and is finalized when its
+/// context is dropped, so the contexts are shared between all the vaults using the same module
+fn load_module(module: &Path) -> Result<Pkcs11> {
+    static MODULES: OnceLock<Mutex<BTreeMap<PathBuf, Pkcs11>>> = OnceLock::new();
+    let mut modules = MODULES
+        .get_or_init(|| Mutex::new(BTreeMap::new()))
+        .lock()
+        .unwrap();
+    if let Some(pkcs11) = modules.get(module) {
+        return Ok(pkcs11.clone());
+    }
+
+    let pkcs11 = Pkcs11::new(module).map_err(|err| Error::LoadModule {
+        module: module.display().to_string(),
+        error: err.to_string(),
+    })?;
+    pkcs11
+        .initialize(CInitializeArgs::OsThreads)
+        .map_err(|err| Error::LoadModule {
+            module: module.display().to_string(),
+            error: err.to_string(),
+        })?;
+    modules.insert(module.to_path_buf(), pkcs11.clone());
+    Ok(pkcs11)
+}
+
+/// CKA_EC_POINT is a DER-encoded OCTET STRING wrapping the public point.
+/// Some tokens return the raw point instead, so both forms are accepted
+fn decode_ec_point(ec_point: &[u8]) -> Result<Vec<u8>> {
+    match ec_point {
+        // Short form length
+        [0x04, len, rest @ ..] if *len as usize == rest.len() && *len < 0x80 => Ok(rest.to_vec()),
+        // Long form length on one byte
+        [0x04, 0x81, len, rest @ ..] if *len as usize == rest.len() => Ok(rest.to_vec()),
+        // Uncompressed P-256 point or Ed25519 point
+        point if point.len() == 65 || point.len() == 32 => Ok(point.to_vec()),
+        _ => Err(Error::InvalidEcPoint.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_ec_point() {
+        let mut p256_point = vec![0x04; 65];
+        p256_point[1] = 1;
+        let mut der = vec![0x04, 0x41];
+        der.extend_from_slice(&p256_point);
+        assert_eq!(decode_ec_point(&der).unwrap(), p256_point);
+        assert_eq!(decode_ec_point(&p256_point).unwrap(), p256_point);
+
+        let ed25519_point = vec![7u8; 32];
+        let mut der = vec![0x04, 0x20];
+        der.extend_from_slice(&ed25519_point);
+        assert_eq!(decode_ec_point(&der).unwrap(), ed25519_point);
+
+        assert!(decode_ec_point(&[0x04, 0x10, 0x01]).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use ockam_core::Result;
+use ockam_vault::{
+    SigningKeyType, SoftwareVaultForVerifyingSignatures, VaultForSigning,
+    VaultForVerifyingSignatures,
+};
+use ockam_vault_pkcs11::{Pkcs11Config, Pkcs11SigningVault};
+
+/// These tests need to be executed with the following environment variables
+/// OCKAM_PKCS11_MODULE: path to the PKCS#11 module, e.g. /usr/lib/softhsm/libsofthsm2.so
+/// OCKAM_PKCS11_SLOT: slot of an initialized token
+/// OCKAM_PKCS11_PIN: user PIN of the token
+///
+/// A SoftHSM token can be initialized with:
+/// softhsm2-util --init-token --free --label ockam --so-pin 1234 --pin 1234
+async fn create_vault() -> Result<Pkcs11SigningVault> {
+    let module = std::env::var("OCKAM_PKCS11_MODULE").unwrap();
+    let slot = std::env::var("OCKAM_PKCS11_SLOT").unwrap().parse().unwrap();
+    let pin = std::env::var("OCKAM_PKCS11_PIN").unwrap();
+    Pkcs11SigningVault::create(Pkcs11Config::new(module, slot).with_pin(pin)).await
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_sign_verify() -> Result<()> {
+    let signing_vault = create_vault().await?;
+    let verifier = SoftwareVaultForVerifyingSignatures::new();
+
+    for key_type in [
+        SigningKeyType::ECDSASHA256CurveP256,
+        SigningKeyType::EdDSACurve25519,
+    ] {
+        let handle = signing_vault.generate_signing_secret_key(key_type).await?;
+        let message = b"hello world";
+        let signature = signing_vault.sign(&handle, message.as_slice()).await?;
+        let public_key = signing_vault.get_verifying_public_key(&handle).await?;
+
+        assert!(
+            verifier
+                .verify_signature(&public_key, message, &signature)
+                .await?
+        );
+
+        signing_vault.delete_signing_secret_key(handle).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_keys_management() -> Result<()> {
+    let signing_vault = create_vault().await?;
+
+    let number_of_keys1 = signing_vault.number_of_keys().await?;
+
+    let handle = signing_vault
+        .generate_signing_secret_key(SigningKeyType::ECDSASHA256CurveP256)
+        .await?;
+
+    let number_of_keys2 = signing_vault.number_of_keys().await?;
+    assert_eq!(number_of_keys1 + 1, number_of_keys2);
+
+    let public_key = signing_vault.get_verifying_public_key(&handle).await?;
+
+    let handle2 = signing_vault.get_secret_key_handle(&public_key).await?;
+    assert_eq!(handle, handle2);
+
+    // Keys created on the token are found again by a new session
+    let signing_vault2 = create_vault().await?;
+    let handle3 = signing_vault2.get_secret_key_handle(&public_key).await?;
+    assert_eq!(handle, handle3);
+
+    signing_vault.delete_signing_secret_key(handle).await?;
+    let number_of_keys3 = signing_vault.number_of_keys().await?;
+    assert_eq!(number_of_keys2, number_of_keys3 + 1);
+
+    Ok(())
+}